@@ -57,7 +57,7 @@ impl<'a> BuiltinBenchmark<'a> {
 	}
 
 	fn gas_cost(&self) -> U256 {
-		self.builtin.cost(&self.input)
+		self.builtin.cost(&self.input, 0)
 	}
 
 	fn run(&self, b: &mut Bencher) {
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::cmp;
-use ethereum_types::{U256, H256};
+use ethereum_types::{U256, H256, Address};
 use super::u256_to_address;
 
 use {evm, vm};
@@ -121,10 +121,18 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 				Request::Gas(Gas::from(1))
 			},
 			instructions::SSTORE => {
+				if schedule.eip1706 {
+					let sentry = Gas::from(schedule.call_stipend);
+					if self.current_gas <= sentry {
+						return Err(vm::Error::OutOfGas);
+					}
+				}
+
 				let address = H256::from(stack.peek(0));
 				let newval = stack.peek(1);
 				let val = U256::from(&*ext.storage_at(&address)?);
 
+				let is_cold = schedule.eip2929 && !ext.al_contains_storage_key(&address);
 				let gas = if schedule.eip1283 {
 					let orig = U256::from(&*ext.initial_storage_at(&address)?);
 					calculate_eip1283_sstore_gas(schedule, &orig, &val, &newval)
@@ -137,25 +145,38 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 						schedule.sstore_reset_gas
 					}
 				};
+				let gas = if is_cold { gas + schedule.cold_sload_gas } else { gas };
 				Request::Gas(Gas::from(gas))
 			},
 			instructions::SLOAD => {
-				Request::Gas(Gas::from(schedule.sload_gas))
+				let key = H256::from(stack.peek(0));
+				let gas = if schedule.eip2929 && !ext.al_contains_storage_key(&key) {
+					schedule.cold_sload_gas
+				} else {
+					schedule.sload_gas
+				};
+				Request::Gas(Gas::from(gas))
 			},
 			instructions::BALANCE => {
-				Request::Gas(Gas::from(schedule.balance_gas))
+				let address = u256_to_address(stack.peek(0));
+				Request::Gas(Gas::from(account_access_gas(ext, &address, schedule.balance_gas)))
 			},
 			instructions::EXTCODESIZE => {
-				Request::Gas(Gas::from(schedule.extcodesize_gas))
+				let address = u256_to_address(stack.peek(0));
+				Request::Gas(Gas::from(account_access_gas(ext, &address, schedule.extcodesize_gas)))
 			},
 			instructions::EXTCODEHASH => {
-				Request::Gas(Gas::from(schedule.extcodehash_gas))
+				let address = u256_to_address(stack.peek(0));
+				Request::Gas(Gas::from(account_access_gas(ext, &address, schedule.extcodehash_gas)))
 			},
 			instructions::SUICIDE => {
 				let mut gas = Gas::from(schedule.suicide_gas);
 
 				let is_value_transfer = !ext.origin_balance()?.is_zero();
 				let address = u256_to_address(stack.peek(0));
+				if schedule.eip2929 && !ext.al_contains_address(&address) {
+					gas = overflowing!(gas.overflow_add(schedule.cold_account_access_gas.into()));
+				}
 				if (
 					!schedule.no_empty && !ext.exists(&address)?
 				) || (
@@ -184,7 +205,9 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 				Request::GasMemCopy(default_gas, mem_needed(stack.peek(0), stack.peek(2))?, Gas::from_u256(*stack.peek(2))?)
 			},
 			instructions::EXTCODECOPY => {
-				Request::GasMemCopy(schedule.extcodecopy_base_gas.into(), mem_needed(stack.peek(1), stack.peek(3))?, Gas::from_u256(*stack.peek(3))?)
+				let address = u256_to_address(stack.peek(0));
+				let gas = account_access_gas(ext, &address, schedule.extcodecopy_base_gas);
+				Request::GasMemCopy(gas.into(), mem_needed(stack.peek(1), stack.peek(3))?, Gas::from_u256(*stack.peek(3))?)
 			},
 			instructions::LOG0 | instructions::LOG1 | instructions::LOG2 | instructions::LOG3 | instructions::LOG4 => {
 				let no_of_topics = instruction.log_topics().expect("log_topics always return some for LOG* instructions; qed");
@@ -195,13 +218,13 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 				Request::GasMem(gas, mem_needed(stack.peek(0), stack.peek(1))?)
 			},
 			instructions::CALL | instructions::CALLCODE => {
-				let mut gas = Gas::from(schedule.call_gas);
+				let address = u256_to_address(stack.peek(1));
+				let mut gas = Gas::from(account_access_gas(ext, &address, schedule.call_gas));
 				let mem = cmp::max(
 					mem_needed(stack.peek(5), stack.peek(6))?,
 					mem_needed(stack.peek(3), stack.peek(4))?
 				);
 
-				let address = u256_to_address(stack.peek(1));
 				let is_value_transfer = !stack.peek(2).is_zero();
 
 				if instruction == instructions::CALL && (
@@ -221,7 +244,8 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 				Request::GasMemProvide(gas, mem, Some(requested))
 			},
 			instructions::DELEGATECALL | instructions::STATICCALL => {
-				let gas = Gas::from(schedule.call_gas);
+				let address = u256_to_address(stack.peek(1));
+				let gas = Gas::from(account_access_gas(ext, &address, schedule.call_gas));
 				let mem = cmp::max(
 					mem_needed(stack.peek(4), stack.peek(5))?,
 					mem_needed(stack.peek(2), stack.peek(3))?
@@ -368,11 +392,24 @@ fn to_word_size<Gas: evm::CostType>(value: Gas) -> (Gas, bool) {
 }
 
 #[inline]
+/// Price of an instruction accessing an account, adding the EIP-2929 surcharge
+/// for the first access of `address` within the transaction.
+fn account_access_gas(ext: &vm::Ext, address: &Address, warm_gas: usize) -> usize {
+	let schedule = ext.schedule();
+	if schedule.eip2929 && !ext.al_contains_address(address) {
+		schedule.cold_account_access_gas
+	} else {
+		warm_gas
+	}
+}
+
 fn calculate_eip1283_sstore_gas<Gas: evm::CostType>(schedule: &Schedule, original: &U256, current: &U256, new: &U256) -> Gas {
+	let dirty_gas = schedule.sstore_dirty_gas.unwrap_or(schedule.sload_gas);
+
 	Gas::from(
 		if current == new {
 			// 1. If current value equals new value (this is a no-op), 200 gas is deducted.
-			schedule.sload_gas
+			dirty_gas
 		} else {
 			// 2. If current value does not equal new value
 			if original == current {
@@ -388,7 +425,7 @@ fn calculate_eip1283_sstore_gas<Gas: evm::CostType>(schedule: &Schedule, origina
 				}
 			} else {
 				// 2.2. If original value does not equal current value (this storage slot is dirty), 200 gas is deducted. Apply both of the following clauses.
-				schedule.sload_gas
+				dirty_gas
 
 				// 2.2.1. If original value is not 0
 				// 2.2.1.1. If current value is 0 (also means that new value is not 0), remove 15000 gas from refund counter. We can prove that refund counter will never go below 0.
@@ -404,6 +441,7 @@ fn calculate_eip1283_sstore_gas<Gas: evm::CostType>(schedule: &Schedule, origina
 
 pub fn handle_eip1283_sstore_clears_refund(ext: &mut vm::Ext, original: &U256, current: &U256, new: &U256) {
 	let sstore_clears_schedule = U256::from(ext.schedule().sstore_refund_gas);
+	let dirty_gas = ext.schedule().sstore_dirty_gas.unwrap_or(ext.schedule().sload_gas);
 
 	if current == new {
 		// 1. If current value equals new value (this is a no-op), 200 gas is deducted.
@@ -438,11 +476,11 @@ pub fn handle_eip1283_sstore_clears_refund(ext: &mut vm::Ext, original: &U256, c
 				// 2.2.2. If original value equals new value (this storage slot is reset)
 				if original.is_zero() {
 					// 2.2.2.1. If original value is 0, add 19800 gas to refund counter.
-					let refund = U256::from(ext.schedule().sstore_set_gas - ext.schedule().sload_gas);
+					let refund = U256::from(ext.schedule().sstore_set_gas - dirty_gas);
 					ext.add_sstore_refund(refund);
 				} else {
 					// 2.2.2.2. Otherwise, add 4800 gas to refund counter.
-					let refund = U256::from(ext.schedule().sstore_reset_gas - ext.schedule().sload_gas);
+					let refund = U256::from(ext.schedule().sstore_reset_gas - dirty_gas);
 					ext.add_sstore_refund(refund);
 				}
 			}
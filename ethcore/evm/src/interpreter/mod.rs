@@ -421,7 +421,10 @@ impl<Cost: CostType> Interpreter<Cost> {
 			((instruction == instructions::RETURNDATACOPY || instruction == instructions::RETURNDATASIZE) && !schedule.have_return_data) ||
 			(instruction == instructions::REVERT && !schedule.have_revert) ||
 			((instruction == instructions::SHL || instruction == instructions::SHR || instruction == instructions::SAR) && !schedule.have_bitwise_shifting) ||
-			(instruction == instructions::EXTCODEHASH && !schedule.have_extcodehash)
+			(instruction == instructions::EXTCODEHASH && !schedule.have_extcodehash) ||
+			(instruction == instructions::CHAINID && !schedule.have_chain_id) ||
+			(instruction == instructions::SELFBALANCE && !schedule.have_selfbalance) ||
+			(instruction == instructions::BASEFEE && !schedule.have_basefee)
 		{
 			return Err(vm::Error::BadInstruction {
 				instruction: instruction as u8
@@ -556,6 +559,9 @@ impl<Cost: CostType> Interpreter<Cost> {
 				let call_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::get_gas_cost_mem`; `gas_gas_mem_cost` guarantees `Some` when instruction is `CALL`/`CALLCODE`/`DELEGATECALL`/`CREATE`; this is one of `CALL`/`CALLCODE`/`DELEGATECALL`; qed");
 				let code_address = self.stack.pop_back();
 				let code_address = u256_to_address(&code_address);
+				if ext.schedule().eip2929 {
+					ext.al_insert_address(code_address);
+				}
 
 				let value = if instruction == instructions::DELEGATECALL {
 					None
@@ -654,8 +660,11 @@ impl<Cost: CostType> Interpreter<Cost> {
 				return Ok(InstructionResult::StopExecution);
 			},
 			instructions::SUICIDE => {
-				let address = self.stack.pop_back();
-				ext.suicide(&u256_to_address(&address))?;
+				let address = u256_to_address(&self.stack.pop_back());
+				if ext.schedule().eip2929 {
+					ext.al_insert_address(address);
+				}
+				ext.suicide(&address)?;
 				return Ok(InstructionResult::StopExecution);
 			},
 			instructions::LOG0 | instructions::LOG1 | instructions::LOG2 | instructions::LOG3 | instructions::LOG4 => {
@@ -706,12 +715,18 @@ impl<Cost: CostType> Interpreter<Cost> {
 			},
 			instructions::SLOAD => {
 				let key = H256::from(&self.stack.pop_back());
+				if ext.schedule().eip2929 {
+					ext.al_insert_storage_key(key);
+				}
 				let word = U256::from(&*ext.storage_at(&key)?);
 				self.stack.push(word);
 			},
 			instructions::SSTORE => {
 				let address = H256::from(&self.stack.pop_back());
 				let val = self.stack.pop_back();
+				if ext.schedule().eip2929 {
+					ext.al_insert_storage_key(address);
+				}
 
 				let current_val = U256::from(&*ext.storage_at(&address)?);
 				// Increase refund for clear
@@ -740,6 +755,9 @@ impl<Cost: CostType> Interpreter<Cost> {
 			},
 			instructions::BALANCE => {
 				let address = u256_to_address(&self.stack.pop_back());
+				if ext.schedule().eip2929 {
+					ext.al_insert_address(address);
+				}
 				let balance = ext.balance(&address)?;
 				self.stack.push(balance);
 			},
@@ -779,11 +797,17 @@ impl<Cost: CostType> Interpreter<Cost> {
 			},
 			instructions::EXTCODESIZE => {
 				let address = u256_to_address(&self.stack.pop_back());
+				if ext.schedule().eip2929 {
+					ext.al_insert_address(address);
+				}
 				let len = ext.extcodesize(&address)?.unwrap_or(0);
 				self.stack.push(U256::from(len));
 			},
 			instructions::EXTCODEHASH => {
 				let address = u256_to_address(&self.stack.pop_back());
+				if ext.schedule().eip2929 {
+					ext.al_insert_address(address);
+				}
 				let hash = ext.extcodehash(&address)?.unwrap_or_else(H256::zero);
 				self.stack.push(U256::from(hash));
 			},
@@ -806,6 +830,9 @@ impl<Cost: CostType> Interpreter<Cost> {
 			},
 			instructions::EXTCODECOPY => {
 				let address = u256_to_address(&self.stack.pop_back());
+				if ext.schedule().eip2929 {
+					ext.al_insert_address(address);
+				}
 				let code = ext.extcode(&address)?;
 				Self::copy_data_to_memory(
 					&mut self.mem,
@@ -836,6 +863,15 @@ impl<Cost: CostType> Interpreter<Cost> {
 			instructions::GASLIMIT => {
 				self.stack.push(ext.env_info().gas_limit.clone());
 			},
+			instructions::CHAINID => {
+				self.stack.push(U256::from(ext.chain_id()));
+			},
+			instructions::SELFBALANCE => {
+				self.stack.push(ext.balance(&self.params.address)?);
+			},
+			instructions::BASEFEE => {
+				self.stack.push(ext.env_info().base_fee.unwrap_or_default());
+			},
 
 			// Stack instructions
 
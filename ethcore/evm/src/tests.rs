@@ -365,6 +365,127 @@ fn test_gas_limit(factory: super::Factory) {
 	assert_store(&ext, 0, "0000000000000000000000000000000000000000000000000000000000001234");
 }
 
+evm_test!{test_chain_id: test_chain_id_int}
+fn test_chain_id(factory: super::Factory) {
+	let code = "46600055".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new_istanbul();
+	ext.chain_id = 9;
+
+	let gas_left = {
+		let mut vm = factory.create(params, ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
+	};
+
+	assert_eq!(gas_left, U256::from(79_995));
+	assert_store(&ext, 0, "0000000000000000000000000000000000000000000000000000000000000009");
+}
+
+evm_test!{test_selfbalance: test_selfbalance_int}
+fn test_selfbalance(factory: super::Factory) {
+	let address = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+	let code = "47600055".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.address = address.clone();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new_istanbul();
+	ext.balances.insert(address, U256::from(0x1234));
+
+	let gas_left = {
+		let mut vm = factory.create(params, ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
+	};
+
+	assert_eq!(gas_left, U256::from(79_992));
+	assert_store(&ext, 0, "0000000000000000000000000000000000000000000000000000000000001234");
+}
+
+evm_test!{test_basefee: test_basefee_int}
+fn test_basefee(factory: super::Factory) {
+	let code = "48600055".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new_london();
+	ext.info.base_fee = Some(U256::from(0x1234));
+
+	let gas_left = {
+		let mut vm = factory.create(params, ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
+	};
+
+	assert_eq!(gas_left, U256::from(77_895));
+	assert_store(&ext, 0, "0000000000000000000000000000000000000000000000000000000000001234");
+}
+
+#[test]
+fn test_chain_id_before_istanbul() {
+	let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+	let code = "46600055".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new_constantinople();
+
+	let err = {
+		let mut vm = factory.create(params, ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap_err()
+	};
+
+	match err {
+		vm::Error::BadInstruction { instruction: 0x46 } => (),
+		_ => assert!(false, "Expected bad instruction")
+	}
+}
+
+evm_test!{test_sstore_sentry: test_sstore_sentry_int}
+fn test_sstore_sentry(factory: super::Factory) {
+	// PUSH1 1, PUSH1 0, SSTORE with exactly the call stipend left before SSTORE.
+	let code = "6001600055".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(2306);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new_istanbul();
+
+	let err = {
+		let mut vm = factory.create(params, ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap_err()
+	};
+
+	match err {
+		vm::Error::OutOfGas => (),
+		_ => assert!(false, "Expected out of gas"),
+	}
+	assert!(ext.store.is_empty());
+}
+
+evm_test!{test_sload_cold_then_warm: test_sload_cold_then_warm_int}
+fn test_sload_cold_then_warm(factory: super::Factory) {
+	// PUSH1 0, SLOAD, PUSH1 0, SLOAD: first access is cold, second is warm.
+	let code = "600054600054".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new_berlin();
+
+	let gas_left = {
+		let mut vm = factory.create(params, ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
+	};
+
+	assert_eq!(gas_left, U256::from(100_000 - 3 - 2100 - 3 - 100));
+	assert!(ext.access_list.contains_storage_key(&Address::default(), &H256::zero()));
+}
+
 evm_test!{test_mul: test_mul_int}
 fn test_mul(factory: super::Factory) {
 	let code = "65012365124623626543219002600055".from_hex().unwrap();
@@ -315,7 +315,7 @@ impl<T: ChainDataFetcher> Client<T> {
 
 			let epoch_proof = self.engine.is_epoch_end(
 				&verified_header,
-				&|h| self.chain.block_header(BlockId::Hash(h)).and_then(|hdr| hdr.decode().ok()),
+				&|h| self.chain.block_header(BlockId::Hash(h)).and_then(|hdr| hdr.decode(self.engine.params().eip1559_transition).ok()),
 				&|h| self.chain.pending_transition(h),
 			);
 
@@ -385,6 +385,7 @@ impl<T: ChainDataFetcher> Client<T> {
 			last_hashes: self.build_last_hashes(header.parent_hash()),
 			gas_used: Default::default(),
 			gas_limit: header.gas_limit(),
+			base_fee: header.decode(self.engine.params().eip1559_transition).ok().and_then(|h| h.base_fee()),
 		})
 	}
 
@@ -425,7 +426,7 @@ impl<T: ChainDataFetcher> Client<T> {
 		// Verify Block Family
 
 		let verify_family_result = {
-			parent_header.decode()
+			parent_header.decode(self.engine.params().eip1559_transition)
 				.map_err(|dec_err| dec_err.into())
 				.and_then(|decoded| {
 					self.engine.verify_block_family(&verified_header, &decoded)
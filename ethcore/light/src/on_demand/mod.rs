@@ -289,6 +289,9 @@ fn guess_capabilities(requests: &[CheckedRequest]) -> Capabilities {
 			CheckedRequest::Execution(ref req, _) => if let Ok(ref hdr) = req.header.as_ref() {
 				update_since(&mut caps.serve_state_since, hdr.number());
 			},
+			CheckedRequest::ExecutionProof(ref req, _) => if let Ok(ref hdr) = req.0.header.as_ref() {
+				update_since(&mut caps.serve_state_since, hdr.number());
+			},
 		}
 	}
 
@@ -21,6 +21,7 @@ use std::sync::Arc;
 
 use bytes::Bytes;
 use ethcore::basic_account::BasicAccount;
+use ethcore::client::CallAnalytics;
 use ethcore::encoded;
 use ethcore::engines::{EthEngine, StateDependentProof};
 use ethcore::machine::EthereumMachine;
@@ -35,7 +36,7 @@ use memorydb::MemoryDB;
 use parking_lot::Mutex;
 use request::{self as net_request, IncompleteRequest, CompleteRequest, Output, OutputKind, Field};
 use rlp::{RlpStream, Rlp};
-use transaction::SignedTransaction;
+use transaction::{SignedTransaction, UnverifiedTransaction};
 use trie::Trie;
 use vm::EnvInfo;
 
@@ -62,6 +63,8 @@ pub enum Request {
 	Code(Code),
 	/// A request for proof of execution.
 	Execution(TransactionProof),
+	/// A request for the state items proving an execution.
+	ExecutionProof(ExecutionProof),
 	/// A request for epoch change signal.
 	Signal(Signal),
 }
@@ -146,6 +149,7 @@ impl_single!(Body, Body, encoded::Block);
 impl_single!(Account, Account, Option<BasicAccount>);
 impl_single!(Code, Code, Bytes);
 impl_single!(Execution, TransactionProof, super::ExecutionResult);
+impl_single!(ExecutionProof, ExecutionProof, Vec<DBValue>);
 impl_single!(Signal, Signal, Vec<u8>);
 
 macro_rules! impl_args {
@@ -257,6 +261,7 @@ pub enum CheckedRequest {
 	Account(Account, net_request::IncompleteAccountRequest),
 	Code(Code, net_request::IncompleteCodeRequest),
 	Execution(TransactionProof, net_request::IncompleteExecutionRequest),
+	ExecutionProof(ExecutionProof, net_request::IncompleteExecutionRequest),
 	Signal(Signal, net_request::IncompleteSignalRequest)
 }
 
@@ -328,18 +333,15 @@ impl From<Request> for CheckedRequest {
 				CheckedRequest::Code(req, net_req)
 			}
 			Request::Execution(req) => {
-				let net_req = net_request::IncompleteExecutionRequest {
-					block_hash: req.header.field(),
-					from: req.tx.sender(),
-					gas: req.tx.gas,
-					gas_price: req.tx.gas_price,
-					action: req.tx.action.clone(),
-					value: req.tx.value,
-					data: req.tx.data.clone(),
-				};
+				let net_req = req.net_request();
 				trace!(target: "on_demand", "Execution request, {:?}", net_req);
 				CheckedRequest::Execution(req, net_req)
 			}
+			Request::ExecutionProof(req) => {
+				let net_req = req.0.net_request();
+				trace!(target: "on_demand", "ExecutionProof request, {:?}", net_req);
+				CheckedRequest::ExecutionProof(req, net_req)
+			}
 			Request::Signal(req) => {
 				let net_req = net_request::IncompleteSignalRequest {
 					block_hash: req.hash.into(),
@@ -366,6 +368,7 @@ impl CheckedRequest {
 			CheckedRequest::Account(_, req) => NetRequest::Account(req),
 			CheckedRequest::Code(_, req) => NetRequest::Code(req),
 			CheckedRequest::Execution(_, req) => NetRequest::Execution(req),
+			CheckedRequest::ExecutionProof(_, req) => NetRequest::Execution(req),
 			CheckedRequest::Signal(_, req) => NetRequest::Signal(req),
 		}
 	}
@@ -381,6 +384,7 @@ impl CheckedRequest {
 			CheckedRequest::Account(ref x, _) => x.header.needs_header(),
 			CheckedRequest::Code(ref x, _) => x.header.needs_header(),
 			CheckedRequest::Execution(ref x, _) => x.header.needs_header(),
+			CheckedRequest::ExecutionProof(ref x, _) => x.0.header.needs_header(),
 			_ => None,
 		}
 	}
@@ -395,6 +399,7 @@ impl CheckedRequest {
 			CheckedRequest::Account(ref mut x, _) => x.header = HeaderRef::Stored(header),
 			CheckedRequest::Code(ref mut x, _) => x.header = HeaderRef::Stored(header),
 			CheckedRequest::Execution(ref mut x, _) => x.header = HeaderRef::Stored(header),
+			CheckedRequest::ExecutionProof(ref mut x, _) => x.0.header = HeaderRef::Stored(header),
 			_ => {},
 		}
 	}
@@ -511,6 +516,7 @@ macro_rules! match_me {
 			CheckedRequest::Account($check, $req) => $e,
 			CheckedRequest::Code($check, $req) => $e,
 			CheckedRequest::Execution($check, $req) => $e,
+			CheckedRequest::ExecutionProof($check, $req) => $e,
 			CheckedRequest::Signal($check, $req) => $e,
 		}
 	}
@@ -549,6 +555,7 @@ impl IncompleteRequest for CheckedRequest {
 			CheckedRequest::Account(_, ref req) => req.check_outputs(f),
 			CheckedRequest::Code(_, ref req) => req.check_outputs(f),
 			CheckedRequest::Execution(_, ref req) => req.check_outputs(f),
+			CheckedRequest::ExecutionProof(_, ref req) => req.check_outputs(f),
 			CheckedRequest::Signal(_, ref req) => req.check_outputs(f),
 		}
 	}
@@ -599,6 +606,10 @@ impl IncompleteRequest for CheckedRequest {
 				trace!(target: "on_demand", "Execution request completed {:?}", req);
 				req.complete().map(CompleteRequest::Execution)
 			}
+			CheckedRequest::ExecutionProof(_, req) => {
+				trace!(target: "on_demand", "ExecutionProof request completed {:?}", req);
+				req.complete().map(CompleteRequest::Execution)
+			}
 			CheckedRequest::Signal(_, req) => {
 				trace!(target: "on_demand", "Signal request completed {:?}", req);
 				req.complete().map(CompleteRequest::Signal)
@@ -659,6 +670,9 @@ impl net_request::CheckedRequest for CheckedRequest {
 			CheckedRequest::Execution(ref prover, _) =>
 				expect!((&NetResponse::Execution(ref res), _) =>
 					prover.check_response(cache, &res.items).map(Response::Execution)),
+			CheckedRequest::ExecutionProof(ref prover, _) =>
+				expect!((&NetResponse::Execution(ref res), _) =>
+					prover.check_response(cache, &res.items).map(Response::ExecutionProof)),
 			CheckedRequest::Signal(ref prover, _) =>
 				expect!((&NetResponse::Signal(ref res), _) =>
 					prover.check_response(cache, &res.signal).map(Response::Signal)),
@@ -689,6 +703,8 @@ pub enum Response {
 	Code(Vec<u8>),
 	/// Response to a request for proved execution.
 	Execution(super::ExecutionResult),
+	/// Response to a request for the state items proving an execution.
+	ExecutionProof(Vec<DBValue>),
 	/// Response to a request for epoch change signal.
 	Signal(Vec<u8>),
 }
@@ -926,7 +942,7 @@ impl Body {
 	pub fn check_response(&self, cache: &Mutex<::cache::Cache>, body: &encoded::Body) -> Result<encoded::Block, Error> {
 		// check the integrity of the the body against the header
 		let header = self.0.as_ref()?;
-		let tx_root = ::triehash::ordered_trie_root(body.transactions_rlp().iter().map(|r| r.as_raw()));
+		let tx_root = ::triehash::ordered_trie_root(body.transactions_rlp().iter().map(|r| UnverifiedTransaction::envelope_from_rlp(&r)));
 		if tx_root != header.transactions_root() {
 			trace!(target: "on_demand", "Body Response: \"WrongTrieRoot\" tx_root: {:?} header_root: {:?}", tx_root, header.transactions_root());
 			return Err(Error::WrongTrieRoot(header.transactions_root(), tx_root));
@@ -954,7 +970,7 @@ impl BlockReceipts {
 	/// Check a response with receipts against the stored header.
 	pub fn check_response(&self, cache: &Mutex<::cache::Cache>, receipts: &[Receipt]) -> Result<Vec<Receipt>, Error> {
 		let receipts_root = self.0.as_ref()?.receipts_root();
-		let found_root = ::triehash::ordered_trie_root(receipts.iter().map(|r| ::rlp::encode(r)));
+		let found_root = ::triehash::ordered_trie_root(receipts.iter().map(|r| r.encode_envelope()));
 
 		if receipts_root == found_root {
 			cache.lock().insert_block_receipts(receipts_root, receipts.to_vec());
@@ -1040,9 +1056,23 @@ pub struct TransactionProof {
 	pub env_info: EnvInfo,
 	/// Consensus engine.
 	pub engine: Arc<EthEngine>,
+	/// Tracing and state diffing to perform while executing.
+	pub analytics: CallAnalytics,
 }
 
 impl TransactionProof {
+	fn net_request(&self) -> net_request::IncompleteExecutionRequest {
+		net_request::IncompleteExecutionRequest {
+			block_hash: self.header.field(),
+			from: self.tx.sender(),
+			gas: self.tx.gas,
+			gas_price: self.tx.gas_price,
+			action: self.tx.action.clone(),
+			value: self.tx.value,
+			data: self.tx.data.clone(),
+		}
+	}
+
 	/// Check the proof, returning the proved execution or indicate that the proof was bad.
 	pub fn check_response(&self, _: &Mutex<::cache::Cache>, state_items: &[DBValue]) -> Result<super::ExecutionResult, Error> {
 		let root = self.header.as_ref()?.state_root();
@@ -1050,12 +1080,13 @@ impl TransactionProof {
 		let mut env_info = self.env_info.clone();
 		env_info.gas_limit = self.tx.gas;
 
-		let proved_execution = state::check_proof(
+		let proved_execution = state::check_proof_with_analytics(
 			state_items,
 			root,
 			&self.tx,
 			self.engine.machine(),
 			&self.env_info,
+			self.analytics,
 		);
 
 		match proved_execution {
@@ -1075,6 +1106,20 @@ impl TransactionProof {
 	}
 }
 
+/// Request for the state items proving the execution of a transaction.
+///
+/// The items are checked the same way as for `TransactionProof`, but handed back so that
+/// proofs of several transactions can be merged and replayed in sequence.
+#[derive(Clone)]
+pub struct ExecutionProof(pub TransactionProof);
+
+impl ExecutionProof {
+	/// Check the proof, returning the proof items or indicate that the proof was bad.
+	pub fn check_response(&self, cache: &Mutex<::cache::Cache>, state_items: &[DBValue]) -> Result<Vec<DBValue>, Error> {
+		self.0.check_response(cache, state_items).map(|_| state_items.to_vec())
+	}
+}
+
 /// Request for epoch signal.
 /// Provide engine and state-dependent proof checker.
 #[derive(Clone)]
@@ -1260,6 +1305,7 @@ mod tests {
 			outcome: TransactionOutcome::StateRoot(H256::random()),
 			gas_used: 21_000u64.into(),
 			log_bloom: Default::default(),
+			transaction_type: Default::default(),
 			logs: Vec::new(),
 		}).collect::<Vec<_>>();
 
@@ -16,22 +16,28 @@
 
 //! Encryption providers.
 
+use std::fs;
 use std::io::Read;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::iter::repeat;
 use std::time::{Instant, Duration};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::collections::hash_map::Entry;
 use parking_lot::Mutex;
 use ethcore::account_provider::AccountProvider;
 use ethereum_types::{H128, H256, Address};
 use ethjson;
-use ethkey::{Signature, Password, Public};
+use ethkey::{Signature, Password, Public, public_to_address};
+use ethkey::crypto::ecies;
 use crypto;
 use futures::Future;
 use fetch::{Fetch, Client as FetchClient, Method, BodyReader, Request};
 use bytes::{Bytes, ToPretty};
 use error::{Error, ErrorKind};
+use rand::{Rng, OsRng};
+use rustc_hex::FromHex;
+use serde_json;
 use url::Url;
 use super::find_account_password;
 
@@ -250,6 +256,188 @@ impl Encryptor for SecretStoreEncryptor {
 	}
 }
 
+/// Configuration for local encryptor
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct LocalEncryptorConfig {
+	/// Path to the file with encrypted contract keys
+	pub keys_path: PathBuf,
+	/// Public keys of accounts, which are able to decrypt contract keys (usually, of all validators)
+	pub validators: Vec<Public>,
+	/// Passwords used to unlock accounts
+	pub passwords: Vec<Password>,
+}
+
+/// Contract key, encrypted with public key of every validator, as it is stored in the keys file.
+#[derive(Serialize, Deserialize)]
+struct StoredContractKey {
+	/// Private contract address.
+	contract: ethjson::hash::Address,
+	/// Hex-encoded ECIES-encrypted contract key for every validator address.
+	keys: BTreeMap<ethjson::hash::Address, String>,
+}
+
+/// Encryption/decryption operations without secret store. Contract keys are randomly generated
+/// by the node, encrypted with public key of every validator and stored in the local file.
+pub struct LocalEncryptor {
+	config: LocalEncryptorConfig,
+	/// Encrypted contract keys.
+	encrypted_keys: Mutex<HashMap<Address, BTreeMap<Address, Bytes>>>,
+	/// Decrypted contract keys.
+	keys: Mutex<HashMap<Address, Bytes>>,
+}
+
+impl LocalEncryptor {
+	/// Create new encryptor, reading previously generated keys from the keys file
+	pub fn new(config: LocalEncryptorConfig) -> Result<Self, Error> {
+		if config.validators.is_empty() {
+			bail!(ErrorKind::Encrypt("Local encryptor requires at least one validator key".into()));
+		}
+
+		let encrypted_keys = match fs::File::open(&config.keys_path) {
+			Ok(file) => {
+				let stored: Vec<StoredContractKey> = serde_json::from_reader(file)
+					.map_err(|e| ErrorKind::Encrypt(format!("Invalid keys file: {}", e)))?;
+				stored.into_iter()
+					.map(|stored| Ok((stored.contract.into(), stored.keys.into_iter()
+						.map(|(validator, key)| Ok((validator.into(), key.from_hex()
+							.map_err(|e| ErrorKind::Encrypt(format!("Invalid keys file: {}", e)))?)))
+						.collect::<Result<BTreeMap<_, _>, Error>>()?)))
+					.collect::<Result<HashMap<_, _>, Error>>()?
+			},
+			Err(ref e) if e.kind() == ::std::io::ErrorKind::NotFound => HashMap::new(),
+			Err(e) => return Err(e.into()),
+		};
+
+		Ok(LocalEncryptor {
+			config,
+			encrypted_keys: Mutex::new(encrypted_keys),
+			keys: Mutex::default(),
+		})
+	}
+
+	/// Decrypt existing contract key or generate the new one.
+	fn retrieve_key(&self, contract_address: &Address, accounts: &AccountProvider, generate: bool) -> Result<Bytes, Error> {
+		if let Some(key) = self.keys.lock().get(contract_address) {
+			return Ok(key.clone());
+		}
+
+		let mut encrypted_keys = self.encrypted_keys.lock();
+		let key = match encrypted_keys.get(contract_address) {
+			Some(contract_keys) => self.decrypt_key(contract_address, contract_keys, accounts)?,
+			None if generate => {
+				// key, generated by the node which is not a validator, could never be decrypted by this node
+				if !self.config.validators.iter().any(|public| accounts.has_account(public_to_address(public))) {
+					bail!(ErrorKind::Encrypt("Contract keys can only be generated by the validator node".into()));
+				}
+
+				trace!(target: "privatetx", "Generating local key for contract {:?}", contract_address);
+
+				let mut key = vec![0u8; INIT_VEC_LEN];
+				OsRng::new()?.fill_bytes(&mut key);
+				let contract_keys = self.config.validators.iter()
+					.map(|public| Ok((public_to_address(public), ecies::encrypt(public, &crypto::DEFAULT_MAC, &key)
+						.map_err(|e| ErrorKind::Encrypt(e.to_string()))?)))
+					.collect::<Result<BTreeMap<_, _>, Error>>()?;
+				encrypted_keys.insert(*contract_address, contract_keys);
+				if let Err(error) = self.write_keys(&encrypted_keys) {
+					encrypted_keys.remove(contract_address);
+					return Err(error);
+				}
+				key
+			},
+			None => bail!(ErrorKind::EncryptionKeyNotFound(*contract_address)),
+		};
+
+		self.keys.lock().insert(*contract_address, key.clone());
+		Ok(key)
+	}
+
+	/// Decrypt contract key with any of local validator accounts.
+	fn decrypt_key(&self, contract_address: &Address, contract_keys: &BTreeMap<Address, Bytes>, accounts: &AccountProvider) -> Result<Bytes, Error> {
+		contract_keys.iter()
+			.filter(|&(validator, _)| accounts.has_account(*validator))
+			.filter_map(|(validator, encrypted_key)| {
+				let password = find_account_password(&self.config.passwords, accounts, validator);
+				accounts.decrypt(*validator, password, &crypto::DEFAULT_MAC, encrypted_key).ok()
+			})
+			.next()
+			.ok_or_else(|| ErrorKind::Decrypt(format!("No local account is able to decrypt key of contract {:?}", contract_address)).into())
+	}
+
+	/// Write encrypted contract keys to the keys file. Keys are written to the temporary file first,
+	/// which then replaces the keys file, so that existing keys are never lost.
+	fn write_keys(&self, encrypted_keys: &HashMap<Address, BTreeMap<Address, Bytes>>) -> Result<(), Error> {
+		let stored: Vec<_> = encrypted_keys.iter()
+			.map(|(contract, keys)| StoredContractKey {
+				contract: (*contract).into(),
+				keys: keys.iter().map(|(validator, key)| ((*validator).into(), key.to_hex())).collect(),
+			})
+			.collect();
+
+		if let Some(dir) = self.config.keys_path.parent() {
+			fs::create_dir_all(dir)?;
+		}
+		let mut temp_path = self.config.keys_path.clone().into_os_string();
+		temp_path.push(".tmp");
+		let temp_path = PathBuf::from(temp_path);
+
+		let mut file = fs::File::create(&temp_path)?;
+		serde_json::to_writer_pretty(&mut file, &stored)
+			.map_err(|e| ErrorKind::Encrypt(format!("Unable to write keys file: {}", e)))?;
+		file.sync_all()?;
+		drop(file);
+		fs::rename(&temp_path, &self.config.keys_path)?;
+
+		Ok(())
+	}
+}
+
+impl Encryptor for LocalEncryptor {
+	fn encrypt(
+		&self,
+		contract_address: &Address,
+		accounts: &AccountProvider,
+		initialisation_vector: &H128,
+		plain_data: &[u8],
+	) -> Result<Bytes, Error> {
+		// retrieve the key, generate it if it doesn't exist yet
+		let key = self.retrieve_key(contract_address, accounts, true)?;
+
+		// encrypt data
+		let mut cypher = Vec::with_capacity(plain_data.len() + initialisation_vector.len());
+		cypher.extend(repeat(0).take(plain_data.len()));
+		crypto::aes::encrypt_128_ctr(&key, initialisation_vector, plain_data, &mut cypher)
+			.map_err(|e| ErrorKind::Encrypt(e.to_string()))?;
+		cypher.extend_from_slice(&initialisation_vector);
+
+		Ok(cypher)
+	}
+
+	fn decrypt(
+		&self,
+		contract_address: &Address,
+		accounts: &AccountProvider,
+		cypher: &[u8],
+	) -> Result<Bytes, Error> {
+		// initialization vector takes INIT_VEC_LEN bytes
+		let cypher_len = cypher.len();
+		if cypher_len < INIT_VEC_LEN {
+			bail!(ErrorKind::Decrypt("Invalid cypher".into()));
+		}
+
+		// retrieve existing key
+		let key = self.retrieve_key(contract_address, accounts, false)?;
+
+		// use symmetric decryption to decrypt document
+		let (cypher, iv) = cypher.split_at(cypher_len - INIT_VEC_LEN);
+		let mut plain_data = Vec::with_capacity(cypher_len - INIT_VEC_LEN);
+		plain_data.extend(repeat(0).take(cypher_len - INIT_VEC_LEN));
+		crypto::aes::decrypt_128_ctr(&key, &iv, cypher, &mut plain_data)
+			.map_err(|e| ErrorKind::Decrypt(e.to_string()))?;
+		Ok(plain_data)
+	}
+}
+
 /// Dummy encryptor.
 #[derive(Default)]
 pub struct NoopEncryptor;
@@ -274,3 +462,74 @@ impl Encryptor for NoopEncryptor {
 		Ok(data.to_vec())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	extern crate tempdir;
+
+	use self::tempdir::TempDir;
+	use ethcore::account_provider::AccountProvider;
+	use ethereum_types::{H128, Address};
+	use ethkey::{Generator, Random};
+	use error::{Error, ErrorKind};
+	use super::{Encryptor, LocalEncryptor, LocalEncryptorConfig};
+
+	#[test]
+	fn local_encryptor_encrypts_and_decrypts_with_validator_key() {
+		let tempdir = TempDir::new("").unwrap();
+		let validator = Random.generate().unwrap();
+		let accounts = AccountProvider::transient_provider();
+		accounts.insert_account(validator.secret().clone(), &"".into()).unwrap();
+
+		let config = LocalEncryptorConfig {
+			keys_path: tempdir.path().join("keys.json"),
+			validators: vec![validator.public().clone(), Random.generate().unwrap().public().clone()],
+			passwords: vec!["".into()],
+		};
+		let contract = Address::from(1);
+		let iv = H128::from(2);
+		let plain_data = b"private state".to_vec();
+
+		// encryption generates new contract key
+		let encryptor = LocalEncryptor::new(config.clone()).unwrap();
+		let cypher = encryptor.encrypt(&contract, &accounts, &iv, &plain_data).unwrap();
+		assert!(cypher != plain_data);
+		assert_eq!(encryptor.decrypt(&contract, &accounts, &cypher).unwrap(), plain_data);
+
+		// contract key is read from the keys file and decrypted with validator account
+		let encryptor = LocalEncryptor::new(config).unwrap();
+		assert_eq!(encryptor.decrypt(&contract, &accounts, &cypher).unwrap(), plain_data);
+		assert_eq!(encryptor.encrypt(&contract, &accounts, &iv, &plain_data).unwrap(), cypher);
+
+		// key of unknown contract isn't generated on decryption
+		match encryptor.decrypt(&Address::from(3), &accounts, &cypher) {
+			Err(Error(ErrorKind::EncryptionKeyNotFound(_), _)) => (),
+			_ => panic!("expected EncryptionKeyNotFound"),
+		}
+	}
+
+	#[test]
+	fn local_encryptor_fails_without_validator_account() {
+		let tempdir = TempDir::new("").unwrap();
+		let validator = Random.generate().unwrap();
+		let validator_accounts = AccountProvider::transient_provider();
+		validator_accounts.insert_account(validator.secret().clone(), &"".into()).unwrap();
+		let accounts = AccountProvider::transient_provider();
+		let config = LocalEncryptorConfig {
+			keys_path: tempdir.path().join("keys.json"),
+			validators: vec![validator.public().clone()],
+			passwords: vec!["".into()],
+		};
+
+		// key isn't generated by the non-validator node
+		let contract = Address::from(1);
+		let encryptor = LocalEncryptor::new(config.clone()).unwrap();
+		assert!(encryptor.encrypt(&contract, &accounts, &H128::from(2), b"data").is_err());
+		assert!(!config.keys_path.exists());
+
+		// key, generated by the validator, can't be decrypted by the non-validator node
+		assert!(encryptor.encrypt(&contract, &validator_accounts, &H128::from(2), b"data").is_ok());
+		let encryptor = LocalEncryptor::new(config).unwrap();
+		assert!(encryptor.decrypt(&contract, &accounts, &[0u8; 32]).is_err());
+	}
+}
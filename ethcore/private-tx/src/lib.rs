@@ -46,6 +46,8 @@ extern crate patricia_trie_ethereum as ethtrie;
 extern crate rlp;
 extern crate url;
 extern crate rustc_hex;
+extern crate rand;
+extern crate serde_json;
 #[macro_use]
 extern crate log;
 #[macro_use]
@@ -56,13 +58,13 @@ extern crate ethabi_contract;
 extern crate error_chain;
 #[macro_use]
 extern crate rlp_derive;
+#[macro_use]
+extern crate serde_derive;
 
-#[cfg(test)]
-extern crate rand;
 #[cfg(test)]
 extern crate ethcore_logger;
 
-pub use encryptor::{Encryptor, SecretStoreEncryptor, EncryptorConfig, NoopEncryptor};
+pub use encryptor::{Encryptor, SecretStoreEncryptor, EncryptorConfig, LocalEncryptor, LocalEncryptorConfig, NoopEncryptor};
 pub use private_transactions::{VerifiedPrivateTransaction, VerificationStore, PrivateTransactionSigningDesc, SigningStore};
 pub use messages::{PrivateTransaction, SignedPrivateTransaction};
 pub use error::{Error, ErrorKind};
@@ -113,6 +115,21 @@ pub struct ProviderConfig {
 	pub passwords: Vec<Password>,
 }
 
+/// Status of the private transaction, waiting for validators signatures.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrivateTransactionStatus {
+	/// Private transaction hash.
+	pub private_transaction_hash: H256,
+	/// Original transaction hash.
+	pub transaction_hash: H256,
+	/// Private contract address.
+	pub contract_address: Address,
+	/// Validators, which have already signed the private state.
+	pub signed_validators: Vec<Address>,
+	/// Validators, which haven't signed the private state yet.
+	pub pending_validators: Vec<Address>,
+}
+
 #[derive(Debug)]
 /// Private transaction execution receipt.
 pub struct Receipt {
@@ -439,6 +456,72 @@ impl Provider where {
 		self.decrypt(address, &state)
 	}
 
+	/// Check that the requester is a validator of the private contract, who has proven the ownership
+	/// of the local validator account with its password.
+	fn ensure_contract_validator(&self, address: &Address, block: BlockId, requester: &Address, password: &Password) -> Result<(), Error> {
+		let contract_validators = self.get_validators(block, address)?;
+		if !contract_validators.contains(requester) || !self.validator_accounts.contains(requester) {
+			bail!(ErrorKind::NotAuthorised(*requester));
+		}
+		if !self.accounts.test_password(requester, password).unwrap_or(false) {
+			bail!(ErrorKind::NotAuthorised(*requester));
+		}
+		Ok(())
+	}
+
+	/// Returns decrypted private state of the contract. Available to validators of the contract only.
+	pub fn private_state(&self, address: &Address, block: BlockId, requester: &Address, password: &Password) -> Result<HashMap<H256, H256>, Error> {
+		self.ensure_contract_validator(address, block, requester, password)?;
+		Ok(Self::snapshot_to_storage(self.get_decrypted_state(address, block)?))
+	}
+
+	/// Returns value from the decrypted private state of the contract. Available to validators of the contract only.
+	pub fn private_storage_at(&self, address: &Address, key: &H256, block: BlockId, requester: &Address, password: &Password) -> Result<H256, Error> {
+		Ok(self.private_state(address, block, requester, password)?.get(key).cloned().unwrap_or_default())
+	}
+
+	/// Returns decrypted private code of the contract. Available to validators of the contract only.
+	pub fn private_code(&self, address: &Address, block: BlockId, requester: &Address, password: &Password) -> Result<Bytes, Error> {
+		self.ensure_contract_validator(address, block, requester, password)?;
+		self.get_decrypted_code(address, block)
+	}
+
+	/// Returns status of the private transaction, created by this node and waiting for validators signatures.
+	/// Transaction could be found either by the private transaction hash or by the original transaction hash.
+	pub fn private_transaction_status(&self, hash: &H256) -> Result<Option<PrivateTransactionStatus>, Error> {
+		let (private_hash, desc) = {
+			let transactions_for_signing = self.transactions_for_signing.read();
+			match transactions_for_signing.get(hash) {
+				Some(desc) => (*hash, desc),
+				None => match transactions_for_signing.find_by_transaction_hash(hash) {
+					Some(found) => found,
+					None => return Ok(None),
+				},
+			}
+		};
+
+		let contract_address = match desc.original_transaction.action {
+			Action::Call(contract_address) => contract_address,
+			Action::Create => bail!(ErrorKind::BadTransactonType),
+		};
+		let state_hash = self.calculate_state_hash(&desc.state, desc.contract_nonce);
+		let signed_validators = desc.received_signatures.iter()
+			.map(|signature| recover(signature, &state_hash).map(|public| public_to_address(&public)))
+			.collect::<Result<Vec<_>, _>>()?;
+		let pending_validators = desc.validators.iter()
+			.filter(|validator| !signed_validators.contains(validator))
+			.cloned()
+			.collect();
+
+		Ok(Some(PrivateTransactionStatus {
+			private_transaction_hash: private_hash,
+			transaction_hash: desc.original_transaction.hash(),
+			contract_address,
+			signed_validators,
+			pending_validators,
+		}))
+	}
+
 	pub fn get_contract_nonce(&self, address: &Address, block: BlockId) -> Result<U256, Error> {
 		let (data, decoder) = private_contract::functions::nonce::call();
 		let value = self.client.call_contract(block, *address, data)?;
@@ -77,6 +77,14 @@ impl pool::ScoredTransaction for VerifiedPrivateTransaction {
 		&self.transaction.gas_price
 	}
 
+	fn max_priority_fee_per_gas(&self) -> U256 {
+		self.transaction.max_priority_fee_per_gas()
+	}
+
+	fn effective_priority_fee(&self, block_base_fee: Option<U256>) -> U256 {
+		self.transaction.effective_priority_fee(block_base_fee)
+	}
+
 	/// Gets transaction nonce.
 	fn nonce(&self) -> U256 {
 		self.transaction.nonce
@@ -133,7 +141,7 @@ impl Default for VerificationStore {
 			verification_pool: RwLock::new(
 				txpool::Pool::new(
 					txpool::NoopListener,
-					pool::scoring::NonceAndGasPrice(pool::PrioritizationStrategy::GasPriceOnly),
+					pool::scoring::NonceAndGasPrice::new(pool::PrioritizationStrategy::GasPriceOnly),
 					pool::Options {
 						max_count: MAX_QUEUE_LEN,
 						max_per_sender: MAX_QUEUE_LEN / 10,
@@ -246,6 +254,13 @@ impl SigningStore {
 		self.transactions.get(private_hash).cloned()
 	}
 
+	/// Find private transaction's description by the hash of the original transaction
+	pub fn find_by_transaction_hash(&self, transaction_hash: &H256) -> Option<(H256, PrivateTransactionSigningDesc)> {
+		self.transactions.iter()
+			.find(|&(_, desc)| desc.original_transaction.hash() == *transaction_hash)
+			.map(|(private_hash, desc)| (*private_hash, desc.clone()))
+	}
+
 	/// Removes desc from the store (after verification is completed)
 	pub fn remove(&mut self, private_hash: &H256) -> Result<(), Error> {
 		self.transactions.remove(private_hash);
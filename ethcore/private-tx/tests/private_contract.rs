@@ -117,6 +117,16 @@ fn private_contract() {
 	let result = pm.private_call(BlockId::Latest, &query_tx).unwrap();
 	assert_eq!(&result.output[..], &("2a00000000000000000000000000000000000000000000000000000000000000".from_hex().unwrap()[..]));
 	assert_eq!(pm.get_validators(BlockId::Latest, &address).unwrap(), validators);
+	assert_eq!(pm.private_storage_at(&address, &0.into(), BlockId::Latest, &key3.address(), &"".into()).unwrap(),
+		"2a00000000000000000000000000000000000000000000000000000000000000".into());
+	assert_eq!(pm.private_state(&address, BlockId::Latest, &key3.address(), &"".into()).unwrap().len(), 1);
+	assert!(!pm.private_code(&address, BlockId::Latest, &key4.address(), &"".into()).unwrap().is_empty());
+
+	// private state is not revealed to non-validators and to requesters without validator password
+	assert!(pm.private_state(&address, BlockId::Latest, &key1.address(), &"".into()).is_err());
+	assert!(pm.private_state(&address, BlockId::Latest, &key3.address(), &"wrong".into()).is_err());
+	assert!(pm.private_code(&address, BlockId::Latest, &key1.address(), &"".into()).is_err());
+	assert_eq!(pm.private_transaction_status(&private_tx.hash()).unwrap(), None);
 
 	// Now try modification with just one signature
 	trace!("Modifying private state");
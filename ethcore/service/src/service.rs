@@ -234,6 +234,11 @@ impl IoHandler<ClientIoMessage> for ClientIoHandler {
 					warn!("Failed to initialize snapshot restoration: {}", e);
 				}
 			}
+			ClientIoMessage::BeginStateRestoration(ref manifest) => {
+				if let Err(e) = self.snapshot.init_state_restore(manifest.clone()) {
+					warn!("Failed to initialize state restoration: {}", e);
+				}
+			}
 			ClientIoMessage::FeedStateChunk(ref hash, ref chunk) => {
 				self.snapshot.feed_state_chunk(*hash, chunk)
 			}
@@ -25,16 +25,17 @@ use std::fmt;
 use std::time::{Instant, Duration};
 
 use ethstore::accounts_dir::MemoryDirectory;
-use ethstore::ethkey::{Address, Message, Public, Secret, Password, Random, Generator};
+use ethstore::ethkey::{Address, Message, Public, Secret, Password, Random, Generator, Mnemonic, DerivationPath};
 use ethjson::misc::AccountMeta;
 use ethstore::{
 	SimpleSecretStore, SecretStore, Error as SSError, EthStore, EthMultiStore,
-	random_string, SecretVaultRef, StoreAccountRef, OpaqueSecret,
+	random_string, SecretVaultRef, StoreAccountRef, OpaqueSecret, Uuid,
 };
 use parking_lot::RwLock;
+use ethereum_types::H256;
 
 pub use ethstore::ethkey::Signature;
-pub use ethstore::{Derivation, IndexDerivation, KeyFile};
+pub use ethstore::{Derivation, IndexDerivation, KeyFile, HdWalletInfo};
 pub use hardware_wallet::{Error as HardwareError, HardwareWalletManager, KeyPath, TransactionInfo};
 pub use super::transaction::{Action, Transaction};
 
@@ -240,6 +241,40 @@ impl AccountProvider {
 		)
 	}
 
+	/// Stores HD wallet seed derived from given BIP-39 mnemonic and passphrase
+	/// and creates wallet's first account. Returns wallet id and address of the account.
+	pub fn new_hd_wallet(&self, mnemonic: &Mnemonic, passphrase: &str, password: &Password, name: String) -> Result<(String, Address), Error> {
+		let seed = mnemonic.seed(passphrase);
+		let wallet = self.sstore.insert_hd_wallet(&seed, password, DerivationPath::ethereum_base(), name)?;
+		let wallet = wallet.to_string();
+		let address = self.new_hd_account(&wallet, password, 0)?;
+		Ok((wallet, address))
+	}
+
+	/// Lists stored HD wallets.
+	pub fn hd_wallets(&self) -> Result<Vec<HdWalletInfo>, Error> {
+		self.sstore.hd_wallets()
+	}
+
+	/// Returns addresses of HD wallet accounts with indexes in `from..from + count`.
+	/// Accounts are not stored.
+	pub fn hd_wallet_addresses(&self, wallet: &str, password: &Password, from: u32, count: u32) -> Result<Vec<Address>, Error> {
+		let wallet: Uuid = wallet.parse().map_err(|_| SSError::HdWalletNotFound)?;
+		self.sstore.hd_wallet_addresses(&wallet, password, from, count)
+	}
+
+	/// Derives HD wallet account with given index and stores it with the wallet password.
+	/// Does not unlock account!
+	pub fn new_hd_account(&self, wallet: &str, password: &Password, index: u32) -> Result<Address, Error> {
+		let wallet: Uuid = wallet.parse().map_err(|_| SSError::HdWalletNotFound)?;
+		let account = self.sstore.insert_hd_account(SecretVaultRef::Root, &wallet, password, index)?;
+		if self.blacklisted_accounts.contains(&account.address) {
+			self.sstore.remove_account(&account, password)?;
+			return Err(SSError::InvalidAccount.into());
+		}
+		Ok(account.address)
+	}
+
 	/// Import a new presale wallet.
 	pub fn import_presale(&self, presale_json: &[u8], password: &Password) -> Result<Address, Error> {
 		let account = self.sstore.import_presale(SecretVaultRef::Root, presale_json, password)?;
@@ -643,6 +678,15 @@ impl AccountProvider {
 		}
 	}
 
+	/// Sign EIP-712 typed data, given its domain separator and message hash, with hardware wallet.
+	pub fn sign_typed_data_with_hardware(&self, address: &Address, domain_hash: &H256, message_hash: &H256) -> Result<Signature, SignError> {
+		match self.hardware_store.as_ref().map(|s| s.sign_typed_data(address, domain_hash, message_hash)) {
+			None | Some(Err(HardwareError::KeyNotFound)) => Err(SignError::NotFound),
+			Some(Err(e)) => Err(From::from(e)),
+			Some(Ok(s)) => Ok(s),
+		}
+	}
+
 	/// Sign transaction with hardware wallet.
 	pub fn sign_transaction_with_hardware(&self, address: &Address, transaction: &Transaction, chain_id: Option<u64>, rlp_encoded_transaction: &[u8]) -> Result<Signature, SignError> {
 		let t_info = TransactionInfo {
@@ -669,7 +713,7 @@ impl AccountProvider {
 mod tests {
 	use super::{AccountProvider, Unlock};
 	use std::time::{Duration, Instant};
-	use ethstore::ethkey::{Generator, Random, Address};
+	use ethstore::ethkey::{Generator, Random, Address, Mnemonic};
 	use ethstore::{StoreAccountRef, Derivation};
 	use ethereum_types::H256;
 
@@ -684,6 +728,21 @@ mod tests {
 		assert!(ap.sign(kp.address(), None, Default::default()).is_err());
 	}
 
+	#[test]
+	fn hd_wallet_accounts() {
+		let ap = AccountProvider::transient_provider();
+		let mnemonic = Mnemonic::from_phrase("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about").unwrap();
+		let (wallet, address) = ap.new_hd_wallet(&mnemonic, "", &"test".into(), "Wallet".into()).unwrap();
+		assert_eq!(address, "9858effd232b4033e47d90003d41ec34ecaeda94".into());
+		assert_eq!(ap.hd_wallets().unwrap()[0].id.to_string(), wallet);
+
+		let addresses = ap.hd_wallet_addresses(&wallet, &"test".into(), 0, 2).unwrap();
+		assert_eq!(addresses[0], address);
+		assert_eq!(ap.new_hd_account(&wallet, &"test".into(), 1).unwrap(), addresses[1]);
+		assert!(ap.unlock_account_permanently(addresses[1], "test".into()).is_ok());
+		assert!(ap.new_hd_account("invalid", &"test".into(), 1).is_err());
+	}
+
 	#[test]
 	fn derived_account_nosave() {
 		let kp = Random.generate().unwrap();
@@ -41,9 +41,9 @@ use error::{Error, BlockError};
 use ethereum_types::{H256, U256, Address, Bloom};
 use factory::Factories;
 use hash::keccak;
-use header::{Header, ExtendedHeader};
+use header::{BlockNumber, Header, ExtendedHeader};
 use receipt::{Receipt, TransactionOutcome};
-use rlp::{Rlp, RlpStream, Encodable, Decodable, DecoderError, encode_list};
+use rlp::{Rlp, RlpStream, Decodable, DecoderError, encode_list};
 use state_db::StateDB;
 use state::State;
 use trace::Tracing;
@@ -73,10 +73,9 @@ impl Block {
 		block_rlp.append_list(&self.uncles);
 		block_rlp.out()
 	}
-}
 
-impl Decodable for Block {
-	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+	/// Decode a block, reading the base fee of headers at or after `eip1559_transition`.
+	pub fn decode_rlp(rlp: &Rlp, eip1559_transition: BlockNumber) -> Result<Self, DecoderError> {
 		if rlp.as_raw().len() != rlp.payload_info()?.total() {
 			return Err(DecoderError::RlpIsTooBig);
 		}
@@ -84,13 +83,19 @@ impl Decodable for Block {
 			return Err(DecoderError::RlpIncorrectListLen);
 		}
 		Ok(Block {
-			header: rlp.val_at(0)?,
+			header: Header::decode_rlp(&rlp.at(0)?, eip1559_transition)?,
 			transactions: rlp.list_at(1)?,
-			uncles: rlp.list_at(2)?,
+			uncles: Header::decode_rlp_list(&rlp.at(2)?, eip1559_transition)?,
 		})
 	}
 }
 
+impl Decodable for Block {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		Block::decode_rlp(rlp, BlockNumber::max_value())
+	}
+}
+
 /// An internal type for a block's common elements.
 #[derive(Clone)]
 pub struct ExecutedBlock {
@@ -142,6 +147,7 @@ impl ExecutedBlock {
 			last_hashes: self.last_hashes.clone(),
 			gas_used: self.receipts.last().map_or(U256::zero(), |r| r.gas_used),
 			gas_limit: self.header.gas_limit().clone(),
+			base_fee: self.header.base_fee(),
 		}
 	}
 
@@ -408,11 +414,11 @@ impl<'x> OpenBlock<'x> {
 		s.engine.on_close_block(&mut s.block)?;
 		s.block.state.commit()?;
 
-		s.block.header.set_transactions_root(ordered_trie_root(s.block.transactions.iter().map(|e| e.rlp_bytes())));
+		s.block.header.set_transactions_root(ordered_trie_root(s.block.transactions.iter().map(|e| e.encode_envelope())));
 		let uncle_bytes = encode_list(&s.block.uncles);
 		s.block.header.set_uncles_hash(keccak(&uncle_bytes));
 		s.block.header.set_state_root(s.block.state.root().clone());
-		s.block.header.set_receipts_root(ordered_trie_root(s.block.receipts.iter().map(|r| r.rlp_bytes())));
+		s.block.header.set_receipts_root(ordered_trie_root(s.block.receipts.iter().map(|r| r.encode_envelope())));
 		s.block.header.set_log_bloom(s.block.receipts.iter().fold(Bloom::zero(), |mut b, r| {
 			b.accrue_bloom(&r.log_bloom);
 			b
@@ -476,7 +482,7 @@ impl LockedBlock {
 			receipt.outcome = TransactionOutcome::Unknown;
 		}
 		self.block.header.set_receipts_root(
-			ordered_trie_root(self.block.receipts.iter().map(|r| r.rlp_bytes()))
+			ordered_trie_root(self.block.receipts.iter().map(|r| r.encode_envelope()))
 		);
 		// compute hash and cache it.
 		self.block.header.compute_hash();
@@ -649,7 +655,7 @@ mod tests {
 		last_hashes: Arc<LastHashes>,
 		factories: Factories,
 	) -> Result<LockedBlock, Error> {
-		let block = Unverified::from_rlp(block_bytes)?;
+		let block = Unverified::from_rlp(block_bytes, engine.params().eip1559_transition)?;
 		let header = block.header;
 		let transactions: Result<Vec<_>, Error> = block
 			.transactions
@@ -701,7 +707,7 @@ mod tests {
 		last_hashes: Arc<LastHashes>,
 		factories: Factories,
 	) -> Result<SealedBlock, Error> {
-		let header = Unverified::from_rlp(block_bytes.clone())?.header;
+		let header = Unverified::from_rlp(block_bytes.clone(), engine.params().eip1559_transition)?.header;
 		Ok(enact_bytes(block_bytes, engine, tracing, db, parent, last_hashes, factories)?
 		   .seal(engine, header.seal().to_vec())?)
 	}
@@ -229,6 +229,8 @@ pub struct BlockChain {
 
 	cache_man: Mutex<CacheManager<CacheId>>,
 
+	eip1559_transition: BlockNumber,
+
 	pending_best_block: RwLock<Option<BestBlock>>,
 	pending_block_hashes: RwLock<HashMap<BlockNumber, H256>>,
 	pending_block_details: RwLock<HashMap<H256, BlockDetails>>,
@@ -453,7 +455,7 @@ impl<'a> Iterator for AncestryWithMetadataIter<'a> {
 		} else {
 			let details = self.chain.block_details(&self.current);
 			let header = self.chain.block_header_data(&self.current)
-				.map(|h| h.decode().expect("Stored block header data is valid RLP; qed"));
+				.map(|h| h.decode(self.chain.eip1559_transition).expect("Stored block header data is valid RLP; qed"));
 
 			match (details, header) {
 				(Some(details), Some(header)) => {
@@ -538,6 +540,7 @@ impl BlockChain {
 			block_receipts: RwLock::new(HashMap::new()),
 			db: db.clone(),
 			cache_man: Mutex::new(cache_man),
+			eip1559_transition: config.eip1559_transition,
 			pending_best_block: RwLock::new(None),
 			pending_block_hashes: RwLock::new(HashMap::new()),
 			pending_block_details: RwLock::new(HashMap::new()),
@@ -591,7 +594,7 @@ impl BlockChain {
 			let mut best_block = bc.best_block.write();
 			*best_block = BestBlock {
 				total_difficulty: best_block_total_difficulty,
-				header: best_block_rlp.decode_header(),
+				header: best_block_rlp.decode_header(bc.eip1559_transition),
 				block: best_block_rlp,
 			};
 		}
@@ -1095,7 +1098,7 @@ impl BlockChain {
 				batch.put(db::COL_EXTRA, b"best", &update.info.hash);
 				*best_block = Some(BestBlock {
 					total_difficulty: update.info.total_difficulty,
-					header: update.block.decode_header(),
+					header: update.block.decode_header(self.eip1559_transition),
 					block: update.block,
 				});
 			}
@@ -1362,6 +1365,11 @@ impl BlockChain {
 		}
 	}
 
+	/// Block number from which stored headers carry the EIP-1559 base fee.
+	pub fn eip1559_transition(&self) -> BlockNumber {
+		self.eip1559_transition
+	}
+
 	/// Get best block hash.
 	pub fn best_block_hash(&self) -> H256 {
 		self.best_block.read().header.hash()
@@ -2054,6 +2062,7 @@ mod tests {
 			outcome: TransactionOutcome::StateRoot(H256::default()),
 			gas_used: 10_000.into(),
 			log_bloom: Default::default(),
+			transaction_type: Default::default(),
 			logs: vec![
 				LogEntry { address: Default::default(), topics: vec![], data: vec![1], },
 				LogEntry { address: Default::default(), topics: vec![], data: vec![2], },
@@ -2063,6 +2072,7 @@ mod tests {
 			outcome: TransactionOutcome::StateRoot(H256::default()),
 			gas_used: 10_000.into(),
 			log_bloom: Default::default(),
+			transaction_type: Default::default(),
 			logs: vec![
 				LogEntry { address: Default::default(), topics: vec![], data: vec![3], },
 			],
@@ -2072,6 +2082,7 @@ mod tests {
 				outcome: TransactionOutcome::StateRoot(H256::default()),
 				gas_used: 10_000.into(),
 				log_bloom: Default::default(),
+				transaction_type: Default::default(),
 				logs: vec![
 					LogEntry { address: Default::default(), topics: vec![], data: vec![4], },
 				],
@@ -2082,6 +2093,7 @@ mod tests {
 				outcome: TransactionOutcome::StateRoot(H256::default()),
 				gas_used: 10_000.into(),
 				log_bloom: Default::default(),
+				transaction_type: Default::default(),
 				logs: vec![
 					LogEntry { address: Default::default(), topics: vec![], data: vec![5], },
 				],
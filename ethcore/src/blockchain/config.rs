@@ -16,6 +16,8 @@
 
 //! Blockchain configuration.
 
+use header::BlockNumber;
+
 /// Blockchain configuration.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Config {
@@ -23,6 +25,8 @@ pub struct Config {
 	pub pref_cache_size: usize,
 	/// Maximum cache size in bytes.
 	pub max_cache_size: usize,
+	/// Block number from which stored headers carry the EIP-1559 base fee.
+	pub eip1559_transition: BlockNumber,
 }
 
 impl Default for Config {
@@ -30,6 +34,7 @@ impl Default for Config {
 		Config {
 			pref_cache_size: 1 << 14,
 			max_cache_size: 1 << 20,
+			eip1559_transition: BlockNumber::max_value(),
 		}
 	}
 }
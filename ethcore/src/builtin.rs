@@ -19,7 +19,7 @@
 use std::cmp::{max, min};
 use std::io::{self, Read};
 
-use byteorder::{ByteOrder, BigEndian};
+use byteorder::{ByteOrder, BigEndian, LittleEndian};
 use parity_crypto::digest;
 use num::{BigUint, Zero, One};
 
@@ -53,8 +53,8 @@ pub trait Impl: Send + Sync {
 
 /// A gas pricing scheme for built-in contracts.
 pub trait Pricer: Send + Sync {
-	/// The gas cost of running this built-in for the given input data.
-	fn cost(&self, input: &[u8]) -> U256;
+	/// The gas cost of running this built-in for the given input data at block number `at`.
+	fn cost(&self, input: &[u8], at: u64) -> U256;
 }
 
 /// A linear pricing model. This computes a price using a base cost and a cost per-word.
@@ -69,7 +69,7 @@ struct ModexpPricer {
 }
 
 impl Pricer for Linear {
-	fn cost(&self, input: &[u8]) -> U256 {
+	fn cost(&self, input: &[u8], _at: u64) -> U256 {
 		U256::from(self.base) + U256::from(self.word) * U256::from((input.len() + 31) / 32)
 	}
 }
@@ -78,17 +78,57 @@ impl Pricer for Linear {
 struct AltBn128PairingPricer {
 	base: usize,
 	pair: usize,
+	eip1108_transition_at: u64,
+	eip1108_transition_base: usize,
+	eip1108_transition_pair: usize,
 }
 
 impl Pricer for AltBn128PairingPricer {
-	fn cost(&self, input: &[u8]) -> U256 {
-		let cost = U256::from(self.base) + U256::from(self.pair) * U256::from(input.len() / 192);
-		cost
+	fn cost(&self, input: &[u8], at: u64) -> U256 {
+		let (base, pair) = if at >= self.eip1108_transition_at {
+			(self.eip1108_transition_base, self.eip1108_transition_pair)
+		} else {
+			(self.base, self.pair)
+		};
+		U256::from(base) + U256::from(pair) * U256::from(input.len() / 192)
+	}
+}
+
+/// A pricing model for the constant-cost alt_bn128 operations (ECADD and ECMUL), repriced by EIP-1108.
+struct AltBn128ConstOperations {
+	price: usize,
+	eip1108_transition_at: u64,
+	eip1108_transition_price: usize,
+}
+
+impl Pricer for AltBn128ConstOperations {
+	fn cost(&self, _input: &[u8], at: u64) -> U256 {
+		if at >= self.eip1108_transition_at {
+			self.eip1108_transition_price.into()
+		} else {
+			self.price.into()
+		}
+	}
+}
+
+/// A pricing model for the blake2 compression function. The cost is linear in the number of rounds.
+struct Blake2FPricer {
+	gas_per_round: u64,
+}
+
+impl Pricer for Blake2FPricer {
+	fn cost(&self, input: &[u8], _at: u64) -> U256 {
+		// Inputs shorter than the rounds field are rejected by `execute`, so they are free here.
+		if input.len() < 4 {
+			return U256::zero();
+		}
+		let rounds = BigEndian::read_u32(&input[..4]);
+		U256::from(self.gas_per_round) * U256::from(rounds)
 	}
 }
 
 impl Pricer for ModexpPricer {
-	fn cost(&self, input: &[u8]) -> U256 {
+	fn cost(&self, input: &[u8], _at: u64) -> U256 {
 		let mut reader = input.chain(io::repeat(0));
 		let mut buf = [0; 32];
 
@@ -164,7 +204,7 @@ pub struct Builtin {
 
 impl Builtin {
 	/// Simple forwarder for cost.
-	pub fn cost(&self, input: &[u8]) -> U256 { self.pricer.cost(input) }
+	pub fn cost(&self, input: &[u8], at: u64) -> U256 { self.pricer.cost(input, at) }
 
 	/// Simple forwarder for execute.
 	pub fn execute(&self, input: &[u8], output: &mut BytesRef) -> Result<(), Error> {
@@ -177,6 +217,7 @@ impl Builtin {
 
 impl From<ethjson::spec::Builtin> for Builtin {
 	fn from(b: ethjson::spec::Builtin) -> Self {
+		let eip1108_transition = b.eip1108_transition.map(Into::into).unwrap_or(u64::max_value());
 		let pricer: Box<Pricer> = match b.pricing {
 			ethjson::spec::Pricing::Linear(linear) => {
 				Box::new(Linear {
@@ -198,6 +239,21 @@ impl From<ethjson::spec::Builtin> for Builtin {
 				Box::new(AltBn128PairingPricer {
 					base: pricer.base,
 					pair: pricer.pair,
+					eip1108_transition_at: eip1108_transition,
+					eip1108_transition_base: pricer.eip1108_transition_base.unwrap_or(pricer.base),
+					eip1108_transition_pair: pricer.eip1108_transition_pair.unwrap_or(pricer.pair),
+				})
+			}
+			ethjson::spec::Pricing::AltBn128ConstOperations(pricer) => {
+				Box::new(AltBn128ConstOperations {
+					price: pricer.price,
+					eip1108_transition_at: eip1108_transition,
+					eip1108_transition_price: pricer.eip1108_transition_price,
+				})
+			}
+			ethjson::spec::Pricing::Blake2F(pricer) => {
+				Box::new(Blake2FPricer {
+					gas_per_round: pricer.gas_per_round,
 				})
 			}
 		};
@@ -221,6 +277,7 @@ pub fn ethereum_builtin(name: &str) -> Box<Impl> {
 		"alt_bn128_add" => Box::new(Bn128AddImpl) as Box<Impl>,
 		"alt_bn128_mul" => Box::new(Bn128MulImpl) as Box<Impl>,
 		"alt_bn128_pairing" => Box::new(Bn128PairingImpl) as Box<Impl>,
+		"blake2_f" => Box::new(Blake2F) as Box<Impl>,
 		_ => panic!("invalid builtin name: {}", name),
 	}
 }
@@ -232,6 +289,8 @@ pub fn ethereum_builtin(name: &str) -> Box<Impl> {
 // - sha256
 // - ripemd160
 // - modexp (EIP198)
+// - alt_bn128 add, mul and pairing (EIP196, EIP197)
+// - blake2 compression function F (EIP152)
 
 #[derive(Debug)]
 struct Identity;
@@ -257,6 +316,9 @@ struct Bn128MulImpl;
 #[derive(Debug)]
 struct Bn128PairingImpl;
 
+#[derive(Debug)]
+struct Blake2F;
+
 impl Impl for Identity {
 	fn execute(&self, input: &[u8], output: &mut BytesRef) -> Result<(), Error> {
 		output.write(0, input);
@@ -556,9 +618,118 @@ impl Bn128PairingImpl {
 	}
 }
 
+/// Initialisation vector of BLAKE2b.
+const BLAKE2_IV: [u64; 8] = [
+	0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+	0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+/// Message word schedule permutations for each round of BLAKE2b.
+const BLAKE2_SIGMA: [[usize; 16]; 10] = [
+	[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+	[14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+	[11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+	[7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+	[9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+	[2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+	[12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+	[13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+	[6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+	[10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+// The G mixing function of BLAKE2b (RFC 7693, section 3.1).
+fn blake2_g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+	v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+	v[d] = (v[d] ^ v[a]).rotate_right(32);
+	v[c] = v[c].wrapping_add(v[d]);
+	v[b] = (v[b] ^ v[c]).rotate_right(24);
+	v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+	v[d] = (v[d] ^ v[a]).rotate_right(16);
+	v[c] = v[c].wrapping_add(v[d]);
+	v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+// The F compression function of BLAKE2b (RFC 7693, section 3.2) with a configurable number of rounds.
+fn blake2_compress(h: &mut [u64; 8], m: &[u64; 16], t: [u64; 2], f: bool, rounds: usize) {
+	let mut v = [0u64; 16];
+	v[..8].copy_from_slice(&h[..]);
+	v[8..].copy_from_slice(&BLAKE2_IV);
+	v[12] ^= t[0];
+	v[13] ^= t[1];
+	if f {
+		v[14] = !v[14];
+	}
+
+	for i in 0..rounds {
+		let s = &BLAKE2_SIGMA[i % 10];
+		blake2_g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+		blake2_g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+		blake2_g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+		blake2_g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+		blake2_g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+		blake2_g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+		blake2_g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+		blake2_g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+	}
+
+	for i in 0..8 {
+		h[i] ^= v[i] ^ v[i + 8];
+	}
+}
+
+impl Impl for Blake2F {
+	/// Can fail if:
+	///     - input length is not exactly 213 bytes
+	///     - the final block indicator flag is neither 0 nor 1
+	fn execute(&self, input: &[u8], output: &mut BytesRef) -> Result<(), Error> {
+		const BLAKE2_F_ARG_LEN: usize = 213;
+
+		if input.len() != BLAKE2_F_ARG_LEN {
+			trace!(target: "builtin", "input length for Blake2 F precompile should be exactly 213 bytes, was {}", input.len());
+			return Err("input length for Blake2 F precompile should be exactly 213 bytes".into())
+		}
+
+		let rounds = BigEndian::read_u32(&input[..4]) as usize;
+
+		let mut h = [0u64; 8];
+		for (i, state_word) in h.iter_mut().enumerate() {
+			*state_word = LittleEndian::read_u64(&input[4 + i * 8..12 + i * 8]);
+		}
+
+		let mut m = [0u64; 16];
+		for (i, msg_word) in m.iter_mut().enumerate() {
+			*msg_word = LittleEndian::read_u64(&input[68 + i * 8..76 + i * 8]);
+		}
+
+		let t = [
+			LittleEndian::read_u64(&input[196..204]),
+			LittleEndian::read_u64(&input[204..212]),
+		];
+
+		let f = match input[212] {
+			1 => true,
+			0 => false,
+			_ => {
+				trace!(target: "builtin", "incorrect final block indicator flag, was: {}", input[212]);
+				return Err("incorrect final block indicator flag".into())
+			}
+		};
+
+		blake2_compress(&mut h, &m, t, f, rounds);
+
+		let mut output_buf = [0u8; 64];
+		for (i, state_word) in h.iter().enumerate() {
+			LittleEndian::write_u64(&mut output_buf[i * 8..(i + 1) * 8], *state_word);
+		}
+		output.write(0, &output_buf[..]);
+		Ok(())
+	}
+}
+
 #[cfg(test)]
 mod tests {
-	use super::{Builtin, Linear, ethereum_builtin, Pricer, ModexpPricer, modexp as me};
+	use super::{Builtin, Linear, ethereum_builtin, Pricer, ModexpPricer, Blake2FPricer, modexp as me};
 	use ethjson;
 	use ethereum_types::U256;
 	use bytes::BytesRef;
@@ -723,7 +894,7 @@ mod tests {
 		{
 			let input = FromHex::from_hex("0000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000003b27bafd00000000000000000000000000000000000000000000000000000000503c8ac3").unwrap();
 			let expected_cost = U256::max_value();
-			assert_eq!(f.cost(&input[..]), expected_cost.into());
+			assert_eq!(f.cost(&input[..], 0), expected_cost.into());
 		}
 
 		// test for potential exp len overflow
@@ -740,7 +911,7 @@ mod tests {
 
 			f.execute(&input[..], &mut BytesRef::Fixed(&mut output[..])).expect("Builtin should fail");
 			assert_eq!(output, expected);
-			assert_eq!(f.cost(&input[..]), expected_cost.into());
+			assert_eq!(f.cost(&input[..], 0), expected_cost.into());
 		}
 
 		// fermat's little theorem example.
@@ -760,7 +931,7 @@ mod tests {
 
 			f.execute(&input[..], &mut BytesRef::Fixed(&mut output[..])).expect("Builtin should not fail");
 			assert_eq!(output, expected);
-			assert_eq!(f.cost(&input[..]), expected_cost.into());
+			assert_eq!(f.cost(&input[..], 0), expected_cost.into());
 		}
 
 		// second example from EIP: zero base.
@@ -779,7 +950,7 @@ mod tests {
 
 			f.execute(&input[..], &mut BytesRef::Fixed(&mut output[..])).expect("Builtin should not fail");
 			assert_eq!(output, expected);
-			assert_eq!(f.cost(&input[..]), expected_cost.into());
+			assert_eq!(f.cost(&input[..], 0), expected_cost.into());
 		}
 
 		// another example from EIP: zero-padding
@@ -799,7 +970,7 @@ mod tests {
 
 			f.execute(&input[..], &mut BytesRef::Fixed(&mut output[..])).expect("Builtin should not fail");
 			assert_eq!(output, expected);
-			assert_eq!(f.cost(&input[..]), expected_cost.into());
+			assert_eq!(f.cost(&input[..], 0), expected_cost.into());
 		}
 
 		// zero-length modulus.
@@ -817,7 +988,7 @@ mod tests {
 
 			f.execute(&input[..], &mut BytesRef::Flexible(&mut output)).expect("Builtin should not fail");
 			assert_eq!(output.len(), 0); // shouldn't have written any output.
-			assert_eq!(f.cost(&input[..]), expected_cost.into());
+			assert_eq!(f.cost(&input[..], 0), expected_cost.into());
 		}
 	}
 
@@ -998,6 +1169,95 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn blake2_f() {
+		let f = Builtin {
+			pricer: Box::new(Blake2FPricer { gas_per_round: 1 }),
+			native: ethereum_builtin("blake2_f"),
+			activate_at: 0,
+		};
+
+		// test vector from EIP-152: 12 rounds compressing "abc", equal to BLAKE2b-512("abc")
+		let input = bytes("\
+				0000000c\
+				48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+				d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b\
+				6162630000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				03000000000000000000000000000000\
+				01"
+		);
+		let expected = bytes("\
+				ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+				7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+		);
+
+		assert_eq!(f.cost(&input[..], 0), U256::from(12));
+
+		let mut output = vec![0u8; 64];
+		f.execute(&input[..], &mut BytesRef::Fixed(&mut output[..])).expect("Builtin should not fail");
+		assert_eq!(output, expected);
+
+		// invalid length
+		error_test(f, &input[..212], Some("input length for Blake2 F precompile should be exactly 213 bytes"));
+	}
+
+	#[test]
+	fn blake2_f_invalid_flag() {
+		let f = Builtin {
+			pricer: Box::new(Blake2FPricer { gas_per_round: 1 }),
+			native: ethereum_builtin("blake2_f"),
+			activate_at: 0,
+		};
+
+		let input = bytes("\
+				0000000c\
+				48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+				d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b\
+				6162630000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				03000000000000000000000000000000\
+				02"
+		);
+
+		error_test(f, &input[..], Some("incorrect final block indicator flag"));
+	}
+
+	#[test]
+	fn bn128_eip1108_pricing() {
+		let add = Builtin::from(ethjson::spec::Builtin {
+			name: "alt_bn128_add".to_owned(),
+			pricing: ethjson::spec::Pricing::AltBn128ConstOperations(ethjson::spec::builtin::AltBn128ConstOperations {
+				price: 500,
+				eip1108_transition_price: 150,
+			}),
+			activate_at: None,
+			eip1108_transition: Some(ethjson::uint::Uint(10.into())),
+		});
+
+		assert_eq!(add.cost(&[0; 128], 9), U256::from(500));
+		assert_eq!(add.cost(&[0; 128], 10), U256::from(150));
+
+		let pairing = Builtin::from(ethjson::spec::Builtin {
+			name: "alt_bn128_pairing".to_owned(),
+			pricing: ethjson::spec::Pricing::AltBn128Pairing(ethjson::spec::builtin::AltBn128Pairing {
+				base: 100_000,
+				pair: 80_000,
+				eip1108_transition_base: Some(45_000),
+				eip1108_transition_pair: Some(34_000),
+			}),
+			activate_at: None,
+			eip1108_transition: Some(ethjson::uint::Uint(10.into())),
+		});
+
+		assert_eq!(pairing.cost(&[0; 384], 9), U256::from(260_000));
+		assert_eq!(pairing.cost(&[0; 384], 10), U256::from(113_000));
+	}
+
 	#[test]
 	#[should_panic]
 	fn from_unknown_linear() {
@@ -1027,10 +1287,10 @@ mod tests {
 			activate_at: 1,
 		};
 
-		assert_eq!(b.cost(&[0; 0]), U256::from(10));
-		assert_eq!(b.cost(&[0; 1]), U256::from(30));
-		assert_eq!(b.cost(&[0; 32]), U256::from(30));
-		assert_eq!(b.cost(&[0; 33]), U256::from(50));
+		assert_eq!(b.cost(&[0; 0], 0), U256::from(10));
+		assert_eq!(b.cost(&[0; 1], 0), U256::from(30));
+		assert_eq!(b.cost(&[0; 32], 0), U256::from(30));
+		assert_eq!(b.cost(&[0; 33], 0), U256::from(50));
 
 		let i = [0u8, 1, 2, 3];
 		let mut o = [255u8; 4];
@@ -1047,12 +1307,13 @@ mod tests {
 				word: 20,
 			}),
 			activate_at: None,
+			eip1108_transition: None,
 		});
 
-		assert_eq!(b.cost(&[0; 0]), U256::from(10));
-		assert_eq!(b.cost(&[0; 1]), U256::from(30));
-		assert_eq!(b.cost(&[0; 32]), U256::from(30));
-		assert_eq!(b.cost(&[0; 33]), U256::from(50));
+		assert_eq!(b.cost(&[0; 0], 0), U256::from(10));
+		assert_eq!(b.cost(&[0; 1], 0), U256::from(30));
+		assert_eq!(b.cost(&[0; 32], 0), U256::from(30));
+		assert_eq!(b.cost(&[0; 33], 0), U256::from(50));
 
 		let i = [0u8, 1, 2, 3];
 		let mut o = [255u8; 4];
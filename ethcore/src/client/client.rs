@@ -27,6 +27,9 @@ use bytes::Bytes;
 use itertools::Itertools;
 use journaldb;
 use trie::{TrieSpec, TrieFactory, Trie};
+use ethtrie::TrieDB;
+use hashdb::HashDB;
+use keccak_hasher::KeccakHasher;
 use kvdb::{DBValue, KeyValueDB, DBTransaction};
 
 // other
@@ -62,18 +65,22 @@ use header::{BlockNumber, Header, ExtendedHeader};
 use io::IoChannel;
 use log_entry::LocalizedLogEntry;
 use miner::{Miner, MinerService};
-use ethcore_miner::pool::VerifiedTransaction;
+use ethcore_miner::pool::{VerifiedTransaction, local_transactions};
 use parking_lot::{Mutex, RwLock};
 use rand::OsRng;
 use receipt::{Receipt, LocalizedReceipt};
 use snapshot::{self, io as snapshot_io};
+use snapshot::range::TrieRange;
 use spec::Spec;
 use state_db::StateDB;
 use state::{self, State};
+use stats::{PrometheusMetrics, PrometheusRegistry};
 use trace;
-use trace::{TraceDB, ImportRequest as TraceImportRequest, LocalizedTrace, Database as TraceDatabase};
+use trace::{TraceDB, ImportRequest as TraceImportRequest, LocalizedTrace, Database as TraceDatabase, DebugTracer, DebugTrace};
 use transaction::{self, LocalizedTransaction, UnverifiedTransaction, SignedTransaction, Transaction, Action};
+use types::basic_account::BasicAccount;
 use types::filter::Filter;
+use types::state_range::{StorageSlot, StorageRange, RangeAccount, AccountRange};
 use types::ancestry_action::AncestryAction;
 use verification;
 use verification::{PreverifiedBlock, Verifier, BlockQueue};
@@ -93,6 +100,8 @@ const MAX_ANCIENT_BLOCKS_QUEUE_SIZE: usize = 4096;
 const MAX_ANCIENT_BLOCKS_TO_IMPORT: usize = 4;
 const MAX_QUEUE_SIZE_TO_SLEEP_ON: usize = 2;
 const MIN_HISTORY_SIZE: u64 = 8;
+// Max number of accounts or storage slots returned by a single range query.
+const MAX_RANGE_ITEMS: usize = 256;
 
 /// Report on the status of a client.
 #[derive(Default, Clone, Debug, Eq, PartialEq)]
@@ -105,6 +114,8 @@ pub struct ClientReport {
 	pub gas_processed: U256,
 	/// Memory used by state DB
 	pub state_db_mem: usize,
+	/// Time spent importing verified blocks.
+	pub import_time: Duration,
 }
 
 impl ClientReport {
@@ -127,6 +138,7 @@ impl<'a> ::std::ops::Sub<&'a ClientReport> for ClientReport {
 		self.transactions_applied -= other.transactions_applied;
 		self.gas_processed = self.gas_processed - other.gas_processed;
 		self.state_db_mem = higher_mem - lower_mem;
+		self.import_time = self.import_time - other.import_time;
 
 		self
 	}
@@ -255,8 +267,8 @@ impl Importer {
 			block_queue,
 			miner,
 			ancient_verifier: AncientVerifier::new(engine.clone()),
+			bad_blocks: bad_blocks::BadBlocks::new(engine.params().eip1559_transition),
 			engine,
-			bad_blocks: Default::default(),
 		})
 	}
 
@@ -324,7 +336,9 @@ impl Importer {
 				self.block_queue.mark_as_bad(&invalid_blocks);
 			}
 			let is_empty = self.block_queue.mark_as_good(&imported_blocks);
-			(imported_blocks, import_results, invalid_blocks, imported, proposed_blocks, start.elapsed(), is_empty)
+			let duration = start.elapsed();
+			client.report.write().import_time += duration;
+			(imported_blocks, import_results, invalid_blocks, imported, proposed_blocks, duration, is_empty)
 		};
 
 		{
@@ -498,7 +512,7 @@ impl Importer {
 			let hash = best_hash;
 			let header = chain.block_header_data(&hash)
 				.expect("Best block is in the database; qed")
-				.decode()
+				.decode(self.engine.params().eip1559_transition)
 				.expect("Stored block header is valid RLP; qed");
 			let details = chain.block_details(&hash)
 				.expect("Best block is in the database; qed");
@@ -607,6 +621,7 @@ impl Importer {
 							last_hashes: client.build_last_hashes(header.parent_hash()),
 							gas_used: U256::default(),
 							gas_limit: u64::max_value().into(),
+							base_fee: header.base_fee(),
 						};
 
 						let call = move |addr, data| {
@@ -696,7 +711,7 @@ impl Client {
 	/// Create a new client with given parameters.
 	/// The database is assumed to have been initialized with the correct columns.
 	pub fn new(
-		config: ClientConfig,
+		mut config: ClientConfig,
 		spec: &Spec,
 		db: Arc<BlockChainDB>,
 		miner: Arc<Miner>,
@@ -725,6 +740,7 @@ impl Client {
 		}
 
 		let gb = spec.genesis_block();
+		config.blockchain.eip1559_transition = spec.params().eip1559_transition;
 		let chain = Arc::new(BlockChain::new(config.blockchain.clone(), &gb, db.clone()));
 		let tracedb = RwLock::new(TraceDB::new(config.tracing.clone(), db.clone(), chain.clone()));
 
@@ -886,6 +902,7 @@ impl Client {
 	/// The env info as of a given block.
 	/// returns `None` if the block unknown.
 	pub fn env_info(&self, id: BlockId) -> Option<EnvInfo> {
+		let eip1559_transition = self.engine.params().eip1559_transition;
 		self.block_header(id).map(|header| {
 			EnvInfo {
 				number: header.number(),
@@ -895,6 +912,7 @@ impl Client {
 				last_hashes: self.build_last_hashes(&header.parent_hash()),
 				gas_used: U256::default(),
 				gas_limit: header.gas_limit(),
+				base_fee: header.decode(eip1559_transition).ok().and_then(|h| h.base_fee()),
 			}
 		})
 	}
@@ -1262,6 +1280,120 @@ impl Client {
 		}
 	}
 
+	fn do_debug_call(
+		machine: &::machine::EthereumMachine,
+		env_info: &EnvInfo,
+		state: &mut State<StateDB>,
+		t: &SignedTransaction,
+		tracer: DebugTracer,
+	) -> Result<(U256, DebugTrace), CallError> {
+		let schedule = machine.schedule(env_info.number);
+
+		match tracer {
+			DebugTracer::StructLogs(config) => {
+				let options = TransactOptions::new(trace::NoopTracer, trace::StructLogTracer::new(config))
+					.dont_check_nonce()
+					.save_output_from_contract();
+				let ret = Executive::new(state, env_info, machine, &schedule).transact_virtual(t, options)?;
+				Ok((ret.gas_used, DebugTrace::StructLogs {
+					gas_used: ret.gas_used,
+					failed: ret.exception.is_some(),
+					output: ret.output,
+					logs: ret.vm_trace.unwrap_or_default(),
+				}))
+			},
+			DebugTracer::Call => {
+				let options = TransactOptions::with_tracing()
+					.dont_check_nonce()
+					.save_output_from_contract();
+				let ret = Executive::new(state, env_info, machine, &schedule).transact_virtual(t, options)?;
+				Ok((ret.gas_used, DebugTrace::Call(ret.trace)))
+			},
+			DebugTracer::Prestate => {
+				let mut original = state.clone();
+				let options = TransactOptions::with_no_tracing().dont_check_nonce();
+				let ret = Executive::new(state, env_info, machine, &schedule).transact_virtual(t, options)?;
+				let prestate = original.to_pod_diff(state).map_err(ExecutionError::from)?;
+				Ok((ret.gas_used, DebugTrace::Prestate(prestate)))
+			},
+		}
+	}
+
+	/// Get the state of the block `id` after its first `tx_index` transactions are replayed
+	/// on top of the parent state, or the final state of the block if `tx_index` is `None`.
+	/// The replayed changes are committed to an in-memory overlay, so the tries can be read directly.
+	fn state_at_transaction(&self, id: BlockId, tx_index: Option<usize>) -> Result<State<StateDB>, CallError> {
+		let tx_index = match tx_index {
+			Some(tx_index) => tx_index,
+			None => return self.state_at(id).ok_or(CallError::StatePruned),
+		};
+
+		let mut env_info = self.env_info(id).ok_or(CallError::StatePruned)?;
+		let body = self.block_body(id).ok_or(CallError::StatePruned)?;
+		let mut state = self.state_at_beginning(id).ok_or(CallError::StatePruned)?;
+		let txs = body.transactions();
+		if tx_index > txs.len() {
+			return Err(CallError::TransactionNotFound);
+		}
+		let machine = self.engine.machine();
+
+		const PROOF: &'static str = "Transactions fetched from blockchain; blockchain transactions are valid; qed";
+
+		for t in txs.into_iter().take(tx_index) {
+			let t = SignedTransaction::new(t).expect(PROOF);
+			let x = Self::do_virtual_call(machine, &env_info, &mut state, &t, Default::default())?;
+			env_info.gas_used = env_info.gas_used + x.gas_used;
+		}
+
+		state.commit().map_err(|_| CallError::StateCorrupt)?;
+		Ok(state)
+	}
+
+	/// Read up to `count` items of a secure trie in hashed key order, starting at `start`.
+	/// Key preimages are available only if they were recorded by the fat DB.
+	/// Returns the items and the hashed key of the item following them.
+	fn trie_range(
+		db: &HashDB<KeccakHasher, DBValue>,
+		root: &H256,
+		start: &H256,
+		count: usize,
+	) -> Result<(Vec<(H256, Option<DBValue>, DBValue)>, Option<H256>), CallError> {
+		let trie = TrieDB::new(db, root).map_err(|_| CallError::StateCorrupt)?;
+		let mut iter = trie.iter().map_err(|_| CallError::StateCorrupt)?;
+		iter.seek(start).map_err(|_| CallError::StateCorrupt)?;
+
+		let mut items = Vec::new();
+		for item in iter {
+			let (key, value) = item.map_err(|_| CallError::StateCorrupt)?;
+			let hashed_key = H256::from_slice(&key);
+			if items.len() == count {
+				return Ok((items, Some(hashed_key)));
+			}
+			// fat DB stores the preimage under the hash of the hashed key
+			let preimage = db.get(&keccak(&hashed_key));
+			items.push((hashed_key, preimage, value));
+		}
+
+		Ok((items, None))
+	}
+
+	fn storage_slots(
+		db: &HashDB<KeccakHasher, DBValue>,
+		root: &H256,
+		start: &H256,
+		count: usize,
+	) -> Result<(Vec<StorageSlot>, Option<H256>), CallError> {
+		let (items, next_key) = Self::trie_range(db, root, start, count)?;
+		let slots = items.into_iter()
+			.map(|(hashed_key, key, value)| Ok(StorageSlot {
+				hashed_key,
+				key: key.map(|key| H256::from_slice(&key)),
+				value: ::rlp::decode(&value).map_err(|_| CallError::StateCorrupt)?,
+			}))
+			.collect::<Result<_, CallError>>()?;
+		Ok((slots, next_key))
+	}
+
 	fn block_number_ref(&self, id: &BlockId) -> Option<BlockNumber> {
 		match *id {
 			BlockId::Number(number) => Some(number),
@@ -1270,22 +1402,6 @@ impl Client {
 			BlockId::Latest => Some(self.chain.read().best_block_number()),
 		}
 	}
-
-	/// Retrieve a decoded header given `BlockId`
-	///
-	/// This method optimizes access patterns for latest block header
-	/// to avoid excessive RLP encoding, decoding and hashing.
-	fn block_header_decoded(&self, id: BlockId) -> Option<Header> {
-		match id {
-			BlockId::Latest
-				=> Some(self.chain.read().best_block_header()),
-			BlockId::Hash(ref hash) if hash == &self.chain.read().best_block_hash()
-				=> Some(self.chain.read().best_block_header()),
-			BlockId::Number(number) if number == self.chain.read().best_block_number()
-				=> Some(self.chain.read().best_block_header()),
-			_   => self.block_header(id).and_then(|h| h.decode().ok())
-		}
-	}
 }
 
 impl snapshot::DatabaseRestore for Client {
@@ -1341,6 +1457,20 @@ impl BlockInfo for Client {
 		Self::block_hash(&chain, id).and_then(|hash| chain.block_header_data(&hash))
 	}
 
+	/// This method optimizes access patterns for latest block header
+	/// to avoid excessive RLP encoding, decoding and hashing.
+	fn block_header_decoded(&self, id: BlockId) -> Option<Header> {
+		match id {
+			BlockId::Latest
+				=> Some(self.chain.read().best_block_header()),
+			BlockId::Hash(ref hash) if hash == &self.chain.read().best_block_hash()
+				=> Some(self.chain.read().best_block_header()),
+			BlockId::Number(number) if number == self.chain.read().best_block_number()
+				=> Some(self.chain.read().best_block_header()),
+			_   => self.block_header(id).and_then(|h| h.decode(self.engine.params().eip1559_transition).ok())
+		}
+	}
+
 	fn best_block_header(&self) -> Header {
 		self.chain.read().best_block_header()
 	}
@@ -1441,12 +1571,29 @@ impl Call for Client {
 			last_hashes: self.build_last_hashes(header.parent_hash()),
 			gas_used: U256::default(),
 			gas_limit: U256::max_value(),
+			base_fee: header.base_fee(),
 		};
 		let machine = self.engine.machine();
 
 		Self::do_virtual_call(&machine, &env_info, state, transaction, analytics)
 	}
 
+	fn debug_call(&self, transaction: &SignedTransaction, tracer: DebugTracer, state: &mut Self::State, header: &Header) -> Result<DebugTrace, CallError> {
+		let env_info = EnvInfo {
+			number: header.number(),
+			author: header.author().clone(),
+			timestamp: header.timestamp(),
+			difficulty: header.difficulty().clone(),
+			last_hashes: self.build_last_hashes(header.parent_hash()),
+			gas_used: U256::default(),
+			gas_limit: U256::max_value(),
+			base_fee: header.base_fee(),
+		};
+		let machine = self.engine.machine();
+
+		Self::do_debug_call(&machine, &env_info, state, transaction, tracer).map(|(_, trace)| trace)
+	}
+
 	fn call_many(&self, transactions: &[(SignedTransaction, CallAnalytics)], state: &mut Self::State, header: &Header) -> Result<Vec<Executed>, CallError> {
 		let mut env_info = EnvInfo {
 			number: header.number(),
@@ -1456,6 +1603,7 @@ impl Call for Client {
 			last_hashes: self.build_last_hashes(header.parent_hash()),
 			gas_used: U256::default(),
 			gas_limit: U256::max_value(),
+			base_fee: header.base_fee(),
 		};
 
 		let mut results = Vec::with_capacity(transactions.len());
@@ -1483,6 +1631,7 @@ impl Call for Client {
 				last_hashes: self.build_last_hashes(header.parent_hash()),
 				gas_used: U256::default(),
 				gas_limit: max,
+				base_fee: header.base_fee(),
 			};
 
 			(init, max, env_info)
@@ -1562,6 +1711,81 @@ impl BadBlocks for Client {
 	}
 }
 
+impl PrometheusMetrics for Client {
+	fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+		let chain = self.chain_info();
+		r.register_gauge("chain_best_block", "Best block number", chain.best_block_number as f64);
+
+		let report = self.report();
+		r.register_counter("import_blocks", "Blocks imported since start", report.blocks_imported as f64);
+		r.register_counter("import_transactions", "Transactions applied since start", report.transactions_applied as f64);
+		r.register_counter("import_gas", "Gas processed since start", report.gas_processed.low_u64() as f64);
+		r.register_counter("import_seconds", "Time spent importing verified blocks", duration_secs(report.import_time));
+
+		let queue = self.queue_info();
+		for &(stage, size) in &[
+			("unverified", queue.unverified_queue_size),
+			("verifying", queue.verifying_queue_size),
+			("verified", queue.verified_queue_size),
+		] {
+			r.register_gauge_with_labels("queue_blocks", "Blocks in the verification queue", &[("stage", stage)], size as f64);
+		}
+
+		let chain_cache = self.blockchain_cache_info();
+		for &(cache, size) in &[
+			("state_db", report.state_db_mem),
+			("queue", queue.mem_used),
+			("blocks", chain_cache.blocks),
+			("block_details", chain_cache.block_details),
+			("transaction_addresses", chain_cache.transaction_addresses),
+			("block_receipts", chain_cache.block_receipts),
+		] {
+			r.register_gauge_with_labels("cache_bytes", "Heap memory used by caches", &[("cache", cache)], size as f64);
+		}
+
+		let txqueue = self.miner.queue_status();
+		r.register_gauge("txqueue_transactions", "Transactions in the pool", txqueue.status.transaction_count as f64);
+		r.register_gauge("txqueue_senders", "Distinct senders of transactions in the pool", txqueue.status.senders as f64);
+		r.register_gauge("txqueue_bytes", "Heap memory used by the pool", txqueue.status.mem_usage as f64);
+
+		let readiness = self.miner.queue_full_status(self);
+		for &(status, count) in &[
+			("ready", readiness.pending),
+			("future", readiness.future),
+			("stalled", readiness.stalled),
+		] {
+			r.register_gauge_with_labels("txqueue_readiness_transactions", "Transactions in the pool by readiness", &[("status", status)], count as f64);
+		}
+
+		let mut local = BTreeMap::new();
+		for status in self.miner.local_transactions().values() {
+			*local.entry(local_transaction_status(status)).or_insert(0) += 1;
+		}
+		for (status, count) in local {
+			r.register_gauge_with_labels("txqueue_local_transactions", "Recently seen local transactions by status", &[("status", status)], count as f64);
+		}
+	}
+}
+
+fn duration_secs(duration: Duration) -> f64 {
+	duration.as_secs() as f64 + duration.subsec_nanos() as f64 / 1_000_000_000.0
+}
+
+fn local_transaction_status(status: &local_transactions::Status) -> &'static str {
+	use self::local_transactions::Status::*;
+
+	match *status {
+		Pending(_) => "pending",
+		Mined(_) => "mined",
+		Culled(_) => "culled",
+		Dropped(_) => "dropped",
+		Replaced { .. } => "replaced",
+		Rejected(..) => "rejected",
+		Invalid(_) => "invalid",
+		Canceled(_) => "canceled",
+	}
+}
+
 impl BlockChainClient for Client {
 	fn replay(&self, id: TransactionId, analytics: CallAnalytics) -> Result<Executed, CallError> {
 		let address = self.transaction_address(id).ok_or(CallError::TransactionNotFound)?;
@@ -1592,6 +1816,50 @@ impl BlockChainClient for Client {
 			})))
 	}
 
+	fn debug_replay(&self, id: TransactionId, tracer: DebugTracer) -> Result<DebugTrace, CallError> {
+		let address = self.transaction_address(id).ok_or(CallError::TransactionNotFound)?;
+		let block = BlockId::Hash(address.block_hash);
+		let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
+		let body = self.block_body(block).ok_or(CallError::StatePruned)?;
+		let mut state = self.state_at_beginning(block).ok_or(CallError::StatePruned)?;
+		let mut txs = body.transactions();
+		let machine = self.engine.machine();
+
+		const PROOF: &'static str = "Transactions fetched from blockchain; blockchain transactions are valid; qed";
+		const INDEX_PROOF: &'static str = "The transaction address contains a valid index within block; qed";
+
+		let t = SignedTransaction::new(txs.get(address.index).cloned().expect(INDEX_PROOF)).expect(PROOF);
+		txs.truncate(address.index);
+		for preceding in txs {
+			let preceding = SignedTransaction::new(preceding).expect(PROOF);
+			let x = Self::do_virtual_call(machine, &env_info, &mut state, &preceding, Default::default())?;
+			env_info.gas_used = env_info.gas_used + x.gas_used;
+		}
+
+		Self::do_debug_call(machine, &env_info, &mut state, &t, tracer).map(|(_, trace)| trace)
+	}
+
+	fn debug_replay_block_transactions(&self, block: BlockId, tracer: DebugTracer) -> Result<Box<Iterator<Item = (H256, DebugTrace)>>, CallError> {
+		let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
+		let body = self.block_body(block).ok_or(CallError::StatePruned)?;
+		let mut state = self.state_at_beginning(block).ok_or(CallError::StatePruned)?;
+		let txs = body.transactions();
+		let engine = self.engine.clone();
+
+		const PROOF: &'static str = "Transactions fetched from blockchain; blockchain transactions are valid; qed";
+		const EXECUTE_PROOF: &'static str = "Transaction replayed; qed";
+
+		Ok(Box::new(txs.into_iter()
+			.map(move |t| {
+				let transaction_hash = t.hash();
+				let t = SignedTransaction::new(t).expect(PROOF);
+				let machine = engine.machine();
+				let (gas_used, trace) = Self::do_debug_call(machine, &env_info, &mut state, &t, tracer).expect(EXECUTE_PROOF);
+				env_info.gas_used = env_info.gas_used + gas_used;
+				(transaction_hash, trace)
+			})))
+	}
+
 	fn mode(&self) -> Mode {
 		let r = self.mode.lock().clone().into();
 		trace!(target: "mode", "Asked for mode = {:?}. returning {:?}", &*self.mode.lock(), r);
@@ -1780,6 +2048,55 @@ impl BlockChainClient for Client {
 		Some(keys)
 	}
 
+	fn storage_range_at(&self, id: BlockId, tx_index: usize, account: &Address, start: &H256, count: u64) -> Result<StorageRange, CallError> {
+		let state = self.state_at_transaction(id, Some(tx_index))?;
+		let root = match state.storage_root(account).map_err(|_| CallError::StateCorrupt)? {
+			Some(root) => root,
+			None => return Ok(StorageRange::default()),
+		};
+
+		let (_, db) = state.drop();
+		let account_db = self.factories.accountdb.readonly(db.as_hashdb(), keccak(account));
+		let count = cmp::min(count as usize, MAX_RANGE_ITEMS);
+		let (storage, next_key) = Self::storage_slots(account_db.as_hashdb(), &root, start, count)?;
+
+		Ok(StorageRange { storage, next_key })
+	}
+
+	fn account_range_at(&self, id: BlockId, tx_index: Option<usize>, start: &H256, count: u64, with_code: bool, with_storage: bool) -> Result<AccountRange, CallError> {
+		let state = self.state_at_transaction(id, tx_index)?;
+		let (root, db) = state.drop();
+		let count = cmp::min(count as usize, MAX_RANGE_ITEMS);
+		let (items, next) = Self::trie_range(db.as_hashdb(), &root, start, count)?;
+
+		let accounts = items.into_iter().map(|(hashed_address, address, value)| {
+			let account: BasicAccount = ::rlp::decode(&value).map_err(|_| CallError::StateCorrupt)?;
+			let account_db = self.factories.accountdb.readonly(db.as_hashdb(), hashed_address);
+			let code = match with_code {
+				true => Some(account_db.get(&account.code_hash).map_or_else(Vec::new, |code| code.into_vec())),
+				false => None,
+			};
+			let (storage, storage_next) = match with_storage {
+				true => {
+					let (slots, next_key) = Self::storage_slots(account_db.as_hashdb(), &account.storage_root, &H256::zero(), MAX_RANGE_ITEMS)?;
+					(Some(slots), next_key)
+				},
+				false => (None, None),
+			};
+
+			Ok(RangeAccount {
+				hashed_address,
+				address: address.map(|address| Address::from_slice(&address)),
+				account,
+				code,
+				storage,
+				storage_next,
+			})
+		}).collect::<Result<_, CallError>>()?;
+
+		Ok(AccountRange { root, accounts, next })
+	}
+
 	fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction> {
 		self.transaction_address(id).and_then(|address| self.chain.read().transaction(&address))
 	}
@@ -1852,6 +2169,19 @@ impl BlockChainClient for Client {
 		self.state_db.read().journal_db().state(hash)
 	}
 
+	fn state_range(&self, block: &H256, account_key: Option<H256>, origin: &H256, max_bytes: usize) -> Option<TrieRange> {
+		let state = self.state_at(BlockId::Hash(*block))?;
+		let range = match account_key {
+			Some(account_key) => state.storage_range(account_key, origin, max_bytes),
+			None => state.account_range(origin, max_bytes),
+		};
+		range.ok()
+	}
+
+	fn state_node(&self, account_key: Option<H256>, hash: &H256) -> Option<Bytes> {
+		self.state_at(BlockId::Latest)?.state_node(account_key, hash)
+	}
+
 	fn encoded_block_receipts(&self, hash: &H256) -> Option<Bytes> {
 		self.chain.read().block_receipts(hash).map(|receipts| ::rlp::encode(&receipts))
 	}
@@ -1979,11 +2309,7 @@ impl BlockChainClient for Client {
 		};
 
 		let traces = self.tracedb.read()
-			.filter(&db_filter)
-			.into_iter()
-			.skip(filter.after.unwrap_or(0))
-			.take(filter.count.unwrap_or(usize::max_value()))
-			.collect();
+			.filter_page(&db_filter, filter.after.unwrap_or(0), filter.count.unwrap_or(usize::max_value()));
 		Some(traces)
 	}
 
@@ -2047,6 +2373,10 @@ impl BlockChainClient for Client {
 		self.importer.miner.ready_transactions(self, max_len, ::miner::PendingOrdering::Priority)
 	}
 
+	fn pooled_transaction(&self, hash: &H256) -> Option<Arc<VerifiedTransaction>> {
+		self.importer.miner.transaction(hash)
+	}
+
 	fn signing_chain_id(&self) -> Option<u64> {
 		self.engine.signing_chain_id(&self.latest_env_info())
 	}
@@ -2059,7 +2389,7 @@ impl BlockChainClient for Client {
 	fn uncle_extra_info(&self, id: UncleId) -> Option<BTreeMap<String, String>> {
 		self.uncle(id)
 			.and_then(|h| {
-				h.decode().map(|dh| {
+				h.decode(self.engine.params().eip1559_transition).map(|dh| {
 					self.engine.extra_info(&dh)
 				}).ok()
 			})
@@ -2205,7 +2535,7 @@ impl ReopenBlock for Client {
 			for h in uncles {
 				if !block.uncles().iter().any(|header| header.hash() == h) {
 					let uncle = chain.block_header_data(&h).expect("find_uncle_hashes only returns hashes for existing headers; qed");
-					let uncle = uncle.decode().expect("decoding failure");
+					let uncle = uncle.decode(engine.params().eip1559_transition).expect("decoding failure");
 					block.push_uncle(uncle).expect("pushing up to maximum_uncle_count;
 												push_uncle is not ok only if more than maximum_uncle_count is pushed;
 												so all push_uncle are Ok;
@@ -2248,7 +2578,7 @@ impl PrepareOpenBlock for Client {
 			.into_iter()
 			.take(engine.maximum_uncle_count(open_block.header().number()))
 			.foreach(|h| {
-				open_block.push_uncle(h.decode().expect("decoding failure")).expect("pushing maximum_uncle_count;
+				open_block.push_uncle(h.decode(engine.params().eip1559_transition).expect("decoding failure")).expect("pushing maximum_uncle_count;
 												open_block was just created;
 												push_uncle is not ok only if more than maximum_uncle_count is pushed;
 												so all push_uncle are Ok;
@@ -2372,6 +2702,17 @@ impl ProvingBlockChainClient for Client {
 			.and_then(move |state| state.prove_account(key1).ok())
 	}
 
+	fn prove_account_storage(&self, address: &Address, storage_keys: &[H256], id: BlockId) -> Option<(Vec<Bytes>, ::types::basic_account::BasicAccount, Vec<(Vec<Bytes>, H256)>)> {
+		let state = self.state_at(id)?;
+		let account_key = keccak(address);
+		let (proof, account) = state.prove_account(account_key).ok()?;
+		let storage = storage_keys.iter()
+			.map(|key| state.prove_storage(account_key, keccak(key)).ok())
+			.collect::<Option<Vec<_>>>()?;
+
+		Some((proof, account, storage))
+	}
+
 	fn prove_transaction(&self, transaction: SignedTransaction, id: BlockId) -> Option<(Bytes, Vec<DBValue>)> {
 		let (header, mut env_info) = match (self.block_header(id), self.env_info(id)) {
 			(Some(s), Some(e)) => (s, e),
@@ -2564,6 +2905,7 @@ mod tests {
 			outcome: TransactionOutcome::StateRoot(state_root),
 			gas_used: gas_used,
 			log_bloom: Default::default(),
+			transaction_type: Default::default(),
 			logs: logs.clone(),
 		};
 
@@ -87,6 +87,7 @@ impl<'a> EvmTestClient<'a> {
 			ForkSpec::EIP158 => Some(ethereum::new_eip161_test()),
 			ForkSpec::Byzantium => Some(ethereum::new_byzantium_test()),
 			ForkSpec::Constantinople => Some(ethereum::new_constantinople_test()),
+			ForkSpec::Istanbul => Some(ethereum::new_istanbul_test()),
 			ForkSpec::EIP158ToByzantiumAt5 => Some(ethereum::new_transition_test()),
 			ForkSpec::FrontierToHomesteadAt5 | ForkSpec::HomesteadToDaoAt5 | ForkSpec::HomesteadToEIP150At5 => None,
 		}
@@ -181,6 +182,7 @@ impl<'a> EvmTestClient<'a> {
 			last_hashes: Arc::new([H256::default(); 256].to_vec()),
 			gas_used: 0.into(),
 			gas_limit: *genesis.gas_limit(),
+			base_fee: genesis.base_fee(),
 		};
 		self.call_envinfo(params, tracer, vm_tracer, info)
 	}
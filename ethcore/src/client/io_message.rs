@@ -29,6 +29,8 @@ pub enum ClientIoMessage {
 	BlockVerified,
 	/// Begin snapshot restoration
 	BeginRestoration(ManifestData),
+	/// Begin restoring the state from trie nodes
+	BeginStateRestoration(ManifestData),
 	/// Feed a state chunk to the snapshot service
 	FeedStateChunk(H256, Bytes),
 	/// Feed a block chunk to the snapshot service
@@ -43,6 +43,7 @@ use client::{
 	BadBlocks,
 };
 use db::{NUM_COLUMNS, COL_STATE};
+use snapshot::range::TrieRange;
 use header::{Header as BlockHeader, BlockNumber};
 use filter::Filter;
 use log_entry::LocalizedLogEntry;
@@ -53,12 +54,13 @@ use miner::{self, Miner, MinerService};
 use spec::Spec;
 use types::basic_account::BasicAccount;
 use types::pruning_info::PruningInfo;
+use types::state_range::{StorageSlot, StorageRange, RangeAccount, AccountRange};
 use verification::queue::QueueInfo;
 use verification::queue::kind::blocks::Unverified;
 use block::{OpenBlock, SealedBlock, ClosedBlock};
 use executive::Executed;
 use error::CallError;
-use trace::LocalizedTrace;
+use trace::{LocalizedTrace, DebugTracer, DebugTrace};
 use state_db::StateDB;
 use header::Header;
 use encoded;
@@ -91,6 +93,8 @@ pub struct TestBlockChainClient {
 	pub code: RwLock<HashMap<Address, Bytes>>,
 	/// Execution result.
 	pub execution_result: RwLock<Option<Result<Executed, CallError>>>,
+	/// Debug trace result.
+	pub debug_trace_result: RwLock<Option<Result<DebugTrace, CallError>>>,
 	/// Transaction receipts.
 	pub receipts: RwLock<HashMap<TransactionId, LocalizedReceipt>>,
 	/// Logs
@@ -170,6 +174,7 @@ impl TestBlockChainClient {
 			storage: RwLock::new(HashMap::new()),
 			code: RwLock::new(HashMap::new()),
 			execution_result: RwLock::new(None),
+			debug_trace_result: RwLock::new(None),
 			receipts: RwLock::new(HashMap::new()),
 			logs: RwLock::new(Vec::new()),
 			queue_size: AtomicUsize::new(0),
@@ -202,6 +207,11 @@ impl TestBlockChainClient {
 		*self.execution_result.write() = Some(result);
 	}
 
+	/// Set the debug trace result.
+	pub fn set_debug_trace_result(&self, result: Result<DebugTrace, CallError>) {
+		*self.debug_trace_result.write() = Some(result);
+	}
+
 	/// Set the balance of account `address` to `balance`.
 	pub fn set_balance(&self, address: Address, balance: U256) {
 		self.balances.write().insert(address, balance);
@@ -295,7 +305,7 @@ impl TestBlockChainClient {
 		rlp.append(&header);
 		rlp.append_raw(&txs, 1);
 		rlp.append_raw(uncles.as_raw(), 1);
-		let unverified = Unverified::from_rlp(rlp.out()).unwrap();
+		let unverified = Unverified::from_rlp(rlp.out(), self.spec.params().eip1559_transition).unwrap();
 		self.import_block(unverified).unwrap();
 	}
 
@@ -309,7 +319,7 @@ impl TestBlockChainClient {
 	/// Make a bad block by setting invalid parent hash.
 	pub fn corrupt_block_parent(&self, n: BlockNumber) {
 		let hash = self.block_hash(BlockId::Number(n)).unwrap();
-		let mut header: BlockHeader = self.block_header(BlockId::Number(n)).unwrap().decode().expect("decoding failed");
+		let mut header: BlockHeader = self.block_header_decoded(BlockId::Number(n)).expect("decoding failed");
 		header.set_parent_hash(H256::from(42));
 		let mut rlp = RlpStream::new_list(3);
 		rlp.append(&header);
@@ -484,10 +494,14 @@ impl BlockInfo for TestBlockChainClient {
 			.map(encoded::Header::new)
 	}
 
+	fn block_header_decoded(&self, id: BlockId) -> Option<Header> {
+		self.block_header(id).and_then(|h| h.decode(self.spec.params().eip1559_transition).ok())
+	}
+
 	fn best_block_header(&self) -> Header {
 		self.block_header(BlockId::Hash(self.chain_info().best_block_hash))
 			.expect("Best block always has header.")
-			.decode()
+			.decode(self.spec.params().eip1559_transition)
 			.expect("decoding failed")
 	}
 
@@ -584,6 +598,10 @@ impl Call for TestBlockChainClient {
 		Ok(res)
 	}
 
+	fn debug_call(&self, _t: &SignedTransaction, _tracer: DebugTracer, _state: &mut Self::State, _header: &Header) -> Result<DebugTrace, CallError> {
+		self.debug_trace_result.read().clone().unwrap()
+	}
+
 	fn estimate_gas(&self, _t: &SignedTransaction, _state: &Self::State, _header: &Header) -> Result<U256, CallError> {
 		Ok(21000.into())
 	}
@@ -637,6 +655,15 @@ impl BlockChainClient for TestBlockChainClient {
 		Ok(Box::new(self.traces.read().clone().unwrap().into_iter().map(|t| t.transaction_hash.unwrap_or(H256::new())).zip(self.execution_result.read().clone().unwrap().into_iter())))
 	}
 
+	fn debug_replay(&self, _id: TransactionId, _tracer: DebugTracer) -> Result<DebugTrace, CallError> {
+		self.debug_trace_result.read().clone().unwrap()
+	}
+
+	fn debug_replay_block_transactions(&self, _block: BlockId, _tracer: DebugTracer) -> Result<Box<Iterator<Item = (H256, DebugTrace)>>, CallError> {
+		let trace = self.debug_trace_result.read().clone().unwrap()?;
+		Ok(Box::new(self.traces.read().clone().unwrap().into_iter().map(move |t| (t.transaction_hash.unwrap_or(H256::new()), trace.clone()))))
+	}
+
 	fn block_total_difficulty(&self, _id: BlockId) -> Option<U256> {
 		Some(U256::zero())
 	}
@@ -670,6 +697,46 @@ impl BlockChainClient for TestBlockChainClient {
 	fn list_storage(&self, _id: BlockId, _account: &Address, _after: Option<&H256>, _count: u64) -> Option<Vec<H256>> {
 		None
 	}
+
+	fn storage_range_at(&self, _id: BlockId, _tx_index: usize, account: &Address, start: &H256, count: u64) -> Result<StorageRange, CallError> {
+		let mut slots: Vec<_> = self.storage.read().iter()
+			.filter(|&(&(ref address, _), _)| address == account)
+			.map(|(&(_, key), value)| StorageSlot { hashed_key: keccak(&key), key: Some(key), value: (*value).into() })
+			.filter(|slot| slot.hashed_key >= *start)
+			.collect();
+		slots.sort_by_key(|slot| slot.hashed_key);
+
+		let next_key = slots.get(count as usize).map(|slot| slot.hashed_key);
+		slots.truncate(count as usize);
+		Ok(StorageRange { storage: slots, next_key })
+	}
+
+	fn account_range_at(&self, _id: BlockId, _tx_index: Option<usize>, start: &H256, count: u64, with_code: bool, _with_storage: bool) -> Result<AccountRange, CallError> {
+		let mut accounts: Vec<_> = self.balances.read().iter()
+			.map(|(address, balance)| RangeAccount {
+				hashed_address: keccak(address),
+				address: Some(*address),
+				account: BasicAccount {
+					nonce: self.nonces.read().get(address).cloned().unwrap_or_default(),
+					balance: *balance,
+					storage_root: ::hash::KECCAK_NULL_RLP,
+					code_hash: self.code.read().get(address).map_or(::hash::KECCAK_EMPTY, keccak),
+				},
+				code: match with_code {
+					true => Some(self.code.read().get(address).cloned().unwrap_or_default()),
+					false => None,
+				},
+				storage: None,
+				storage_next: None,
+			})
+			.filter(|account| account.hashed_address >= *start)
+			.collect();
+		accounts.sort_by_key(|account| account.hashed_address);
+
+		let next = accounts.get(count as usize).map(|account| account.hashed_address);
+		accounts.truncate(count as usize);
+		Ok(AccountRange { root: ::hash::KECCAK_NULL_RLP, accounts, next })
+	}
 	fn transaction(&self, _id: TransactionId) -> Option<LocalizedTransaction> {
 		None	// Simple default.
 	}
@@ -789,6 +856,14 @@ impl BlockChainClient for TestBlockChainClient {
 		None
 	}
 
+	fn state_range(&self, _block: &H256, _account_key: Option<H256>, _origin: &H256, _max_bytes: usize) -> Option<TrieRange> {
+		None
+	}
+
+	fn state_node(&self, _account_key: Option<H256>, _hash: &H256) -> Option<Bytes> {
+		None
+	}
+
 	fn encoded_block_receipts(&self, hash: &H256) -> Option<Bytes> {
 		// starts with 'f' ?
 		if *hash > H256::from("f000000000000000000000000000000000000000000000000000000000000000") {
@@ -841,6 +916,10 @@ impl BlockChainClient for TestBlockChainClient {
 		self.miner.ready_transactions(self, 4096, miner::PendingOrdering::Priority)
 	}
 
+	fn pooled_transaction(&self, hash: &H256) -> Option<Arc<VerifiedTransaction>> {
+		self.miner.transaction(hash)
+	}
+
 	fn signing_chain_id(&self) -> Option<u64> { None }
 
 	fn mode(&self) -> Mode { Mode::Active }
@@ -904,6 +983,24 @@ impl ProvingBlockChainClient for TestBlockChainClient {
 		None
 	}
 
+	fn prove_account_storage(&self, address: &Address, storage_keys: &[H256], id: BlockId) -> Option<(Vec<Bytes>, BasicAccount, Vec<(Vec<Bytes>, H256)>)> {
+		if id != BlockId::Latest {
+			return None;
+		}
+
+		let account = BasicAccount {
+			nonce: self.nonces.read().get(address).cloned().unwrap_or(self.spec.params().account_start_nonce),
+			balance: self.balances.read().get(address).cloned().unwrap_or_else(U256::zero),
+			storage_root: ::hash::KECCAK_NULL_RLP,
+			code_hash: self.code.read().get(address).map_or(::hash::KECCAK_EMPTY, keccak),
+		};
+		let storage = storage_keys.iter()
+			.map(|key| (Vec::new(), self.storage.read().get(&(*address, *key)).cloned().unwrap_or_else(H256::new)))
+			.collect();
+
+		Some((Vec::new(), account, storage))
+	}
+
 	fn prove_transaction(&self, _: SignedTransaction, _: BlockId) -> Option<(Bytes, Vec<DBValue>)> {
 		None
 	}
@@ -31,13 +31,14 @@ use filter::Filter;
 use header::{BlockNumber};
 use log_entry::LocalizedLogEntry;
 use receipt::LocalizedReceipt;
-use trace::LocalizedTrace;
+use trace::{LocalizedTrace, DebugTracer, DebugTrace};
 use transaction::{self, LocalizedTransaction, SignedTransaction};
 use verification::queue::QueueInfo as BlockQueueInfo;
 use verification::queue::kind::blocks::Unverified;
 use state::StateInfo;
 use header::Header;
 use engines::EthEngine;
+use snapshot::range::TrieRange;
 
 use ethereum_types::{H256, U256, Address};
 use ethcore_miner::pool::VerifiedTransaction;
@@ -51,6 +52,7 @@ use types::call_analytics::CallAnalytics;
 use types::blockchain_info::BlockChainInfo;
 use types::block_status::BlockStatus;
 use types::pruning_info::PruningInfo;
+use types::state_range::{StorageRange, AccountRange};
 
 /// State information to be used during client query
 pub enum StateOrBlock {
@@ -123,6 +125,9 @@ pub trait BlockInfo {
 	/// Get raw block header data by block id.
 	fn block_header(&self, id: BlockId) -> Option<encoded::Header>;
 
+	/// Get decoded block header by block id.
+	fn block_header_decoded(&self, id: BlockId) -> Option<Header>;
+
 	/// Get the best block header.
 	fn best_block_header(&self) -> Header;
 
@@ -189,6 +194,9 @@ pub trait Call {
 	/// Returns a vector of successes or a failure if any of the transaction fails.
 	fn call_many(&self, txs: &[(SignedTransaction, CallAnalytics)], state: &mut Self::State, header: &Header) -> Result<Vec<Executed>, CallError>;
 
+	/// Makes a non-persistent transaction call with the given debug tracer.
+	fn debug_call(&self, tx: &SignedTransaction, tracer: DebugTracer, state: &mut Self::State, header: &Header) -> Result<DebugTrace, CallError>;
+
 	/// Estimates how much gas will be necessary for a call.
 	fn estimate_gas(&self, t: &SignedTransaction, state: &Self::State, header: &Header) -> Result<U256, CallError>;
 }
@@ -219,7 +227,7 @@ pub trait BadBlocks {
 
 /// Blockchain database client. Owns and manages a blockchain and a block queue.
 pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContract + RegistryInfo + ImportBlock
-+ IoClient + BadBlocks {
++ IoClient + BadBlocks + EngineInfo {
 	/// Look up the block number for the given block ID.
 	fn block_number(&self, id: BlockId) -> Option<BlockNumber>;
 
@@ -272,6 +280,16 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 	/// If `after` is set the list starts with the following item.
 	fn list_storage(&self, id: BlockId, account: &Address, after: Option<&H256>, count: u64) -> Option<Vec<H256>>;
 
+	/// Get up to `count` storage slots of `account`, ordered by hashed key and starting with `start`,
+	/// in the state after the first `tx_index` transactions of the block `id` are replayed.
+	/// Slot keys are known only if fat DB is in operation.
+	fn storage_range_at(&self, id: BlockId, tx_index: usize, account: &Address, start: &H256, count: u64) -> Result<StorageRange, CallError>;
+
+	/// Get up to `count` accounts, ordered by hashed address and starting with `start`, in the state
+	/// after the first `tx_index` transactions of the block `id` are replayed, or in the final state
+	/// of the block if `tx_index` is `None`. Addresses are known only if fat DB is in operation.
+	fn account_range_at(&self, id: BlockId, tx_index: Option<usize>, start: &H256, count: u64, with_code: bool, with_storage: bool) -> Result<AccountRange, CallError>;
+
 	/// Get transaction with given hash.
 	fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction>;
 
@@ -294,6 +312,14 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 	/// Get latest state node
 	fn state_data(&self, hash: &H256) -> Option<Bytes>;
 
+	/// Get a range of the account trie at the state of the given block, or of the storage trie
+	/// of the account with the given address hash, together with the proof of its ends.
+	fn state_range(&self, block: &H256, account_key: Option<H256>, origin: &H256, max_bytes: usize) -> Option<TrieRange>;
+
+	/// Get a latest state node of the account trie, or of the storage trie or the code of the
+	/// account with the given address hash.
+	fn state_node(&self, account_key: Option<H256>, hash: &H256) -> Option<Bytes>;
+
 	/// Get raw block receipts data by block header hash.
 	fn encoded_block_receipts(&self, hash: &H256) -> Option<Bytes>;
 
@@ -315,6 +341,12 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 	/// Replays all the transactions in a given block for inspection.
 	fn replay_block_transactions(&self, block: BlockId, analytics: CallAnalytics) -> Result<Box<Iterator<Item = (H256, Executed)>>, CallError>;
 
+	/// Replays a given transaction with the given debug tracer.
+	fn debug_replay(&self, t: TransactionId, tracer: DebugTracer) -> Result<DebugTrace, CallError>;
+
+	/// Replays all the transactions in a given block with the given debug tracer.
+	fn debug_replay_block_transactions(&self, block: BlockId, tracer: DebugTracer) -> Result<Box<Iterator<Item = (H256, DebugTrace)>>, CallError>;
+
 	/// Returns traces matching given filter.
 	fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>>;
 
@@ -333,6 +365,9 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 	/// List all ready transactions that should be propagated to other peers.
 	fn transactions_to_propagate(&self) -> Vec<Arc<VerifiedTransaction>>;
 
+	/// Get a transaction from the pool by its hash.
+	fn pooled_transaction(&self, hash: &H256) -> Option<Arc<VerifiedTransaction>>;
+
 	/// Sorted list of transaction gas prices from at least last sample_size blocks.
 	fn gas_price_corpus(&self, sample_size: usize) -> ::stats::Corpus<U256> {
 		let mut h = self.chain_info().best_block_hash;
@@ -354,6 +389,29 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 		corpus.into()
 	}
 
+	/// Sorted list of effective transaction priority fees from at least last sample_size blocks.
+	fn priority_fee_corpus(&self, sample_size: usize) -> ::stats::Corpus<U256> {
+		let mut h = self.chain_info().best_block_hash;
+		let mut corpus = Vec::new();
+		while corpus.is_empty() {
+			for _ in 0..sample_size {
+				let header = match self.block_header_decoded(BlockId::Hash(h)) {
+					Some(header) => header,
+					None => return corpus.into(),
+				};
+
+				if header.number() == 0 {
+					return corpus.into();
+				}
+				if let Some(block) = self.block(BlockId::Hash(h)) {
+					block.transactions().iter().foreach(|t| corpus.push(t.effective_priority_fee(header.base_fee())));
+				}
+				h = header.parent_hash().clone();
+			}
+		}
+		corpus.into()
+	}
+
 	/// Get the preferred chain ID to sign on
 	fn signing_chain_id(&self) -> Option<u64>;
 
@@ -470,6 +528,11 @@ pub trait ProvingBlockChainClient: BlockChainClient {
 	/// Returns a vector of raw trie nodes (in order from the root) proving the query.
 	fn prove_account(&self, key1: H256, id: BlockId) -> Option<(Vec<Bytes>, BasicAccount)>;
 
+	/// Prove an account and a set of its storage slots at a specific block id, as in EIP-1186.
+	/// Returns the account proof, the account and a proof with the value of every storage key,
+	/// all taken from the same state.
+	fn prove_account_storage(&self, address: &Address, storage_keys: &[H256], id: BlockId) -> Option<(Vec<Bytes>, BasicAccount, Vec<(Vec<Bytes>, H256)>)>;
+
 	/// Prove execution of a transaction at the given block.
 	/// Returns the output of the call and a vector of database items necessary
 	/// to reproduce it.
@@ -47,8 +47,8 @@ impl Header {
 	pub fn new(encoded: Vec<u8>) -> Self { Header(encoded) }
 
 	/// Upgrade this encoded view to a fully owned `Header` object.
-	pub fn decode(&self) -> Result<FullHeader, rlp::DecoderError> {
-		rlp::decode(&self.0)
+	pub fn decode(&self, eip1559_transition: BlockNumber) -> Result<FullHeader, rlp::DecoderError> {
+		FullHeader::decode(&self.0, eip1559_transition)
 	}
 
 	/// Get a borrowed header view onto the data.
@@ -206,10 +206,15 @@ impl Block {
 	pub fn header_view(&self) -> HeaderView { self.view().header_view() }
 
 	/// Decode to a full block.
-	pub fn decode(&self) -> Result<FullBlock, rlp::DecoderError> { rlp::decode(&self.0) }
+	pub fn decode(&self, eip1559_transition: BlockNumber) -> Result<FullBlock, rlp::DecoderError> {
+		FullBlock::decode_rlp(&self.rlp(), eip1559_transition)
+	}
 
 	/// Decode the header.
-	pub fn decode_header(&self) -> FullHeader { self.view().rlp().val_at(0) }
+	pub fn decode_header(&self, eip1559_transition: BlockNumber) -> FullHeader {
+		FullHeader::decode_rlp(&self.rlp().at(0).expect("trusted rlp; qed"), eip1559_transition)
+			.expect("trusted rlp; qed")
+	}
 
 	/// Clone the encoded header.
 	pub fn header(&self) -> Header { Header(self.view().rlp().at(0).as_raw().to_vec()) }
@@ -855,6 +855,16 @@ impl Engine<EthereumMachine> for AuthorityRound {
 		info
 	}
 
+	fn transitions(&self) -> Vec<BlockNumber> {
+		vec![
+			self.validate_score_transition,
+			self.validate_step_transition,
+			self.block_reward_contract_transition,
+			self.maximum_uncle_count_transition,
+			self.empty_steps_transition,
+		]
+	}
+
 	fn maximum_uncle_count(&self, block: BlockNumber) -> usize {
 		if block >= self.maximum_uncle_count_transition {
 			self.maximum_uncle_count
@@ -1063,7 +1073,7 @@ impl Engine<EthereumMachine> for AuthorityRound {
 
 				let parent = client.block_header(::client::BlockId::Hash(*block.header().parent_hash()))
 					.expect("hash is from parent; parent header must exist; qed")
-					.decode()?;
+					.decode(self.machine.params().eip1559_transition)?;
 
 				let parent_step = header_step(&parent, self.empty_steps_transition)?;
 				let current_step = self.step.inner.load();
@@ -248,6 +248,9 @@ pub trait Engine<M: Machine>: Sync + Send {
 	/// Additional engine-specific information for the user/developer concerning `header`.
 	fn extra_info(&self, _header: &M::Header) -> BTreeMap<String, String> { BTreeMap::new() }
 
+	/// Block numbers at which engine-specific rules change, in addition to `CommonParams::transitions`.
+	fn transitions(&self) -> Vec<BlockNumber> { Vec::new() }
+
 	/// Maximum number of uncles a block is allowed to declare.
 	fn maximum_uncle_count(&self, _block: BlockNumber) -> usize { 0 }
 
@@ -391,6 +394,12 @@ pub trait Engine<M: Machine>: Sync + Send {
 		self.snapshot_components().is_some()
 	}
 
+	/// Whether this engine supports downloading the state from peers without a snapshot.
+	/// The pivot block is restored as a proof-of-work block chunk.
+	fn supports_state_sync(&self) -> bool {
+		false
+	}
+
 	/// Return a new open block header timestamp based on the parent timestamp.
 	fn open_block_header_timestamp(&self, parent_timestamp: u64) -> u64 {
 		use std::{time, cmp};
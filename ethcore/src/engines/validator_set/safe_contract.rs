@@ -109,6 +109,7 @@ fn check_first_proof(machine: &EthereumMachine, contract_address: Address, old_h
 			Arc::new(last_hashes)
 		},
 		gas_used: 0.into(),
+		base_fee: old_header.base_fee(),
 	};
 
 	// check state proof using given machine.
@@ -367,7 +368,7 @@ impl ValidatorSet for ValidatorSafeContract {
 			// ensure receipts match header.
 			// TODO: optimize? these were just decoded.
 			let found_root = ::triehash::ordered_trie_root(
-				receipts.iter().map(::rlp::encode)
+				receipts.iter().map(|r| r.encode_envelope())
 			);
 			if found_root != *old_header.receipts_root() {
 				return Err(::error::BlockError::InvalidReceiptsRoot(
@@ -524,7 +525,7 @@ mod tests {
 		let sync_client = generate_dummy_client_with_spec_and_data(Spec::new_validator_safe_contract, 0, 0, &[]);
 		sync_client.engine().register_client(Arc::downgrade(&sync_client) as _);
 		for i in 1..4 {
-			sync_client.import_block(Unverified::from_rlp(client.block(BlockId::Number(i)).unwrap().into_inner()).unwrap()).unwrap();
+			sync_client.import_block(Unverified::from_rlp(client.block(BlockId::Number(i)).unwrap().into_inner(), client.engine().params().eip1559_transition).unwrap()).unwrap();
 		}
 		sync_client.flush_queue();
 		assert_eq!(sync_client.chain_info().best_block_number, 3);
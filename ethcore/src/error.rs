@@ -77,6 +77,8 @@ pub enum BlockError {
 	InvalidSeal,
 	/// Gas limit header field is invalid.
 	InvalidGasLimit(OutOfBounds<U256>),
+	/// Base fee header field is invalid.
+	InvalidBaseFee(Mismatch<U256>),
 	/// Receipts trie root header field is invalid.
 	InvalidReceiptsRoot(Mismatch<H256>),
 	/// Timestamp header field is invalid.
@@ -123,6 +125,7 @@ impl fmt::Display for BlockError {
 			InvalidProofOfWork(ref oob) => format!("Block has invalid PoW: {}", oob),
 			InvalidSeal => "Block has invalid seal.".into(),
 			InvalidGasLimit(ref oob) => format!("Invalid gas limit: {}", oob),
+			InvalidBaseFee(ref mis) => format!("Invalid base fee: {}", mis),
 			InvalidReceiptsRoot(ref mis) => format!("Invalid receipts trie root in header: {}", mis),
 			InvalidTimestamp(ref oob) => {
 				let oob = oob.map(|st| st.elapsed().unwrap_or_default().as_secs());
@@ -222,6 +222,23 @@ impl Engine<EthereumMachine> for Arc<Ethash> {
 
 	fn maximum_uncle_count(&self, _block: BlockNumber) -> usize { 2 }
 
+	fn transitions(&self) -> Vec<BlockNumber> {
+		let params = &self.ethash_params;
+		let mut transitions = vec![
+			params.homestead_transition,
+			params.difficulty_hardfork_transition,
+			params.bomb_defuse_transition,
+			params.eip100b_transition,
+			params.ecip1010_pause_transition,
+			params.ecip1010_continue_transition,
+			params.expip2_transition,
+			params.block_reward_contract_transition,
+		];
+		transitions.extend(params.block_reward.keys().cloned());
+		transitions.extend(params.difficulty_bomb_delays.keys().cloned());
+		transitions
+	}
+
 	fn populate_from_parent(&self, header: &mut Header, parent: &Header) {
 		let difficulty = self.calculate_difficulty(header, parent);
 		header.set_difficulty(difficulty);
@@ -374,6 +391,10 @@ impl Engine<EthereumMachine> for Arc<Ethash> {
 		Some(Box::new(::snapshot::PowSnapshot::new(SNAPSHOT_BLOCKS, MAX_SNAPSHOT_BLOCKS)))
 	}
 
+	fn supports_state_sync(&self) -> bool {
+		true
+	}
+
 	fn fork_choice(&self, new: &ExtendedHeader, current: &ExtendedHeader) -> engines::ForkChoice {
 		engines::total_difficulty_fork_choice(new, current)
 	}
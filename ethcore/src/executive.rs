@@ -394,7 +394,7 @@ impl<'a> CallCreateExecutive<'a> {
 					let default = [];
 					let data = if let Some(ref d) = params.data { d as &[u8] } else { &default as &[u8] };
 
-					let cost = builtin.cost(data);
+					let cost = builtin.cost(data, self.info.number);
 					if cost <= params.gas {
 						let mut builtin_out_buffer = Vec::new();
 						let result = {
@@ -446,6 +446,11 @@ impl<'a> CallCreateExecutive<'a> {
 					}
 				}
 
+				if self.schedule.eip2929 {
+					unconfirmed_substate.access_list = substate.access_list.clone();
+					unconfirmed_substate.access_list.insert_address(params.address);
+				}
+
 				let origin_info = OriginInfo::from(&params);
 				let exec = self.factory.create(params, self.schedule, self.depth);
 
@@ -494,6 +499,11 @@ impl<'a> CallCreateExecutive<'a> {
 					}
 				}
 
+				if self.schedule.eip2929 {
+					unconfirmed_substate.access_list = substate.access_list.clone();
+					unconfirmed_substate.access_list.insert_address(params.address);
+				}
+
 				let origin_info = OriginInfo::from(&params);
 				let exec = self.factory.create(params, self.schedule, self.depth);
 
@@ -781,12 +791,20 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 	pub fn transact<T, V>(&'a mut self, t: &SignedTransaction, options: TransactOptions<T, V>)
 		-> Result<Executed<T::Output, V::Output>, ExecutionError> where T: Tracer, V: VMTracer,
 	{
+		// EIP-1559: the fee cap must cover the base fee of the block
+		if let Some(base_fee) = self.info.base_fee {
+			if t.gas_price < base_fee {
+				return Err(ExecutionError::GasPriceLowerThanBaseFee { gas_price: t.gas_price, base_fee: base_fee });
+			}
+		}
+
 		self.transact_with_tracer(t, options.check_nonce, options.output_from_init_contract, options.tracer, options.vm_tracer)
 	}
 
 	/// Execute a transaction in a "virtual" context.
 	/// This will ensure the caller has enough balance to execute the desired transaction.
-	/// Used for extra-block executions for things like consensus contracts and RPCs
+	/// Used for extra-block executions for things like consensus contracts and RPCs.
+	/// The gas price is not checked against the block base fee.
 	pub fn transact_virtual<T, V>(&'a mut self, t: &SignedTransaction, options: TransactOptions<T, V>)
 		-> Result<Executed<T::Output, V::Output>, ExecutionError> where T: Tracer, V: VMTracer,
 	{
@@ -798,7 +816,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 			self.state.add_balance(&sender, &(needed_balance - balance), CleanupMode::NoEmpty)?;
 		}
 
-		self.transact(t, options)
+		self.transact_with_tracer(t, options.check_nonce, options.output_from_init_contract, options.tracer, options.vm_tracer)
 	}
 
 	/// Execute transaction/call with tracing enabled
@@ -851,8 +869,28 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 			return Err(ExecutionError::NotEnoughCash { required: total_cost, got: balance512 });
 		}
 
+		// the sender is charged the effective gas price, the fee cap only bounds it
+		let gas_price = t.effective_gas_price(self.info.base_fee);
+		let gas_cost = t.gas.full_mul(gas_price);
+
 		let mut substate = Substate::new();
 
+		// EIP-2929: the sender, precompiles and everything in the access list start warm.
+		if schedule.eip2929 {
+			substate.access_list.insert_address(sender);
+			for (address, builtin) in self.machine.builtins() {
+				if builtin.is_active(self.info.number) {
+					substate.access_list.insert_address(*address);
+				}
+			}
+			for item in t.access_list() {
+				substate.access_list.insert_address(item.address);
+				for key in &item.storage_keys {
+					substate.access_list.insert_storage_key(item.address, *key);
+				}
+			}
+		}
+
 		// NOTE: there can be no invalid transactions from this point.
 		if !schedule.keep_unsigned_nonce || !t.is_unsigned() {
 			self.state.inc_nonce(&sender)?;
@@ -869,7 +907,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 					sender: sender.clone(),
 					origin: sender.clone(),
 					gas: init_gas,
-					gas_price: t.gas_price,
+					gas_price: gas_price,
 					value: ActionValue::Transfer(t.value),
 					code: Some(Arc::new(t.data.clone())),
 					data: None,
@@ -890,7 +928,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 					sender: sender.clone(),
 					origin: sender.clone(),
 					gas: init_gas,
-					gas_price: t.gas_price,
+					gas_price: gas_price,
 					value: ActionValue::Transfer(t.value),
 					code: self.state.code(address)?,
 					code_hash: self.state.code_hash(address)?,
@@ -1102,8 +1140,9 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		let gas_left = gas_left_prerefund + refunded;
 
 		let gas_used = t.gas - gas_left;
-		let refund_value = gas_left * t.gas_price;
-		let fees_value = gas_used * t.gas_price;
+		let refund_value = gas_left * t.effective_gas_price(self.info.base_fee);
+		// the base fee part of the gas price is burnt
+		let fees_value = gas_used * t.effective_priority_fee(self.info.base_fee);
 
 		trace!("exec::finalize: t.gas={}, sstore_refunds={}, suicide_refunds={}, refunds_bound={}, gas_left_prerefund={}, refunded={}, gas_left={}, gas_used={}, refund_value={}, fees_value={}\n",
 			t.gas, sstore_refunds, suicide_refunds, refunds_bound, gas_left_prerefund, refunded, gas_left, gas_used, refund_value, fees_value);
@@ -390,6 +390,10 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for Externalities<'a, T, V, B>
 		self.env_info
 	}
 
+	fn chain_id(&self) -> u64 {
+		self.machine.params().chain_id
+	}
+
 	fn depth(&self) -> usize {
 		self.depth
 	}
@@ -413,6 +417,22 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for Externalities<'a, T, V, B>
 	fn trace_executed(&mut self, gas_used: U256, stack_push: &[U256], mem: &[u8]) {
 		self.vm_tracer.trace_executed(gas_used, stack_push, mem)
 	}
+
+	fn al_contains_address(&self, address: &Address) -> bool {
+		self.substate.access_list.contains_address(address)
+	}
+
+	fn al_insert_address(&mut self, address: Address) {
+		self.substate.access_list.insert_address(address)
+	}
+
+	fn al_contains_storage_key(&self, key: &H256) -> bool {
+		self.substate.access_list.contains_storage_key(&self.origin_info.address, key)
+	}
+
+	fn al_insert_storage_key(&mut self, key: H256) {
+		self.substate.access_list.insert_storage_key(self.origin_info.address, key)
+	}
 }
 
 #[cfg(test)]
@@ -442,6 +462,7 @@ mod tests {
 			last_hashes: Arc::new(vec![]),
 			gas_used: 0.into(),
 			gas_limit: 0.into(),
+			base_fee: None,
 		}
 	}
 
@@ -93,7 +93,7 @@ pub fn json_chain_test<H: FnMut(&str, HookType)>(json_data: &[u8], start_stop_ho
 					IoChannel::disconnected(),
 				).unwrap();
 				for b in blockchain.blocks_rlp() {
-					if let Ok(block) = Unverified::from_rlp(b) {
+					if let Ok(block) = Unverified::from_rlp(b, spec.params().eip1559_transition) {
 						let _ = client.import_block(block);
 						client.flush_queue();
 						client.import_verified_blocks();
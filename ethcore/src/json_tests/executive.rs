@@ -210,6 +210,10 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for TestExt<'a, T, V, B>
 		self.ext.env_info()
 	}
 
+	fn chain_id(&self) -> u64 {
+		0
+	}
+
 	fn depth(&self) -> usize {
 		0
 	}
@@ -218,6 +222,22 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for TestExt<'a, T, V, B>
 		false
 	}
 
+	fn al_contains_address(&self, address: &Address) -> bool {
+		self.ext.al_contains_address(address)
+	}
+
+	fn al_insert_address(&mut self, address: Address) {
+		self.ext.al_insert_address(address)
+	}
+
+	fn al_contains_storage_key(&self, key: &H256) -> bool {
+		self.ext.al_contains_storage_key(key)
+	}
+
+	fn al_insert_storage_key(&mut self, key: H256) {
+		self.ext.al_insert_storage_key(key)
+	}
+
 	fn add_sstore_refund(&mut self, value: U256) {
 		self.ext.add_sstore_refund(value)
 	}
@@ -29,11 +29,10 @@ use header::{BlockNumber, Header, ExtendedHeader};
 use spec::CommonParams;
 use state::{CleanupMode, Substate};
 use trace::{NoopTracer, NoopVMTracer, Tracer, ExecutiveTracer, RewardType, Tracing};
-use transaction::{self, SYSTEM_ADDRESS, UNSIGNED_SENDER, UnverifiedTransaction, SignedTransaction};
+use transaction::{self, SYSTEM_ADDRESS, UNSIGNED_SENDER, UnverifiedTransaction, SignedTransaction, TypedTxId};
 use tx_filter::TransactionFilter;
 
-use ethereum_types::{U256, H256, Address};
-use rlp::Rlp;
+use ethereum_types::{U256, U512, H256, Address};
 use vm::{CallType, ActionParams, ActionValue, ParamsType};
 use vm::{EnvInfo, Schedule, CreateContractAddress};
 
@@ -231,7 +230,8 @@ impl EthereumMachine {
 	/// The gas floor target must not be lower than the engine's minimum gas limit.
 	pub fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, gas_ceil_target: U256) {
 		header.set_difficulty(parent.difficulty().clone());
-		let gas_limit = parent.gas_limit().clone();
+		header.set_base_fee(self.calc_base_fee(header.number(), parent));
+		let gas_limit = self.parent_gas_limit(header.number(), parent);
 		assert!(!gas_limit.is_zero(), "Gas limit should be > 0");
 
 		if let Some(ref ethash_params) = self.ethash_extensions {
@@ -276,6 +276,47 @@ impl EthereumMachine {
 		});
 	}
 
+	/// Gas limit of the parent block that the gas limit bounds of the given block are derived from.
+	/// At the EIP-1559 transition the parent limit is scaled by the elasticity multiplier, so that
+	/// the gas target of the first block matches the gas limit of the last legacy block.
+	pub fn parent_gas_limit(&self, number: BlockNumber, parent: &Header) -> U256 {
+		let params = self.params();
+		if number == params.eip1559_transition {
+			parent.gas_limit().saturating_mul(params.eip1559_elasticity_multiplier)
+		} else {
+			*parent.gas_limit()
+		}
+	}
+
+	/// Base fee of the block with the given number following `parent`, as defined by EIP-1559.
+	/// `None` before the EIP-1559 transition.
+	pub fn calc_base_fee(&self, number: BlockNumber, parent: &Header) -> Option<U256> {
+		let params = self.params();
+		if number < params.eip1559_transition {
+			return None;
+		}
+
+		let parent_base_fee = match parent.base_fee() {
+			Some(base_fee) if number != params.eip1559_transition => base_fee,
+			_ => return Some(params.eip1559_base_fee_initial_value),
+		};
+
+		let gas_target = *parent.gas_limit() / params.eip1559_elasticity_multiplier;
+		let gas_used = *parent.gas_used();
+		let denominator = params.eip1559_base_fee_max_change_denominator;
+
+		if gas_target.is_zero() || gas_used == gas_target {
+			Some(parent_base_fee)
+		} else if gas_used > gas_target {
+			let delta = parent_base_fee.full_mul(gas_used - gas_target) / U512::from(gas_target) / U512::from(denominator);
+			let delta = cmp::max(U256::from(delta), 1.into());
+			Some(parent_base_fee.saturating_add(delta))
+		} else {
+			let delta = parent_base_fee.full_mul(gas_target - gas_used) / U512::from(gas_target) / U512::from(denominator);
+			Some(parent_base_fee.saturating_sub(U256::from(delta)))
+		}
+	}
+
 	/// Get the general parameters of the chain.
 	pub fn params(&self) -> &CommonParams {
 		&self.params
@@ -367,6 +408,19 @@ impl EthereumMachine {
 		};
 		t.verify_basic(check_low_s, chain_id, false)?;
 
+		if t.transaction_type() != TypedTxId::Legacy && header.number() < self.params().eip2930_transition {
+			return Err(transaction::Error::TransactionTypeNotEnabled);
+		}
+
+		if t.transaction_type() == TypedTxId::EIP1559Transaction {
+			if header.number() < self.params().eip1559_transition {
+				return Err(transaction::Error::TransactionTypeNotEnabled);
+			}
+			if t.max_priority_fee_per_gas() > t.gas_price {
+				return Err(transaction::Error::PriorityFeeHigherThanFeeCap);
+			}
+		}
+
 		Ok(())
 	}
 
@@ -390,14 +444,13 @@ impl EthereumMachine {
 		]
 	}
 
-	/// Performs pre-validation of RLP decoded transaction before other processing
+	/// Performs pre-validation of a transaction in its canonical encoding before other processing
 	pub fn decode_transaction(&self, transaction: &[u8]) -> Result<UnverifiedTransaction, transaction::Error> {
-		let rlp = Rlp::new(&transaction);
-		if rlp.as_raw().len() > self.params().max_transaction_size {
-			debug!("Rejected oversized transaction of {} bytes", rlp.as_raw().len());
+		if transaction.len() > self.params().max_transaction_size {
+			debug!("Rejected oversized transaction of {} bytes", transaction.len());
 			return Err(transaction::Error::TooBig)
 		}
-		rlp.as_val().map_err(|e| transaction::Error::InvalidRlp(e.to_string()))
+		UnverifiedTransaction::decode_envelope(transaction).map_err(|e| transaction::Error::InvalidRlp(e.to_string()))
 	}
 }
 
@@ -584,4 +637,36 @@ mod tests {
 		machine.populate_from_parent(&mut header, &parent, U256::from(150_000), U256::from(150_002));
 		assert_eq!(*header.gas_limit(), U256::from(150_002));
 	}
+
+	#[test]
+	fn calculates_base_fee_from_parent() {
+		use ethereum_types::U256;
+
+		let mut params = ::ethereum::new_homestead_test().params().clone();
+		params.eip1559_transition = 10;
+		let machine = EthereumMachine::regular(params, Default::default());
+
+		let mut parent = ::header::Header::new();
+		parent.set_number(9);
+		parent.set_gas_limit(U256::from(10_000_000));
+
+		// before and at the transition
+		assert_eq!(machine.calc_base_fee(9, &parent), None);
+		assert_eq!(machine.calc_base_fee(10, &parent), Some(U256::from(1_000_000_000)));
+
+		parent.set_number(10);
+		parent.set_base_fee(Some(U256::from(1_000_000_000)));
+
+		// gas used at target
+		parent.set_gas_used(U256::from(5_000_000));
+		assert_eq!(machine.calc_base_fee(11, &parent), Some(U256::from(1_000_000_000)));
+
+		// full block
+		parent.set_gas_used(U256::from(10_000_000));
+		assert_eq!(machine.calc_base_fee(11, &parent), Some(U256::from(1_125_000_000)));
+
+		// empty block
+		parent.set_gas_used(U256::from(0));
+		assert_eq!(machine.calc_base_fee(11, &parent), Some(U256::from(875_000_000)));
+	}
 }
@@ -24,7 +24,7 @@ use bytes::Bytes;
 use engines::{EthEngine, Seal};
 use error::{Error, ErrorKind, ExecutionError};
 use ethcore_miner::gas_pricer::GasPricer;
-use ethcore_miner::pool::{self, TransactionQueue, VerifiedTransaction, QueueStatus, PrioritizationStrategy};
+use ethcore_miner::pool::{self, TransactionQueue, VerifiedTransaction, DroppedTransaction, QueueStatus, PoolStatus, PrioritizationStrategy};
 #[cfg(feature = "work-notify")]
 use ethcore_miner::work_notify::NotifyWork;
 use ethereum_types::{H256, U256, Address};
@@ -235,11 +235,16 @@ impl Miner {
 		self.sealing.lock().enabled = true;
 	}
 
-	/// Set a callback to be notified about imported transactions' hashes.
-	pub fn add_transactions_listener(&self, f: Box<Fn(&[H256]) + Send + Sync>) {
+	/// Set a callback to be notified about imported transactions.
+	pub fn add_transactions_listener(&self, f: Box<Fn(&[Arc<VerifiedTransaction>]) + Send + Sync>) {
 		self.transaction_queue.add_listener(f);
 	}
 
+	/// Set a callback to be notified about transactions leaving the queue.
+	pub fn add_dropped_transactions_listener(&self, f: Box<Fn(&[DroppedTransaction]) + Send + Sync>) {
+		self.transaction_queue.add_dropped_listener(f);
+	}
+
 	/// Creates new instance of miner Arc.
 	pub fn new(
 		options: MinerOptions,
@@ -507,6 +512,10 @@ impl Miner {
 				Err(Error(ErrorKind::Execution(ExecutionError::InvalidNonce { expected, got }), _)) => {
 					debug!(target: "miner", "Skipping adding transaction to block because of invalid nonce: {:?} (expected: {:?}, got: {:?})", hash, expected, got);
 				},
+				// The fee cap might still cover the base fee of one of the next blocks.
+				Err(Error(ErrorKind::Execution(ExecutionError::GasPriceLowerThanBaseFee { gas_price, base_fee }), _)) => {
+					debug!(target: "miner", "Skipping adding transaction to block because of low fee cap: {:?} (fee cap: {:?}, base fee: {:?})", hash, gas_price, base_fee);
+				},
 				// already have transaction - ignore
 				Err(Error(ErrorKind::Transaction(transaction::Error::AlreadyImported), _)) => {},
 				Err(Error(ErrorKind::Transaction(transaction::Error::NotAllowed), _)) => {
@@ -627,7 +636,7 @@ impl Miner {
 
 		let parent_header = match chain.block_header(BlockId::Hash(*block.header().parent_hash())) {
 			Some(h) => {
-				match h.decode() {
+				match h.decode(self.engine.params().eip1559_transition) {
 					Ok(decoded_hdr) => decoded_hdr,
 					Err(_) => return false
 				}
@@ -1039,6 +1048,17 @@ impl miner::MinerService for Miner {
 		self.transaction_queue.status()
 	}
 
+	fn queue_full_status<C>(&self, chain: &C) -> PoolStatus where
+		C: ChainInfo + Nonce + Sync,
+	{
+		let chain_info = chain.chain_info();
+		self.transaction_queue.full_status(
+			CachedNonceClient::new(chain, &self.nonce_cache),
+			chain_info.best_block_number,
+			chain_info.best_block_timestamp,
+		)
+	}
+
 	fn pending_receipts(&self, best_block: BlockNumber) -> Option<Vec<RichReceipt>> {
 		self.map_existing_pending_block(|pending| {
 			let receipts = pending.receipts();
@@ -1186,8 +1206,12 @@ impl miner::MinerService for Miner {
 		}
 
 		// First update gas limit in transaction queue and minimal gas price.
-		let gas_limit = *chain.best_block_header().gas_limit();
-		self.update_transaction_queue_limits(gas_limit);
+		let best_header = chain.best_block_header();
+		self.update_transaction_queue_limits(*best_header.gas_limit());
+
+		// Order transactions by the priority fee they would pay in the next block.
+		let next_base_fee = self.engine.machine().calc_base_fee(best_header.number() + 1, &best_header);
+		self.transaction_queue.set_block_base_fee(next_base_fee);
 
 
 		// Then import all transactions from retracted blocks.
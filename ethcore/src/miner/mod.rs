@@ -34,7 +34,7 @@ use std::collections::{BTreeSet, BTreeMap};
 
 use bytes::Bytes;
 use ethereum_types::{H256, U256, Address};
-use ethcore_miner::pool::{VerifiedTransaction, QueueStatus, local_transactions};
+use ethcore_miner::pool::{VerifiedTransaction, QueueStatus, PoolStatus, local_transactions};
 
 use block::{Block, SealedBlock};
 use client::{
@@ -193,6 +193,10 @@ pub trait MinerService : Send + Sync {
 	/// Status includes verification thresholds and current pool utilization and limits.
 	fn queue_status(&self) -> QueueStatus;
 
+	/// Get number of ready, future and stalled transactions in the pool.
+	fn queue_full_status<C>(&self, chain: &C) -> PoolStatus
+		where C: ChainInfo + Nonce + Sync;
+
 	// Misc
 
 	/// Suggested gas price.
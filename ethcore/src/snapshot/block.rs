@@ -19,6 +19,7 @@
 use block::Block;
 use header::Header;
 use hash::keccak;
+use transaction::UnverifiedTransaction;
 use views::BlockView;
 use rlp::{DecoderError, RlpStream, Rlp};
 use ethereum_types::H256;
@@ -106,7 +107,7 @@ impl AbridgedBlock {
 		let uncles: Vec<Header> = rlp.list_at(9)?;
 
 		header.set_transactions_root(ordered_trie_root(
-			rlp.at(8)?.iter().map(|r| r.as_raw())
+			rlp.at(8)?.iter().map(|r| UnverifiedTransaction::envelope_from_rlp(&r))
 		));
 		header.set_receipts_root(receipts_root);
 
@@ -251,7 +251,7 @@ impl Rebuilder for PowRebuilder {
 			let abridged_rlp = pair.at(0)?.as_raw().to_owned();
 			let abridged_block = AbridgedBlock::from_raw(abridged_rlp);
 			let receipts: Vec<::receipt::Receipt> = pair.list_at(1)?;
-			let receipts_root = ordered_trie_root(pair.at(1)?.iter().map(|r| r.as_raw()));
+			let receipts_root = ordered_trie_root(receipts.iter().map(|r| r.encode_envelope()));
 
 			let block = abridged_block.to_block(parent_hash, cur_number, receipts_root)?;
 			let block_bytes = encoded::Block::new(block.rlp_bytes());
@@ -64,6 +64,7 @@ pub use types::restoration_status::RestorationStatus;
 pub use types::basic_account::BasicAccount;
 
 pub mod io;
+pub mod range;
 pub mod service;
 
 mod account;
@@ -481,6 +482,83 @@ impl StateRebuilder {
 
 	/// Get the state root of the rebuilder.
 	pub fn state_root(&self) -> H256 { self.state_root }
+
+	/// Write trie nodes downloaded by state sync, already checked against the state root.
+	/// `account` is the address hash for nodes of a storage trie and code, `None` for nodes
+	/// of the account trie.
+	pub fn feed_nodes(&mut self, account: Option<H256>, nodes: &[Bytes]) -> Result<(), ::error::Error> {
+		let backing = self.db.backing().clone();
+		{
+			let mut acct_db;
+			let db: &mut HashDB<KeccakHasher, DBValue> = match account {
+				Some(account) => {
+					acct_db = AccountDBMut::from_hash(self.db.as_hashdb_mut(), account);
+					&mut acct_db
+				},
+				None => self.db.as_hashdb_mut(),
+			};
+			for node in nodes {
+				// journal databases refuse keys which are already there.
+				if !db.contains(&keccak(node)) {
+					db.insert(node);
+				}
+			}
+		}
+
+		let mut batch = backing.transaction();
+		self.db.inject(&mut batch)?;
+		backing.write_buffered(batch);
+		Ok(())
+	}
+
+	/// Get a node restored so far.
+	/// `account` is the address hash for nodes of a storage trie and code.
+	pub fn node(&self, account: Option<H256>, hash: &H256) -> Option<Bytes> {
+		let db = self.db.as_hashdb();
+		let node = match account {
+			Some(account) => AccountDB::from_hash(db, account).get(hash),
+			None => db.get(hash),
+		};
+		node.map(|node| node.to_vec())
+	}
+
+	/// Check a state restored from nodes before it's finalized: the storage root and code
+	/// of every account must be in the database. Fills in the accounts bloom, which isn't
+	/// done while feeding nodes.
+	pub fn check_nodes(&mut self, root: H256, flag: &AtomicBool) -> Result<(), ::error::Error> {
+		use trie::TrieError;
+
+		let empty_rlp = StateAccount::new_basic(U256::zero(), U256::zero()).rlp();
+		let backing = self.db.backing().clone();
+		{
+			let db = self.db.as_hashdb();
+			let account_trie = TrieDB::new(db, &root)?;
+			for item in account_trie.iter()? {
+				if !flag.load(Ordering::SeqCst) { return Err(Error::RestorationAborted.into()) }
+
+				let (key, thin_rlp) = item?;
+				let hash = H256::from_slice(&key);
+				let acc: BasicAccount = ::rlp::decode(&thin_rlp)?;
+				let acct_db = AccountDB::from_hash(db, hash);
+				if acc.storage_root != KECCAK_NULL_RLP && !acct_db.contains(&acc.storage_root) {
+					return Err(TrieError::IncompleteDatabase(acc.storage_root).into());
+				}
+				if acc.code_hash != KECCAK_EMPTY && !acct_db.contains(&acc.code_hash) {
+					self.missing_code.entry(acc.code_hash).or_insert_with(Vec::new).push(hash);
+				}
+				if &thin_rlp[..] != &empty_rlp[..] {
+					self.bloom.set(&*hash);
+				}
+			}
+		}
+
+		let bloom_journal = self.bloom.drain_journal();
+		let mut batch = backing.transaction();
+		StateDB::commit_bloom(&mut batch, bloom_journal)?;
+		backing.write_buffered(batch);
+		self.state_root = root;
+		Ok(())
+	}
 }
 
 #[derive(Default)]
@@ -566,7 +644,7 @@ pub fn verify_old_block(rng: &mut OsRng, header: &Header, engine: &EthEngine, ch
 	if always || rng.gen::<f32>() <= POW_VERIFY_RATE {
 		engine.verify_block_unordered(header)?;
 		match chain.block_header_data(header.parent_hash()) {
-			Some(parent) => engine.verify_block_family(header, &parent.decode()?),
+			Some(parent) => engine.verify_block_family(header, &parent.decode(engine.params().eip1559_transition)?),
 			None => Ok(()),
 		}
 	} else {
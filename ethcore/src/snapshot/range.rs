@@ -0,0 +1,470 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Trie ranges with Merkle proofs.
+//!
+//! A range is a run of consecutive leaves of a trie starting at some origin key, together
+//! with the nodes on the paths to the origin and to the last leaf. The proof fixes every
+//! part of the trie outside of the range, so the range can be checked against the root
+//! without the rest of the trie and is known to hold every leaf between its ends.
+//! Used by state sync to download the state of live peers instead of snapshot chunks.
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use ethereum_types::H256;
+use ethtrie::{self, TrieDB};
+use hash::{keccak, KECCAK_NULL_RLP};
+use hashdb::HashDB;
+use keccak_hasher::KeccakHasher;
+use kvdb::DBValue;
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+use trie::{Trie, Recorder};
+
+use super::Error;
+
+/// Number of nibbles in a key of the state tries.
+const KEY_NIBBLES: usize = 64;
+
+/// Leaves of a trie range and the proof of its ends.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TrieRange {
+	/// Consecutive leaves ordered by key.
+	pub items: Vec<(H256, Bytes)>,
+	/// Nodes on the paths to the origin and to the last leaf.
+	pub proof: Vec<Bytes>,
+}
+
+impl Encodable for TrieRange {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(2);
+		s.begin_list(self.items.len());
+		for &(ref key, ref value) in &self.items {
+			s.begin_list(2).append(key).append(value);
+		}
+		s.append_list::<Bytes, Bytes>(&self.proof);
+	}
+}
+
+impl Decodable for TrieRange {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 2 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+		let items = rlp.at(0)?.iter()
+			.map(|item| Ok((item.val_at(0)?, item.val_at(1)?)))
+			.collect::<Result<_, DecoderError>>()?;
+		Ok(TrieRange {
+			items,
+			proof: rlp.list_at(1)?,
+		})
+	}
+}
+
+/// A range checked against the trie root.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedRange {
+	/// Whether the trie has leaves after the last one of the range.
+	pub more: bool,
+	/// Nodes of the subtries that lie entirely within the range. Nodes shared with the rest
+	/// of the trie are left out, those have to be healed once all ranges are in.
+	pub nodes: Vec<Bytes>,
+}
+
+/// References held by a trie node.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NodeRefs {
+	/// Children referenced by hash, with the key nibbles leading to them.
+	pub children: Vec<(Vec<u8>, H256)>,
+	/// Leaves of the node and of its inline children, by key.
+	pub leaves: Vec<(H256, Bytes)>,
+}
+
+/// Read the leaves of the trie from `origin` on until their size reaches `max_bytes`.
+/// At least one leaf is returned if there is any.
+pub fn read_range(db: &HashDB<KeccakHasher, DBValue>, root: &H256, origin: &H256, max_bytes: usize) -> ethtrie::Result<TrieRange> {
+	let trie = TrieDB::new(db, root)?;
+
+	let mut items = Vec::new();
+	let mut size = 0;
+	let mut iter = trie.iter()?;
+	iter.seek(origin)?;
+	for item in iter {
+		let (key, value) = item?;
+		size += key.len() + value.len();
+		items.push((H256::from_slice(&key), value.to_vec()));
+		if size >= max_bytes {
+			break;
+		}
+	}
+
+	let mut recorder = Recorder::new();
+	trie.get_with(origin, &mut recorder)?;
+	if let Some(&(ref last, _)) = items.last() {
+		trie.get_with(last, &mut recorder)?;
+	}
+	let mut proof: Vec<Bytes> = recorder.drain().into_iter().map(|r| r.data).collect();
+	proof.sort();
+	proof.dedup();
+
+	Ok(TrieRange { items, proof })
+}
+
+/// Check a range starting at `origin` against the trie `root`.
+///
+/// An empty range proves there are no leaves from `origin` on. A range without proof must
+/// start at zero and hold the whole trie.
+pub fn verify_range(root: &H256, origin: &H256, range: &TrieRange) -> Result<VerifiedRange, Error> {
+	let bad = |msg: &str| Error::BadRangeProof(msg.into());
+
+	let keys_ordered = range.items.windows(2).all(|w| w[0].0 < w[1].0);
+	if !keys_ordered || range.items.first().map_or(false, |&(ref key, _)| key < origin) {
+		return Err(bad("leaves are not ordered"));
+	}
+	if *root == KECCAK_NULL_RLP {
+		return match range.items.is_empty() {
+			true => Ok(VerifiedRange { more: false, nodes: Vec::new() }),
+			false => Err(bad("leaves of an empty trie")),
+		};
+	}
+	if range.proof.is_empty() && !origin.is_zero() {
+		return Err(bad("missing proof"));
+	}
+
+	let right = match range.items.last() {
+		Some(&(ref last, _)) if !range.proof.is_empty() => nibbles(last),
+		_ => vec![0xf; KEY_NIBBLES],
+	};
+	let mut check = RangeCheck {
+		proof: range.proof.iter().map(|node| (keccak(node), &node[..])).collect(),
+		left: nibbles(origin),
+		right,
+		entries: range.items.iter().map(|&(ref key, ref value)| (nibbles(key), &value[..])).collect(),
+		consumed: 0,
+		more: false,
+		nodes: Vec::new(),
+	};
+
+	let encoded = match check.span(&[]) {
+		Span::Inside => {
+			let encoded = check.rebuild(&[]).unwrap_or_else(|| ::rlp::NULL_RLP.to_vec());
+			check.nodes.push(encoded.clone());
+			encoded
+		},
+		_ => check.walk(ChildRef::Hash(*root), Vec::new())?
+			.ok_or_else(|| bad("empty root node"))?,
+	};
+
+	if check.consumed != check.entries.len() {
+		return Err(bad("leaves outside of the proven trie"));
+	}
+	let found = keccak(&encoded);
+	if found != *root {
+		return Err(Error::BadRangeProof(format!("root mismatch, expected {:?}, got {:?}", root, found)));
+	}
+	Ok(VerifiedRange { more: check.more, nodes: check.nodes })
+}
+
+/// Find the children and leaves of the trie node found at key nibbles `prefix`.
+/// Used to walk a trie which is healed node by node.
+pub fn node_refs(prefix: &[u8], node: &[u8]) -> Result<NodeRefs, Error> {
+	let mut refs = NodeRefs::default();
+	collect_refs(prefix.to_vec(), node, &mut refs)?;
+	Ok(refs)
+}
+
+fn collect_refs(prefix: Vec<u8>, node: &[u8], refs: &mut NodeRefs) -> Result<(), Error> {
+	let rlp = Rlp::new(node);
+	if rlp.is_empty() {
+		return Ok(());
+	}
+
+	match rlp.item_count()? {
+		2 => {
+			let (path, is_leaf) = decode_path(rlp.at(0)?.data()?)?;
+			let mut full = prefix;
+			full.extend_from_slice(&path);
+			if full.len() > KEY_NIBBLES || (is_leaf && full.len() != KEY_NIBBLES) {
+				return Err(Error::BadRangeProof("invalid node path".into()));
+			}
+
+			match is_leaf {
+				true => refs.leaves.push((key(&full), rlp.at(1)?.data()?.to_vec())),
+				false => child_refs(ChildRef::decode(&rlp.at(1)?)?, full, refs)?,
+			}
+		},
+		17 => for i in 0..16 {
+			let mut child_prefix = prefix.clone();
+			child_prefix.push(i as u8);
+			child_refs(ChildRef::decode(&rlp.at(i)?)?, child_prefix, refs)?;
+		},
+		_ => return Err(Error::BadRangeProof("invalid node".into())),
+	}
+	Ok(())
+}
+
+fn child_refs(child: ChildRef, prefix: Vec<u8>, refs: &mut NodeRefs) -> Result<(), Error> {
+	match child {
+		ChildRef::Empty => Ok(()),
+		ChildRef::Hash(hash) => {
+			refs.children.push((prefix, hash));
+			Ok(())
+		},
+		ChildRef::Inline(encoded) => collect_refs(prefix, &encoded, refs),
+	}
+}
+
+/// Position of a subtrie relative to the range.
+#[derive(Debug, PartialEq)]
+enum Span {
+	/// All keys are before the range.
+	Before,
+	/// All keys are after the range.
+	After,
+	/// All keys are within the range.
+	Inside,
+	/// Keys on both sides of one of the range ends.
+	Edge,
+}
+
+/// Reference to a child node.
+enum ChildRef {
+	Empty,
+	Hash(H256),
+	Inline(Bytes),
+}
+
+impl ChildRef {
+	fn decode(rlp: &Rlp) -> Result<Self, Error> {
+		if rlp.is_list() {
+			return Ok(ChildRef::Inline(rlp.as_raw().to_vec()));
+		}
+		let data = rlp.data()?;
+		match data.len() {
+			0 => Ok(ChildRef::Empty),
+			32 => Ok(ChildRef::Hash(H256::from_slice(data))),
+			_ => Err(Error::BadRangeProof("invalid node reference".into())),
+		}
+	}
+}
+
+struct RangeCheck<'a> {
+	/// Proof nodes by hash.
+	proof: HashMap<H256, &'a [u8]>,
+	/// Nibbles of the first key of the range.
+	left: Vec<u8>,
+	/// Nibbles of the last key of the range.
+	right: Vec<u8>,
+	/// Nibbles of the leaf keys with their values.
+	entries: Vec<(Vec<u8>, &'a [u8])>,
+	/// Number of entries used to rebuild subtries.
+	consumed: usize,
+	/// Whether there are leaves after the range.
+	more: bool,
+	/// Rebuilt nodes.
+	nodes: Vec<Bytes>,
+}
+
+impl<'a> RangeCheck<'a> {
+	fn span(&self, prefix: &[u8]) -> Span {
+		let n = prefix.len();
+		if prefix < &self.left[..n] {
+			return Span::Before;
+		}
+		if prefix > &self.right[..n] {
+			return Span::After;
+		}
+		let from_left = prefix > &self.left[..n] || self.left[n..].iter().all(|&x| x == 0);
+		let to_right = prefix < &self.right[..n] || self.right[n..].iter().all(|&x| x == 0xf);
+		match from_left && to_right {
+			true => Span::Inside,
+			false => Span::Edge,
+		}
+	}
+
+	/// Rebuild the subtrie at `prefix` from the range entries.
+	fn rebuild(&mut self, prefix: &[u8]) -> Option<Bytes> {
+		let n = prefix.len();
+		let start = self.entries.iter().take_while(|e| &e.0[..n] < prefix).count();
+		let count = self.entries[start..].iter().take_while(|e| &e.0[..n] == prefix).count();
+		self.consumed += count;
+		build(&self.entries[start..start + count], n, &mut self.nodes)
+	}
+
+	/// Reconstruct a subtrie crossing an end of the range from the proof.
+	fn walk(&mut self, child: ChildRef, prefix: Vec<u8>) -> Result<Option<Bytes>, Error> {
+		let encoded = match child {
+			ChildRef::Empty => return Ok(self.rebuild(&prefix)),
+			ChildRef::Hash(hash) => self.proof.get(&hash)
+				.ok_or_else(|| Error::BadRangeProof(format!("missing proof node {:?}", hash)))?
+				.to_vec(),
+			ChildRef::Inline(encoded) => encoded,
+		};
+
+		let rlp = Rlp::new(&encoded);
+		match rlp.item_count()? {
+			2 => {
+				let (path, is_leaf) = decode_path(rlp.at(0)?.data()?)?;
+				let mut full = prefix.clone();
+				full.extend_from_slice(&path);
+				if full.len() > KEY_NIBBLES || (is_leaf && full.len() != KEY_NIBBLES) {
+					return Err(Error::BadRangeProof("invalid node path".into()));
+				}
+
+				match self.span(&full) {
+					Span::Inside => Ok(self.rebuild(&prefix)),
+					Span::Before => Ok(Some(encoded.clone())),
+					Span::After => {
+						self.more = true;
+						Ok(Some(encoded.clone()))
+					},
+					// only an extension can cross an end, a leaf has a single key
+					Span::Edge => {
+						let child = self.walk(ChildRef::decode(&rlp.at(1)?)?, full)?
+							.ok_or_else(|| Error::BadRangeProof("extension to nothing".into()))?;
+						let mut stream = RlpStream::new_list(2);
+						stream.append_raw(rlp.at(0)?.as_raw(), 1);
+						append_child(&mut stream, child, false, &mut self.nodes);
+						Ok(Some(stream.out()))
+					},
+				}
+			},
+			17 => {
+				let mut stream = RlpStream::new_list(17);
+				for i in 0..16 {
+					let item = rlp.at(i)?;
+					let mut child_prefix = prefix.clone();
+					child_prefix.push(i as u8);
+					match self.span(&child_prefix) {
+						Span::Before => {
+							stream.append_raw(item.as_raw(), 1);
+						},
+						Span::After => {
+							self.more |= !item.is_empty();
+							stream.append_raw(item.as_raw(), 1);
+						},
+						Span::Inside => match self.rebuild(&child_prefix) {
+							Some(child) => append_child(&mut stream, child, true, &mut self.nodes),
+							None => { stream.append_empty_data(); },
+						},
+						Span::Edge => match self.walk(ChildRef::decode(&item)?, child_prefix)? {
+							Some(child) => append_child(&mut stream, child, false, &mut self.nodes),
+							None => { stream.append_empty_data(); },
+						},
+					}
+				}
+				stream.append_raw(rlp.at(16)?.as_raw(), 1);
+				Ok(Some(stream.out()))
+			},
+			_ => Err(Error::BadRangeProof("invalid node".into())),
+		}
+	}
+}
+
+/// Build the subtrie of sorted `entries` sharing their first `depth` nibbles.
+/// Nodes referenced by hash are stored in `nodes`, the encoded subtrie root is returned.
+fn build(entries: &[(Vec<u8>, &[u8])], depth: usize, nodes: &mut Vec<Bytes>) -> Option<Bytes> {
+	let (first, last) = match (entries.first(), entries.last()) {
+		(Some(first), Some(last)) => (first, last),
+		_ => return None,
+	};
+
+	if entries.len() == 1 {
+		let mut stream = RlpStream::new_list(2);
+		stream.append(&encode_path(&first.0[depth..], true));
+		stream.append(&first.1.to_vec());
+		return Some(stream.out());
+	}
+
+	let common = first.0[depth..].iter().zip(&last.0[depth..]).take_while(|&(a, b)| a == b).count();
+	if common > 0 {
+		let child = build(entries, depth + common, nodes).expect("entries are not empty; qed");
+		let mut stream = RlpStream::new_list(2);
+		stream.append(&encode_path(&first.0[depth..depth + common], false));
+		append_child(&mut stream, child, true, nodes);
+		return Some(stream.out());
+	}
+
+	let mut stream = RlpStream::new_list(17);
+	let mut start = 0;
+	for nibble in 0..16 {
+		let count = entries[start..].iter().take_while(|e| e.0[depth] == nibble).count();
+		match build(&entries[start..start + count], depth + 1, nodes) {
+			Some(child) => append_child(&mut stream, child, true, nodes),
+			None => { stream.append_empty_data(); },
+		}
+		start += count;
+	}
+	stream.append_empty_data();
+	Some(stream.out())
+}
+
+/// Append a reference to an encoded child node, storing the node if it's referenced by hash
+/// and `store` is set.
+fn append_child(stream: &mut RlpStream, child: Bytes, store: bool, nodes: &mut Vec<Bytes>) {
+	if child.len() < 32 {
+		stream.append_raw(&child, 1);
+	} else {
+		stream.append(&keccak(&child));
+		if store {
+			nodes.push(child);
+		}
+	}
+}
+
+fn nibbles(key: &H256) -> Vec<u8> {
+	key.iter().flat_map(|b| vec![b >> 4, b & 0x0f]).collect()
+}
+
+fn key(nibbles: &[u8]) -> H256 {
+	let bytes: Vec<u8> = nibbles.chunks(2).map(|pair| (pair[0] << 4) | pair[1]).collect();
+	H256::from_slice(&bytes)
+}
+
+/// Hex-prefix encoding of a node path.
+fn encode_path(nibbles: &[u8], is_leaf: bool) -> Bytes {
+	let flag = if is_leaf { 0x20 } else { 0 };
+	let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+	let rest = match nibbles.len() % 2 {
+		1 => {
+			encoded.push(flag | 0x10 | nibbles[0]);
+			&nibbles[1..]
+		},
+		_ => {
+			encoded.push(flag);
+			nibbles
+		},
+	};
+	encoded.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+	encoded
+}
+
+/// Decode a hex-prefix encoded node path, returns the nibbles and whether it's a leaf.
+fn decode_path(encoded: &[u8]) -> Result<(Vec<u8>, bool), Error> {
+	let flag = match encoded.first() {
+		Some(first) if first >> 4 < 4 => first >> 4,
+		_ => return Err(Error::BadRangeProof("invalid node path".into())),
+	};
+	let mut nibbles = Vec::with_capacity(encoded.len() * 2);
+	if flag & 1 == 1 {
+		nibbles.push(encoded[0] & 0x0f);
+	}
+	for b in &encoded[1..] {
+		nibbles.push(b >> 4);
+		nibbles.push(b & 0x0f);
+	}
+	Ok((nibbles, flag & 2 == 2))
+}
@@ -24,22 +24,27 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 use super::{ManifestData, StateRebuilder, Rebuilder, RestorationStatus, SnapshotService, MAX_CHUNK_SIZE};
+use super::block::AbridgedBlock;
 use super::io::{SnapshotReader, LooseReader, SnapshotWriter, LooseWriter};
 
-use blockchain::{BlockChain, BlockChainDB, BlockChainDBHandler};
+use block::Block;
+
+use blockchain::{BlockChain, BlockChainDB, BlockChainDBHandler, Config as BlockChainConfig};
 use client::{Client, ChainInfo, ClientIoMessage};
 use engines::EthEngine;
 use error::{Error, ErrorKind as SnapshotErrorKind};
 use snapshot::{Error as SnapshotError};
 use hash::keccak;
 use ids::BlockId;
+use views::BlockView;
 
 use io::IoChannel;
 
-use ethereum_types::H256;
+use ethereum_types::{H256, U256};
 use parking_lot::{Mutex, RwLock, RwLockReadGuard};
 use bytes::Bytes;
 use journaldb::Algorithm;
+use rlp::{Rlp, RlpStream};
 use snappy;
 
 /// Helper for removing directories in case of error.
@@ -80,6 +85,8 @@ struct Restoration {
 	final_state_root: H256,
 	guard: Guard,
 	db: Arc<BlockChainDB>,
+	from_nodes: bool,
+	nodes_pending: bool,
 }
 
 struct RestorationParams<'a> {
@@ -90,6 +97,7 @@ struct RestorationParams<'a> {
 	genesis: &'a [u8], // genesis block of the chain.
 	guard: Guard, // guard for the restoration directory.
 	engine: &'a EthEngine,
+	from_nodes: bool, // whether the state is fed as trie nodes rather than chunks.
 }
 
 impl Restoration {
@@ -102,7 +110,11 @@ impl Restoration {
 
 		let raw_db = params.db;
 
-		let chain = BlockChain::new(Default::default(), params.genesis, raw_db.clone());
+		let chain_config = BlockChainConfig {
+			eip1559_transition: params.engine.params().eip1559_transition,
+			..Default::default()
+		};
+		let chain = BlockChain::new(chain_config, params.genesis, raw_db.clone());
 		let components = params.engine.snapshot_components()
 			.ok_or_else(|| ::snapshot::Error::SnapshotsUnsupported)?;
 
@@ -121,9 +133,36 @@ impl Restoration {
 			final_state_root: root,
 			guard: params.guard,
 			db: raw_db,
+			from_nodes: params.from_nodes,
+			nodes_pending: params.from_nodes,
 		})
 	}
 
+	// move a restoration from nodes to another block, keeping the nodes written so far.
+	fn retarget(&mut self, manifest: ManifestData, genesis: &[u8], engine: &EthEngine) -> Result<(), Error> {
+		let chain_config = BlockChainConfig {
+			eip1559_transition: engine.params().eip1559_transition,
+			..Default::default()
+		};
+		let chain = BlockChain::new(chain_config, genesis, self.db.clone());
+		let components = engine.snapshot_components()
+			.ok_or_else(|| ::snapshot::Error::SnapshotsUnsupported)?;
+
+		self.secondary = components.rebuilder(chain, self.db.clone(), &manifest)?;
+		self.final_state_root = manifest.state_root;
+		self.manifest = manifest;
+		Ok(())
+	}
+
+	// feeds trie nodes of the state.
+	fn feed_nodes(&mut self, account: Option<H256>, nodes: &[Bytes]) -> Result<(), Error> {
+		if self.nodes_pending {
+			self.state.feed_nodes(account, nodes)?;
+		}
+
+		Ok(())
+	}
+
 	// feeds a state chunk, aborts early if `flag` becomes false.
 	fn feed_state(&mut self, hash: H256, chunk: &[u8], flag: &AtomicBool) -> Result<(), Error> {
 		if self.state_chunks_left.contains(&hash) {
@@ -168,11 +207,16 @@ impl Restoration {
 	}
 
 	// finish up restoration.
-	fn finalize(mut self, engine: &EthEngine) -> Result<(), Error> {
+	fn finalize(mut self, engine: &EthEngine, flag: &AtomicBool) -> Result<(), Error> {
 		use trie::TrieError;
 
 		if !self.is_done() { return Ok(()) }
 
+		// a state restored from nodes is only known to be complete once it's been walked.
+		if self.from_nodes {
+			self.state.check_nodes(self.final_state_root, flag)?;
+		}
+
 		// verify final state root.
 		let root = self.state.state_root();
 		if root != self.final_state_root {
@@ -196,7 +240,7 @@ impl Restoration {
 
 	// is everything done?
 	fn is_done(&self) -> bool {
-		self.block_chunks_left.is_empty() && self.state_chunks_left.is_empty()
+		self.block_chunks_left.is_empty() && self.state_chunks_left.is_empty() && !self.nodes_pending
 	}
 }
 
@@ -413,6 +457,28 @@ impl Service {
 	/// Initialize the restoration synchronously.
 	/// The recover flag indicates whether to recover the restored snapshot.
 	pub fn init_restore(&self, manifest: ManifestData, recover: bool) -> Result<(), Error> {
+		self.init_restoration(manifest, recover, false)
+	}
+
+	/// Initialize a restoration of the state from trie nodes synchronously,
+	/// at the block given by the manifest. The manifest lists no chunks.
+	/// If such a restoration is already underway, it's moved to the new block
+	/// and keeps the nodes fed so far.
+	pub fn init_state_restore(&self, manifest: ManifestData) -> Result<(), Error> {
+		{
+			let mut res = self.restoration.lock();
+			if let Some(ref mut rest) = *res {
+				if rest.nodes_pending {
+					trace!(target: "snapshot", "Moving state restoration to block #{}", manifest.block_number);
+					return rest.retarget(manifest, &self.genesis_block, &*self.engine);
+				}
+			}
+		}
+
+		self.init_restoration(manifest, false, true)
+	}
+
+	fn init_restoration(&self, manifest: ManifestData, recover: bool, from_nodes: bool) -> Result<(), Error> {
 		let mut res = self.restoration.lock();
 
 		let rest_dir = self.restoration_dir();
@@ -468,6 +534,7 @@ impl Service {
 			genesis: &self.genesis_block,
 			guard: Guard::new(rest_db),
 			engine: &*self.engine,
+			from_nodes: from_nodes,
 		};
 
 		let state_chunks = manifest.state_hashes.len();
@@ -478,7 +545,9 @@ impl Service {
 		self.restoring_snapshot.store(true, Ordering::SeqCst);
 
 		// Import previous chunks, continue if it fails
-		self.import_prev_chunks(&mut res, manifest).ok();
+		if !from_nodes {
+			self.import_prev_chunks(&mut res, manifest).ok();
+		}
 
 		*self.status.lock() = RestorationStatus::Ongoing {
 			state_chunks: state_chunks as u32,
@@ -556,7 +625,7 @@ impl Service {
 
 		// destroy the restoration before replacing databases and snapshot.
 		rest.take()
-			.map(|r| r.finalize(&*self.engine))
+			.map(|r| r.finalize(&*self.engine, &self.restoring_snapshot))
 			.unwrap_or(Ok(()))?;
 
 		self.replace_client_db()?;
@@ -658,6 +727,49 @@ impl Service {
 	pub fn feed_block_chunk(&self, hash: H256, chunk: &[u8]) {
 		self.feed_chunk(hash, chunk, false);
 	}
+
+	fn fail_restoration(&self, e: Error) {
+		warn!("Encountered error during state restoration: {}", e);
+		*self.restoration.lock() = None;
+		*self.status.lock() = RestorationStatus::Failed;
+		let _ = fs::remove_dir_all(self.restoration_dir());
+	}
+
+	/// Feed trie nodes of the state to be written synchronously.
+	/// no-op if no restoration from nodes is underway.
+	pub fn feed_state_nodes(&self, account: Option<H256>, nodes: &[Bytes]) {
+		let res = match *self.restoration.lock() {
+			Some(ref mut rest) => rest.feed_nodes(account, nodes),
+			None => return,
+		};
+
+		if let Err(e) = res {
+			self.fail_restoration(e);
+		}
+	}
+
+	// turn the pivot block into a block chunk for the restoration's secondary rebuilder.
+	fn pivot_chunk(&self, block: &[u8], receipts: &[u8], parent_td: U256) -> Result<Bytes, Error> {
+		// check the encoding before viewing it.
+		Block::decode_rlp(&Rlp::new(block), self.engine.params().eip1559_transition)?;
+		Rlp::new(receipts).item_count()?;
+
+		let block = view!(BlockView, block);
+		let header = block.header_view();
+		let abridged = AbridgedBlock::from_block_view(&block);
+
+		let mut pair = RlpStream::new_list(2);
+		pair.append_raw(&abridged.into_inner(), 1);
+		pair.append_raw(receipts, 1);
+
+		let mut stream = RlpStream::new_list(4);
+		stream.append(&header.number().saturating_sub(1))
+			.append(&header.parent_hash())
+			.append(&parent_td)
+			.append_raw(&pair.out(), 1);
+
+		Ok(snappy::compress(&stream.out()))
+	}
 }
 
 impl SnapshotService for Service {
@@ -739,6 +851,41 @@ impl SnapshotService for Service {
 		}
 	}
 
+	fn begin_state_restore(&self, manifest: ManifestData) {
+		if let Err(e) = self.io_channel.lock().send(ClientIoMessage::BeginStateRestoration(manifest)) {
+			trace!("Error sending snapshot service message: {:?}", e);
+		}
+	}
+
+	fn restore_state_nodes(&self, account: Option<H256>, nodes: Vec<Bytes>) {
+		self.feed_state_nodes(account, &nodes);
+	}
+
+	fn restored_state_node(&self, account: Option<H256>, hash: &H256) -> Option<Bytes> {
+		match *self.restoration.lock() {
+			Some(ref rest) if rest.nodes_pending => rest.state.node(account, hash),
+			_ => None,
+		}
+	}
+
+	fn finish_state_restore(&self, block: Bytes, receipts: Bytes, parent_total_difficulty: U256) {
+		let chunk = match self.pivot_chunk(&block, &receipts, parent_total_difficulty) {
+			Ok(chunk) => chunk,
+			Err(e) => return self.fail_restoration(e),
+		};
+		let hash = keccak(&chunk);
+
+		match *self.restoration.lock() {
+			Some(ref mut rest) if rest.nodes_pending => {
+				rest.nodes_pending = false;
+				rest.block_chunks_left.insert(hash);
+			},
+			_ => return,
+		}
+
+		self.restore_block_chunk(hash, chunk);
+	}
+
 	fn shutdown(&self) {
 		self.abort_restore();
 	}
@@ -838,6 +985,7 @@ mod tests {
 			genesis: &gb,
 			guard: Guard::benign(),
 			engine: &*spec.engine.clone(),
+			from_nodes: false,
 		};
 
 		let mut restoration = Restoration::new(params).unwrap();
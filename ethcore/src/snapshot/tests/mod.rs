@@ -18,6 +18,7 @@
 
 mod proof_of_work;
 mod proof_of_authority;
+mod range;
 mod state;
 mod service;
 
@@ -0,0 +1,154 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Trie range proof tests.
+
+use ethereum_types::H256;
+use ethtrie::TrieDBMut;
+use hash::{keccak, KECCAK_NULL_RLP};
+use hashdb::HashDB;
+use keccak_hasher::KeccakHasher;
+use kvdb::DBValue;
+use memorydb::MemoryDB;
+use trie::TrieMut;
+
+use snapshot::range::{node_refs, read_range, verify_range, TrieRange};
+
+fn trie(count: u64) -> (MemoryDB<KeccakHasher, DBValue>, H256, Vec<(H256, Vec<u8>)>) {
+	let mut db = MemoryDB::new();
+	let mut root = H256::new();
+	let mut items: Vec<_> = (0..count)
+		.map(|i| (keccak(H256::from(i)), keccak(H256::from(i + count)).to_vec()))
+		.collect();
+	{
+		let mut trie = TrieDBMut::new(&mut db, &mut root);
+		for &(ref key, ref value) in &items {
+			trie.insert(key, value).unwrap();
+		}
+	}
+	items.sort();
+	(db, root, items)
+}
+
+fn next_key(key: &H256) -> H256 {
+	let mut next = *key;
+	for byte in next.iter_mut().rev() {
+		*byte = byte.wrapping_add(1);
+		if *byte != 0 {
+			break;
+		}
+	}
+	next
+}
+
+#[test]
+fn ranges_cover_the_trie() {
+	let (db, root, items) = trie(500);
+	let mut origin = H256::zero();
+	let mut downloaded = Vec::new();
+	loop {
+		let range = read_range(&db, &root, &origin, 2000).unwrap();
+		let verified = verify_range(&root, &origin, &range).unwrap();
+		// every node rebuilt from the range is a node of the trie
+		for node in &verified.nodes {
+			assert!(db.contains(&keccak(node)));
+		}
+		downloaded.extend(range.items.iter().cloned());
+		match (verified.more, range.items.last()) {
+			(true, Some(&(ref last, _))) => origin = next_key(last),
+			_ => break,
+		}
+	}
+	assert_eq!(downloaded, items);
+}
+
+#[test]
+fn range_past_the_end() {
+	let (db, root, items) = trie(50);
+	let origin = next_key(&items.last().unwrap().0);
+	let range = read_range(&db, &root, &origin, 2000).unwrap();
+	assert!(range.items.is_empty());
+	assert!(!verify_range(&root, &origin, &range).unwrap().more);
+
+	// an empty range can't hide existing leaves
+	let origin = items[40].0;
+	let mut range = read_range(&db, &root, &origin, 2000).unwrap();
+	range.items.clear();
+	assert!(verify_range(&root, &origin, &range).is_err());
+}
+
+#[test]
+fn whole_trie_without_proof() {
+	let (db, root, items) = trie(50);
+	let range = TrieRange { items: items.clone(), proof: Vec::new() };
+	let verified = verify_range(&root, &H256::zero(), &range).unwrap();
+	assert!(!verified.more);
+	for node in &verified.nodes {
+		assert!(db.contains(&keccak(node)));
+	}
+
+	let partial = TrieRange { items: items[..49].to_vec(), proof: Vec::new() };
+	assert!(verify_range(&root, &H256::zero(), &partial).is_err());
+	assert!(verify_range(&KECCAK_NULL_RLP, &H256::zero(), &TrieRange::default()).is_ok());
+}
+
+#[test]
+fn rejects_tampered_ranges() {
+	let (db, root, _) = trie(200);
+	let mut origin = H256::zero();
+	origin[0] = 0x40;
+	let range = read_range(&db, &root, &origin, 1000).unwrap();
+	assert!(range.items.len() > 3);
+	assert!(verify_range(&root, &origin, &range).is_ok());
+
+	let mut changed = range.clone();
+	changed.items[1].1[0] ^= 1;
+	assert!(verify_range(&root, &origin, &changed).is_err());
+
+	let mut missing = range.clone();
+	missing.items.remove(1);
+	assert!(verify_range(&root, &origin, &missing).is_err());
+
+	let mut extra = range.clone();
+	let key = next_key(&extra.items[1].0);
+	extra.items.insert(2, (key, vec![1, 2, 3]));
+	assert!(verify_range(&root, &origin, &extra).is_err());
+
+	let mut unordered = range.clone();
+	unordered.items.swap(0, 1);
+	assert!(verify_range(&root, &origin, &unordered).is_err());
+
+	let mut unproven = range.clone();
+	unproven.proof.clear();
+	assert!(verify_range(&root, &origin, &unproven).is_err());
+
+	assert_eq!(::rlp::decode::<TrieRange>(&::rlp::encode(&range)).unwrap(), range);
+}
+
+#[test]
+fn walk_node_refs() {
+	let (db, root, items) = trie(300);
+	let mut leaves = Vec::new();
+	let mut stack = vec![(Vec::new(), root)];
+	while let Some((prefix, hash)) = stack.pop() {
+		let refs = node_refs(&prefix, &db.get(&hash).unwrap()).unwrap();
+		leaves.extend(refs.leaves);
+		stack.extend(refs.children);
+	}
+	leaves.sort();
+	assert_eq!(leaves, items);
+	assert_eq!(node_refs(&[], &::rlp::NULL_RLP).unwrap(), Default::default());
+}
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::{ManifestData, RestorationStatus};
-use ethereum_types::H256;
+use ethereum_types::{H256, U256};
 use bytes::Bytes;
 
 /// The interface for a snapshot network service.
@@ -55,6 +55,24 @@ pub trait SnapshotService : Sync + Send {
 	/// no-op if currently restoring.
 	fn restore_block_chunk(&self, hash: H256, chunk: Bytes);
 
+	/// Begin restoring the state from trie nodes at the block given by the manifest,
+	/// which lists no chunks. If such a restoration is in progress, it's moved to the
+	/// new block and keeps the nodes restored so far.
+	fn begin_state_restore(&self, manifest: ManifestData);
+
+	/// Feed verified trie nodes of the state to be written synchronously.
+	/// `account` is the address hash for storage nodes and code, `None` for account nodes.
+	/// no-op if not currently restoring from nodes.
+	fn restore_state_nodes(&self, account: Option<H256>, nodes: Vec<Bytes>);
+
+	/// Get a node restored so far by a restoration from nodes.
+	fn restored_state_node(&self, account: Option<H256>, hash: &H256) -> Option<Bytes>;
+
+	/// Finish a restoration from nodes with the RLP of the block in the manifest, its receipts
+	/// and the total difficulty of its parent. The state is checked and the restoration
+	/// finalized asynchronously.
+	fn finish_state_restore(&self, block: Bytes, receipts: Bytes, parent_total_difficulty: U256);
+
 	/// Shutdown the Snapshot Service by aborting any ongoing restore
 	fn shutdown(&self);
 }
@@ -122,6 +122,28 @@ pub struct CommonParams {
 	pub eip1283_transition: BlockNumber,
 	/// Number of first block where EIP-1014 rules begin.
 	pub eip1014_transition: BlockNumber,
+	/// Number of first block where EIP-1344 rules begin: CHAINID opcode.
+	pub eip1344_transition: BlockNumber,
+	/// Number of first block where EIP-1884 rules begin: repricing and SELFBALANCE opcode.
+	pub eip1884_transition: BlockNumber,
+	/// Number of first block where EIP-2028 rules begin: cheaper calldata.
+	pub eip2028_transition: BlockNumber,
+	/// Number of first block where EIP-2200 rules begin: net gas metering for SSTORE.
+	pub eip2200_transition: BlockNumber,
+	/// Number of first block where EIP-2929 rules begin: cold/warm state access pricing.
+	pub eip2929_transition: BlockNumber,
+	/// Number of first block where EIP-2930 rules begin: typed access list transactions.
+	pub eip2930_transition: BlockNumber,
+	/// Number of first block where EIP-1559 rules begin: base fee and dynamic fee transactions.
+	pub eip1559_transition: BlockNumber,
+	/// Bound divisor of the base fee change between blocks.
+	pub eip1559_base_fee_max_change_denominator: U256,
+	/// Ratio of the block gas limit to the block gas target.
+	pub eip1559_elasticity_multiplier: U256,
+	/// Base fee of the first block after the EIP-1559 transition.
+	pub eip1559_base_fee_initial_value: U256,
+	/// Number of first block where EIP-3198 rules begin: BASEFEE opcode.
+	pub eip3198_transition: BlockNumber,
 	/// Number of first block where dust cleanup rules (EIP-168 and EIP169) begin.
 	pub dust_protection_transition: BlockNumber,
 	/// Nonce cap increase per block. Nonce cap is only checked if dust protection is enabled.
@@ -188,7 +210,27 @@ impl CommonParams {
 		schedule.have_return_data = block_number >= self.eip211_transition;
 		schedule.have_bitwise_shifting = block_number >= self.eip145_transition;
 		schedule.have_extcodehash = block_number >= self.eip1052_transition;
-		schedule.eip1283 = block_number >= self.eip1283_transition;
+		schedule.have_chain_id = block_number >= self.eip1344_transition;
+		schedule.have_selfbalance = block_number >= self.eip1884_transition;
+		schedule.eip1283 = block_number >= self.eip1283_transition || block_number >= self.eip2200_transition;
+		if block_number >= self.eip1884_transition {
+			schedule.sload_gas = 800;
+			schedule.balance_gas = 700;
+			schedule.extcodehash_gas = 700;
+		}
+		if block_number >= self.eip2028_transition {
+			schedule.tx_data_non_zero_gas = 16;
+		}
+		if block_number >= self.eip2200_transition {
+			schedule.eip1706 = true;
+			schedule.sstore_dirty_gas = Some(800);
+		}
+		if block_number >= self.eip2929_transition {
+			schedule.enable_eip2929();
+		}
+		schedule.eip2930 = block_number >= self.eip2930_transition;
+		schedule.eip1559 = block_number >= self.eip1559_transition;
+		schedule.have_basefee = block_number >= self.eip3198_transition;
 		if block_number >= self.eip210_transition {
 			schedule.blockhash_gas = 800;
 		}
@@ -226,6 +268,47 @@ impl CommonParams {
 
 		None
 	}
+
+	/// Block numbers of all rule changes configured in the parameters, including the fork block.
+	/// Unsorted, may contain duplicates as well as `0` and `BlockNumber::max_value()` for
+	/// transitions active from genesis or disabled.
+	pub fn transitions(&self) -> Vec<BlockNumber> {
+		// EIP-98 is left out: it was never scheduled and specs park it on an arbitrary far block.
+		let mut transitions = vec![
+			self.eip150_transition,
+			self.eip160_transition,
+			self.eip161abc_transition,
+			self.eip161d_transition,
+			self.eip658_transition,
+			self.eip155_transition,
+			self.validate_receipts_transition,
+			self.validate_chain_id_transition,
+			self.eip140_transition,
+			self.eip210_transition,
+			self.eip211_transition,
+			self.eip214_transition,
+			self.eip145_transition,
+			self.eip1052_transition,
+			self.eip1283_transition,
+			self.eip1014_transition,
+			self.eip1344_transition,
+			self.eip1884_transition,
+			self.eip2028_transition,
+			self.eip2200_transition,
+			self.eip2929_transition,
+			self.eip2930_transition,
+			self.eip1559_transition,
+			self.eip3198_transition,
+			self.dust_protection_transition,
+			self.wasm_activation_transition,
+			self.kip4_transition,
+			self.kip6_transition,
+			self.max_code_size_transition,
+			self.transaction_permission_contract_transition,
+		];
+		transitions.extend(self.fork_block.map(|(number, _)| number));
+		transitions
+	}
 }
 
 impl From<ethjson::spec::Params> for CommonParams {
@@ -300,6 +383,43 @@ impl From<ethjson::spec::Params> for CommonParams {
 				BlockNumber::max_value,
 				Into::into,
 			),
+			eip1344_transition: p.eip1344_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip1884_transition: p.eip1884_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip2028_transition: p.eip2028_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip2200_transition: p.eip2200_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip2929_transition: p.eip2929_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip2930_transition: p.eip2930_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip1559_transition: p.eip1559_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip1559_base_fee_max_change_denominator:
+				p.eip1559_base_fee_max_change_denominator.map_or(8.into(), Into::into),
+			eip1559_elasticity_multiplier: p.eip1559_elasticity_multiplier.map_or(2.into(), Into::into),
+			eip1559_base_fee_initial_value:
+				p.eip1559_base_fee_initial_value.map_or(1_000_000_000.into(), Into::into),
+			eip3198_transition: p.eip3198_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
 			dust_protection_transition: p.dust_protection_transition.map_or_else(
 				BlockNumber::max_value,
 				Into::into,
@@ -648,6 +768,7 @@ impl Spec {
 				last_hashes: Default::default(),
 				gas_used: U256::zero(),
 				gas_limit: U256::max_value(),
+				base_fee: None,
 			};
 
 			let from = Address::default();
@@ -729,6 +850,17 @@ impl Spec {
 		self.params().fork_block
 	}
 
+	/// Get the ordered block numbers of the chain's hard forks, used to compute the EIP-2124
+	/// fork identifier. Forks active from genesis and disabled ones are left out.
+	pub fn fork_transitions(&self) -> Vec<BlockNumber> {
+		let mut transitions = self.params().transitions();
+		transitions.extend(self.engine.transitions());
+		transitions.retain(|&n| n != 0 && n != BlockNumber::max_value());
+		transitions.sort();
+		transitions.dedup();
+		transitions
+	}
+
 	/// Get the header of the genesis block.
 	pub fn genesis_header(&self) -> Header {
 		let mut header: Header = Default::default();
@@ -865,6 +997,7 @@ impl Spec {
 				gas_limit: U256::max_value(),
 				last_hashes: Arc::new(Vec::new()),
 				gas_used: 0.into(),
+				base_fee: genesis.base_fee(),
 			};
 
 			let from = Address::default();
@@ -1039,4 +1172,53 @@ mod tests {
 		assert_eq!(state.storage_at(&address, &H256::zero()).unwrap(), expected);
 		assert_eq!(state.balance(&address).unwrap(), 1.into());
 	}
+
+	#[test]
+	fn istanbul_transitions() {
+		let mut params = ::ethereum::new_constantinople_test().params().clone();
+		params.eip1344_transition = 10;
+		params.eip1884_transition = 10;
+		params.eip2028_transition = 10;
+		params.eip2200_transition = 10;
+
+		let before = params.schedule(9);
+		assert!(!before.have_chain_id);
+		assert!(!before.have_selfbalance);
+		assert!(!before.eip1706);
+		assert_eq!(before.sload_gas, 200);
+		assert_eq!(before.tx_data_non_zero_gas, 68);
+		assert_eq!(before.sstore_dirty_gas, None);
+
+		let after = params.schedule(10);
+		assert!(after.have_chain_id);
+		assert!(after.have_selfbalance);
+		assert!(after.eip1283);
+		assert!(after.eip1706);
+		assert_eq!(after.sload_gas, 800);
+		assert_eq!(after.balance_gas, 700);
+		assert_eq!(after.extcodehash_gas, 700);
+		assert_eq!(after.tx_data_non_zero_gas, 16);
+		assert_eq!(after.sstore_dirty_gas, Some(800));
+	}
+
+	#[test]
+	fn berlin_transitions() {
+		let mut params = ::ethereum::new_istanbul_test().params().clone();
+		params.eip2929_transition = 10;
+		params.eip2930_transition = 10;
+
+		let before = params.schedule(9);
+		assert!(!before.eip2929);
+		assert!(!before.eip2930);
+		assert_eq!(before.sload_gas, 800);
+		assert_eq!(before.sstore_dirty_gas, Some(800));
+
+		let after = params.schedule(10);
+		assert!(after.eip2929);
+		assert!(after.eip2930);
+		assert_eq!(after.sload_gas, 100);
+		assert_eq!(after.call_gas, 100);
+		assert_eq!(after.sstore_reset_gas, 2900);
+		assert_eq!(after.sstore_dirty_gas, Some(100));
+	}
 }
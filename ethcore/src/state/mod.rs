@@ -38,9 +38,11 @@ use pod_state::{self, PodState};
 use types::basic_account::BasicAccount;
 use executed::{Executed, ExecutionError};
 use types::state_diff::StateDiff;
+use types::call_analytics::CallAnalytics;
 use transaction::SignedTransaction;
 use state_db::StateDB;
 use factory::VmFactory;
+use snapshot::range::{read_range, TrieRange};
 
 use ethereum_types::{H256, U256, Address};
 use hashdb::{HashDB, AsHashDB};
@@ -200,24 +202,105 @@ pub fn check_proof(
 	machine: &Machine,
 	env_info: &EnvInfo,
 ) -> ProvedExecution {
+	check_proof_with_analytics(proof, root, transaction, machine, env_info, CallAnalytics::default())
+}
+
+/// Check the given proof of execution, tracing the transaction as requested by `analytics`.
+pub fn check_proof_with_analytics(
+	proof: &[DBValue],
+	root: H256,
+	transaction: &SignedTransaction,
+	machine: &Machine,
+	env_info: &EnvInfo,
+	analytics: CallAnalytics,
+) -> ProvedExecution {
+	match proof_check_state(proof, root, machine, env_info) {
+		Some(mut state) => execute_proved(&mut state, env_info, machine, transaction, analytics),
+		None => ProvedExecution::BadProof,
+	}
+}
+
+/// Check the given proof of executing several transactions in sequence, each one on the state
+/// left by the previous ones, like the transactions of a block.
+/// Stops after the first transaction which doesn't complete.
+pub fn check_proof_many(
+	proof: &[DBValue],
+	root: H256,
+	transactions: &[(SignedTransaction, CallAnalytics)],
+	machine: &Machine,
+	env_info: &EnvInfo,
+) -> Vec<ProvedExecution> {
+	let mut state = match proof_check_state(proof, root, machine, env_info) {
+		Some(state) => state,
+		None => return vec![ProvedExecution::BadProof],
+	};
+
+	let mut env_info = env_info.clone();
+	let mut results = Vec::with_capacity(transactions.len());
+	for &(ref transaction, analytics) in transactions {
+		match execute_proved(&mut state, &env_info, machine, transaction, analytics) {
+			ProvedExecution::Complete(executed) => {
+				env_info.gas_used = env_info.gas_used + executed.gas_used;
+				results.push(ProvedExecution::Complete(executed));
+			},
+			other => {
+				results.push(other);
+				break;
+			},
+		}
+	}
+	results
+}
+
+fn proof_check_state(proof: &[DBValue], root: H256, machine: &Machine, env_info: &EnvInfo) -> Option<State<self::backend::ProofCheck>> {
 	let backend = self::backend::ProofCheck::new(proof);
 	let mut factories = Factories::default();
 	factories.accountdb = ::account_db::Factory::Plain;
 
-	let res = State::from_existing(
+	State::from_existing(
 		backend,
 		root,
 		machine.account_start_nonce(env_info.number),
 		factories
-	);
+	).ok()
+}
 
-	let mut state = match res {
-		Ok(state) => state,
-		Err(_) => return ProvedExecution::BadProof,
+fn execute_proved(
+	state: &mut State<self::backend::ProofCheck>,
+	env_info: &EnvInfo,
+	machine: &Machine,
+	transaction: &SignedTransaction,
+	analytics: CallAnalytics,
+) -> ProvedExecution {
+	fn execute<T, V>(
+		state: &mut State<self::backend::ProofCheck>,
+		env_info: &EnvInfo,
+		machine: &Machine,
+		state_diff: bool,
+		transaction: &SignedTransaction,
+		options: TransactOptions<T, V>,
+	) -> Result<Executed<T::Output, V::Output>, ExecutionError> where
+		T: trace::Tracer,
+		V: trace::VMTracer,
+	{
+		let original = if state_diff { Some(state.clone()) } else { None };
+		let mut executed = state.execute(env_info, machine, transaction, options.save_output_from_contract(), true)?;
+
+		if let Some(original) = original {
+			executed.state_diff = Some(state.diff_from(original).map_err(ExecutionError::from)?);
+		}
+		Ok(executed)
+	}
+
+	let state_diff = analytics.state_diffing;
+	let res = match (analytics.transaction_tracing, analytics.vm_tracing) {
+		(true, true) => execute(state, env_info, machine, state_diff, transaction, TransactOptions::with_tracing_and_vm_tracing()),
+		(true, false) => execute(state, env_info, machine, state_diff, transaction, TransactOptions::with_tracing()),
+		(false, true) => execute(state, env_info, machine, state_diff, transaction, TransactOptions::with_vm_tracing()),
+		(false, false) => execute(state, env_info, machine, state_diff, transaction, TransactOptions::with_no_tracing()),
 	};
 
-	let options = TransactOptions::with_no_tracing().save_output_from_contract();
-	match state.execute(env_info, machine, transaction, options, true) {
+	match res {
 		Ok(executed) => ProvedExecution::Complete(executed),
 		Err(ExecutionError::Internal(_)) => ProvedExecution::BadProof,
 		Err(e) => ProvedExecution::Failed(e),
@@ -826,7 +909,8 @@ impl<B: Backend> State<B> {
 		};
 
 		let output = e.output;
-		let receipt = Receipt::new(outcome, e.cumulative_gas_used, e.logs);
+		let mut receipt = Receipt::new(outcome, e.cumulative_gas_used, e.logs);
+		receipt.transaction_type = t.transaction_type();
 		trace!(target: "state", "Transaction receipt: {:?}", receipt);
 
 		Ok(ApplyOutcome {
@@ -1203,6 +1287,37 @@ impl<B: Backend> State<B> {
 		let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), account_key);
 		acc.prove_storage(account_db.as_hashdb(), storage_key)
 	}
+
+	/// Read a range of the account trie from `origin` on, with a proof of its ends.
+	/// See `snapshot::range` for details.
+	pub fn account_range(&self, origin: &H256, max_bytes: usize) -> TrieResult<TrieRange> {
+		read_range(self.db.as_hashdb(), &self.root, origin, max_bytes)
+	}
+
+	/// Read a range of an account's storage trie from `origin` on, with a proof of its ends.
+	/// Returns an empty range if the account doesn't exist.
+	/// `account_key` == keccak(address)
+	pub fn storage_range(&self, account_key: H256, origin: &H256, max_bytes: usize) -> TrieResult<TrieRange> {
+		let trie = TrieDB::new(self.db.as_hashdb(), &self.root)?;
+		let from_rlp = |b: &[u8]| ::rlp::decode::<BasicAccount>(b).expect("decoding db value failed");
+		let storage_root = match trie.get_with(&account_key, from_rlp)? {
+			Some(acc) => acc.storage_root,
+			None => return Ok(TrieRange::default()),
+		};
+
+		let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), account_key);
+		read_range(account_db.as_hashdb(), &storage_root, origin, max_bytes)
+	}
+
+	/// Get a node of the account trie, or a node of the storage trie or the code of the
+	/// account with the given address hash.
+	pub fn state_node(&self, account_key: Option<H256>, hash: &H256) -> Option<Bytes> {
+		let node = match account_key {
+			Some(account_key) => self.factories.accountdb.readonly(self.db.as_hashdb(), account_key).get(hash),
+			None => self.db.as_hashdb().get(hash),
+		};
+		node.map(|node| node.to_vec())
+	}
 }
 
 impl<B: Backend> fmt::Debug for State<B> {
@@ -1213,22 +1328,38 @@ impl<B: Backend> fmt::Debug for State<B> {
 
 // TODO: cloning for `State` shouldn't be possible in general; Remove this and use
 // checkpoints where possible.
-impl Clone for State<StateDB> {
-	fn clone(&self) -> State<StateDB> {
-		let cache = {
-			let mut cache: HashMap<Address, AccountEntry> = HashMap::new();
-			for (key, val) in self.cache.borrow().iter() {
-				if let Some(entry) = val.clone_if_dirty() {
-					cache.insert(key.clone(), entry);
-				}
+impl<B: Backend> State<B> {
+	// Copy of the dirty part of the account cache; clean entries are reloaded from the backend.
+	fn dirty_cache(&self) -> HashMap<Address, AccountEntry> {
+		let mut cache: HashMap<Address, AccountEntry> = HashMap::new();
+		for (key, val) in self.cache.borrow().iter() {
+			if let Some(entry) = val.clone_if_dirty() {
+				cache.insert(key.clone(), entry);
 			}
-			cache
-		};
+		}
+		cache
+	}
+}
 
+impl Clone for State<StateDB> {
+	fn clone(&self) -> State<StateDB> {
 		State {
 			db: self.db.boxed_clone(),
 			root: self.root.clone(),
-			cache: RefCell::new(cache),
+			cache: RefCell::new(self.dirty_cache()),
+			checkpoints: RefCell::new(Vec::new()),
+			account_start_nonce: self.account_start_nonce.clone(),
+			factories: self.factories.clone(),
+		}
+	}
+}
+
+impl Clone for State<self::backend::ProofCheck> {
+	fn clone(&self) -> State<self::backend::ProofCheck> {
+		State {
+			db: self.db.clone(),
+			root: self.root.clone(),
+			cache: RefCell::new(self.dirty_cache()),
 			checkpoints: RefCell::new(Vec::new()),
 			account_start_nonce: self.account_start_nonce.clone(),
 			factories: self.factories.clone(),
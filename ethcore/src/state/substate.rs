@@ -19,6 +19,7 @@ use std::collections::HashSet;
 use ethereum_types::{U256, Address};
 use log_entry::LogEntry;
 use evm::{Schedule, CleanDustMode};
+use vm::AccessList;
 use super::CleanupMode;
 
 /// State changes which should be applied in finalize,
@@ -39,6 +40,9 @@ pub struct Substate {
 
 	/// Created contracts.
 	pub contracts_created: Vec<Address>,
+
+	/// Accounts and storage slots accessed so far in the transaction (EIP-2929).
+	pub access_list: AccessList,
 }
 
 impl Substate {
@@ -47,13 +51,25 @@ impl Substate {
 		Substate::default()
 	}
 
+	/// Creates new substate for a nested call, inheriting the accessed accounts and storage
+	/// slots of the parent.
+	pub fn with_access_list(access_list: AccessList) -> Self {
+		Substate {
+			access_list: access_list,
+			..Substate::default()
+		}
+	}
+
 	/// Merge secondary substate `s` into self, accruing each element correspondingly.
+	///
+	/// The access list of `s` is expected to be a superset of `self`'s and replaces it.
 	pub fn accrue(&mut self, s: Substate) {
 		self.suicides.extend(s.suicides);
 		self.touched.extend(s.touched);
 		self.logs.extend(s.logs);
 		self.sstore_clears_refund = self.sstore_clears_refund + s.sstore_clears_refund;
 		self.contracts_created.extend(s.contracts_created);
+		self.access_list = s.access_list;
 	}
 
 	/// Get the cleanup mode object from this.
@@ -97,7 +97,7 @@ fn imports_good_block() {
 		IoChannel::disconnected(),
 	).unwrap();
 	let good_block = get_good_dummy_block();
-	if client.import_block(Unverified::from_rlp(good_block).unwrap()).is_err() {
+	if client.import_block(Unverified::from_rlp(good_block, spec.params().eip1559_transition).unwrap()).is_err() {
 		panic!("error importing block being good by definition");
 	}
 	client.flush_queue();
@@ -324,6 +324,29 @@ fn does_not_propagate_delayed_transactions() {
 	assert_eq!(2, client.miner().ready_transactions(&*client, 10, PendingOrdering::Priority).len());
 }
 
+#[test]
+fn account_storage_proof() {
+	use ::client::ProvingBlockChainClient;
+
+	let client = generate_dummy_client(0);
+	let address = Address::random();
+	let test_spec = Spec::new_test();
+	let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+	b.block_mut().state_mut().add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
+	b.block_mut().state_mut().set_storage(&address, 1.into(), 7.into()).unwrap();
+	b.block_mut().state_mut().commit().unwrap();
+	let b = b.close_and_lock().unwrap().seal(&*test_spec.engine, vec![]).unwrap();
+	client.import_sealed_block(b).unwrap();
+
+	let (proof, account, storage) = client.prove_account_storage(&address, &[1.into(), 2.into()], BlockId::Latest).unwrap();
+	let root = *client.best_block_header().state_root();
+	assert_eq!(keccak(&proof[0]), root);
+	assert_eq!(account.balance, 5.into());
+	assert_eq!(keccak(&storage[0].0[0]), account.storage_root);
+	assert_eq!(storage[0].1, 7.into());
+	assert_eq!(storage[1].1, 0.into());
+}
+
 #[test]
 fn transaction_proof() {
 	use ::client::ProvingBlockChainClient;
@@ -365,3 +388,54 @@ fn transaction_proof() {
 	assert_eq!(state.balance(&Address::default()).unwrap(), 5.into());
 	assert_eq!(state.balance(&address).unwrap(), 95.into());
 }
+
+#[test]
+fn transaction_proof_many() {
+	use ::client::ProvingBlockChainClient;
+	use types::call_analytics::CallAnalytics;
+
+	let client = generate_dummy_client(0);
+	let address = Address::random();
+	let recipient = Address::random();
+	let test_spec = Spec::new_test();
+	for _ in 0..20 {
+		let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+		b.block_mut().state_mut().add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
+		b.block_mut().state_mut().commit().unwrap();
+		let b = b.close_and_lock().unwrap().seal(&*test_spec.engine, vec![]).unwrap();
+		client.import_sealed_block(b).unwrap();
+	}
+
+	let transfer = |nonce: u64, to: Address, value: u64| Transaction {
+		nonce: nonce.into(),
+		gas_price: 0.into(),
+		gas: 21000.into(),
+		action: Action::Call(to),
+		value: value.into(),
+		data: Vec::new(),
+	}.fake_sign(address);
+
+	let first = transfer(0, Address::default(), 5);
+	let second = transfer(1, recipient, 10);
+
+	// every transaction is proved on its own against the same state, as light servers do.
+	let mut proof = client.prove_transaction(first.clone(), BlockId::Latest).unwrap().1;
+	proof.extend(client.prove_transaction(second.clone(), BlockId::Latest).unwrap().1);
+
+	let root = *client.best_block_header().state_root();
+	let machine = test_spec.engine.machine();
+	let env_info = client.latest_env_info();
+	let analytics = CallAnalytics { transaction_tracing: true, vm_tracing: false, state_diffing: true };
+
+	let mut results = state::check_proof_many(&proof, root, &[(first, Default::default()), (second, analytics)], &machine, &env_info);
+	assert_eq!(results.len(), 2);
+	let executed = match results.pop().unwrap() {
+		state::ProvedExecution::Complete(executed) => executed,
+		other => panic!("unexpected proof check result: {:?}", other),
+	};
+
+	assert_eq!(executed.trace.len(), 1);
+	let diff = executed.state_diff.unwrap();
+	assert_eq!(diff.raw[&address].balance, ::types::account_diff::Diff::Changed(95.into(), 85.into()));
+	assert_eq!(diff.raw[&recipient].balance, ::types::account_diff::Diff::Born(10.into()));
+}
@@ -15,21 +15,35 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Trace database.
-use std::collections::HashMap;
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
 use blockchain::{BlockChainDB};
+use byteorder::{ByteOrder, BigEndian};
 use heapsize::HeapSizeOf;
-use ethereum_types::{H256, H264};
+use ethereum_types::{H256, H264, Address};
 use kvdb::{DBTransaction};
 use parking_lot::RwLock;
+use rlp;
 use header::BlockNumber;
-use trace::{LocalizedTrace, Config, Filter, Database as TraceDatabase, ImportRequest, DatabaseExtras};
+use trace::{LocalizedTrace, Config, Filter, AddressesFilter, Database as TraceDatabase, ImportRequest, DatabaseExtras};
 use db::{self, Key, Writable, Readable, CacheUpdatePolicy};
 use super::flat::{FlatTrace, FlatBlockTraces, FlatTransactionTraces};
+use super::trace::{Action, Res};
 use cache_manager::CacheManager;
 
 const TRACE_DB_VER: &'static [u8] = b"1.0";
 
+/// Prefix of address index entries: `address ++ role ++ block number ++ transaction number`
+/// mapping to the positions of the matching traces within the transaction.
+const INDEX_ENTRY_PREFIX: u8 = 0;
+/// Prefix of per-block lists of address index entries, keyed by block number.
+const INDEX_BLOCK_PREFIX: u8 = 1;
+/// Number of the first block covered by the address index.
+const INDEX_START_KEY: &'static [u8] = &[2];
+
+const LOW_LEVEL_DB_PROOF: &'static str = "Low level database error. Some issue with disk?";
+
 #[derive(Debug, Copy, Clone)]
 enum TraceDBIndex {
 	/// Block traces index.
@@ -47,6 +61,62 @@ impl Key<FlatBlockTraces> for H256 {
 	}
 }
 
+/// Side of a trace an address is indexed by.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum AddressRole {
+	From = 0,
+	To = 1,
+}
+
+fn index_entry_prefix(address: &Address, role: AddressRole) -> Vec<u8> {
+	let mut key = Vec::with_capacity(34);
+	key.push(INDEX_ENTRY_PREFIX);
+	key.extend_from_slice(address);
+	key.push(role as u8);
+	key
+}
+
+fn index_entry_key(address: &Address, role: AddressRole, block_number: BlockNumber, tx_number: usize) -> Vec<u8> {
+	let mut key = index_entry_prefix(address, role);
+	let mut position = [0u8; 12];
+	BigEndian::write_u64(&mut position[..8], block_number);
+	BigEndian::write_u32(&mut position[8..], tx_number as u32);
+	key.extend_from_slice(&position);
+	key
+}
+
+fn index_block_key(block_number: BlockNumber) -> [u8; 9] {
+	let mut key = [INDEX_BLOCK_PREFIX; 9];
+	BigEndian::write_u64(&mut key[1..], block_number);
+	key
+}
+
+/// Addresses a trace is found by with `Filter::matches`. Failed creates never match a filter, so they are not indexed.
+fn indexed_addresses(trace: &FlatTrace) -> (Option<Address>, Option<Address>) {
+	match trace.action {
+		Action::Call(ref call) => (Some(call.from), Some(call.to)),
+		Action::Create(ref create) => match trace.result {
+			Res::Create(ref result) => (Some(create.from), Some(result.address)),
+			_ => (None, None),
+		},
+		Action::Suicide(ref suicide) => (Some(suicide.address), Some(suicide.refund_address)),
+		Action::Reward(ref reward) => (None, Some(reward.author)),
+	}
+}
+
+/// Appends `traces` to the page, skipping the first `after` of them and taking at most `count`.
+fn extend_page(page: &mut Vec<LocalizedTrace>, traces: Vec<LocalizedTrace>, after: &mut usize, count: &mut usize) {
+	if traces.len() <= *after {
+		*after -= traces.len();
+		return;
+	}
+
+	let len = page.len();
+	page.extend(traces.into_iter().skip(*after).take(*count));
+	*count -= page.len() - len;
+	*after = 0;
+}
+
 /// Database to store transaction execution trace.
 ///
 /// Whenever a transaction is executed by EVM it's execution trace is stored
@@ -64,6 +134,10 @@ pub struct TraceDB<T> where T: DatabaseExtras {
 	enabled: bool,
 	/// extras
 	extras: Arc<T>,
+	/// address index enabled
+	address_index: bool,
+	/// first block covered by the address index
+	index_start: RwLock<Option<BlockNumber>>,
 }
 
 impl<T> TraceDB<T> where T: DatabaseExtras {
@@ -74,6 +148,18 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 			.expect("Genesis block is always inserted upon extras db creation qed");
 		batch.write(db::COL_TRACE, &genesis, &FlatBlockTraces::default());
 		batch.put(db::COL_TRACE, b"version", TRACE_DB_VER);
+
+		// blocks imported while the index is disabled are not indexed,
+		// so it has to be rebuilt from scratch once it's enabled again
+		let index_start = match config.address_index {
+			true => db.key_value().get(db::COL_TRACE_INDEX, INDEX_START_KEY)
+				.expect(LOW_LEVEL_DB_PROOF)
+				.map(|start| BigEndian::read_u64(&start)),
+			false => {
+				batch.delete(db::COL_TRACE_INDEX, INDEX_START_KEY);
+				None
+			},
+		};
 		db.key_value().write(batch).expect("failed to update version");
 
 		TraceDB {
@@ -82,6 +168,8 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 			db,
 			enabled: config.enabled,
 			extras: extras,
+			address_index: config.address_index,
+			index_start: RwLock::new(index_start),
 		}
 	}
 
@@ -140,6 +228,164 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 			.collect()
 	}
 
+	/// Updates the address index with enacted blocks of the import request.
+	///
+	/// Entries of blocks no longer in the canonical chain, i.e. everything indexed
+	/// from the first enacted block up to the end of the old chain, are removed first.
+	fn update_address_index(&self, batch: &mut DBTransaction, request: &ImportRequest) {
+		let range_start = request.block_number + 1 - request.enacted.len() as u64;
+		let old_end = range_start - 1 + request.retracted as u64;
+		for number in range_start..cmp::max(old_end, request.block_number) + 1 {
+			let block_key = index_block_key(number);
+			if let Some(keys) = self.db.key_value().get(db::COL_TRACE_INDEX, &block_key).expect(LOW_LEVEL_DB_PROOF) {
+				for key in rlp::decode_list::<Vec<u8>>(&keys) {
+					batch.delete(db::COL_TRACE_INDEX, &key);
+				}
+				batch.delete(db::COL_TRACE_INDEX, &block_key);
+			}
+		}
+
+		for (i, block_hash) in request.enacted.iter().enumerate() {
+			let number = range_start + i as u64;
+			let traces = match *block_hash == request.block_hash {
+				true => request.traces.clone(),
+				false => self.traces(block_hash).expect("Traces database is incomplete."),
+			};
+
+			let mut entries = BTreeMap::new();
+			let tx_traces: Vec<FlatTransactionTraces> = traces.into();
+			for (tx_number, tx_traces) in tx_traces.into_iter().enumerate() {
+				let flat_traces: Vec<FlatTrace> = tx_traces.into();
+				for (position, trace) in flat_traces.iter().enumerate() {
+					let (from, to) = indexed_addresses(trace);
+					let roles = from.map(|from| (from, AddressRole::From)).into_iter()
+						.chain(to.map(|to| (to, AddressRole::To)));
+					for (address, role) in roles {
+						entries.entry(index_entry_key(&address, role, number, tx_number))
+							.or_insert_with(Vec::new)
+							.push(position);
+					}
+				}
+			}
+
+			for (key, positions) in &entries {
+				batch.put(db::COL_TRACE_INDEX, key, &rlp::encode_list::<usize, _>(positions));
+			}
+			let keys: Vec<Vec<u8>> = entries.into_iter().map(|(key, _)| key).collect();
+			batch.put(db::COL_TRACE_INDEX, &index_block_key(number), &rlp::encode_list::<Vec<u8>, _>(&keys));
+		}
+
+		let mut index_start = self.index_start.write();
+		if index_start.is_none() {
+			let mut start = [0u8; 8];
+			BigEndian::write_u64(&mut start, range_start);
+			batch.put(db::COL_TRACE_INDEX, INDEX_START_KEY, &start);
+			*index_start = Some(range_start);
+		}
+	}
+
+	/// Returns positions `(block number, transaction number, trace number)` of the first `limit`
+	/// traces in the given block range with one of the addresses on the given side.
+	/// Returns `None` if the filter matches every address.
+	fn indexed_positions(
+		&self,
+		addresses: &AddressesFilter,
+		role: AddressRole,
+		start: BlockNumber,
+		end: BlockNumber,
+		limit: usize,
+	) -> Option<BTreeSet<(BlockNumber, usize, usize)>> {
+		if addresses.matches_all() {
+			return None;
+		}
+
+		let mut positions = BTreeSet::new();
+		for address in addresses.addresses() {
+			let prefix = index_entry_prefix(address, role);
+			let start_key = index_entry_key(address, role, start, 0);
+			let mut entries = self.db.key_value().iter_from_prefix(db::COL_TRACE_INDEX, &start_key).peekable();
+			// in-memory databases only yield keys starting with the whole `start_key`, which is empty unless
+			// the address was touched by the first transaction of block `start` => scan all entries of the
+			// address instead, the ones of earlier blocks are skipped below.
+			let from_address_start = match entries.peek().is_some() {
+				true => None,
+				false => Some(self.db.key_value().iter_from_prefix(db::COL_TRACE_INDEX, &prefix)),
+			};
+			let entries = entries.chain(from_address_start.into_iter().flat_map(|entries| entries))
+				// iterator may continue beyond values beginning with this prefix.
+				.take_while(|&(ref key, _)| key.starts_with(&prefix));
+
+			// positions past the first `limit` ones of a single address are never part of the page
+			let mut collected = 0;
+			for (key, value) in entries {
+				let number = BigEndian::read_u64(&key[prefix.len()..]);
+				if number < start {
+					continue;
+				}
+				if number > end || collected >= limit {
+					break;
+				}
+
+				let tx_number = BigEndian::read_u32(&key[prefix.len() + 8..]) as usize;
+				let trace_positions = rlp::decode_list::<usize>(&value);
+				collected += trace_positions.len();
+				positions.extend(trace_positions.into_iter().map(|position| (number, tx_number, position)));
+			}
+		}
+		Some(positions)
+	}
+
+	/// Filters traces with the address index. At least one of the address filters must be non-empty.
+	fn filter_indexed(&self, filter: &Filter, start: BlockNumber, end: BlockNumber, after: usize, count: usize) -> Vec<LocalizedTrace> {
+		// the first positions of an intersection may be anywhere in the range on both sides
+		let limit = match filter.from_address.matches_all() || filter.to_address.matches_all() {
+			true => after.saturating_add(count),
+			false => usize::max_value(),
+		};
+		let from = self.indexed_positions(&filter.from_address, AddressRole::From, start, end, limit);
+		let to = self.indexed_positions(&filter.to_address, AddressRole::To, start, end, limit);
+		let positions = match (from, to) {
+			(Some(from), Some(to)) => from.intersection(&to).cloned().collect(),
+			(Some(positions), None) | (None, Some(positions)) => positions,
+			(None, None) => BTreeSet::new(),
+		};
+
+		let mut block: Option<(BlockNumber, H256, Vec<Vec<FlatTrace>>)> = None;
+		positions.into_iter()
+			.skip(after)
+			.take(count)
+			.map(|(number, tx_number, position)| {
+				if block.as_ref().map_or(true, |&(n, _, _)| n != number) {
+					let hash = self.extras.block_hash(number)
+						.expect("Expected to find block hash. Extras db is probably corrupted");
+					let traces: Vec<FlatTransactionTraces> = self.traces(&hash)
+						.expect("Expected to find a trace. Db is probably corrupted.")
+						.into();
+					block = Some((number, hash, traces.into_iter().map(Into::into).collect()));
+				}
+
+				let &(_, block_hash, ref traces) = block.as_ref().expect("block is loaded above; qed");
+				let trace = traces[tx_number][position].clone();
+				let (transaction_number, transaction_hash) = match self.extras.transaction_hash(number, tx_number) {
+					Some(hash) => (Some(tx_number), Some(hash)),
+					//None means trace without transaction (reward)
+					None => (None, None),
+				};
+
+				LocalizedTrace {
+					action: trace.action,
+					result: trace.result,
+					subtraces: trace.subtraces,
+					trace_address: trace.trace_address.into_iter().collect(),
+					transaction_number,
+					transaction_hash,
+					block_number: number,
+					block_hash,
+				}
+			})
+			.collect()
+	}
+
 	fn matching_transaction_traces(
 		&self,
 		filter: &Filter,
@@ -215,6 +461,10 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 				.expect("Low level database error. Some issue with disk?");
 		}
 
+		if self.address_index && !request.enacted.is_empty() {
+			self.update_address_index(batch, &request);
+		}
+
 		// insert new block traces into the cache and the database
 		{
 			let mut traces = self.traces.write();
@@ -309,21 +559,45 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 	}
 
 	fn filter(&self, filter: &Filter) -> Vec<LocalizedTrace> {
-		let possibilities = filter.bloom_possibilities();
-		let numbers = self.db.trace_blooms()
-			.filter(filter.range.start as u64, filter.range.end as u64, &possibilities)
-			.expect("Low level database error. Some issue with disk?");
+		self.filter_page(filter, 0, usize::max_value())
+	}
+
+	fn filter_page(&self, filter: &Filter, after: usize, count: usize) -> Vec<LocalizedTrace> {
+		let (start, end) = (filter.range.start as BlockNumber, filter.range.end as BlockNumber);
+		let uses_index = !(filter.from_address.matches_all() && filter.to_address.matches_all());
+		// blocks from `indexed_from` on are looked up in the address index, the rest by blooms
+		let indexed_from = match *self.index_start.read() {
+			Some(index_start) if self.address_index && uses_index => cmp::max(index_start, start),
+			_ => end + 1,
+		};
+
+		let mut page = Vec::new();
+		let (mut after, mut count) = (after, count);
+		if start < indexed_from {
+			let possibilities = filter.bloom_possibilities();
+			let numbers = self.db.trace_blooms()
+				.filter(start, cmp::min(end, indexed_from - 1), &possibilities)
+				.expect(LOW_LEVEL_DB_PROOF);
+
+			for n in numbers {
+				if count == 0 {
+					break;
+				}
 
-		numbers.into_iter()
-			.flat_map(|n| {
 				let number = n as BlockNumber;
 				let hash = self.extras.block_hash(number)
 					.expect("Expected to find block hash. Extras db is probably corrupted");
 				let traces = self.traces(&hash)
 					.expect("Expected to find a trace. Db is probably corrupted.");
-				self.matching_block_traces(filter, traces, hash, number)
-			})
-			.collect()
+				extend_page(&mut page, self.matching_block_traces(filter, traces, hash, number), &mut after, &mut count);
+			}
+		}
+
+		if indexed_from <= end && count > 0 {
+			page.extend(self.filter_indexed(filter, indexed_from, end, after, count));
+		}
+
+		page
 	}
 }
 
@@ -581,6 +855,85 @@ mod tests {
 		assert_eq!(tracedb.trace(2, 0, vec![]).unwrap(), create_simple_localized_trace(2, block_2.clone(), tx_2.clone()));
 	}
 
+	fn create_call_import_request(block_number: BlockNumber, block_hash: H256, from: Address, retracted: usize) -> ImportRequest {
+		ImportRequest {
+			traces: FlatBlockTraces::from(vec![FlatTransactionTraces::from(vec![FlatTrace {
+				trace_address: Default::default(),
+				subtraces: 0,
+				action: Action::Call(Call {
+					from: from,
+					to: 2.into(),
+					value: 3.into(),
+					gas: 4.into(),
+					input: vec![],
+					call_type: CallType::Call,
+				}),
+				result: Res::FailedCall(TraceError::OutOfGas),
+			}])]),
+			block_hash: block_hash.clone(),
+			block_number: block_number,
+			enacted: vec![block_hash],
+			retracted: retracted,
+		}
+	}
+
+	#[test]
+	fn test_filter_with_address_index() {
+		let db = new_db();
+		let mut config = Config::default();
+		config.enabled = true;
+		config.address_index = true;
+		let block_1 = H256::from(0xa1);
+		let block_2 = H256::from(0xa2);
+		let block_2b = H256::from(0xb2);
+		let tx_1 = H256::from(0xff);
+		let tx_2 = H256::from(0xaf);
+
+		let mut extras = Extras::default();
+		extras.block_hashes.insert(0, H256::default());
+		extras.block_hashes.insert(1, block_1.clone());
+		extras.block_hashes.insert(2, block_2b.clone());
+		extras.transaction_hashes.insert(1, vec![tx_1.clone()]);
+		extras.transaction_hashes.insert(2, vec![tx_2.clone()]);
+
+		let tracedb = TraceDB::new(config, db.clone(), Arc::new(extras));
+
+		// import block 1 and 2, then replace block 2 with a block calling from another address
+		for request in vec![
+			create_call_import_request(1, block_1.clone(), Address::from(1), 0),
+			create_call_import_request(2, block_2.clone(), Address::from(1), 0),
+			create_call_import_request(2, block_2b.clone(), Address::from(3), 1),
+		] {
+			let mut batch = DBTransaction::new();
+			tracedb.import(&mut batch, request);
+			db.key_value().write(batch).unwrap();
+		}
+
+		let filter = |from: Vec<Address>, to: Vec<Address>| Filter {
+			range: (0..2),
+			from_address: AddressesFilter::from(from),
+			to_address: AddressesFilter::from(to),
+		};
+
+		let traces = tracedb.filter(&filter(vec![Address::from(1)], vec![]));
+		assert_eq!(traces, vec![create_simple_localized_trace(1, block_1.clone(), tx_1.clone())]);
+
+		let traces = tracedb.filter(&filter(vec![Address::from(3)], vec![Address::from(2)]));
+		assert_eq!(traces.len(), 1);
+		assert_eq!(traces[0].block_hash, block_2b);
+		assert_eq!(traces[0].transaction_hash, Some(tx_2.clone()));
+
+		let traces = tracedb.filter(&filter(vec![], vec![Address::from(2)]));
+		assert_eq!(traces.len(), 2);
+
+		let traces = tracedb.filter_page(&filter(vec![], vec![Address::from(2)]), 1, 1);
+		assert_eq!(traces.len(), 1);
+		assert_eq!(traces[0].block_hash, block_2b);
+
+		let traces = tracedb.filter(&filter(vec![Address::from(1)], vec![Address::from(3)]));
+		assert!(traces.is_empty());
+	}
+
 	#[test]
 	fn query_trace_after_reopen() {
 		let db = new_db();
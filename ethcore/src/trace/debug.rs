@@ -0,0 +1,59 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tracers backing the geth-compatible `debug_trace*` calls.
+
+use bytes::Bytes;
+use ethereum_types::U256;
+use pod_state::PodState;
+use trace::{FlatTrace, StructLog, StructLogConfig};
+
+/// Tracer to run a transaction with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DebugTracer {
+	/// Opcode-level struct logs.
+	StructLogs(StructLogConfig),
+	/// Call traces, nested by the consumer into a call tree.
+	Call,
+	/// State of every account touched by the transaction, before its execution.
+	Prestate,
+}
+
+impl Default for DebugTracer {
+	fn default() -> Self {
+		DebugTracer::StructLogs(Default::default())
+	}
+}
+
+/// Output of a `DebugTracer`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DebugTrace {
+	/// Struct logs of the transaction.
+	StructLogs {
+		/// Gas used by the transaction.
+		gas_used: U256,
+		/// True if the outer call resulted in an exceptional exit.
+		failed: bool,
+		/// Output of the outer call.
+		output: Bytes,
+		/// Traced instructions.
+		logs: Vec<StructLog>,
+	},
+	/// Flat call traces of the transaction.
+	Call(Vec<FlatTrace>),
+	/// Accounts touched by the transaction, before its execution.
+	Prestate(PodState),
+}
@@ -18,16 +18,20 @@
 
 mod config;
 mod db;
+mod debug;
 mod executive_tracer;
 mod import;
 mod noop_tracer;
+mod struct_log_tracer;
 mod types;
 
 pub use self::config::Config;
 pub use self::db::TraceDB;
+pub use self::debug::{DebugTracer, DebugTrace};
 pub use self::noop_tracer::{NoopTracer, NoopVMTracer};
 pub use self::executive_tracer::{ExecutiveTracer, ExecutiveVMTracer};
 pub use self::import::ImportRequest;
+pub use self::struct_log_tracer::{StructLogTracer, StructLog, StructLogConfig};
 pub use self::localized::LocalizedTrace;
 
 pub use self::types::{filter, flat, localized, trace, Tracing};
@@ -127,4 +131,7 @@ pub trait Database {
 
 	/// Filter traces matching given filter.
 	fn filter(&self, filter: &Filter) -> Vec<LocalizedTrace>;
+
+	/// Filter traces matching given filter, skipping the first `after` matches and returning at most `count` of them.
+	fn filter_page(&self, filter: &Filter, after: usize, count: usize) -> Vec<LocalizedTrace>;
 }
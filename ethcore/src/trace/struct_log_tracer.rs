@@ -0,0 +1,240 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Struct log tracer. Records every executed instruction the way geth's `debug_traceTransaction` does.
+
+use std::collections::BTreeMap;
+use ethereum_types::{H256, U256};
+use evm::Instruction;
+use trace::VMTracer;
+
+/// Struct log tracer options.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct StructLogConfig {
+	/// Don't record memory.
+	pub disable_memory: bool,
+	/// Don't record the stack.
+	pub disable_stack: bool,
+	/// Don't record storage.
+	pub disable_storage: bool,
+}
+
+/// A single traced instruction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructLog {
+	/// Program counter.
+	pub pc: usize,
+	/// Executed instruction.
+	pub instruction: u8,
+	/// Gas left before the instruction.
+	pub gas: U256,
+	/// Gas cost of the instruction.
+	pub gas_cost: U256,
+	/// Call depth, 1 for the outermost call.
+	pub depth: usize,
+	/// Stack before the instruction, bottom item first.
+	pub stack: Option<Vec<U256>>,
+	/// Memory before the instruction.
+	pub memory: Option<Vec<u8>>,
+	/// Storage slots of the executing contract read or written so far.
+	pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+impl StructLog {
+	/// Mnemonic of the traced instruction.
+	pub fn op_name(&self) -> &'static str {
+		Instruction::from_u8(self.instruction).map_or("INVALID", |i| i.info().name)
+	}
+}
+
+/// State of a single call frame.
+#[derive(Default)]
+struct Frame {
+	stack: Vec<U256>,
+	memory: Vec<u8>,
+	storage: BTreeMap<H256, H256>,
+	/// Instruction being executed and the slot it reads, if it's an `SLOAD`.
+	executing: Option<(u8, Option<H256>)>,
+}
+
+/// VM tracer producing struct logs.
+///
+/// The interpreter reports only the items pushed by each instruction, so the tracer keeps
+/// a copy of the stack, memory and accessed storage of every call frame.
+pub struct StructLogTracer {
+	config: StructLogConfig,
+	logs: Vec<StructLog>,
+	frames: Vec<Frame>,
+	current_gas: U256,
+}
+
+impl StructLogTracer {
+	/// Create a new tracer with given options.
+	pub fn new(config: StructLogConfig) -> Self {
+		StructLogTracer {
+			config,
+			logs: Vec::new(),
+			frames: Vec::new(),
+			current_gas: U256::zero(),
+		}
+	}
+}
+
+impl VMTracer for StructLogTracer {
+	type Output = Vec<StructLog>;
+
+	fn trace_next_instruction(&mut self, _pc: usize, _instruction: u8, current_gas: U256) -> bool {
+		self.current_gas = current_gas;
+		true
+	}
+
+	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: U256, _mem_written: Option<(usize, usize)>, store_written: Option<(U256, U256)>) {
+		let depth = self.frames.len();
+		let config = self.config;
+		let frame = match self.frames.last_mut() {
+			Some(frame) => frame,
+			None => return,
+		};
+
+		if let Some((key, value)) = store_written {
+			frame.storage.insert(key.into(), value.into());
+		}
+		let loaded_key = if instruction == Instruction::SLOAD as u8 {
+			frame.stack.last().map(|key| H256::from(*key))
+		} else {
+			None
+		};
+		frame.executing = Some((instruction, loaded_key));
+
+		self.logs.push(StructLog {
+			pc,
+			instruction,
+			gas: self.current_gas,
+			gas_cost,
+			depth,
+			stack: if config.disable_stack { None } else { Some(frame.stack.clone()) },
+			memory: if config.disable_memory { None } else { Some(frame.memory.clone()) },
+			storage: if config.disable_storage { None } else { Some(frame.storage.clone()) },
+		});
+	}
+
+	fn trace_executed(&mut self, _gas_used: U256, stack_push: &[U256], mem: &[u8]) {
+		let frame = match self.frames.last_mut() {
+			Some(frame) => frame,
+			None => return,
+		};
+		let (instruction, loaded_key) = match frame.executing.take() {
+			Some(executing) => executing,
+			None => return,
+		};
+
+		let popped = Instruction::from_u8(instruction).map_or(0, |i| i.info().args);
+		let remaining = frame.stack.len().saturating_sub(popped);
+		frame.stack.truncate(remaining);
+		frame.stack.extend_from_slice(stack_push);
+
+		if !self.config.disable_memory {
+			frame.memory.clear();
+			frame.memory.extend_from_slice(mem);
+		}
+
+		// the loaded value is only known now; `SLOAD` is never followed by a subcall, so the last log is its own
+		if let (Some(key), Some(value)) = (loaded_key, stack_push.first()) {
+			frame.storage.insert(key, H256::from(*value));
+			if let Some(storage) = self.logs.last_mut().and_then(|log| log.storage.as_mut()) {
+				storage.insert(key, H256::from(*value));
+			}
+		}
+	}
+
+	fn prepare_subtrace(&mut self, _code: &[u8]) {
+		self.frames.push(Frame::default());
+	}
+
+	fn done_subtrace(&mut self) {
+		self.frames.pop();
+	}
+
+	fn drain(self) -> Option<Vec<StructLog>> {
+		Some(self.logs)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_track_stack_memory_and_storage() {
+		let mut tracer = StructLogTracer::new(Default::default());
+		tracer.prepare_subtrace(&[]);
+
+		// PUSH1 0x2a
+		tracer.trace_next_instruction(0, 0x60, 100.into());
+		tracer.trace_prepare_execute(0, 0x60, 3.into(), None, None);
+		tracer.trace_executed(97.into(), &[0x2a.into()], &[]);
+
+		// SLOAD
+		tracer.trace_next_instruction(2, 0x54, 97.into());
+		tracer.trace_prepare_execute(2, 0x54, 50.into(), None, None);
+		tracer.trace_executed(47.into(), &[7.into()], &[]);
+
+		// PUSH1 0x00
+		tracer.trace_next_instruction(3, 0x60, 47.into());
+		tracer.trace_prepare_execute(3, 0x60, 3.into(), None, None);
+		tracer.trace_executed(44.into(), &[0.into()], &[]);
+
+		// MSTORE
+		tracer.trace_next_instruction(5, 0x52, 44.into());
+		tracer.trace_prepare_execute(5, 0x52, 6.into(), Some((0, 32)), None);
+		tracer.trace_executed(38.into(), &[], &[1u8; 32]);
+
+		// STOP
+		tracer.trace_next_instruction(6, 0x00, 38.into());
+		tracer.trace_prepare_execute(6, 0x00, 0.into(), None, None);
+		tracer.done_subtrace();
+
+		let logs = tracer.drain().unwrap();
+		assert_eq!(logs.len(), 5);
+		assert_eq!(logs[1].op_name(), "SLOAD");
+		assert_eq!(logs[1].gas, 97.into());
+		assert_eq!(logs[1].depth, 1);
+		assert_eq!(logs[1].stack, Some(vec![0x2a.into()]));
+		assert_eq!(logs[1].storage.as_ref().unwrap().get(&H256::from(0x2a)), Some(&H256::from(7)));
+		assert_eq!(logs[3].stack, Some(vec![7.into(), 0.into()]));
+		assert_eq!(logs[4].stack, Some(vec![]));
+		assert_eq!(logs[4].memory, Some(vec![1u8; 32]));
+	}
+
+	#[test]
+	fn should_respect_disabled_fields() {
+		let mut tracer = StructLogTracer::new(StructLogConfig {
+			disable_memory: true,
+			disable_stack: true,
+			disable_storage: true,
+		});
+		tracer.prepare_subtrace(&[]);
+		tracer.trace_next_instruction(0, 0x55, 100.into());
+		tracer.trace_prepare_execute(0, 0x55, 20000.into(), None, Some((1.into(), 2.into())));
+		tracer.trace_executed(80.into(), &[], &[]);
+		tracer.done_subtrace();
+
+		let logs = tracer.drain().unwrap();
+		assert_eq!(logs[0].stack, None);
+		assert_eq!(logs[0].memory, None);
+		assert_eq!(logs[0].storage, None);
+	}
+}
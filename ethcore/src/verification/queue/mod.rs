@@ -735,6 +735,7 @@ mod tests {
 	use test_helpers::{get_good_dummy_block_seq, get_good_dummy_block};
 	use error::*;
 	use views::BlockView;
+	use header::BlockNumber;
 	use bytes::Bytes;
 
 	// create a test block queue.
@@ -756,7 +757,7 @@ mod tests {
 	}
 
 	fn new_unverified(bytes: Bytes) -> Unverified {
-		Unverified::from_rlp(bytes).expect("Should be valid rlp")
+		Unverified::from_rlp(bytes, BlockNumber::max_value()).expect("Should be valid rlp")
 	}
 
 	#[test]
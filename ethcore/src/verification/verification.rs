@@ -36,7 +36,7 @@ use client::{BlockInfo, CallContract};
 use engines::EthEngine;
 use error::{BlockError, Error};
 use header::{BlockNumber, Header};
-use transaction::SignedTransaction;
+use transaction::{SignedTransaction, UnverifiedTransaction};
 use verification::queue::kind::blocks::Unverified;
 
 /// Preprocessed block data gathered in `verify_block_unordered` call
@@ -233,7 +233,7 @@ fn verify_uncles(block: &PreverifiedBlock, bc: &BlockProvider, engine: &EthEngin
 				return Err(From::from(BlockError::UncleParentNotInChain(uncle_parent.hash())));
 			}
 
-			let uncle_parent = uncle_parent.decode()?;
+			let uncle_parent = uncle_parent.decode(engine.params().eip1559_transition)?;
 			verify_parent(&uncle, &uncle_parent, engine)?;
 			engine.verify_block_family(&uncle, &uncle_parent)?;
 			verified.insert(uncle.hash());
@@ -330,13 +330,21 @@ fn verify_parent(header: &Header, parent: &Header, engine: &EthEngine) -> Result
 		return Err(BlockError::RidiculousNumber(OutOfBounds { min: Some(1), max: None, found: header.number() }).into());
 	}
 
-	let parent_gas_limit = *parent.gas_limit();
+	let parent_gas_limit = engine.machine().parent_gas_limit(header.number(), parent);
 	let min_gas = parent_gas_limit - parent_gas_limit / gas_limit_divisor;
 	let max_gas = parent_gas_limit + parent_gas_limit / gas_limit_divisor;
 	if header.gas_limit() <= &min_gas || header.gas_limit() >= &max_gas {
 		return Err(From::from(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas), max: Some(max_gas), found: header.gas_limit().clone() })));
 	}
 
+	let expected_base_fee = engine.machine().calc_base_fee(header.number(), parent);
+	if header.base_fee() != expected_base_fee {
+		return Err(From::from(BlockError::InvalidBaseFee(Mismatch {
+			expected: expected_base_fee.unwrap_or_default(),
+			found: header.base_fee().unwrap_or_default(),
+		})));
+	}
+
 	Ok(())
 }
 
@@ -344,7 +352,7 @@ fn verify_parent(header: &Header, parent: &Header, engine: &EthEngine) -> Result
 fn verify_block_integrity(block: &Unverified) -> Result<(), Error> {
 	let block_rlp = Rlp::new(&block.bytes);
 	let tx = block_rlp.at(1)?;
-	let expected_root = ordered_trie_root(tx.iter().map(|r| r.as_raw()));
+	let expected_root = ordered_trie_root(tx.iter().map(|r| UnverifiedTransaction::envelope_from_rlp(&r)));
 	if &expected_root != block.header.transactions_root() {
 		bail!(BlockError::InvalidTransactionsRoot(Mismatch {
 			expected: expected_root,
@@ -424,7 +432,7 @@ mod tests {
 		}
 
 		pub fn insert(&mut self, bytes: Bytes) {
-			let header = Unverified::from_rlp(bytes.clone()).unwrap().header;
+			let header = Unverified::from_rlp(bytes.clone(), BlockNumber::max_value()).unwrap().header;
 			let hash = header.hash();
 			self.blocks.insert(hash, bytes);
 			self.numbers.insert(header.number(), hash);
@@ -464,7 +472,7 @@ mod tests {
 		/// Get the familial details concerning a block.
 		fn block_details(&self, hash: &H256) -> Option<BlockDetails> {
 			self.blocks.get(hash).map(|bytes| {
-				let header = Unverified::from_rlp(bytes.to_vec()).unwrap().header;
+				let header = Unverified::from_rlp(bytes.to_vec(), BlockNumber::max_value()).unwrap().header;
 				BlockDetails {
 					number: header.number(),
 					total_difficulty: *header.difficulty(),
@@ -500,12 +508,12 @@ mod tests {
 	}
 
 	fn basic_test(bytes: &[u8], engine: &EthEngine) -> Result<(), Error> {
-		let unverified = Unverified::from_rlp(bytes.to_vec())?;
+		let unverified = Unverified::from_rlp(bytes.to_vec(), engine.params().eip1559_transition)?;
 		verify_block_basic(&unverified, engine, true)
 	}
 
 	fn family_test<BC>(bytes: &[u8], engine: &EthEngine, bc: &BC) -> Result<(), Error> where BC: BlockProvider {
-		let block = Unverified::from_rlp(bytes.to_vec()).unwrap();
+		let block = Unverified::from_rlp(bytes.to_vec(), engine.params().eip1559_transition).unwrap();
 		let header = block.header;
 		let transactions: Vec<_> = block.transactions
 			.into_iter()
@@ -519,7 +527,7 @@ mod tests {
 		let client = ::client::TestBlockChainClient::default();
 		let parent = bc.block_header_data(header.parent_hash())
 			.ok_or(BlockError::UnknownParent(header.parent_hash().clone()))?
-			.decode()?;
+			.decode(engine.params().eip1559_transition)?;
 
 		let block = PreverifiedBlock {
 			header,
@@ -537,7 +545,7 @@ mod tests {
 	}
 
 	fn unordered_test(bytes: &[u8], engine: &EthEngine) -> Result<(), Error> {
-		let un = Unverified::from_rlp(bytes.to_vec())?;
+		let un = Unverified::from_rlp(bytes.to_vec(), engine.params().eip1559_transition)?;
 		verify_block_unordered(un, engine, false)?;
 		Ok(())
 	}
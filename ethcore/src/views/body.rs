@@ -92,7 +92,7 @@ impl<'a> BodyView<'a> {
 
 	/// Return transaction hashes.
 	pub fn transaction_hashes(&self) -> Vec<H256> {
-		self.transactions_rlp().iter().map(|rlp| keccak(rlp.as_raw())).collect()
+		self.transactions_rlp().iter().map(|rlp| keccak(UnverifiedTransaction::envelope_from_rlp(&rlp.rlp))).collect()
 	}
 
 	/// Returns transaction at given index without deserializing unnecessary data.
@@ -18,12 +18,17 @@
 use bytes::Bytes;
 use ethereum_types::{H256, U256};
 use hash::keccak;
+use transaction::UnverifiedTransaction;
 // use rlp::{Rlp, Decodable};
 use super::ViewRlp;
 
 /// View onto transaction rlp.
 pub struct TransactionView<'a> {
-	rlp: ViewRlp<'a>
+	rlp: ViewRlp<'a>,
+	/// List of the transaction fields; the payload of the envelope for typed transactions.
+	fields: ViewRlp<'a>,
+	/// Index of the nonce in `fields`; typed transactions start with the chain id.
+	offset: usize,
 }
 
 impl<'a> TransactionView<'a> {
@@ -44,8 +49,14 @@ impl<'a> TransactionView<'a> {
 	/// }
 	/// ```
 	pub fn new(rlp: ViewRlp<'a>) -> TransactionView<'a> {
+		let (fields, offset) = match rlp.rlp.is_list() {
+			true => (rlp.clone(), 0),
+			false => (rlp.inner_at(1), 1),
+		};
 		TransactionView {
-			rlp: rlp
+			rlp: rlp,
+			fields: fields,
+			offset: offset,
 		}
 	}
 
@@ -56,32 +67,32 @@ impl<'a> TransactionView<'a> {
 
 	/// Returns transaction hash.
 	pub fn hash(&self) -> H256 {
-		keccak(self.rlp.as_raw())
+		keccak(UnverifiedTransaction::envelope_from_rlp(&self.rlp.rlp))
 	}
 
 	/// Get the nonce field of the transaction.
-	pub fn nonce(&self) -> U256 { self.rlp.val_at(0) }
+	pub fn nonce(&self) -> U256 { self.fields.val_at(self.offset) }
 
 	/// Get the gas_price field of the transaction.
-	pub fn gas_price(&self) -> U256 { self.rlp.val_at(1) }
+	pub fn gas_price(&self) -> U256 { self.fields.val_at(self.offset + 1) }
 
 	/// Get the gas field of the transaction.
-	pub fn gas(&self) -> U256 { self.rlp.val_at(2) }
+	pub fn gas(&self) -> U256 { self.fields.val_at(self.offset + 2) }
 
 	/// Get the value field of the transaction.
-	pub fn value(&self) -> U256 { self.rlp.val_at(4) }
+	pub fn value(&self) -> U256 { self.fields.val_at(self.offset + 4) }
 
 	/// Get the data field of the transaction.
-	pub fn data(&self) -> Bytes { self.rlp.val_at(5) }
+	pub fn data(&self) -> Bytes { self.fields.val_at(self.offset + 5) }
 
-	/// Get the v field of the transaction.
-	pub fn v(&self) -> u8 { let r: u16 = self.rlp.val_at(6); r as u8 }
+	/// Get the v field of the transaction; the y parity for typed transactions.
+	pub fn v(&self) -> u8 { let r: u16 = self.fields.val_at(6 + 2 * self.offset); r as u8 }
 
 	/// Get the r field of the transaction.
-	pub fn r(&self) -> U256 { self.rlp.val_at(7) }
+	pub fn r(&self) -> U256 { self.fields.val_at(7 + 2 * self.offset) }
 
 	/// Get the s field of the transaction.
-	pub fn s(&self) -> U256 { self.rlp.val_at(8) }
+	pub fn s(&self) -> U256 { self.fields.val_at(8 + 2 * self.offset) }
 }
 
 #[cfg(test)]
@@ -96,6 +96,19 @@ impl<'a, 'view> ViewRlp<'a> where 'a : 'view {
 	pub fn as_raw(&'view self) -> &'a [u8] {
 		self.rlp.as_raw()
 	}
+
+	/// Returns rlp encoded within this rlp string, skipping the first `skip` bytes of the string.
+	/// Panics if this rlp is not a string.
+	pub fn inner_at(&self, skip: usize) -> ViewRlp<'a> {
+		let data = self.expect_valid_rlp(self.rlp.data());
+		self.new_from_rlp(Rlp::new(&data[skip..]))
+	}
+}
+
+impl<'a> Clone for ViewRlp<'a> {
+	fn clone(&self) -> Self {
+		self.new_from_rlp(Rlp::new(self.rlp.as_raw()))
+	}
 }
 
 /// Iterator over rlp-slice list elements.
@@ -37,8 +37,9 @@ use chain::{ChainSync, SyncStatus as EthSyncStatus};
 use std::net::{SocketAddr, AddrParseError};
 use std::str::FromStr;
 use parking_lot::RwLock;
-use chain::{ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_62,
-	PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2, PAR_PROTOCOL_VERSION_3,
+use chain::{ETH_PROTOCOL_VERSION_66, ETH_PROTOCOL_VERSION_65, ETH_PROTOCOL_VERSION_64,
+	ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_62,
+	PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2, PAR_PROTOCOL_VERSION_3, PAR_PROTOCOL_VERSION_4,
 	PRIVATE_TRANSACTION_PACKET, SIGNED_PRIVATE_TRANSACTION_PACKET};
 use light::client::AsLightClient;
 use light::Provider;
@@ -93,7 +94,7 @@ impl WarpSync {
 }
 
 /// Sync configuration
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct SyncConfig {
 	/// Max blocks to download ahead
 	pub max_download_ahead_blocks: usize,
@@ -107,10 +108,17 @@ pub struct SyncConfig {
 	pub light_subprotocol_name: [u8; 3],
 	/// Fork block to check
 	pub fork_block: Option<(BlockNumber, H256)>,
+	/// Hard fork blocks of the chain, used to compute the EIP-2124 fork id.
+	pub fork_transitions: Vec<BlockNumber>,
 	/// Enable snapshot sync
 	pub warp_sync: WarpSync,
+	/// Download the state from the live state of peers when warp sync finds no snapshot.
+	/// Only supported on proof-of-work chains.
+	pub state_sync: bool,
 	/// Enable light client server.
 	pub serve_light: bool,
+	/// Block number from which headers carry the EIP-1559 base fee.
+	pub eip1559_transition: BlockNumber,
 }
 
 impl Default for SyncConfig {
@@ -122,8 +130,11 @@ impl Default for SyncConfig {
 			subprotocol_name: ETH_PROTOCOL,
 			light_subprotocol_name: LIGHT_PROTOCOL,
 			fork_block: None,
+			fork_transitions: Vec::new(),
 			warp_sync: WarpSync::Disabled,
+			state_sync: false,
 			serve_light: false,
+			eip1559_transition: BlockNumber::max_value(),
 		}
 	}
 }
@@ -139,6 +150,9 @@ pub trait SyncProvider: Send + Sync {
 	/// Get the enode if available.
 	fn enode(&self) -> Option<String>;
 
+	/// Get the EIP-778 node record if available.
+	fn node_record(&self) -> Option<String>;
+
 	/// Returns propagation count for pending transactions.
 	fn transactions_stats(&self) -> BTreeMap<H256, TransactionStats>;
 }
@@ -315,7 +329,7 @@ impl EthSync {
 			})
 		};
 
-		let chain_sync = ChainSync::new(params.config, &*params.chain, params.private_tx_handler.clone());
+		let chain_sync = ChainSync::new(params.config.clone(), &*params.chain, params.private_tx_handler.clone());
 		let service = NetworkService::new(params.network_config.clone().into_basic()?, connection_filter)?;
 
 		let sync = Arc::new(EthSync {
@@ -372,6 +386,10 @@ impl SyncProvider for EthSync {
 		self.network.external_url()
 	}
 
+	fn node_record(&self) -> Option<String> {
+		self.network.node_record()
+	}
+
 	fn transactions_stats(&self) -> BTreeMap<H256, TransactionStats> {
 		let sync = self.eth_handler.sync.read();
 		sync.transactions_stats()
@@ -497,10 +515,10 @@ impl ChainNotify for EthSync {
 			_ => {},
 		}
 
-		self.network.register_protocol(self.eth_handler.clone(), self.subprotocol_name, &[ETH_PROTOCOL_VERSION_62, ETH_PROTOCOL_VERSION_63])
+		self.network.register_protocol(self.eth_handler.clone(), self.subprotocol_name, &[ETH_PROTOCOL_VERSION_62, ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_64, ETH_PROTOCOL_VERSION_65, ETH_PROTOCOL_VERSION_66])
 			.unwrap_or_else(|e| warn!("Error registering ethereum protocol: {:?}", e));
 		// register the warp sync subprotocol
-		self.network.register_protocol(self.eth_handler.clone(), WARP_SYNC_PROTOCOL_ID, &[PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2, PAR_PROTOCOL_VERSION_3])
+		self.network.register_protocol(self.eth_handler.clone(), WARP_SYNC_PROTOCOL_ID, &[PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2, PAR_PROTOCOL_VERSION_3, PAR_PROTOCOL_VERSION_4])
 			.unwrap_or_else(|e| warn!("Error registering snapshot sync protocol: {:?}", e));
 
 		// register the light protocol.
@@ -544,7 +562,7 @@ struct TxRelay(Arc<BlockChainClient>);
 impl LightHandler for TxRelay {
 	fn on_transactions(&self, ctx: &EventContext, relay: &[::transaction::UnverifiedTransaction]) {
 		trace!(target: "pip", "Relaying {} transactions from peer {}", relay.len(), ctx.peer());
-		self.0.queue_transactions(relay.iter().map(|tx| ::rlp::encode(tx)).collect(), ctx.peer())
+		self.0.queue_transactions(relay.iter().map(|tx| tx.encode_envelope()).collect(), ctx.peer())
 	}
 }
 
@@ -749,6 +767,9 @@ pub trait LightSyncProvider {
 	/// Get the enode if available.
 	fn enode(&self) -> Option<String>;
 
+	/// Get the EIP-778 node record if available.
+	fn node_record(&self) -> Option<String>;
+
 	/// Returns propagation count for pending transactions.
 	fn transactions_stats(&self) -> BTreeMap<H256, TransactionStats>;
 }
@@ -932,6 +953,10 @@ impl LightSyncProvider for LightSync {
 		self.network.external_url()
 	}
 
+	fn node_record(&self) -> Option<String> {
+		self.network.node_record()
+	}
+
 	fn network_id(&self) -> u64 {
 		self.network_id
 	}
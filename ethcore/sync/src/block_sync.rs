@@ -140,12 +140,14 @@ pub struct BlockDownloader {
 	limit_reorg: bool,
 	/// consecutive useless headers this round
 	useless_headers_count: usize,
+	/// Block number from which headers carry the EIP-1559 base fee.
+	eip1559_transition: BlockNumber,
 }
 
 impl BlockDownloader {
 	/// Create a new instance of syncing strategy.
 	/// For BlockSet::NewBlocks this won't reorganize to before the last kept state.
-	pub fn new(block_set: BlockSet, start_hash: &H256, start_number: BlockNumber) -> Self {
+	pub fn new(block_set: BlockSet, start_hash: &H256, start_number: BlockNumber, eip1559_transition: BlockNumber) -> Self {
 		let (limit_reorg, sync_receipts) = match block_set {
 			BlockSet::NewBlocks => (true, false),
 			BlockSet::OldBlocks => (false, true)
@@ -166,6 +168,7 @@ impl BlockDownloader {
 			retract_step: 1,
 			limit_reorg: limit_reorg,
 			useless_headers_count: 0,
+			eip1559_transition: eip1559_transition,
 		}
 	}
 
@@ -260,7 +263,7 @@ impl BlockDownloader {
 		let mut hashes = Vec::new();
 		let mut last_header = None;
 		for i in 0..item_count {
-			let info = SyncHeader::from_rlp(r.at(i)?.as_raw().to_vec())?;
+			let info = SyncHeader::from_rlp(r.at(i)?.as_raw().to_vec(), self.eip1559_transition)?;
 			let number = BlockNumber::from(info.header.number());
 			let hash = info.header.hash();
 
@@ -363,7 +366,7 @@ impl BlockDownloader {
 		} else {
 			let mut bodies = Vec::with_capacity(item_count);
 			for i in 0..item_count {
-				let body = SyncBody::from_rlp(r.at(i)?.as_raw())?;
+				let body = SyncBody::from_rlp(r.at(i)?.as_raw(), self.eip1559_transition)?;
 				bodies.push(body);
 			}
 
@@ -664,7 +667,7 @@ mod tests {
 		let spec = Spec::new_test();
 		let genesis_hash = spec.genesis_header().hash();
 
-		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &genesis_hash, 0);
+		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &genesis_hash, 0, BlockNumber::max_value());
 		downloader.state = State::ChainHead;
 
 		let mut chain = TestBlockChainClient::new();
@@ -746,7 +749,7 @@ mod tests {
 		let parent_hash = headers[1].hash();
 		headers.push(dummy_header(129, parent_hash));
 
-		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &H256::random(), 0);
+		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &H256::random(), 0, BlockNumber::max_value());
 		downloader.state = State::Blocks;
 		downloader.blocks.reset_to(vec![headers[0].hash()]);
 
@@ -816,7 +819,7 @@ mod tests {
 			headers.push(header);
 		}
 
-		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &headers[0].hash(), 0);
+		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &headers[0].hash(), 0, BlockNumber::max_value());
 		downloader.state = State::Blocks;
 		downloader.blocks.reset_to(vec![headers[0].hash()]);
 
@@ -880,7 +883,7 @@ mod tests {
 			headers.push(header);
 		}
 
-		let mut downloader = BlockDownloader::new(BlockSet::OldBlocks, &headers[0].hash(), 0);
+		let mut downloader = BlockDownloader::new(BlockSet::OldBlocks, &headers[0].hash(), 0, BlockNumber::max_value());
 		downloader.state = State::Blocks;
 		downloader.blocks.reset_to(vec![headers[0].hash()]);
 
@@ -913,7 +916,7 @@ mod tests {
 		let spec = Spec::new_test();
 		let genesis_hash = spec.genesis_header().hash();
 
-		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &genesis_hash, 0);
+		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &genesis_hash, 0, BlockNumber::max_value());
 		downloader.state = State::ChainHead;
 
 		let mut chain = TestBlockChainClient::new();
@@ -953,7 +956,7 @@ mod tests {
 		let spec = Spec::new_test();
 		let genesis_hash = spec.genesis_header().hash();
 
-		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &genesis_hash, 0);
+		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &genesis_hash, 0, BlockNumber::max_value());
 		downloader.state = State::ChainHead;
 
 		let mut chain = TestBlockChainClient::new();
@@ -22,9 +22,10 @@ use triehash_ethereum::ordered_trie_root;
 use bytes::Bytes;
 use rlp::{Rlp, RlpStream, DecoderError};
 use network;
-use ethcore::header::Header as BlockHeader;
+use ethcore::header::{BlockNumber, Header as BlockHeader};
 use ethcore::verification::queue::kind::blocks::Unverified;
 use transaction::UnverifiedTransaction;
+use types::receipt::Receipt;
 
 known_heap_size!(0, HeaderId);
 
@@ -42,9 +43,9 @@ impl HeapSizeOf for SyncHeader {
 }
 
 impl SyncHeader {
-	pub fn from_rlp(bytes: Bytes) -> Result<Self, DecoderError> {
+	pub fn from_rlp(bytes: Bytes, eip1559_transition: BlockNumber) -> Result<Self, DecoderError> {
 		let result = SyncHeader {
-			header: ::rlp::decode(&bytes)?,
+			header: BlockHeader::decode(&bytes, eip1559_transition)?,
 			bytes,
 		};
 
@@ -60,7 +61,7 @@ pub struct SyncBody {
 }
 
 impl SyncBody {
-	pub fn from_rlp(bytes: &[u8]) -> Result<Self, DecoderError> {
+	pub fn from_rlp(bytes: &[u8], eip1559_transition: BlockNumber) -> Result<Self, DecoderError> {
 		let rlp = Rlp::new(bytes);
 		let transactions_rlp = rlp.at(0)?;
 		let uncles_rlp = rlp.at(1)?;
@@ -69,7 +70,7 @@ impl SyncBody {
 			transactions_bytes: transactions_rlp.as_raw().to_vec(),
 			transactions: transactions_rlp.as_list()?,
 			uncles_bytes: uncles_rlp.as_raw().to_vec(),
-			uncles: uncles_rlp.as_list()?,
+			uncles: BlockHeader::decode_rlp_list(&uncles_rlp, eip1559_transition)?,
 		};
 
 		Ok(result)
@@ -416,7 +417,7 @@ impl BlockCollection {
 
 	fn insert_body(&mut self, body: SyncBody) -> Result<H256, network::Error> {
 		let header_id = {
-			let tx_root = ordered_trie_root(Rlp::new(&body.transactions_bytes).iter().map(|r| r.as_raw()));
+			let tx_root = ordered_trie_root(Rlp::new(&body.transactions_bytes).iter().map(|r| UnverifiedTransaction::envelope_from_rlp(&r)));
 			let uncles = keccak(&body.uncles_bytes);
 			HeaderId {
 				transactions_root: tx_root,
@@ -449,7 +450,7 @@ impl BlockCollection {
 	fn insert_receipt(&mut self, r: Bytes) -> Result<Vec<H256>, network::Error> {
 		let receipt_root = {
 			let receipts = Rlp::new(&r);
-			ordered_trie_root(receipts.iter().map(|r| r.as_raw()))
+			ordered_trie_root(receipts.iter().map(|r| Receipt::envelope_from_rlp(&r)))
 		};
 		self.downloading_receipts.remove(&receipt_root);
 		match self.receipt_ids.entry(receipt_root) {
@@ -608,7 +609,7 @@ mod test {
 		let blocks: Vec<_> = (0..nblocks)
 			.map(|i| (&client as &BlockChainClient).block(BlockId::Number(i as BlockNumber)).unwrap().into_inner())
 			.collect();
-		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec()).unwrap()).collect();
+		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect();
 		let hashes: Vec<_> = headers.iter().map(|h| h.header.hash()).collect();
 		let heads: Vec<_> = hashes.iter().enumerate().filter_map(|(i, h)| if i % 20 == 0 { Some(*h) } else { None }).collect();
 		bc.reset_to(heads);
@@ -636,7 +637,7 @@ mod test {
 
 		assert_eq!(
 			bc.drain().into_iter().map(|b| b.block).collect::<Vec<_>>(),
-			blocks[0..6].iter().map(|b| Unverified::from_rlp(b.to_vec()).unwrap()).collect::<Vec<_>>()
+			blocks[0..6].iter().map(|b| Unverified::from_rlp(b.to_vec(), BlockNumber::max_value()).unwrap()).collect::<Vec<_>>()
 		);
 		assert!(!bc.contains(&hashes[0]));
 		assert_eq!(hashes[5], bc.head.unwrap());
@@ -650,7 +651,7 @@ mod test {
 		bc.insert_headers(headers[5..10].into_iter().map(Clone::clone).collect());
 		assert_eq!(
 			bc.drain().into_iter().map(|b| b.block).collect::<Vec<_>>(),
-			blocks[6..16].iter().map(|b| Unverified::from_rlp(b.to_vec()).unwrap()).collect::<Vec<_>>()
+			blocks[6..16].iter().map(|b| Unverified::from_rlp(b.to_vec(), BlockNumber::max_value()).unwrap()).collect::<Vec<_>>()
 		);
 
 		assert_eq!(hashes[15], bc.heads[0]);
@@ -670,7 +671,7 @@ mod test {
 		let blocks: Vec<_> = (0..nblocks)
 			.map(|i| (&client as &BlockChainClient).block(BlockId::Number(i as BlockNumber)).unwrap().into_inner())
 			.collect();
-		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec()).unwrap()).collect();
+		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect();
 		let hashes: Vec<_> = headers.iter().map(|h| h.header.hash()).collect();
 		let heads: Vec<_> = hashes.iter().enumerate().filter_map(|(i, h)| if i % 20 == 0 { Some(*h) } else { None }).collect();
 		bc.reset_to(heads);
@@ -694,7 +695,7 @@ mod test {
 		let blocks: Vec<_> = (0..nblocks)
 			.map(|i| (&client as &BlockChainClient).block(BlockId::Number(i as BlockNumber)).unwrap().into_inner())
 			.collect();
-		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec()).unwrap()).collect();
+		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect();
 		let hashes: Vec<_> = headers.iter().map(|h| h.header.hash()).collect();
 		let heads: Vec<_> = hashes.iter().enumerate().filter_map(|(i, h)| if i % 20 == 0 { Some(*h) } else { None }).collect();
 		bc.reset_to(heads);
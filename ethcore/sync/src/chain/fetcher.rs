@@ -0,0 +1,330 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fetcher of announced transactions.
+//!
+//! eth/65 peers announce transaction hashes instead of pushing full transactions.
+//! Announced hashes are queued and requested in batches, with at most one request in
+//! flight per peer. If a peer doesn't deliver a transaction or doesn't answer in time,
+//! the transaction is requested from the next peer that announced it.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+use ethereum_types::H256;
+use fastmap::H256FastMap;
+use network::PeerId;
+
+/// How long to wait for a `PooledTransactions` response.
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximal number of announced hashes waiting to be fetched.
+const MAX_ANNOUNCED: usize = 16384;
+/// Maximal number of hashes announced by a single peer waiting to be fetched.
+const MAX_ANNOUNCED_PER_PEER: usize = 4096;
+/// Maximal number of peers remembered as announcers of a single transaction.
+const MAX_ANNOUNCERS: usize = 8;
+/// Number of recently fetched hashes that are not requested again.
+const MAX_KNOWN: usize = 32768;
+
+struct Request {
+	hashes: Vec<H256>,
+	sent: Instant,
+}
+
+/// Schedules requests of announced transactions.
+#[derive(Default)]
+pub struct TransactionFetcher {
+	/// Announced hashes being fetched, with the peers that can still be asked for them.
+	announced: H256FastMap<Vec<PeerId>>,
+	/// Hashes waiting for a request, in order of announcement.
+	queue: VecDeque<H256>,
+	/// Hashes with a request in flight.
+	fetching: HashSet<H256>,
+	/// Request in flight of each peer.
+	requests: HashMap<PeerId, Request>,
+	/// Recently fetched hashes.
+	known: HashSet<H256>,
+	/// Order in which `known` hashes were added, the oldest are forgotten first.
+	known_order: VecDeque<H256>,
+	/// Hashes announced by each peer, some of them may have been fetched or dropped meanwhile.
+	peer_announced: HashMap<PeerId, HashSet<H256>>,
+}
+
+impl TransactionFetcher {
+	/// Record hashes announced by a peer. Returns the number of hashes that weren't
+	/// known or announced before.
+	pub fn announced(&mut self, peer_id: PeerId, hashes: &[H256]) -> usize {
+		let mut pending = self.peer_announced.remove(&peer_id).unwrap_or_default();
+		if pending.len() + hashes.len() > MAX_ANNOUNCED_PER_PEER {
+			let announced = &self.announced;
+			pending.retain(|hash| announced.contains_key(hash));
+		}
+
+		let mut new = 0;
+		for hash in hashes {
+			if self.known.contains(hash) {
+				continue;
+			}
+			if !pending.contains(hash) && pending.len() >= MAX_ANNOUNCED_PER_PEER {
+				trace!(target: "sync", "{}: Too many pending announcements, ignoring {:?}", peer_id, hash);
+				continue;
+			}
+			if let Some(peers) = self.announced.get_mut(hash) {
+				if !peers.contains(&peer_id) && peers.len() < MAX_ANNOUNCERS {
+					peers.push(peer_id);
+					pending.insert(*hash);
+				}
+				continue;
+			}
+			if self.announced.len() >= MAX_ANNOUNCED {
+				trace!(target: "sync", "{}: Too many announced transactions, ignoring {:?}", peer_id, hash);
+				continue;
+			}
+			self.announced.insert(*hash, vec![peer_id]);
+			self.queue.push_back(*hash);
+			pending.insert(*hash);
+			new += 1;
+		}
+
+		if !pending.is_empty() {
+			self.peer_announced.insert(peer_id, pending);
+		}
+		new
+	}
+
+	/// Assign waiting hashes to idle peers that announced them.
+	/// Returns the requests to send; they are considered in flight from `now`.
+	pub fn schedule(&mut self, now: Instant, max_per_request: usize) -> Vec<(PeerId, Vec<H256>)> {
+		let mut batches: HashMap<PeerId, Vec<H256>> = HashMap::new();
+		let mut waiting = VecDeque::new();
+		while let Some(hash) = self.queue.pop_front() {
+			if self.fetching.contains(&hash) {
+				continue;
+			}
+			let requests = &self.requests;
+			let peers = match self.announced.get_mut(&hash) {
+				Some(peers) => peers,
+				// delivered or dropped meanwhile
+				None => continue,
+			};
+			let available = peers.iter().position(|peer_id| {
+				!requests.contains_key(peer_id) && batches.get(peer_id).map_or(true, |b| b.len() < max_per_request)
+			});
+			match available {
+				Some(index) => {
+					let peer_id = peers.remove(index);
+					batches.entry(peer_id).or_insert_with(Vec::new).push(hash);
+					self.fetching.insert(hash);
+				},
+				None => waiting.push_back(hash),
+			}
+		}
+		self.queue = waiting;
+
+		batches.into_iter()
+			.map(|(peer_id, hashes)| {
+				self.requests.insert(peer_id, Request { hashes: hashes.clone(), sent: now });
+				(peer_id, hashes)
+			})
+			.collect()
+	}
+
+	/// Record transactions received from a peer. Hashes of the pending request to that peer
+	/// that weren't delivered are rescheduled for other announcers.
+	/// Returns the number of requested transactions the peer delivered.
+	pub fn delivered(&mut self, peer_id: PeerId, hashes: &[H256]) -> usize {
+		let request = self.requests.remove(&peer_id);
+		let requested: HashSet<H256> = request.map(|r| r.hashes.into_iter().collect()).unwrap_or_default();
+		let mut delivered = 0;
+		for hash in hashes {
+			if requested.contains(hash) {
+				delivered += 1;
+			}
+			self.mark_known(hash);
+		}
+		for hash in requested {
+			if !self.known.contains(&hash) {
+				self.retry(hash);
+			}
+		}
+		delivered
+	}
+
+	/// Reschedule requests that weren't answered in time. Returns the peers that timed out.
+	pub fn expire(&mut self, now: Instant) -> Vec<PeerId> {
+		let expired: Vec<PeerId> = self.requests.iter()
+			.filter(|&(_, request)| now.duration_since(request.sent) > FETCH_TIMEOUT)
+			.map(|(peer_id, _)| *peer_id)
+			.collect();
+		for peer_id in &expired {
+			let request = self.requests.remove(peer_id).expect("peer_id is from requests; qed");
+			for hash in request.hashes {
+				self.retry(hash);
+			}
+		}
+		expired
+	}
+
+	/// Forget a disconnected peer, rescheduling its pending request.
+	pub fn peer_disconnected(&mut self, peer_id: PeerId) {
+		self.peer_announced.remove(&peer_id);
+		for peers in self.announced.values_mut() {
+			peers.retain(|p| *p != peer_id);
+		}
+		if let Some(request) = self.requests.remove(&peer_id) {
+			for hash in request.hashes {
+				self.retry(hash);
+			}
+		}
+		// hashes announced only by this peer can't be fetched any more
+		let fetching = &self.fetching;
+		self.announced.retain(|hash, peers| !peers.is_empty() || fetching.contains(hash));
+	}
+
+	/// Mark a transaction as received, it won't be requested again.
+	pub fn mark_known(&mut self, hash: &H256) {
+		self.announced.remove(hash);
+		self.fetching.remove(hash);
+		if self.known.insert(*hash) {
+			self.known_order.push_back(*hash);
+			if self.known_order.len() > MAX_KNOWN {
+				let oldest = self.known_order.pop_front().expect("known_order is not empty; qed");
+				self.known.remove(&oldest);
+			}
+		}
+	}
+
+	/// Queue a hash that was not delivered again, or drop it if nobody else announced it.
+	fn retry(&mut self, hash: H256) {
+		self.fetching.remove(&hash);
+		let has_announcers = self.announced.get(&hash).map_or(false, |peers| !peers.is_empty());
+		if has_announcers {
+			self.queue.push_back(hash);
+		} else {
+			self.announced.remove(&hash);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn hashes(range: ::std::ops::Range<u64>) -> Vec<H256> {
+		range.map(H256::from).collect()
+	}
+
+	#[test]
+	fn requests_each_hash_once() {
+		let now = Instant::now();
+		let mut fetcher = TransactionFetcher::default();
+		assert_eq!(fetcher.announced(1, &hashes(1..4)), 3);
+		assert_eq!(fetcher.announced(2, &hashes(2..6)), 2);
+
+		let mut requests = fetcher.schedule(now, 256);
+		requests.sort();
+		assert_eq!(requests, vec![(1, hashes(1..4)), (2, hashes(4..6))]);
+		// nothing more to request while both peers are busy
+		assert_eq!(fetcher.schedule(now, 256), vec![]);
+	}
+
+	#[test]
+	fn limits_request_size() {
+		let now = Instant::now();
+		let mut fetcher = TransactionFetcher::default();
+		fetcher.announced(1, &hashes(1..6));
+
+		assert_eq!(fetcher.schedule(now, 2), vec![(1, hashes(1..3))]);
+		assert_eq!(fetcher.delivered(1, &hashes(1..3)), 2);
+		assert_eq!(fetcher.schedule(now, 2), vec![(1, hashes(3..5))]);
+	}
+
+	#[test]
+	fn rotates_undelivered_hashes() {
+		let now = Instant::now();
+		let mut fetcher = TransactionFetcher::default();
+		fetcher.announced(1, &hashes(1..3));
+		fetcher.announced(2, &hashes(1..3));
+
+		assert_eq!(fetcher.schedule(now, 256), vec![(1, hashes(1..3))]);
+		// peer 1 only had the first one, the second is asked from peer 2
+		assert_eq!(fetcher.delivered(1, &hashes(1..2)), 1);
+		assert_eq!(fetcher.schedule(now, 256), vec![(2, hashes(2..3))]);
+		// nobody else announced it
+		assert_eq!(fetcher.delivered(2, &[]), 0);
+		assert_eq!(fetcher.schedule(now, 256), vec![]);
+		assert!(fetcher.announced.is_empty());
+	}
+
+	#[test]
+	fn rotates_on_timeout() {
+		let now = Instant::now();
+		let mut fetcher = TransactionFetcher::default();
+		fetcher.announced(1, &hashes(1..2));
+		fetcher.announced(2, &hashes(1..2));
+
+		assert_eq!(fetcher.schedule(now, 256), vec![(1, hashes(1..2))]);
+		assert_eq!(fetcher.expire(now + FETCH_TIMEOUT), vec![]);
+		assert_eq!(fetcher.expire(now + FETCH_TIMEOUT + Duration::from_secs(1)), vec![1]);
+		assert_eq!(fetcher.schedule(now, 256), vec![(2, hashes(1..2))]);
+		// late response from peer 1 is still accepted
+		assert_eq!(fetcher.delivered(1, &hashes(1..2)), 0);
+		assert_eq!(fetcher.delivered(2, &[]), 0);
+		assert_eq!(fetcher.schedule(now, 256), vec![]);
+	}
+
+	#[test]
+	fn does_not_refetch_known_hashes() {
+		let now = Instant::now();
+		let mut fetcher = TransactionFetcher::default();
+		fetcher.announced(1, &hashes(1..3));
+		fetcher.schedule(now, 256);
+		fetcher.delivered(1, &hashes(1..3));
+		fetcher.mark_known(&3.into());
+
+		assert_eq!(fetcher.announced(2, &hashes(1..5)), 1);
+		assert_eq!(fetcher.schedule(now, 256), vec![(2, hashes(4..5))]);
+	}
+
+	#[test]
+	fn reschedules_on_disconnect() {
+		let now = Instant::now();
+		let mut fetcher = TransactionFetcher::default();
+		fetcher.announced(1, &hashes(1..3));
+		fetcher.announced(2, &hashes(1..2));
+		assert_eq!(fetcher.schedule(now, 256), vec![(1, hashes(1..3))]);
+
+		fetcher.peer_disconnected(1);
+		assert_eq!(fetcher.schedule(now, 256), vec![(2, hashes(1..2))]);
+		assert!(!fetcher.announced.contains_key(&2.into()));
+	}
+
+	#[test]
+	fn limits_pending_announcements_per_peer() {
+		let now = Instant::now();
+		let mut fetcher = TransactionFetcher::default();
+		let max = MAX_ANNOUNCED_PER_PEER as u64;
+		assert_eq!(fetcher.announced(1, &hashes(1..max + 2)), MAX_ANNOUNCED_PER_PEER);
+		assert_eq!(fetcher.announced(1, &hashes(max + 2..max + 3)), 0);
+		// other peers are not affected
+		assert_eq!(fetcher.announced(2, &hashes(max + 1..max + 3)), 2);
+
+		// fetched hashes make room for new announcements
+		fetcher.schedule(now, MAX_ANNOUNCED_PER_PEER);
+		assert_eq!(fetcher.delivered(1, &hashes(1..max + 1)), MAX_ANNOUNCED_PER_PEER);
+		assert_eq!(fetcher.announced(1, &hashes(max + 3..max + 4)), 1);
+	}
+}
@@ -0,0 +1,236 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EIP-2124 fork identifier.
+//!
+//! The fork id is a CRC32 checksum of the genesis hash and the numbers of all hard forks
+//! passed so far, together with the number of the next scheduled fork. It is exchanged
+//! in the eth/64 status packet so peers on an incompatible chain can be told apart
+//! without downloading any headers.
+
+use std::fmt;
+use ethereum_types::H256;
+use ethcore::header::BlockNumber;
+use rlp::{Rlp, RlpStream, Encodable, Decodable, DecoderError};
+
+/// Fork identifier as announced in the status packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForkId {
+	/// CRC32 checksum of the genesis hash and the passed fork blocks.
+	pub hash: u32,
+	/// Next scheduled fork block, `0` if none is known.
+	pub next: BlockNumber,
+}
+
+impl Encodable for ForkId {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		let hash = [(self.hash >> 24) as u8, (self.hash >> 16) as u8, (self.hash >> 8) as u8, self.hash as u8];
+		s.begin_list(2);
+		s.append(&&hash[..]);
+		s.append(&self.next);
+	}
+}
+
+impl Decodable for ForkId {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		let hash = rlp.at(0)?.data()?;
+		if hash.len() != 4 {
+			return Err(DecoderError::Custom("Fork hash must be 4 bytes long"));
+		}
+		Ok(ForkId {
+			hash: hash.iter().fold(0, |acc, b| (acc << 8) | *b as u32),
+			next: rlp.val_at(1)?,
+		})
+	}
+}
+
+/// Reason a remote fork id is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForkIdError {
+	/// Remote is on one of our past forks but doesn't know about the fork that followed it.
+	RemoteStale,
+	/// Remote passed a fork we don't know about, or we passed a fork the remote announced
+	/// for a different block.
+	LocalIncompatibleOrStale,
+}
+
+impl fmt::Display for ForkIdError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let msg = match *self {
+			ForkIdError::RemoteStale => "remote needs a software update for an upcoming fork",
+			ForkIdError::LocalIncompatibleOrStale => "remote is on an incompatible chain or we need a software update",
+		};
+		f.write_str(msg)
+	}
+}
+
+/// Computes the local fork id and validates fork ids of remote peers.
+pub struct ForkFilter {
+	/// Ordered fork blocks, without genesis.
+	forks: Vec<BlockNumber>,
+	/// `hashes[i]` is the checksum after passing the first `i` forks.
+	hashes: Vec<u32>,
+}
+
+impl ForkFilter {
+	/// Create a new filter from the genesis hash and ordered fork blocks of the chain.
+	pub fn new(genesis: H256, mut forks: Vec<BlockNumber>) -> Self {
+		forks.retain(|&n| n != 0);
+		forks.sort();
+		forks.dedup();
+
+		let mut hashes = Vec::with_capacity(forks.len() + 1);
+		let mut hash = crc32(0, &genesis);
+		hashes.push(hash);
+		for fork in &forks {
+			let number = [
+				(fork >> 56) as u8, (fork >> 48) as u8, (fork >> 40) as u8, (fork >> 32) as u8,
+				(fork >> 24) as u8, (fork >> 16) as u8, (fork >> 8) as u8, *fork as u8,
+			];
+			hash = crc32(hash, &number);
+			hashes.push(hash);
+		}
+		ForkFilter { forks, hashes }
+	}
+
+	/// Index of the last fork passed at `head`.
+	fn index(&self, head: BlockNumber) -> usize {
+		self.forks.iter().take_while(|&&fork| fork <= head).count()
+	}
+
+	/// Fork id to announce with the chain at `head`.
+	pub fn current(&self, head: BlockNumber) -> ForkId {
+		let index = self.index(head);
+		ForkId {
+			hash: self.hashes[index],
+			next: self.forks.get(index).cloned().unwrap_or(0),
+		}
+	}
+
+	/// Validate a remote fork id against the local chain at `head` using the EIP-2124 rules.
+	pub fn validate(&self, head: BlockNumber, remote: &ForkId) -> Result<(), ForkIdError> {
+		let index = self.index(head);
+
+		// Same fork: compatible unless the remote announced a fork we already passed.
+		if remote.hash == self.hashes[index] {
+			if remote.next != 0 && head >= remote.next {
+				return Err(ForkIdError::LocalIncompatibleOrStale);
+			}
+			return Ok(());
+		}
+		// Remote is behind: it must know about the fork that followed its current one.
+		if let Some(past) = self.hashes[..index].iter().position(|&hash| hash == remote.hash) {
+			if self.forks[past] != remote.next {
+				return Err(ForkIdError::RemoteStale);
+			}
+			return Ok(());
+		}
+		// Remote is ahead on a fork we know about, we're still syncing.
+		if self.hashes[index + 1..].iter().any(|&hash| hash == remote.hash) {
+			return Ok(());
+		}
+		Err(ForkIdError::LocalIncompatibleOrStale)
+	}
+}
+
+/// Update a CRC32 (IEEE) checksum with `data`.
+fn crc32(crc: u32, data: &[u8]) -> u32 {
+	let mut crc = !crc;
+	for byte in data {
+		crc ^= *byte as u32;
+		for _ in 0..8 {
+			crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+		}
+	}
+	!crc
+}
+
+#[cfg(test)]
+mod tests {
+	use rlp;
+	use super::*;
+
+	fn mainnet() -> ForkFilter {
+		let genesis = "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3".into();
+		ForkFilter::new(genesis, vec![1150000, 1920000, 2463000, 2675000, 4370000, 7280000, 7280000])
+	}
+
+	#[test]
+	fn crc32_checksum() {
+		assert_eq!(crc32(0, b""), 0);
+		assert_eq!(crc32(0, b"123456789"), 0xcbf4_3926);
+		assert_eq!(crc32(crc32(0, b"12345"), b"6789"), 0xcbf4_3926);
+	}
+
+	#[test]
+	fn mainnet_fork_ids() {
+		let filter = mainnet();
+		let expected = [
+			(0, 0xfc64ec04, 1150000),
+			(1149999, 0xfc64ec04, 1150000),
+			(1150000, 0x97c2c34c, 1920000),
+			(1919999, 0x97c2c34c, 1920000),
+			(1920000, 0x91d1f948, 2463000),
+			(2463000, 0x7a64da13, 2675000),
+			(2675000, 0x3edd5b10, 4370000),
+			(4370000, 0xa00bc324, 7280000),
+			(7279999, 0xa00bc324, 7280000),
+			(7280000, 0x668db0af, 0),
+			(7987396, 0x668db0af, 0),
+		];
+		for &(head, hash, next) in &expected {
+			assert_eq!(filter.current(head), ForkId { hash, next }, "head {}", head);
+		}
+	}
+
+	#[test]
+	fn mainnet_validation() {
+		let filter = mainnet();
+		let id = |hash, next| ForkId { hash, next };
+		let incompatible = Err(ForkIdError::LocalIncompatibleOrStale);
+
+		// same fork, with or without knowledge of a future one
+		assert_eq!(filter.validate(7987396, &id(0x668db0af, 0)), Ok(()));
+		assert_eq!(filter.validate(7987396, &id(0x668db0af, 0xffffffff)), Ok(()));
+		assert_eq!(filter.validate(7279999, &id(0xa00bc324, 0)), Ok(()));
+		assert_eq!(filter.validate(7279999, &id(0xa00bc324, 7280000)), Ok(()));
+		// remote is syncing and knows about the next fork
+		assert_eq!(filter.validate(7987396, &id(0xa00bc324, 7280000)), Ok(()));
+		assert_eq!(filter.validate(7987396, &id(0x3edd5b10, 4370000)), Ok(()));
+		// we are syncing
+		assert_eq!(filter.validate(7279999, &id(0x668db0af, 0)), Ok(()));
+		assert_eq!(filter.validate(4369999, &id(0xa00bc324, 0)), Ok(()));
+		// remote doesn't know about a fork we passed
+		assert_eq!(filter.validate(7987396, &id(0xa00bc324, 0)), Err(ForkIdError::RemoteStale));
+		// remote passed a fork we don't know
+		assert_eq!(filter.validate(7987396, &id(0x5cddc0e1, 0)), incompatible);
+		assert_eq!(filter.validate(7279999, &id(0x5cddc0e1, 0)), incompatible);
+		assert_eq!(filter.validate(4369999, &id(0xafec6b27, 0)), incompatible);
+		// remote announced a fork at a block we already passed
+		assert_eq!(filter.validate(88888888, &id(0x668db0af, 88888888)), incompatible);
+		assert_eq!(filter.validate(7279999, &id(0xa00bc324, 7279999)), incompatible);
+	}
+
+	#[test]
+	fn fork_id_rlp() {
+		let id = ForkId { hash: 0xdeadbeef, next: 0xbaddcafe };
+		let encoded = rlp::encode(&id).into_vec();
+		assert_eq!(encoded, vec![0xca, 0x84, 0xde, 0xad, 0xbe, 0xef, 0x84, 0xba, 0xdd, 0xca, 0xfe]);
+		assert_eq!(rlp::decode::<ForkId>(&encoded), Ok(id));
+		assert_eq!(rlp::encode(&ForkId { hash: 0, next: 0 }).into_vec(), vec![0xc6, 0x84, 0, 0, 0, 0, 0x80]);
+		assert!(rlp::decode::<ForkId>(&[0xc5, 0x83, 0, 0, 0, 0x80]).is_err());
+	}
+}
@@ -16,24 +16,31 @@
 
 use api::WARP_SYNC_PROTOCOL_ID;
 use block_sync::{BlockDownloaderImportError as DownloaderImportError, DownloadAction};
+use blocks::{SyncBody, SyncHeader};
 use bytes::Bytes;
-use ethcore::client::{BlockId, BlockStatus};
+use ethcore::client::{BlockId, BlockStatus, EngineInfo};
 use ethcore::error::{Error as EthcoreError, ErrorKind as EthcoreErrorKind, ImportErrorKind, BlockError};
 use ethcore::header::BlockNumber;
 use ethcore::snapshot::{ManifestData, RestorationStatus};
 use ethcore::verification::queue::kind::blocks::Unverified;
+use ethcore::verification::verify_header_params;
 use ethereum_types::{H256, U256};
 use hash::keccak;
 use network::PeerId;
 use rlp::Rlp;
 use snapshot::ChunkType;
+use state_sync::StateRequest;
 use std::cmp;
 use std::mem;
 use std::collections::HashSet;
 use std::time::Instant;
 use sync_io::SyncIo;
+use transaction::UnverifiedTransaction;
 
+use super::fork_filter::ForkId;
 use super::{
+	has_request_id,
+	split_request_id,
 	BlockSet,
 	ChainSync,
 	ForkConfirmation,
@@ -43,20 +50,27 @@ use super::{
 	SyncRequester,
 	SyncState,
 	ETH_PROTOCOL_VERSION_62,
-	ETH_PROTOCOL_VERSION_63,
+	ETH_PROTOCOL_VERSION_64,
+	ETH_PROTOCOL_VERSION_66,
 	MAX_NEW_BLOCK_AGE,
 	MAX_NEW_HASHES,
+	MAX_TRANSACTION_HASHES_TO_ANNOUNCE,
 	PAR_PROTOCOL_VERSION_1,
-	PAR_PROTOCOL_VERSION_3,
+	PAR_PROTOCOL_VERSION_4,
+	SNAPSHOT_RESTORE_THRESHOLD,
 	BLOCK_BODIES_PACKET,
 	BLOCK_HEADERS_PACKET,
 	NEW_BLOCK_HASHES_PACKET,
 	NEW_BLOCK_PACKET,
+	NEW_POOLED_TRANSACTION_HASHES_PACKET,
+	POOLED_TRANSACTIONS_PACKET,
 	PRIVATE_TRANSACTION_PACKET,
 	RECEIPTS_PACKET,
 	SIGNED_PRIVATE_TRANSACTION_PACKET,
 	SNAPSHOT_DATA_PACKET,
 	SNAPSHOT_MANIFEST_PACKET,
+	STATE_NODES_PACKET,
+	STATE_RANGE_PACKET,
 	STATUS_PACKET,
 	TRANSACTIONS_PACKET,
 };
@@ -72,9 +86,33 @@ impl SyncHandler {
 			return;
 		}
 		let rlp = Rlp::new(data);
+		let rlp = if has_request_id(io, peer, packet_id) {
+			match split_request_id(&rlp) {
+				// only the pending block request is tracked, transactions are matched by hash
+				Ok((request_id, payload)) => {
+					let expected = packet_id == POOLED_TRANSACTIONS_PACKET
+						|| sync.peers.get(&peer).map_or(false, |p| p.request_id == Some(request_id));
+					if !expected {
+						debug!(target: "sync", "{}: Ignored response {} to unknown request {}", peer, packet_id, request_id);
+						return;
+					}
+					payload
+				},
+				Err(e) => {
+					debug!(target: "sync", "{} -> Malformed packet {} : {}", peer, packet_id, e);
+					io.disable_peer(peer);
+					sync.deactivate_peer(io, peer);
+					return;
+				},
+			}
+		} else {
+			rlp
+		};
 		let result = match packet_id {
 			STATUS_PACKET => SyncHandler::on_peer_status(sync, io, peer, &rlp),
 			TRANSACTIONS_PACKET => SyncHandler::on_peer_transactions(sync, io, peer, &rlp),
+			POOLED_TRANSACTIONS_PACKET => SyncHandler::on_peer_pooled_transactions(sync, io, peer, &rlp),
+			NEW_POOLED_TRANSACTION_HASHES_PACKET => SyncHandler::on_peer_new_pooled_transaction_hashes(sync, io, peer, &rlp),
 			BLOCK_HEADERS_PACKET => SyncHandler::on_peer_block_headers(sync, io, peer, &rlp),
 			BLOCK_BODIES_PACKET => SyncHandler::on_peer_block_bodies(sync, io, peer, &rlp),
 			RECEIPTS_PACKET => SyncHandler::on_peer_block_receipts(sync, io, peer, &rlp),
@@ -82,6 +120,8 @@ impl SyncHandler {
 			NEW_BLOCK_HASHES_PACKET => SyncHandler::on_peer_new_hashes(sync, io, peer, &rlp),
 			SNAPSHOT_MANIFEST_PACKET => SyncHandler::on_snapshot_manifest(sync, io, peer, &rlp),
 			SNAPSHOT_DATA_PACKET => SyncHandler::on_snapshot_data(sync, io, peer, &rlp),
+			STATE_RANGE_PACKET => SyncHandler::on_state_range(sync, io, peer, &rlp),
+			STATE_NODES_PACKET => SyncHandler::on_state_nodes(sync, io, peer, &rlp),
 			PRIVATE_TRANSACTION_PACKET => SyncHandler::on_private_transaction(sync, io, peer, &rlp),
 			SIGNED_PRIVATE_TRANSACTION_PACKET => SyncHandler::on_signed_private_transaction(sync, io, peer, &rlp),
 			_ => {
@@ -122,8 +162,11 @@ impl SyncHandler {
 		if sync.peers.contains_key(&peer_id) {
 			debug!(target: "sync", "Disconnected {}", peer_id);
 			sync.clear_peer_download(peer_id);
-			sync.peers.remove(&peer_id);
+			// the session may already be gone, so the node id recorded on status is used
+			let id = sync.peers.remove(&peer_id).and_then(|peer| peer.id);
 			sync.active_peers.remove(&peer_id);
+			sync.transaction_fetcher.peer_disconnected(peer_id);
+			sync.transactions_stats.peer_disconnected(id);
 
 			if sync.state == SyncState::SnapshotManifest {
 				// Check if we are asking other peers for
@@ -159,13 +202,14 @@ impl SyncHandler {
 			return Ok(());
 		}
 		let difficulty: U256 = r.val_at(1)?;
+		let block = Unverified::from_rlp(r.at(0)?.as_raw().to_vec(), sync.eip1559_transition)?;
+		let hash = block.header.hash();
 		if let Some(ref mut peer) = sync.peers.get_mut(&peer_id) {
 			if peer.difficulty.map_or(true, |pd| difficulty > pd) {
 				peer.difficulty = Some(difficulty);
+				peer.difficulty_hash = Some(hash);
 			}
 		}
-		let block = Unverified::from_rlp(r.at(0)?.as_raw().to_vec())?;
-		let hash = block.header.hash();
 		let number = block.header.number();
 		trace!(target: "sync", "{} -> NewBlock ({})", peer_id, hash);
 		if number > sync.highest_block.unwrap_or(0) {
@@ -226,6 +270,7 @@ impl SyncHandler {
 		if let Some(ref mut peer) = sync.peers.get_mut(&peer_id) {
 			// Peer has new blocks with unknown difficulty
 			peer.difficulty = None;
+			peer.difficulty_hash = None;
 			if let Some(&(Ok(ref h), _)) = hashes.last() {
 				peer.latest_hash = h.clone();
 			}
@@ -289,6 +334,9 @@ impl SyncHandler {
 
 	/// Called by peer once it has new block bodies
 	fn on_peer_block_bodies(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
+		if SyncHandler::is_state_response(sync, peer_id) {
+			return SyncHandler::on_state_pivot_body(sync, io, peer_id, r);
+		}
 		sync.clear_peer_download(peer_id);
 		let block_set = sync.peers.get(&peer_id)
 			.and_then(|p| p.block_set)
@@ -336,6 +384,7 @@ impl SyncHandler {
 		{
 			let peer = sync.peers.get_mut(&peer_id).expect("Is only called when peer is present in peers");
 			peer.asking = PeerAsking::Nothing;
+			peer.request_id = None;
 			let item_count = r.item_count()?;
 			let (fork_number, fork_hash) = sync.fork_block.expect("ForkHeader request is sent only fork block is Some; qed").clone();
 
@@ -373,6 +422,9 @@ impl SyncHandler {
 		if is_fork_header_request {
 			return SyncHandler::on_peer_fork_header(sync, io, peer_id, r);
 		}
+		if SyncHandler::is_state_response(sync, peer_id) {
+			return SyncHandler::on_state_pivot_headers(sync, io, peer_id, r);
+		}
 
 		sync.clear_peer_download(peer_id);
 		let expected_hash = sync.peers.get(&peer_id).and_then(|p| p.asking_hash);
@@ -432,6 +484,9 @@ impl SyncHandler {
 
 	/// Called by peer once it has new block receipts
 	fn on_peer_block_receipts(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
+		if SyncHandler::is_state_response(sync, peer_id) {
+			return SyncHandler::on_state_pivot_receipts(sync, io, peer_id, r);
+		}
 		sync.clear_peer_download(peer_id);
 		let block_set = sync.peers.get(&peer_id).and_then(|p| p.block_set).unwrap_or(BlockSet::NewBlocks);
 		let allowed = sync.peers.get(&peer_id).map(|p| p.is_allowed()).unwrap_or(false);
@@ -563,6 +618,151 @@ impl SyncHandler {
 		Ok(())
 	}
 
+	/// Check if the peer is answering a state sync request.
+	fn is_state_response(sync: &ChainSync, peer_id: PeerId) -> bool {
+		sync.peers.get(&peer_id).map_or(false, |p| p.asking == PeerAsking::StateData)
+	}
+
+	/// Take the state sync request the peer is answering, if the response is still expected.
+	fn take_state_request(sync: &mut ChainSync, peer_id: PeerId) -> Option<StateRequest> {
+		let allowed = sync.peers.get(&peer_id).map_or(false, |p| p.can_sync());
+		sync.clear_peer_download(peer_id);
+		let request = sync.peers.get_mut(&peer_id).and_then(|p| p.asking_state.take());
+		if !sync.reset_peer_asking(peer_id, PeerAsking::StateData) || !allowed || sync.state != SyncState::StateSync {
+			trace!(target: "sync", "{}: Ignored unexpected state data", peer_id);
+			return None;
+		}
+		request
+	}
+
+	/// Called when headers to choose or confirm the state sync pivot are downloaded from a peer.
+	fn on_state_pivot_headers(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
+		let request = match SyncHandler::take_state_request(sync, peer_id) {
+			Some(request @ StateRequest::PivotHeaders(..)) => request,
+			Some(request @ StateRequest::ConfirmPivot(_)) => request,
+			_ => return Ok(()),
+		};
+		let headers = r.iter()
+			.map(|item| SyncHeader::from_rlp(item.as_raw().to_vec(), sync.eip1559_transition))
+			.collect::<Result<Vec<_>, _>>()?;
+		trace!(target: "sync", "{} -> BlockHeaders ({} entries) for the state sync pivot", peer_id, headers.len());
+		SyncHandler::verify_state_pivot_headers(io, &headers)?;
+
+		match request {
+			StateRequest::ConfirmPivot(hash) => {
+				let result = sync.state_sync.on_pivot_confirmation(peer_id, &hash, headers);
+				if sync.state_sync.is_confirming_pivot() && sync.state_sync.pivot_answers() >= sync.get_state_sync_peers(io).len() {
+					trace!(target: "sync", "State sync pivot {} is not known to enough peers", hash);
+					sync.state_sync.reject_pivot();
+				}
+				result
+			},
+			StateRequest::PivotHeaders(latest, latest_difficulty) => {
+				sync.state_sync.on_pivot_headers(peer_id, &latest, latest_difficulty, headers)?;
+
+				let best_block = io.chain().chain_info().best_block_number;
+				if sync.state_sync.pivot_number().map_or(false, |n| n <= best_block + SNAPSHOT_RESTORE_THRESHOLD) {
+					trace!(target: "sync", "State sync pivot is too close to the best block, not syncing state");
+					// the restoration is underway if the pivot has been moved
+					io.snapshot_service().abort_restore();
+					sync.state_sync.clear();
+					sync.state = if sync.warp_sync.is_warp_only() { SyncState::WaitingPeers } else { SyncState::Idle };
+				}
+				Ok(())
+			},
+			_ => Ok(()),
+		}
+	}
+
+	/// Check the seal and the difficulty of headers downloaded for the state sync pivot, newest first.
+	fn verify_state_pivot_headers(io: &SyncIo, headers: &[SyncHeader]) -> Result<(), DownloaderImportError> {
+		let engine = io.chain().engine();
+		for (i, header) in headers.iter().enumerate() {
+			let header = &header.header;
+			let result = verify_header_params(header, engine, false)
+				.and_then(|_| engine.verify_block_basic(header))
+				.and_then(|_| engine.verify_block_unordered(header))
+				.and_then(|_| match headers.get(i + 1) {
+					Some(parent) => engine.verify_block_family(header, &parent.header),
+					None => Ok(()),
+				});
+			if let Err(e) = result {
+				debug!(target: "sync", "Bad state sync pivot header #{}: {}", header.number(), e);
+				return Err(DownloaderImportError::Invalid);
+			}
+		}
+		Ok(())
+	}
+
+	/// Called when the body of the state sync pivot is downloaded from a peer.
+	fn on_state_pivot_body(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
+		match SyncHandler::take_state_request(sync, peer_id) {
+			Some(StateRequest::PivotBody(_)) => (),
+			_ => return Ok(()),
+		}
+		if r.item_count()? != 1 {
+			return Err(DownloaderImportError::Useless);
+		}
+		trace!(target: "sync", "{} -> BlockBodies for the state sync pivot", peer_id);
+		let body = SyncBody::from_rlp(r.at(0)?.as_raw(), sync.eip1559_transition)?;
+		sync.state_sync.on_pivot_body(io.snapshot_service(), body)
+	}
+
+	/// Called when the receipts of the state sync pivot are downloaded from a peer.
+	fn on_state_pivot_receipts(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
+		match SyncHandler::take_state_request(sync, peer_id) {
+			Some(StateRequest::PivotReceipts(_)) => (),
+			_ => return Ok(()),
+		}
+		if r.item_count()? != 1 {
+			return Err(DownloaderImportError::Useless);
+		}
+		trace!(target: "sync", "{} -> BlockReceipts for the state sync pivot", peer_id);
+		sync.state_sync.on_pivot_receipts(io.snapshot_service(), r.at(0)?.as_raw().to_vec())
+	}
+
+	/// Called when a state range is downloaded from a peer.
+	fn on_state_range(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
+		let (block, account, origin) = match SyncHandler::take_state_request(sync, peer_id) {
+			Some(StateRequest::Range { block, account, origin }) => (block, account, origin),
+			_ => return Ok(()),
+		};
+		let range = if r.is_empty() { None } else { Some(r.as_val()?) };
+		trace!(target: "sync", "{} -> StateRange {:?} from {}", peer_id, account, origin);
+
+		let result = sync.state_sync.on_range(io.snapshot_service(), peer_id, &block, account, &origin, range);
+		if result == Err(DownloaderImportError::Useless) && sync.state_sync.unavailable_peers() >= sync.get_state_sync_peers(io).len() {
+			// no peer keeps the state of the pivot any more
+			sync.state_sync.move_pivot();
+			sync.active_peers = sync.peers.keys().cloned().collect();
+			return Ok(());
+		}
+		result?;
+
+		if sync.state_sync.is_complete() {
+			// wait for the pivot block to be restored
+			sync.state = SyncState::SnapshotWaiting;
+		}
+		Ok(())
+	}
+
+	/// Called when state nodes are downloaded from a peer.
+	fn on_state_nodes(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
+		let requested = match SyncHandler::take_state_request(sync, peer_id) {
+			Some(StateRequest::Nodes(requested)) => requested,
+			_ => return Ok(()),
+		};
+		let nodes: Vec<Bytes> = r.as_list()?;
+		trace!(target: "sync", "{} -> StateNodes ({} entries)", peer_id, nodes.len());
+		sync.state_sync.on_nodes(io.snapshot_service(), &requested, nodes)?;
+
+		if sync.state_sync.is_complete() {
+			// wait for the pivot block to be restored
+			sync.state = SyncState::SnapshotWaiting;
+		}
+		Ok(())
+	}
+
 	/// Called by peer to report status
 	fn on_peer_status(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
 		sync.handshaking_peers.remove(&peer_id);
@@ -572,6 +772,7 @@ impl SyncHandler {
 			protocol_version: protocol_version,
 			network_id: r.val_at(1)?,
 			difficulty: Some(r.val_at(2)?),
+			difficulty_hash: Some(r.val_at(3)?),
 			latest_hash: r.val_at(3)?,
 			genesis: r.val_at(4)?,
 			asking: PeerAsking::Nothing,
@@ -583,9 +784,17 @@ impl SyncHandler {
 			expired: false,
 			confirmation: if sync.fork_block.is_none() { ForkConfirmation::Confirmed } else { ForkConfirmation::Unconfirmed },
 			asking_snapshot_data: None,
+			asking_state: None,
 			snapshot_hash: if warp_protocol { Some(r.val_at(5)?) } else { None },
 			snapshot_number: if warp_protocol { Some(r.val_at(6)?) } else { None },
 			block_set: None,
+			request_id: None,
+			id: io.peer_session_info(peer_id).and_then(|info| info.id),
+		};
+		let fork_id: Option<ForkId> = if io.eth_protocol_version(peer_id) >= ETH_PROTOCOL_VERSION_64.0 {
+			Some(r.val_at(if warp_protocol { 7 } else { 5 })?)
+		} else {
+			None
 		};
 
 		trace!(target: "sync", "New peer {} (protocol: {}, network: {:?}, difficulty: {:?}, latest:{}, genesis:{}, snapshot:{:?})",
@@ -608,10 +817,17 @@ impl SyncHandler {
 			trace!(target: "sync", "Peer {} network id mismatch (ours: {}, theirs: {})", peer_id, sync.network_id, peer.network_id);
 			return Err(DownloaderImportError::Invalid);
 		}
+		if let Some(fork_id) = fork_id {
+			if let Err(e) = sync.fork_filter.validate(chain_info.best_block_number, &fork_id) {
+				debug!(target: "sync", "Peer {} fork id mismatch (ours: {:?}, theirs: {:?}): {}",
+					peer_id, sync.fork_filter.current(chain_info.best_block_number), fork_id, e);
+				return Err(DownloaderImportError::Invalid);
+			}
+		}
 
 		if false
-			|| (warp_protocol && (peer.protocol_version < PAR_PROTOCOL_VERSION_1.0 || peer.protocol_version > PAR_PROTOCOL_VERSION_3.0))
-			|| (!warp_protocol && (peer.protocol_version < ETH_PROTOCOL_VERSION_62.0 || peer.protocol_version > ETH_PROTOCOL_VERSION_63.0))
+			|| (warp_protocol && (peer.protocol_version < PAR_PROTOCOL_VERSION_1.0 || peer.protocol_version > PAR_PROTOCOL_VERSION_4.0))
+			|| (!warp_protocol && (peer.protocol_version < ETH_PROTOCOL_VERSION_62.0 || peer.protocol_version > ETH_PROTOCOL_VERSION_66.0))
 		{
 			trace!(target: "sync", "Peer {} unsupported eth protocol ({})", peer_id, peer.protocol_version);
 			return Err(DownloaderImportError::Invalid);
@@ -634,15 +850,22 @@ impl SyncHandler {
 		Ok(())
 	}
 
-	/// Called when peer sends us new transactions
-	fn on_peer_transactions(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
-		// Accept transactions only when fully synced
+	/// Checks if transactions from the peer should be imported, only done when fully synced
+	fn accepts_transactions(sync: &ChainSync, io: &SyncIo, peer_id: PeerId) -> bool {
 		if !io.is_chain_queue_empty() || (sync.state != SyncState::Idle && sync.state != SyncState::NewBlocks) {
 			trace!(target: "sync", "{} Ignoring transactions while syncing", peer_id);
-			return Ok(());
+			return false;
 		}
 		if !sync.peers.get(&peer_id).map_or(false, |p| p.can_sync()) {
 			trace!(target: "sync", "{} Ignoring transactions from unconfirmed/unknown peer", peer_id);
+			return false;
+		}
+		true
+	}
+
+	/// Called when peer sends us new transactions
+	fn on_peer_transactions(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
+		if !SyncHandler::accepts_transactions(sync, io, peer_id) {
 			return Ok(());
 		}
 
@@ -651,13 +874,67 @@ impl SyncHandler {
 		let mut transactions = Vec::with_capacity(item_count);
 		for i in 0 .. item_count {
 			let rlp = r.at(i)?;
-			let tx = rlp.as_raw().to_vec();
+			let tx = UnverifiedTransaction::envelope_from_rlp(&rlp).to_vec();
+			// no need to fetch it if someone announces it later
+			sync.transaction_fetcher.mark_known(&keccak(&tx));
 			transactions.push(tx);
 		}
 		io.chain().queue_transactions(transactions, peer_id);
 		Ok(())
 	}
 
+	/// Called when peer responds to our request for announced transactions
+	fn on_peer_pooled_transactions(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
+		let item_count = r.item_count()?;
+		let mut hashes = Vec::with_capacity(item_count);
+		let mut transactions = Vec::with_capacity(item_count);
+		for i in 0 .. item_count {
+			let rlp = r.at(i)?;
+			let tx = UnverifiedTransaction::envelope_from_rlp(&rlp).to_vec();
+			hashes.push(keccak(&tx));
+			transactions.push(tx);
+		}
+
+		// settle the request even if we can't import the transactions now
+		let delivered = sync.transaction_fetcher.delivered(peer_id, &hashes);
+		trace!(target: "sync", "{:02} -> PooledTransactions ({} entries, {} requested)", peer_id, item_count, delivered);
+		let id = io.peer_session_info(peer_id).and_then(|info| info.id);
+		sync.transactions_stats.delivered(id, delivered);
+		SyncRequester::request_announced_transactions(sync, io);
+
+		if SyncHandler::accepts_transactions(sync, io, peer_id) {
+			io.chain().queue_transactions(transactions, peer_id);
+		}
+		Ok(())
+	}
+
+	/// Called when peer announces transactions, schedules fetching of the ones we don't have yet
+	fn on_peer_new_pooled_transaction_hashes(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
+		if !SyncHandler::accepts_transactions(sync, io, peer_id) {
+			return Ok(());
+		}
+
+		let hashes = r.iter()
+			.take(MAX_TRANSACTION_HASHES_TO_ANNOUNCE)
+			.map(|item| item.as_val())
+			.collect::<Result<Vec<H256>, _>>()?;
+		let item_count = hashes.len();
+		let unknown = hashes.iter()
+			.filter(|hash| io.chain().pooled_transaction(hash).is_none())
+			.cloned()
+			.collect::<Vec<_>>();
+		// the peer has them, no need to announce them back
+		if let Some(peer_info) = sync.peers.get_mut(&peer_id) {
+			peer_info.last_sent_transactions.extend(hashes);
+		}
+		let new = sync.transaction_fetcher.announced(peer_id, &unknown);
+		trace!(target: "sync", "{:02} -> NewPooledTransactionHashes ({} entries, {} new)", peer_id, item_count, new);
+		let id = io.peer_session_info(peer_id).and_then(|info| info.id);
+		sync.transactions_stats.announced_by(id, item_count);
+		SyncRequester::request_announced_transactions(sync, io);
+		Ok(())
+	}
+
 	/// Called when peer sends us signed private transaction packet
 	fn on_signed_private_transaction(sync: &mut ChainSync, _io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
 		if !sync.peers.get(&peer_id).map_or(false, |p| p.can_sync()) {
@@ -708,14 +985,16 @@ impl SyncHandler {
 mod tests {
 	use ethcore::client::{ChainInfo, EachBlockWith, TestBlockChainClient};
 	use parking_lot::RwLock;
-	use rlp::{Rlp};
+	use rlp::{Rlp, RlpStream};
 	use std::collections::{VecDeque};
 	use tests::helpers::{TestIo};
 	use tests::snapshot::TestSnapshotService;
 
 	use super::*;
+	use super::super::{with_request_id, ETH_PROTOCOL_VERSION_65, GET_POOLED_TRANSACTIONS_PACKET};
 	use super::super::tests::{
 		dummy_sync_with_peer,
+		insert_dummy_peer,
 		get_dummy_block,
 		get_dummy_blocks,
 		get_dummy_hashes,
@@ -808,4 +1087,67 @@ mod tests {
 
 		assert!(result.is_ok());
 	}
+
+	#[test]
+	fn matches_responses_by_request_id() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		{
+			let peer = sync.peers.get_mut(&0).unwrap();
+			peer.asking = PeerAsking::BlockHeaders;
+			peer.request_id = Some(7);
+		}
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		io.eth_protocol_version = ETH_PROTOCOL_VERSION_66.0;
+		let headers = RlpStream::new_list(0).out();
+
+		SyncHandler::on_packet(&mut sync, &mut io, 0, BLOCK_HEADERS_PACKET, &with_request_id(8, &headers));
+		assert_eq!(sync.peers[&0].asking, PeerAsking::BlockHeaders);
+		assert_eq!(sync.peers[&0].request_id, Some(7));
+
+		SyncHandler::on_packet(&mut sync, &mut io, 0, BLOCK_HEADERS_PACKET, &with_request_id(7, &headers));
+		assert!(sync.peers[&0].request_id != Some(7));
+	}
+
+	#[test]
+	fn fetches_announced_transactions() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		insert_dummy_peer(&mut sync, 1, client.block_hash_delta_minus(5));
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		io.eth_protocol_version = ETH_PROTOCOL_VERSION_65.0;
+		let hashes = |hashes: &[H256]| {
+			let mut rlp = RlpStream::new_list(hashes.len());
+			for h in hashes {
+				rlp.append(h);
+			}
+			rlp.out()
+		};
+		let (h1, h2) = (H256::from(1), H256::from(2));
+
+		SyncHandler::on_peer_new_pooled_transaction_hashes(&mut sync, &mut io, 0, &Rlp::new(&hashes(&[h1, h2]))).unwrap();
+		// already being fetched from the first peer
+		SyncHandler::on_peer_new_pooled_transaction_hashes(&mut sync, &mut io, 1, &Rlp::new(&hashes(&[h1]))).unwrap();
+		assert_eq!(io.packets.len(), 1);
+		assert_eq!(io.packets[0].recipient, 0);
+		assert_eq!(io.packets[0].packet_id, GET_POOLED_TRANSACTIONS_PACKET);
+		assert_eq!(io.packets[0].data, hashes(&[h1, h2]));
+
+		// the first peer doesn't have them any more, ask the other announcer
+		SyncHandler::on_peer_pooled_transactions(&mut sync, &mut io, 0, &Rlp::new(&hashes(&[]))).unwrap();
+		assert_eq!(io.packets.len(), 2);
+		assert_eq!(io.packets[1].recipient, 1);
+		assert_eq!(io.packets[1].data, hashes(&[h1]));
+
+		let stats = sync.transactions_stats.peer(&Default::default()).unwrap();
+		assert_eq!(stats.announced_by, 3);
+		assert_eq!(stats.requested, 3);
+		assert_eq!(stats.delivered, 0);
+	}
 }
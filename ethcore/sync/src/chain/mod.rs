@@ -16,7 +16,7 @@
 
 //! `BlockChain` synchronization strategy.
 //! Syncs to peers and keeps up to date.
-//! This implementation uses ethereum protocol v63
+//! This implementation uses ethereum protocol v62 - v66
 //!
 //! Syncing strategy summary.
 //! Split the chain into ranges of N blocks each. Download ranges sequentially. Split each range into subchains of M blocks. Download subchains in parallel.
@@ -87,6 +87,8 @@
 //!
 //! All other messages are ignored.
 
+mod fetcher;
+mod fork_filter;
 mod handler;
 mod propagator;
 mod requester;
@@ -103,7 +105,7 @@ use fastmap::H256FastMap;
 use parking_lot::RwLock;
 use bytes::Bytes;
 use rlp::{Rlp, RlpStream, DecoderError};
-use network::{self, PeerId, PacketId};
+use network::{self, PeerId, PacketId, NodeId};
 use ethcore::header::{BlockNumber};
 use ethcore::client::{BlockChainClient, BlockStatus, BlockId, BlockChainInfo, BlockQueueInfo};
 use ethcore::snapshot::{RestorationStatus};
@@ -112,11 +114,14 @@ use super::{WarpSync, SyncConfig};
 use block_sync::{BlockDownloader, DownloadAction};
 use rand::Rng;
 use snapshot::{Snapshot};
+use state_sync::{StateSync, StateRequest, PIVOT_MIN_PEERS};
 use api::{EthProtocolInfo as PeerInfoDigest, WARP_SYNC_PROTOCOL_ID};
 use private_tx::PrivateTxHandler;
 use transactions_stats::{TransactionsStats, Stats as TransactionStats};
 use transaction::UnverifiedTransaction;
 
+use self::fetcher::TransactionFetcher;
+use self::fork_filter::ForkFilter;
 use self::handler::SyncHandler;
 use self::propagator::SyncPropagator;
 use self::requester::SyncRequester;
@@ -126,6 +131,12 @@ known_heap_size!(0, PeerInfo);
 
 pub type PacketDecodeError = DecoderError;
 
+/// 66 version of Ethereum protocol (request ids in requests and responses).
+pub const ETH_PROTOCOL_VERSION_66: (u8, u8) = (66, 0x11);
+/// 65 version of Ethereum protocol (pooled transaction announcements).
+pub const ETH_PROTOCOL_VERSION_65: (u8, u8) = (65, 0x11);
+/// 64 version of Ethereum protocol (EIP-2124 fork id in status).
+pub const ETH_PROTOCOL_VERSION_64: (u8, u8) = (64, 0x11);
 /// 63 version of Ethereum protocol.
 pub const ETH_PROTOCOL_VERSION_63: (u8, u8) = (63, 0x11);
 /// 62 version of Ethereum protocol.
@@ -136,12 +147,17 @@ pub const PAR_PROTOCOL_VERSION_1: (u8, u8) = (1, 0x15);
 pub const PAR_PROTOCOL_VERSION_2: (u8, u8) = (2, 0x16);
 /// 3 version of Parity protocol (private transactions messages added).
 pub const PAR_PROTOCOL_VERSION_3: (u8, u8) = (3, 0x18);
+/// 4 version of Parity protocol (state range messages added).
+pub const PAR_PROTOCOL_VERSION_4: (u8, u8) = (4, 0x1c);
 
 pub const MAX_BODIES_TO_SEND: usize = 256;
 pub const MAX_HEADERS_TO_SEND: usize = 512;
 pub const MAX_NODE_DATA_TO_SEND: usize = 1024;
 pub const MAX_RECEIPTS_TO_SEND: usize = 1024;
 pub const MAX_RECEIPTS_HEADERS_TO_SEND: usize = 256;
+pub const MAX_POOLED_TRANSACTIONS_TO_SEND: usize = 256;
+pub const MAX_POOLED_TRANSACTIONS_TO_REQUEST: usize = 256;
+pub const MAX_STATE_RANGE_BYTES_TO_SEND: usize = 2 * 1024 * 1024;
 const MIN_PEERS_PROPAGATION: usize = 4;
 const MAX_PEERS_PROPAGATION: usize = 128;
 const MAX_PEER_LAG_PROPAGATION: BlockNumber = 20;
@@ -150,6 +166,8 @@ const MAX_NEW_BLOCK_AGE: BlockNumber = 20;
 // maximal packet size with transactions (cannot be greater than 16MB - protocol limitation).
 // keep it under 8MB as well, cause it seems that it may result oversized after compression.
 const MAX_TRANSACTION_PACKET_SIZE: usize = 5 * 1024 * 1024;
+// Maximal number of transaction hashes in a single announcement, sent or received.
+const MAX_TRANSACTION_HASHES_TO_ANNOUNCE: usize = 4096;
 // Min number of blocks to be behind for a snapshot sync
 const SNAPSHOT_RESTORE_THRESHOLD: BlockNumber = 30000;
 const SNAPSHOT_MIN_PEERS: usize = 3;
@@ -162,6 +180,9 @@ pub const BLOCK_HEADERS_PACKET: u8 = 0x04;
 pub const GET_BLOCK_BODIES_PACKET: u8 = 0x05;
 const BLOCK_BODIES_PACKET: u8 = 0x06;
 const NEW_BLOCK_PACKET: u8 = 0x07;
+const NEW_POOLED_TRANSACTION_HASHES_PACKET: u8 = 0x08;
+pub const GET_POOLED_TRANSACTIONS_PACKET: u8 = 0x09;
+const POOLED_TRANSACTIONS_PACKET: u8 = 0x0a;
 
 pub const GET_NODE_DATA_PACKET: u8 = 0x0d;
 pub const NODE_DATA_PACKET: u8 = 0x0e;
@@ -175,6 +196,10 @@ pub const SNAPSHOT_DATA_PACKET: u8 = 0x14;
 pub const CONSENSUS_DATA_PACKET: u8 = 0x15;
 pub const PRIVATE_TRANSACTION_PACKET: u8 = 0x16;
 pub const SIGNED_PRIVATE_TRANSACTION_PACKET: u8 = 0x17;
+pub const GET_STATE_RANGE_PACKET: u8 = 0x18;
+pub const STATE_RANGE_PACKET: u8 = 0x19;
+pub const GET_STATE_NODES_PACKET: u8 = 0x1a;
+pub const STATE_NODES_PACKET: u8 = 0x1b;
 
 const MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD: usize = 3;
 
@@ -186,6 +211,7 @@ const RECEIPTS_TIMEOUT: Duration = Duration::from_secs(10);
 const FORK_HEADER_TIMEOUT: Duration = Duration::from_secs(3);
 const SNAPSHOT_MANIFEST_TIMEOUT: Duration = Duration::from_secs(5);
 const SNAPSHOT_DATA_TIMEOUT: Duration = Duration::from_secs(120);
+const STATE_DATA_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 /// Sync state
@@ -198,6 +224,8 @@ pub enum SyncState {
 	SnapshotData,
 	/// Waiting for snapshot restoration progress.
 	SnapshotWaiting,
+	/// Downloading state ranges from the live state of peers
+	StateSync,
 	/// Downloading new blocks
 	Blocks,
 	/// Initial chain sync complete. Waiting for new packets
@@ -247,7 +275,8 @@ impl SyncStatus {
 		match self.state {
 			SyncState::SnapshotManifest |
 				SyncState::SnapshotData |
-				SyncState::SnapshotWaiting => true,
+				SyncState::SnapshotWaiting |
+				SyncState::StateSync => true,
 			_ => false,
 		}
 	}
@@ -279,6 +308,7 @@ pub enum PeerAsking {
 	BlockReceipts,
 	SnapshotManifest,
 	SnapshotData,
+	StateData,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
@@ -312,6 +342,8 @@ pub struct PeerInfo {
 	latest_hash: H256,
 	/// Peer total difficulty if known
 	difficulty: Option<U256>,
+	/// Hash of the block `difficulty` is the total difficulty of, if it was announced by the peer
+	difficulty_hash: Option<H256>,
 	/// Type of data currenty being requested from peer.
 	asking: PeerAsking,
 	/// A set of block numbers being requested
@@ -320,6 +352,8 @@ pub struct PeerInfo {
 	asking_hash: Option<H256>,
 	/// Holds requested snapshot chunk hash if any.
 	asking_snapshot_data: Option<H256>,
+	/// Holds pending state sync request if any.
+	asking_state: Option<StateRequest>,
 	/// Request timestamp
 	ask_time: Instant,
 	/// Holds a set of transactions recently sent to this peer to avoid spamming.
@@ -336,6 +370,10 @@ pub struct PeerInfo {
 	snapshot_number: Option<BlockNumber>,
 	/// Block set requested
 	block_set: Option<BlockSet>,
+	/// Id of the pending request, eth/66 responses are matched against it.
+	request_id: Option<RequestId>,
+	/// Peer node id if known, transaction stats of the peer are kept under it.
+	id: Option<NodeId>,
 }
 
 impl PeerInfo {
@@ -374,6 +412,38 @@ pub mod random {
 
 pub type RlpResponseResult = Result<Option<(PacketId, RlpStream)>, PacketDecodeError>;
 pub type Peers = HashMap<PeerId, PeerInfo>;
+pub type RequestId = u64;
+
+/// Whether the packet is prefixed with a request id when exchanged with the peer.
+/// Both sides negotiate the same eth version, so the framing is agreed on whichever
+/// subprotocol the packet is sent.
+fn has_request_id(io: &SyncIo, peer_id: PeerId, packet_id: PacketId) -> bool {
+	let is_request_or_response = match packet_id {
+		GET_BLOCK_HEADERS_PACKET | BLOCK_HEADERS_PACKET |
+		GET_BLOCK_BODIES_PACKET | BLOCK_BODIES_PACKET |
+		GET_NODE_DATA_PACKET | NODE_DATA_PACKET |
+		GET_RECEIPTS_PACKET | RECEIPTS_PACKET |
+		GET_POOLED_TRANSACTIONS_PACKET | POOLED_TRANSACTIONS_PACKET => true,
+		_ => false,
+	};
+	is_request_or_response && io.eth_protocol_version(peer_id) >= ETH_PROTOCOL_VERSION_66.0
+}
+
+/// Prefix the payload of an eth/66 packet with its request id: `[request_id, payload]`.
+fn with_request_id(request_id: RequestId, payload: &[u8]) -> Bytes {
+	let mut packet = RlpStream::new_list(2);
+	packet.append(&request_id);
+	packet.append_raw(payload, 1);
+	packet.out()
+}
+
+/// Split an eth/66 packet into its request id and payload.
+fn split_request_id<'a>(rlp: &Rlp<'a>) -> Result<(RequestId, Rlp<'a>), DecoderError> {
+	if rlp.item_count()? != 2 {
+		return Err(DecoderError::RlpIncorrectListLen);
+	}
+	Ok((rlp.val_at(0)?, rlp.at(1)?))
+}
 
 /// Blockchain sync handler.
 /// See module documentation for more details.
@@ -398,8 +468,14 @@ pub struct ChainSync {
 	network_id: u64,
 	/// Optional fork block to check
 	fork_block: Option<(BlockNumber, H256)>,
+	/// EIP-2124 fork id of the chain.
+	fork_filter: ForkFilter,
+	/// Block number from which headers carry the EIP-1559 base fee.
+	eip1559_transition: BlockNumber,
 	/// Snapshot downloader.
 	snapshot: Snapshot,
+	/// State range downloader.
+	state_sync: StateSync,
 	/// Connected peers pending Status message.
 	/// Value is request timestamp.
 	handshaking_peers: HashMap<PeerId, Instant>,
@@ -407,12 +483,18 @@ pub struct ChainSync {
 	sync_start_time: Option<Instant>,
 	/// Transactions propagation statistics
 	transactions_stats: TransactionsStats,
+	/// Fetcher of transactions announced by eth/65 peers.
+	transaction_fetcher: TransactionFetcher,
 	/// Enable ancient block downloading
 	download_old_blocks: bool,
 	/// Shared private tx service.
 	private_tx_handler: Arc<PrivateTxHandler>,
 	/// Enable warp sync.
 	warp_sync: WarpSync,
+	/// Download the state from peers when no snapshot is found.
+	state_sync_enabled: bool,
+	/// Id of the last request sent to an eth/66 peer.
+	last_request_id: RequestId,
 }
 
 impl ChainSync {
@@ -429,17 +511,23 @@ impl ChainSync {
 			peers: HashMap::new(),
 			handshaking_peers: HashMap::new(),
 			active_peers: HashSet::new(),
-			new_blocks: BlockDownloader::new(BlockSet::NewBlocks, &chain_info.best_block_hash, chain_info.best_block_number),
+			new_blocks: BlockDownloader::new(BlockSet::NewBlocks, &chain_info.best_block_hash, chain_info.best_block_number, config.eip1559_transition),
 			old_blocks: None,
 			last_sent_block_number: 0,
 			network_id: config.network_id,
 			fork_block: config.fork_block,
+			fork_filter: ForkFilter::new(chain_info.genesis_hash, config.fork_transitions),
+			eip1559_transition: config.eip1559_transition,
 			download_old_blocks: config.download_old_blocks,
 			snapshot: Snapshot::new(),
+			state_sync: StateSync::new(),
 			sync_start_time: None,
 			transactions_stats: TransactionsStats::default(),
+			transaction_fetcher: TransactionFetcher::default(),
 			private_tx_handler,
 			warp_sync: config.warp_sync,
+			state_sync_enabled: config.state_sync,
+			last_request_id: 0,
 		};
 		sync.update_targets(chain);
 		sync
@@ -459,7 +547,7 @@ impl ChainSync {
 		let last_imported_number = self.new_blocks.last_imported_block_number();
 		SyncStatus {
 			state: self.state.clone(),
-			protocol_version: ETH_PROTOCOL_VERSION_63.0,
+			protocol_version: ETH_PROTOCOL_VERSION_66.0,
 			network_id: self.network_id,
 			start_block_number: self.starting_block,
 			last_imported_block_number: Some(last_imported_number),
@@ -530,11 +618,12 @@ impl ChainSync {
 	/// Restart sync
 	pub fn reset_and_continue(&mut self, io: &mut SyncIo) {
 		trace!(target: "sync", "Restarting");
-		if self.state == SyncState::SnapshotData {
+		if self.state == SyncState::SnapshotData || self.state == SyncState::StateSync {
 			debug!(target:"sync", "Aborting snapshot restore");
 			io.snapshot_service().abort_restore();
 		}
 		self.snapshot.clear();
+		self.state_sync.clear();
 		self.reset(io, None);
 		self.continue_sync(io);
 	}
@@ -606,6 +695,11 @@ impl ChainSync {
 				trace!(target: "sync", "Starting unconfirmed snapshot sync {:?} with {:?}", hash, peers);
 				self.start_snapshot_sync(io, peers);
 			}
+		} else if timeout && self.state_sync_enabled && self.get_state_sync_peers(io).len() >= PIVOT_MIN_PEERS {
+			trace!(target: "sync", "No snapshots found, starting state sync");
+			self.state_sync.clear();
+			self.state = SyncState::StateSync;
+			self.continue_sync(io);
 		} else if timeout && !self.warp_sync.is_warp_only() {
 			trace!(target: "sync", "No snapshots found, starting full sync");
 			self.state = SyncState::Idle;
@@ -638,13 +732,13 @@ impl ChainSync {
 	pub fn update_targets(&mut self, chain: &BlockChainClient) {
 		// Do not assume that the block queue/chain still has our last_imported_block
 		let chain = chain.chain_info();
-		self.new_blocks = BlockDownloader::new(BlockSet::NewBlocks, &chain.best_block_hash, chain.best_block_number);
+		self.new_blocks = BlockDownloader::new(BlockSet::NewBlocks, &chain.best_block_hash, chain.best_block_number, self.eip1559_transition);
 		self.old_blocks = None;
 		if self.download_old_blocks {
 			if let (Some(ancient_block_hash), Some(ancient_block_number)) = (chain.ancient_block_hash, chain.ancient_block_number) {
 
 				trace!(target: "sync", "Downloading old blocks from {:?} (#{}) till {:?} (#{:?})", ancient_block_hash, ancient_block_number, chain.first_block_hash, chain.first_block_number);
-				let mut downloader = BlockDownloader::new(BlockSet::OldBlocks, &ancient_block_hash, ancient_block_number);
+				let mut downloader = BlockDownloader::new(BlockSet::OldBlocks, &ancient_block_hash, ancient_block_number, self.eip1559_transition);
 				if let Some(hash) = chain.first_block_hash {
 					trace!(target: "sync", "Downloader target set to {:?}", hash);
 					downloader.set_target(&hash);
@@ -715,13 +809,13 @@ impl ChainSync {
 			trace!(target: "sync", "Skipping deactivated peer {}", peer_id);
 			return;
 		}
-		let (peer_latest, peer_difficulty, peer_snapshot_number, peer_snapshot_hash) = {
+		let (peer_latest, peer_difficulty, peer_difficulty_hash, peer_snapshot_number, peer_snapshot_hash) = {
 			if let Some(peer) = self.peers.get_mut(&peer_id) {
 				if peer.asking != PeerAsking::Nothing || !peer.can_sync() {
 					trace!(target: "sync", "Skipping busy peer {}", peer_id);
 					return;
 				}
-				(peer.latest_hash.clone(), peer.difficulty.clone(), peer.snapshot_number.as_ref().cloned().unwrap_or(0), peer.snapshot_hash.as_ref().cloned())
+				(peer.latest_hash.clone(), peer.difficulty.clone(), peer.difficulty_hash.clone(), peer.snapshot_number.as_ref().cloned().unwrap_or(0), peer.snapshot_hash.as_ref().cloned())
 			} else {
 				return;
 			}
@@ -807,6 +901,25 @@ impl ChainSync {
 						SyncRequester::request_snapshot_data(self, io, peer_id);
 					}
 				},
+				SyncState::StateSync => {
+					if !self.state_sync.is_choosing_pivot() {
+						match io.snapshot_service().status() {
+							RestorationStatus::Ongoing { .. } => (),
+							_ => {
+								trace!(target: "sync", "Waiting for the state restoration to start");
+								return;
+							},
+						}
+					}
+
+					if ChainSync::is_state_sync_peer(io, peer_id) {
+						// the pivot is anchored on a block whose total difficulty was announced by the peer
+						let peer_head = peer_difficulty_hash.and_then(|hash| peer_difficulty.map(|difficulty| (hash, difficulty)));
+						if let Some(request) = self.state_sync.request(io.snapshot_service(), peer_id, peer_head) {
+							SyncRequester::request_state(self, io, peer_id, request);
+						}
+					}
+				},
 				SyncState::SnapshotManifest | //already downloading from other peer
 					SyncState::Waiting |
 					SyncState::SnapshotWaiting => ()
@@ -845,6 +958,11 @@ impl ChainSync {
 						self.snapshot.clear_chunk_download(&hash);
 					}
 				},
+				PeerAsking::StateData => {
+					if let Some(ref request) = peer.asking_state {
+						self.state_sync.clear_request(request);
+					}
+				},
 				_ => (),
 			}
 		}
@@ -890,11 +1008,18 @@ impl ChainSync {
 		}
 	}
 
+	/// Allocate an id for a request to an eth/66 peer.
+	fn next_request_id(&mut self) -> RequestId {
+		self.last_request_id = self.last_request_id.wrapping_add(1);
+		self.last_request_id
+	}
+
 	/// Reset peer status after request is complete.
 	fn reset_peer_asking(&mut self, peer_id: PeerId, asking: PeerAsking) -> bool {
 		if let Some(ref mut peer) = self.peers.get_mut(&peer_id) {
 			peer.expired = false;
 			peer.block_set = None;
+			peer.request_id = None;
 			if peer.asking != asking {
 				trace!(target:"sync", "Asking {:?} while expected {:?}", peer.asking, asking);
 				peer.asking = PeerAsking::Nothing;
@@ -911,9 +1036,12 @@ impl ChainSync {
 	fn send_status(&mut self, io: &mut SyncIo, peer: PeerId) -> Result<(), network::Error> {
 		let warp_protocol_version = io.protocol_version(&WARP_SYNC_PROTOCOL_ID, peer);
 		let warp_protocol = warp_protocol_version != 0;
-		let protocol = if warp_protocol { warp_protocol_version } else { ETH_PROTOCOL_VERSION_63.0 };
+		let eth_protocol_version = io.eth_protocol_version(peer);
+		let protocol = if warp_protocol { warp_protocol_version } else { eth_protocol_version };
+		let fork_id = eth_protocol_version >= ETH_PROTOCOL_VERSION_64.0;
 		trace!(target: "sync", "Sending status to {}, protocol version {}", peer, protocol);
-		let mut packet = RlpStream::new_list(if warp_protocol { 7 } else { 5 });
+		let item_count = if warp_protocol { 7 } else { 5 };
+		let mut packet = RlpStream::new_list(if fork_id { item_count + 1 } else { item_count });
 		let chain = io.chain().chain_info();
 		packet.append(&(protocol as u32));
 		packet.append(&self.network_id);
@@ -927,6 +1055,9 @@ impl ChainSync {
 			packet.append(&manifest_hash);
 			packet.append(&block_number);
 		}
+		if fork_id {
+			packet.append(&self.fork_filter.current(chain.best_block_number));
+		}
 		io.respond(STATUS_PACKET, packet.out())
 	}
 
@@ -943,6 +1074,7 @@ impl ChainSync {
 				PeerAsking::ForkHeader => elapsed > FORK_HEADER_TIMEOUT,
 				PeerAsking::SnapshotManifest => elapsed > SNAPSHOT_MANIFEST_TIMEOUT,
 				PeerAsking::SnapshotData => elapsed > SNAPSHOT_DATA_TIMEOUT,
+				PeerAsking::StateData => elapsed > STATE_DATA_TIMEOUT,
 			};
 			if timeout {
 				debug!(target:"sync", "Timeout {}", peer_id);
@@ -962,6 +1094,14 @@ impl ChainSync {
 				io.disconnect_peer(*peer);
 			}
 		}
+
+		// Ask other announcers for transactions that weren't delivered in time
+		for peer_id in self.transaction_fetcher.expire(tick) {
+			trace!(target: "sync", "{}: Pooled transactions request timeout", peer_id);
+			let id = io.peer_session_info(peer_id).and_then(|info| info.id);
+			self.transactions_stats.timed_out(id);
+		}
+		SyncRequester::request_announced_transactions(self, io);
 	}
 
 	fn check_resume(&mut self, io: &mut SyncIo) {
@@ -990,10 +1130,22 @@ impl ChainSync {
 						trace!(target: "sync", "Snapshot restoration aborted");
 						self.state = SyncState::WaitingPeers;
 						self.snapshot.clear();
+						self.state_sync.clear();
 						self.continue_sync(io);
 					},
 				}
 			},
+			SyncState::StateSync => {
+				match io.snapshot_service().status() {
+					RestorationStatus::Failed if !self.state_sync.is_choosing_pivot() => {
+						trace!(target: "sync", "State restoration aborted");
+						self.state = SyncState::WaitingPeers;
+						self.state_sync.clear();
+					},
+					_ => (),
+				}
+				self.continue_sync(io);
+			},
 			_ => (),
 		}
 	}
@@ -1082,6 +1234,18 @@ impl ChainSync {
 		self.peers.iter().filter_map(|(id, p)| if p.protocol_version >= PAR_PROTOCOL_VERSION_2.0 { Some(*id) } else { None }).collect()
 	}
 
+	/// Check if the peer serves state ranges.
+	fn is_state_sync_peer(io: &SyncIo, peer_id: PeerId) -> bool {
+		io.protocol_version(&WARP_SYNC_PROTOCOL_ID, peer_id) >= PAR_PROTOCOL_VERSION_4.0
+	}
+
+	fn get_state_sync_peers(&self, io: &SyncIo) -> Vec<PeerId> {
+		self.peers.iter()
+			.filter(|&(id, p)| p.can_sync() && ChainSync::is_state_sync_peer(io, *id))
+			.map(|(id, _)| *id)
+			.collect()
+	}
+
 	fn get_private_transaction_peers(&self, transaction_hash: &H256) -> Vec<PeerId> {
 		self.peers.iter().filter_map(
 			|(id, p)| if p.protocol_version >= PAR_PROTOCOL_VERSION_3.0
@@ -1281,6 +1445,7 @@ pub mod tests {
 				network_id: 0,
 				latest_hash: peer_latest_hash,
 				difficulty: None,
+				difficulty_hash: None,
 				asking: PeerAsking::Nothing,
 				asking_blocks: Vec::new(),
 				asking_hash: None,
@@ -1292,7 +1457,10 @@ pub mod tests {
 				snapshot_number: None,
 				snapshot_hash: None,
 				asking_snapshot_data: None,
+				asking_state: None,
 				block_set: None,
+				request_id: None,
+				id: None,
 			});
 
 	}
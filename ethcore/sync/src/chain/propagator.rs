@@ -29,13 +29,16 @@ use transaction::SignedTransaction;
 use super::{
 	random,
 	ChainSync,
+	MAX_TRANSACTION_HASHES_TO_ANNOUNCE,
 	MAX_TRANSACTION_PACKET_SIZE,
 	MAX_PEER_LAG_PROPAGATION,
 	MAX_PEERS_PROPAGATION,
 	MIN_PEERS_PROPAGATION,
+	ETH_PROTOCOL_VERSION_65,
 	CONSENSUS_DATA_PACKET,
 	NEW_BLOCK_HASHES_PACKET,
 	NEW_BLOCK_PACKET,
+	NEW_POOLED_TRANSACTION_HASHES_PACKET,
 	TRANSACTIONS_PACKET,
 };
 
@@ -130,7 +133,13 @@ impl SyncPropagator {
 		let mut affected_peers = HashSet::new();
 		if !transactions.is_empty() {
 			let peers = SyncPropagator::select_peers_for_transactions(sync, |_| true);
-			affected_peers = SyncPropagator::propagate_transactions_to_peers(sync, io, peers, transactions);
+			// the rest of eth/65 peers only get the hashes and fetch what they miss
+			let announce_peers = sync.peers.keys()
+				.filter(|peer_id| !peers.contains(peer_id) && io.eth_protocol_version(**peer_id) >= ETH_PROTOCOL_VERSION_65.0)
+				.cloned()
+				.collect::<Vec<_>>();
+			affected_peers = SyncPropagator::announce_transactions_to_peers(sync, io, announce_peers, &transactions);
+			affected_peers.extend(&SyncPropagator::propagate_transactions_to_peers(sync, io, peers, transactions));
 		}
 
 		// most of times service_transactions will be empty
@@ -144,6 +153,49 @@ impl SyncPropagator {
 		affected_peers.len()
 	}
 
+	fn announce_transactions_to_peers(sync: &mut ChainSync, io: &mut SyncIo, peers: Vec<PeerId>, transactions: &[&SignedTransaction]) -> HashSet<PeerId> {
+		let all_transactions_hashes = transactions.iter()
+			.map(|tx| tx.hash())
+			.collect::<HashSet<H256>>();
+
+		let mut announced = HashSet::new();
+		for peer_id in peers {
+			let to_announce = {
+				let peer_info = sync.peers.get_mut(&peer_id)
+					.expect("peer_id is from peers; peers are selected from sync.peers; qed");
+				let to_announce = transactions.iter()
+					.map(|tx| tx.hash())
+					.filter(|hash| !peer_info.last_sent_transactions.contains(hash))
+					.take(MAX_TRANSACTION_HASHES_TO_ANNOUNCE)
+					.collect::<Vec<_>>();
+				peer_info.last_sent_transactions = all_transactions_hashes
+					.intersection(&peer_info.last_sent_transactions)
+					.chain(&to_announce)
+					.cloned()
+					.collect();
+				to_announce
+			};
+			if to_announce.is_empty() {
+				continue;
+			}
+
+			let mut packet = RlpStream::new_list(to_announce.len());
+			for hash in &to_announce {
+				packet.append(hash);
+			}
+			let id = io.peer_session_info(peer_id).and_then(|info| info.id);
+			sync.transactions_stats.announced_to(id, to_announce.len());
+			SyncPropagator::send_packet(io, peer_id, NEW_POOLED_TRANSACTION_HASHES_PACKET, packet.out());
+			trace!(target: "sync", "{:02} <- NewPooledTransactionHashes ({} entries)", peer_id, to_announce.len());
+			announced.insert(peer_id);
+		}
+		if !announced.is_empty() {
+			debug!(target: "sync", "Announced transactions to {} peers.", announced.len());
+		}
+
+		announced
+	}
+
 	fn propagate_transactions_to_peers(sync: &mut ChainSync, io: &mut SyncIo, peers: Vec<PeerId>, transactions: Vec<&SignedTransaction>) -> HashSet<PeerId> {
 		let all_transactions_hashes = transactions.iter()
 			.map(|tx| tx.hash())
@@ -415,6 +467,7 @@ mod tests {
 				network_id: 0,
 				latest_hash: client.block_hash_delta_minus(1),
 				difficulty: None,
+				difficulty_hash: None,
 				asking: PeerAsking::Nothing,
 				asking_blocks: Vec::new(),
 				asking_hash: None,
@@ -426,7 +479,10 @@ mod tests {
 				snapshot_number: None,
 				snapshot_hash: None,
 				asking_snapshot_data: None,
+				asking_state: None,
 				block_set: None,
+				request_id: None,
+				id: None,
 			});
 		let ss = TestSnapshotService::new();
 		let mut io = TestIo::new(&mut client, &ss, &queue, None);
@@ -559,6 +615,39 @@ mod tests {
 		assert_eq!(stats.len(), 1, "Should maintain stats for single transaction.")
 	}
 
+	#[test]
+	fn announces_transactions_to_peers_not_receiving_them() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let tx_hash = client.insert_transaction_to_queue();
+		let block_hash = client.block_hash_delta_minus(1);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client, Arc::new(NoopPrivateTxHandler));
+		for peer_id in 0..20 {
+			insert_dummy_peer(&mut sync, peer_id, block_hash);
+		}
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		io.eth_protocol_version = ETH_PROTOCOL_VERSION_65.0;
+
+		let peer_count = SyncPropagator::propagate_new_transactions(&mut sync, &mut io);
+		let peer_count2 = SyncPropagator::propagate_new_transactions(&mut sync, &mut io);
+
+		// every peer gets either the transaction or its hash, and only once
+		assert_eq!(20, peer_count);
+		assert_eq!(0, peer_count2);
+		assert_eq!(20, io.packets.len());
+		let announcements = io.packets.iter()
+			.filter(|p| p.packet_id == NEW_POOLED_TRANSACTION_HASHES_PACKET)
+			.collect::<Vec<_>>();
+		assert!(announcements.len() > 10);
+		for packet in &announcements {
+			assert_eq!(Rlp::new(&packet.data).as_list::<H256>().unwrap(), vec![tx_hash]);
+		}
+		let stats = sync.transactions_stats.peer(&Default::default()).unwrap();
+		assert_eq!(stats.announced_to, announcements.len());
+	}
+
 	#[test]
 	fn should_propagate_service_transaction_to_selected_peers_only() {
 		let mut client = TestBlockChainClient::new();
@@ -21,19 +21,26 @@ use ethcore::header::BlockNumber;
 use ethereum_types::H256;
 use network::{PeerId, PacketId};
 use rlp::RlpStream;
+use state_sync::{StateRequest, PIVOT_DISTANCE, STATE_RANGE_SIZE};
 use std::time::Instant;
 use sync_io::SyncIo;
 
 use super::{
+	has_request_id,
+	with_request_id,
 	BlockSet,
 	ChainSync,
 	PeerAsking,
 	ETH_PROTOCOL_VERSION_63,
 	GET_BLOCK_BODIES_PACKET,
 	GET_BLOCK_HEADERS_PACKET,
+	GET_POOLED_TRANSACTIONS_PACKET,
 	GET_RECEIPTS_PACKET,
 	GET_SNAPSHOT_DATA_PACKET,
 	GET_SNAPSHOT_MANIFEST_PACKET,
+	GET_STATE_NODES_PACKET,
+	GET_STATE_RANGE_PACKET,
+	MAX_POOLED_TRANSACTIONS_TO_REQUEST,
 };
 
 /// The Chain Sync Requester: requesting data to other peers
@@ -97,6 +104,71 @@ impl SyncRequester {
 		SyncRequester::send_request(sync, io, peer_id, PeerAsking::SnapshotManifest, GET_SNAPSHOT_MANIFEST_PACKET, rlp.out());
 	}
 
+	/// Request state sync data from a peer.
+	pub fn request_state(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, request: StateRequest) {
+		let (packet_id, packet) = match request {
+			StateRequest::PivotHeaders(ref hash, _) => {
+				trace!(target: "sync", "{} <- GetBlockHeaders: state sync pivot from {}", peer_id, hash);
+				let mut rlp = RlpStream::new_list(4);
+				rlp.append(hash);
+				rlp.append(&(PIVOT_DISTANCE + 1));
+				rlp.append(&0u32);
+				rlp.append(&1u32);
+				(GET_BLOCK_HEADERS_PACKET, rlp.out())
+			},
+			StateRequest::ConfirmPivot(ref hash) => {
+				trace!(target: "sync", "{} <- GetBlockHeaders: state sync pivot {} to confirm", peer_id, hash);
+				let mut rlp = RlpStream::new_list(4);
+				rlp.append(hash);
+				rlp.append(&1u32);
+				rlp.append(&0u32);
+				rlp.append(&0u32);
+				(GET_BLOCK_HEADERS_PACKET, rlp.out())
+			},
+			StateRequest::PivotBody(ref hash) => {
+				trace!(target: "sync", "{} <- GetBlockBodies: state sync pivot {}", peer_id, hash);
+				let mut rlp = RlpStream::new_list(1);
+				rlp.append(hash);
+				(GET_BLOCK_BODIES_PACKET, rlp.out())
+			},
+			StateRequest::PivotReceipts(ref hash) => {
+				trace!(target: "sync", "{} <- GetReceipts: state sync pivot {}", peer_id, hash);
+				let mut rlp = RlpStream::new_list(1);
+				rlp.append(hash);
+				(GET_RECEIPTS_PACKET, rlp.out())
+			},
+			StateRequest::Range { ref block, ref account, ref origin } => {
+				trace!(target: "sync", "{} <- GetStateRange: {:?} from {} at {}", peer_id, account, origin, block);
+				let mut rlp = RlpStream::new_list(4);
+				rlp.append(block);
+				match *account {
+					Some(ref account) => rlp.append(account),
+					None => rlp.append_empty_data(),
+				};
+				rlp.append(origin);
+				rlp.append(&STATE_RANGE_SIZE);
+				(GET_STATE_RANGE_PACKET, rlp.out())
+			},
+			StateRequest::Nodes(ref nodes) => {
+				trace!(target: "sync", "{} <- GetStateNodes: {} entries", peer_id, nodes.len());
+				let mut rlp = RlpStream::new_list(nodes.len());
+				for &(ref account, ref hash) in nodes {
+					rlp.begin_list(2);
+					match *account {
+						Some(ref account) => rlp.append(account),
+						None => rlp.append_empty_data(),
+					};
+					rlp.append(hash);
+				}
+				(GET_STATE_NODES_PACKET, rlp.out())
+			},
+		};
+		if let Some(ref mut peer) = sync.peers.get_mut(&peer_id) {
+			peer.asking_state = Some(request);
+		}
+		SyncRequester::send_request(sync, io, peer_id, PeerAsking::StateData, packet_id, packet);
+	}
+
 	/// Request headers from a peer by block hash
 	fn request_headers_by_hash(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, h: &H256, count: u64, skip: u64, reverse: bool, set: BlockSet) {
 		trace!(target: "sync", "{} <- GetBlockHeaders: {} entries starting from {}, set = {:?}", peer_id, count, h, set);
@@ -124,6 +196,34 @@ impl SyncRequester {
 		peer.block_set = Some(set);
 	}
 
+	/// Request announced transactions that are not being fetched yet from idle announcers.
+	pub fn request_announced_transactions(sync: &mut ChainSync, io: &mut SyncIo) {
+		let requests = sync.transaction_fetcher.schedule(Instant::now(), MAX_POOLED_TRANSACTIONS_TO_REQUEST);
+		for (peer_id, hashes) in requests {
+			let id = io.peer_session_info(peer_id).and_then(|info| info.id);
+			sync.transactions_stats.requested(id, hashes.len());
+			SyncRequester::request_pooled_transactions(sync, io, peer_id, &hashes);
+		}
+	}
+
+	/// Request announced transactions from a peer. Doesn't interfere with pending block requests.
+	fn request_pooled_transactions(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, hashes: &[H256]) {
+		trace!(target: "sync", "{} <- GetPooledTransactions: {} entries", peer_id, hashes.len());
+		let mut rlp = RlpStream::new_list(hashes.len());
+		for h in hashes {
+			rlp.append(h);
+		}
+		let packet = if has_request_id(io, peer_id, GET_POOLED_TRANSACTIONS_PACKET) {
+			with_request_id(sync.next_request_id(), &rlp.out())
+		} else {
+			rlp.out()
+		};
+		if let Err(e) = io.send(peer_id, GET_POOLED_TRANSACTIONS_PACKET, packet) {
+			debug!(target:"sync", "Error sending request: {:?}", e);
+			io.disconnect_peer(peer_id);
+		}
+	}
+
 	/// Request snapshot chunk from a peer.
 	fn request_snapshot_chunk(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, chunk: &H256) {
 		trace!(target: "sync", "{} <- GetSnapshotData {:?}", peer_id, chunk);
@@ -134,12 +234,18 @@ impl SyncRequester {
 
 	/// Generic request sender
 	fn send_request(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, asking: PeerAsking,  packet_id: PacketId, packet: Bytes) {
+		let request_id = if has_request_id(io, peer_id, packet_id) { Some(sync.next_request_id()) } else { None };
 		if let Some(ref mut peer) = sync.peers.get_mut(&peer_id) {
 			if peer.asking != PeerAsking::Nothing {
 				warn!(target:"sync", "Asking {:?} while requesting {:?}", peer.asking, asking);
 			}
 			peer.asking = asking;
 			peer.ask_time = Instant::now();
+			peer.request_id = request_id;
+			let packet = match request_id {
+				Some(request_id) => with_request_id(request_id, &packet),
+				None => packet,
+			};
 			// TODO [ToDr] This seems quite fragile. Be careful when protocol is updated.
 			let result = if packet_id >= ETH_PROTOCOL_VERSION_63.1 {
 				io.send_protocol(WARP_SYNC_PROTOCOL_ID, peer_id, packet_id, packet)
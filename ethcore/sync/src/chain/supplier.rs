@@ -26,7 +26,11 @@ use std::cmp;
 use sync_io::SyncIo;
 
 use super::{
+	has_request_id,
+	split_request_id,
+	with_request_id,
 	ChainSync,
+	RequestId,
 	RlpResponseResult,
 	PacketDecodeError,
 	BLOCK_BODIES_PACKET,
@@ -35,18 +39,27 @@ use super::{
 	GET_BLOCK_BODIES_PACKET,
 	GET_BLOCK_HEADERS_PACKET,
 	GET_NODE_DATA_PACKET,
+	GET_POOLED_TRANSACTIONS_PACKET,
 	GET_RECEIPTS_PACKET,
 	GET_SNAPSHOT_DATA_PACKET,
 	GET_SNAPSHOT_MANIFEST_PACKET,
+	GET_STATE_NODES_PACKET,
+	GET_STATE_RANGE_PACKET,
 	MAX_BODIES_TO_SEND,
 	MAX_HEADERS_TO_SEND,
 	MAX_NODE_DATA_TO_SEND,
+	MAX_POOLED_TRANSACTIONS_TO_SEND,
 	MAX_RECEIPTS_HEADERS_TO_SEND,
 	MAX_RECEIPTS_TO_SEND,
+	MAX_STATE_RANGE_BYTES_TO_SEND,
+	MAX_TRANSACTION_PACKET_SIZE,
 	NODE_DATA_PACKET,
+	POOLED_TRANSACTIONS_PACKET,
 	RECEIPTS_PACKET,
 	SNAPSHOT_DATA_PACKET,
 	SNAPSHOT_MANIFEST_PACKET,
+	STATE_NODES_PACKET,
+	STATE_RANGE_PACKET,
 };
 
 /// The Chain Sync Supplier: answers requests from peers with available data
@@ -56,30 +69,53 @@ impl SyncSupplier {
 	/// Dispatch incoming requests and responses
 	pub fn dispatch_packet(sync: &RwLock<ChainSync>, io: &mut SyncIo, peer: PeerId, packet_id: u8, data: &[u8]) {
 		let rlp = Rlp::new(data);
+		let (request_id, rlp) = if has_request_id(io, peer, packet_id) {
+			match split_request_id(&rlp) {
+				Ok((request_id, payload)) => (Some(request_id), payload),
+				Err(e) => {
+					debug!(target:"sync", "{} -> Malformed packet {} : {}", peer, packet_id, e);
+					return;
+				}
+			}
+		} else {
+			(None, rlp)
+		};
 		let result = match packet_id {
-			GET_BLOCK_BODIES_PACKET => SyncSupplier::return_rlp(io, &rlp, peer,
+			GET_BLOCK_BODIES_PACKET => SyncSupplier::return_rlp(io, &rlp, peer, request_id,
 				SyncSupplier::return_block_bodies,
 				|e| format!("Error sending block bodies: {:?}", e)),
 
-			GET_BLOCK_HEADERS_PACKET => SyncSupplier::return_rlp(io, &rlp, peer,
+			GET_BLOCK_HEADERS_PACKET => SyncSupplier::return_rlp(io, &rlp, peer, request_id,
 				SyncSupplier::return_block_headers,
 				|e| format!("Error sending block headers: {:?}", e)),
 
-			GET_RECEIPTS_PACKET => SyncSupplier::return_rlp(io, &rlp, peer,
+			GET_RECEIPTS_PACKET => SyncSupplier::return_rlp(io, &rlp, peer, request_id,
 				SyncSupplier::return_receipts,
 				|e| format!("Error sending receipts: {:?}", e)),
 
-			GET_NODE_DATA_PACKET => SyncSupplier::return_rlp(io, &rlp, peer,
+			GET_NODE_DATA_PACKET => SyncSupplier::return_rlp(io, &rlp, peer, request_id,
 				SyncSupplier::return_node_data,
 				|e| format!("Error sending nodes: {:?}", e)),
 
-			GET_SNAPSHOT_MANIFEST_PACKET => SyncSupplier::return_rlp(io, &rlp, peer,
+			GET_POOLED_TRANSACTIONS_PACKET => SyncSupplier::return_rlp(io, &rlp, peer, request_id,
+				SyncSupplier::return_pooled_transactions,
+				|e| format!("Error sending pooled transactions: {:?}", e)),
+
+			GET_SNAPSHOT_MANIFEST_PACKET => SyncSupplier::return_rlp(io, &rlp, peer, request_id,
 				SyncSupplier::return_snapshot_manifest,
 				|e| format!("Error sending snapshot manifest: {:?}", e)),
 
-			GET_SNAPSHOT_DATA_PACKET => SyncSupplier::return_rlp(io, &rlp, peer,
+			GET_SNAPSHOT_DATA_PACKET => SyncSupplier::return_rlp(io, &rlp, peer, request_id,
 				SyncSupplier::return_snapshot_data,
 				|e| format!("Error sending snapshot data: {:?}", e)),
+
+			GET_STATE_RANGE_PACKET => SyncSupplier::return_rlp(io, &rlp, peer, request_id,
+				SyncSupplier::return_state_range,
+				|e| format!("Error sending state range: {:?}", e)),
+
+			GET_STATE_NODES_PACKET => SyncSupplier::return_rlp(io, &rlp, peer, request_id,
+				SyncSupplier::return_state_nodes,
+				|e| format!("Error sending state nodes: {:?}", e)),
 			CONSENSUS_DATA_PACKET => ChainSync::on_consensus_packet(io, peer, &rlp),
 			_ => {
 				sync.write().on_packet(io, peer, packet_id, data);
@@ -238,6 +274,33 @@ impl SyncSupplier {
 		Ok(Some((RECEIPTS_PACKET, rlp_result)))
 	}
 
+	/// Respond to GetPooledTransactions request
+	fn return_pooled_transactions(io: &SyncIo, r: &Rlp, peer_id: PeerId) -> RlpResponseResult {
+		let count = cmp::min(r.item_count().unwrap_or(0), MAX_POOLED_TRANSACTIONS_TO_SEND);
+		trace!(target: "sync", "{} -> GetPooledTransactions: {} entries", peer_id, count);
+		if count == 0 {
+			debug!(target: "sync", "Empty GetPooledTransactions request, ignoring.");
+			return Ok(None);
+		}
+		let mut added = 0usize;
+		let mut data = Bytes::new();
+		for i in 0..count {
+			// unknown transactions are skipped, the requester matches the response by hashes
+			if let Some(tx) = io.chain().pooled_transaction(&r.val_at::<H256>(i)?) {
+				let mut tx_rlp = ::rlp::encode(tx.signed()).into_vec();
+				if data.len() + tx_rlp.len() > MAX_TRANSACTION_PACKET_SIZE {
+					break;
+				}
+				data.append(&mut tx_rlp);
+				added += 1;
+			}
+		}
+		let mut rlp = RlpStream::new_list(added);
+		rlp.append_raw(&data, added);
+		trace!(target: "sync", "{} -> GetPooledTransactions: returned {} entries", peer_id, added);
+		Ok(Some((POOLED_TRANSACTIONS_PACKET, rlp)))
+	}
+
 	/// Respond to GetSnapshotManifest request
 	fn return_snapshot_manifest(io: &SyncIo, r: &Rlp, peer_id: PeerId) -> RlpResponseResult {
 		let count = r.item_count().unwrap_or(0);
@@ -280,7 +343,53 @@ impl SyncSupplier {
 		Ok(Some((SNAPSHOT_DATA_PACKET, rlp)))
 	}
 
-	fn return_rlp<FRlp, FError>(io: &mut SyncIo, rlp: &Rlp, peer: PeerId, rlp_func: FRlp, error_func: FError) -> Result<(), PacketDecodeError>
+	/// Respond to GetStateRange request
+	fn return_state_range(io: &SyncIo, r: &Rlp, peer_id: PeerId) -> RlpResponseResult {
+		let block: H256 = r.val_at(0)?;
+		let account: Option<H256> = if r.at(1)?.is_empty() { None } else { Some(r.val_at(1)?) };
+		let origin: H256 = r.val_at(2)?;
+		let max_bytes = cmp::min(r.val_at::<usize>(3)?, MAX_STATE_RANGE_BYTES_TO_SEND);
+		trace!(target: "warp", "{} -> GetStateRange {:?} from {} at {}", peer_id, account, origin, block);
+		let rlp = match io.chain().state_range(&block, account, &origin, max_bytes) {
+			Some(range) => {
+				trace!(target: "warp", "{} <- StateRange: {} entries", peer_id, range.items.len());
+				let mut rlp = RlpStream::new();
+				rlp.append(&range);
+				rlp
+			},
+			None => {
+				trace!(target: "warp", "{}: No state range to return", peer_id);
+				RlpStream::new_list(0)
+			}
+		};
+		Ok(Some((STATE_RANGE_PACKET, rlp)))
+	}
+
+	/// Respond to GetStateNodes request
+	fn return_state_nodes(io: &SyncIo, r: &Rlp, peer_id: PeerId) -> RlpResponseResult {
+		let count = cmp::min(r.item_count().unwrap_or(0), MAX_NODE_DATA_TO_SEND);
+		trace!(target: "warp", "{} -> GetStateNodes: {} entries", peer_id, count);
+		if count == 0 {
+			debug!(target: "warp", "Empty GetStateNodes request, ignoring.");
+			return Ok(None);
+		}
+		let mut data = Vec::new();
+		for i in 0..count {
+			let item = r.at(i)?;
+			let account: Option<H256> = if item.at(0)?.is_empty() { None } else { Some(item.val_at(0)?) };
+			if let Some(node) = io.chain().state_node(account, &item.val_at(1)?) {
+				data.push(node);
+			}
+		}
+		trace!(target: "warp", "{} <- StateNodes: {} entries", peer_id, data.len());
+		let mut rlp = RlpStream::new_list(data.len());
+		for d in data {
+			rlp.append(&d);
+		}
+		Ok(Some((STATE_NODES_PACKET, rlp)))
+	}
+
+	fn return_rlp<FRlp, FError>(io: &mut SyncIo, rlp: &Rlp, peer: PeerId, request_id: Option<RequestId>, rlp_func: FRlp, error_func: FError) -> Result<(), PacketDecodeError>
 		where FRlp : Fn(&SyncIo, &Rlp, PeerId) -> RlpResponseResult,
 			FError : FnOnce(network::Error) -> String
 	{
@@ -288,7 +397,11 @@ impl SyncSupplier {
 		match response {
 			Err(e) => Err(e),
 			Ok(Some((packet_id, rlp_stream))) => {
-				io.respond(packet_id, rlp_stream.out()).unwrap_or_else(
+				let packet = match request_id {
+					Some(request_id) => with_request_id(request_id, &rlp_stream.out()),
+					None => rlp_stream.out(),
+				};
+				io.respond(packet_id, packet).unwrap_or_else(
 					|e| debug!(target: "sync", "{:?}", error_func(e)));
 				Ok(())
 			}
@@ -307,6 +420,8 @@ mod test {
 	use bytes::Bytes;
 	use rlp::{Rlp, RlpStream};
 	use super::{*, super::tests::*};
+	use super::super::ETH_PROTOCOL_VERSION_66;
+	use hash::keccak;
 	use blocks::SyncHeader;
 	use ethcore::client::{BlockChainClient, EachBlockWith, TestBlockChainClient};
 
@@ -330,14 +445,14 @@ mod test {
 			rlp.out()
 		}
 		fn to_header_vec(rlp: ::chain::RlpResponseResult) -> Vec<SyncHeader> {
-			Rlp::new(&rlp.unwrap().unwrap().1.out()).iter().map(|r| SyncHeader::from_rlp(r.as_raw().to_vec()).unwrap()).collect()
+			Rlp::new(&rlp.unwrap().unwrap().1.out()).iter().map(|r| SyncHeader::from_rlp(r.as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect()
 		}
 
 		let mut client = TestBlockChainClient::new();
 		client.add_blocks(100, EachBlockWith::Nothing);
 		let blocks: Vec<_> = (0 .. 100)
 			.map(|i| (&client as &BlockChainClient).block(BlockId::Number(i as BlockNumber)).map(|b| b.into_inner()).unwrap()).collect();
-		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec()).unwrap()).collect();
+		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect();
 		let hashes: Vec<_> = headers.iter().map(|h| h.header.hash()).collect();
 
 		let queue = RwLock::new(VecDeque::new());
@@ -407,6 +522,31 @@ mod test {
 		assert_eq!(1, io.packets.len());
 	}
 
+	#[test]
+	fn return_state_range_unavailable() {
+		let mut client = TestBlockChainClient::new();
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let mut request = RlpStream::new_list(4);
+		request.append(&H256::from(1));
+		request.append_empty_data();
+		request.append(&H256::new());
+		request.append(&1024usize);
+		let request = request.out();
+
+		// the state isn't there, so an empty response tells the peer to move on
+		let (packet_id, rlp) = SyncSupplier::return_state_range(&io, &Rlp::new(&request), 0).unwrap().unwrap();
+		assert_eq!(packet_id, STATE_RANGE_PACKET);
+		assert!(Rlp::new(&rlp.out()).is_empty());
+
+		let mut nodes = RlpStream::new_list(1);
+		nodes.begin_list(2).append_empty_data().append(&H256::from(1));
+		let (_, rlp) = SyncSupplier::return_state_nodes(&io, &Rlp::new(&nodes.out()), 0).unwrap().unwrap();
+		assert_eq!(Ok(0), Rlp::new(&rlp.out()).item_count());
+	}
+
 	#[test]
 	fn return_receipts_empty() {
 		let mut client = TestBlockChainClient::new();
@@ -448,4 +588,57 @@ mod test {
 		ChainSync::dispatch_packet(&RwLock::new(sync), &mut io, 0usize, GET_RECEIPTS_PACKET, &receipts_request);
 		assert_eq!(1, io.packets.len());
 	}
+
+	#[test]
+	fn return_pooled_transactions() {
+		let mut client = TestBlockChainClient::new();
+		let tx1 = client.insert_transaction_to_queue();
+		let tx2 = client.insert_transaction_to_queue();
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let mut request = RlpStream::new_list(3);
+		request.append(&tx1);
+		request.append(&H256::from(1));
+		request.append(&tx2);
+
+		let (packet_id, response) = SyncSupplier::return_pooled_transactions(&io, &Rlp::new(&request.out()), 0).unwrap().unwrap();
+		let response = response.out();
+		// unknown hashes are skipped
+		let hashes: Vec<H256> = Rlp::new(&response).iter().map(|tx| keccak(tx.as_raw())).collect();
+		assert_eq!(packet_id, POOLED_TRANSACTIONS_PACKET);
+		assert_eq!(hashes, vec![tx1, tx2]);
+	}
+
+	#[test]
+	fn responds_with_request_id() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let queue = RwLock::new(VecDeque::new());
+		let sync = dummy_sync_with_peer(H256::new(), &client);
+		let malformed_sync = dummy_sync_with_peer(H256::new(), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, Some(0));
+		io.eth_protocol_version = ETH_PROTOCOL_VERSION_66.0;
+
+		let mut request = RlpStream::new_list(4);
+		request.append(&1u64);
+		request.append(&2u64);
+		request.append(&0u64);
+		request.append(&0u32);
+		let request = with_request_id(42, &request.out());
+		ChainSync::dispatch_packet(&RwLock::new(sync), &mut io, 0, GET_BLOCK_HEADERS_PACKET, &request);
+
+		assert_eq!(1, io.packets.len());
+		let response = Rlp::new(&io.packets[0].data);
+		assert_eq!(response.val_at::<RequestId>(0), Ok(42));
+		assert_eq!(response.at(1).unwrap().item_count(), Ok(2));
+
+		// eth/66 requests without an id are malformed
+		let mut request = RlpStream::new_list(1);
+		request.append(&H256::new());
+		ChainSync::dispatch_packet(&RwLock::new(malformed_sync), &mut io, 0, GET_BLOCK_BODIES_PACKET, &request.out());
+		assert_eq!(1, io.packets.len());
+	}
 }
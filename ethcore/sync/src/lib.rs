@@ -59,6 +59,7 @@ mod block_sync;
 mod sync_io;
 mod private_tx;
 mod snapshot;
+mod state_sync;
 mod transactions_stats;
 
 pub mod light_sync;
@@ -124,7 +124,7 @@ impl AncestorSearch {
 		match self {
 			AncestorSearch::Awaiting(id, start, req) => {
 				if &id == ctx.req_id() {
-					match response::verify(ctx.data(), &req) {
+					match response::verify(ctx.data(), &req, ctx.eip1559_transition()) {
 						Ok(headers) => {
 							for header in &headers {
 								if client.is_known(&header.hash()) {
@@ -218,6 +218,7 @@ struct ResponseCtx<'a> {
 	req_id: ReqId,
 	ctx: &'a BasicContext,
 	data: &'a [encoded::Header],
+	eip1559_transition: u64,
 }
 
 impl<'a> ResponseContext for ResponseCtx<'a> {
@@ -225,6 +226,7 @@ impl<'a> ResponseContext for ResponseCtx<'a> {
 	fn req_id(&self) -> &ReqId { &self.req_id }
 	fn data(&self) -> &[encoded::Header] { self.data }
 	fn punish_responder(&self) { self.ctx.disable_peer(self.peer) }
+	fn eip1559_transition(&self) -> u64 { self.eip1559_transition }
 }
 
 /// Light client synchronization manager. See module docs for more details.
@@ -388,6 +390,7 @@ impl<L: AsLightClient + Send + Sync> Handler for LightSync<L> {
 				req_id: req_id,
 				ctx: ctx.as_basic(),
 				data: headers,
+				eip1559_transition: self.client.as_light_client().engine().params().eip1559_transition,
 			};
 
 			*state = match mem::replace(&mut *state, SyncState::Idle) {
@@ -44,6 +44,8 @@ pub trait ResponseContext {
 	fn data(&self) -> &[encoded::Header];
 	/// Punish the responder.
 	fn punish_responder(&self);
+	/// Block number from which headers carry the EIP-1559 base fee.
+	fn eip1559_transition(&self) -> u64;
 }
 
 /// Reasons for sync round abort.
@@ -191,7 +193,7 @@ impl Fetcher {
 			return SyncRound::Fetch(self);
 		}
 
-		match response::verify(headers, &request.headers_request) {
+		match response::verify(headers, &request.headers_request, ctx.eip1559_transition()) {
 			Err(e) => {
 				trace!(target: "sync", "Punishing peer {} for invalid response ({})", ctx.responder(), e);
 				ctx.punish_responder();
@@ -376,7 +378,7 @@ impl RoundStart {
 			}
 		};
 
-		match response::verify(ctx.data(), &req) {
+		match response::verify(ctx.data(), &req, ctx.eip1559_transition()) {
 			Ok(headers) => {
 				if self.sparse_headers.is_empty()
 					&& headers.get(0).map_or(false, |x| x.parent_hash() != &self.start_block.1) {
@@ -0,0 +1,896 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State download from the live state of peers.
+//!
+//! Instead of snapshot chunks, the state at a recent pivot block is downloaded as ranges of
+//! the account and storage tries, each checked against its root with a Merkle range proof.
+//! The account key space is split in a few tasks downloaded in parallel; storage tries and
+//! code are fetched for the accounts found in the ranges.
+//!
+//! The pivot is taken from the headers of a single peer, so its state is only downloaded once
+//! a few other peers have confirmed they know the pivot block.
+//!
+//! Peers only keep the state of recent blocks, so the pivot is moved to a newer block once
+//! no peer has its state any more. Ranges downloaded so far are kept, and the account trie
+//! is healed afterwards by walking it from the new root and fetching the missing nodes by
+//! hash. Storage tries which have changed are downloaded again.
+//!
+//! The restoration is finished by the snapshot service with the pivot block, as after
+//! a snapshot restoration.
+
+use std::collections::{HashMap, HashSet};
+
+use block_sync::BlockDownloaderImportError as DownloaderImportError;
+use blocks::{SyncBody, SyncHeader};
+use bytes::Bytes;
+use ethcore::header::BlockNumber;
+use ethcore::snapshot::{BasicAccount, ManifestData, SnapshotService};
+use ethcore::snapshot::range::{node_refs, verify_range, TrieRange};
+use ethereum_types::{H256, U256};
+use hash::{keccak, KECCAK_EMPTY, KECCAK_NULL_RLP};
+use network::PeerId;
+use rlp::{self, Rlp, RlpStream};
+use transaction::UnverifiedTransaction;
+use triehash_ethereum::ordered_trie_root;
+use types::receipt::Receipt;
+
+/// Number of blocks the pivot is behind the best block of the peer it's taken from.
+/// Peers prune older states, so it's kept close to the head.
+pub const PIVOT_DISTANCE: u64 = 32;
+/// Size of the state ranges asked for.
+pub const STATE_RANGE_SIZE: usize = 512 * 1024;
+/// Number of peers which must know the pivot block before its state is downloaded.
+pub const PIVOT_MIN_PEERS: usize = 3;
+/// Maximum number of nodes asked for at once.
+pub const MAX_STATE_NODES_TO_REQUEST: usize = 384;
+/// Number of tasks the account key space is split in, one for each leading nibble.
+const ACCOUNT_TASKS: usize = 16;
+/// Nodes of the account trie visited by the heal walk on each request.
+const HEAL_WALK_STEPS: usize = 4096;
+
+/// A state sync request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateRequest {
+	/// Headers back from a peer's block with the given total difficulty, the oldest one becomes the pivot.
+	PivotHeaders(H256, U256),
+	/// Header of the pivot block, asked for from another peer to confirm it.
+	ConfirmPivot(H256),
+	/// Body of the pivot block.
+	PivotBody(H256),
+	/// Receipts of the pivot block.
+	PivotReceipts(H256),
+	/// Range of the account trie, or of the storage trie of an account, at a block.
+	Range {
+		block: H256,
+		account: Option<H256>,
+		origin: H256,
+	},
+	/// Trie nodes or code by hash, with the account they belong to.
+	Nodes(Vec<(Option<H256>, H256)>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+	/// Choosing the pivot block.
+	Pivot,
+	/// Waiting for peers to confirm the pivot block.
+	ConfirmPivot,
+	/// Fetching the body and receipts of the pivot block.
+	PivotBlock,
+	/// Downloading ranges.
+	Ranges,
+	/// Walking the account trie for nodes the ranges have missed.
+	Heal,
+	/// The state is complete.
+	Done,
+}
+
+/// Part of the account key space.
+struct AccountTask {
+	/// Next key to download from.
+	origin: H256,
+	/// First key of the next task.
+	limit: Option<H256>,
+	busy: bool,
+	done: bool,
+}
+
+/// Storage trie of an account.
+struct StorageTask {
+	root: H256,
+	origin: H256,
+	busy: bool,
+	/// Proof nodes, written once the whole trie is in so the root is only there for
+	/// complete tries.
+	proof: Vec<Bytes>,
+}
+
+struct Pivot {
+	header: SyncHeader,
+	parent_total_difficulty: U256,
+	body: Option<SyncBody>,
+	receipts: Option<Bytes>,
+}
+
+/// State sync downloader.
+pub struct StateSync {
+	phase: Phase,
+	pivot: Option<Pivot>,
+	/// Whether a request for the pivot is pending.
+	pivot_busy: bool,
+	/// Peers asked to confirm the pivot.
+	pivot_asked: HashSet<PeerId>,
+	/// Peers which know the pivot.
+	pivot_confirmed: HashSet<PeerId>,
+	/// Peers which don't know the pivot.
+	pivot_refused: HashSet<PeerId>,
+	/// Whether the pivot has been moved since ranges were downloaded.
+	moved: bool,
+	accounts: Vec<AccountTask>,
+	/// Proof nodes of the account ranges, written once all accounts are in.
+	account_proof: Vec<Bytes>,
+	storage: HashMap<H256, StorageTask>,
+	/// Accounts waiting for code, by code hash.
+	code: HashMap<H256, Vec<H256>>,
+	/// Nodes of the account trie left to visit by the heal walk, with their key nibbles.
+	heal: Vec<(Vec<u8>, H256)>,
+	/// Account trie nodes missing from the restored state, with their key nibbles.
+	missing: HashMap<H256, Vec<u8>>,
+	/// Nodes and code being downloaded.
+	requested: HashSet<(Option<H256>, H256)>,
+	/// Peers without the state of the pivot.
+	unavailable: HashSet<PeerId>,
+}
+
+impl StateSync {
+	/// Create a new instance.
+	pub fn new() -> StateSync {
+		StateSync {
+			phase: Phase::Pivot,
+			pivot: None,
+			pivot_busy: false,
+			pivot_asked: HashSet::new(),
+			pivot_confirmed: HashSet::new(),
+			pivot_refused: HashSet::new(),
+			moved: false,
+			accounts: account_tasks(),
+			account_proof: Vec::new(),
+			storage: HashMap::new(),
+			code: HashMap::new(),
+			heal: Vec::new(),
+			missing: HashMap::new(),
+			requested: HashSet::new(),
+			unavailable: HashSet::new(),
+		}
+	}
+
+	/// Clear everything.
+	pub fn clear(&mut self) {
+		*self = StateSync::new();
+	}
+
+	/// Check if the pivot block is being chosen or downloaded.
+	pub fn is_choosing_pivot(&self) -> bool {
+		self.phase == Phase::Pivot || self.phase == Phase::ConfirmPivot || self.phase == Phase::PivotBlock
+	}
+
+	/// Check if the pivot block is waiting for confirmations.
+	pub fn is_confirming_pivot(&self) -> bool {
+		self.phase == Phase::ConfirmPivot
+	}
+
+	/// Number of peers which have answered whether they know the pivot.
+	pub fn pivot_answers(&self) -> usize {
+		self.pivot_confirmed.len() + self.pivot_refused.len()
+	}
+
+	/// Check if the whole state is in and the restoration has been finished.
+	pub fn is_complete(&self) -> bool {
+		self.phase == Phase::Done
+	}
+
+	/// Number of the pivot block.
+	pub fn pivot_number(&self) -> Option<BlockNumber> {
+		self.pivot.as_ref().map(|p| p.header.header.number())
+	}
+
+	/// Number of peers found without the state of the pivot.
+	pub fn unavailable_peers(&self) -> usize {
+		self.unavailable.len()
+	}
+
+	/// Choose a newer pivot block, keeping the progress of the account tasks.
+	/// Storage tries being downloaded are dropped, they are healed under the new pivot.
+	pub fn move_pivot(&mut self) {
+		trace!(target: "sync", "Moving state sync pivot from #{:?}", self.pivot_number());
+		self.reject_pivot();
+		self.moved = true;
+		self.account_proof.clear();
+		self.storage.clear();
+		self.heal.clear();
+		self.missing.clear();
+		self.requested.clear();
+		self.unavailable.clear();
+		for task in &mut self.accounts {
+			task.busy = false;
+		}
+	}
+
+	/// Drop the pivot block before its state is downloaded and choose another one.
+	pub fn reject_pivot(&mut self) {
+		self.phase = Phase::Pivot;
+		self.pivot = None;
+		self.pivot_busy = false;
+		self.pivot_asked.clear();
+		self.pivot_confirmed.clear();
+		self.pivot_refused.clear();
+	}
+
+	/// Find something to download from a peer. `peer_head` is a block of the peer with its total difficulty, if known.
+	pub fn request(&mut self, snapshot_service: &SnapshotService, peer_id: PeerId, peer_head: Option<(H256, U256)>) -> Option<StateRequest> {
+		match self.phase {
+			Phase::Pivot if !self.pivot_busy => {
+				// the total difficulty of the pivot is derived from the peer's
+				let (hash, difficulty) = peer_head?;
+				self.pivot_busy = true;
+				Some(StateRequest::PivotHeaders(hash, difficulty))
+			},
+			Phase::ConfirmPivot if !self.pivot_asked.contains(&peer_id) => {
+				self.pivot_asked.insert(peer_id);
+				let pivot = self.pivot.as_ref().expect("pivot is set before it's confirmed; qed");
+				Some(StateRequest::ConfirmPivot(pivot.header.header.hash()))
+			},
+			Phase::PivotBlock if !self.pivot_busy => {
+				self.pivot_busy = true;
+				let pivot = self.pivot.as_ref().expect("pivot is set before its block is downloaded; qed");
+				let hash = pivot.header.header.hash();
+				match pivot.body {
+					None => Some(StateRequest::PivotBody(hash)),
+					Some(_) => Some(StateRequest::PivotReceipts(hash)),
+				}
+			},
+			Phase::Ranges | Phase::Heal => {
+				if self.phase == Phase::Heal {
+					self.walk(snapshot_service);
+				}
+				let request = self.needed_data();
+				if request.is_none() {
+					self.check_progress(snapshot_service);
+				}
+				request
+			},
+			_ => None,
+		}
+	}
+
+	/// Give up a pending request.
+	pub fn clear_request(&mut self, request: &StateRequest) {
+		match *request {
+			StateRequest::PivotHeaders(..) | StateRequest::PivotBody(_) | StateRequest::PivotReceipts(_) => {
+				self.pivot_busy = false;
+			},
+			// the peer isn't asked again
+			StateRequest::ConfirmPivot(_) => (),
+			StateRequest::Range { account: None, ref origin, .. } => {
+				self.accounts[task_index(origin)].busy = false;
+			},
+			StateRequest::Range { account: Some(ref account), .. } => {
+				if let Some(task) = self.storage.get_mut(account) {
+					task.busy = false;
+				}
+			},
+			StateRequest::Nodes(ref nodes) => {
+				for node in nodes {
+					self.requested.remove(node);
+				}
+			},
+		}
+	}
+
+	/// Handle the headers asked for to choose the pivot, newest first. The headers must have been verified.
+	/// `latest_difficulty` is the total difficulty of the newest one, as announced by the peer.
+	pub fn on_pivot_headers(&mut self, peer_id: PeerId, latest: &H256, latest_difficulty: U256, headers: Vec<SyncHeader>) -> Result<(), DownloaderImportError> {
+		self.pivot_busy = false;
+		if self.phase != Phase::Pivot {
+			return Ok(());
+		}
+		if headers.len() as u64 != PIVOT_DISTANCE + 1 {
+			trace!(target: "sync", "Peer chain is too short for a state sync pivot");
+			return Err(DownloaderImportError::Useless);
+		}
+		if headers[0].header.hash() != *latest || headers.windows(2).any(|w| *w[0].header.parent_hash() != w[1].header.hash()
+			|| w[0].header.number() != w[1].header.number() + 1)
+		{
+			return Err(DownloaderImportError::Invalid);
+		}
+
+		let difficulty = headers.iter()
+			.fold(Some(U256::zero()), |total, h| total.and_then(|t| t.checked_add(*h.header.difficulty())));
+		let parent_total_difficulty = difficulty.and_then(|d| latest_difficulty.checked_sub(d))
+			.ok_or(DownloaderImportError::Invalid)?;
+
+		let header = headers.into_iter().last().expect("headers are not empty; qed");
+		trace!(target: "sync", "State sync pivot #{} {}", header.header.number(), header.header.hash());
+		self.pivot = Some(Pivot {
+			header,
+			parent_total_difficulty,
+			body: None,
+			receipts: None,
+		});
+		self.phase = Phase::ConfirmPivot;
+		self.pivot_asked.insert(peer_id);
+		self.on_pivot_confirmed(peer_id);
+		Ok(())
+	}
+
+	/// Handle the header asked for from a peer to confirm the pivot, `hash` is the hash of the pivot it was asked for.
+	pub fn on_pivot_confirmation(&mut self, peer_id: PeerId, hash: &H256, headers: Vec<SyncHeader>) -> Result<(), DownloaderImportError> {
+		let pivot_hash = match self.pivot {
+			Some(ref pivot) if self.phase == Phase::ConfirmPivot => pivot.header.header.hash(),
+			_ => return Ok(()),
+		};
+		if pivot_hash != *hash {
+			return Ok(());
+		}
+		match headers.len() {
+			0 => {
+				trace!(target: "sync", "{}: Pivot {} is unknown", peer_id, pivot_hash);
+				self.pivot_refused.insert(peer_id);
+				Err(DownloaderImportError::Useless)
+			},
+			1 if headers[0].header.hash() == pivot_hash => {
+				self.on_pivot_confirmed(peer_id);
+				Ok(())
+			},
+			_ => Err(DownloaderImportError::Invalid),
+		}
+	}
+
+	/// Handle the body of the pivot block.
+	pub fn on_pivot_body(&mut self, snapshot_service: &SnapshotService, body: SyncBody) -> Result<(), DownloaderImportError> {
+		self.pivot_busy = false;
+		if self.phase != Phase::PivotBlock {
+			return Ok(());
+		}
+		{
+			let pivot = self.pivot.as_mut().expect("pivot is set before its block is downloaded; qed");
+			let transactions_root = ordered_trie_root(Rlp::new(&body.transactions_bytes).iter().map(|r| UnverifiedTransaction::envelope_from_rlp(&r)));
+			if transactions_root != *pivot.header.header.transactions_root() || keccak(&body.uncles_bytes) != *pivot.header.header.uncles_hash() {
+				return Err(DownloaderImportError::Invalid);
+			}
+			pivot.body = Some(body);
+		}
+		self.check_pivot(snapshot_service);
+		Ok(())
+	}
+
+	/// Handle the receipts of the pivot block.
+	pub fn on_pivot_receipts(&mut self, snapshot_service: &SnapshotService, receipts: Bytes) -> Result<(), DownloaderImportError> {
+		self.pivot_busy = false;
+		if self.phase != Phase::PivotBlock {
+			return Ok(());
+		}
+		{
+			let pivot = self.pivot.as_mut().expect("pivot is set before its block is downloaded; qed");
+			let receipts_root = ordered_trie_root(Rlp::new(&receipts).iter().map(|r| Receipt::envelope_from_rlp(&r)));
+			if receipts_root != *pivot.header.header.receipts_root() {
+				return Err(DownloaderImportError::Invalid);
+			}
+			pivot.receipts = Some(receipts);
+		}
+		self.check_pivot(snapshot_service);
+		Ok(())
+	}
+
+	/// Handle a state range, `None` if the peer doesn't have the state of the block.
+	pub fn on_range(
+		&mut self,
+		snapshot_service: &SnapshotService,
+		peer_id: PeerId,
+		block: &H256,
+		account: Option<H256>,
+		origin: &H256,
+		range: Option<TrieRange>,
+	) -> Result<(), DownloaderImportError> {
+		let (pivot_hash, state_root) = match self.pivot {
+			Some(ref pivot) => (pivot.header.header.hash(), *pivot.header.header.state_root()),
+			None => return Ok(()),
+		};
+		let expected = (self.phase == Phase::Ranges || self.phase == Phase::Heal) && *block == pivot_hash;
+		let root = match account {
+			None => {
+				let task = &mut self.accounts[task_index(origin)];
+				task.busy = false;
+				if !expected || task.done || task.origin != *origin {
+					return Ok(());
+				}
+				state_root
+			},
+			Some(ref account) => match self.storage.get_mut(account) {
+				Some(task) => {
+					task.busy = false;
+					if !expected || task.origin != *origin {
+						return Ok(());
+					}
+					task.root
+				},
+				None => return Ok(()),
+			},
+		};
+
+		let range = match range {
+			Some(range) => range,
+			None => {
+				trace!(target: "sync", "{}: No state for the pivot {}", peer_id, pivot_hash);
+				self.unavailable.insert(peer_id);
+				return Err(DownloaderImportError::Useless);
+			},
+		};
+		let verified = verify_range(&root, origin, &range).map_err(|e| {
+			debug!(target: "sync", "{}: Bad state range: {}", peer_id, e);
+			DownloaderImportError::Invalid
+		})?;
+
+		let next = match range.items.last() {
+			Some(&(ref last, _)) if verified.more => next_key(last),
+			_ => None,
+		};
+		if !verified.nodes.is_empty() {
+			snapshot_service.restore_state_nodes(account, verified.nodes);
+		}
+
+		match account {
+			None => {
+				for &(ref key, ref value) in &range.items {
+					let account: BasicAccount = rlp::decode(value).map_err(|_| DownloaderImportError::Invalid)?;
+					self.note_account(snapshot_service, key, &account);
+				}
+				self.account_proof.extend(range.proof);
+
+				// a range may run into the following tasks
+				for task in self.accounts[task_index(origin)..].iter_mut().filter(|t| !t.done && t.origin >= *origin) {
+					match next {
+						Some(next) if task.limit.map_or(true, |limit| next < limit) => {
+							if task.origin < next {
+								task.origin = next;
+							}
+						},
+						Some(next) if task.origin >= next => (),
+						_ => task.done = true,
+					}
+				}
+			},
+			Some(ref account) => {
+				let done = {
+					let task = self.storage.get_mut(account).expect("task was found above; qed");
+					task.proof.extend(range.proof);
+					match next {
+						Some(next) => {
+							task.origin = next;
+							false
+						},
+						None => true,
+					}
+				};
+				if done {
+					let task = self.storage.remove(account).expect("task was found above; qed");
+					if !task.proof.is_empty() {
+						snapshot_service.restore_state_nodes(Some(*account), task.proof);
+					}
+				}
+			},
+		}
+
+		self.check_progress(snapshot_service);
+		Ok(())
+	}
+
+	/// Handle trie nodes and code asked for by hash.
+	pub fn on_nodes(&mut self, snapshot_service: &SnapshotService, requested: &[(Option<H256>, H256)], nodes: Vec<Bytes>) -> Result<(), DownloaderImportError> {
+		let mut nodes: HashMap<H256, Bytes> = nodes.into_iter().map(|node| (keccak(&node), node)).collect();
+		let mut delivered = 0;
+		for &(account, ref hash) in requested {
+			self.requested.remove(&(account, *hash));
+			let node = match nodes.remove(hash) {
+				Some(node) => node,
+				None => continue,
+			};
+			delivered += 1;
+			match account {
+				Some(_) => {
+					for account in self.code.remove(hash).unwrap_or_else(Vec::new) {
+						snapshot_service.restore_state_nodes(Some(account), vec![node.clone()]);
+					}
+				},
+				None => {
+					if let Some(prefix) = self.missing.remove(hash) {
+						snapshot_service.restore_state_nodes(None, vec![node]);
+						self.heal.push((prefix, *hash));
+					}
+				},
+			}
+		}
+
+		if delivered == 0 {
+			return Err(DownloaderImportError::Useless);
+		}
+		self.check_progress(snapshot_service);
+		Ok(())
+	}
+
+	// download the pivot block once enough peers know it.
+	fn on_pivot_confirmed(&mut self, peer_id: PeerId) {
+		self.pivot_confirmed.insert(peer_id);
+		if self.pivot_confirmed.len() >= PIVOT_MIN_PEERS {
+			trace!(target: "sync", "State sync pivot confirmed by {:?}", self.pivot_confirmed);
+			self.phase = Phase::PivotBlock;
+		}
+	}
+
+	// begin the restoration once the pivot block is in.
+	fn check_pivot(&mut self, snapshot_service: &SnapshotService) {
+		let manifest = match self.pivot {
+			Some(Pivot { ref header, body: Some(_), receipts: Some(_), .. }) => ManifestData {
+				version: 2,
+				state_hashes: Vec::new(),
+				block_hashes: Vec::new(),
+				state_root: *header.header.state_root(),
+				block_number: header.header.number(),
+				block_hash: header.header.hash(),
+			},
+			_ => return,
+		};
+		trace!(target: "sync", "Restoring state at #{}", manifest.block_number);
+		snapshot_service.begin_state_restore(manifest);
+		self.phase = Phase::Ranges;
+	}
+
+	// queue the storage trie and code of an account if they aren't restored yet.
+	fn note_account(&mut self, snapshot_service: &SnapshotService, key: &H256, account: &BasicAccount) {
+		if account.storage_root != KECCAK_NULL_RLP && !self.storage.contains_key(key)
+			&& snapshot_service.restored_state_node(Some(*key), &account.storage_root).is_none()
+		{
+			self.storage.insert(*key, StorageTask {
+				root: account.storage_root,
+				origin: H256::zero(),
+				busy: false,
+				proof: Vec::new(),
+			});
+		}
+		if account.code_hash != KECCAK_EMPTY && snapshot_service.restored_state_node(Some(*key), &account.code_hash).is_none() {
+			let accounts = self.code.entry(account.code_hash).or_insert_with(Vec::new);
+			if !accounts.contains(key) {
+				accounts.push(*key);
+			}
+		}
+	}
+
+	// find nodes, code or a range to download.
+	fn needed_data(&mut self) -> Option<StateRequest> {
+		let nodes: Vec<_> = self.code.iter()
+			.map(|(hash, accounts)| (Some(accounts[0]), *hash))
+			.chain(self.missing.keys().map(|hash| (None, *hash)))
+			.filter(|node| !self.requested.contains(node))
+			.take(MAX_STATE_NODES_TO_REQUEST)
+			.collect();
+		if !nodes.is_empty() {
+			self.requested.extend(nodes.iter().cloned());
+			return Some(StateRequest::Nodes(nodes));
+		}
+
+		let block = self.pivot.as_ref().expect("ranges are downloaded after the pivot is set; qed").header.header.hash();
+		if let Some((account, task)) = self.storage.iter_mut().find(|&(_, ref task)| !task.busy) {
+			task.busy = true;
+			return Some(StateRequest::Range { block, account: Some(*account), origin: task.origin });
+		}
+		if let Some(task) = self.accounts.iter_mut().find(|task| !task.busy && !task.done) {
+			task.busy = true;
+			return Some(StateRequest::Range { block, account: None, origin: task.origin });
+		}
+		None
+	}
+
+	// visit nodes of the account trie, queueing whatever is missing.
+	fn walk(&mut self, snapshot_service: &SnapshotService) {
+		for _ in 0..HEAL_WALK_STEPS {
+			let (prefix, hash) = match self.heal.pop() {
+				Some(node) => node,
+				None => return,
+			};
+			let node = match snapshot_service.restored_state_node(None, &hash) {
+				Some(node) => node,
+				None => {
+					self.missing.insert(hash, prefix);
+					continue;
+				},
+			};
+			let refs = match node_refs(&prefix, &node) {
+				Ok(refs) => refs,
+				Err(e) => {
+					debug!(target: "sync", "Undecodable state node {}: {}", hash, e);
+					continue;
+				},
+			};
+			self.heal.extend(refs.children);
+			for (key, value) in refs.leaves {
+				match rlp::decode::<BasicAccount>(&value) {
+					Ok(account) => self.note_account(snapshot_service, &key, &account),
+					Err(e) => debug!(target: "sync", "Undecodable account {}: {}", key, e),
+				}
+			}
+		}
+	}
+
+	// move on once the current phase is over.
+	fn check_progress(&mut self, snapshot_service: &SnapshotService) {
+		let idle = self.storage.is_empty() && self.code.is_empty() && self.requested.is_empty();
+		match self.phase {
+			Phase::Ranges if idle && self.accounts.iter().all(|task| task.done) => {
+				let proof = ::std::mem::replace(&mut self.account_proof, Vec::new());
+				if !proof.is_empty() {
+					snapshot_service.restore_state_nodes(None, proof);
+				}
+				if self.moved {
+					let root = *self.pivot.as_ref().expect("ranges are downloaded after the pivot is set; qed").header.header.state_root();
+					trace!(target: "sync", "Healing state trie {}", root);
+					self.heal.push((Vec::new(), root));
+					self.phase = Phase::Heal;
+				} else {
+					self.finish(snapshot_service);
+				}
+			},
+			Phase::Heal if idle && self.heal.is_empty() && self.missing.is_empty() => {
+				self.finish(snapshot_service);
+			},
+			_ => (),
+		}
+	}
+
+	fn finish(&mut self, snapshot_service: &SnapshotService) {
+		let pivot = self.pivot.as_ref().expect("state is downloaded after the pivot is set; qed");
+		let body = pivot.body.as_ref().expect("ranges are downloaded after the pivot block; qed");
+		let receipts = pivot.receipts.clone().expect("ranges are downloaded after the pivot block; qed");
+
+		let mut block = RlpStream::new_list(3);
+		block.append_raw(&pivot.header.bytes, 1);
+		block.append_raw(&body.transactions_bytes, 1);
+		block.append_raw(&body.uncles_bytes, 1);
+
+		trace!(target: "sync", "State sync complete at #{}", pivot.header.header.number());
+		snapshot_service.finish_state_restore(block.out(), receipts, pivot.parent_total_difficulty);
+		self.phase = Phase::Done;
+	}
+}
+
+fn account_tasks() -> Vec<AccountTask> {
+	let start = |i: usize| {
+		let mut key = H256::zero();
+		key[0] = (i << 4) as u8;
+		key
+	};
+	(0..ACCOUNT_TASKS).map(|i| AccountTask {
+		origin: start(i),
+		limit: if i + 1 < ACCOUNT_TASKS { Some(start(i + 1)) } else { None },
+		busy: false,
+		done: false,
+	}).collect()
+}
+
+/// Index of the account task holding a key.
+fn task_index(key: &H256) -> usize {
+	(key[0] >> 4) as usize
+}
+
+/// The key following `key`, `None` for the last one.
+fn next_key(key: &H256) -> Option<H256> {
+	let mut next = *key;
+	for byte in next.iter_mut().rev() {
+		*byte = byte.wrapping_add(1);
+		if *byte != 0 {
+			return Some(next);
+		}
+	}
+	None
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use ethcore::header::Header;
+	use ethcore::snapshot::RestorationStatus;
+	use tests::snapshot::TestSnapshotService;
+
+	fn headers(count: u64) -> Vec<SyncHeader> {
+		let mut parent_hash = H256::zero();
+		let mut headers = Vec::new();
+		for number in 0..count {
+			let mut header = Header::new();
+			header.set_number(number);
+			header.set_parent_hash(parent_hash);
+			header.set_difficulty(U256::from(10));
+			header.set_state_root(KECCAK_NULL_RLP);
+			parent_hash = header.hash();
+			headers.push(SyncHeader::from_rlp(::rlp::encode(&header).into_vec(), BlockNumber::max_value()).unwrap());
+		}
+		headers.reverse();
+		headers
+	}
+
+	fn empty_body() -> SyncBody {
+		let mut stream = RlpStream::new_list(2);
+		stream.begin_list(0);
+		stream.begin_list(0);
+		SyncBody::from_rlp(&stream.out(), BlockNumber::max_value()).unwrap()
+	}
+
+	// leaf node of a trie with a single account, which is also its root.
+	fn single_account(key: &H256, account: &BasicAccount) -> (H256, Bytes) {
+		let mut path = vec![0x20];
+		path.extend_from_slice(key);
+		let mut stream = RlpStream::new_list(2);
+		stream.append(&path);
+		stream.append(&::rlp::encode(account).into_vec());
+		let node = stream.out();
+		(keccak(&node), node)
+	}
+
+	#[test]
+	fn chooses_pivot() {
+		let mut sync = StateSync::new();
+		let ss = TestSnapshotService::new();
+		let latest = keccak("latest");
+		// peers with unknown total difficulty can't provide the pivot
+		assert_eq!(sync.request(&ss, 0, None), None);
+		assert_eq!(sync.request(&ss, 0, Some((latest, U256::from(1000)))), Some(StateRequest::PivotHeaders(latest, U256::from(1000))));
+		assert_eq!(sync.request(&ss, 1, Some((latest, U256::from(1000)))), None);
+
+		let headers = headers(PIVOT_DISTANCE + 10);
+		let (newest, chain) = (headers[0].header.hash(), &headers[..PIVOT_DISTANCE as usize + 1]);
+		assert_eq!(sync.on_pivot_headers(0, &newest, U256::from(1000), chain[..5].to_vec()), Err(DownloaderImportError::Useless));
+		assert_eq!(sync.on_pivot_headers(0, &latest, U256::from(1000), chain.to_vec()), Err(DownloaderImportError::Invalid));
+		let mut broken = chain.to_vec();
+		broken.swap(3, 4);
+		assert_eq!(sync.on_pivot_headers(0, &newest, U256::from(1000), broken), Err(DownloaderImportError::Invalid));
+		assert_eq!(sync.on_pivot_headers(0, &newest, U256::from(10), chain.to_vec()), Err(DownloaderImportError::Invalid));
+
+		assert_eq!(sync.on_pivot_headers(0, &newest, U256::from(1000), chain.to_vec()), Ok(()));
+		assert_eq!(sync.pivot_number(), Some(9));
+		assert_eq!(sync.pivot.as_ref().unwrap().parent_total_difficulty, U256::from(1000 - 10 * (PIVOT_DISTANCE + 1)));
+		let pivot_hash = chain[PIVOT_DISTANCE as usize].header.hash();
+		let pivot = vec![chain[PIVOT_DISTANCE as usize].clone()];
+
+		// the pivot block is downloaded once other peers know it
+		assert!(sync.is_confirming_pivot());
+		assert_eq!(sync.request(&ss, 0, Some((latest, U256::from(1000)))), None);
+		for peer in 1..3 {
+			assert_eq!(sync.request(&ss, peer, None), Some(StateRequest::ConfirmPivot(pivot_hash)));
+		}
+		assert_eq!(sync.on_pivot_confirmation(1, &pivot_hash, Vec::new()), Err(DownloaderImportError::Useless));
+		assert_eq!(sync.on_pivot_confirmation(2, &pivot_hash, chain[..1].to_vec()), Err(DownloaderImportError::Invalid));
+		assert_eq!(sync.pivot_answers(), 2);
+		for peer in 3..PIVOT_MIN_PEERS + 2 {
+			assert!(sync.is_confirming_pivot());
+			assert_eq!(sync.request(&ss, peer, None), Some(StateRequest::ConfirmPivot(pivot_hash)));
+			assert_eq!(sync.on_pivot_confirmation(peer, &pivot_hash, pivot.clone()), Ok(()));
+		}
+		assert!(!sync.is_confirming_pivot());
+		assert_eq!(sync.request(&ss, 5, None), Some(StateRequest::PivotBody(pivot_hash)));
+	}
+
+	#[test]
+	fn rejects_pivot() {
+		let mut sync = StateSync::new();
+		let ss = TestSnapshotService::new();
+		let headers = headers(PIVOT_DISTANCE + 1);
+		let newest = headers[0].header.hash();
+		sync.request(&ss, 0, Some((newest, U256::from(1000))));
+		sync.on_pivot_headers(0, &newest, U256::from(1000), headers).unwrap();
+		assert!(sync.is_confirming_pivot());
+
+		sync.reject_pivot();
+		assert!(!sync.is_confirming_pivot());
+		assert_eq!(sync.pivot_number(), None);
+		assert_eq!(sync.pivot_answers(), 0);
+		assert_eq!(sync.request(&ss, 1, Some((newest, U256::from(1000)))), Some(StateRequest::PivotHeaders(newest, U256::from(1000))));
+	}
+
+	#[test]
+	fn downloads_state() {
+		let mut sync = StateSync::new();
+		let ss = TestSnapshotService::new();
+		let key = keccak("account");
+		let code = vec![1u8, 2, 3];
+		let account = BasicAccount {
+			nonce: U256::zero(),
+			balance: U256::from(5),
+			storage_root: KECCAK_NULL_RLP,
+			code_hash: keccak(&code),
+		};
+		let (root, node) = single_account(&key, &account);
+
+		let mut headers = headers(PIVOT_DISTANCE + 1);
+		for header in &mut headers {
+			header.header.set_state_root(root);
+			header.bytes = ::rlp::encode(&header.header).into_vec();
+		}
+		// relink the chain after changing the roots
+		for i in (0..headers.len() - 1).rev() {
+			let parent_hash = headers[i + 1].header.hash();
+			headers[i].header.set_parent_hash(parent_hash);
+			headers[i].bytes = ::rlp::encode(&headers[i].header).into_vec();
+		}
+		let newest = headers[0].header.hash();
+		let pivot_hash = headers[PIVOT_DISTANCE as usize].header.hash();
+
+		let pivot = vec![headers[PIVOT_DISTANCE as usize].clone()];
+		sync.request(&ss, 0, Some((newest, U256::from(1000))));
+		sync.on_pivot_headers(0, &newest, U256::from(1000), headers).unwrap();
+		for peer in 1..PIVOT_MIN_PEERS {
+			sync.request(&ss, peer, None);
+			sync.on_pivot_confirmation(peer, &pivot_hash, pivot.clone()).unwrap();
+		}
+		sync.request(&ss, 0, Some((newest, U256::from(1000))));
+		sync.on_pivot_body(&ss, empty_body()).unwrap();
+		assert_eq!(sync.request(&ss, 0, Some((newest, U256::from(1000)))), Some(StateRequest::PivotReceipts(pivot_hash)));
+		sync.on_pivot_receipts(&ss, ::rlp::EMPTY_LIST_RLP.to_vec()).unwrap();
+		assert!(!sync.is_choosing_pivot());
+		assert!(ss.status() != RestorationStatus::Inactive);
+
+		// the whole trie fits in the range of the first task
+		let request = StateRequest::Range { block: pivot_hash, account: None, origin: H256::zero() };
+		assert_eq!(sync.request(&ss, 0, Some((newest, U256::from(1000)))), Some(request));
+		let range = TrieRange { items: vec![(key, ::rlp::encode(&account).into_vec())], proof: Vec::new() };
+		sync.on_range(&ss, 0, &pivot_hash, None, &H256::zero(), Some(range)).unwrap();
+		assert_eq!(ss.restored_state_node(None, &root), Some(node));
+		assert!(sync.accounts.iter().all(|task| task.done));
+
+		assert_eq!(sync.request(&ss, 0, Some((newest, U256::from(1000)))), Some(StateRequest::Nodes(vec![(Some(key), keccak(&code))])));
+		assert!(!sync.is_complete());
+		sync.on_nodes(&ss, &[(Some(key), keccak(&code))], vec![code]).unwrap();
+		assert!(sync.is_complete());
+		assert_eq!(ss.status(), RestorationStatus::Inactive);
+	}
+
+	#[test]
+	fn moves_pivot() {
+		let mut sync = StateSync::new();
+		sync.phase = Phase::Ranges;
+		sync.accounts[3].origin[5] = 1;
+		sync.accounts[4].busy = true;
+		sync.storage.insert(keccak("account"), StorageTask { root: keccak("root"), origin: H256::zero(), busy: true, proof: Vec::new() });
+		sync.unavailable.insert(1);
+
+		sync.move_pivot();
+		assert!(sync.is_choosing_pivot());
+		assert!(sync.storage.is_empty());
+		assert_eq!(sync.unavailable_peers(), 0);
+		assert_eq!(sync.accounts[3].origin[5], 1);
+		assert!(sync.accounts.iter().all(|task| !task.busy));
+	}
+
+	#[test]
+	fn key_order() {
+		let mut key = H256::zero();
+		key[31] = 0xff;
+		key[30] = 0x01;
+		let mut expected = H256::zero();
+		expected[30] = 0x02;
+		assert_eq!(next_key(&key), Some(expected));
+		assert_eq!(next_key(&H256::from(U256::max_value())), None);
+		assert_eq!(task_index(&H256::from(U256::max_value())), ACCOUNT_TASKS - 1);
+	}
+}
@@ -17,7 +17,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use hash::keccak;
-use ethereum_types::H256;
+use ethereum_types::{H256, U256};
 use parking_lot::Mutex;
 use bytes::Bytes;
 use ethcore::snapshot::{SnapshotService, ManifestData, RestorationStatus};
@@ -33,6 +33,7 @@ pub struct TestSnapshotService {
 	restoration_manifest: Mutex<Option<ManifestData>>,
 	state_restoration_chunks: Mutex<HashMap<H256, Bytes>>,
 	block_restoration_chunks: Mutex<HashMap<H256, Bytes>>,
+	state_restoration_nodes: Mutex<Option<HashMap<(Option<H256>, H256), Bytes>>>,
 }
 
 impl TestSnapshotService {
@@ -43,6 +44,7 @@ impl TestSnapshotService {
 			restoration_manifest: Mutex::new(None),
 			state_restoration_chunks: Mutex::new(HashMap::new()),
 			block_restoration_chunks: Mutex::new(HashMap::new()),
+			state_restoration_nodes: Mutex::new(None),
 		}
 	}
 
@@ -67,6 +69,7 @@ impl TestSnapshotService {
 			restoration_manifest: Mutex::new(None),
 			state_restoration_chunks: Mutex::new(HashMap::new()),
 			block_restoration_chunks: Mutex::new(HashMap::new()),
+			state_restoration_nodes: Mutex::new(None),
 		}
 	}
 }
@@ -89,6 +92,9 @@ impl SnapshotService for TestSnapshotService {
 	}
 
 	fn status(&self) -> RestorationStatus {
+		if self.state_restoration_nodes.lock().is_some() {
+			return RestorationStatus::Ongoing { state_chunks: 0, block_chunks: 0, state_chunks_done: 0, block_chunks_done: 0 };
+		}
 		match *self.restoration_manifest.lock() {
 			Some(ref manifest) if self.state_restoration_chunks.lock().len() == manifest.state_hashes.len() &&
 				self.block_restoration_chunks.lock().len() == manifest.block_hashes.len() => RestorationStatus::Inactive,
@@ -118,6 +124,7 @@ impl SnapshotService for TestSnapshotService {
 
 	fn abort_restore(&self) {
 		*self.restoration_manifest.lock() = None;
+		*self.state_restoration_nodes.lock() = None;
 		self.state_restoration_chunks.lock().clear();
 		self.block_restoration_chunks.lock().clear();
 	}
@@ -134,6 +141,25 @@ impl SnapshotService for TestSnapshotService {
 		}
 	}
 
+	fn begin_state_restore(&self, manifest: ManifestData) {
+		*self.restoration_manifest.lock() = Some(manifest);
+		self.state_restoration_nodes.lock().get_or_insert_with(HashMap::new);
+	}
+
+	fn restore_state_nodes(&self, account: Option<H256>, nodes: Vec<Bytes>) {
+		if let Some(ref mut restored) = *self.state_restoration_nodes.lock() {
+			restored.extend(nodes.into_iter().map(|node| ((account, keccak(&node)), node)));
+		}
+	}
+
+	fn restored_state_node(&self, account: Option<H256>, hash: &H256) -> Option<Bytes> {
+		self.state_restoration_nodes.lock().as_ref().and_then(|restored| restored.get(&(account, *hash)).cloned())
+	}
+
+	fn finish_state_restore(&self, _block: Bytes, _receipts: Bytes, _parent_total_difficulty: U256) {
+		*self.state_restoration_nodes.lock() = None;
+	}
+
 	fn shutdown(&self) {
 		self.abort_restore();
 	}
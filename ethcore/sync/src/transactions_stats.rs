@@ -49,9 +49,25 @@ impl<'a> From<&'a Stats> for TransactionStats {
 	}
 }
 
+/// Transaction announcement and fetch counters of a single peer.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct PeerStats {
+	/// Transaction hashes we announced to the peer.
+	pub announced_to: usize,
+	/// Transaction hashes the peer announced to us.
+	pub announced_by: usize,
+	/// Transactions we requested from the peer.
+	pub requested: usize,
+	/// Requested transactions the peer delivered.
+	pub delivered: usize,
+	/// Requests to the peer that timed out.
+	pub timeouts: usize,
+}
+
 #[derive(Debug, Default)]
 pub struct TransactionsStats {
 	pending_transactions: H256FastMap<Stats>,
+	peers: HashMap<NodeId, PeerStats>,
 }
 
 impl TransactionsStats {
@@ -73,6 +89,51 @@ impl TransactionsStats {
 		&self.pending_transactions
 	}
 
+	/// Increases number of transaction hashes announced to given `enode_id`.
+	pub fn announced_to(&mut self, enode_id: Option<NodeId>, count: usize) {
+		let stats = self.peer_mut(enode_id);
+		stats.announced_to = stats.announced_to.saturating_add(count);
+	}
+
+	/// Increases number of transaction hashes announced by given `enode_id`.
+	pub fn announced_by(&mut self, enode_id: Option<NodeId>, count: usize) {
+		let stats = self.peer_mut(enode_id);
+		stats.announced_by = stats.announced_by.saturating_add(count);
+	}
+
+	/// Increases number of transactions requested from given `enode_id`.
+	pub fn requested(&mut self, enode_id: Option<NodeId>, count: usize) {
+		let stats = self.peer_mut(enode_id);
+		stats.requested = stats.requested.saturating_add(count);
+	}
+
+	/// Increases number of requested transactions delivered by given `enode_id`.
+	pub fn delivered(&mut self, enode_id: Option<NodeId>, count: usize) {
+		let stats = self.peer_mut(enode_id);
+		stats.delivered = stats.delivered.saturating_add(count);
+	}
+
+	/// Increases number of timed out transaction requests to given `enode_id`.
+	pub fn timed_out(&mut self, enode_id: Option<NodeId>) {
+		let stats = self.peer_mut(enode_id);
+		stats.timeouts = stats.timeouts.saturating_add(1);
+	}
+
+	/// Drops announce and fetch stats of a disconnected peer.
+	pub fn peer_disconnected(&mut self, enode_id: Option<NodeId>) {
+		self.peers.remove(&enode_id.unwrap_or_default());
+	}
+
+	/// Returns announce and fetch stats of given `enode_id` or `None` if the peer is not known.
+	#[cfg(test)]
+	pub fn peer(&self, enode_id: &NodeId) -> Option<&PeerStats> {
+		self.peers.get(enode_id)
+	}
+
+	fn peer_mut(&mut self, enode_id: Option<NodeId>) -> &mut PeerStats {
+		self.peers.entry(enode_id.unwrap_or_default()).or_insert_with(PeerStats::default)
+	}
+
 	/// Retains only transactions present in given `HashSet`.
 	pub fn retain(&mut self, hashes: &HashSet<H256>) {
 		let to_remove = self.pending_transactions.keys()
@@ -90,7 +151,7 @@ impl TransactionsStats {
 mod tests {
 
 	use std::collections::{HashMap, HashSet};
-	use super::{Stats, TransactionsStats};
+	use super::{Stats, PeerStats, TransactionsStats};
 
 	#[test]
 	fn should_keep_track_of_propagations() {
@@ -131,4 +192,31 @@ mod tests {
 		let stats = stats.get(&hash);
 		assert_eq!(stats, None);
 	}
+
+	#[test]
+	fn should_keep_track_of_announcements_and_fetches() {
+		// given
+		let mut stats = TransactionsStats::default();
+		let enodeid1 = 2.into();
+		let enodeid2 = 5.into();
+
+		// when
+		stats.announced_to(Some(enodeid1), 10);
+		stats.announced_by(Some(enodeid1), 4);
+		stats.requested(Some(enodeid1), 3);
+		stats.delivered(Some(enodeid1), 2);
+		stats.requested(Some(enodeid2), 1);
+		stats.timed_out(Some(enodeid2));
+		stats.peer_disconnected(Some(enodeid2));
+
+		// then
+		assert_eq!(stats.peer(&enodeid1), Some(&PeerStats {
+			announced_to: 10,
+			announced_by: 4,
+			requested: 3,
+			delivered: 2,
+			timeouts: 0,
+		}));
+		assert_eq!(stats.peer(&enodeid2), None);
+	}
 }
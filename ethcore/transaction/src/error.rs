@@ -79,6 +79,10 @@ pub enum Error {
 	TooBig,
 	/// Invalid RLP encoding
 	InvalidRlp(String),
+	/// Transaction type is not enabled on this chain yet.
+	TransactionTypeNotEnabled,
+	/// Max priority fee per gas is higher than the max fee per gas.
+	PriorityFeeHigherThanFeeCap,
 }
 
 impl From<ethkey::Error> for Error {
@@ -119,6 +123,8 @@ impl fmt::Display for Error {
 			NotAllowed => "Sender does not have permissions to execute this type of transction".into(),
 			TooBig => "Transaction too big".into(),
 			InvalidRlp(ref err) => format!("Transaction has invalid RLP structure: {}.", err),
+			TransactionTypeNotEnabled => "Transaction type is not enabled for this block".into(),
+			PriorityFeeHigherThanFeeCap => "Max priority fee per gas is higher than max fee per gas".into(),
 		};
 
 		f.write_fmt(format_args!("Transaction error ({})", msg))
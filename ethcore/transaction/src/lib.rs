@@ -16,6 +16,7 @@
 
 //! Ethereum Transactions
 
+extern crate common_types as types;
 extern crate ethereum_types;
 extern crate ethjson;
 extern crate ethkey;
@@ -33,3 +34,4 @@ mod transaction;
 
 pub use error::Error;
 pub use transaction::*;
+pub use types::transaction_type::TypedTxId;
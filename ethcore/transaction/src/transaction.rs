@@ -16,6 +16,7 @@
 
 //! Transaction data structure.
 
+use std::{cmp, mem};
 use std::ops::Deref;
 use ethereum_types::{H256, H160, Address, U256};
 use error;
@@ -25,6 +26,7 @@ use evm::Schedule;
 use hash::keccak;
 use heapsize::HeapSizeOf;
 use rlp::{self, RlpStream, Rlp, DecoderError, Encodable};
+use types::transaction_type::TypedTxId;
 
 type Bytes = Vec<u8>;
 type BlockNumber = u64;
@@ -68,6 +70,44 @@ impl rlp::Encodable for Action {
 	}
 }
 
+/// Account and storage keys which a transaction declares to access (EIP-2930).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AccessListItem {
+	/// Accessed account.
+	pub address: Address,
+	/// Accessed storage keys of the account.
+	pub storage_keys: Vec<H256>,
+}
+
+impl rlp::Decodable for AccessListItem {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 2 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+		Ok(AccessListItem {
+			address: rlp.val_at(0)?,
+			storage_keys: rlp.list_at(1)?,
+		})
+	}
+}
+
+impl rlp::Encodable for AccessListItem {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(2);
+		s.append(&self.address);
+		s.append_list(&self.storage_keys);
+	}
+}
+
+impl HeapSizeOf for AccessListItem {
+	fn heap_size_of_children(&self) -> usize {
+		self.storage_keys.capacity() * mem::size_of::<H256>()
+	}
+}
+
+/// Transaction access list (EIP-2930).
+pub type AccessList = Vec<AccessListItem>;
+
 /// Transaction activation condition.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Condition {
@@ -84,6 +124,14 @@ pub mod signature {
 		v + if let Some(n) = chain_id { 35 + n * 2 } else { 27 }
 	}
 
+	/// Adds chain id into v, `None` if the result doesn't fit.
+	/// Used for chain ids which come from untrusted input.
+	pub fn checked_chain_replay_protection(v: u64, chain_id: u64) -> Option<u64> {
+		chain_id.checked_mul(2)
+			.and_then(|n| n.checked_add(35))
+			.and_then(|n| n.checked_add(v))
+	}
+
 	/// Returns refined v
 	/// 0 if `v` would have been 27 under "Electrum" notation, 1 if 28 or 4 if invalid.
 	pub fn check_replay_protection(v: u64) -> u8 {
@@ -132,6 +180,36 @@ impl Transaction {
 	}
 }
 
+impl Transaction {
+	/// Append the signing payload of an EIP-2930 transaction into RLP stream
+	fn rlp_append_access_list_payload(&self, s: &mut RlpStream, chain_id: u64, access_list: &AccessList) {
+		s.begin_list(8);
+		s.append(&chain_id);
+		s.append(&self.nonce);
+		s.append(&self.gas_price);
+		s.append(&self.gas);
+		s.append(&self.action);
+		s.append(&self.value);
+		s.append(&self.data);
+		s.append_list(access_list);
+	}
+
+	/// Append the signing payload of an EIP-1559 transaction into RLP stream.
+	/// The gas price of the transaction is used as the fee cap.
+	fn rlp_append_eip1559_payload(&self, s: &mut RlpStream, chain_id: u64, max_priority_fee_per_gas: &U256, access_list: &AccessList) {
+		s.begin_list(9);
+		s.append(&chain_id);
+		s.append(&self.nonce);
+		s.append(max_priority_fee_per_gas);
+		s.append(&self.gas_price);
+		s.append(&self.gas);
+		s.append(&self.action);
+		s.append(&self.value);
+		s.append(&self.data);
+		s.append_list(access_list);
+	}
+}
+
 impl HeapSizeOf for Transaction {
 	fn heap_size_of_children(&self) -> usize {
 		self.data.heap_size_of_children()
@@ -179,6 +257,9 @@ impl From<ethjson::transaction::Transaction> for UnverifiedTransaction {
 			s: t.s.into(),
 			v: t.v.into(),
 			hash: 0.into(),
+			transaction_type: TypedTxId::Legacy,
+			access_list: AccessList::new(),
+			max_priority_fee_per_gas: U256::zero(),
 		}.compute_hash()
 	}
 }
@@ -199,6 +280,75 @@ impl Transaction {
 			.expect("secret is valid so it's recoverable")
 	}
 
+	/// The message hash of the transaction as an EIP-2930 access list transaction.
+	pub fn access_list_hash(&self, chain_id: u64, access_list: &AccessList) -> H256 {
+		let mut stream = RlpStream::new();
+		self.rlp_append_access_list_payload(&mut stream, chain_id, access_list);
+		let mut payload = vec![TypedTxId::AccessList.to_byte().expect("typed transaction has a type byte; qed")];
+		payload.extend_from_slice(stream.as_raw());
+		keccak(payload)
+	}
+
+	/// Signs the transaction as an EIP-2930 access list transaction coming from `sender`.
+	pub fn sign_with_access_list(self, secret: &Secret, chain_id: u64, access_list: AccessList) -> SignedTransaction {
+		let sig = ::ethkey::sign(secret, &self.access_list_hash(chain_id, &access_list))
+			.expect("data is valid and context has signing capabilities; qed");
+		SignedTransaction::new(self.with_access_list_signature(sig, chain_id, access_list))
+			.expect("secret is valid so it's recoverable")
+	}
+
+	/// The message hash of the transaction as an EIP-1559 transaction.
+	pub fn eip1559_hash(&self, chain_id: u64, max_priority_fee_per_gas: &U256, access_list: &AccessList) -> H256 {
+		let mut stream = RlpStream::new();
+		self.rlp_append_eip1559_payload(&mut stream, chain_id, max_priority_fee_per_gas, access_list);
+		let mut payload = vec![TypedTxId::EIP1559Transaction.to_byte().expect("typed transaction has a type byte; qed")];
+		payload.extend_from_slice(stream.as_raw());
+		keccak(payload)
+	}
+
+	/// Signs the transaction as an EIP-1559 transaction coming from `sender`.
+	/// The gas price of the transaction is used as the fee cap.
+	pub fn sign_eip1559(self, secret: &Secret, chain_id: u64, max_priority_fee_per_gas: U256, access_list: AccessList) -> SignedTransaction {
+		let sig = ::ethkey::sign(secret, &self.eip1559_hash(chain_id, &max_priority_fee_per_gas, &access_list))
+			.expect("data is valid and context has signing capabilities; qed");
+		SignedTransaction::new(self.with_eip1559_signature(sig, chain_id, max_priority_fee_per_gas, access_list))
+			.expect("secret is valid so it's recoverable")
+	}
+
+	/// Signs the transaction with signature as an EIP-1559 transaction.
+	pub fn with_eip1559_signature(
+		self,
+		sig: Signature,
+		chain_id: u64,
+		max_priority_fee_per_gas: U256,
+		access_list: AccessList,
+	) -> UnverifiedTransaction {
+		UnverifiedTransaction {
+			unsigned: self,
+			r: sig.r().into(),
+			s: sig.s().into(),
+			v: signature::add_chain_replay_protection(sig.v() as u64, Some(chain_id)),
+			hash: 0.into(),
+			transaction_type: TypedTxId::EIP1559Transaction,
+			access_list: access_list,
+			max_priority_fee_per_gas: max_priority_fee_per_gas,
+		}.compute_hash()
+	}
+
+	/// Signs the transaction with signature as an EIP-2930 access list transaction.
+	pub fn with_access_list_signature(self, sig: Signature, chain_id: u64, access_list: AccessList) -> UnverifiedTransaction {
+		UnverifiedTransaction {
+			unsigned: self,
+			r: sig.r().into(),
+			s: sig.s().into(),
+			v: signature::add_chain_replay_protection(sig.v() as u64, Some(chain_id)),
+			hash: 0.into(),
+			transaction_type: TypedTxId::AccessList,
+			access_list: access_list,
+			max_priority_fee_per_gas: U256::zero(),
+		}.compute_hash()
+	}
+
 	/// Signs the transaction with signature.
 	pub fn with_signature(self, sig: Signature, chain_id: Option<u64>) -> UnverifiedTransaction {
 		UnverifiedTransaction {
@@ -207,6 +357,9 @@ impl Transaction {
 			s: sig.s().into(),
 			v: signature::add_chain_replay_protection(sig.v() as u64, chain_id),
 			hash: 0.into(),
+			transaction_type: TypedTxId::Legacy,
+			access_list: AccessList::new(),
+			max_priority_fee_per_gas: U256::zero(),
 		}.compute_hash()
 	}
 
@@ -219,6 +372,9 @@ impl Transaction {
 			s: U256::one(),
 			v: 0,
 			hash: 0.into(),
+			transaction_type: TypedTxId::Legacy,
+			access_list: AccessList::new(),
+			max_priority_fee_per_gas: U256::zero(),
 		}.compute_hash()
 	}
 
@@ -231,6 +387,9 @@ impl Transaction {
 				s: U256::one(),
 				v: 0,
 				hash: 0.into(),
+				transaction_type: TypedTxId::Legacy,
+				access_list: AccessList::new(),
+				max_priority_fee_per_gas: U256::zero(),
 			}.compute_hash(),
 			sender: from,
 			public: None,
@@ -246,6 +405,9 @@ impl Transaction {
 				s: U256::zero(),
 				v: chain_id,
 				hash: 0.into(),
+				transaction_type: TypedTxId::Legacy,
+				access_list: AccessList::new(),
+				max_priority_fee_per_gas: U256::zero(),
 			}.compute_hash(),
 			sender: UNSIGNED_SENDER,
 			public: None,
@@ -260,6 +422,14 @@ impl Transaction {
 		)
 	}
 
+	/// Get the cost in gas of the given access list.
+	pub fn access_list_gas_required(access_list: &AccessList, schedule: &Schedule) -> u64 {
+		access_list.iter().fold(0, |g, item| {
+			g + schedule.tx_access_list_address_gas as u64
+				+ item.storage_keys.len() as u64 * schedule.tx_access_list_storage_key_gas as u64
+		})
+	}
+
 	/// Get the transaction cost in gas for this transaction.
 	pub fn gas_required(&self, schedule: &Schedule) -> u64 {
 		Self::gas_required_for(match self.action{Action::Create=>true, Action::Call(_)=>false}, &self.data, schedule)
@@ -280,11 +450,18 @@ pub struct UnverifiedTransaction {
 	s: U256,
 	/// Hash of the transaction
 	hash: H256,
+	/// Type of the transaction envelope (EIP-2718).
+	transaction_type: TypedTxId,
+	/// Access list, empty for legacy transactions (EIP-2930).
+	access_list: AccessList,
+	/// Maximum tip per gas paid to the block author, zero unless this is an EIP-1559
+	/// transaction. The gas price of the plain transaction is then the fee cap.
+	max_priority_fee_per_gas: U256,
 }
 
 impl HeapSizeOf for UnverifiedTransaction {
 	fn heap_size_of_children(&self) -> usize {
-		self.unsigned.heap_size_of_children()
+		self.unsigned.heap_size_of_children() + self.access_list.heap_size_of_children()
 	}
 }
 
@@ -298,6 +475,14 @@ impl Deref for UnverifiedTransaction {
 
 impl rlp::Decodable for UnverifiedTransaction {
 	fn decode(d: &Rlp) -> Result<Self, DecoderError> {
+		if d.is_data() {
+			// only typed transactions are wrapped in a string, legacy ones are plain lists
+			let envelope = d.data()?;
+			if envelope.first().map_or(false, |b| *b >= 0xc0) {
+				return Err(DecoderError::Custom("Legacy transaction wrapped in a string"));
+			}
+			return UnverifiedTransaction::decode_envelope(envelope);
+		}
 		if d.item_count()? != 9 {
 			return Err(DecoderError::RlpIncorrectListLen);
 		}
@@ -315,6 +500,9 @@ impl rlp::Decodable for UnverifiedTransaction {
 			r: d.val_at(7)?,
 			s: d.val_at(8)?,
 			hash: hash,
+			transaction_type: TypedTxId::Legacy,
+			access_list: AccessList::new(),
+			max_priority_fee_per_gas: U256::zero(),
 		})
 	}
 }
@@ -326,11 +514,170 @@ impl rlp::Encodable for UnverifiedTransaction {
 impl UnverifiedTransaction {
 	/// Used to compute hash of created transactions
 	fn compute_hash(mut self) -> UnverifiedTransaction {
-		let hash = keccak(&*self.rlp_bytes());
+		let hash = keccak(&*self.encode_envelope());
 		self.hash = hash;
 		self
 	}
 
+	/// Decodes a transaction from its canonical encoding: an RLP list for legacy
+	/// transactions or `type || payload` for typed transactions (EIP-2718).
+	pub fn decode_envelope(bytes: &[u8]) -> Result<Self, DecoderError> {
+		let type_byte = match bytes.first() {
+			Some(b) => *b,
+			None => return Err(DecoderError::RlpIsTooShort),
+		};
+		if type_byte >= 0xc0 {
+			return rlp::decode(bytes);
+		}
+		match TypedTxId::from_byte(type_byte) {
+			Some(TypedTxId::AccessList) => Self::decode_access_list_payload(&bytes[1..]),
+			Some(TypedTxId::EIP1559Transaction) => Self::decode_eip1559_payload(&bytes[1..]),
+			_ => Err(DecoderError::Custom("Unknown transaction type")),
+		}
+	}
+
+	fn decode_access_list_payload(payload: &[u8]) -> Result<Self, DecoderError> {
+		let d = Rlp::new(payload);
+		if d.item_count()? != 11 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+		let chain_id: u64 = d.val_at(0)?;
+		let y_parity: u64 = d.val_at(8)?;
+		if y_parity > 1 {
+			return Err(DecoderError::Custom("Invalid signature y parity"));
+		}
+		let mut envelope = Vec::with_capacity(payload.len() + 1);
+		envelope.push(TypedTxId::AccessList.to_byte().expect("typed transaction has a type byte; qed"));
+		envelope.extend_from_slice(payload);
+		Ok(UnverifiedTransaction {
+			unsigned: Transaction {
+				nonce: d.val_at(1)?,
+				gas_price: d.val_at(2)?,
+				gas: d.val_at(3)?,
+				action: d.val_at(4)?,
+				value: d.val_at(5)?,
+				data: d.val_at(6)?,
+			},
+			v: signature::checked_chain_replay_protection(y_parity, chain_id)
+				.ok_or(DecoderError::Custom("Chain id out of range"))?,
+			r: d.val_at(9)?,
+			s: d.val_at(10)?,
+			hash: keccak(&envelope),
+			transaction_type: TypedTxId::AccessList,
+			access_list: d.list_at(7)?,
+			max_priority_fee_per_gas: U256::zero(),
+		})
+	}
+
+	fn decode_eip1559_payload(payload: &[u8]) -> Result<Self, DecoderError> {
+		let d = Rlp::new(payload);
+		if d.item_count()? != 12 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+		let chain_id: u64 = d.val_at(0)?;
+		let y_parity: u64 = d.val_at(9)?;
+		if y_parity > 1 {
+			return Err(DecoderError::Custom("Invalid signature y parity"));
+		}
+		let mut envelope = Vec::with_capacity(payload.len() + 1);
+		envelope.push(TypedTxId::EIP1559Transaction.to_byte().expect("typed transaction has a type byte; qed"));
+		envelope.extend_from_slice(payload);
+		Ok(UnverifiedTransaction {
+			unsigned: Transaction {
+				nonce: d.val_at(1)?,
+				gas_price: d.val_at(3)?,
+				gas: d.val_at(4)?,
+				action: d.val_at(5)?,
+				value: d.val_at(6)?,
+				data: d.val_at(7)?,
+			},
+			v: signature::checked_chain_replay_protection(y_parity, chain_id)
+				.ok_or(DecoderError::Custom("Chain id out of range"))?,
+			r: d.val_at(10)?,
+			s: d.val_at(11)?,
+			hash: keccak(&envelope),
+			transaction_type: TypedTxId::EIP1559Transaction,
+			access_list: d.list_at(8)?,
+			max_priority_fee_per_gas: d.val_at(2)?,
+		})
+	}
+
+	/// Canonical encoding of the transaction, which is hashed and stored in the transactions trie:
+	/// an RLP list for legacy transactions, `type || payload` for typed ones (EIP-2718).
+	pub fn encode_envelope(&self) -> Bytes {
+		match self.transaction_type.to_byte() {
+			None => self.rlp_bytes().to_vec(),
+			Some(type_byte) => {
+				let is_eip1559 = self.transaction_type == TypedTxId::EIP1559Transaction;
+				let mut s = RlpStream::new_list(if is_eip1559 { 12 } else { 11 });
+				s.append(&self.chain_id().expect("typed transactions always have a chain id; qed"));
+				s.append(&self.nonce);
+				if is_eip1559 {
+					s.append(&self.max_priority_fee_per_gas);
+				}
+				s.append(&self.gas_price);
+				s.append(&self.gas);
+				s.append(&self.action);
+				s.append(&self.value);
+				s.append(&self.data);
+				s.append_list(&self.access_list);
+				s.append(&self.standard_v());
+				s.append(&self.r);
+				s.append(&self.s);
+				let mut envelope = vec![type_byte];
+				envelope.extend_from_slice(s.as_raw());
+				envelope
+			},
+		}
+	}
+
+	/// Returns the canonical encoding of a transaction given its RLP item in a block body,
+	/// without decoding it. Typed transactions are RLP strings wrapping the envelope.
+	pub fn envelope_from_rlp<'a>(rlp: &Rlp<'a>) -> &'a [u8] {
+		match rlp.is_data() {
+			true => rlp.data().unwrap_or(rlp.as_raw()),
+			false => rlp.as_raw(),
+		}
+	}
+
+	/// Type of the transaction envelope.
+	pub fn transaction_type(&self) -> TypedTxId {
+		self.transaction_type
+	}
+
+	/// Access list of the transaction, empty for legacy transactions.
+	pub fn access_list(&self) -> &AccessList {
+		&self.access_list
+	}
+
+	/// Maximum tip per gas paid to the block author. This is the gas price for transactions
+	/// other than EIP-1559 ones.
+	pub fn max_priority_fee_per_gas(&self) -> U256 {
+		match self.transaction_type {
+			TypedTxId::EIP1559Transaction => self.max_priority_fee_per_gas,
+			_ => self.gas_price,
+		}
+	}
+
+	/// Price paid per unit of gas in a block with the given base fee (EIP-1559).
+	pub fn effective_gas_price(&self, base_fee: Option<U256>) -> U256 {
+		match (self.transaction_type, base_fee) {
+			(TypedTxId::EIP1559Transaction, Some(base_fee)) =>
+				cmp::min(self.gas_price, base_fee.saturating_add(self.max_priority_fee_per_gas)),
+			_ => self.gas_price,
+		}
+	}
+
+	/// Tip per unit of gas paid to the block author in a block with the given base fee (EIP-1559).
+	pub fn effective_priority_fee(&self, base_fee: Option<U256>) -> U256 {
+		self.effective_gas_price(base_fee).saturating_sub(base_fee.unwrap_or_default())
+	}
+
+	/// Get the transaction cost in gas for this transaction, including its access list.
+	pub fn gas_required(&self, schedule: &Schedule) -> u64 {
+		self.unsigned.gas_required(schedule) + Transaction::access_list_gas_required(&self.access_list, schedule)
+	}
+
 	/// Checks is signature is empty.
 	pub fn is_unsigned(&self) -> bool {
 		self.r.is_zero() && self.s.is_zero()
@@ -338,6 +685,10 @@ impl UnverifiedTransaction {
 
 	/// Append object with a signature into RLP stream
 	fn rlp_append_sealed_transaction(&self, s: &mut RlpStream) {
+		if self.transaction_type != TypedTxId::Legacy {
+			s.append(&self.encode_envelope());
+			return;
+		}
 		s.begin_list(9);
 		s.append(&self.nonce);
 		s.append(&self.gas_price);
@@ -355,20 +706,47 @@ impl UnverifiedTransaction {
 		&self.unsigned
 	}
 
-	pub fn standard_v(&self) -> u8 { signature::check_replay_protection(self.v) }
+	pub fn standard_v(&self) -> u8 {
+		match self.transaction_type {
+			TypedTxId::Legacy => signature::check_replay_protection(self.v),
+			_ => ((self.v - 35) % 2) as u8,
+		}
+	}
 
 	/// The `v` value that appears in the RLP.
-	pub fn original_v(&self) -> u64 { self.v }
+	pub fn original_v(&self) -> u64 {
+		match self.transaction_type {
+			TypedTxId::Legacy => self.v,
+			_ => self.standard_v() as u64,
+		}
+	}
 
 	/// The chain ID, or `None` if this is a global transaction.
 	pub fn chain_id(&self) -> Option<u64> {
 		match self.v {
+			v if self.transaction_type != TypedTxId::Legacy => Some((v - 35) / 2),
 			v if self.is_unsigned() => Some(v),
 			v if v > 36 => Some((v - 35) / 2),
 			_ => None,
 		}
 	}
 
+	/// The hash which is signed by the sender of the transaction.
+	pub fn signature_hash(&self) -> H256 {
+		match self.transaction_type {
+			TypedTxId::Legacy => self.unsigned.hash(self.chain_id()),
+			TypedTxId::AccessList => self.unsigned.access_list_hash(
+				self.chain_id().expect("typed transactions always have a chain id; qed"),
+				&self.access_list,
+			),
+			TypedTxId::EIP1559Transaction => self.unsigned.eip1559_hash(
+				self.chain_id().expect("typed transactions always have a chain id; qed"),
+				&self.max_priority_fee_per_gas,
+				&self.access_list,
+			),
+		}
+	}
+
 	/// Construct a signature object from the sig.
 	pub fn signature(&self) -> Signature {
 		Signature::from_rsv(&self.r.into(), &self.s.into(), self.standard_v())
@@ -383,14 +761,14 @@ impl UnverifiedTransaction {
 		}
 	}
 
-	/// Get the hash of this transaction (keccak of the RLP).
+	/// Get the hash of this transaction (keccak of the canonical encoding).
 	pub fn hash(&self) -> H256 {
 		self.hash
 	}
 
 	/// Recovers the public key of the sender.
 	pub fn recover_public(&self) -> Result<Public, ethkey::Error> {
-		Ok(recover(&self.signature(), &self.unsigned.hash(self.chain_id()))?)
+		Ok(recover(&self.signature(), &self.signature_hash())?)
 	}
 
 	/// Verify basic signature params. Does not attempt sender recovery.
@@ -634,6 +1012,150 @@ mod tests {
 		assert_eq!(t.chain_id(), Some(69));
 	}
 
+	#[test]
+	fn should_encode_and_decode_access_list_transaction() {
+		use ethkey::{Random, Generator};
+
+		let key = Random.generate().unwrap();
+		let access_list = vec![AccessListItem {
+			address: Address::from(0x69),
+			storage_keys: vec![H256::from(1), H256::from(2)],
+		}];
+		let t = Transaction {
+			action: Action::Call(Address::from(0x69)),
+			nonce: U256::from(42),
+			gas_price: U256::from(3000),
+			gas: U256::from(50_000),
+			value: U256::from(1),
+			data: b"Hello!".to_vec()
+		}.sign_with_access_list(&key.secret(), 69, access_list.clone());
+		assert_eq!(Address::from(keccak(key.public())), t.sender());
+		assert_eq!(t.chain_id(), Some(69));
+		assert_eq!(t.transaction_type(), TypedTxId::AccessList);
+		assert!(t.original_v() <= 1);
+
+		let envelope = t.encode_envelope();
+		assert_eq!(envelope[0], 0x01);
+		assert_eq!(t.hash(), keccak(&envelope));
+
+		let decoded = UnverifiedTransaction::decode_envelope(&envelope).unwrap();
+		assert_eq!(decoded, *t);
+		assert_eq!(decoded.access_list(), &access_list);
+		assert_eq!(SignedTransaction::new(decoded).unwrap().sender(), t.sender());
+
+		// within a block body the envelope is wrapped in an RLP string
+		let wrapped = rlp::encode(&*t);
+		let decoded: UnverifiedTransaction = rlp::decode(&wrapped).unwrap();
+		assert_eq!(decoded, *t);
+		assert_eq!(UnverifiedTransaction::envelope_from_rlp(&Rlp::new(&wrapped)), &envelope[..]);
+	}
+
+	#[test]
+	fn should_reject_chain_id_overflow() {
+		let mut s = RlpStream::new_list(11);
+		s.append(&u64::max_value());
+		for _ in 0..6 {
+			s.append_empty_data();
+		}
+		s.begin_list(0);
+		s.append(&1u8);
+		s.append(&1u8);
+		s.append(&1u8);
+		let mut envelope = vec![0x01];
+		envelope.extend_from_slice(s.as_raw());
+		assert_eq!(
+			UnverifiedTransaction::decode_envelope(&envelope),
+			Err(DecoderError::Custom("Chain id out of range"))
+		);
+	}
+
+	#[test]
+	fn should_reject_wrapped_legacy_transaction() {
+		let t = Transaction {
+			action: Action::Create,
+			nonce: U256::from(42),
+			gas_price: U256::from(3000),
+			gas: U256::from(50_000),
+			value: U256::from(1),
+			data: b"Hello!".to_vec()
+		}.fake_sign(Address::from(0x69));
+		let legacy = rlp::encode(&*t);
+		assert!(rlp::decode::<UnverifiedTransaction>(&legacy).is_ok());
+		let wrapped = rlp::encode(&legacy.to_vec());
+		assert!(rlp::decode::<UnverifiedTransaction>(&wrapped).is_err());
+	}
+
+	#[test]
+	fn should_reject_unknown_transaction_type() {
+		assert!(UnverifiedTransaction::decode_envelope(&[0x7f, 0xc0]).is_err());
+		assert!(UnverifiedTransaction::decode_envelope(&[]).is_err());
+	}
+
+	#[test]
+	fn should_charge_for_access_list() {
+		let schedule = Schedule::new_berlin();
+		let access_list = vec![
+			AccessListItem { address: Address::from(1), storage_keys: vec![H256::from(1), H256::from(2)] },
+			AccessListItem { address: Address::from(2), storage_keys: vec![] },
+		];
+		let t = Transaction {
+			action: Action::Call(Address::from(1)),
+			nonce: U256::zero(),
+			gas_price: U256::zero(),
+			gas: U256::from(100_000),
+			value: U256::zero(),
+			data: vec![],
+		}.with_access_list_signature(Signature::default(), 1, access_list);
+		assert_eq!(t.gas_required(&schedule), 21000 + 2 * 2400 + 2 * 1900);
+	}
+
+	#[test]
+	fn should_encode_and_decode_eip1559_transaction() {
+		use ethkey::{Random, Generator};
+
+		let key = Random.generate().unwrap();
+		let t = Transaction {
+			action: Action::Call(Address::from(0x69)),
+			nonce: U256::from(42),
+			gas_price: U256::from(3000),
+			gas: U256::from(50_000),
+			value: U256::from(1),
+			data: b"Hello!".to_vec()
+		}.sign_eip1559(&key.secret(), 69, U256::from(200), vec![]);
+		assert_eq!(Address::from(keccak(key.public())), t.sender());
+		assert_eq!(t.transaction_type(), TypedTxId::EIP1559Transaction);
+		assert_eq!(t.max_priority_fee_per_gas(), U256::from(200));
+
+		let envelope = t.encode_envelope();
+		assert_eq!(envelope[0], 0x02);
+		assert_eq!(t.hash(), keccak(&envelope));
+
+		let decoded = UnverifiedTransaction::decode_envelope(&envelope).unwrap();
+		assert_eq!(decoded, *t);
+		assert_eq!(SignedTransaction::new(decoded).unwrap().sender(), t.sender());
+	}
+
+	#[test]
+	fn should_compute_effective_gas_price() {
+		let t = Transaction {
+			action: Action::Create,
+			nonce: U256::zero(),
+			gas_price: U256::from(3000),
+			gas: U256::from(50_000),
+			value: U256::zero(),
+			data: vec![],
+		};
+		let legacy = t.clone().with_signature(Signature::default(), Some(1));
+		let dynamic = t.with_eip1559_signature(Signature::default(), 1, U256::from(200), vec![]);
+
+		assert_eq!(legacy.effective_gas_price(Some(U256::from(1000))), U256::from(3000));
+		assert_eq!(legacy.effective_priority_fee(Some(U256::from(1000))), U256::from(2000));
+		assert_eq!(dynamic.effective_gas_price(Some(U256::from(1000))), U256::from(1200));
+		assert_eq!(dynamic.effective_priority_fee(Some(U256::from(1000))), U256::from(200));
+		assert_eq!(dynamic.effective_gas_price(Some(U256::from(2900))), U256::from(3000));
+		assert_eq!(dynamic.effective_priority_fee(Some(U256::from(2900))), U256::from(100));
+	}
+
 	#[test]
 	fn should_agree_with_vitalik() {
 		use rustc_hex::FromHex;
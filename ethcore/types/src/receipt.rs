@@ -21,7 +21,9 @@ use heapsize::HeapSizeOf;
 use rlp::{Rlp, RlpStream, Encodable, Decodable, DecoderError};
 
 use BlockNumber;
+use bytes::Bytes;
 use log_entry::{LogEntry, LocalizedLogEntry};
+use transaction_type::TypedTxId;
 
 /// Transaction outcome store in the receipt.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -45,10 +47,12 @@ pub struct Receipt {
 	pub logs: Vec<LogEntry>,
 	/// Transaction outcome.
 	pub outcome: TransactionOutcome,
+	/// Type of the transaction this receipt belongs to (EIP-2718).
+	pub transaction_type: TypedTxId,
 }
 
 impl Receipt {
-	/// Create a new receipt.
+	/// Create a new receipt of a legacy transaction.
 	pub fn new(outcome: TransactionOutcome, gas_used: U256, logs: Vec<LogEntry>) -> Self {
 		Self {
 			gas_used,
@@ -58,12 +62,49 @@ impl Receipt {
 			}),
 			logs,
 			outcome,
+			transaction_type: TypedTxId::Legacy,
 		}
 	}
-}
 
-impl Encodable for Receipt {
-	fn rlp_append(&self, s: &mut RlpStream) {
+	/// Canonical encoding of the receipt, which is stored in the receipts trie:
+	/// an RLP list for legacy transactions, `type || payload` for typed ones (EIP-2718).
+	pub fn encode_envelope(&self) -> Bytes {
+		let mut s = RlpStream::new();
+		self.rlp_append_payload(&mut s);
+		match self.transaction_type.to_byte() {
+			None => s.out(),
+			Some(type_byte) => {
+				let mut envelope = vec![type_byte];
+				envelope.extend_from_slice(s.as_raw());
+				envelope
+			},
+		}
+	}
+
+	/// Decodes a receipt from its canonical encoding.
+	pub fn decode_envelope(bytes: &[u8]) -> Result<Self, DecoderError> {
+		let type_byte = match bytes.first() {
+			Some(b) => *b,
+			None => return Err(DecoderError::RlpIsTooShort),
+		};
+		if type_byte >= 0xc0 {
+			return Self::decode_payload(&Rlp::new(bytes), TypedTxId::Legacy);
+		}
+		match TypedTxId::from_byte(type_byte) {
+			Some(transaction_type) => Self::decode_payload(&Rlp::new(&bytes[1..]), transaction_type),
+			None => Err(DecoderError::Custom("Unknown transaction type")),
+		}
+	}
+
+	/// Returns the canonical encoding of a receipt as it appears in a receipts list.
+	pub fn envelope_from_rlp<'a>(rlp: &Rlp<'a>) -> &'a [u8] {
+		match rlp.is_data() {
+			true => rlp.data().unwrap_or(rlp.as_raw()),
+			false => rlp.as_raw(),
+		}
+	}
+
+	fn rlp_append_payload(&self, s: &mut RlpStream) {
 		match self.outcome {
 			TransactionOutcome::Unknown => {
 				s.begin_list(3);
@@ -81,16 +122,15 @@ impl Encodable for Receipt {
 		s.append(&self.log_bloom);
 		s.append_list(&self.logs);
 	}
-}
 
-impl Decodable for Receipt {
-	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+	fn decode_payload(rlp: &Rlp, transaction_type: TypedTxId) -> Result<Self, DecoderError> {
 		if rlp.item_count()? == 3 {
 			Ok(Receipt {
 				outcome: TransactionOutcome::Unknown,
 				gas_used: rlp.val_at(0)?,
 				log_bloom: rlp.val_at(1)?,
 				logs: rlp.list_at(2)?,
+				transaction_type,
 			})
 		} else {
 			Ok(Receipt {
@@ -104,12 +144,31 @@ impl Decodable for Receipt {
 					} else {
 						TransactionOutcome::StateRoot(first.as_val()?)
 					}
-				}
+				},
+				transaction_type,
 			})
 		}
 	}
 }
 
+impl Encodable for Receipt {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		match self.transaction_type {
+			TypedTxId::Legacy => self.rlp_append_payload(s),
+			_ => { s.append(&self.encode_envelope()); },
+		}
+	}
+}
+
+impl Decodable for Receipt {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		match rlp.is_data() {
+			true => Receipt::decode_envelope(rlp.data()?),
+			false => Receipt::decode_payload(rlp, TypedTxId::Legacy),
+		}
+	}
+}
+
 impl HeapSizeOf for Receipt {
 	fn heap_size_of_children(&self) -> usize {
 		self.logs.heap_size_of_children()
@@ -170,6 +229,7 @@ pub struct LocalizedReceipt {
 mod tests {
 	use super::{Receipt, TransactionOutcome};
 	use log_entry::LogEntry;
+	use transaction_type::TypedTxId;
 
 	#[test]
 	fn test_no_state_root() {
@@ -221,4 +281,28 @@ mod tests {
 		let decoded: Receipt = ::rlp::decode(&encoded).expect("decoding receipt failed");
 		assert_eq!(decoded, r);
 	}
+
+	#[test]
+	fn test_typed_receipt() {
+		let mut r = Receipt::new(
+			TransactionOutcome::StatusCode(1),
+			0x40cae.into(),
+			vec![LogEntry {
+				address: "dcf421d093428b096ca501a7cd1a740855a7976f".into(),
+				topics: vec![],
+				data: vec![0u8; 32]
+			}]
+		);
+		let legacy_envelope = r.encode_envelope();
+		r.transaction_type = TypedTxId::AccessList;
+
+		let envelope = r.encode_envelope();
+		assert_eq!(envelope[0], 0x01);
+		assert_eq!(&envelope[1..], &legacy_envelope[..]);
+		assert_eq!(Receipt::decode_envelope(&envelope).unwrap(), r);
+
+		let encoded = ::rlp::encode(&r);
+		let decoded: Receipt = ::rlp::decode(&encoded).expect("decoding receipt failed");
+		assert_eq!(decoded, r);
+	}
 }
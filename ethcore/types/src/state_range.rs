@@ -0,0 +1,69 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Paged views of the account and storage tries.
+
+use ethereum_types::{H256, U256, Address};
+use bytes::Bytes;
+use basic_account::BasicAccount;
+
+/// A single storage slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageSlot {
+	/// Hash of the slot key, i.e. its position in the storage trie.
+	pub hashed_key: H256,
+	/// Slot key, if its preimage is known.
+	pub key: Option<H256>,
+	/// Slot value.
+	pub value: U256,
+}
+
+/// Storage slots of an account, ordered by hashed key.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StorageRange {
+	/// Storage slots.
+	pub storage: Vec<StorageSlot>,
+	/// Hashed key of the first slot following the range, if any.
+	pub next_key: Option<H256>,
+}
+
+/// A single account of an account range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeAccount {
+	/// Hash of the address, i.e. the position of the account in the state trie.
+	pub hashed_address: H256,
+	/// Address, if its preimage is known.
+	pub address: Option<Address>,
+	/// Account as stored in the state trie.
+	pub account: BasicAccount,
+	/// Account code, if requested.
+	pub code: Option<Bytes>,
+	/// First page of storage slots of the account, if requested.
+	pub storage: Option<Vec<StorageSlot>>,
+	/// Hashed key of the first storage slot following the returned ones, if any.
+	pub storage_next: Option<H256>,
+}
+
+/// Accounts of the state trie, ordered by hashed address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountRange {
+	/// State root the range was read from.
+	pub root: H256,
+	/// Accounts.
+	pub accounts: Vec<RangeAccount>,
+	/// Hashed address of the first account following the range, if any.
+	pub next: Option<H256>,
+}
@@ -0,0 +1,52 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction type identifiers (EIP-2718).
+
+/// Transaction type identifiers (EIP-2718).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TypedTxId {
+	/// Untyped transaction, encoded as a bare RLP list.
+	Legacy,
+	/// EIP-2930 transaction with an access list.
+	AccessList,
+	/// EIP-1559 transaction with a fee cap and a priority fee.
+	EIP1559Transaction,
+}
+
+impl Default for TypedTxId {
+	fn default() -> TypedTxId { TypedTxId::Legacy }
+}
+
+impl TypedTxId {
+	/// The type byte prefixing the transaction payload, `None` for legacy transactions.
+	pub fn to_byte(&self) -> Option<u8> {
+		match *self {
+			TypedTxId::Legacy => None,
+			TypedTxId::AccessList => Some(0x01),
+			TypedTxId::EIP1559Transaction => Some(0x02),
+		}
+	}
+
+	/// Parses the type byte of a typed transaction envelope.
+	pub fn from_byte(b: u8) -> Option<TypedTxId> {
+		match b {
+			0x01 => Some(TypedTxId::AccessList),
+			0x02 => Some(TypedTxId::EIP1559Transaction),
+			_ => None,
+		}
+	}
+}
@@ -0,0 +1,81 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Set of accounts and storage slots accessed during a transaction (EIP-2929).
+
+use std::collections::HashSet;
+use ethereum_types::{H256, Address};
+
+/// Accessed addresses and storage keys of a transaction.
+///
+/// An entry which is present is considered "warm"; anything else is "cold".
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccessList {
+	addresses: HashSet<Address>,
+	storage_keys: HashSet<(Address, H256)>,
+}
+
+impl AccessList {
+	/// Returns true if the address has already been accessed.
+	pub fn contains_address(&self, address: &Address) -> bool {
+		self.addresses.contains(address)
+	}
+
+	/// Marks the address as accessed.
+	pub fn insert_address(&mut self, address: Address) {
+		self.addresses.insert(address);
+	}
+
+	/// Returns true if the storage slot has already been accessed.
+	pub fn contains_storage_key(&self, address: &Address, key: &H256) -> bool {
+		self.storage_keys.contains(&(*address, *key))
+	}
+
+	/// Marks the storage slot as accessed.
+	pub fn insert_storage_key(&mut self, address: Address, key: H256) {
+		self.storage_keys.insert((address, key));
+	}
+
+	/// Number of accessed addresses.
+	pub fn addresses_len(&self) -> usize {
+		self.addresses.len()
+	}
+
+	/// Number of accessed storage slots.
+	pub fn storage_keys_len(&self) -> usize {
+		self.storage_keys.len()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::AccessList;
+
+	#[test]
+	fn insert_and_contains() {
+		let mut list = AccessList::default();
+		assert!(!list.contains_address(&1.into()));
+		list.insert_address(1.into());
+		assert!(list.contains_address(&1.into()));
+
+		assert!(!list.contains_storage_key(&1.into(), &2.into()));
+		list.insert_storage_key(1.into(), 2.into());
+		assert!(list.contains_storage_key(&1.into(), &2.into()));
+		assert!(!list.contains_storage_key(&2.into(), &2.into()));
+		assert_eq!(list.addresses_len(), 1);
+		assert_eq!(list.storage_keys_len(), 1);
+	}
+}
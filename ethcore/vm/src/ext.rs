@@ -144,6 +144,9 @@ pub trait Ext {
 	/// Returns environment info.
 	fn env_info(&self) -> &EnvInfo;
 
+	/// Returns the chain ID of the blockchain
+	fn chain_id(&self) -> u64;
+
 	/// Returns current depth of execution.
 	///
 	/// If contract A calls contract B, and contract B calls C,
@@ -167,4 +170,16 @@ pub trait Ext {
 
 	/// Check if running in static context.
 	fn is_static(&self) -> bool;
+
+	/// Check whether an address has been accessed in the current transaction (EIP-2929).
+	fn al_contains_address(&self, address: &Address) -> bool;
+
+	/// Mark an address as accessed in the current transaction (EIP-2929).
+	fn al_insert_address(&mut self, address: Address);
+
+	/// Check whether a storage key of the current contract has been accessed in the current transaction (EIP-2929).
+	fn al_contains_storage_key(&self, key: &H256) -> bool;
+
+	/// Mark a storage key of the current contract as accessed in the current transaction (EIP-2929).
+	fn al_insert_storage_key(&mut self, key: H256);
 }
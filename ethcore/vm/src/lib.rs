@@ -32,6 +32,7 @@ mod schedule;
 mod ext;
 mod return_data;
 mod error;
+mod access_list;
 
 pub mod tests;
 
@@ -41,6 +42,7 @@ pub use env_info::{EnvInfo, LastHashes};
 pub use schedule::{Schedule, CleanDustMode, WasmCosts};
 pub use ext::{Ext, MessageCallResult, ContractCreateResult, CreateContractAddress};
 pub use return_data::{ReturnData, GasLeft};
+pub use access_list::AccessList;
 pub use error::{Error, Result, TrapResult, TrapError, TrapKind, ExecTrapResult, ExecTrapError};
 
 /// Virtual Machine interface
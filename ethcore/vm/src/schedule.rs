@@ -28,6 +28,12 @@ pub struct Schedule {
 	pub have_revert: bool,
 	/// Does it have a EXTCODEHASH instruction
 	pub have_extcodehash: bool,
+	/// Does it have a CHAINID instruction
+	pub have_chain_id: bool,
+	/// Does it have a SELFBALANCE instruction
+	pub have_selfbalance: bool,
+	/// Does it have a BASEFEE instruction
+	pub have_basefee: bool,
 	/// VM stack limit
 	pub stack_limit: usize,
 	/// Max number of nested calls/creates
@@ -50,6 +56,8 @@ pub struct Schedule {
 	pub sstore_reset_gas: usize,
 	/// Gas refund for `SSTORE` clearing (when `storage!=0`, `new==0`)
 	pub sstore_refund_gas: usize,
+	/// Gas price for a no-op or dirty `SSTORE` under net gas metering. Falls back to `sload_gas` if `None`.
+	pub sstore_dirty_gas: Option<usize>,
 	/// Gas price for `JUMPDEST` opcode
 	pub jumpdest_gas: usize,
 	/// Gas price for `LOG*`
@@ -119,6 +127,24 @@ pub struct Schedule {
 	pub kill_dust: CleanDustMode,
 	/// Enable EIP-1283 rules
 	pub eip1283: bool,
+	/// Enable EIP-1706 rules (`SSTORE` fails when gas left is not above the call stipend)
+	pub eip1706: bool,
+	/// Enable EIP-2929 rules (cold/warm state access pricing)
+	pub eip2929: bool,
+	/// Gas price for the first access of a storage slot within a transaction (EIP-2929)
+	pub cold_sload_gas: usize,
+	/// Gas price for the first access of an account within a transaction (EIP-2929)
+	pub cold_account_access_gas: usize,
+	/// Gas price for accessing an already accessed account or storage slot (EIP-2929)
+	pub warm_storage_read_gas: usize,
+	/// Enable EIP-2930 typed access list transactions
+	pub eip2930: bool,
+	/// Cost for each address in a transaction access list (EIP-2930)
+	pub tx_access_list_address_gas: usize,
+	/// Cost for each storage key in a transaction access list (EIP-2930)
+	pub tx_access_list_storage_key_gas: usize,
+	/// Enable EIP-1559 base fee and dynamic fee transactions
+	pub eip1559: bool,
 	/// VM execution does not increase null signed address nonce if this field is true.
 	pub keep_unsigned_nonce: bool,
 	/// Wasm extra schedule settings, if wasm activated
@@ -210,6 +236,9 @@ impl Schedule {
 			have_return_data: false,
 			have_bitwise_shifting: false,
 			have_extcodehash: false,
+			have_chain_id: false,
+			have_selfbalance: false,
+			have_basefee: false,
 			stack_limit: 1024,
 			max_depth: 1024,
 			tier_step_gas: [0, 2, 3, 5, 8, 10, 20, 0],
@@ -221,6 +250,7 @@ impl Schedule {
 			sstore_set_gas: 20000,
 			sstore_reset_gas: 5000,
 			sstore_refund_gas: 15000,
+			sstore_dirty_gas: None,
 			jumpdest_gas: 1,
 			log_gas: 375,
 			log_data_gas: 8,
@@ -253,6 +283,15 @@ impl Schedule {
 			have_static_call: false,
 			kill_dust: CleanDustMode::Off,
 			eip1283: false,
+			eip1706: false,
+			eip2929: false,
+			cold_sload_gas: 2100,
+			cold_account_access_gas: 2600,
+			warm_storage_read_gas: 100,
+			eip2930: false,
+			tx_access_list_address_gas: 2400,
+			tx_access_list_storage_key_gas: 1900,
+			eip1559: false,
 			keep_unsigned_nonce: false,
 			wasm: None,
 		}
@@ -275,6 +314,54 @@ impl Schedule {
 		schedule
 	}
 
+	/// Schedule for the Istanbul fork of the Ethereum main net.
+	pub fn new_istanbul() -> Schedule {
+		let mut schedule = Self::new_constantinople();
+		schedule.have_extcodehash = true;
+		schedule.have_chain_id = true; // EIP 1344
+		schedule.have_selfbalance = true; // EIP 1884
+		schedule.sload_gas = 800; // EIP 1884
+		schedule.balance_gas = 700; // EIP 1884
+		schedule.extcodehash_gas = 700; // EIP 1884
+		schedule.tx_data_non_zero_gas = 16; // EIP 2028
+		schedule.eip1283 = true; // EIP 2200
+		schedule.eip1706 = true; // EIP 2200
+		schedule.sstore_dirty_gas = Some(800); // EIP 2200
+		schedule
+	}
+
+	/// Schedule for the Berlin fork of the Ethereum main net.
+	pub fn new_berlin() -> Schedule {
+		let mut schedule = Self::new_istanbul();
+		schedule.eip2930 = true; // EIP 2930
+		schedule.enable_eip2929(); // EIP 2929
+		schedule
+	}
+
+	/// Schedule for the London fork of the Ethereum main net.
+	pub fn new_london() -> Schedule {
+		let mut schedule = Self::new_berlin();
+		schedule.eip1559 = true; // EIP 1559
+		schedule.have_basefee = true; // EIP 3198
+		schedule
+	}
+
+	/// Switch to EIP-2929 cold/warm state access pricing.
+	///
+	/// Instruction base prices become the warm access price; the cold surcharge is
+	/// applied by the gasometer on first access of an account or storage slot.
+	pub fn enable_eip2929(&mut self) {
+		self.eip2929 = true;
+		self.sload_gas = self.warm_storage_read_gas;
+		self.sstore_dirty_gas = Some(self.warm_storage_read_gas);
+		self.sstore_reset_gas = 5000 - self.cold_sload_gas;
+		self.call_gas = self.warm_storage_read_gas;
+		self.balance_gas = self.warm_storage_read_gas;
+		self.extcodesize_gas = self.warm_storage_read_gas;
+		self.extcodecopy_base_gas = self.warm_storage_read_gas;
+		self.extcodehash_gas = self.warm_storage_read_gas;
+	}
+
 	fn new(efcd: bool, hdc: bool, tcg: usize) -> Schedule {
 		Schedule {
 			exceptional_failed_code_deposit: efcd,
@@ -284,6 +371,9 @@ impl Schedule {
 			have_return_data: false,
 			have_bitwise_shifting: false,
 			have_extcodehash: false,
+			have_chain_id: false,
+			have_selfbalance: false,
+			have_basefee: false,
 			stack_limit: 1024,
 			max_depth: 1024,
 			tier_step_gas: [0, 2, 3, 5, 8, 10, 20, 0],
@@ -295,6 +385,7 @@ impl Schedule {
 			sstore_set_gas: 20000,
 			sstore_reset_gas: 5000,
 			sstore_refund_gas: 15000,
+			sstore_dirty_gas: None,
 			jumpdest_gas: 1,
 			log_gas: 375,
 			log_data_gas: 8,
@@ -327,6 +418,15 @@ impl Schedule {
 			have_static_call: false,
 			kill_dust: CleanDustMode::Off,
 			eip1283: false,
+			eip1706: false,
+			eip2929: false,
+			cold_sload_gas: 2100,
+			cold_account_access_gas: 2600,
+			warm_storage_read_gas: 100,
+			eip2930: false,
+			tx_access_list_address_gas: 2400,
+			tx_access_list_storage_key_gas: 1900,
+			eip1559: false,
 			keep_unsigned_nonce: false,
 			wasm: None,
 		}
@@ -22,7 +22,7 @@ use bytes::Bytes;
 use {
 	CallType, Schedule, EnvInfo,
 	ReturnData, Ext, ContractCreateResult, MessageCallResult,
-	CreateContractAddress, Result, GasLeft,
+	CreateContractAddress, Result, GasLeft, AccessList,
 };
 use hash::keccak;
 use error::TrapKind;
@@ -63,10 +63,12 @@ pub struct FakeExt {
 	pub codes: HashMap<Address, Arc<Bytes>>,
 	pub logs: Vec<FakeLogEntry>,
 	pub info: EnvInfo,
+	pub chain_id: u64,
 	pub schedule: Schedule,
 	pub balances: HashMap<Address, U256>,
 	pub tracing: bool,
 	pub is_static: bool,
+	pub access_list: AccessList,
 }
 
 // similar to the normal `finalize` function, but ignoring NeedsReturn.
@@ -98,6 +100,27 @@ impl FakeExt {
 		ext
 	}
 
+	/// New fake externalities with Istanbul schedule rules
+	pub fn new_istanbul() -> Self {
+		let mut ext = FakeExt::default();
+		ext.schedule = Schedule::new_istanbul();
+		ext
+	}
+
+	/// New fake externalities with Berlin schedule rules
+	pub fn new_berlin() -> Self {
+		let mut ext = FakeExt::default();
+		ext.schedule = Schedule::new_berlin();
+		ext
+	}
+
+	/// New fake externalities with London schedule rules
+	pub fn new_london() -> Self {
+		let mut ext = FakeExt::default();
+		ext.schedule = Schedule::new_london();
+		ext
+	}
+
 	/// Alter fake externalities to allow wasm
 	pub fn with_wasm(mut self) -> Self {
 		self.schedule.wasm = Some(Default::default());
@@ -223,6 +246,10 @@ impl Ext for FakeExt {
 		&self.info
 	}
 
+	fn chain_id(&self) -> u64 {
+		self.chain_id
+	}
+
 	fn depth(&self) -> usize {
 		self.depth
 	}
@@ -242,4 +269,20 @@ impl Ext for FakeExt {
 	fn trace_next_instruction(&mut self, _pc: usize, _instruction: u8, _gas: U256) -> bool {
 		self.tracing
 	}
+
+	fn al_contains_address(&self, address: &Address) -> bool {
+		self.access_list.contains_address(address)
+	}
+
+	fn al_insert_address(&mut self, address: Address) {
+		self.access_list.insert_address(address);
+	}
+
+	fn al_contains_storage_key(&self, key: &H256) -> bool {
+		self.access_list.contains_storage_key(&Address::default(), key)
+	}
+
+	fn al_insert_storage_key(&mut self, key: H256) {
+		self.access_list.insert_storage_key(Address::default(), key);
+	}
 }
@@ -30,7 +30,7 @@ use std::num::ParseIntError;
 use std::{env, fmt, process, io, sync};
 
 use docopt::Docopt;
-use ethkey::{KeyPair, Random, Brain, BrainPrefix, Prefix, Error as EthkeyError, Generator, sign, verify_public, verify_address, brain_recover};
+use ethkey::{KeyPair, Random, Brain, BrainPrefix, Prefix, Mnemonic, Bip44, DerivationPath, Error as EthkeyError, Generator, sign, verify_public, verify_address, brain_recover};
 use rustc_hex::{FromHex, FromHexError};
 
 const USAGE: &'static str = r#"
@@ -40,6 +40,7 @@ Parity Ethereum keys generator.
 Usage:
     ethkey info <secret-or-phrase> [options]
     ethkey generate random [options]
+    ethkey generate mnemonic [options]
     ethkey generate prefix <prefix> [options]
     ethkey sign <secret> <message>
     ethkey verify public <public> <signature> <message>
@@ -53,10 +54,15 @@ Options:
     -p, --public       Display only the public key.
     -a, --address      Display only the address.
     -b, --brain        Use parity brain wallet algorithm. Not recommended.
+    -m, --mnemonic     Treat phrase as BIP-39 mnemonic and derive the key along BIP-32 path.
+    --words NUM        Number of words of generated mnemonic [default: 12].
+    --passphrase PASS  BIP-39 mnemonic passphrase [default: ].
+    --path PATH        BIP-32 derivation path of the key [default: m/44'/60'/0'/0/0].
 
 Commands:
     info               Display public key and address of the secret.
     generate random    Generates new random Ethereum key.
+    generate mnemonic  Generates new random BIP-39 mnemonic and derives the key from it.
     generate prefix    Random generation, but address must start with a prefix ("vanity address").
     sign               Sign message using a secret key.
     verify             Verify signer of the signature by public key or address.
@@ -68,6 +74,7 @@ struct Args {
 	cmd_info: bool,
 	cmd_generate: bool,
 	cmd_random: bool,
+	cmd_mnemonic: bool,
 	cmd_prefix: bool,
 	cmd_sign: bool,
 	cmd_verify: bool,
@@ -86,6 +93,10 @@ struct Args {
 	flag_public: bool,
 	flag_address: bool,
 	flag_brain: bool,
+	flag_mnemonic: bool,
+	flag_words: usize,
+	flag_passphrase: String,
+	flag_path: String,
 }
 
 #[derive(Debug)]
@@ -199,6 +210,11 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 			let phrase_info = validate_phrase(&phrase);
 			let keypair = Brain::new(phrase).generate().expect("Brain wallet generator is infallible; qed");
 			(keypair, Some(phrase_info))
+		} else if args.flag_mnemonic {
+			let mnemonic = Mnemonic::from_phrase(&args.arg_secret_or_phrase).map_err(|e| EthkeyError::Custom(e.to_string()))?;
+			let path: DerivationPath = args.flag_path.parse()?;
+			let keypair = Bip44::new(mnemonic, args.flag_passphrase.clone(), path.clone()).generate()?;
+			(keypair, Some(format!("derivation path: {}", path)))
 		} else {
 			let secret = args.arg_secret_or_phrase.parse().map_err(|_| EthkeyError::InvalidSecret)?;
 			(KeyPair::from_secret(secret)?, None)
@@ -215,6 +231,12 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 			} else {
 				(Random.generate()?, None)
 			}
+		} else if args.cmd_mnemonic {
+			let mnemonic = Mnemonic::new(args.flag_words).map_err(|e| EthkeyError::Custom(e.to_string()))?;
+			let path: DerivationPath = args.flag_path.parse()?;
+			let phrase = format!("recovery phrase: {}\nderivation path: {}", mnemonic.phrase(), path);
+			let keypair = Bip44::new(mnemonic, args.flag_passphrase.clone(), path).generate()?;
+			(keypair, Some(phrase))
 		} else if args.cmd_prefix {
 			let prefix = args.arg_prefix.from_hex()?;
 			let brain = args.flag_brain;
@@ -405,6 +427,17 @@ address: 006e27b6a72e1f34c626762f3c4761547aff1421".to_owned();
 		assert_eq!(execute(command).unwrap(), expected);
 	}
 
+	#[test]
+	fn mnemonic() {
+		let command = vec!["ethkey", "info", "--mnemonic", "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about", "--address"]
+			.into_iter()
+			.map(Into::into)
+			.collect::<Vec<String>>();
+
+		let expected = "9858effd232b4033e47d90003d41ec34ecaeda94".to_owned();
+		assert_eq!(execute(command).unwrap(), expected);
+	}
+
 	#[test]
 	fn sign() {
 		let command = vec!["ethkey", "sign", "17d08f5fe8c77af811caa0c9a187e668ce3b74a99acc3f6d976f075fa8e0be55", "bd50b7370c3f96733b31744c6c45079e7ae6c8d299613246d28ebcef507ec987"]
@@ -0,0 +1,232 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! BIP-39 mnemonic phrases
+//! https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki
+
+use std::fmt;
+use rand::Rng;
+use rand::os::OsRng;
+use parity_crypto::{digest, pbkdf2};
+use unicode_normalization::UnicodeNormalization;
+use mem::Memzero;
+
+/// Number of PBKDF2 rounds used to produce the seed.
+const SEED_ITERATIONS: u32 = 2048;
+/// Allowed mnemonic lengths (in words).
+pub const MNEMONIC_WORDS: &'static [usize] = &[12, 15, 18, 21, 24];
+
+lazy_static! {
+	static ref WORDS: Vec<&'static str> = include_str!("../res/bip39_english.txt").lines().collect();
+}
+
+quick_error! {
+	#[derive(Debug, PartialEq)]
+	pub enum Error {
+		InvalidWordsCount(count: usize) {
+			display("invalid number of words: {}, expected one of {:?}", count, MNEMONIC_WORDS)
+		}
+		InvalidEntropyLength(len: usize) {
+			display("invalid entropy length: {} bytes", len)
+		}
+		UnknownWord(word: String) {
+			display("unknown word: {}", word)
+		}
+		InvalidChecksum {
+			display("invalid mnemonic checksum")
+		}
+		Rng(msg: String) {
+			display("random number generator error: {}", msg)
+		}
+	}
+}
+
+/// BIP-39 mnemonic phrase using english wordlist.
+#[derive(Clone, PartialEq)]
+pub struct Mnemonic {
+	phrase: String,
+	entropy: Memzero<Vec<u8>>,
+}
+
+impl fmt::Debug for Mnemonic {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Mnemonic(******)")
+	}
+}
+
+/// 512-bit seed derived from mnemonic phrase and passphrase.
+pub struct Seed(Memzero<Vec<u8>>);
+
+impl Seed {
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.0
+	}
+}
+
+impl Mnemonic {
+	/// Generate new random mnemonic with given number of words.
+	pub fn new(words: usize) -> Result<Self, Error> {
+		if !MNEMONIC_WORDS.contains(&words) {
+			return Err(Error::InvalidWordsCount(words));
+		}
+
+		let mut entropy = Memzero::from(vec![0u8; words * 4 / 3]);
+		OsRng::new().map_err(|e| Error::Rng(e.to_string()))?.fill_bytes(&mut entropy);
+		Mnemonic::from_entropy(&entropy)
+	}
+
+	/// Create mnemonic encoding given entropy.
+	/// Entropy must be 16, 20, 24, 28 or 32 bytes long.
+	pub fn from_entropy(entropy: &[u8]) -> Result<Self, Error> {
+		if entropy.len() < 16 || entropy.len() > 32 || entropy.len() % 4 != 0 {
+			return Err(Error::InvalidEntropyLength(entropy.len()));
+		}
+
+		// entropy -- checksum (first ENT / 32 bits of sha256(entropy))
+		let checksum = digest::sha256(entropy);
+		let bits = entropy.len() * 8 + entropy.len() / 4;
+		let bit = |i: usize| {
+			let byte = if i < entropy.len() * 8 { entropy[i / 8] } else { checksum[i / 8 - entropy.len()] };
+			(byte >> (7 - i % 8)) & 1
+		};
+
+		let phrase = (0..bits / 11)
+			.map(|word| (0..11).fold(0usize, |index, i| (index << 1) | bit(word * 11 + i) as usize))
+			.map(|index| WORDS[index])
+			.collect::<Vec<_>>()
+			.join(" ");
+
+		Ok(Mnemonic {
+			phrase: phrase,
+			entropy: entropy.to_vec().into(),
+		})
+	}
+
+	/// Parse and validate mnemonic phrase.
+	pub fn from_phrase(phrase: &str) -> Result<Self, Error> {
+		let phrase = phrase.nfkd().collect::<String>();
+		let words: Vec<_> = phrase.split_whitespace().collect();
+		if !MNEMONIC_WORDS.contains(&words.len()) {
+			return Err(Error::InvalidWordsCount(words.len()));
+		}
+
+		let mut bits = Vec::with_capacity(words.len() * 11);
+		for word in &words {
+			let index = WORDS.binary_search_by(|probe| <str as Ord>::cmp(probe, word)).map_err(|_| Error::UnknownWord(word.to_string()))?;
+			bits.extend((0..11).rev().map(|i| (index >> i) & 1 == 1));
+		}
+
+		let entropy_bits = bits.len() * 32 / 33;
+		let entropy: Vec<u8> = bits[..entropy_bits]
+			.chunks(8)
+			.map(|byte| byte.iter().fold(0u8, |acc, bit| (acc << 1) | *bit as u8))
+			.collect();
+
+		let mnemonic = Mnemonic::from_entropy(&entropy)?;
+		if mnemonic.phrase != words.join(" ") {
+			return Err(Error::InvalidChecksum);
+		}
+
+		Ok(mnemonic)
+	}
+
+	/// Check that given phrase is a valid mnemonic.
+	pub fn validate(phrase: &str) -> Result<(), Error> {
+		Mnemonic::from_phrase(phrase).map(|_| ())
+	}
+
+	/// Mnemonic phrase.
+	pub fn phrase(&self) -> &str {
+		&self.phrase
+	}
+
+	/// Entropy encoded by mnemonic.
+	pub fn entropy(&self) -> &[u8] {
+		&self.entropy
+	}
+
+	/// Derive 512-bit seed protected with given (possibly empty) passphrase.
+	pub fn seed(&self, passphrase: &str) -> Seed {
+		let salt = format!("mnemonic{}", passphrase).nfkd().collect::<String>();
+		let mut seed = [0u8; 64];
+		pbkdf2::sha512(SEED_ITERATIONS, pbkdf2::Salt(salt.as_bytes()), pbkdf2::Secret(self.phrase.as_bytes()), &mut seed);
+		let result = Seed(seed.to_vec().into());
+		for byte in seed.iter_mut() {
+			*byte = 0;
+		}
+		result
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rustc_hex::{FromHex, ToHex};
+	use super::{Mnemonic, Error, WORDS};
+
+	#[test]
+	fn wordlist_is_sorted() {
+		assert_eq!(WORDS.len(), 2048);
+		assert!(WORDS.windows(2).all(|w| w[0] < w[1]));
+	}
+
+	#[test]
+	fn should_match_test_vectors() {
+		let vectors = [
+			(
+				"00000000000000000000000000000000",
+				"abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+				"c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+			),
+			(
+				"7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+				"legal winner thank year wave sausage worth useful legal winner thank yellow",
+				"2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+			),
+		];
+
+		for &(entropy, phrase, seed) in &vectors {
+			let entropy: Vec<u8> = entropy.from_hex().unwrap();
+			let mnemonic = Mnemonic::from_entropy(&entropy).unwrap();
+			assert_eq!(mnemonic.phrase(), phrase);
+			assert_eq!(mnemonic.seed("TREZOR").as_bytes().to_hex(), seed);
+
+			let parsed = Mnemonic::from_phrase(phrase).unwrap();
+			assert_eq!(parsed.entropy(), &entropy[..]);
+		}
+	}
+
+	#[test]
+	fn should_generate_valid_mnemonic() {
+		for &words in &[12, 24] {
+			let mnemonic = Mnemonic::new(words).unwrap();
+			assert_eq!(mnemonic.phrase().split(' ').count(), words);
+			assert_eq!(Mnemonic::from_phrase(mnemonic.phrase()).unwrap(), mnemonic);
+		}
+		assert_eq!(Mnemonic::new(13), Err(Error::InvalidWordsCount(13)));
+	}
+
+	#[test]
+	fn should_reject_invalid_phrases() {
+		let bad_checksum = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+		assert_eq!(Mnemonic::validate(bad_checksum), Err(Error::InvalidChecksum));
+
+		let unknown_word = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon parity";
+		assert_eq!(Mnemonic::validate(unknown_word), Err(Error::UnknownWord("parity".into())));
+
+		assert_eq!(Mnemonic::validate("abandon about"), Err(Error::InvalidWordsCount(2)));
+		assert!(Mnemonic::validate("  abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about ").is_ok());
+	}
+}
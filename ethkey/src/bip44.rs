@@ -0,0 +1,155 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! BIP-32 derivation paths and BIP-44 key generation
+//! https://github.com/bitcoin/bips/blob/master/bip-0044.mediawiki
+
+use std::fmt;
+use std::str::FromStr;
+use bip39::Mnemonic;
+use extended::ExtendedKeyPair;
+use super::{Generator, KeyPair, Error};
+
+/// Offset of hardened derivation indexes.
+pub const HARDENED_OFFSET: u32 = 1 << 31;
+/// BIP-44 base path of ethereum accounts; account index is appended to it.
+pub const ETHEREUM_BASE_PATH: &'static str = "m/44'/60'/0'/0";
+
+/// BIP-32 derivation path, e.g. `m/44'/60'/0'/0/0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+	/// Default path of ethereum account with given index.
+	pub fn ethereum(index: u32) -> Self {
+		DerivationPath::ethereum_base().child(index)
+	}
+
+	/// Default base path of ethereum accounts.
+	pub fn ethereum_base() -> Self {
+		ETHEREUM_BASE_PATH.parse().expect("ETHEREUM_BASE_PATH is a valid derivation path; qed")
+	}
+
+	/// Path extended with given (soft for indexes below `HARDENED_OFFSET`) child index.
+	pub fn child(&self, index: u32) -> Self {
+		let mut path = self.0.clone();
+		path.push(index);
+		DerivationPath(path)
+	}
+
+	/// Derivation indexes; hardened indexes include `HARDENED_OFFSET`.
+	pub fn indexes(&self) -> &[u32] {
+		&self.0
+	}
+}
+
+impl FromStr for DerivationPath {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = || Error::Custom(format!("Invalid derivation path: {}", s));
+		let mut parts = s.trim().split('/');
+		if parts.next() != Some("m") {
+			return Err(invalid());
+		}
+
+		parts
+			.map(|part| {
+				let (index, hardened) = match part.ends_with('\'') {
+					true => (&part[..part.len() - 1], true),
+					false => (part, false),
+				};
+				let index: u32 = index.parse().map_err(|_| invalid())?;
+				match (index < HARDENED_OFFSET, hardened) {
+					(false, _) => Err(invalid()),
+					(true, true) => Ok(index + HARDENED_OFFSET),
+					(true, false) => Ok(index),
+				}
+			})
+			.collect::<Result<Vec<_>, _>>()
+			.map(DerivationPath)
+	}
+}
+
+impl fmt::Display for DerivationPath {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "m")?;
+		for index in &self.0 {
+			match *index >= HARDENED_OFFSET {
+				true => write!(f, "/{}'", index - HARDENED_OFFSET)?,
+				false => write!(f, "/{}", index)?,
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Derives keypair from BIP-39 mnemonic along given BIP-32 path.
+pub struct Bip44 {
+	mnemonic: Mnemonic,
+	passphrase: String,
+	path: DerivationPath,
+}
+
+impl Bip44 {
+	pub fn new(mnemonic: Mnemonic, passphrase: String, path: DerivationPath) -> Self {
+		Bip44 {
+			mnemonic: mnemonic,
+			passphrase: passphrase,
+			path: path,
+		}
+	}
+}
+
+impl Generator for Bip44 {
+	type Error = Error;
+
+	fn generate(&mut self) -> Result<KeyPair, Self::Error> {
+		let seed = self.mnemonic.seed(&self.passphrase);
+		let master = ExtendedKeyPair::with_seed(seed.as_bytes())
+			.map_err(|e| Error::Custom(format!("{:?}", e)))?;
+		let derived = master.derive_path(&self.path)
+			.map_err(|e| Error::Custom(format!("{:?}", e)))?;
+		KeyPair::from_secret(derived.secret().as_raw().clone())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use {Generator, Mnemonic};
+	use super::{Bip44, DerivationPath, HARDENED_OFFSET};
+
+	#[test]
+	fn should_parse_and_display_paths() {
+		let path: DerivationPath = "m/44'/60'/0'/0/7".parse().unwrap();
+		assert_eq!(path.indexes(), &[44 + HARDENED_OFFSET, 60 + HARDENED_OFFSET, HARDENED_OFFSET, 0, 7]);
+		assert_eq!(path.to_string(), "m/44'/60'/0'/0/7");
+		assert_eq!(path, DerivationPath::ethereum(7));
+		assert_eq!("m".parse::<DerivationPath>().unwrap().indexes().len(), 0);
+
+		assert!("44'/60'".parse::<DerivationPath>().is_err());
+		assert!("m/44'/x".parse::<DerivationPath>().is_err());
+		assert!("m/2147483648".parse::<DerivationPath>().is_err());
+	}
+
+	#[test]
+	fn should_derive_ethereum_account() {
+		let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+		let mnemonic = Mnemonic::from_phrase(phrase).unwrap();
+		let keypair = Bip44::new(mnemonic, String::new(), DerivationPath::ethereum(0)).generate().unwrap();
+		assert_eq!(keypair.address(), "9858effd232b4033e47d90003d41ec34ecaeda94".into());
+	}
+}
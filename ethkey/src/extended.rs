@@ -18,6 +18,7 @@
 
 use secret::Secret;
 use Public;
+use bip44::DerivationPath;
 use ethereum_types::H256;
 pub use self::derivation::Error as DerivationError;
 
@@ -201,6 +202,19 @@ impl ExtendedKeyPair {
 			secret: derived,
 		})
 	}
+
+	/// Derive keypair along given BIP-32 path.
+	pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, DerivationError> {
+		let derived = path.indexes().iter().fold(
+			ExtendedSecret::with_code(self.secret.secret.clone(), self.secret.chain_code),
+			|secret, index| secret.derive(Derivation::from(*index)),
+		);
+
+		Ok(ExtendedKeyPair {
+			public: ExtendedPublic::from_secret(&derived)?,
+			secret: derived,
+		})
+	}
 }
 
 // Derivation functions for private and public keys
@@ -29,6 +29,7 @@ extern crate rustc_hex;
 extern crate secp256k1;
 extern crate serde;
 extern crate tiny_keccak;
+extern crate unicode_normalization;
 
 #[macro_use]
 extern crate lazy_static;
@@ -37,6 +38,8 @@ extern crate log;
 #[macro_use]
 extern crate serde_derive;
 
+mod bip39;
+mod bip44;
 mod brain;
 mod brain_prefix;
 mod error;
@@ -54,6 +57,8 @@ pub mod crypto;
 pub mod math;
 
 pub use self::parity_wordlist::Error as WordlistError;
+pub use self::bip39::{Mnemonic, Seed, Error as MnemonicError, MNEMONIC_WORDS};
+pub use self::bip44::{Bip44, DerivationPath, HARDENED_OFFSET, ETHEREUM_BASE_PATH};
 pub use self::brain::Brain;
 pub use self::brain_prefix::BrainPrefix;
 pub use self::error::Error;
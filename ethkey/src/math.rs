@@ -76,6 +76,17 @@ pub fn generation_point() -> Public {
 	public
 }
 
+/// Decode public key from its SEC1 compressed form
+pub fn public_from_compressed(data: &[u8]) -> Result<Public, Error> {
+	if data.len() != 33 {
+		return Err(Error::InvalidPublic);
+	}
+	let key_public = key::PublicKey::from_slice(&SECP256K1, data)?;
+	let mut public = Public::default();
+	set_public(&mut public, &key_public);
+	Ok(public)
+}
+
 /// Return secp256k1 elliptic curve order
 pub fn curve_order() -> U256 {
 	H256::from_slice(&CURVE_ORDER).into()
@@ -99,7 +110,16 @@ fn set_public(public: &mut Public, key_public: &key::PublicKey) {
 #[cfg(test)]
 mod tests {
 	use super::super::{Random, Generator};
-	use super::{public_add, public_sub};
+	use super::{public_add, public_sub, public_from_compressed};
+
+	#[test]
+	fn public_is_decompressed() {
+		let public = Random.generate().unwrap().public().clone();
+		let mut compressed = vec![2 + (public[63] & 1)];
+		compressed.extend_from_slice(&public[0..32]);
+		assert_eq!(public_from_compressed(&compressed).unwrap(), public);
+		assert!(public_from_compressed(&compressed[1..]).is_err());
+	}
 
 	#[test]
 	fn public_addition_is_commutative() {
@@ -33,7 +33,7 @@ use std::{env, process, fs, fmt};
 use docopt::Docopt;
 use ethstore::accounts_dir::{KeyDirectory, RootDiskDirectory};
 use ethstore::ethkey::{Address, Password};
-use ethstore::{EthStore, SimpleSecretStore, SecretStore, import_accounts, PresaleWallet, SecretVaultRef, StoreAccountRef};
+use ethstore::{EthStore, SimpleSecretStore, SecretStore, import_accounts, PresaleWallet, SecretVaultRef, StoreAccountRef, KdfAlgorithm};
 
 mod crack;
 
@@ -42,8 +42,9 @@ Parity Ethereum key management tool.
   Copyright 2015-2018 Parity Technologies (UK) Ltd.
 
 Usage:
-    ethstore insert <secret> <password> [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
+    ethstore insert <secret> <password> [--kdf KDF] [--iterations NUM] [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
     ethstore change-pwd <address> <old-pwd> <new-pwd> [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
+    ethstore reencrypt <address> <password> [--kdf KDF] [--iterations NUM] [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
     ethstore list [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
     ethstore import [--src DIR] [--dir DIR]
     ethstore import-wallet <path> <password> [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
@@ -52,7 +53,7 @@ Usage:
     ethstore sign <address> <password> <message> [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
     ethstore public <address> <password> [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
     ethstore list-vaults [--dir DIR]
-    ethstore create-vault <vault> <password> [--dir DIR]
+    ethstore create-vault <vault> <password> [--kdf KDF] [--iterations NUM] [--dir DIR]
     ethstore change-vault-pwd <vault> <old-pwd> <new-pwd> [--dir DIR]
     ethstore move-to-vault <address> <vault> <password> [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
     ethstore move-from-vault <address> <vault> <password> [--dir DIR]
@@ -70,10 +71,16 @@ Options:
     --src DIR                Specify import source. It may be either
                              parity, parity-(chain), geth, geth-test
                              or a path [default: geth].
+    --kdf KDF                Specify key derivation function used to encrypt
+                             new keys. It may be either pbkdf2 or argon2id
+                             [default: pbkdf2].
+    --iterations NUM         Specify number of key derivation iterations. Defaults
+                             to 10240 for pbkdf2 and 3 for argon2id.
 
 Commands:
     insert             Save account with password.
     change-pwd         Change password.
+    reencrypt          Re-encrypt account with given key derivation function.
     list               List accounts.
     import             Import accounts from src.
     import-wallet      Import presale wallet.
@@ -92,6 +99,7 @@ Commands:
 struct Args {
 	cmd_insert: bool,
 	cmd_change_pwd: bool,
+	cmd_reencrypt: bool,
 	cmd_list: bool,
 	cmd_import: bool,
 	cmd_import_wallet: bool,
@@ -116,6 +124,8 @@ struct Args {
 	flag_dir: String,
 	flag_vault: String,
 	flag_vault_pwd: String,
+	flag_kdf: String,
+	flag_iterations: Option<u32>,
 }
 
 enum Error {
@@ -189,6 +199,12 @@ fn open_args_vault_account(store: &EthStore, address: Address, args: &Args) -> R
 	}
 }
 
+fn kdf_args(args: &Args) -> Result<(KdfAlgorithm, u32), Error> {
+	let kdf: KdfAlgorithm = args.flag_kdf.parse().map_err(ethstore::Error::Custom)?;
+	let iterations = args.flag_iterations.unwrap_or_else(|| kdf.default_iterations());
+	Ok((kdf, iterations))
+}
+
 fn format_accounts(accounts: &[Address]) -> String {
 	accounts.iter()
 		.enumerate()
@@ -214,7 +230,8 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 	let args: Args = Docopt::new(USAGE)
 		.and_then(|d| d.argv(command).deserialize())?;
 
-	let store = EthStore::open(key_dir(&args.flag_dir)?)?;
+	let (kdf, iterations) = kdf_args(&args)?;
+	let store = EthStore::open_with_kdf(key_dir(&args.flag_dir)?, kdf, iterations)?;
 
 	return if args.cmd_insert {
 		let secret = args.arg_secret.parse().map_err(|_| ethstore::Error::InvalidSecret)?;
@@ -229,6 +246,12 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 		let account_ref = open_args_vault_account(&store, address, &args)?;
 		let ok = store.change_password(&account_ref, &old_pwd, &new_pwd).is_ok();
 		Ok(format!("{}", ok))
+	} else if args.cmd_reencrypt {
+		let address = args.arg_address.parse().map_err(|_| ethstore::Error::InvalidAccount)?;
+		let password = load_password(&args.arg_password)?;
+		let account_ref = open_args_vault_account(&store, address, &args)?;
+		let ok = store.reencrypt_account(&account_ref, &password, kdf, iterations).is_ok();
+		Ok(format!("{}", ok))
 	} else if args.cmd_list {
 		let vault_ref = open_args_vault(&store, &args)?;
 		let accounts = store.accounts()?;
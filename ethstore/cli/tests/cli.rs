@@ -79,4 +79,18 @@ fn cli_cmd() {
 			   "--vault", "test-vault",
 			   "--vault-pwd", test_password]);
 	assert_eq!(output, "0x35f222d88b80151857a2877826d940104887376a94c1cbd2c8c7c192eb701df88a18a4ecb8b05b1466c5b3706042027b5e079fe3a3683e66d822b0e047aa3418\n");
+
+	let output = run(&["reencrypt", &address[2..], test_vault_addr,
+			   "--kdf", "argon2id",
+			   "--iterations", "1",
+			   "--dir", dir_str,
+			   "--vault", "test-vault",
+			   "--vault-pwd", test_password]);
+	assert_eq!(output, "true\n");
+
+	let output = run(&["public", &address[2..], test_vault_addr,
+			   "--dir", dir_str,
+			   "--vault", "test-vault",
+			   "--vault-pwd", test_password]);
+	assert_eq!(output, "0x35f222d88b80151857a2877826d940104887376a94c1cbd2c8c7c192eb701df88a18a4ecb8b05b1466c5b3706042027b5e079fe3a3683e66d822b0e047aa3418\n");
 }
@@ -15,12 +15,13 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::str;
+use argon2;
 use ethkey::{Password, Secret};
 use {json, Error, crypto};
 use crypto::Keccak256;
 use random::Random;
 use smallvec::SmallVec;
-use account::{Cipher, Kdf, Aes128Ctr, Pbkdf2, Prf};
+use account::{Cipher, Kdf, KdfAlgorithm, Aes128Ctr, Pbkdf2, Argon2id, Prf};
 
 /// Encrypted data
 #[derive(Debug, PartialEq, Clone)]
@@ -80,34 +81,69 @@ impl Crypto {
 	/// Encrypt custom plain data
 	pub fn with_plain(plain: &[u8], password: &Password, iterations: u32) -> Result<Self, crypto::Error> {
 		let salt: [u8; 32] = Random::random();
-		let iv: [u8; 16] = Random::random();
 
 		// two parts of derived key
 		// DK = [ DK[0..15] DK[16..31] ] = [derived_left_bits, derived_right_bits]
 		let (derived_left_bits, derived_right_bits) = crypto::derive_key_iterations(password.as_bytes(), &salt, iterations);
 
+		let kdf = Kdf::Pbkdf2(Pbkdf2 {
+			dklen: crypto::KEY_LENGTH as u32,
+			salt: salt,
+			c: iterations,
+			prf: Prf::HmacSha256,
+		});
+
+		Crypto::encrypt(plain, kdf, &derived_left_bits, &derived_right_bits)
+	}
+
+	/// Encrypt account secret using given key derivation function
+	pub fn with_secret_kdf(secret: &Secret, password: &Password, kdf: KdfAlgorithm, iterations: u32) -> Result<Self, Error> {
+		Crypto::with_plain_kdf(&*secret, password, kdf, iterations)
+	}
+
+	/// Encrypt custom plain data using given key derivation function
+	pub fn with_plain_kdf(plain: &[u8], password: &Password, kdf: KdfAlgorithm, iterations: u32) -> Result<Self, Error> {
+		match kdf {
+			KdfAlgorithm::Pbkdf2 => Ok(Crypto::with_plain(plain, password, iterations)?),
+			KdfAlgorithm::Argon2id { memory_kib } => {
+				if memory_kib > json::ARGON2ID_MAX_MEMORY_KIB || iterations == 0 || iterations > json::ARGON2ID_MAX_ITERATIONS {
+					return Err(Error::Custom(format!("Argon2id parameters out of bounds: at most {} KiB of memory and 1 to {} passes", json::ARGON2ID_MAX_MEMORY_KIB, json::ARGON2ID_MAX_ITERATIONS)));
+				}
+
+				let kdf = Kdf::Argon2id(Argon2id {
+					dklen: crypto::KEY_LENGTH as u32,
+					m: memory_kib,
+					t: iterations,
+					p: 1,
+					salt: Random::random(),
+				});
+
+				let (derived_left_bits, derived_right_bits) = derive_key(&kdf, password)?;
+				Ok(Crypto::encrypt(plain, kdf, &derived_left_bits, &derived_right_bits)?)
+			},
+		}
+	}
+
+	fn encrypt(plain: &[u8], kdf: Kdf, derived_left_bits: &[u8], derived_right_bits: &[u8]) -> Result<Self, crypto::Error> {
+		let iv: [u8; 16] = Random::random();
+
 		// preallocated (on-stack in case of `Secret`) buffer to hold cipher
 		// length = length(plain) as we are using CTR-approach
 		let plain_len = plain.len();
 		let mut ciphertext: SmallVec<[u8; 32]> = SmallVec::from_vec(vec![0; plain_len]);
 
 		// aes-128-ctr with initial vector of iv
-		crypto::aes::encrypt_128_ctr(&derived_left_bits, &iv, plain, &mut *ciphertext)?;
+		crypto::aes::encrypt_128_ctr(derived_left_bits, &iv, plain, &mut *ciphertext)?;
 
 		// KECCAK(DK[16..31] ++ <ciphertext>), where DK[16..31] - derived_right_bits
-		let mac = crypto::derive_mac(&derived_right_bits, &*ciphertext).keccak256();
+		let mac = crypto::derive_mac(derived_right_bits, &*ciphertext).keccak256();
 
 		Ok(Crypto {
 			cipher: Cipher::Aes128Ctr(Aes128Ctr {
 				iv: iv,
 			}),
 			ciphertext: ciphertext.into_vec(),
-			kdf: Kdf::Pbkdf2(Pbkdf2 {
-				dklen: crypto::KEY_LENGTH as u32,
-				salt: salt,
-				c: iterations,
-				prf: Prf::HmacSha256,
-			}),
+			kdf: kdf,
 			mac: mac,
 		})
 	}
@@ -129,10 +165,7 @@ impl Crypto {
 	}
 
 	fn do_decrypt(&self, password: &Password, expected_len: usize) -> Result<Vec<u8>, Error> {
-		let (derived_left_bits, derived_right_bits) = match self.kdf {
-			Kdf::Pbkdf2(ref params) => crypto::derive_key_iterations(password.as_bytes(), &params.salt, params.c),
-			Kdf::Scrypt(ref params) => crypto::scrypt::derive_key(password.as_bytes(), &params.salt, params.n, params.p, params.r)?,
-		};
+		let (derived_left_bits, derived_right_bits) = derive_key(&self.kdf, password)?;
 
 		let mac = crypto::derive_mac(&derived_right_bits, &self.ciphertext).keccak256();
 
@@ -155,9 +188,35 @@ impl Crypto {
 	}
 }
 
+// derive both parts of the key from password.
+fn derive_key(kdf: &Kdf, password: &Password) -> Result<(Vec<u8>, Vec<u8>), Error> {
+	match *kdf {
+		Kdf::Pbkdf2(ref params) => Ok(crypto::derive_key_iterations(password.as_bytes(), &params.salt, params.c)),
+		Kdf::Scrypt(ref params) => Ok(crypto::scrypt::derive_key(password.as_bytes(), &params.salt, params.n, params.p, params.r)?),
+		Kdf::Argon2id(ref params) => {
+			let config = argon2::Config {
+				variant: argon2::Variant::Argon2id,
+				version: argon2::Version::Version13,
+				mem_cost: params.m,
+				time_cost: params.t,
+				lanes: params.p,
+				thread_mode: argon2::ThreadMode::Sequential,
+				secret: &[],
+				ad: &[],
+				hash_length: crypto::KEY_LENGTH as u32,
+			};
+
+			let mut derived_left_bits = argon2::hash_raw(password.as_bytes(), &params.salt, &config)?;
+			let derived_right_bits = derived_left_bits.split_off(crypto::KEY_LENGTH / 2);
+			Ok((derived_left_bits, derived_right_bits))
+		},
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use ethkey::{Generator, Random};
+	use account::{Kdf, KdfAlgorithm};
 	use super::{Crypto, Error};
 
 	#[test]
@@ -202,4 +261,33 @@ mod tests {
 		let decrypted_data = crypto.decrypt(&passwd).unwrap();
 		assert_eq!(&original_data, &decrypted_data);
 	}
+
+	#[test]
+	fn crypto_with_argon2id() {
+		let keypair = Random.generate().unwrap();
+		let passwd = "this is sparta".into();
+		let kdf = KdfAlgorithm::Argon2id { memory_kib: 256 };
+		let crypto = Crypto::with_secret_kdf(keypair.secret(), &passwd, kdf, 2).unwrap();
+		match crypto.kdf {
+			Kdf::Argon2id(ref params) => {
+				assert_eq!(params.m, 256);
+				assert_eq!(params.t, 2);
+			},
+			_ => panic!("expected Argon2id key derivation"),
+		}
+
+		assert_eq!(keypair.secret(), &crypto.secret(&passwd).unwrap());
+		assert_matches!(crypto.secret(&"this is sparta!".into()), Err(Error::InvalidPassword));
+	}
+
+	#[test]
+	fn crypto_argon2id_json_roundtrip() {
+		let original_data = b"{}";
+		let passwd = "this is sparta".into();
+		let crypto = Crypto::with_plain_kdf(&original_data[..], &passwd, KdfAlgorithm::Argon2id { memory_kib: 256 }, 1).unwrap();
+		let json: String = crypto.clone().into();
+		let decoded: Crypto = json.parse().unwrap();
+		assert_eq!(decoded, crypto);
+		assert_eq!(original_data[..], *decoded.decrypt(&passwd).unwrap());
+	}
 }
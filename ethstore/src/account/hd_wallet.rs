@@ -0,0 +1,173 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethkey::{Address, Derivation, DerivationPath, ExtendedKeyPair, KeyPair, Password, Seed};
+use {json, Error};
+use account::{Version, KdfAlgorithm};
+use super::crypto::Crypto;
+
+/// Maximal number of addresses derived by a single `HdWallet::addresses` call.
+pub const MAX_HD_WALLET_ADDRESSES: u32 = 1000;
+
+/// HD wallet representation. Only the BIP-39 seed is stored,
+/// wallet accounts are derived from it along `path` on demand.
+#[derive(Debug, PartialEq, Clone)]
+pub struct HdWallet {
+	/// Wallet ID
+	pub id: [u8; 16],
+	/// Wallet version
+	pub version: Version,
+	/// Encrypted seed.
+	pub crypto: Crypto,
+	/// Derivation path of wallet accounts (without account index)
+	pub path: DerivationPath,
+	/// Wallet filename
+	pub filename: Option<String>,
+	/// Wallet name
+	pub name: String,
+	/// Wallet metadata
+	pub meta: String,
+}
+
+impl Into<json::HdWalletFile> for HdWallet {
+	fn into(self) -> json::HdWalletFile {
+		json::HdWalletFile {
+			id: From::from(self.id),
+			version: self.version.into(),
+			crypto: self.crypto.into(),
+			path: self.path.to_string(),
+			name: Some(self.name),
+			meta: Some(self.meta),
+		}
+	}
+}
+
+impl HdWallet {
+	/// Create a new HD wallet
+	pub fn create(
+		seed: &Seed,
+		id: [u8; 16],
+		password: &Password,
+		kdf: KdfAlgorithm,
+		iterations: u32,
+		path: DerivationPath,
+		name: String,
+	) -> Result<Self, Error> {
+		Ok(HdWallet {
+			id: id,
+			version: Version::V3,
+			crypto: Crypto::with_plain_kdf(seed.as_bytes(), password, kdf, iterations)?,
+			path: path,
+			filename: None,
+			name: name,
+			meta: "{}".to_owned(),
+		})
+	}
+
+	/// Create a new `HdWallet` from the given `json`.
+	pub fn from_file(json: json::HdWalletFile, filename: Option<String>) -> Result<Self, Error> {
+		Ok(HdWallet {
+			id: json.id.into(),
+			version: json.version.into(),
+			crypto: json.crypto.into(),
+			path: json.path.parse().map_err(|_| Error::InvalidKeyFile(format!("Invalid derivation path: {}", json.path)))?,
+			filename: filename,
+			name: json.name.unwrap_or(String::new()),
+			meta: json.meta.unwrap_or("{}".to_owned()),
+		})
+	}
+
+	/// Derivation path of account with given index.
+	pub fn account_path(&self, index: u32) -> DerivationPath {
+		self.path.child(index)
+	}
+
+	/// Derive keypair of account with given index.
+	pub fn derive(&self, password: &Password, index: u32) -> Result<KeyPair, Error> {
+		let extended = self.extended(password)?.derive(Derivation::from(index))?;
+		Ok(KeyPair::from_secret(extended.secret().as_raw().clone())?)
+	}
+
+	/// Derive addresses of accounts with indexes in `from..from + count`.
+	/// Fails if `count` exceeds `MAX_HD_WALLET_ADDRESSES`.
+	pub fn addresses(&self, password: &Password, from: u32, count: u32) -> Result<Vec<Address>, Error> {
+		if count > MAX_HD_WALLET_ADDRESSES {
+			return Err(Error::Custom(format!("Cannot derive more than {} addresses at once", MAX_HD_WALLET_ADDRESSES)));
+		}
+
+		let extended = self.extended(password)?;
+		(from..from.saturating_add(count))
+			.map(|index| -> Result<Address, Error> {
+				let derived = extended.derive(Derivation::from(index))?;
+				Ok(KeyPair::from_secret(derived.secret().as_raw().clone())?.address())
+			})
+			.collect()
+	}
+
+	/// Check if password matches the wallet.
+	pub fn check_password(&self, password: &Password) -> bool {
+		self.crypto.decrypt(password).is_ok()
+	}
+
+	// extended keypair at wallet derivation path
+	fn extended(&self, password: &Password) -> Result<ExtendedKeyPair, Error> {
+		let seed = self.crypto.decrypt(password)?;
+		Ok(ExtendedKeyPair::with_seed(&seed)?.derive_path(&self.path)?)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethkey::{Mnemonic, DerivationPath};
+	use account::KdfAlgorithm;
+	use super::HdWallet;
+
+	fn wallet() -> HdWallet {
+		let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+		let seed = Mnemonic::from_phrase(phrase).unwrap().seed("");
+		HdWallet::create(&seed, [0u8; 16], &"password".into(), KdfAlgorithm::Pbkdf2, 1024, DerivationPath::ethereum_base(), "Test".into()).unwrap()
+	}
+
+	#[test]
+	fn should_derive_bip44_accounts() {
+		let wallet = wallet();
+		let password = "password".into();
+
+		let keypair = wallet.derive(&password, 0).unwrap();
+		assert_eq!(keypair.address(), "9858effd232b4033e47d90003d41ec34ecaeda94".into());
+		assert_eq!(wallet.account_path(0).to_string(), "m/44'/60'/0'/0/0");
+
+		let addresses = wallet.addresses(&password, 0, 3).unwrap();
+		assert_eq!(addresses.len(), 3);
+		assert_eq!(addresses[0], keypair.address());
+		assert_eq!(addresses[2], wallet.derive(&password, 2).unwrap().address());
+	}
+
+	#[test]
+	fn should_require_valid_password() {
+		let wallet = wallet();
+		assert!(wallet.check_password(&"password".into()));
+		assert!(!wallet.check_password(&"wrong".into()));
+		assert!(wallet.derive(&"wrong".into(), 0).is_err());
+	}
+
+	#[test]
+	fn should_roundtrip_through_json() {
+		let wallet = wallet();
+		let json: ::json::HdWalletFile = wallet.clone().into();
+		assert_eq!(HdWallet::from_file(json, None).unwrap(), wallet);
+	}
+}
@@ -14,8 +14,71 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::fmt;
+use std::str::FromStr;
+use crypto::KEY_ITERATIONS;
 use json;
 
+/// Default memory cost of Argon2id, in KiB.
+pub const ARGON2ID_MEMORY_KIB: u32 = 64 * 1024;
+
+/// Default number of Argon2id passes over the memory.
+pub const ARGON2ID_ITERATIONS: u32 = 3;
+
+/// Key derivation function used to encrypt new key material.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum KdfAlgorithm {
+	/// PBKDF2 with HMAC-SHA256.
+	Pbkdf2,
+	/// Argon2id with given memory cost in KiB.
+	Argon2id {
+		/// Memory cost in KiB.
+		memory_kib: u32,
+	},
+}
+
+impl KdfAlgorithm {
+	/// Argon2id with the default memory cost.
+	pub fn argon2id() -> Self {
+		KdfAlgorithm::Argon2id { memory_kib: ARGON2ID_MEMORY_KIB }
+	}
+
+	/// Recommended number of iterations for this algorithm.
+	pub fn default_iterations(&self) -> u32 {
+		match *self {
+			KdfAlgorithm::Pbkdf2 => KEY_ITERATIONS as u32,
+			KdfAlgorithm::Argon2id { .. } => ARGON2ID_ITERATIONS,
+		}
+	}
+}
+
+impl Default for KdfAlgorithm {
+	fn default() -> Self {
+		KdfAlgorithm::Pbkdf2
+	}
+}
+
+impl FromStr for KdfAlgorithm {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"pbkdf2" => Ok(KdfAlgorithm::Pbkdf2),
+			"argon2id" => Ok(KdfAlgorithm::argon2id()),
+			other => Err(format!("Unsupported key derivation function: {}", other)),
+		}
+	}
+}
+
+impl fmt::Display for KdfAlgorithm {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			KdfAlgorithm::Pbkdf2 => write!(f, "pbkdf2"),
+			KdfAlgorithm::Argon2id { .. } => write!(f, "argon2id"),
+		}
+	}
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Prf {
 	HmacSha256,
@@ -38,10 +101,20 @@ pub struct Scrypt {
 	pub salt: [u8; 32],
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct Argon2id {
+	pub dklen: u32,
+	pub m: u32,
+	pub t: u32,
+	pub p: u32,
+	pub salt: [u8; 32],
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Kdf {
 	Pbkdf2(Pbkdf2),
 	Scrypt(Scrypt),
+	Argon2id(Argon2id),
 }
 
 impl From<json::Prf> for Prf {
@@ -106,11 +179,36 @@ impl Into<json::Scrypt> for Scrypt {
 	}
 }
 
+impl From<json::Argon2id> for Argon2id {
+	fn from(json: json::Argon2id) -> Self {
+		Argon2id {
+			dklen: json.dklen,
+			m: json.m,
+			t: json.t,
+			p: json.p,
+			salt: json.salt.into(),
+		}
+	}
+}
+
+impl Into<json::Argon2id> for Argon2id {
+	fn into(self) -> json::Argon2id {
+		json::Argon2id {
+			dklen: self.dklen,
+			m: self.m,
+			t: self.t,
+			p: self.p,
+			salt: From::from(self.salt),
+		}
+	}
+}
+
 impl From<json::Kdf> for Kdf {
 	fn from(json: json::Kdf) -> Self {
 		match json {
 			json::Kdf::Pbkdf2(params) => Kdf::Pbkdf2(From::from(params)),
 			json::Kdf::Scrypt(params) => Kdf::Scrypt(From::from(params)),
+			json::Kdf::Argon2id(params) => Kdf::Argon2id(From::from(params)),
 		}
 	}
 }
@@ -120,6 +218,7 @@ impl Into<json::Kdf> for Kdf {
 		match self {
 			Kdf::Pbkdf2(params) => json::Kdf::Pbkdf2(params.into()),
 			Kdf::Scrypt(params) => json::Kdf::Scrypt(params.into()),
+			Kdf::Argon2id(params) => json::Kdf::Argon2id(params.into()),
 		}
 	}
 }
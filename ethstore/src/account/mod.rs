@@ -16,12 +16,14 @@
 
 mod cipher;
 mod crypto;
+mod hd_wallet;
 mod kdf;
 mod safe_account;
 mod version;
 
 pub use self::cipher::{Cipher, Aes128Ctr};
 pub use self::crypto::Crypto;
-pub use self::kdf::{Kdf, Pbkdf2, Scrypt, Prf};
+pub use self::hd_wallet::{HdWallet, MAX_HD_WALLET_ADDRESSES};
+pub use self::kdf::{Kdf, KdfAlgorithm, Pbkdf2, Scrypt, Argon2id, Prf, ARGON2ID_MEMORY_KIB, ARGON2ID_ITERATIONS};
 pub use self::safe_account::SafeAccount;
 pub use self::version::Version;
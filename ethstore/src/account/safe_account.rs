@@ -17,7 +17,7 @@
 use ethkey::{self, KeyPair, sign, Address, Password, Signature, Message, Public, Secret};
 use ethkey::crypto::ecdh::agree;
 use {json, Error};
-use account::Version;
+use account::{Version, KdfAlgorithm};
 use crypto;
 use super::crypto::Crypto;
 
@@ -74,6 +74,27 @@ impl SafeAccount {
 		})
 	}
 
+	/// Create a new account with secret encrypted using given key derivation function
+	pub fn create_with_kdf(
+		keypair: &KeyPair,
+		id: [u8; 16],
+		password: &Password,
+		kdf: KdfAlgorithm,
+		iterations: u32,
+		name: String,
+		meta: String
+	) -> Result<Self, Error> {
+		Ok(SafeAccount {
+			id: id,
+			version: Version::V3,
+			crypto: Crypto::with_secret_kdf(keypair.secret(), password, kdf, iterations)?,
+			address: keypair.address(),
+			filename: None,
+			name: name,
+			meta: meta,
+		})
+	}
+
 	/// Create a new `SafeAccount` from the given `json`; if it was read from a
 	/// file, the `filename` should be `Some` name. If it is as yet anonymous, then it
 	/// can be left `None`.
@@ -108,14 +129,14 @@ impl SafeAccount {
 	}
 
 	/// Create a new `VaultKeyFile` from the given `self`
-	pub fn into_vault_file(self, iterations: u32, password: &Password) -> Result<json::VaultKeyFile, Error> {
+	pub fn into_vault_file(self, kdf: KdfAlgorithm, iterations: u32, password: &Password) -> Result<json::VaultKeyFile, Error> {
 		let meta_plain = json::VaultKeyMeta {
 			address: self.address.into(),
 			name: Some(self.name),
 			meta: Some(self.meta),
 		};
 		let meta_plain = meta_plain.write().map_err(|e| Error::Custom(format!("{:?}", e)))?;
-		let meta_crypto = Crypto::with_plain(&meta_plain, password, iterations)?;
+		let meta_crypto = Crypto::with_plain_kdf(&meta_plain, password, kdf, iterations)?;
 
 		Ok(json::VaultKeyFile {
 			id: self.id.into(),
@@ -150,12 +171,12 @@ impl SafeAccount {
 	}
 
 	/// Change account's password.
-	pub fn change_password(&self, old_password: &Password, new_password: &Password, iterations: u32) -> Result<Self, Error> {
+	pub fn change_password(&self, old_password: &Password, new_password: &Password, kdf: KdfAlgorithm, iterations: u32) -> Result<Self, Error> {
 		let secret = self.crypto.secret(old_password)?;
 		let result = SafeAccount {
 			id: self.id.clone(),
 			version: self.version.clone(),
-			crypto: Crypto::with_secret(&secret, new_password, iterations)?,
+			crypto: Crypto::with_secret_kdf(&secret, new_password, kdf, iterations)?,
 			address: self.address.clone(),
 			filename: self.filename.clone(),
 			name: self.name.clone(),
@@ -164,6 +185,12 @@ impl SafeAccount {
 		Ok(result)
 	}
 
+	/// Re-encrypt account's secret with given key derivation function.
+	/// Password, id, address and metadata stay the same.
+	pub fn reencrypt(&self, password: &Password, kdf: KdfAlgorithm, iterations: u32) -> Result<Self, Error> {
+		self.change_password(password, password, kdf, iterations)
+	}
+
 	/// Check if password matches the account.
 	pub fn check_password(&self, password: &Password) -> bool {
 		self.crypto.secret(password).is_ok()
@@ -173,6 +200,7 @@ impl SafeAccount {
 #[cfg(test)]
 mod tests {
 	use ethkey::{Generator, Random, verify_public, Message};
+	use account::{Kdf, KdfAlgorithm};
 	use super::SafeAccount;
 
 	#[test]
@@ -193,10 +221,28 @@ mod tests {
 		let i = 10240;
 		let message = Message::default();
 		let account = SafeAccount::create(&keypair, [0u8; 16], &first_password, i, "Test".to_owned(), "{}".to_owned()).unwrap();
-		let new_account = account.change_password(&first_password, &sec_password, i).unwrap();
+		let new_account = account.change_password(&first_password, &sec_password, KdfAlgorithm::Pbkdf2, i).unwrap();
 		assert!(account.sign(&first_password, &message).is_ok());
 		assert!(account.sign(&sec_password, &message).is_err());
 		assert!(new_account.sign(&first_password, &message).is_err());
 		assert!(new_account.sign(&sec_password, &message).is_ok());
 	}
+
+	#[test]
+	fn reencrypt_with_argon2id() {
+		let keypair = Random.generate().unwrap();
+		let password = "hello world".into();
+		let message = Message::default();
+		let account = SafeAccount::create(&keypair, [1u8; 16], &password, 10240, "Test".to_owned(), "{}".to_owned()).unwrap();
+		let new_account = account.reencrypt(&password, KdfAlgorithm::Argon2id { memory_kib: 256 }, 1).unwrap();
+		match new_account.crypto.kdf {
+			Kdf::Argon2id(_) => (),
+			_ => panic!("expected Argon2id key derivation"),
+		}
+		assert_eq!(new_account.id, account.id);
+		assert_eq!(new_account.address, account.address);
+		assert_eq!(new_account.name, account.name);
+		let signature = new_account.sign(&password, &message).unwrap();
+		assert!(verify_public(keypair.public(), &signature, &message).unwrap());
+	}
 }
@@ -19,11 +19,15 @@ use std::io::Write;
 use std::path::{PathBuf, Path};
 use std::collections::HashMap;
 use time;
-use {json, SafeAccount, Error};
+use {json, SafeAccount, HdWallet, Error};
 use json::Uuid;
-use super::{KeyDirectory, VaultKeyDirectory, VaultKeyDirectoryProvider, VaultKey};
+use super::{KeyDirectory, VaultKeyDirectory, VaultKeyDirectoryProvider, VaultKey, HdWalletDirectory};
 use super::vault::{VAULT_FILE_NAME, VaultDiskDirectory};
 
+/// Name of the subdirectory storing HD wallets.
+/// Vault names can't start with a dot, so it never clashes with a vault.
+pub const HD_WALLETS_DIR: &'static str = ".hd";
+
 const IGNORED_FILES: &'static [&'static str] = &[
 	"thumbs.db",
 	"address_book.json",
@@ -238,6 +242,10 @@ impl<T> DiskDirectory<T> where T: KeyFileManager {
 	pub fn key_manager(&self) -> &T {
 		&self.key_manager
 	}
+
+	fn hd_wallets_path(&self) -> PathBuf {
+		self.path.join(HD_WALLETS_DIR)
+	}
 }
 
 impl<T> KeyDirectory for DiskDirectory<T> where T: KeyFileManager {
@@ -280,6 +288,10 @@ impl<T> KeyDirectory for DiskDirectory<T> where T: KeyFileManager {
 		Some(self)
 	}
 
+	fn as_hd_wallet_directory(&self) -> Option<&HdWalletDirectory> {
+		Some(self)
+	}
+
 	fn unique_repr(&self) -> Result<u64, Error> {
 		self.last_modification_date()
 	}
@@ -316,6 +328,55 @@ impl<T> VaultKeyDirectoryProvider for DiskDirectory<T> where T: KeyFileManager {
 	}
 }
 
+impl<T> HdWalletDirectory for DiskDirectory<T> where T: KeyFileManager {
+	fn load_hd_wallets(&self) -> Result<Vec<HdWallet>, Error> {
+		let path = self.hd_wallets_path();
+		if !path.is_dir() {
+			return Ok(Vec::new());
+		}
+
+		Ok(fs::read_dir(&path)?
+			.flat_map(Result::ok)
+			.map(|entry| entry.path())
+			.filter(|path| path.is_file())
+			.filter_map(|path| {
+				let filename = path.file_name().and_then(|n| n.to_str()).map(str::to_owned);
+				fs::File::open(&path)
+					.map_err(Into::into)
+					.and_then(|file| json::HdWalletFile::load(file).map_err(|e| Error::InvalidKeyFile(format!("{:?}", e))))
+					.and_then(|json| HdWallet::from_file(json, filename))
+					.map_err(|err| warn!("Invalid HD wallet file: {:?} ({})", path, err))
+					.ok()
+			})
+			.collect())
+	}
+
+	fn insert_hd_wallet(&self, wallet: HdWallet) -> Result<HdWallet, Error> {
+		let path = self.hd_wallets_path();
+		fs::create_dir_all(&path)?;
+
+		let filename = find_unique_filename_using_random_suffix(&path, &format!("{}.json", Uuid::from(wallet.id)))?;
+		let mut wallet = wallet;
+		wallet.filename = Some(filename.clone());
+
+		{
+			let mut file = create_new_file_with_permissions_to_owner(&path.join(&filename))?;
+			let json: json::HdWalletFile = wallet.clone().into();
+			json.write(&mut file).map_err(|e| Error::Custom(format!("{:?}", e)))?;
+
+			file.flush()?;
+			file.sync_all()?;
+		}
+
+		Ok(wallet)
+	}
+
+	fn remove_hd_wallet(&self, wallet: &HdWallet) -> Result<(), Error> {
+		let filename = wallet.filename.as_ref().ok_or(Error::HdWalletNotFound)?;
+		fs::remove_file(self.hd_wallets_path().join(filename)).map_err(From::from)
+	}
+}
+
 impl KeyFileManager for DiskKeyFileManager {
 	fn read<T>(&self, filename: Option<String>, reader: T) -> Result<SafeAccount, Error> where T: io::Read {
 		let key_file = json::KeyFile::load(reader).map_err(|e| Error::Custom(format!("{:?}", e)))?;
@@ -347,8 +408,8 @@ mod test {
 
 	use std::{env, fs};
 	use super::{KeyDirectory, RootDiskDirectory, VaultKey};
-	use account::SafeAccount;
-	use ethkey::{Random, Generator};
+	use account::{SafeAccount, HdWallet, KdfAlgorithm};
+	use ethkey::{Random, Generator, Mnemonic, DerivationPath};
 	use self::tempdir::TempDir;
 
 	#[test]
@@ -404,6 +465,30 @@ mod test {
 		let _ = fs::remove_dir_all(dir);
 	}
 
+	#[test]
+	fn should_manage_hd_wallets() {
+		// given
+		let temp_path = TempDir::new("").unwrap();
+		let directory = RootDiskDirectory::create(temp_path.path()).unwrap();
+		let hd_wallets = directory.as_hd_wallet_directory().unwrap();
+		let seed = Mnemonic::new(12).unwrap().seed("");
+		let wallet = HdWallet::create(&seed, [1u8; 16], &"password".into(), KdfAlgorithm::Pbkdf2, 1024, DerivationPath::ethereum_base(), "Test".into()).unwrap();
+
+		// when
+		let wallet = hd_wallets.insert_hd_wallet(wallet).unwrap();
+
+		// then
+		assert_eq!(hd_wallets.load_hd_wallets().unwrap(), vec![wallet.clone()]);
+		assert_eq!(directory.load().unwrap().len(), 0);
+		assert!(directory.as_vault_provider().unwrap().list_vaults().unwrap().is_empty());
+
+		// and when
+		hd_wallets.remove_hd_wallet(&wallet).unwrap();
+
+		// then
+		assert!(hd_wallets.load_hd_wallets().unwrap().is_empty());
+	}
+
 	#[test]
 	fn should_manage_vaults() {
 		// given
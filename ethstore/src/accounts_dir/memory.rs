@@ -19,13 +19,14 @@ use parking_lot::RwLock;
 use itertools;
 use ethkey::Address;
 
-use {SafeAccount, Error};
-use super::KeyDirectory;
+use {SafeAccount, HdWallet, Error};
+use super::{KeyDirectory, HdWalletDirectory};
 
 /// Accounts in-memory storage.
 #[derive(Default)]
 pub struct MemoryDirectory {
 	accounts: RwLock<HashMap<Address, Vec<SafeAccount>>>,
+	hd_wallets: RwLock<Vec<HdWallet>>,
 }
 
 impl KeyDirectory for MemoryDirectory {
@@ -65,6 +66,10 @@ impl KeyDirectory for MemoryDirectory {
 		Ok(())
 	}
 
+	fn as_hd_wallet_directory(&self) -> Option<&HdWalletDirectory> {
+		Some(self)
+	}
+
 	fn unique_repr(&self) -> Result<u64, Error> {
 		let mut val = 0u64;
 		let accounts = self.accounts.read();
@@ -72,3 +77,25 @@ impl KeyDirectory for MemoryDirectory {
 		Ok(val)
 	}
 }
+
+impl HdWalletDirectory for MemoryDirectory {
+	fn load_hd_wallets(&self) -> Result<Vec<HdWallet>, Error> {
+		Ok(self.hd_wallets.read().clone())
+	}
+
+	fn insert_hd_wallet(&self, wallet: HdWallet) -> Result<HdWallet, Error> {
+		self.hd_wallets.write().push(wallet.clone());
+		Ok(wallet)
+	}
+
+	fn remove_hd_wallet(&self, wallet: &HdWallet) -> Result<(), Error> {
+		let mut hd_wallets = self.hd_wallets.write();
+		match hd_wallets.iter().position(|w| w.id == wallet.id) {
+			Some(position) => {
+				hd_wallets.remove(position);
+				Ok(())
+			},
+			None => Err(Error::HdWalletNotFound),
+		}
+	}
+}
@@ -18,7 +18,7 @@
 
 use ethkey::Password;
 use std::path::{PathBuf};
-use {SafeAccount, Error};
+use {SafeAccount, HdWallet, Error, KdfAlgorithm};
 
 mod disk;
 mod memory;
@@ -40,6 +40,8 @@ pub enum SetKeyError {
 pub struct VaultKey {
 	/// Vault password
 	pub password: Password,
+	/// Key derivation function used to produce a derived key from password
+	pub kdf: KdfAlgorithm,
 	/// Number of iterations to produce a derived key from password
 	pub iterations: u32,
 }
@@ -58,6 +60,8 @@ pub trait KeyDirectory: Send + Sync {
 	fn path(&self) -> Option<&PathBuf> { None }
 	/// Return vault provider, if available
 	fn as_vault_provider(&self) -> Option<&VaultKeyDirectoryProvider> { None }
+	/// Return HD wallets storage, if available
+	fn as_hd_wallet_directory(&self) -> Option<&HdWalletDirectory> { None }
 	/// Unique representation of directory account collection
 	fn unique_repr(&self) -> Result<u64, Error>;
 }
@@ -74,6 +78,16 @@ pub trait VaultKeyDirectoryProvider {
 	fn vault_meta(&self, name: &str) -> Result<String, Error>;
 }
 
+/// HD wallets storage
+pub trait HdWalletDirectory: Send + Sync {
+	/// Read HD wallets from directory
+	fn load_hd_wallets(&self) -> Result<Vec<HdWallet>, Error>;
+	/// Insert new HD wallet to directory
+	fn insert_hd_wallet(&self, wallet: HdWallet) -> Result<HdWallet, Error>;
+	/// Remove HD wallet from directory
+	fn remove_hd_wallet(&self, wallet: &HdWallet) -> Result<(), Error>;
+}
+
 /// Vault directory
 pub trait VaultKeyDirectory: KeyDirectory {
 	/// Cast to `KeyDirectory`
@@ -97,8 +111,14 @@ pub use self::vault::VaultDiskDirectory;
 impl VaultKey {
 	/// Create new vault key
 	pub fn new(password: &Password, iterations: u32) -> Self {
+		VaultKey::with_kdf(password, KdfAlgorithm::Pbkdf2, iterations)
+	}
+
+	/// Create new vault key using given key derivation function
+	pub fn with_kdf(password: &Password, kdf: KdfAlgorithm, iterations: u32) -> Self {
 		VaultKey {
 			password: password.clone(),
+			kdf: kdf,
 			iterations: iterations,
 		}
 	}
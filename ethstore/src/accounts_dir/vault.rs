@@ -204,7 +204,7 @@ impl KeyFileManager for VaultKeyFileManager {
 		account.meta = json::remove_vault_name_from_json_meta(&account.meta)
 			.map_err(|err| Error::Custom(format!("{:?}", err)))?;
 
-		let vault_file: json::VaultKeyFile = account.into_vault_file(self.key.iterations, &self.key.password)?;
+		let vault_file: json::VaultKeyFile = account.into_vault_file(self.key.kdf, self.key.iterations, &self.key.password)?;
 		vault_file.write(writer).map_err(|e| Error::Custom(format!("{:?}", e)))
 	}
 }
@@ -235,7 +235,7 @@ fn check_vault_name(name: &str) -> bool {
 /// Vault can be empty, but still must be pluggable => we store vault password in separate file
 fn create_vault_file<P>(vault_dir_path: P, key: &VaultKey, meta: &str) -> Result<(), Error> where P: AsRef<Path> {
 	let password_hash = key.password.as_bytes().keccak256();
-	let crypto = Crypto::with_plain(&password_hash, &key.password, key.iterations)?;
+	let crypto = Crypto::with_plain_kdf(&password_hash, &key.password, key.kdf, key.iterations)?;
 
 	let vault_file_path = vault_dir_path.as_ref().join(VAULT_FILE_NAME);
 	let temp_vault_file_name = disk::find_unique_filename_using_random_suffix(vault_dir_path.as_ref(), &VAULT_TEMP_FILE_NAME)?;
@@ -283,6 +283,7 @@ mod test {
 	use std::fs;
 	use std::io::Write;
 	use std::path::PathBuf;
+	use account::KdfAlgorithm;
 	use super::VaultKey;
 	use super::{VAULT_FILE_NAME, check_vault_name, make_vault_dir_path, create_vault_file, read_vault_file, VaultDiskDirectory};
 	use self::tempdir::TempDir;
@@ -408,6 +409,26 @@ mod test {
 		assert!(vault.is_ok());
 	}
 
+	#[test]
+	fn argon2id_vault_directory_can_be_created() {
+		// given
+		let temp_path = TempDir::new("").unwrap();
+		let key = VaultKey::with_kdf(&"password".into(), KdfAlgorithm::Argon2id { memory_kib: 256 }, 1);
+		let dir: PathBuf = temp_path.path().into();
+
+		// when
+		let vault = VaultDiskDirectory::create(&dir, "vault", key.clone());
+
+		// then
+		assert!(vault.is_ok());
+
+		// and when
+		let vault = VaultDiskDirectory::at(&dir, "vault", VaultKey::new(&"password".into(), 1024));
+
+		// then
+		assert!(vault.is_ok());
+	}
+
 	#[test]
 	fn vault_directory_cannot_be_created_if_already_exists() {
 		// given
@@ -16,6 +16,7 @@
 
 use std::fmt;
 use std::io::Error as IoError;
+use argon2;
 use ethkey::{self, Error as EthKeyError};
 use crypto::{self, Error as EthCryptoError};
 use ethkey::DerivationError;
@@ -45,6 +46,10 @@ pub enum Error {
 	InvalidVaultName,
 	/// Vault not found
 	VaultNotFound,
+	/// HD wallets are not supported.
+	HdWalletsAreNotSupported,
+	/// HD wallet not found
+	HdWalletNotFound,
 	/// Account creation failed.
 	CreationFailed,
 	/// `EthKey` error
@@ -53,6 +58,8 @@ pub enum Error {
 	EthKeyCrypto(ethkey::crypto::Error),
 	/// `EthCrypto` error
 	EthCrypto(EthCryptoError),
+	/// Argon2 key derivation error
+	Argon2(argon2::Error),
 	/// Derivation error
 	Derivation(DerivationError),
 	/// Custom error
@@ -73,10 +80,13 @@ impl fmt::Display for Error {
 			Error::UnsupportedVault => "Vault is not supported for this operation".into(),
 			Error::InvalidVaultName => "Invalid vault name".into(),
 			Error::VaultNotFound => "Vault not found".into(),
+			Error::HdWalletsAreNotSupported => "HD wallets are not supported".into(),
+			Error::HdWalletNotFound => "HD wallet not found".into(),
 			Error::CreationFailed => "Account creation failed".into(),
 			Error::EthKey(ref err) => err.to_string(),
 			Error::EthKeyCrypto(ref err) => err.to_string(),
 			Error::EthCrypto(ref err) => err.to_string(),
+			Error::Argon2(ref err) => format!("Argon2 error: {}", err),
 			Error::Derivation(ref err) => format!("Derivation error: {:?}", err),
 			Error::Custom(ref s) => s.clone(),
 		};
@@ -121,6 +131,12 @@ impl From<crypto::error::SymmError> for Error {
 	}
 }
 
+impl From<argon2::Error> for Error {
+	fn from(err: argon2::Error) -> Self {
+		Error::Argon2(err)
+	}
+}
+
 impl From<DerivationError> for Error {
 	fn from(err: DerivationError) -> Self {
 		Error::Derivation(err)
@@ -22,12 +22,12 @@ use std::time::{Instant, Duration};
 
 use crypto::KEY_ITERATIONS;
 use random::Random;
-use ethkey::{self, Signature, Password, Address, Message, Secret, Public, KeyPair, ExtendedKeyPair};
-use accounts_dir::{KeyDirectory, VaultKeyDirectory, VaultKey, SetKeyError};
-use account::SafeAccount;
+use ethkey::{self, Signature, Password, Address, Message, Secret, Public, KeyPair, ExtendedKeyPair, Seed, DerivationPath};
+use accounts_dir::{KeyDirectory, VaultKeyDirectory, VaultKey, SetKeyError, HdWalletDirectory};
+use account::{SafeAccount, HdWallet, KdfAlgorithm};
 use presale::PresaleWallet;
 use json::{self, Uuid, OpaqueKeyFile};
-use {import, Error, SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation, OpaqueSecret};
+use {import, Error, SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation, OpaqueSecret, HdWalletInfo};
 
 /// Accounts store.
 pub struct EthStore {
@@ -42,8 +42,13 @@ impl EthStore {
 
 	/// Open a new account store with given key directory backend and custom number of iterations.
 	pub fn open_with_iterations(directory: Box<KeyDirectory>, iterations: u32) -> Result<Self, Error> {
+		Self::open_with_kdf(directory, KdfAlgorithm::Pbkdf2, iterations)
+	}
+
+	/// Open a new account store with given key directory backend, key derivation function and number of iterations.
+	pub fn open_with_kdf(directory: Box<KeyDirectory>, kdf: KdfAlgorithm, iterations: u32) -> Result<Self, Error> {
 		Ok(EthStore {
-			store: EthMultiStore::open_with_iterations(directory, iterations)?,
+			store: EthMultiStore::open_with_kdf(directory, kdf, iterations)?,
 		})
 	}
 
@@ -229,6 +234,30 @@ impl SecretStore for EthStore {
 		self.store.update(account_ref, old, safe_account)
 	}
 
+	fn reencrypt_account(&self, account_ref: &StoreAccountRef, password: &Password, kdf: KdfAlgorithm, iterations: u32) -> Result<(), Error> {
+		self.store.reencrypt_account(account_ref, password, kdf, iterations)
+	}
+
+	fn insert_hd_wallet(&self, seed: &Seed, password: &Password, path: DerivationPath, name: String) -> Result<Uuid, Error> {
+		self.store.insert_hd_wallet(seed, password, path, name)
+	}
+
+	fn hd_wallets(&self) -> Result<Vec<HdWalletInfo>, Error> {
+		self.store.hd_wallets()
+	}
+
+	fn hd_wallet_addresses(&self, wallet: &Uuid, password: &Password, from: u32, count: u32) -> Result<Vec<Address>, Error> {
+		self.store.hd_wallet_addresses(wallet, password, from, count)
+	}
+
+	fn insert_hd_account(&self, vault: SecretVaultRef, wallet: &Uuid, password: &Password, index: u32) -> Result<StoreAccountRef, Error> {
+		self.store.insert_hd_account(vault, wallet, password, index)
+	}
+
+	fn remove_hd_wallet(&self, wallet: &Uuid, password: &Password) -> Result<(), Error> {
+		self.store.remove_hd_wallet(wallet, password)
+	}
+
 	fn local_path(&self) -> PathBuf {
 		self.store.dir.path().cloned().unwrap_or_else(PathBuf::new)
 	}
@@ -257,6 +286,7 @@ impl SecretStore for EthStore {
 /// Similar to `EthStore` but may store many accounts (with different passwords) for the same `Address`
 pub struct EthMultiStore {
 	dir: Box<KeyDirectory>,
+	kdf: KdfAlgorithm,
 	iterations: u32,
 	// order lock: cache, then vaults
 	cache: RwLock<BTreeMap<StoreAccountRef, Vec<SafeAccount>>>,
@@ -278,9 +308,15 @@ impl EthMultiStore {
 
 	/// Open new multi-accounts store with given key directory backend and custom number of iterations for new keys.
 	pub fn open_with_iterations(directory: Box<KeyDirectory>, iterations: u32) -> Result<Self, Error> {
+		Self::open_with_kdf(directory, KdfAlgorithm::Pbkdf2, iterations)
+	}
+
+	/// Open new multi-accounts store with given key directory backend, key derivation function and number of iterations for new keys.
+	pub fn open_with_kdf(directory: Box<KeyDirectory>, kdf: KdfAlgorithm, iterations: u32) -> Result<Self, Error> {
 		let store = EthMultiStore {
 			dir: directory,
 			vaults: Mutex::new(HashMap::new()),
+			kdf: kdf,
 			iterations: iterations,
 			cache: Default::default(),
 			timestamp: Mutex::new(Timestamp {
@@ -304,6 +340,80 @@ impl EthMultiStore {
 		self.timestamp.lock().refresh_time = time;
 	}
 
+	/// Re-encrypt all accounts matching given reference and password using given key derivation function.
+	/// Account's address, UUID and password stay unchanged.
+	pub fn reencrypt_account(&self, account_ref: &StoreAccountRef, password: &Password, kdf: KdfAlgorithm, iterations: u32) -> Result<(), Error> {
+		let accounts = self.get_matching(account_ref, password)?;
+
+		if accounts.is_empty() {
+			return Err(Error::InvalidPassword);
+		}
+
+		for account in accounts {
+			let new_account = account.reencrypt(password, kdf, iterations)?;
+			self.update(account_ref, account, new_account)?;
+		}
+
+		Ok(())
+	}
+
+	/// Store HD wallet seed, encrypted with given password.
+	pub fn insert_hd_wallet(&self, seed: &Seed, password: &Password, path: DerivationPath, name: String) -> Result<Uuid, Error> {
+		let id: [u8; 16] = Random::random();
+		let wallet = HdWallet::create(seed, id, password, self.kdf, self.iterations, path, name)?;
+		self.hd_wallet_directory()?.insert_hd_wallet(wallet)?;
+		Ok(id.into())
+	}
+
+	/// List stored HD wallets.
+	pub fn hd_wallets(&self) -> Result<Vec<HdWalletInfo>, Error> {
+		Ok(self.hd_wallet_directory()?.load_hd_wallets()?
+			.into_iter()
+			.map(|wallet| HdWalletInfo {
+				id: wallet.id.into(),
+				name: wallet.name,
+				path: wallet.path,
+			})
+			.collect())
+	}
+
+	/// Derive addresses of HD wallet accounts with indexes in `from..from + count`.
+	pub fn hd_wallet_addresses(&self, wallet: &Uuid, password: &Password, from: u32, count: u32) -> Result<Vec<Address>, Error> {
+		self.get_hd_wallet(wallet)?.addresses(password, from, count)
+	}
+
+	/// Derive HD wallet account with given index and store it in given vault.
+	pub fn insert_hd_account(&self, vault: SecretVaultRef, wallet: &Uuid, password: &Password, index: u32) -> Result<StoreAccountRef, Error> {
+		let wallet = self.get_hd_wallet(wallet)?;
+		let keypair = wallet.derive(password, index)?;
+		let meta = format!(r#"{{"hdWallet":"{}","hdPath":"{}"}}"#, Uuid::from(wallet.id), wallet.account_path(index));
+		let id: [u8; 16] = Random::random();
+		let account = SafeAccount::create_with_kdf(&keypair, id, password, self.kdf, self.iterations, "".to_owned(), meta)?;
+		self.import(vault, account)
+	}
+
+	/// Remove HD wallet. Accounts already derived from it are kept.
+	pub fn remove_hd_wallet(&self, wallet: &Uuid, password: &Password) -> Result<(), Error> {
+		let wallet = self.get_hd_wallet(wallet)?;
+		if !wallet.check_password(password) {
+			return Err(Error::InvalidPassword);
+		}
+
+		self.hd_wallet_directory()?.remove_hd_wallet(&wallet)
+	}
+
+	fn hd_wallet_directory(&self) -> Result<&HdWalletDirectory, Error> {
+		self.dir.as_hd_wallet_directory().ok_or(Error::HdWalletsAreNotSupported)
+	}
+
+	fn get_hd_wallet(&self, wallet: &Uuid) -> Result<HdWallet, Error> {
+		let id: [u8; 16] = wallet.clone().into();
+		self.hd_wallet_directory()?.load_hd_wallets()?
+			.into_iter()
+			.find(|w| w.id == id)
+			.ok_or(Error::HdWalletNotFound)
+	}
+
 	fn reload_if_changed(&self) -> Result<(), Error> {
 		let mut last_timestamp = self.timestamp.lock();
 		let now = Instant::now();
@@ -458,7 +568,7 @@ impl SimpleSecretStore for EthMultiStore {
 	fn insert_account(&self, vault: SecretVaultRef, secret: Secret, password: &Password) -> Result<StoreAccountRef, Error> {
 		let keypair = KeyPair::from_secret(secret).map_err(|_| Error::CreationFailed)?;
 		let id: [u8; 16] = Random::random();
-		let account = SafeAccount::create(&keypair, id, password, self.iterations, "".to_owned(), "{}".to_owned())?;
+		let account = SafeAccount::create_with_kdf(&keypair, id, password, self.kdf, self.iterations, "".to_owned(), "{}".to_owned())?;
 		self.import(vault, account)
 	}
 
@@ -537,7 +647,7 @@ impl SimpleSecretStore for EthMultiStore {
 
 		for account in accounts {
 			// Change password
-			let new_account = account.change_password(old_password, new_password, self.iterations)?;
+			let new_account = account.change_password(old_password, new_password, self.kdf, self.iterations)?;
 			self.update(account_ref, account, new_account)?;
 		}
 
@@ -577,7 +687,7 @@ impl SimpleSecretStore for EthMultiStore {
 			let mut vaults = self.vaults.lock();
 			if !vaults.contains_key(&name.to_owned()) {
 				let vault_provider = self.dir.as_vault_provider().ok_or(Error::VaultsAreNotSupported)?;
-				let vault = vault_provider.create(name, VaultKey::new(password, self.iterations))?;
+				let vault = vault_provider.create(name, VaultKey::with_kdf(password, self.kdf, self.iterations))?;
 				vaults.insert(name.to_owned(), vault);
 				true
 			} else {
@@ -597,7 +707,7 @@ impl SimpleSecretStore for EthMultiStore {
 			let mut vaults = self.vaults.lock();
 			if !vaults.contains_key(&name.to_owned()) {
 				let vault_provider = self.dir.as_vault_provider().ok_or(Error::VaultsAreNotSupported)?;
-				let vault = vault_provider.open(name, VaultKey::new(password, self.iterations))?;
+				let vault = vault_provider.open(name, VaultKey::with_kdf(password, self.kdf, self.iterations))?;
 				vaults.insert(name.to_owned(), vault);
 				true
 			} else {
@@ -633,7 +743,7 @@ impl SimpleSecretStore for EthMultiStore {
 		let old_key = self.vaults.lock().get(name).map(|v| v.key()).ok_or(Error::VaultNotFound)?;
 		let vault_provider = self.dir.as_vault_provider().ok_or(Error::VaultsAreNotSupported)?;
 		let vault = vault_provider.open(name, old_key)?;
-		match vault.set_key(VaultKey::new(new_password, self.iterations)) {
+		match vault.set_key(VaultKey::with_kdf(new_password, self.kdf, self.iterations)) {
 			Ok(_) => {
 				self.close_vault(name)
 					.and_then(|_| self.open_vault(name, new_password))
@@ -692,6 +802,8 @@ mod tests {
 	use accounts_dir::{KeyDirectory, MemoryDirectory, RootDiskDirectory};
 	use ethkey::{Random, Generator, KeyPair};
 	use secret_store::{SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation};
+	use account::{Kdf, KdfAlgorithm};
+	use ethkey::{Mnemonic, DerivationPath};
 	use super::{EthStore, EthMultiStore};
 	use self::tempdir::TempDir;
 	use ethereum_types::H256;
@@ -1125,4 +1237,78 @@ mod tests {
 		// then
 		assert!(exported.is_ok(), "Should export single account: {:?}", exported);
 	}
+
+	#[test]
+	fn should_reencrypt_account_with_argon2id() {
+		// given
+		let store = store();
+		let keypair = keypair();
+		let passwd = "test".into();
+		let address = store.insert_account(SecretVaultRef::Root, keypair.secret().clone(), &passwd).unwrap();
+		let uuid = store.uuid(&address).unwrap();
+
+		// when
+		store.reencrypt_account(&address, &passwd, KdfAlgorithm::Argon2id { memory_kib: 256 }, 1).unwrap();
+
+		// then
+		let account = store.get(&address).unwrap();
+		match account.crypto.kdf {
+			Kdf::Argon2id(ref params) => assert_eq!(params.m, 256),
+			_ => panic!("expected Argon2id key derivation"),
+		}
+		assert_eq!(store.uuid(&address).unwrap(), uuid);
+		assert_eq!(store.accounts().unwrap(), vec![StoreAccountRef::root(keypair.address())]);
+		assert!(store.test_password(&address, &passwd).unwrap());
+		assert!(store.reencrypt_account(&address, &"x".into(), KdfAlgorithm::Pbkdf2, 1024).is_err());
+	}
+
+	#[test]
+	fn should_insert_account_with_store_kdf() {
+		// given
+		let store = EthStore::open_with_kdf(Box::new(MemoryDirectory::default()), KdfAlgorithm::Argon2id { memory_kib: 256 }, 1).unwrap();
+		let keypair = keypair();
+		let passwd = "test".into();
+
+		// when
+		let address = store.insert_account(SecretVaultRef::Root, keypair.secret().clone(), &passwd).unwrap();
+
+		// then
+		match store.get(&address).unwrap().crypto.kdf {
+			Kdf::Argon2id(_) => (),
+			_ => panic!("expected Argon2id key derivation"),
+		}
+		assert!(store.test_password(&address, &passwd).unwrap());
+	}
+
+	#[test]
+	fn should_derive_accounts_from_hd_wallet() {
+		// given
+		let store = store();
+		let passwd = "test".into();
+		let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+		let seed = Mnemonic::from_phrase(phrase).unwrap().seed("");
+
+		// when
+		let wallet = store.insert_hd_wallet(&seed, &passwd, DerivationPath::ethereum_base(), "Wallet".into()).unwrap();
+		let addresses = store.hd_wallet_addresses(&wallet, &passwd, 0, 2).unwrap();
+		let account = store.insert_hd_account(SecretVaultRef::Root, &wallet, &passwd, 1).unwrap();
+
+		// then
+		assert_eq!(store.hd_wallets().unwrap().len(), 1);
+		assert_eq!(store.hd_wallets().unwrap()[0].id, wallet);
+		assert_eq!(addresses[0], "9858effd232b4033e47d90003d41ec34ecaeda94".into());
+		assert_eq!(account, StoreAccountRef::root(addresses[1]));
+		assert_eq!(store.accounts().unwrap(), vec![account.clone()]);
+		assert!(store.meta(&account).unwrap().contains("m/44'/60'/0'/0/1"));
+		assert!(store.hd_wallet_addresses(&wallet, &"x".into(), 0, 1).is_err());
+		assert!(store.hd_wallet_addresses(&wallet, &passwd, 0, 1001).is_err());
+
+		// and when
+		assert!(store.remove_hd_wallet(&wallet, &"x".into()).is_err());
+		store.remove_hd_wallet(&wallet, &passwd).unwrap();
+
+		// then
+		assert!(store.hd_wallets().unwrap().is_empty());
+		assert!(store.test_password(&account, &passwd).unwrap());
+	}
 }
@@ -19,7 +19,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::ser::SerializeStruct;
 use serde::de::{Visitor, MapAccess, Error};
 use serde_json;
-use super::{Cipher, CipherSer, CipherSerParams, Kdf, KdfSer, KdfSerParams, H256, Bytes};
+use super::{Cipher, CipherSer, CipherSerParams, Kdf, KdfSer, KdfSerParams, H256, Bytes, Error as JsonError};
 
 pub type CipherText = Bytes;
 
@@ -140,6 +140,12 @@ impl<'a> Visitor<'a> for CryptoVisitor {
 		let kdf = match (kdf, kdfparams) {
 			(Some(KdfSer::Pbkdf2), Some(KdfSerParams::Pbkdf2(params))) => Kdf::Pbkdf2(params),
 			(Some(KdfSer::Scrypt), Some(KdfSerParams::Scrypt(params))) => Kdf::Scrypt(params),
+			(Some(KdfSer::Argon2id), Some(KdfSerParams::Argon2id(params))) => {
+				if !params.is_valid() {
+					return Err(V::Error::custom(JsonError::InvalidKdfParams));
+				}
+				Kdf::Argon2id(params)
+			},
 			(Some(_), Some(_)) => return Err(V::Error::custom("Invalid cipherparams")),
 			(None, _) => return Err(V::Error::missing_field("kdf")),
 			(Some(_), None) => return Err(V::Error::missing_field("kdfparams")),
@@ -182,6 +188,10 @@ impl Serialize for Crypto {
 				crypto.serialize_field("kdf", &KdfSer::Scrypt)?;
 				crypto.serialize_field("kdfparams", params)?;
 			},
+			Kdf::Argon2id(ref params) => {
+				crypto.serialize_field("kdf", &KdfSer::Argon2id)?;
+				crypto.serialize_field("kdfparams", params)?;
+			},
 		}
 
 		crypto.serialize_field("mac", &self.mac)?;
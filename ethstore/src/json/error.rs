@@ -21,6 +21,7 @@ pub enum Error {
 	UnsupportedCipher,
 	InvalidCipherParams,
 	UnsupportedKdf,
+	InvalidKdfParams,
 	InvalidUuid,
 	UnsupportedVersion,
 	InvalidCiphertext,
@@ -34,6 +35,7 @@ impl fmt::Display for Error {
 			Error::InvalidUuid => write!(f, "Invalid Uuid"),
 			Error::UnsupportedVersion => write!(f, "Unsupported version"),
 			Error::UnsupportedKdf => write!(f, "Unsupported kdf"),
+			Error::InvalidKdfParams => write!(f, "Invalid kdf params"),
 			Error::InvalidCiphertext => write!(f, "Invalid ciphertext"),
 			Error::UnsupportedCipher => write!(f, "Unsupported cipher"),
 			Error::InvalidCipherParams => write!(f, "Invalid cipher params"),
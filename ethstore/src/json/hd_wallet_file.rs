@@ -0,0 +1,81 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::{Read, Write};
+use serde_json;
+use super::{Uuid, Version, Crypto};
+
+/// HD wallet file
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct HdWalletFile {
+	/// Wallet id
+	pub id: Uuid,
+	/// Wallet version
+	pub version: Version,
+	/// BIP-39 seed, encrypted with wallet password
+	pub crypto: Crypto,
+	/// BIP-32 derivation path of wallet accounts (without account index)
+	pub path: String,
+	/// Wallet name
+	pub name: Option<String>,
+	/// Wallet metadata
+	pub meta: Option<String>,
+}
+
+impl HdWalletFile {
+	pub fn load<R>(reader: R) -> Result<Self, serde_json::Error> where R: Read {
+		serde_json::from_reader(reader)
+	}
+
+	pub fn write<W>(&self, writer: &mut W) -> Result<(), serde_json::Error> where W: Write {
+		serde_json::to_writer(writer, self)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use serde_json;
+	use json::{HdWalletFile, Version, Crypto, Cipher, Aes128Ctr, Kdf, Pbkdf2, Prf};
+
+	#[test]
+	fn to_and_from_json() {
+		let file = HdWalletFile {
+			id: "4e9a7fbb-5d7a-4d37-9a7c-9a42d8c1d1f4".into(),
+			version: Version::V3,
+			crypto: Crypto {
+				cipher: Cipher::Aes128Ctr(Aes128Ctr {
+					iv: "0155e3690be19fbfbecabcd440aa284b".into(),
+				}),
+				ciphertext: "4d6938a1f49b7782".into(),
+				kdf: Kdf::Pbkdf2(Pbkdf2 {
+					c: 1024,
+					dklen: 32,
+					prf: Prf::HmacSha256,
+					salt: "b6a9338a7ccd39288a86dba73bfecd9101b4f3db9c9830e7c76afdbd4f6872e5".into(),
+				}),
+				mac: "16381463ea11c6eb2239a9f339c2e780516d29d234ce30ac5f166f9080b5a262".into(),
+			},
+			path: "m/44'/60'/0'/0".into(),
+			name: Some("Wallet".into()),
+			meta: None,
+		};
+
+		let serialized = serde_json::to_string(&file).unwrap();
+		let deserialized = serde_json::from_str(&serialized).unwrap();
+
+		assert_eq!(file, deserialized);
+	}
+}
@@ -22,7 +22,7 @@ use serde::de::{Visitor, Error as SerdeError};
 use super::Error;
 
 /// Universaly unique identifier.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Uuid([u8; 16]);
 
 impl From<[u8; 16]> for Uuid {
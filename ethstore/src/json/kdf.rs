@@ -23,6 +23,7 @@ use super::{Error, H256};
 pub enum KdfSer {
 	Pbkdf2,
 	Scrypt,
+	Argon2id,
 }
 
 impl Serialize for KdfSer {
@@ -31,6 +32,7 @@ impl Serialize for KdfSer {
 		match *self {
 			KdfSer::Pbkdf2 => serializer.serialize_str("pbkdf2"),
 			KdfSer::Scrypt => serializer.serialize_str("scrypt"),
+			KdfSer::Argon2id => serializer.serialize_str("argon2id"),
 		}
 	}
 }
@@ -55,6 +57,7 @@ impl<'a> Visitor<'a> for KdfSerVisitor {
 		match value {
 			"pbkdf2" => Ok(KdfSer::Pbkdf2),
 			"scrypt" => Ok(KdfSer::Scrypt),
+			"argon2id" => Ok(KdfSer::Argon2id),
 			_ => Err(SerdeError::custom(Error::UnsupportedKdf))
 		}
 	}
@@ -123,10 +126,39 @@ pub struct Scrypt {
 	pub salt: H256,
 }
 
+/// Maximal Argon2id memory cost accepted in key files, in KiB.
+pub const ARGON2ID_MAX_MEMORY_KIB: u32 = 1024 * 1024;
+/// Maximal number of Argon2id passes accepted in key files.
+pub const ARGON2ID_MAX_ITERATIONS: u32 = 64;
+/// Maximal Argon2id degree of parallelism accepted in key files.
+pub const ARGON2ID_MAX_LANES: u32 = 16;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Argon2id {
+	pub dklen: u32,
+	/// Memory cost in KiB.
+	pub m: u32,
+	/// Number of passes over the memory.
+	pub t: u32,
+	/// Degree of parallelism.
+	pub p: u32,
+	pub salt: H256,
+}
+
+impl Argon2id {
+	/// Whether the parameters are within the bounds a key file may request.
+	pub fn is_valid(&self) -> bool {
+		self.m <= ARGON2ID_MAX_MEMORY_KIB
+			&& self.t >= 1 && self.t <= ARGON2ID_MAX_ITERATIONS
+			&& self.p >= 1 && self.p <= ARGON2ID_MAX_LANES
+	}
+}
+
 #[derive(Debug, PartialEq)]
 pub enum KdfSerParams {
 	Pbkdf2(Pbkdf2),
 	Scrypt(Scrypt),
+	Argon2id(Argon2id),
 }
 
 impl Serialize for KdfSerParams {
@@ -135,6 +167,7 @@ impl Serialize for KdfSerParams {
 		match *self {
 			KdfSerParams::Pbkdf2(ref params) => params.serialize(serializer),
 			KdfSerParams::Scrypt(ref params) => params.serialize(serializer),
+			KdfSerParams::Argon2id(ref params) => params.serialize(serializer),
 		}
 	}
 }
@@ -147,7 +180,8 @@ impl<'a> Deserialize<'a> for KdfSerParams {
 		let v: Value = Deserialize::deserialize(deserializer)?;
 
 		from_value(v.clone()).map(KdfSerParams::Pbkdf2)
-			.or_else(|_| from_value(v).map(KdfSerParams::Scrypt))
+			.or_else(|_| from_value(v.clone()).map(KdfSerParams::Scrypt))
+			.or_else(|_| from_value(v).map(KdfSerParams::Argon2id))
 			.map_err(|_| D::Error::custom("Invalid KDF algorithm"))
 	}
 }
@@ -156,4 +190,5 @@ impl<'a> Deserialize<'a> for KdfSerParams {
 pub enum Kdf {
 	Pbkdf2(Pbkdf2),
 	Scrypt(Scrypt),
+	Argon2id(Argon2id),
 }
@@ -190,7 +190,7 @@ impl KeyFile {
 mod tests {
 	use std::str::FromStr;
 	use serde_json;
-	use json::{KeyFile, Uuid, Version, Crypto, Cipher, Aes128Ctr, Kdf, Scrypt};
+	use json::{KeyFile, Uuid, Version, Crypto, Cipher, Aes128Ctr, Kdf, Scrypt, Argon2id};
 
 	#[test]
 	fn basic_keyfile() {
@@ -245,6 +245,73 @@ mod tests {
 		assert_eq!(keyfile, expected);
 	}
 
+	#[test]
+	fn argon2id_keyfile() {
+		let json = r#"
+		{
+			"address": "6edddfc6349aff20bc6467ccf276c5b52487f7a8",
+			"crypto": {
+				"cipher": "aes-128-ctr",
+				"ciphertext": "7203da0676d141b138cd7f8e1a4365f59cc1aa6978dc5443f364ca943d7cb4bc",
+				"cipherparams": {
+					"iv": "b5a7ec855ec9e2c405371356855fec83"
+				},
+				"kdf": "argon2id",
+				"kdfparams": {
+					"dklen": 32,
+					"m": 65536,
+					"t": 3,
+					"p": 1,
+					"salt": "1e8642fdf1f87172492c1412fc62f8db75d796cdfa9c53c3f2b11e44a2a1b209"
+				},
+				"mac": "46325c5d4e8c991ad2683d525c7854da387138b6ca45068985aa4959fa2b8c8f"
+			},
+			"id": "8777d9f6-7860-4b9b-88b7-0b57ee6b3a73",
+			"version": 3
+		}"#;
+
+		let keyfile: KeyFile = serde_json::from_str(json).unwrap();
+		assert_eq!(keyfile.crypto.kdf, Kdf::Argon2id(Argon2id {
+			dklen: 32,
+			m: 65536,
+			t: 3,
+			p: 1,
+			salt: "1e8642fdf1f87172492c1412fc62f8db75d796cdfa9c53c3f2b11e44a2a1b209".into(),
+		}));
+
+		let serialized = serde_json::to_string(&keyfile).unwrap();
+		let deserialized: KeyFile = serde_json::from_str(&serialized).unwrap();
+		assert_eq!(keyfile, deserialized);
+	}
+
+	#[test]
+	fn argon2id_keyfile_with_excessive_memory_cost() {
+		let json = r#"
+		{
+			"address": "6edddfc6349aff20bc6467ccf276c5b52487f7a8",
+			"crypto": {
+				"cipher": "aes-128-ctr",
+				"ciphertext": "7203da0676d141b138cd7f8e1a4365f59cc1aa6978dc5443f364ca943d7cb4bc",
+				"cipherparams": {
+					"iv": "b5a7ec855ec9e2c405371356855fec83"
+				},
+				"kdf": "argon2id",
+				"kdfparams": {
+					"dklen": 32,
+					"m": 4294967295,
+					"t": 3,
+					"p": 1,
+					"salt": "1e8642fdf1f87172492c1412fc62f8db75d796cdfa9c53c3f2b11e44a2a1b209"
+				},
+				"mac": "46325c5d4e8c991ad2683d525c7854da387138b6ca45068985aa4959fa2b8c8f"
+			},
+			"id": "8777d9f6-7860-4b9b-88b7-0b57ee6b3a73",
+			"version": 3
+		}"#;
+
+		assert!(serde_json::from_str::<KeyFile>(json).is_err());
+	}
+
 	#[test]
 	fn capital_crypto_keyfile() {
 		let json = r#"
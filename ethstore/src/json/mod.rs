@@ -21,6 +21,7 @@ mod cipher;
 mod crypto;
 mod error;
 mod hash;
+mod hd_wallet_file;
 mod id;
 mod kdf;
 mod key_file;
@@ -34,8 +35,9 @@ pub use self::cipher::{Cipher, CipherSer, CipherSerParams, Aes128Ctr};
 pub use self::crypto::{Crypto, CipherText};
 pub use self::error::Error;
 pub use self::hash::{H128, H160, H256};
+pub use self::hd_wallet_file::HdWalletFile;
 pub use self::id::Uuid;
-pub use self::kdf::{Kdf, KdfSer, Prf, Pbkdf2, Scrypt, KdfSerParams};
+pub use self::kdf::{Kdf, KdfSer, Prf, Pbkdf2, Scrypt, Argon2id, KdfSerParams, ARGON2ID_MAX_MEMORY_KIB, ARGON2ID_MAX_ITERATIONS, ARGON2ID_MAX_LANES};
 pub use self::key_file::{KeyFile, OpaqueKeyFile};
 pub use self::presale::{PresaleWallet, Encseed};
 pub use self::vault_file::VaultFile;
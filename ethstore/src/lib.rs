@@ -18,6 +18,7 @@
 
 #![warn(missing_docs)]
 
+extern crate argon2;
 extern crate dir;
 extern crate itertools;
 extern crate libc;
@@ -58,7 +59,7 @@ mod presale;
 mod random;
 mod secret_store;
 
-pub use self::account::{SafeAccount, Crypto};
+pub use self::account::{SafeAccount, HdWallet, Crypto, KdfAlgorithm, MAX_HD_WALLET_ADDRESSES};
 pub use self::error::Error;
 pub use self::ethstore::{EthStore, EthMultiStore};
 pub use self::import::{import_account, import_accounts, read_geth_accounts};
@@ -66,8 +67,9 @@ pub use self::json::OpaqueKeyFile as KeyFile;
 pub use self::presale::PresaleWallet;
 pub use self::secret_store::{
 	SecretVaultRef, StoreAccountRef, SimpleSecretStore, SecretStore,
-	Derivation, IndexDerivation,
+	Derivation, IndexDerivation, HdWalletInfo,
 };
+pub use self::json::Uuid;
 pub use self::random::random_string;
 pub use self::parity_wordlist::random_phrase;
 
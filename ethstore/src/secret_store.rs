@@ -17,8 +17,8 @@
 use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 use std::cmp::Ordering;
-use ethkey::{Address, Message, Signature, Secret, Password, Public};
-use Error;
+use ethkey::{Address, Message, Signature, Secret, Password, Public, Seed, DerivationPath};
+use {Error, KdfAlgorithm};
 use json::{Uuid, OpaqueKeyFile};
 use ethereum_types::H256;
 use OpaqueSecret;
@@ -53,6 +53,17 @@ impl ::std::borrow::Borrow<Address> for StoreAccountRef {
 	}
 }
 
+/// Stored HD wallet description
+#[derive(Debug, Clone, PartialEq)]
+pub struct HdWalletInfo {
+	/// Wallet id
+	pub id: Uuid,
+	/// Wallet name
+	pub name: String,
+	/// Derivation path of wallet accounts (without account index)
+	pub path: DerivationPath,
+}
+
 /// Simple Secret Store API
 pub trait SimpleSecretStore: Send + Sync {
 	/// Inserts new accounts to the store (or vault) with given password.
@@ -136,6 +147,20 @@ pub trait SecretStore: SimpleSecretStore {
 	fn set_name(&self, account: &StoreAccountRef, name: String) -> Result<(), Error>;
 	/// Modifies account name.
 	fn set_meta(&self, account: &StoreAccountRef, meta: String) -> Result<(), Error>;
+	/// Re-encrypts account's key file using given key derivation function and number of iterations.
+	/// Account's address, UUID and password stay unchanged.
+	fn reencrypt_account(&self, account: &StoreAccountRef, password: &Password, kdf: KdfAlgorithm, iterations: u32) -> Result<(), Error>;
+
+	/// Stores HD wallet seed, encrypted with given password. Wallet accounts are derived along `path` on demand.
+	fn insert_hd_wallet(&self, seed: &Seed, password: &Password, path: DerivationPath, name: String) -> Result<Uuid, Error>;
+	/// Lists stored HD wallets.
+	fn hd_wallets(&self) -> Result<Vec<HdWalletInfo>, Error>;
+	/// Returns addresses of HD wallet accounts with indexes in `from..from + count`.
+	fn hd_wallet_addresses(&self, wallet: &Uuid, password: &Password, from: u32, count: u32) -> Result<Vec<Address>, Error>;
+	/// Derives HD wallet account with given index and stores it in given vault, encrypted with wallet password.
+	fn insert_hd_account(&self, vault: SecretVaultRef, wallet: &Uuid, password: &Password, index: u32) -> Result<StoreAccountRef, Error>;
+	/// Removes HD wallet. Accounts already derived from it are kept.
+	fn remove_hd_wallet(&self, wallet: &Uuid, password: &Password) -> Result<(), Error>;
 
 	/// Returns local path of the store.
 	fn local_path(&self) -> PathBuf;
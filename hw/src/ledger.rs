@@ -56,6 +56,7 @@ mod commands {
 	pub const GET_ETH_PUBLIC_ADDRESS: u8 = 0x02;
 	pub const SIGN_ETH_TRANSACTION: u8 = 0x04;
 	pub const SIGN_ETH_PERSONAL_MESSAGE: u8 = 0x08;
+	pub const SIGN_ETH_EIP_712: u8 = 0x0C;
 }
 
 /// Hardware wallet error.
@@ -321,6 +322,14 @@ impl Manager {
 			}
 		}
 
+		// Signing EIP-712 typed data is only supported by Ledger firmware version 1.5.0 or newer
+		if command == commands::SIGN_ETH_EIP_712 {
+			let version = Self::get_firmware_version(&handle)?;
+			if version < FirmwareVersion::new(1, 5, 0) {
+				return Err(Error::Protocol("Signing EIP-712 typed data with Ledger requires version 1.5.0"));
+			}
+		}
+
 		let mut chunk= [0_u8; MAX_CHUNK_SIZE];
 		let derivation_path = self.get_derivation_path();
 
@@ -361,6 +370,14 @@ impl Manager {
 	pub fn sign_message(&self, address: &Address, msg: &[u8]) -> Result<Signature, Error> {
 		self.signer_helper(address, msg, commands::SIGN_ETH_PERSONAL_MESSAGE)
 	}
+
+	/// Sign EIP-712 typed data; the device receives domain separator and message hash only.
+	pub fn sign_typed_data(&self, address: &Address, domain_hash: &H256, message_hash: &H256) -> Result<Signature, Error> {
+		let mut data = Vec::with_capacity(64);
+		data.extend_from_slice(domain_hash);
+		data.extend_from_slice(message_hash);
+		self.signer_helper(address, &data, commands::SIGN_ETH_EIP_712)
+	}
 }
 
 // Try to connect to the device using polling in at most the time specified by the `timeout`
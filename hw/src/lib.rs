@@ -37,7 +37,7 @@ mod trezor;
 use std::sync::{Arc, atomic, atomic::AtomicBool};
 use std::{fmt, time::Duration};
 
-use ethereum_types::U256;
+use ethereum_types::{H256, U256};
 use ethkey::{Address, Signature};
 use parking_lot::Mutex;
 
@@ -271,6 +271,17 @@ impl HardwareWalletManager {
 		}
 	}
 
+	/// Sign EIP-712 typed data, given its domain separator and message hash (only supported by Ledger)
+	pub fn sign_typed_data(&self, address: &Address, domain_hash: &H256, message_hash: &H256) -> Result<Signature, Error> {
+		if self.ledger.get_wallet(address).is_some() {
+			Ok(self.ledger.sign_typed_data(address, domain_hash, message_hash)?)
+		} else if self.trezor.get_wallet(address).is_some() {
+			Err(Error::TrezorDevice(trezor::Error::NoSigningMessage))
+		} else {
+			Err(Error::KeyNotFound)
+		}
+	}
+
 	/// Sign transaction data with wallet managing `address`.
 	pub fn sign_transaction(&self, address: &Address, t_info: &TransactionInfo, encoded_transaction: &[u8]) -> Result<Signature, Error> {
 		if self.ledger.get_wallet(address).is_some() {
@@ -41,6 +41,26 @@ pub struct AltBn128Pairing {
 	pub base: usize,
 	/// Price per point pair.
 	pub pair: usize,
+	/// Base price after EIP-1108 transition.
+	pub eip1108_transition_base: Option<usize>,
+	/// Price per point pair after EIP-1108 transition.
+	pub eip1108_transition_pair: Option<usize>,
+}
+
+/// Pricing for constant alt_bn128 operations (ECADD and ECMUL).
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct AltBn128ConstOperations {
+	/// Price.
+	pub price: usize,
+	/// Price after EIP-1108 transition.
+	pub eip1108_transition_price: usize,
+}
+
+/// Pricing for the blake2 compression function (EIP-152).
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct Blake2F {
+	/// Price per round.
+	pub gas_per_round: u64,
 }
 
 /// Pricing variants.
@@ -55,6 +75,12 @@ pub enum Pricing {
 	/// Pricing for alt_bn128_pairing exponentiation.
 	#[serde(rename="alt_bn128_pairing")]
 	AltBn128Pairing(AltBn128Pairing),
+	/// Pricing for constant alt_bn128 operations.
+	#[serde(rename="alt_bn128_const_operations")]
+	AltBn128ConstOperations(AltBn128ConstOperations),
+	/// Pricing for the blake2 compression function.
+	#[serde(rename="blake2_f")]
+	Blake2F(Blake2F),
 }
 
 /// Spec builtin.
@@ -66,12 +92,14 @@ pub struct Builtin {
 	pub pricing: Pricing,
 	/// Activation block.
 	pub activate_at: Option<Uint>,
+	/// EIP-1108 transition block, changes the alt_bn128 builtin pricing.
+	pub eip1108_transition: Option<Uint>,
 }
 
 #[cfg(test)]
 mod tests {
 	use serde_json;
-	use spec::builtin::{Builtin, Pricing, Linear, Modexp};
+	use spec::builtin::{Builtin, Pricing, Linear, Modexp, AltBn128ConstOperations, Blake2F};
 	use uint::Uint;
 
 	#[test]
@@ -99,4 +127,35 @@ mod tests {
 		assert_eq!(deserialized.pricing, Pricing::Modexp(Modexp { divisor: 5 }));
 		assert_eq!(deserialized.activate_at, Some(Uint(100000.into())));
 	}
+
+	#[test]
+	fn eip1108_transition() {
+		let s = r#"{
+			"name": "alt_bn128_add",
+			"eip1108_transition": 100000,
+			"pricing": { "alt_bn128_const_operations": { "price": 500, "eip1108_transition_price": 150 } }
+		}"#;
+
+		let deserialized: Builtin = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.name, "alt_bn128_add");
+		assert_eq!(deserialized.pricing, Pricing::AltBn128ConstOperations(AltBn128ConstOperations {
+			price: 500,
+			eip1108_transition_price: 150,
+		}));
+		assert_eq!(deserialized.eip1108_transition, Some(Uint(100000.into())));
+	}
+
+	#[test]
+	fn blake2_f() {
+		let s = r#"{
+			"name": "blake2_f",
+			"activate_at": "0xffffff",
+			"pricing": { "blake2_f": { "gas_per_round": 123 } }
+		}"#;
+
+		let deserialized: Builtin = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.name, "blake2_f");
+		assert_eq!(deserialized.pricing, Pricing::Blake2F(Blake2F { gas_per_round: 123 }));
+		assert_eq!(deserialized.activate_at, Some(Uint(0xffffff.into())));
+	}
 }
@@ -114,6 +114,39 @@ pub struct Params {
 	#[serde(rename="eip1014Transition")]
 	pub eip1014_transition: Option<Uint>,
 	/// See `CommonParams` docs.
+	#[serde(rename="eip1344Transition")]
+	pub eip1344_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip1884Transition")]
+	pub eip1884_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip2028Transition")]
+	pub eip2028_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip2200Transition")]
+	pub eip2200_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip2929Transition")]
+	pub eip2929_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip2930Transition")]
+	pub eip2930_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip1559Transition")]
+	pub eip1559_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip1559BaseFeeMaxChangeDenominator")]
+	pub eip1559_base_fee_max_change_denominator: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip1559ElasticityMultiplier")]
+	pub eip1559_elasticity_multiplier: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip1559BaseFeeInitialValue")]
+	pub eip1559_base_fee_initial_value: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip3198Transition")]
+	pub eip3198_transition: Option<Uint>,
+	/// See `CommonParams` docs.
 	#[serde(rename="dustProtectionTransition")]
 	pub dust_protection_transition: Option<Uint>,
 	/// See `CommonParams` docs.
@@ -177,7 +210,10 @@ mod tests {
 			"accountStartNonce": "0x01",
 			"gasLimitBoundDivisor": "0x20",
 			"maxCodeSize": "0x1000",
-			"wasmActivationTransition": "0x1010"
+			"wasmActivationTransition": "0x1010",
+			"eip1344Transition": "0x2000",
+			"eip2200Transition": "0x2000",
+			"eip2930Transition": "0x3000"
 		}"#;
 
 		let deserialized: Params = serde_json::from_str(s).unwrap();
@@ -190,6 +226,11 @@ mod tests {
 		assert_eq!(deserialized.gas_limit_bound_divisor, Uint(U256::from(0x20)));
 		assert_eq!(deserialized.max_code_size, Some(Uint(U256::from(0x1000))));
 		assert_eq!(deserialized.wasm_activation_transition, Some(Uint(U256::from(0x1010))));
+		assert_eq!(deserialized.eip1344_transition, Some(Uint(U256::from(0x2000))));
+		assert_eq!(deserialized.eip1884_transition, None);
+		assert_eq!(deserialized.eip2200_transition, Some(Uint(U256::from(0x2000))));
+		assert_eq!(deserialized.eip2929_transition, None);
+		assert_eq!(deserialized.eip2930_transition, Some(Uint(U256::from(0x3000))));
 	}
 
 	#[test]
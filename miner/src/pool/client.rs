@@ -55,7 +55,7 @@ pub trait Client: fmt::Debug + Sync {
 		-> Result<transaction::SignedTransaction, transaction::Error>;
 
 	/// Estimate minimal gas requirurement for given transaction.
-	fn required_gas(&self, tx: &transaction::Transaction) -> U256;
+	fn required_gas(&self, tx: &transaction::UnverifiedTransaction) -> U256;
 
 	/// Fetch account details for given sender.
 	fn account_details(&self, address: &Address) -> AccountDetails;
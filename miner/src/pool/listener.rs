@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Notifier for new transaction hashes.
+//! Notifier for new and dropped transactions.
 
 use std::fmt;
 use std::sync::Arc;
@@ -24,44 +24,113 @@ use txpool::{self, VerifiedTransaction};
 
 use pool::VerifiedTransaction as Transaction;
 
-type Listener = Box<Fn(&[H256]) + Send + Sync>;
+type Listener = Box<Fn(&[Arc<Transaction>]) + Send + Sync>;
+type DroppedListener = Box<Fn(&[DroppedTransaction]) + Send + Sync>;
 
-/// Manages notifications to pending transaction listeners.
+/// Reason of a transaction leaving the pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DropReason {
+	/// Replaced by a transaction with the same sender and nonce.
+	Replaced(H256),
+	/// Pushed out by a transaction with a higher score because of the pool limits.
+	Evicted(Option<H256>),
+	/// Marked as invalid.
+	Invalid,
+	/// Canceled by the user.
+	Canceled,
+}
+
+/// A transaction that left the pool.
+#[derive(Debug, Clone)]
+pub struct DroppedTransaction {
+	/// The dropped transaction.
+	pub transaction: Arc<Transaction>,
+	/// Why it was dropped.
+	pub reason: DropReason,
+}
+
+/// Manages notifications to pending and dropped transaction listeners.
 #[derive(Default)]
 pub struct Notifier {
 	listeners: Vec<Listener>,
-	pending: Vec<H256>,
+	dropped_listeners: Vec<DroppedListener>,
+	pending: Vec<Arc<Transaction>>,
+	dropped: Vec<DroppedTransaction>,
 }
 
 impl fmt::Debug for Notifier {
 	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
 		fmt.debug_struct("Notifier")
 			.field("listeners", &self.listeners.len())
-			.field("pending", &self.pending)
+			.field("dropped_listeners", &self.dropped_listeners.len())
+			.field("pending", &self.pending.iter().map(|tx| *tx.hash()).collect::<Vec<_>>())
+			.field("dropped", &self.dropped.iter().map(|d| *d.transaction.hash()).collect::<Vec<_>>())
 			.finish()
 	}
 }
 
 impl Notifier {
-	/// Add new listener to receive notifications.
+	/// Add new listener to receive notifications about imported transactions.
 	pub fn add(&mut self, f: Listener) {
 		self.listeners.push(f)
 	}
 
-	/// Notify listeners about all currently pending transactions.
+	/// Add new listener to receive notifications about dropped transactions.
+	pub fn add_dropped(&mut self, f: DroppedListener) {
+		self.dropped_listeners.push(f)
+	}
+
+	/// Notify listeners about all currently pending and dropped transactions.
 	pub fn notify(&mut self) {
-		for l in &self.listeners {
-			(l)(&self.pending);
+		if !self.pending.is_empty() {
+			for l in &self.listeners {
+				(l)(&self.pending);
+			}
+		}
+
+		if !self.dropped.is_empty() {
+			for l in &self.dropped_listeners {
+				(l)(&self.dropped);
+			}
 		}
 
 		self.pending.clear();
+		self.dropped.clear();
+	}
+
+	fn drop_transaction(&mut self, tx: &Arc<Transaction>, reason: DropReason) {
+		if !self.dropped_listeners.is_empty() {
+			self.dropped.push(DroppedTransaction {
+				transaction: tx.clone(),
+				reason,
+			});
+		}
 	}
 }
 
 impl txpool::Listener<Transaction> for Notifier {
-	fn added(&mut self, tx: &Arc<Transaction>, _old: Option<&Arc<Transaction>>) {
-		self.pending.push(*tx.hash());
+	fn added(&mut self, tx: &Arc<Transaction>, old: Option<&Arc<Transaction>>) {
+		if !self.listeners.is_empty() {
+			self.pending.push(tx.clone());
+		}
+		if let Some(old) = old {
+			self.drop_transaction(old, DropReason::Replaced(*tx.hash()));
+		}
 	}
+
+	fn dropped(&mut self, tx: &Arc<Transaction>, new: Option<&Transaction>) {
+		self.drop_transaction(tx, DropReason::Evicted(new.map(|new| *new.hash())));
+	}
+
+	fn invalid(&mut self, tx: &Arc<Transaction>) {
+		self.drop_transaction(tx, DropReason::Invalid);
+	}
+
+	fn canceled(&mut self, tx: &Arc<Transaction>) {
+		self.drop_transaction(tx, DropReason::Canceled);
+	}
+
+	// culled transactions are mostly mined ones, they are not reported as dropped
 }
 
 /// Transaction pool logger.
@@ -124,8 +193,8 @@ mod tests {
 		// given
 		let received = Arc::new(Mutex::new(vec![]));
 		let r = received.clone();
-		let listener = Box::new(move |hashes: &[H256]| {
-			*r.lock() = hashes.iter().map(|x| *x).collect();
+		let listener = Box::new(move |transactions: &[Arc<Transaction>]| {
+			*r.lock() = transactions.iter().map(|tx| *tx.hash()).collect();
 		});
 
 		let mut tx_listener = Notifier::default();
@@ -144,6 +213,55 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn should_notify_dropped_listeners() {
+		// given
+		let received = Arc::new(Mutex::new(vec![]));
+		let r = received.clone();
+		let listener = Box::new(move |dropped: &[DroppedTransaction]| {
+			*r.lock() = dropped.iter().map(|d| (*d.transaction.hash(), d.reason.clone())).collect();
+		});
+
+		let mut tx_listener = Notifier::default();
+		tx_listener.add_dropped(listener);
+
+		// when
+		let old = new_tx();
+		let new = new_tx();
+		tx_listener.added(&new, Some(&old));
+		tx_listener.canceled(&new);
+		assert_eq!(*received.lock(), vec![]);
+
+		// then
+		tx_listener.notify();
+		assert_eq!(*received.lock(), vec![
+			(*old.hash(), DropReason::Replaced(*new.hash())),
+			(*new.hash(), DropReason::Canceled),
+		]);
+	}
+
+	#[test]
+	fn should_not_notify_dropped_listeners_about_culled_transactions() {
+		// given
+		let received = Arc::new(Mutex::new(vec![]));
+		let r = received.clone();
+		let listener = Box::new(move |dropped: &[DroppedTransaction]| {
+			*r.lock() = dropped.iter().map(|d| (*d.transaction.hash(), d.reason.clone())).collect();
+		});
+
+		let mut tx_listener = Notifier::default();
+		tx_listener.add_dropped(listener);
+
+		// when
+		let tx = new_tx();
+		tx_listener.added(&tx, None);
+		tx_listener.culled(&tx);
+
+		// then
+		tx_listener.notify();
+		assert_eq!(*received.lock(), vec![]);
+	}
+
 	fn new_tx() -> Arc<Transaction> {
 		let signed = transaction::Transaction {
 			action: transaction::Action::Create,
@@ -33,8 +33,9 @@ pub mod verifier;
 #[cfg(test)]
 mod tests;
 
+pub use self::listener::{DroppedTransaction, DropReason};
 pub use self::queue::{TransactionQueue, Status as QueueStatus};
-pub use self::txpool::{VerifiedTransaction as PoolVerifiedTransaction, Options};
+pub use self::txpool::{VerifiedTransaction as PoolVerifiedTransaction, Options, Status as PoolStatus};
 
 /// How to prioritize transactions in the pool
 ///
@@ -116,6 +117,12 @@ pub trait ScoredTransaction {
 	/// Gets transaction gas price.
 	fn gas_price(&self) -> &U256;
 
+	/// Gets maximal priority fee per gas the sender is willing to pay.
+	fn max_priority_fee_per_gas(&self) -> U256;
+
+	/// Gets tip per unit of gas paid to the block author in a block with given base fee.
+	fn effective_priority_fee(&self, block_base_fee: Option<U256>) -> U256;
+
 	/// Gets transaction nonce.
 	fn nonce(&self) -> U256;
 }
@@ -193,6 +200,14 @@ impl ScoredTransaction for VerifiedTransaction {
 		&self.transaction.gas_price
 	}
 
+	fn max_priority_fee_per_gas(&self) -> U256 {
+		self.transaction.max_priority_fee_per_gas()
+	}
+
+	fn effective_priority_fee(&self, block_base_fee: Option<U256>) -> U256 {
+		self.transaction.effective_priority_fee(block_base_fee)
+	}
+
 	/// Gets transaction nonce.
 	fn nonce(&self) -> U256 {
 		self.transaction.nonce
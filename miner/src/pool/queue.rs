@@ -215,7 +215,7 @@ impl TransactionQueue {
 		let max_count = limits.max_count;
 		TransactionQueue {
 			insertion_id: Default::default(),
-			pool: RwLock::new(txpool::Pool::new(Default::default(), scoring::NonceAndGasPrice(strategy), limits)),
+			pool: RwLock::new(txpool::Pool::new(Default::default(), scoring::NonceAndGasPrice::new(strategy), limits)),
 			options: RwLock::new(verification_options),
 			cached_pending: RwLock::new(CachedPending::none()),
 			recently_rejected: RecentlyRejected::new(cmp::max(MIN_REJECTED_CACHE_SIZE, max_count / 4)),
@@ -416,6 +416,19 @@ impl TransactionQueue {
 		(pending_readiness, state_readiness)
 	}
 
+	/// Returns the number of ready, future and stalled transactions in the pool.
+	///
+	/// Checks readiness of every transaction, so it's considerably more expensive than `status`.
+	pub fn full_status<C: client::NonceClient>(
+		&self,
+		client: C,
+		block_number: u64,
+		current_timestamp: u64,
+	) -> txpool::Status {
+		let ready = Self::ready(client, block_number, current_timestamp, None);
+		self.pool.read().status(ready)
+	}
+
 	/// Culls all stalled transactions from the pool.
 	pub fn cull<C: client::NonceClient + Clone>(
 		&self,
@@ -449,6 +462,8 @@ impl TransactionQueue {
 			let state_readiness = ready::State::new(client.clone(), stale_id, nonce_cap);
 			removed += self.pool.write().cull(Some(chunk), state_readiness);
 		}
+		// Flush notifications queued while culling.
+		(self.pool.write().listener_mut().1).0.notify();
 		debug!(target: "txqueue", "Removed {} stalled transactions. {}", removed, self.status());
 	}
 
@@ -496,10 +511,15 @@ impl TransactionQueue {
 		let results = {
 			let mut pool = self.pool.write();
 
-			hashes
+			let results = hashes
 				.into_iter()
 				.map(|hash| pool.remove(hash, is_invalid))
-				.collect::<Vec<_>>()
+				.collect::<Vec<_>>();
+
+			// Notify about dropped transactions.
+			(pool.listener_mut().1).0.notify();
+
+			results
 		};
 
 		if results.iter().any(Option::is_some) {
@@ -518,10 +538,27 @@ impl TransactionQueue {
 	pub fn penalize<'a, T: IntoIterator<Item = &'a Address>>(&self, senders: T) {
 		let mut pool = self.pool.write();
 		for sender in senders {
-			pool.update_scores(sender, ());
+			pool.update_scores(sender, scoring::ScoringEvent::Penalize);
 		}
 	}
 
+	/// Updates the base fee of the block the pending transactions are going to be included in
+	/// and re-scores all transactions, so that they are ordered by their effective tip.
+	pub fn set_block_base_fee(&self, block_base_fee: Option<U256>) {
+		let mut pool = self.pool.write();
+		let previous = pool.scoring().block_base_fee();
+		if previous == block_base_fee {
+			return;
+		}
+
+		pool.scoring().set_block_base_fee(block_base_fee);
+		let senders: Vec<_> = pool.senders().cloned().collect();
+		for sender in &senders {
+			pool.update_scores(sender, scoring::ScoringEvent::BlockBaseFeeChanged(previous));
+		}
+		self.cached_pending.write().clear();
+	}
+
 	/// Returns gas price of currently the worst transaction in the pool.
 	pub fn current_worst_gas_price(&self) -> U256 {
 		match self.pool.read().worst_transaction() {
@@ -561,11 +598,17 @@ impl TransactionQueue {
 	}
 
 	/// Add a callback to be notified about all transactions entering the pool.
-	pub fn add_listener(&self, f: Box<Fn(&[H256]) + Send + Sync>) {
+	pub fn add_listener(&self, f: Box<Fn(&[Arc<pool::VerifiedTransaction>]) + Send + Sync>) {
 		let mut pool = self.pool.write();
 		(pool.listener_mut().1).0.add(f);
 	}
 
+	/// Add a callback to be notified about transactions leaving the pool.
+	pub fn add_dropped_listener(&self, f: Box<Fn(&[pool::DroppedTransaction]) + Send + Sync>) {
+		let mut pool = self.pool.write();
+		(pool.listener_mut().1).0.add_dropped(f);
+	}
+
 	/// Check if pending set is cached.
 	#[cfg(test)]
 	pub fn is_pending_cached(&self) -> bool {
@@ -23,13 +23,16 @@
 //! is high enough to prevent attacking miners by requiring them to reshuffle/reexecute
 //! the queue too often.
 //!
-//! Transactions between senders are prioritized using `gas price`. Higher `gas price`
-//! yields more profits for miners. Additionally we prioritize transactions that originate
+//! Transactions between senders are prioritized using the effective tip, i.e. the part of
+//! the `gas price` that is paid to the miner on top of the block base fee (EIP-1559).
+//! Higher tips yield more profits for miners. Additionally we prioritize transactions that originate
 //! from our local node (own transactions).
 
 use std::cmp;
+use std::sync::Arc;
 
 use ethereum_types::U256;
+use parking_lot::RwLock;
 use txpool::{self, scoring};
 use super::{verifier, PrioritizationStrategy, VerifiedTransaction, ScoredTransaction};
 
@@ -43,14 +46,64 @@ fn bump_gas_price(old_gp: U256) -> U256 {
 	old_gp.saturating_add(old_gp >> GAS_PRICE_BUMP_SHIFT)
 }
 
+/// Events which cause re-scoring of transactions from a single sender.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoringEvent {
+	/// Lower the priority of all non-local transactions.
+	Penalize,
+	/// The block base fee changed from the given one, effective tips have to be recomputed.
+	BlockBaseFeeChanged(Option<U256>),
+}
+
 /// Simple, gas-price based scoring for transactions.
 ///
 /// NOTE: Currently penalization does not apply to new transactions that enter the pool.
 /// We might want to store penalization status in some persistent state.
 #[derive(Debug, Clone)]
-pub struct NonceAndGasPrice(pub PrioritizationStrategy);
+pub struct NonceAndGasPrice {
+	/// Prioritization strategy.
+	pub strategy: PrioritizationStrategy,
+	/// Base fee of the block the transactions are going to be included in.
+	/// Shared between the clones, so that the queue can update it on every new block.
+	block_base_fee: Arc<RwLock<Option<U256>>>,
+}
 
 impl NonceAndGasPrice {
+	/// Creates new scoring without a block base fee.
+	pub fn new(strategy: PrioritizationStrategy) -> Self {
+		NonceAndGasPrice {
+			strategy,
+			block_base_fee: Default::default(),
+		}
+	}
+
+	/// Returns the block base fee used to compute effective tips.
+	pub fn block_base_fee(&self) -> Option<U256> {
+		*self.block_base_fee.read()
+	}
+
+	/// Sets the block base fee used to compute effective tips.
+	///
+	/// NOTE: Scores of transactions already in the pool have to be updated
+	/// with `ScoringEvent::BlockBaseFeeChanged`.
+	pub fn set_block_base_fee(&self, block_base_fee: Option<U256>) {
+		*self.block_base_fee.write() = block_base_fee;
+	}
+
+	fn score<P: ScoredTransaction>(&self, tx: &P) -> U256 {
+		self.score_at(tx, self.block_base_fee())
+	}
+
+	fn score_at<P: ScoredTransaction>(&self, tx: &P, block_base_fee: Option<U256>) -> U256 {
+		let score = tx.effective_priority_fee(block_base_fee);
+		let boost = match tx.priority() {
+			super::Priority::Local => 15,
+			super::Priority::Retracted => 10,
+			super::Priority::Regular => 0,
+		};
+		score << boost
+	}
+
 	/// Decide if the transaction should even be considered into the pool (if the pool is full).
 	///
 	/// Used by Verifier to quickly reject transactions that don't have any chance to get into the pool later on,
@@ -63,13 +116,23 @@ impl NonceAndGasPrice {
 			return true
 		}
 
-		&old.transaction.gas_price > new.gas_price()
+		let block_base_fee = self.block_base_fee();
+		old.effective_priority_fee(block_base_fee) > new.effective_priority_fee(block_base_fee)
 	}
 }
 
+/// Number of bits the score has been shifted by penalization, given the unpenalized score.
+fn penalty_shift(unpenalized: U256, score: U256) -> usize {
+	let mut shift = 0;
+	while shift < 256 && score < (unpenalized >> shift) {
+		shift += 3;
+	}
+	shift
+}
+
 impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txpool::VerifiedTransaction {
 	type Score = U256;
-	type Event = ();
+	type Event = ScoringEvent;
 
 	fn compare(&self, old: &P, other: &P) -> cmp::Ordering {
 		old.nonce().cmp(&other.nonce())
@@ -80,14 +143,16 @@ impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txp
 			return scoring::Choice::InsertNew
 		}
 
+		// both the fee cap and the tip have to be bumped
 		let old_gp = old.gas_price();
 		let new_gp = new.gas_price();
+		let old_tip = old.max_priority_fee_per_gas();
+		let new_tip = new.max_priority_fee_per_gas();
 
-		let min_required_gp = bump_gas_price(*old_gp);
-
-		match min_required_gp.cmp(&new_gp) {
-			cmp::Ordering::Greater => scoring::Choice::RejectNew,
-			_ => scoring::Choice::ReplaceOld,
+		if bump_gas_price(*old_gp) > *new_gp || bump_gas_price(old_tip) > new_tip {
+			scoring::Choice::RejectNew
+		} else {
+			scoring::Choice::ReplaceOld
 		}
 	}
 
@@ -101,17 +166,10 @@ impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txp
 				assert!(i < txs.len());
 				assert!(i < scores.len());
 
-				scores[i] = *txs[i].transaction.gas_price();
-				let boost = match txs[i].priority() {
-					super::Priority::Local => 15,
-					super::Priority::Retracted => 10,
-					super::Priority::Regular => 0,
-				};
-				scores[i] = scores[i] << boost;
+				scores[i] = self.score(&*txs[i].transaction);
 			},
-			// We are only sending an event in case of penalization.
-			// So just lower the priority of all non-local transactions.
-			Change::Event(_) => {
+			// Lower the priority of all non-local transactions.
+			Change::Event(ScoringEvent::Penalize) => {
 				for (score, tx) in scores.iter_mut().zip(txs) {
 					// Never penalize local transactions.
 					if !tx.priority().is_local() {
@@ -119,6 +177,13 @@ impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txp
 					}
 				}
 			},
+			// Keep the penalization of the sender, the score is shifted the same way as before.
+			Change::Event(ScoringEvent::BlockBaseFeeChanged(previous)) => {
+				for (score, tx) in scores.iter_mut().zip(txs) {
+					let shift = penalty_shift(self.score_at(&*tx.transaction, previous), *score);
+					*score = self.score(&*tx.transaction) >> shift;
+				}
+			},
 		}
 	}
 
@@ -134,8 +199,9 @@ impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txp
 			// accept local transactions over the limit
 			scoring::Choice::InsertNew
 		} else {
-			let old_score = (old.priority(), old.gas_price());
-			let new_score = (new.priority(), new.gas_price());
+			let block_base_fee = self.block_base_fee();
+			let old_score = (old.priority(), old.effective_priority_fee(block_base_fee));
+			let new_score = (new.priority(), new.effective_priority_fee(block_base_fee));
 			if new_score > old_score {
 				scoring::Choice::ReplaceOld
 			} else {
@@ -161,7 +227,7 @@ mod tests {
 
 	#[test]
 	fn should_replace_same_sender_by_nonce() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 
 		let tx1 = Tx {
 			nonce: 1,
@@ -202,7 +268,7 @@ mod tests {
 	#[test]
 	fn should_replace_different_sender_by_priority_and_gas_price() {
 		// given
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let tx_regular_low_gas = {
 			let tx = Tx {
 				nonce: 1,
@@ -253,7 +319,7 @@ mod tests {
 	#[test]
 	fn should_calculate_score_correctly() {
 		// given
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let (tx1, tx2, tx3) = Tx::default().signed_triple();
 		let transactions = vec![tx1, tx2, tx3].into_iter().enumerate().map(|(i, tx)| {
 			let mut verified = tx.verified();
@@ -299,7 +365,60 @@ mod tests {
 		assert_eq!(scores, vec![32768.into(), 1024.into(), 1.into()]);
 
 		// Check penalization
-		scoring.update_scores(&transactions, &mut *scores, scoring::Change::Event(()));
+		scoring.update_scores(&transactions, &mut *scores, scoring::Change::Event(ScoringEvent::Penalize));
 		assert_eq!(scores, vec![32768.into(), 128.into(), 0.into()]);
 	}
+
+	#[test]
+	fn should_score_by_effective_tip() {
+		// given
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
+		let keypair = Random.generate().unwrap();
+		let legacy = Tx::gas_price(10).signed().verified();
+		let dynamic = Tx::gas_price(20).unsigned().sign_eip1559(keypair.secret(), 1, 2.into(), vec![]).verified();
+		let transactions = vec![legacy, dynamic].into_iter().map(|verified| txpool::Transaction {
+			insertion_id: 0,
+			transaction: Arc::new(verified),
+		}).collect::<Vec<_>>();
+		let mut scores = vec![U256::from(0), 0.into()];
+
+		// when
+		scoring.update_scores(&transactions, &mut *scores, scoring::Change::InsertedAt(0));
+		scoring.update_scores(&transactions, &mut *scores, scoring::Change::InsertedAt(1));
+
+		// then
+		assert_eq!(scores, vec![10.into(), 20.into()]);
+
+		// when
+		scoring.set_block_base_fee(Some(5.into()));
+		scoring.update_scores(&transactions, &mut *scores, scoring::Change::Event(ScoringEvent::BlockBaseFeeChanged(None)));
+
+		// then
+		assert_eq!(scores, vec![5.into(), 2.into()]);
+		assert_eq!(scoring.should_replace(&*transactions[1].transaction, &*transactions[0].transaction), ReplaceOld);
+	}
+
+	#[test]
+	fn should_keep_penalization_on_base_fee_change() {
+		// given
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
+		let transactions = vec![Tx::gas_price(800).signed().verified(), Tx::gas_price(640).signed().verified()]
+			.into_iter()
+			.map(|verified| txpool::Transaction {
+				insertion_id: 0,
+				transaction: Arc::new(verified),
+			}).collect::<Vec<_>>();
+		let mut scores = vec![U256::from(0), 0.into()];
+		scoring.update_scores(&transactions, &mut *scores, scoring::Change::InsertedAt(0));
+		scoring.update_scores(&transactions, &mut *scores, scoring::Change::InsertedAt(1));
+		scoring.update_scores(&transactions[..1], &mut scores[..1], scoring::Change::Event(ScoringEvent::Penalize));
+		assert_eq!(scores, vec![100.into(), 640.into()]);
+
+		// when
+		scoring.set_block_base_fee(Some(160.into()));
+		scoring.update_scores(&transactions, &mut *scores, scoring::Change::Event(ScoringEvent::BlockBaseFeeChanged(None)));
+
+		// then
+		assert_eq!(scores, vec![80.into(), 480.into()]);
+	}
 }
@@ -17,8 +17,7 @@
 use std::sync::{atomic, Arc};
 
 use ethereum_types::{U256, H256, Address};
-use rlp::Rlp;
-use transaction::{self, Transaction, SignedTransaction, UnverifiedTransaction};
+use transaction::{self, SignedTransaction, UnverifiedTransaction};
 
 use pool;
 use pool::client::AccountDetails;
@@ -119,7 +118,7 @@ impl pool::client::Client for TestClient {
 		details
 	}
 
-	fn required_gas(&self, _tx: &Transaction) -> U256 {
+	fn required_gas(&self, _tx: &UnverifiedTransaction) -> U256 {
 		self.gas_required
 	}
 
@@ -132,11 +131,10 @@ impl pool::client::Client for TestClient {
 	}
 
 	fn decode_transaction(&self, transaction: &[u8]) -> Result<UnverifiedTransaction, transaction::Error> {
-		let rlp = Rlp::new(&transaction);
-		if rlp.as_raw().len() > self.max_transaction_size {
+		if transaction.len() > self.max_transaction_size {
 			return Err(transaction::Error::TooBig)
 		}
-		rlp.as_val().map_err(|e| transaction::Error::InvalidRlp(e.to_string()))
+		UnverifiedTransaction::decode_envelope(transaction).map_err(|e| transaction::Error::InvalidRlp(e.to_string()))
 	}
 
 }
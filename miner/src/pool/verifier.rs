@@ -27,7 +27,6 @@ use std::sync::Arc;
 use std::sync::atomic::{self, AtomicUsize};
 
 use ethereum_types::{U256, H256};
-use rlp::Encodable;
 use transaction;
 use txpool;
 
@@ -105,7 +104,7 @@ impl Transaction {
 		}
 	}
 
-	fn transaction(&self) -> &transaction::Transaction {
+	fn transaction(&self) -> &transaction::UnverifiedTransaction {
 		match *self {
 			Transaction::Unverified(ref tx) => &*tx,
 			Transaction::Retracted(ref tx) => &*tx,
@@ -254,7 +253,7 @@ impl<C: Client> txpool::Verifier<Transaction> for Verifier<C, ::pool::scoring::N
 		};
 
 		// Verify RLP payload
-		if let Err(err) = self.client.decode_transaction(&transaction.rlp_bytes()) {
+		if let Err(err) = self.client.decode_transaction(&transaction.encode_envelope()) {
 			debug!(target: "txqueue", "[{:?}] Rejected transaction's rlp payload", err);
 			bail!(err)
 		}
@@ -15,9 +15,10 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::path::PathBuf;
-use ethcore::ethstore::{EthStore, SecretStore, import_account, import_accounts, read_geth_accounts};
+use ethcore::ethstore::{EthStore, SecretStore, KdfAlgorithm, import_account, import_accounts, read_geth_accounts};
 use ethcore::ethstore::accounts_dir::RootDiskDirectory;
 use ethcore::ethstore::SecretVaultRef;
+use ethkey::Mnemonic;
 use ethcore::account_provider::{AccountProvider, AccountProviderSettings};
 use helpers::{password_prompt, password_from_file};
 use params::SpecType;
@@ -38,10 +39,13 @@ pub struct ListAccounts {
 
 #[derive(Debug, PartialEq)]
 pub struct NewAccount {
+	pub kdf: KdfAlgorithm,
 	pub iterations: u32,
 	pub path: String,
 	pub spec: SpecType,
 	pub password_file: Option<String>,
+	/// create HD wallet from newly generated mnemonic phrase
+	pub mnemonic: bool,
 }
 
 #[derive(Debug, PartialEq)]
@@ -77,9 +81,9 @@ fn keys_dir(path: String, spec: SpecType) -> Result<RootDiskDirectory, String> {
 	RootDiskDirectory::create(path).map_err(|e| format!("Could not open keys directory: {}", e))
 }
 
-fn secret_store(dir: Box<RootDiskDirectory>, iterations: Option<u32>) -> Result<EthStore, String> {
-	match iterations {
-		Some(i) => EthStore::open_with_iterations(dir, i),
+fn secret_store(dir: Box<RootDiskDirectory>, kdf: Option<(KdfAlgorithm, u32)>) -> Result<EthStore, String> {
+	match kdf {
+		Some((kdf, iterations)) => EthStore::open_with_kdf(dir, kdf, iterations),
 		_ => EthStore::open(dir)
 	}.map_err(|e| format!("Could not open keys store: {}", e))
 }
@@ -91,8 +95,15 @@ fn new(n: NewAccount) -> Result<String, String> {
 	};
 
 	let dir = Box::new(keys_dir(n.path, n.spec)?);
-	let secret_store = Box::new(secret_store(dir, Some(n.iterations))?);
+	let secret_store = Box::new(secret_store(dir, Some((n.kdf, n.iterations)))?);
 	let acc_provider = AccountProvider::new(secret_store, AccountProviderSettings::default());
+	if n.mnemonic {
+		let mnemonic = Mnemonic::new(12).map_err(|e| format!("Could not generate mnemonic: {}", e))?;
+		let (_, new_account) = acc_provider.new_hd_wallet(&mnemonic, "", &password, String::new())
+			.map_err(|e| format!("Could not create new HD wallet: {}", e))?;
+		return Ok(format!("Mnemonic phrase (write it down, it will not be shown again):\n{}\n0x{:x}", mnemonic.phrase(), new_account));
+	}
+
 	let new_account = acc_provider.new_account(&password).map_err(|e| format!("Could not create new account: {}", e))?;
 	Ok(format!("0x{:x}", new_account))
 }
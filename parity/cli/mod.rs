@@ -41,6 +41,10 @@ usage! {
 
 			CMD cmd_account_new {
 				"Create a new account (and its associated key) for the given --chain (default: mainnet)",
+
+				FLAG flag_account_new_mnemonic: (bool) = false,
+				"--mnemonic",
+				"Generate a BIP-39 mnemonic phrase, store it as an HD wallet and create its first account (m/44'/60'/0'/0/0). The phrase is printed once and is not recoverable from the node.",
 			}
 
 			CMD cmd_account_list {
@@ -219,6 +223,50 @@ usage! {
 			}
 		}
 
+		CMD cmd_secretstore
+		{
+			"Manage Secret Store key shares stored at --secretstore-path. The shares are opened with --secretstore-master-password or --secretstore-master-key-file if given",
+
+			CMD cmd_secretstore_export
+			{
+				"Export key shares into a file. Exported shares are encrypted with the transfer password",
+
+				ARG arg_secretstore_export_transfer_password: (Option<String>) = None,
+				"--transfer-password=[FILE]",
+				"Provide a file containing a password to encrypt exported key shares with.",
+
+				ARG arg_secretstore_export_file: (Option<String>) = None,
+				"[FILE]",
+				"Path to the exported file",
+			}
+
+			CMD cmd_secretstore_import
+			{
+				"Import key shares, exported from another node, encrypting them with the master key of this node",
+
+				ARG arg_secretstore_import_transfer_password: (Option<String>) = None,
+				"--transfer-password=[FILE]",
+				"Provide a file containing a password the imported key shares are encrypted with.",
+
+				ARG arg_secretstore_import_file: (Option<String>) = None,
+				"[FILE]",
+				"Path to the file to import from",
+			}
+
+			CMD cmd_secretstore_reencrypt
+			{
+				"Re-encrypt key shares with a new master key. Unencrypted key shares are encrypted",
+
+				ARG arg_secretstore_reencrypt_master_password: (Option<String>) = None,
+				"--new-master-password=[FILE]",
+				"Provide a file containing a new master password.",
+
+				ARG arg_secretstore_reencrypt_master_key_file: (Option<String>) = None,
+				"--new-master-key-file=[FILE]",
+				"Provide a file containing a new hex-encoded 256-bit master key.",
+			}
+		}
+
 		CMD cmd_export_hardcoded_sync
 		{
 			"Print the hashed light clients headers of the given --chain (default: mainnet) in a JSON format. To be used as hardcoded headers in a genesis file.",
@@ -332,6 +380,10 @@ usage! {
 			"--keys-iterations=[NUM]",
 			"Specify the number of iterations to use when deriving key from the password (bigger is more secure)",
 
+			ARG arg_keys_kdf: (String) = "pbkdf2", or |c: &Config| c.account.as_ref()?.keys_kdf.clone(),
+			"--keys-kdf=[KDF]",
+			"Specify the key derivation function used to encrypt new keys and vaults. KDF may be either pbkdf2 or argon2id. --keys-iterations only applies to pbkdf2.",
+
 			ARG arg_accounts_refresh: (u64) = 5u64, or |c: &Config| c.account.as_ref()?.refresh_time.clone(),
 			"--accounts-refresh=[TIME]",
 			"Specify the cache time of accounts read from disk. If you manage thousands of accounts set this to 0 to disable refresh.",
@@ -369,6 +421,10 @@ usage! {
 			"--private-sstore-threshold=[NUM]",
 			"Specify secret store threshold used for encrypting private transactions.",
 
+			ARG arg_private_validator_keys: (Option<String>) = None, or |c: &Config| c.private_tx.as_ref()?.validator_keys.as_ref().map(|vec| vec.join(",")),
+			"--private-validator-keys=[PUBLICS]",
+			"Encrypt private contracts keys locally instead of using secret store. PUBLICS is a comma-delimited list of hex-encoded public keys of validators, each of which is able to decrypt contracts keys.",
+
 			ARG arg_private_passwords: (Option<String>) = None, or |c: &Config| c.private_tx.as_ref()?.passwords.clone(),
 			"--private-passwords=[FILE]...",
 			"Provide a file containing passwords for unlocking accounts (signer, private account, validators).",
@@ -391,6 +447,10 @@ usage! {
 			"--reserved-only",
 			"Connect only to reserved nodes.",
 
+			FLAG flag_state_sync: (bool) = false, or |_| None,
+			"--state-sync",
+			"Download the state from peers when warp sync finds no snapshot. Proof-of-work chains only.",
+
 			FLAG flag_no_ancient_blocks: (bool) = false, or |_| None,
 			"--no-ancient-blocks",
 			"Disable downloading old blocks after snapshot restoration or warp sync. Not recommended.",
@@ -562,6 +622,19 @@ usage! {
 			"--ipfs-api-cors=[URL]",
 			"Specify CORS header for IPFS API responses. Special options: \"all\", \"none\".",
 
+		["Metrics Options"]
+			FLAG flag_metrics: (bool) = false, or |c: &Config| c.metrics.as_ref()?.enable.clone(),
+			"--metrics",
+			"Enable the Prometheus metrics endpoint. Metrics are served at /metrics.",
+
+			ARG arg_metrics_port: (u16) = 3000u16, or |c: &Config| c.metrics.as_ref()?.port.clone(),
+			"--metrics-port=[PORT]",
+			"Configure on which port the metrics server should listen.",
+
+			ARG arg_metrics_interface: (String) = "local", or |c: &Config| c.metrics.as_ref()?.interface.clone(),
+			"--metrics-interface=[IP]",
+			"Specify the hostname portion of the metrics server, IP should be an interface's IP address, or all (all interfaces) or local.",
+
 		["Light Client Options"]
 			ARG arg_on_demand_retry_count: (Option<usize>) = None, or |c: &Config| c.light.as_ref()?.on_demand_retry_count,
 			"--on-demand-retry-count=[RETRIES]",
@@ -588,6 +661,10 @@ usage! {
 			"--secretstore-acl-contract=[SOURCE]",
 			"Secret Store permissioning contract address source: none, registry (contract address is read from 'secretstore_acl_checker' entry in registry) or address.",
 
+			ARG arg_secretstore_acl_cache_window: (u64) = 0u64, or |c: &Config| c.secretstore.as_ref()?.acl_cache_window.clone(),
+			"--secretstore-acl-cache-window=[SECS]",
+			"Number of seconds for which permissions read from the permissioning contract are used while the blockchain client is syncing or unavailable. 0 disables the permissions cache.",
+
 			ARG arg_secretstore_contract: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.service_contract.clone(),
 			"--secretstore-contract=[SOURCE]",
 			"Secret Store Service contract address source: none, registry (contract address is read from 'secretstore_service' entry in registry) or address.",
@@ -644,6 +721,14 @@ usage! {
 			"--secretstore-admin=[PUBLIC]",
 			"Hex-encoded public key of secret store administrator.",
 
+			ARG arg_secretstore_master_password: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.master_password.clone(),
+			"--secretstore-master-password=[FILE]",
+			"Provide a file containing a password used to encrypt stored key shares. Key shares are stored unencrypted if neither this nor --secretstore-master-key-file is given.",
+
+			ARG arg_secretstore_master_key_file: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.master_key_file.clone(),
+			"--secretstore-master-key-file=[FILE]",
+			"Provide a file containing a hex-encoded 256-bit key used to encrypt stored key shares.",
+
 		["Sealing/Mining Options"]
 			FLAG flag_force_sealing: (bool) = false, or |c: &Config| c.mining.as_ref()?.force_sealing.clone(),
 			"--force-sealing",
@@ -820,6 +905,10 @@ usage! {
 			"--tracing=[BOOL]",
 			"Indicates if full transaction tracing should be enabled. Works only if client had been fully synced with tracing enabled. BOOL may be one of auto, on, off. auto uses last used value of this option (off if it does not exist).", // footprint option
 
+			FLAG flag_tracing_address_index: (bool) = false, or |c: &Config| c.footprint.as_ref()?.tracing_address_index.clone(),
+			"--tracing-address-index",
+			"Maintain an index of traces by address to speed up trace_filter queries for specific addresses. Only blocks imported while the index is enabled are indexed, older blocks are still searched block by block.",
+
 			ARG arg_pruning: (String) = "auto", or |c: &Config| c.footprint.as_ref()?.pruning.clone(),
 			"--pruning=[METHOD]",
 			"Configure pruning of the state/storage trie. METHOD may be one of auto, archive, fast: archive - keep all state trie data. No pruning. fast - maintain journal overlay. Fast but 50MB used. auto - use the method most recently synced or default to fast if none synced.",
@@ -887,6 +976,18 @@ usage! {
 			"--whisper-pool-size=[MB]",
 			"Target size of the whisper message pool in megabytes.",
 
+			ARG arg_whisper_min_pow: (f64) = 0f64, or |c: &Config| c.whisper.as_ref()?.min_pow.clone(),
+			"--whisper-min-pow=[POW]",
+			"Minimum proof-of-work of whisper messages accepted from peers. It is advertised to peers so they don't send messages with less work.",
+
+			FLAG flag_whisper_topic_filter: (bool) = false, or |c: &Config| c.whisper.as_ref()?.topic_filter.clone(),
+			"--whisper-topic-filter",
+			"Only receive whisper messages matching the topics of local filters, advertising them to peers as a bloom filter. Other messages won't be relayed.",
+
+			ARG arg_whisper_mail_server_path: (Option<String>) = None, or |c: &Config| c.whisper.as_ref()?.mail_server_path.clone(),
+			"--whisper-mail-server-path=[PATH]",
+			"Run a whisper mail server, persisting received messages to a database at PATH and serving historical messages to peers.",
+
 		["Legacy Options"]
 			// Options that are hidden from config, but are still unique for its functionality.
 
@@ -1107,6 +1208,7 @@ struct Config {
 	secretstore: Option<SecretStore>,
 	private_tx: Option<PrivateTransactions>,
 	ipfs: Option<Ipfs>,
+	metrics: Option<Metrics>,
 	mining: Option<Mining>,
 	footprint: Option<Footprint>,
 	snapshots: Option<Snapshots>,
@@ -1147,6 +1249,7 @@ struct Account {
 	unlock: Option<Vec<String>>,
 	password: Option<Vec<String>>,
 	keys_iterations: Option<u32>,
+	keys_kdf: Option<String>,
 	refresh_time: Option<u64>,
 	disable_hardware: Option<bool>,
 	fast_unlock: Option<bool>,
@@ -1162,6 +1265,7 @@ struct PrivateTransactions {
 	passwords: Option<String>,
 	sstore_url: Option<String>,
 	sstore_threshold: Option<u32>,
+	validator_keys: Option<Vec<String>>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1279,6 +1383,9 @@ struct SecretStore {
 	http_interface: Option<String>,
 	http_port: Option<u16>,
 	path: Option<String>,
+	master_password: Option<String>,
+	master_key_file: Option<String>,
+	acl_cache_window: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1291,6 +1398,14 @@ struct Ipfs {
 	hosts: Option<Vec<String>>,
 }
 
+#[derive(Default, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Metrics {
+	enable: Option<bool>,
+	port: Option<u16>,
+	interface: Option<String>,
+}
+
 #[derive(Default, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct Mining {
@@ -1340,6 +1455,7 @@ struct Stratum {
 #[serde(deny_unknown_fields)]
 struct Footprint {
 	tracing: Option<String>,
+	tracing_address_index: Option<bool>,
 	pruning: Option<String>,
 	pruning_history: Option<u64>,
 	pruning_memory: Option<usize>,
@@ -1377,6 +1493,9 @@ struct Misc {
 struct Whisper {
 	enabled: Option<bool>,
 	pool_size: Option<usize>,
+	min_pow: Option<f64>,
+	topic_filter: Option<bool>,
+	mail_server_path: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1437,6 +1556,9 @@ mod tests {
 		let args = Args::parse(&["parity", "--pruning", "archive"]).unwrap();
 		assert_eq!(args.arg_pruning, "archive");
 
+		let args = Args::parse(&["parity", "account", "new", "--mnemonic"]).unwrap();
+		assert_eq!(args.flag_account_new_mnemonic, true);
+
 		let args = Args::parse(&["parity", "export", "state", "--no-storage"]).unwrap();
 		assert_eq!(args.flag_export_state_no_storage, true);
 
@@ -1578,6 +1700,10 @@ mod tests {
 			cmd_tools_hash: false,
 			cmd_db: false,
 			cmd_db_kill: false,
+			cmd_secretstore: false,
+			cmd_secretstore_export: false,
+			cmd_secretstore_import: false,
+			cmd_secretstore_reencrypt: false,
 			cmd_export_hardcoded_sync: false,
 
 			// Arguments
@@ -1591,10 +1717,17 @@ mod tests {
 			arg_snapshot_file: None,
 			arg_restore_file: None,
 			arg_tools_hash_file: None,
+			arg_secretstore_export_transfer_password: None,
+			arg_secretstore_export_file: None,
+			arg_secretstore_import_transfer_password: None,
+			arg_secretstore_import_file: None,
+			arg_secretstore_reencrypt_master_password: None,
+			arg_secretstore_reencrypt_master_key_file: None,
 
 			arg_signer_sign_id: None,
 			arg_signer_reject_id: None,
 			arg_dapp_path: None,
+			flag_account_new_mnemonic: false,
 			arg_account_import_path: None,
 			arg_wallet_import_path: None,
 
@@ -1628,6 +1761,7 @@ mod tests {
 			arg_unlock: Some("0xdeadbeefcafe0000000000000000000000000000".into()),
 			arg_password: vec!["~/.safe/password.file".into()],
 			arg_keys_iterations: 10240u32,
+			arg_keys_kdf: "pbkdf2".into(),
 			arg_accounts_refresh: 5u64,
 			flag_no_hardware_wallets: false,
 			flag_fast_unlock: false,
@@ -1640,6 +1774,7 @@ mod tests {
 			arg_private_account: Some("0xdeadbeefcafe0000000000000000000000000000".into()),
 			arg_private_sstore_url: Some("http://localhost:8082".into()),
 			arg_private_sstore_threshold: Some(0),
+			arg_private_validator_keys: None,
 
 			flag_force_ui: false,
 			flag_no_ui: false,
@@ -1665,6 +1800,7 @@ mod tests {
 			arg_node_key: None,
 			arg_reserved_peers: Some("./path_to_file".into()),
 			flag_reserved_only: false,
+			flag_state_sync: false,
 			flag_no_ancient_blocks: false,
 			flag_no_serve_light: false,
 
@@ -1717,6 +1853,9 @@ mod tests {
 			arg_secretstore_http_interface: "local".into(),
 			arg_secretstore_http_port: 8082u16,
 			arg_secretstore_path: "$HOME/.parity/secretstore".into(),
+			arg_secretstore_master_password: None,
+			arg_secretstore_master_key_file: None,
+			arg_secretstore_acl_cache_window: 0u64,
 
 			// IPFS
 			flag_ipfs_api: false,
@@ -1725,6 +1864,11 @@ mod tests {
 			arg_ipfs_api_cors: "null".into(),
 			arg_ipfs_api_hosts: "none".into(),
 
+			// -- Metrics Options
+			flag_metrics: false,
+			arg_metrics_port: 3000u16,
+			arg_metrics_interface: "local".into(),
+
 			// -- Sealing/Mining Options
 			arg_author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 			arg_engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
@@ -1766,6 +1910,7 @@ mod tests {
 
 			// -- Footprint Options
 			arg_tracing: "auto".into(),
+			flag_tracing_address_index: false,
 			arg_pruning: "auto".into(),
 			arg_pruning_history: 64u64,
 			arg_pruning_memory: 500usize,
@@ -1802,6 +1947,9 @@ mod tests {
 			// -- Whisper options.
 			flag_whisper: false,
 			arg_whisper_pool_size: 20,
+			arg_whisper_min_pow: 0f64,
+			flag_whisper_topic_filter: false,
+			arg_whisper_mail_server_path: None,
 
 			// -- Legacy Options
 			flag_warp: false,
@@ -1902,6 +2050,7 @@ mod tests {
 				unlock: Some(vec!["0x1".into(), "0x2".into(), "0x3".into()]),
 				password: Some(vec!["passwdfile path".into()]),
 				keys_iterations: None,
+				keys_kdf: None,
 				refresh_time: None,
 				disable_hardware: None,
 				fast_unlock: None,
@@ -1987,6 +2136,9 @@ mod tests {
 				http_interface: None,
 				http_port: Some(8082),
 				path: None,
+				master_password: None,
+				master_key_file: None,
+				acl_cache_window: None,
 			}),
 			private_tx: None,
 			ipfs: Some(Ipfs {
@@ -1996,6 +2148,7 @@ mod tests {
 				cors: None,
 				hosts: None,
 			}),
+			metrics: None,
 			mining: Some(Mining {
 				author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 				engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
@@ -2032,6 +2185,7 @@ mod tests {
 			}),
 			footprint: Some(Footprint {
 				tracing: Some("on".into()),
+				tracing_address_index: None,
 				pruning: Some("fast".into()),
 				pruning_history: Some(64),
 				pruning_memory: None,
@@ -2064,6 +2218,9 @@ mod tests {
 			whisper: Some(Whisper {
 				enabled: Some(true),
 				pool_size: Some(50),
+				min_pow: None,
+				topic_filter: None,
+				mail_server_path: None,
 			}),
 			stratum: None,
 		});
@@ -22,12 +22,13 @@ use std::collections::BTreeMap;
 use std::cmp;
 use cli::{Args, ArgsError};
 use hash::keccak;
-use ethereum_types::{U256, H256, Address};
+use ethereum_types::{U256, H256, Address, clean_0x};
 use parity_version::{version_data, version};
 use bytes::Bytes;
 use ansi_term::Colour;
 use sync::{NetworkConfiguration, validate_node_url, self};
 use ethcore::ethstore::ethkey::{Secret, Public};
+use ethcore::ethstore::KdfAlgorithm;
 use ethcore::client::{VMType};
 use ethcore::miner::{stratum, MinerOptions};
 use ethcore::snapshot::SnapshotConfiguration;
@@ -44,8 +45,10 @@ use params::{ResealPolicy, AccountsConfig, GasPricerConfig, MinerExtras, SpecTyp
 use ethcore_logger::Config as LogConfig;
 use dir::{self, Directories, default_hypervisor_path, default_local_path, default_data_path};
 use ipfs::Configuration as IpfsConfiguration;
-use ethcore_private_tx::{ProviderConfig, EncryptorConfig};
-use secretstore::{NodeSecretKey, Configuration as SecretStoreConfiguration, ContractAddress as SecretStoreContractAddress};
+use metrics::Configuration as MetricsConfiguration;
+use ethcore_private_tx::{ProviderConfig, EncryptorConfig, LocalEncryptorConfig};
+use secretstore::{NodeSecretKey, Configuration as SecretStoreConfiguration, ContractAddress as SecretStoreContractAddress,
+	MasterKeySource as SecretStoreMasterKeySource, SecretStoreCmd};
 use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 use run::RunCmd;
 use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, DataFormat};
@@ -84,6 +87,7 @@ pub enum Cmd {
 	Snapshot(SnapshotCommand),
 	Hash(Option<String>),
 	ExportHardcodedSync(ExportHsyncCmd),
+	SecretStore(SecretStoreCmd),
 }
 
 pub struct Execute {
@@ -139,6 +143,7 @@ impl Configuration {
 		let warp_sync = !self.args.flag_no_warp;
 		let geth_compatibility = self.args.flag_geth;
 		let ipfs_conf = self.ipfs_config();
+		let metrics_conf = self.metrics_config();
 		let secretstore_conf = self.secretstore_config()?;
 		let format = self.format()?;
 
@@ -183,11 +188,14 @@ impl Configuration {
 			}))
 		} else if self.args.cmd_account {
 			let account_cmd = if self.args.cmd_account_new {
+				let (kdf, iterations) = self.keys_kdf()?;
 				let new_acc = NewAccount {
-					iterations: self.args.arg_keys_iterations,
+					kdf: kdf,
+					iterations: iterations,
 					path: dirs.keys,
 					spec: spec,
 					password_file: self.accounts_config()?.password_files.first().map(|x| x.to_owned()),
+					mnemonic: self.args.flag_account_new_mnemonic,
 				};
 				AccountCmd::New(new_acc)
 			} else if self.args.cmd_account_list {
@@ -330,6 +338,41 @@ impl Configuration {
 				compaction: compaction,
 			};
 			Cmd::ExportHardcodedSync(export_hs_cmd)
+		} else if self.args.cmd_secretstore {
+			let data_path = self.directories().secretstore;
+			let master_key = self.secretstore_master_key()?;
+			let secretstore_cmd = if self.args.cmd_secretstore_export {
+				SecretStoreCmd::Export {
+					data_path: data_path,
+					master_key: master_key,
+					transfer_password_file: self.args.arg_secretstore_export_transfer_password.clone()
+						.ok_or_else(|| "--transfer-password is required to export key shares".to_owned())?,
+					file_path: self.args.arg_secretstore_export_file.clone(),
+				}
+			} else if self.args.cmd_secretstore_import {
+				SecretStoreCmd::Import {
+					data_path: data_path,
+					master_key: master_key,
+					transfer_password_file: self.args.arg_secretstore_import_transfer_password.clone()
+						.ok_or_else(|| "--transfer-password is required to import key shares".to_owned())?,
+					file_path: self.args.arg_secretstore_import_file.clone(),
+				}
+			} else if self.args.cmd_secretstore_reencrypt {
+				let new_master_key = match (self.args.arg_secretstore_reencrypt_master_password.clone(), self.args.arg_secretstore_reencrypt_master_key_file.clone()) {
+					(Some(_), Some(_)) => return Err("--new-master-password and --new-master-key-file are mutually exclusive".into()),
+					(Some(password_file), None) => SecretStoreMasterKeySource::PasswordFile(password_file),
+					(None, Some(key_file)) => SecretStoreMasterKeySource::KeyFile(key_file),
+					(None, None) => return Err("--new-master-password or --new-master-key-file is required to re-encrypt key shares".into()),
+				};
+				SecretStoreCmd::Reencrypt {
+					data_path: data_path,
+					master_key: master_key,
+					new_master_key: new_master_key,
+				}
+			} else {
+				unreachable!();
+			};
+			Cmd::SecretStore(secretstore_cmd)
 		} else {
 			let daemon = if self.args.cmd_daemon {
 				Some(self.args.arg_daemon_pid_file.clone().expect("CLI argument is required; qed"))
@@ -340,6 +383,7 @@ impl Configuration {
 			let verifier_settings = self.verifier_settings();
 			let whisper_config = self.whisper_config();
 			let (private_provider_conf, private_enc_conf, private_tx_enabled) = self.private_provider_config()?;
+			let private_local_enc_conf = self.private_local_encryptor_config()?;
 
 			let run_cmd = RunCmd {
 				cache_config: cache_config,
@@ -367,16 +411,20 @@ impl Configuration {
 				mode: mode,
 				tracing: tracing,
 				fat_db: fat_db,
+				tracing_address_index: self.args.flag_tracing_address_index,
 				compaction: compaction,
 				vm_type: vm_type,
 				warp_sync: warp_sync,
 				warp_barrier: self.args.arg_warp_barrier,
+				state_sync: self.args.flag_state_sync,
 				geth_compatibility: geth_compatibility,
 				net_settings: self.network_settings()?,
 				ipfs_conf: ipfs_conf,
+				metrics_conf: metrics_conf,
 				secretstore_conf: secretstore_conf,
 				private_provider_conf: private_provider_conf,
 				private_encryptor_conf: private_enc_conf,
+				private_local_encryptor_conf: private_local_enc_conf,
 				private_tx_enabled,
 				name: self.args.arg_identity,
 				custom_bootnodes: self.args.arg_bootnodes.is_some(),
@@ -496,9 +544,21 @@ impl Configuration {
 		self.args.arg_notify_work.as_ref().map_or_else(Vec::new, |s| s.split(',').map(|s| s.to_owned()).collect())
 	}
 
+	fn keys_kdf(&self) -> Result<(KdfAlgorithm, u32), String> {
+		let kdf: KdfAlgorithm = self.args.arg_keys_kdf.parse()?;
+		let iterations = match kdf {
+			KdfAlgorithm::Pbkdf2 => self.args.arg_keys_iterations,
+			KdfAlgorithm::Argon2id { .. } => kdf.default_iterations(),
+		};
+
+		Ok((kdf, iterations))
+	}
+
 	fn accounts_config(&self) -> Result<AccountsConfig, String> {
+		let (kdf, iterations) = self.keys_kdf()?;
 		let cfg = AccountsConfig {
-			iterations: self.args.arg_keys_iterations,
+			kdf: kdf,
+			iterations: iterations,
 			refresh_time: self.args.arg_accounts_refresh,
 			testnet: self.args.flag_testnet,
 			password_files: self.args.arg_password.iter().map(|s| replace_home(&self.directories().base, s)).collect(),
@@ -601,6 +661,8 @@ impl Configuration {
 			http_port: self.args.arg_ports_shift + self.args.arg_secretstore_http_port,
 			data_path: self.directories().secretstore,
 			admin_public: self.secretstore_admin_public()?,
+			master_key: self.secretstore_master_key()?,
+			acl_cache_window: self.secretstore_acl_cache_window(),
 		})
 	}
 
@@ -614,6 +676,14 @@ impl Configuration {
 		}
 	}
 
+	fn metrics_config(&self) -> MetricsConfiguration {
+		MetricsConfiguration {
+			enabled: self.args.flag_metrics,
+			port: self.args.arg_ports_shift + self.args.arg_metrics_port,
+			interface: self.metrics_interface(),
+		}
+	}
+
 	fn gas_pricer_config(&self) -> Result<GasPricerConfig, String> {
 		fn wei_per_gas(usd_per_tx: f32, usd_per_eth: f32) -> U256 {
 			let wei_per_usd: f32 = 1.0e18 / usd_per_eth;
@@ -897,6 +967,28 @@ impl Configuration {
 		Ok((provider_conf, encryptor_conf, self.args.flag_private_enabled))
 	}
 
+	fn private_local_encryptor_config(&self) -> Result<Option<LocalEncryptorConfig>, String> {
+		let validators = match self.args.arg_private_validator_keys {
+			Some(ref keys) if !keys.is_empty() => keys.split(',')
+				.map(|key| clean_0x(key).parse().map_err(|_| format!("Invalid validator public key: {:?}", key)))
+				.collect::<Result<Vec<_>, _>>()?,
+			_ => return Ok(None),
+		};
+
+		if self.args.arg_private_sstore_url.is_some() {
+			return Err("--private-validator-keys and --private-sstore-url are mutually exclusive".into());
+		}
+
+		Ok(Some(LocalEncryptorConfig {
+			keys_path: PathBuf::from(&self.directories().base).join("private_keys.json"),
+			validators,
+			passwords: match self.args.arg_private_passwords.clone() {
+				Some(file) => passwords_from_files(&vec![file].as_slice())?,
+				None => Vec::new(),
+			},
+		}))
+	}
+
 	fn snapshot_config(&self) -> Result<SnapshotConfiguration, String> {
 		let conf = SnapshotConfiguration {
 			no_periodic: self.args.flag_no_periodic_snapshot,
@@ -1019,6 +1111,10 @@ impl Configuration {
 		self.interface(&self.args.arg_ipfs_api_interface)
 	}
 
+	fn metrics_interface(&self) -> String {
+		self.interface(&self.args.arg_metrics_interface)
+	}
+
 	fn secretstore_interface(&self) -> String {
 		self.interface(&self.args.arg_secretstore_interface)
 	}
@@ -1045,6 +1141,22 @@ impl Configuration {
 		}
 	}
 
+	fn secretstore_acl_cache_window(&self) -> Option<Duration> {
+		match self.args.arg_secretstore_acl_cache_window {
+			0 => None,
+			secs => Some(Duration::from_secs(secs)),
+		}
+	}
+
+	fn secretstore_master_key(&self) -> Result<Option<SecretStoreMasterKeySource>, String> {
+		match (self.args.arg_secretstore_master_password.clone(), self.args.arg_secretstore_master_key_file.clone()) {
+			(Some(_), Some(_)) => Err("--secretstore-master-password and --secretstore-master-key-file are mutually exclusive".into()),
+			(Some(password_file), None) => Ok(Some(SecretStoreMasterKeySource::PasswordFile(password_file))),
+			(None, Some(key_file)) => Ok(Some(SecretStoreMasterKeySource::KeyFile(key_file))),
+			(None, None) => Ok(None),
+		}
+	}
+
 	fn secretstore_nodes(&self) -> Result<BTreeMap<Public, (String, u16)>, String> {
 		let mut nodes = BTreeMap::new();
 		for node in self.args.arg_secretstore_nodes.split(',').filter(|n| n != &"") {
@@ -1135,6 +1247,10 @@ impl Configuration {
 		::whisper::Config {
 			enabled: self.args.flag_whisper,
 			target_message_pool_size: self.args.arg_whisper_pool_size * 1024 * 1024,
+			pow_requirement: self.args.arg_whisper_min_pow,
+			topic_filtering: self.args.flag_whisper_topic_filter,
+			mail_server_path: self.args.arg_whisper_mail_server_path.as_ref()
+				.map(|path| replace_home(&self.directories().base, path)),
 		}
 	}
 }
@@ -1155,6 +1271,7 @@ mod tests {
 
 	use tempdir::TempDir;
 	use ethcore::client::{VMType, BlockId};
+	use ethcore::ethstore::KdfAlgorithm;
 	use ethcore::miner::MinerOptions;
 	use miner::pool::PrioritizationStrategy;
 	use parity_rpc::NetworkSettings;
@@ -1199,10 +1316,26 @@ mod tests {
 		let args = vec!["parity", "account", "new"];
 		let conf = parse(&args);
 		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Account(AccountCmd::New(NewAccount {
+			kdf: KdfAlgorithm::Pbkdf2,
 			iterations: 10240,
 			path: Directories::default().keys,
 			password_file: None,
 			spec: SpecType::default(),
+			mnemonic: false,
+		})));
+	}
+
+	#[test]
+	fn test_command_account_new_mnemonic() {
+		let args = vec!["parity", "account", "new", "--mnemonic"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Account(AccountCmd::New(NewAccount {
+			kdf: KdfAlgorithm::Pbkdf2,
+			iterations: 10240,
+			path: Directories::default().keys,
+			password_file: None,
+			spec: SpecType::default(),
+			mnemonic: true,
 		})));
 	}
 
@@ -1334,6 +1467,32 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_secretstore_export() {
+		let args = vec!["parity", "secretstore", "export", "--transfer-password", "transfer.pwd", "--secretstore-master-password", "master.pwd", "shares.json"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::SecretStore(SecretStoreCmd::Export {
+			data_path: Directories::default().secretstore,
+			master_key: Some(SecretStoreMasterKeySource::PasswordFile("master.pwd".into())),
+			transfer_password_file: "transfer.pwd".into(),
+			file_path: Some("shares.json".into()),
+		}));
+	}
+
+	#[test]
+	fn test_command_secretstore_reencrypt() {
+		let args = vec!["parity", "secretstore", "reencrypt", "--new-master-key-file", "master.key"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::SecretStore(SecretStoreCmd::Reencrypt {
+			data_path: Directories::default().secretstore,
+			master_key: None,
+			new_master_key: SecretStoreMasterKeySource::KeyFile("master.key".into()),
+		}));
+
+		let args = vec!["parity", "secretstore", "reencrypt"];
+		assert!(parse(&args).into_command().is_err());
+	}
+
 	#[test]
 	fn test_command_signer_new_token() {
 		let args = vec!["parity", "signer", "new-token"];
@@ -1390,6 +1549,7 @@ mod tests {
 			network_id: None,
 			warp_sync: true,
 			warp_barrier: None,
+			state_sync: false,
 			acc_conf: Default::default(),
 			gas_pricer_conf: Default::default(),
 			miner_extras: Default::default(),
@@ -1410,13 +1570,16 @@ mod tests {
 			geth_compatibility: false,
 			net_settings: Default::default(),
 			ipfs_conf: Default::default(),
+			metrics_conf: Default::default(),
 			secretstore_conf: Default::default(),
 			private_provider_conf: Default::default(),
 			private_encryptor_conf: Default::default(),
+			private_local_encryptor_conf: None,
 			private_tx_enabled: false,
 			name: "".into(),
 			custom_bootnodes: false,
 			fat_db: Default::default(),
+			tracing_address_index: false,
 			snapshot_conf: Default::default(),
 			stratum: None,
 			check_seal: true,
@@ -1780,6 +1943,7 @@ mod tests {
 		assert_eq!(conf0.secretstore_config().unwrap().port, 8084);
 		assert_eq!(conf0.secretstore_config().unwrap().http_port, 8083);
 		assert_eq!(conf0.ipfs_config().port, 5002);
+		assert_eq!(conf0.metrics_config().port, 3001);
 		assert_eq!(conf0.stratum_options().unwrap().unwrap().port, 8009);
 
 		assert_eq!(conf1.net_addresses().unwrap().0.port(), 30304);
@@ -1792,6 +1956,27 @@ mod tests {
 		assert_eq!(conf1.ipfs_config().port, 5002);
 	}
 
+	#[test]
+	fn should_parse_private_validator_keys() {
+		let key = "a5d7b0e5a9c1f4b3b0a6a1d8c5e2f9b4a7d0c3e6f9b2a5d8c1e4f7a0b3d6c9e2f5a8b1d4c7e0f3a6b9d2c5e8f1a4b7d0c3e6f9b2a5d8c1e4f7a0b3d6c9e2f5a8";
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--private-validator-keys", &format!("0x{}", key)]);
+		let conf2 = parse(&["parity", "--private-validator-keys", key, "--private-sstore-url", "http://localhost:8082"]);
+
+		assert_eq!(conf0.private_local_encryptor_config(), Ok(None));
+		assert_eq!(conf1.private_local_encryptor_config().unwrap().unwrap().validators, vec![key.parse().unwrap()]);
+		assert!(conf2.private_local_encryptor_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_secretstore_acl_cache_window() {
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--secretstore-acl-cache-window", "600"]);
+
+		assert_eq!(conf0.secretstore_config().unwrap().acl_cache_window, None);
+		assert_eq!(conf1.secretstore_config().unwrap().acl_cache_window, Some(Duration::from_secs(600)));
+	}
+
 	#[test]
 	fn should_expose_all_servers() {
 		// given
@@ -1920,4 +2105,20 @@ mod tests {
 			_ => panic!("Should be Cmd::Run"),
 		}
 	}
+
+	#[test]
+	fn should_parse_whisper_config() {
+		let conf0 = parse(&["parity", "--whisper"]);
+		let conf1 = parse(&["parity", "--whisper", "--whisper-min-pow=0.2", "--whisper-topic-filter",
+			"--whisper-mail-server-path=/tmp/whisper-mail"]);
+
+		assert_eq!(conf0.whisper_config(), ::whisper::Config { enabled: true, ..Default::default() });
+		assert_eq!(conf1.whisper_config(), ::whisper::Config {
+			enabled: true,
+			target_message_pool_size: 10 * 1024 * 1024,
+			pow_requirement: 0.2,
+			topic_filtering: true,
+			mail_server_path: Some("/tmp/whisper-mail".into()),
+		});
+	}
 }
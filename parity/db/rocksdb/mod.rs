@@ -66,6 +66,22 @@ pub fn open_secretstore_db(data_path: &str) -> Result<Arc<KeyValueDB>, String> {
 	Ok(Arc::new(Database::open_default(&db_path).map_err(|e| format!("Error opening database: {:?}", e))?))
 }
 
+/// Open secret store ACL permissions cache DB using the given secret store data path.
+#[cfg(feature = "secretstore")]
+pub fn open_secretstore_acl_cache_db(data_path: &str) -> Result<Arc<KeyValueDB>, String> {
+	use std::path::PathBuf;
+
+	let mut db_path = PathBuf::from(data_path);
+	db_path.push("acl_cache");
+	let db_path = db_path.to_str().ok_or_else(|| "Invalid secretstore path".to_string())?;
+	Ok(Arc::new(Database::open_default(&db_path).map_err(|e| format!("Error opening database: {:?}", e))?))
+}
+
+/// Open whisper mail server DB at the given path.
+pub fn open_whisper_mail_server_db(path: &str) -> Result<Arc<KeyValueDB>, String> {
+	Ok(Arc::new(Database::open_default(path).map_err(|e| format!("Error opening database: {:?}", e))?))
+}
+
 /// Create a restoration db handler using the config generated by `client_path` and `client_config`.
 pub fn restoration_db_handler(client_path: &Path, client_config: &ClientConfig) -> Box<BlockChainDBHandler> {
 	let client_db_config = helpers::client_db_config(client_path, client_config);
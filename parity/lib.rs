@@ -71,6 +71,7 @@ extern crate node_filter;
 extern crate keccak_hash as hash;
 extern crate journaldb;
 extern crate registrar;
+extern crate stats;
 
 #[macro_use]
 extern crate log as rlog;
@@ -96,6 +97,7 @@ mod deprecated;
 mod helpers;
 mod informant;
 mod light_helpers;
+mod metrics;
 mod modules;
 mod params;
 mod presale;
@@ -206,6 +208,7 @@ fn execute<Cr, Rr>(command: Execute, on_client_rq: Cr, on_updater_rq: Rr) -> Res
 		Cmd::SignerReject { id, port, authfile } => rpc_cli::signer_reject(id, port, authfile).map(|s| ExecutionAction::Instant(Some(s))),
 		Cmd::Snapshot(snapshot_cmd) => snapshot::execute(snapshot_cmd).map(|s| ExecutionAction::Instant(Some(s))),
 		Cmd::ExportHardcodedSync(export_hs_cmd) => export_hardcoded_sync::execute(export_hs_cmd).map(|s| ExecutionAction::Instant(Some(s))),
+		Cmd::SecretStore(secretstore_cmd) => secretstore::execute(secretstore_cmd).map(|s| ExecutionAction::Instant(Some(s))),
 	}
 }
 
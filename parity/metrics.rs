@@ -0,0 +1,228 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus metrics endpoint.
+
+use std::fs;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ethcore::client::Client;
+use futures::{self, future, Future};
+use parking_lot::Mutex;
+use parity_rpc::hyper::{self, server, Method, StatusCode};
+use parity_rpc::hyper::header::ContentType;
+use parity_rpc::informant::RpcStats;
+use stats::{PrometheusMetrics, PrometheusRegistry};
+use sync::{SyncProvider, ManageNetwork};
+
+/// Prefix of all exported metric names.
+const METRICS_PREFIX: &'static str = "parity_";
+/// How long the measured size of the database is reused.
+const DB_SIZE_REFRESH: Duration = Duration::from_secs(60);
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Configuration {
+	pub enabled: bool,
+	pub port: u16,
+	pub interface: String,
+}
+
+impl Default for Configuration {
+	fn default() -> Self {
+		Configuration {
+			enabled: false,
+			port: 3000,
+			interface: "127.0.0.1".into(),
+		}
+	}
+}
+
+/// Components exporting metrics.
+pub struct Dependencies {
+	pub client: Arc<Client>,
+	pub sync: Arc<SyncProvider>,
+	pub net: Arc<ManageNetwork>,
+	pub rpc_stats: Arc<RpcStats>,
+	pub db_path: PathBuf,
+}
+
+impl PrometheusMetrics for Dependencies {
+	fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+		self.client.prometheus_metrics(r);
+		self.rpc_stats.prometheus_metrics(r);
+
+		let status = self.sync.status();
+		r.register_gauge("sync_start_block", "Best block when the sync started", status.start_block_number as f64);
+		r.register_gauge("sync_last_imported_block", "Last downloaded and imported block", status.last_imported_block_number.unwrap_or(0) as f64);
+		r.register_gauge("sync_highest_block", "Highest block in the download queue", status.highest_block_number.unwrap_or(0) as f64);
+		r.register_gauge("sync_last_imported_old_block", "Last downloaded and imported ancient block", status.last_imported_old_block_number.unwrap_or(0) as f64);
+		r.register_gauge("sync_blocks_target", "Total number of blocks for the sync process", status.blocks_total as f64);
+		r.register_gauge("sync_blocks_received", "Blocks downloaded so far", status.blocks_received as f64);
+		r.register_gauge("sync_snapshot_chunks", "Chunks of the snapshot being restored", status.num_snapshot_chunks as f64);
+		r.register_gauge("sync_snapshot_chunks_done", "Restored chunks of the snapshot", status.snapshot_chunks_done as f64);
+		r.register_gauge("sync_snapshot_syncing", "Whether a snapshot is being restored", if status.is_snapshot_syncing() { 1.0 } else { 0.0 });
+		r.register_gauge("sync_active_peers", "Peers the sync is downloading from", status.num_active_peers as f64);
+		r.register_gauge_with_labels("cache_bytes", "Heap memory used by caches", &[("cache", "sync")], status.mem_used as f64);
+
+		let peers_range = self.net.num_peers_range();
+		r.register_gauge("net_peers", "Connected peers", status.num_peers as f64);
+		r.register_gauge("net_peers_min", "Configured minimal number of peers", peers_range.start as f64);
+		r.register_gauge("net_peers_max", "Configured maximal number of peers", peers_range.end.saturating_sub(1) as f64);
+
+		let (mut eth, mut pip) = (0, 0);
+		for peer in self.sync.peers() {
+			if peer.eth_info.is_some() { eth += 1; }
+			if peer.pip_info.is_some() { pip += 1; }
+		}
+		r.register_gauge_with_labels("net_protocol_peers", "Connected peers by protocol", &[("protocol", "eth")], eth as f64);
+		r.register_gauge_with_labels("net_protocol_peers", "Connected peers by protocol", &[("protocol", "pip")], pip as f64);
+	}
+}
+
+/// Exported metrics, including the ones that are too expensive to be read on every scrape.
+struct Metrics {
+	deps: Dependencies,
+	db_size: Mutex<Option<(Instant, u64)>>,
+}
+
+impl Metrics {
+	fn db_size(&self) -> u64 {
+		let mut db_size = self.db_size.lock();
+		match *db_size {
+			Some((measured_at, size)) if measured_at.elapsed() < DB_SIZE_REFRESH => size,
+			_ => {
+				let size = dir_size(&self.deps.db_path);
+				*db_size = Some((Instant::now(), size));
+				size
+			},
+		}
+	}
+}
+
+impl PrometheusMetrics for Metrics {
+	fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+		self.deps.prometheus_metrics(r);
+
+		// the bundled `kvdb-rocksdb` doesn't expose RocksDB properties, so the size of its files is measured
+		// at most once per `DB_SIZE_REFRESH` instead.
+		r.register_gauge("db_size_bytes", "Size of the database files on disk", self.db_size() as f64);
+	}
+}
+
+fn dir_size(path: &Path) -> u64 {
+	let entries = match fs::read_dir(path) {
+		Ok(entries) => entries,
+		Err(_) => return 0,
+	};
+
+	entries.filter_map(Result::ok).map(|entry| match entry.metadata() {
+		Ok(ref metadata) if metadata.is_dir() => dir_size(&entry.path()),
+		Ok(metadata) => metadata.len(),
+		Err(_) => 0,
+	}).sum()
+}
+
+struct Handler {
+	metrics: Arc<Metrics>,
+}
+
+impl server::Service for Handler {
+	type Request = hyper::Request;
+	type Response = hyper::Response;
+	type Error = hyper::Error;
+	type Future = future::FutureResult<hyper::Response, hyper::Error>;
+
+	fn call(&self, request: Self::Request) -> Self::Future {
+		let response = match (request.method(), request.uri().path()) {
+			(&Method::Get, "/metrics") => {
+				let mut registry = PrometheusRegistry::new(METRICS_PREFIX);
+				self.metrics.prometheus_metrics(&mut registry);
+				hyper::Response::new()
+					.with_status(StatusCode::Ok)
+					.with_header(ContentType("text/plain; version=0.0.4".parse().expect("valid mime type; qed")))
+					.with_body(registry.render())
+			},
+			_ => hyper::Response::new()
+				.with_status(StatusCode::NotFound)
+				.with_header(ContentType::plaintext())
+				.with_body("Not found"),
+		};
+
+		future::ok(response)
+	}
+}
+
+/// Running metrics server. Stops the server when dropped.
+pub struct Listening {
+	close: Option<futures::sync::oneshot::Sender<()>>,
+	thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Drop for Listening {
+	fn drop(&mut self) {
+		if let Some(close) = self.close.take() {
+			let _ = close.send(());
+		}
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+pub fn start_server(conf: Configuration, deps: Dependencies) -> Result<Option<Listening>, String> {
+	if !conf.enabled {
+		return Ok(None);
+	}
+
+	let ip: IpAddr = conf.interface.parse().map_err(|_| format!("Invalid metrics interface: {}", conf.interface))?;
+	let addr = SocketAddr::new(ip, conf.port);
+	let metrics = Arc::new(Metrics {
+		deps,
+		db_size: Mutex::new(None),
+	});
+
+	let (close, shutdown_signal) = futures::sync::oneshot::channel::<()>();
+	let (tx, rx) = mpsc::sync_channel(1);
+	let thread = thread::spawn(move || {
+		let send = |res| tx.send(res).expect("rx end is never dropped; qed");
+		let server = match server::Http::new().bind(&addr, move || Ok(Handler { metrics: metrics.clone() })) {
+			Ok(server) => {
+				send(Ok(()));
+				server
+			},
+			Err(err) => {
+				send(Err(err));
+				return;
+			}
+		};
+
+		let _ = server.run_until(shutdown_signal.map_err(|_| {}));
+	});
+
+	// Wait for server to start successfuly.
+	rx.recv().expect("tx end is never dropped; qed")
+		.map_err(|e| format!("Metrics server error: {} ({})", e, addr))?;
+	info!("Metrics server listening on {}", addr);
+
+	Ok(Some(Listening {
+		close: Some(close),
+		thread: Some(thread),
+	}))
+}
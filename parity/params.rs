@@ -19,6 +19,7 @@ use std::time::Duration;
 
 use ethcore::client::Mode;
 use ethcore::ethereum;
+use ethcore::ethstore::KdfAlgorithm;
 use ethcore::spec::{Spec, SpecParams};
 use ethereum_types::{U256, Address};
 use futures_cpupool::CpuPool;
@@ -214,6 +215,7 @@ impl str::FromStr for ResealPolicy {
 
 #[derive(Debug, PartialEq)]
 pub struct AccountsConfig {
+	pub kdf: KdfAlgorithm,
 	pub iterations: u32,
 	pub refresh_time: u64,
 	pub testnet: bool,
@@ -226,6 +228,7 @@ pub struct AccountsConfig {
 impl Default for AccountsConfig {
 	fn default() -> Self {
 		AccountsConfig {
+			kdf: KdfAlgorithm::Pbkdf2,
 			iterations: 10240,
 			refresh_time: 5,
 			testnet: false,
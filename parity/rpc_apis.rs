@@ -308,8 +308,12 @@ impl FullDependencies {
 					if !for_generic_pubsub {
 						let client = EthPubSubClient::new(self.client.clone(), self.remote.clone());
 						let h = client.handler();
-						self.miner.add_transactions_listener(Box::new(move |hashes| if let Some(h) = h.upgrade() {
-							h.notify_new_transactions(hashes);
+						self.miner.add_transactions_listener(Box::new(move |transactions| if let Some(h) = h.upgrade() {
+							h.notify_pending_transactions(transactions);
+						}));
+						let h = client.handler();
+						self.miner.add_dropped_transactions_listener(Box::new(move |dropped| if let Some(h) = h.upgrade() {
+							h.notify_dropped_transactions(dropped);
 						}));
 
 						if let Some(h) = client.handler().upgrade() {
@@ -579,7 +583,10 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 					).to_delegate())
 				},
 				Api::Traces => {
-					handler.extend_with(light::TracesClient.to_delegate())
+					handler.extend_with(light::TracesClient::new(
+						Arc::new(dispatcher.clone()),
+						self.gas_price_percentile,
+					).to_delegate())
 				},
 				Api::Rpc => {
 					let modules = to_modules(&apis);
@@ -45,7 +45,7 @@ use parity_reactor::EventLoop;
 use parity_rpc::{Origin, Metadata, NetworkSettings, informant, is_major_importing};
 use updater::{UpdatePolicy, Updater};
 use parity_version::version;
-use ethcore_private_tx::{ProviderConfig, EncryptorConfig, SecretStoreEncryptor};
+use ethcore_private_tx::{ProviderConfig, EncryptorConfig, LocalEncryptorConfig, Encryptor, SecretStoreEncryptor, LocalEncryptor};
 use params::{
 	SpecType, Pruning, AccountsConfig, GasPricerConfig, MinerExtras, Switch,
 	tracing_switch_to_bool, fatdb_switch_to_bool, mode_switch_to_bool
@@ -56,6 +56,7 @@ use dir::{Directories, DatabaseDirectories};
 use cache::CacheConfig;
 use user_defaults::UserDefaults;
 use ipfs;
+use metrics;
 use jsonrpc_core;
 use modules;
 use registrar::{RegistrarClient, Asynchronous};
@@ -106,6 +107,7 @@ pub struct RunCmd {
 	pub network_id: Option<u64>,
 	pub warp_sync: bool,
 	pub warp_barrier: Option<u64>,
+	pub state_sync: bool,
 	pub acc_conf: AccountsConfig,
 	pub gas_pricer_conf: GasPricerConfig,
 	pub miner_extras: MinerExtras,
@@ -113,14 +115,17 @@ pub struct RunCmd {
 	pub mode: Option<Mode>,
 	pub tracing: Switch,
 	pub fat_db: Switch,
+	pub tracing_address_index: bool,
 	pub compaction: DatabaseCompactionProfile,
 	pub vm_type: VMType,
 	pub geth_compatibility: bool,
 	pub net_settings: NetworkSettings,
 	pub ipfs_conf: ipfs::Configuration,
+	pub metrics_conf: metrics::Configuration,
 	pub secretstore_conf: secretstore::Configuration,
 	pub private_provider_conf: ProviderConfig,
 	pub private_encryptor_conf: EncryptorConfig,
+	pub private_local_encryptor_conf: Option<LocalEncryptorConfig>,
 	pub private_tx_enabled: bool,
 	pub name: String,
 	pub custom_bootnodes: bool,
@@ -248,7 +253,7 @@ fn execute_light_impl(cmd: RunCmd, logger: Arc<RotatingLogger>) -> Result<Runnin
 
 	let mut attached_protos = Vec::new();
 	let whisper_factory = if cmd.whisper.enabled {
-		let whisper_factory = ::whisper::setup(cmd.whisper.target_message_pool_size, &mut attached_protos)
+		let whisper_factory = ::whisper::setup(&cmd.whisper, &mut attached_protos)
 			.map_err(|e| format!("Failed to initialize whisper: {}", e))?;
 		whisper_factory
 	} else {
@@ -450,6 +455,8 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	}
 
 	sync_config.fork_block = spec.fork_block();
+	sync_config.fork_transitions = spec.fork_transitions();
+	sync_config.eip1559_transition = spec.params().eip1559_transition;
 	let mut warp_sync = spec.engine.supports_warp() && cmd.warp_sync;
 	if warp_sync {
 		// Logging is not initialized yet, so we print directly to stderr
@@ -469,6 +476,7 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 		(true, _) => sync::WarpSync::Enabled,
 		_ => sync::WarpSync::Disabled,
 	};
+	sync_config.state_sync = warp_sync && cmd.state_sync && spec.engine.supports_state_sync();
 	sync_config.download_old_blocks = cmd.download_old_blocks;
 	sync_config.serve_light = cmd.serve_light;
 
@@ -546,6 +554,7 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	client_config.queue.verifier_settings = cmd.verifier_settings;
 	client_config.transaction_verification_queue_size = ::std::cmp::max(2048, txpool_size / 4);
 	client_config.snapshot = cmd.snapshot_conf.clone();
+	client_config.tracing.address_index = cmd.tracing_address_index;
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
@@ -560,6 +569,12 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	let client_db = restoration_db_handler.open(&client_path)
 		.map_err(|e| format!("Failed to open database {:?}", e))?;
 
+	// create private transactions encryptor.
+	let private_encryptor: Box<Encryptor> = match cmd.private_local_encryptor_conf {
+		Some(conf) => Box::new(LocalEncryptor::new(conf).map_err(|e| e.to_string())?),
+		None => Box::new(SecretStoreEncryptor::new(cmd.private_encryptor_conf, fetch.clone()).map_err(|e| e.to_string())?),
+	};
+
 	// create client service.
 	let service = ClientService::start(
 		client_config,
@@ -570,7 +585,7 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 		&cmd.dirs.ipc_path(),
 		miner.clone(),
 		account_provider.clone(),
-		Box::new(SecretStoreEncryptor::new(cmd.private_encryptor_conf, fetch.clone()).map_err(|e| e.to_string())?),
+		private_encryptor,
 		cmd.private_provider_conf,
 	).map_err(|e| format!("Client service error: {:?}", e))?;
 
@@ -639,7 +654,7 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	let mut attached_protos = Vec::new();
 
 	let whisper_factory = if cmd.whisper.enabled {
-		let whisper_factory = ::whisper::setup(cmd.whisper.target_message_pool_size, &mut attached_protos)
+		let whisper_factory = ::whisper::setup(&cmd.whisper, &mut attached_protos)
 			.map_err(|e| format!("Failed to initialize whisper: {}", e))?;
 
 		whisper_factory
@@ -762,6 +777,16 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	// the ipfs server
 	let ipfs_server = ipfs::start_server(cmd.ipfs_conf.clone(), client.clone())?;
 
+	// the metrics server
+	let metrics_deps = metrics::Dependencies {
+		client: client.clone(),
+		sync: sync_provider.clone(),
+		net: manage_network.clone(),
+		rpc_stats: rpc_stats.clone(),
+		db_path: client_path.clone(),
+	};
+	let metrics_server = metrics::start_server(cmd.metrics_conf.clone(), metrics_deps)?;
+
 	// the informant
 	let informant = Arc::new(Informant::new(
 		FullNodeInformantData {
@@ -820,7 +845,7 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 			informant,
 			client,
 			client_service: Arc::new(service),
-			keep_alive: Box::new((watcher, updater, ws_server, http_server, ipc_server, secretstore_key_server, ipfs_server, event_loop)),
+			keep_alive: Box::new((watcher, updater, ws_server, http_server, ipc_server, secretstore_key_server, ipfs_server, metrics_server, event_loop)),
 		}
 	})
 }
@@ -966,7 +991,7 @@ fn prepare_account_provider(spec: &SpecType, dirs: &Directories, data_dir: &str,
 		},
 	};
 
-	let ethstore = EthStore::open_with_iterations(dir, cfg.iterations).map_err(|e| format!("Could not open keys directory: {}", e))?;
+	let ethstore = EthStore::open_with_kdf(dir, cfg.kdf, cfg.iterations).map_err(|e| format!("Could not open keys directory: {}", e))?;
 	if cfg.refresh_time > 0 {
 		ethstore.set_refresh_time(::std::time::Duration::from_secs(cfg.refresh_time));
 	}
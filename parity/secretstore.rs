@@ -16,6 +16,7 @@
 
 use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::Duration;
 use dir::default_data_path;
 use dir::helpers::replace_home;
 use ethcore::account_provider::AccountProvider;
@@ -34,6 +35,15 @@ pub enum NodeSecretKey {
 	KeyStore(Address),
 }
 
+/// Source of the master key, used to encrypt stored key shares.
+#[derive(Debug, PartialEq, Clone)]
+pub enum MasterKeySource {
+	/// Master key is derived from the password, read from the file.
+	PasswordFile(String),
+	/// Hex-encoded master key is read from the file.
+	KeyFile(String),
+}
+
 /// Secret store service contract address.
 #[derive(Debug, PartialEq, Clone)]
 pub enum ContractAddress {
@@ -82,6 +92,46 @@ pub struct Configuration {
 	pub data_path: String,
 	/// Administrator public key.
 	pub admin_public: Option<Public>,
+	/// Master key source. If None, key shares are stored unencrypted.
+	pub master_key: Option<MasterKeySource>,
+	/// Time window, within which cached ACL permissions are used. If None, permissions are not cached.
+	pub acl_cache_window: Option<Duration>,
+}
+
+/// Offline key shares management command.
+#[derive(Debug, PartialEq)]
+pub enum SecretStoreCmd {
+	/// Export key shares.
+	Export {
+		/// Data directory path for secret store.
+		data_path: String,
+		/// Master key source.
+		master_key: Option<MasterKeySource>,
+		/// File containing transfer password.
+		transfer_password_file: String,
+		/// Exported file path. If None, shares are printed to stdout.
+		file_path: Option<String>,
+	},
+	/// Import key shares.
+	Import {
+		/// Data directory path for secret store.
+		data_path: String,
+		/// Master key source.
+		master_key: Option<MasterKeySource>,
+		/// File containing transfer password.
+		transfer_password_file: String,
+		/// Imported file path. If None, shares are read from stdin.
+		file_path: Option<String>,
+	},
+	/// Re-encrypt key shares with new master key.
+	Reencrypt {
+		/// Data directory path for secret store.
+		data_path: String,
+		/// Master key source.
+		master_key: Option<MasterKeySource>,
+		/// New master key source.
+		new_master_key: MasterKeySource,
+	},
 }
 
 /// Secret store dependencies
@@ -100,7 +150,7 @@ pub struct Dependencies<'a> {
 
 #[cfg(not(feature = "secretstore"))]
 mod server {
-	use super::{Configuration, Dependencies};
+	use super::{Configuration, Dependencies, SecretStoreCmd};
 
 	/// Noop key server implementation
 	pub struct KeyServer;
@@ -111,16 +161,24 @@ mod server {
 			Ok(KeyServer)
 		}
 	}
+
+	/// Execute key shares management command
+	pub fn execute(_cmd: SecretStoreCmd) -> Result<String, String> {
+		Err("Parity is compiled without Secret Store support".into())
+	}
 }
 
 #[cfg(feature = "secretstore")]
 mod server {
+	use std::fs::File;
+	use std::io::{self, Read, Write};
 	use std::sync::Arc;
 	use ethcore_secretstore;
-	use ethkey::KeyPair;
+	use ethkey::{KeyPair, Secret};
 	use ansi_term::Colour::{Red, White};
 	use db;
-	use super::{Configuration, Dependencies, NodeSecretKey, ContractAddress};
+	use helpers::password_from_file;
+	use super::{Configuration, Dependencies, NodeSecretKey, ContractAddress, MasterKeySource, SecretStoreCmd};
 
 	fn into_service_contract_address(address: ContractAddress) -> ethcore_secretstore::ContractAddress {
 		match address {
@@ -129,6 +187,29 @@ mod server {
 		}
 	}
 
+	fn into_master_key(source: Option<MasterKeySource>) -> Result<Option<ethcore_secretstore::MasterKey>, String> {
+		let source = match source {
+			Some(source) => source,
+			None => return Ok(None),
+		};
+
+		match source {
+			MasterKeySource::PasswordFile(path) => password_from_file(path)
+				.map(ethcore_secretstore::MasterKey::Passphrase)
+				.map(Some),
+			MasterKeySource::KeyFile(path) => {
+				let mut key = String::new();
+				File::open(&path)
+					.and_then(|mut file| file.read_to_string(&mut key))
+					.map_err(|e| format!("{} Unable to read master key file: {}", path, e))?;
+				key.trim().trim_left_matches("0x").parse::<Secret>()
+					.map(ethcore_secretstore::MasterKey::Raw)
+					.map(Some)
+					.map_err(|e| format!("{} Invalid master key: {}", path, e))
+			},
+		}
+	}
+
 	/// Key server
 	pub struct KeyServer {
 		_key_server: Box<ethcore_secretstore::KeyServer>,
@@ -167,6 +248,7 @@ mod server {
 			}
 
 			let key_server_name = format!("{}:{}", conf.interface, conf.port);
+			let master_key = into_master_key(conf.master_key.take())?;
 			let mut cconf = ethcore_secretstore::ServiceConfiguration {
 				listener_address: if conf.http_enabled { Some(ethcore_secretstore::NodeAddress {
 					address: conf.http_interface.clone(),
@@ -178,6 +260,8 @@ mod server {
 				service_contract_doc_store_address: conf.service_contract_doc_store_address.map(into_service_contract_address),
 				service_contract_doc_sretr_address: conf.service_contract_doc_sretr_address.map(into_service_contract_address),
 				acl_check_contract_address: conf.acl_check_contract_address.map(into_service_contract_address),
+				acl_cache_window: conf.acl_cache_window,
+				master_key: master_key,
 				cluster_config: ethcore_secretstore::ClusterConfiguration {
 					threads: 4,
 					listener_address: ethcore_secretstore::NodeAddress {
@@ -198,7 +282,8 @@ mod server {
 			cconf.cluster_config.nodes.insert(self_secret.public().clone(), cconf.cluster_config.listener_address.clone());
 
 			let db = db::open_secretstore_db(&conf.data_path)?;
-			let key_server = ethcore_secretstore::start(deps.client, deps.sync, deps.miner, self_secret, cconf, db)
+			let acl_cache_db = db::open_secretstore_acl_cache_db(&conf.data_path)?;
+			let key_server = ethcore_secretstore::start(deps.client, deps.sync, deps.miner, self_secret, cconf, db, acl_cache_db)
 				.map_err(|e| format!("Error starting KeyServer {}: {}", key_server_name, e))?;
 
 			Ok(KeyServer {
@@ -206,9 +291,57 @@ mod server {
 			})
 		}
 	}
+
+	/// Execute key shares management command
+	pub fn execute(cmd: SecretStoreCmd) -> Result<String, String> {
+		match cmd {
+			SecretStoreCmd::Export { data_path, master_key, transfer_password_file, file_path } => {
+				let master_key = into_master_key(master_key)?;
+				let transfer_password = password_from_file(transfer_password_file)?;
+				let db = db::open_secretstore_db(&data_path)?;
+				let exported = ethcore_secretstore::export_key_shares(db, master_key.as_ref(), &transfer_password)
+					.map_err(|e| format!("Error exporting key shares: {}", e))?;
+
+				match file_path {
+					Some(file_path) => {
+						File::create(&file_path)
+							.and_then(|mut file| file.write_all(&exported))
+							.map_err(|e| format!("Cannot write to file {}: {}", file_path, e))?;
+						Ok(format!("Key shares exported to {}", file_path))
+					},
+					None => String::from_utf8(exported).map_err(|e| format!("Error exporting key shares: {}", e)),
+				}
+			},
+			SecretStoreCmd::Import { data_path, master_key, transfer_password_file, file_path } => {
+				let master_key = into_master_key(master_key)?;
+				let transfer_password = password_from_file(transfer_password_file)?;
+				let mut data = Vec::new();
+				match file_path {
+					Some(file_path) => File::open(&file_path)
+						.and_then(|mut file| file.read_to_end(&mut data))
+						.map_err(|e| format!("Cannot read file {}: {}", file_path, e))?,
+					None => io::stdin().read_to_end(&mut data)
+						.map_err(|e| format!("Cannot read from stdin: {}", e))?,
+				};
+
+				let db = db::open_secretstore_db(&data_path)?;
+				let imported = ethcore_secretstore::import_key_shares(db, master_key.as_ref(), &data, &transfer_password)
+					.map_err(|e| format!("Error importing key shares: {}", e))?;
+				Ok(format!("{} key shares imported", imported))
+			},
+			SecretStoreCmd::Reencrypt { data_path, master_key, new_master_key } => {
+				let master_key = into_master_key(master_key)?;
+				let new_master_key = into_master_key(Some(new_master_key))?.expect("source is given; qed");
+				let db = db::open_secretstore_db(&data_path)?;
+				let reencrypted = ethcore_secretstore::reencrypt_key_shares(db, master_key.as_ref(), &new_master_key)
+					.map_err(|e| format!("Error re-encrypting key shares: {}", e))?;
+				Ok(format!("{} key shares re-encrypted", reencrypted))
+			},
+		}
+	}
 }
 
-pub use self::server::KeyServer;
+pub use self::server::{KeyServer, execute};
 use ethkey::Password;
 
 impl Default for Configuration {
@@ -226,6 +359,8 @@ impl Default for Configuration {
 			service_contract_doc_sretr_address: None,
 			self_secret: None,
 			admin_public: None,
+			master_key: None,
+			acl_cache_window: None,
 			nodes: BTreeMap::new(),
 			key_server_set_contract_address: Some(ContractAddress::Registry),
 			interface: "127.0.0.1".to_owned(),
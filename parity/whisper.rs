@@ -17,17 +17,25 @@
 use std::sync::Arc;
 use std::io;
 
+use network::NodeId;
 use sync::{AttachedProtocol, ManageNetwork};
 use parity_rpc::Metadata;
+use parity_whisper::mail_server::MailRequest;
 use parity_whisper::message::Message;
 use parity_whisper::net::{self as whisper_net, Network as WhisperNetwork};
 use parity_whisper::rpc::{WhisperClient, PoolHandle, FilterManager};
 
 /// Whisper config.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq)]
 pub struct Config {
 	pub enabled: bool,
 	pub target_message_pool_size: usize,
+	/// Minimum PoW required of messages sent by peers.
+	pub pow_requirement: f64,
+	/// Only receive messages matching the topics of local filters.
+	pub topic_filtering: bool,
+	/// Database path of the mail server, if enabled.
+	pub mail_server_path: Option<String>,
 }
 
 impl Default for Config {
@@ -35,6 +43,9 @@ impl Default for Config {
 		Config {
 			enabled: false,
 			target_message_pool_size: 10 * 1024 * 1024,
+			pow_requirement: 0f64,
+			topic_filtering: false,
+			mail_server_path: None,
 		}
 	}
 }
@@ -62,6 +73,17 @@ impl PoolHandle for NetPoolHandle {
 	fn pool_status(&self) -> whisper_net::PoolStatus {
 		self.handle.pool_status()
 	}
+
+	fn request_messages(&self, peer: NodeId, request: MailRequest) -> bool {
+		let mut res = false;
+		let mut request = Some(request);
+		self.net.with_proto_context(whisper_net::PROTOCOL_ID, &mut |ctx| {
+			if let Some(request) = request.take() {
+				res = self.handle.request_messages(&peer, request, ctx);
+			}
+		});
+		res
+	}
 }
 
 /// Factory for standard whisper RPC.
@@ -79,13 +101,23 @@ impl RpcFactory {
 
 /// Sets up whisper protocol and RPC handler.
 ///
-/// Will target the given pool size.
+/// Will target the configured pool size.
 #[cfg(not(feature = "ipc"))]
-pub fn setup(target_pool_size: usize, protos: &mut Vec<AttachedProtocol>)
+pub fn setup(config: &Config, protos: &mut Vec<AttachedProtocol>)
 	-> io::Result<Option<RpcFactory>>
 {
 	let manager = Arc::new(FilterManager::new()?);
-	let net = Arc::new(WhisperNetwork::new(target_pool_size, manager.clone()));
+	let mut net = WhisperNetwork::new(config.target_message_pool_size, manager.clone())
+		.with_pow_requirement(config.pow_requirement)
+		.with_topic_filtering(config.topic_filtering);
+
+	if let Some(ref path) = config.mail_server_path {
+		let db = ::db::open_whisper_mail_server_db(path)
+			.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+		net = net.with_mail_server(::parity_whisper::mail_server::MailServer::new(db, None));
+	}
+
+	let net = Arc::new(net);
 
 	protos.push(AttachedProtocol {
 		handler: net.clone() as Arc<_>,
@@ -107,7 +139,7 @@ pub fn setup(target_pool_size: usize, protos: &mut Vec<AttachedProtocol>)
 
 // TODO: make it possible to attach generic protocols in IPC.
 #[cfg(feature = "ipc")]
-pub fn setup(_target_pool_size: usize, _protos: &mut Vec<AttachedProtocol>)
+pub fn setup(_config: &Config, _protos: &mut Vec<AttachedProtocol>)
 	-> io::Result<Option<RpcFactory>>
 {
 	Ok(None)
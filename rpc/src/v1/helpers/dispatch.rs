@@ -43,7 +43,7 @@ use transaction::{Action, SignedTransaction, PendingTransaction, Transaction, Er
 use jsonrpc_core::{BoxFuture, Result, Error};
 use jsonrpc_core::futures::{future, Future, Poll, Async};
 use jsonrpc_core::futures::future::Either;
-use v1::helpers::{errors, nonce, TransactionRequest, FilledTransactionRequest, ConfirmationPayload};
+use v1::helpers::{errors, eip712, nonce, TransactionRequest, FilledTransactionRequest, ConfirmationPayload};
 use v1::types::{
 	H256 as RpcH256, H520 as RpcH520, Bytes as RpcBytes,
 	RichRawTransaction as RpcRichRawTransaction,
@@ -51,6 +51,7 @@ use v1::types::{
 	ConfirmationResponse,
 	SignRequest as RpcSignRequest,
 	DecryptRequest as RpcDecryptRequest,
+	EIP712SignRequest as RpcEIP712SignRequest,
 };
 use rlp;
 
@@ -693,6 +694,29 @@ pub fn execute<D: Dispatcher + 'static>(
 				);
 			Box::new(future::done(res))
 		},
+		ConfirmationPayload::EIP712SignMessage(address, data) => {
+			let hashes = match eip712::hash_typed_data(&data) {
+				Ok(hashes) => hashes,
+				Err(e) => return Box::new(future::err(e)),
+			};
+			if accounts.is_hardware_address(&address) {
+				let signature = accounts.sign_typed_data_with_hardware(&address, &hashes.domain, &hashes.message)
+					.map(|s| H520(s.into_electrum()))
+					.map(RpcH520::from)
+					.map(ConfirmationResponse::Signature)
+					.map(WithToken::No)
+					.map_err(|e| errors::account("Error signing typed data with hardware wallet", e));
+
+				return Box::new(future::done(signature));
+			}
+			let res = signature(&accounts, address, hashes.digest(), pass)
+				.map(|result| result
+					.map(|rsv| H520(rsv.into_electrum()))
+					.map(RpcH520::from)
+					.map(ConfirmationResponse::Signature)
+				);
+			Box::new(future::done(res))
+		},
 		ConfirmationPayload::Decrypt(address, data) => {
 			if accounts.is_hardware_address(&address) {
 				return Box::new(future::err(errors::unsupported("Decrypting via hardware wallets is not supported.", None)));
@@ -758,6 +782,14 @@ pub fn default_gas_price<C, M>(client: &C, miner: &M, percentile: usize) -> U256
 	client.gas_price_corpus(100).percentile(percentile).cloned().unwrap_or_else(|| miner.sensible_gas_price())
 }
 
+/// Extract the default priority fee per gas from a client and miner.
+pub fn default_max_priority_fee_per_gas<C, M>(client: &C, miner: &M, percentile: usize) -> U256 where
+	C: BlockChainClient,
+	M: MinerService,
+{
+	client.priority_fee_corpus(100).percentile(percentile).cloned().unwrap_or_else(|| miner.sensible_gas_price())
+}
+
 /// Convert RPC confirmation payload to signer confirmation payload.
 /// May need to resolve in the future to fetch things like gas price.
 pub fn from_rpc<D>(payload: RpcConfirmationPayload, default_account: Address, dispatcher: &D) -> BoxFuture<ConfirmationPayload>
@@ -778,5 +810,10 @@ pub fn from_rpc<D>(payload: RpcConfirmationPayload, default_account: Address, di
 		RpcConfirmationPayload::EthSignMessage(RpcSignRequest { address, data }) => {
 			Box::new(future::ok(ConfirmationPayload::EthSignMessage(address.into(), data.into())))
 		},
+		RpcConfirmationPayload::EIP712SignMessage(RpcEIP712SignRequest { address, data }) => {
+			// reject malformed typed data before it reaches the signer queue
+			Box::new(future::done(eip712::hash_typed_data(&data)
+				.map(|_| ConfirmationPayload::EIP712SignMessage(address.into(), data))))
+		},
 	}
 }
@@ -0,0 +1,424 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EIP-712 typed structured data validation and hashing.
+//! https://github.com/ethereum/EIPs/blob/master/EIPS/eip-712.md
+
+use std::collections::{BTreeMap, BTreeSet};
+use ethereum_types::{H256, U256};
+use hash::keccak;
+use jsonrpc_core::Error;
+use rustc_hex::FromHex;
+use serde_json::Value;
+
+use v1::helpers::errors;
+use v1::types::{EIP712, EIP712Field};
+
+/// Name of the signing domain type.
+pub const DOMAIN_TYPE: &'static str = "EIP712Domain";
+
+/// Fields allowed in `EIP712Domain` along with their types.
+const DOMAIN_FIELDS: [(&'static str, &'static str); 5] = [
+	("name", "string"),
+	("version", "string"),
+	("chainId", "uint256"),
+	("verifyingContract", "address"),
+	("salt", "bytes32"),
+];
+
+/// Hashes of validated typed data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedDataHashes {
+	/// Domain separator, i.e. `hashStruct(domain)`
+	pub domain: H256,
+	/// `hashStruct(message)`
+	pub message: H256,
+}
+
+impl TypedDataHashes {
+	/// Hash to be signed: `keccak256("\x19\x01" ‖ domainSeparator ‖ hashStruct(message))`.
+	pub fn digest(&self) -> H256 {
+		let mut data = Vec::with_capacity(2 + 32 + 32);
+		data.extend_from_slice(b"\x19\x01");
+		data.extend_from_slice(&self.domain);
+		data.extend_from_slice(&self.message);
+		keccak(data)
+	}
+}
+
+/// Validate typed data against its schema and compute domain separator and message hash.
+pub fn hash_typed_data(data: &EIP712) -> Result<TypedDataHashes, Error> {
+	let schema = Schema::new(&data.types, &data.primary_type)
+		.map_err(|e| errors::invalid_params("typed data", e))?;
+
+	Ok(TypedDataHashes {
+		domain: schema.hash_struct(DOMAIN_TYPE, &data.domain)
+			.map_err(|e| errors::invalid_params("typed data domain", e))?,
+		message: schema.hash_struct(&data.primary_type, &data.message)
+			.map_err(|e| errors::invalid_params("typed data message", e))?,
+	})
+}
+
+#[derive(Debug, PartialEq)]
+enum Kind<'a> {
+	Address,
+	Bool,
+	String,
+	Bytes,
+	FixedBytes(usize),
+	Uint(usize),
+	Int(usize),
+	Struct(&'a str),
+	Array(Box<Kind<'a>>, Option<usize>),
+}
+
+impl<'a> Kind<'a> {
+	fn parse(types: &'a BTreeMap<String, Vec<EIP712Field>>, ty: &'a str) -> Result<Self, String> {
+		if ty.ends_with(']') {
+			let open = ty.rfind('[').ok_or_else(|| format!("Invalid type: {}", ty))?;
+			let len = match &ty[open + 1..ty.len() - 1] {
+				"" => None,
+				len => Some(len.parse().map_err(|_| format!("Invalid array length: {}", ty))?),
+			};
+			return Ok(Kind::Array(Box::new(Kind::parse(types, &ty[..open])?), len));
+		}
+
+		let sized = |prefix: &str| -> Option<usize> {
+			match ty.starts_with(prefix) {
+				true => ty[prefix.len()..].parse().ok(),
+				false => None,
+			}
+		};
+
+		Ok(match ty {
+			"address" => Kind::Address,
+			"bool" => Kind::Bool,
+			"string" => Kind::String,
+			"bytes" => Kind::Bytes,
+			_ if types.contains_key(ty) => Kind::Struct(ty),
+			_ => match (sized("bytes"), sized("uint"), sized("int")) {
+				(Some(len), _, _) if len >= 1 && len <= 32 => Kind::FixedBytes(len),
+				(_, Some(bits), _) if bits % 8 == 0 && bits >= 8 && bits <= 256 => Kind::Uint(bits),
+				(_, _, Some(bits)) if bits % 8 == 0 && bits >= 8 && bits <= 256 => Kind::Int(bits),
+				_ => return Err(format!("Unknown type: {}", ty)),
+			},
+		})
+	}
+
+	fn struct_name(&self) -> Option<&'a str> {
+		match *self {
+			Kind::Struct(name) => Some(name),
+			Kind::Array(ref inner, _) => inner.struct_name(),
+			_ => None,
+		}
+	}
+}
+
+struct Schema<'a> {
+	types: &'a BTreeMap<String, Vec<EIP712Field>>,
+	fields: BTreeMap<&'a str, Vec<(&'a str, Kind<'a>)>>,
+}
+
+impl<'a> Schema<'a> {
+	fn new(types: &'a BTreeMap<String, Vec<EIP712Field>>, primary_type: &str) -> Result<Self, String> {
+		let mut fields = BTreeMap::new();
+		for (name, members) in types {
+			if !is_identifier(name) || Kind::parse(&BTreeMap::new(), name).is_ok() {
+				return Err(format!("Invalid type name: {}", name));
+			}
+
+			let mut names = BTreeSet::new();
+			let mut kinds = Vec::with_capacity(members.len());
+			for member in members {
+				if !is_identifier(&member.name) || !names.insert(&member.name) {
+					return Err(format!("Invalid or duplicated member name: {}.{}", name, member.name));
+				}
+				kinds.push((member.name.as_str(), Kind::parse(types, &member.type_)?));
+			}
+			fields.insert(name.as_str(), kinds);
+		}
+
+		let domain = types.get(DOMAIN_TYPE).ok_or_else(|| format!("Missing {} type", DOMAIN_TYPE))?;
+		for member in domain {
+			if !DOMAIN_FIELDS.iter().any(|&(name, ty)| name == member.name && ty == member.type_) {
+				return Err(format!("Invalid {} member: {} {}", DOMAIN_TYPE, member.type_, member.name));
+			}
+		}
+
+		if !types.contains_key(primary_type) {
+			return Err(format!("Unknown primary type: {}", primary_type));
+		}
+
+		Ok(Schema {
+			types: types,
+			fields: fields,
+		})
+	}
+
+	/// `encodeType`: the type itself followed by its dependencies sorted by name.
+	fn encode_type(&self, name: &str) -> String {
+		let mut deps = BTreeSet::new();
+		self.dependencies(name, &mut deps);
+		deps.remove(name);
+
+		::std::iter::once(name).chain(deps.into_iter())
+			.map(|ty| {
+				let members = self.types[ty].iter()
+					.map(|member| format!("{} {}", member.type_, member.name))
+					.collect::<Vec<_>>()
+					.join(",");
+				format!("{}({})", ty, members)
+			})
+			.collect()
+	}
+
+	fn dependencies<'b>(&'b self, name: &'b str, deps: &mut BTreeSet<&'b str>) {
+		if !deps.insert(name) {
+			return;
+		}
+		for &(_, ref kind) in &self.fields[name] {
+			if let Some(dep) = kind.struct_name() {
+				self.dependencies(dep, deps);
+			}
+		}
+	}
+
+	fn hash_struct(&self, name: &str, value: &Value) -> Result<H256, String> {
+		let object = value.as_object().ok_or_else(|| format!("Expected {} object", name))?;
+		let fields = &self.fields[name];
+		if let Some(unknown) = object.keys().find(|key| !fields.iter().any(|&(field, _)| field == key.as_str())) {
+			return Err(format!("Unknown member: {}.{}", name, unknown));
+		}
+
+		let mut data = Vec::with_capacity(32 * (fields.len() + 1));
+		data.extend_from_slice(&keccak(self.encode_type(name)));
+		for &(field, ref kind) in fields {
+			let value = object.get(field).ok_or_else(|| format!("Missing member: {}.{}", name, field))?;
+			let encoded = self.encode_value(kind, value).map_err(|e| format!("{}.{}: {}", name, field, e))?;
+			data.extend_from_slice(&encoded);
+		}
+		Ok(keccak(data))
+	}
+
+	fn encode_value(&self, kind: &Kind, value: &Value) -> Result<H256, String> {
+		match *kind {
+			Kind::Struct(name) => self.hash_struct(name, value),
+			Kind::Array(ref inner, len) => {
+				let items = value.as_array().ok_or("Expected array")?;
+				if len.map_or(false, |len| len != items.len()) {
+					return Err(format!("Expected {} items, got {}", len.unwrap_or_default(), items.len()));
+				}
+				let mut data = Vec::with_capacity(32 * items.len());
+				for item in items {
+					data.extend_from_slice(&self.encode_value(inner, item)?);
+				}
+				Ok(keccak(data))
+			},
+			Kind::Address => {
+				let bytes = parse_hex(value)?;
+				if bytes.len() != 20 {
+					return Err("Invalid address".into());
+				}
+				let mut result = H256::zero();
+				result[12..].copy_from_slice(&bytes);
+				Ok(result)
+			},
+			Kind::Bool => value.as_bool().map(|b| to_h256(U256::from(b as u8))).ok_or_else(|| "Expected bool".into()),
+			Kind::String => value.as_str().map(keccak).ok_or_else(|| "Expected string".into()),
+			Kind::Bytes => parse_hex(value).map(keccak),
+			Kind::FixedBytes(len) => {
+				let bytes = parse_hex(value)?;
+				if bytes.len() != len {
+					return Err(format!("Expected {} bytes, got {}", len, bytes.len()));
+				}
+				let mut result = H256::zero();
+				result[..len].copy_from_slice(&bytes);
+				Ok(result)
+			},
+			Kind::Uint(bits) => match parse_integer(value)? {
+				(false, value) if value.bits() <= bits => Ok(to_h256(value)),
+				_ => Err(format!("Value out of uint{} range", bits)),
+			},
+			Kind::Int(bits) => {
+				let bound = U256::one() << (bits - 1);
+				match parse_integer(value)? {
+					(false, value) if value < bound => Ok(to_h256(value)),
+					(true, value) if value <= bound => Ok(to_h256(value.overflowing_neg().0)),
+					_ => Err(format!("Value out of int{} range", bits)),
+				}
+			},
+		}
+	}
+}
+
+fn is_identifier(name: &str) -> bool {
+	let mut chars = name.chars();
+	match chars.next() {
+		Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$' => chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$'),
+		_ => false,
+	}
+}
+
+fn to_h256(value: U256) -> H256 {
+	let mut result = H256::zero();
+	value.to_big_endian(&mut result);
+	result
+}
+
+fn parse_hex(value: &Value) -> Result<Vec<u8>, String> {
+	match value.as_str() {
+		Some(s) if s.starts_with("0x") => s[2..].from_hex().map_err(|e| format!("Invalid hex: {}", e)),
+		_ => Err("Expected 0x-prefixed hex string".into()),
+	}
+}
+
+/// Parses JSON number, decimal string or 0x-prefixed hex string.
+/// Returns sign (`true` for negative values) and magnitude.
+fn parse_integer(value: &Value) -> Result<(bool, U256), String> {
+	if let Some(n) = value.as_u64() {
+		return Ok((false, n.into()));
+	}
+	if let Some(n) = value.as_i64() {
+		return Ok((true, U256::from(n.wrapping_neg() as u64)));
+	}
+
+	let s = value.as_str().ok_or("Expected integer")?;
+	let (negative, s) = match s.starts_with('-') {
+		true => (true, &s[1..]),
+		false => (false, s),
+	};
+	let magnitude = match s.starts_with("0x") {
+		true => {
+			let hex = if s.len() % 2 == 1 { format!("0{}", &s[2..]) } else { s[2..].to_owned() };
+			let bytes: Vec<u8> = hex.from_hex().map_err(|e| format!("Invalid hex: {}", e))?;
+			if bytes.is_empty() || bytes.len() > 32 {
+				return Err(format!("Invalid integer: {}", s));
+			}
+			U256::from(&bytes[..])
+		},
+		false => U256::from_dec_str(s).map_err(|_| format!("Invalid integer: {}", s))?,
+	};
+	Ok((negative && !magnitude.is_zero(), magnitude))
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use v1::types::EIP712;
+	use super::{hash_typed_data, Schema};
+
+	const MAIL: &'static str = r#"{
+		"types": {
+			"EIP712Domain": [
+				{ "name": "name", "type": "string" },
+				{ "name": "version", "type": "string" },
+				{ "name": "chainId", "type": "uint256" },
+				{ "name": "verifyingContract", "type": "address" }
+			],
+			"Person": [
+				{ "name": "name", "type": "string" },
+				{ "name": "wallet", "type": "address" }
+			],
+			"Mail": [
+				{ "name": "from", "type": "Person" },
+				{ "name": "to", "type": "Person" },
+				{ "name": "contents", "type": "string" }
+			]
+		},
+		"primaryType": "Mail",
+		"domain": {
+			"name": "Ether Mail",
+			"version": "1",
+			"chainId": 1,
+			"verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+		},
+		"message": {
+			"from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+			"to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+			"contents": "Hello, Bob!"
+		}
+	}"#;
+
+	fn typed_data(json: &str) -> EIP712 {
+		serde_json::from_str(json).unwrap()
+	}
+
+	#[test]
+	fn should_hash_example_from_eip() {
+		let data = typed_data(MAIL);
+		let schema = Schema::new(&data.types, &data.primary_type).unwrap();
+		assert_eq!(schema.encode_type("Mail"), "Mail(Person from,Person to,string contents)Person(string name,address wallet)");
+
+		let hashes = hash_typed_data(&data).unwrap();
+		assert_eq!(hashes.domain, "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f".into());
+		assert_eq!(hashes.message, "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e".into());
+		assert_eq!(hashes.digest(), "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2".into());
+	}
+
+	#[test]
+	fn should_accept_integer_encodings() {
+		let data = MAIL.replace(r#""chainId": 1"#, r#""chainId": "0x1""#);
+		assert_eq!(hash_typed_data(&typed_data(&data)).unwrap(), hash_typed_data(&typed_data(MAIL)).unwrap());
+
+		let data = MAIL.replace(r#""chainId": 1"#, r#""chainId": "1""#);
+		assert_eq!(hash_typed_data(&typed_data(&data)).unwrap(), hash_typed_data(&typed_data(MAIL)).unwrap());
+	}
+
+	#[test]
+	fn should_reject_invalid_typed_data() {
+		let invalid = vec![
+			// missing domain type
+			MAIL.replace("EIP712Domain", "Domain"),
+			// unknown primary type
+			MAIL.replace(r#""primaryType": "Mail""#, r#""primaryType": "Letter""#),
+			// unknown member type
+			MAIL.replace(r#""type": "Person" },"#, r#""type": "Persona" },"#),
+			// invalid domain member
+			MAIL.replace(r#""name": "chainId", "type": "uint256""#, r#""name": "chainId", "type": "string""#),
+			// value not matching its type
+			MAIL.replace(r#""chainId": 1"#, r#""chainId": "one""#),
+			MAIL.replace(r#""chainId": 1"#, r#""chainId": -1"#),
+			MAIL.replace("0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826", "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD8"),
+			// missing and unknown members
+			MAIL.replace(r#""contents": "Hello, Bob!""#, r#""content": "Hello, Bob!""#),
+		];
+
+		for data in invalid {
+			assert!(hash_typed_data(&typed_data(&data)).is_err(), "{}", data);
+		}
+	}
+
+	#[test]
+	fn should_encode_arrays_and_signed_integers() {
+		let data = r#"{
+			"types": {
+				"EIP712Domain": [{ "name": "name", "type": "string" }],
+				"Batch": [
+					{ "name": "values", "type": "int8[2]" },
+					{ "name": "flags", "type": "bool[]" },
+					{ "name": "tag", "type": "bytes4" }
+				]
+			},
+			"primaryType": "Batch",
+			"domain": { "name": "Test" },
+			"message": { "values": [-128, 127], "flags": [true, false], "tag": "0x01020304" }
+		}"#;
+		assert!(hash_typed_data(&typed_data(data)).is_ok());
+		assert!(hash_typed_data(&typed_data(&data.replace("-128", "-129"))).is_err());
+		assert!(hash_typed_data(&typed_data(&data.replace("127]", "127, 1]"))).is_err());
+		assert!(hash_typed_data(&typed_data(&data.replace("0x01020304", "0x010203"))).is_err());
+	}
+}
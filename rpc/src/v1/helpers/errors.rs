@@ -343,6 +343,8 @@ pub fn transaction_message(error: &TransactionError) -> String {
 		NotAllowed => "Transaction is not permitted.".into(),
 		TooBig => "Transaction is too big, see chain specification for the limit.".into(),
 		InvalidRlp(ref descr) => format!("Invalid RLP data: {}", descr),
+		TransactionTypeNotEnabled => "Transaction type is not enabled yet, see chain specification for the transition block.".into(),
+		PriorityFeeHigherThanFeeCap => "Max priority fee per gas cannot be higher than max fee per gas.".into(),
 	}
 }
 
@@ -417,6 +419,14 @@ pub fn unknown_block() -> Error {
 	}
 }
 
+pub fn insufficient_replay_proof() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::EXECUTION_ERROR),
+		message: "Proofs fetched from light peers don't cover all the state needed to replay the transactions.".into(),
+		data: None,
+	}
+}
+
 pub fn no_light_peers() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::NO_LIGHT_PEERS),
@@ -21,10 +21,12 @@ use std::sync::Arc;
 
 use light::on_demand::error::Error as OnDemandError;
 use ethcore::basic_account::BasicAccount;
+use ethcore::client::CallAnalytics;
 use ethcore::encoded;
 use ethcore::filter::Filter as EthcoreFilter;
 use ethcore::ids::BlockId;
 use ethcore::receipt::Receipt;
+use ethcore::state::{self, ProvedExecution};
 
 use jsonrpc_core::{Result, Error};
 use jsonrpc_core::futures::{future, Future};
@@ -190,8 +192,8 @@ impl LightFetch {
 		}))
 	}
 
-	/// Helper for getting proved execution.
-	pub fn proved_read_only_execution(&self, req: CallRequest, num: Trailing<BlockNumber>) -> impl Future<Item = ExecutionResult, Error = Error> + Send {
+	/// Helper for getting proved execution, traced as requested by `analytics`.
+	pub fn proved_read_only_execution(&self, req: CallRequest, num: Trailing<BlockNumber>, analytics: CallAnalytics) -> impl Future<Item = ExecutionResult, Error = Error> + Send {
 		const DEFAULT_GAS_PRICE: u64 = 21_000;
 		// starting gas when gas not provided.
 		const START_GAS: u64 = 50_000;
@@ -199,19 +201,7 @@ impl LightFetch {
 		let (sync, on_demand, client) = (self.sync.clone(), self.on_demand.clone(), self.client.clone());
 		let req: CallRequestHelper = req.into();
 
-		// Note: Here we treat `Pending` as `Latest`.
-		//       Since light clients don't produce pending blocks
-		//       (they don't have state) we can safely fallback to `Latest`.
-		let id = match num.unwrap_or_default() {
-			BlockNumber::Num(n) => BlockId::Number(n),
-			BlockNumber::Earliest => BlockId::Earliest,
-			BlockNumber::Latest => BlockId::Latest,
-			BlockNumber::Pending => {
-				warn!("`Pending` is deprecated and may be removed in future versions. Falling back to `Latest`");
-				BlockId::Latest
-			}
-		};
-
+		let id = execution_block_id(num);
 		let from = req.from.unwrap_or_else(|| Address::zero());
 		let nonce_fut = match req.nonce {
 			Some(nonce) => Either::A(future::ok(Some(nonce))),
@@ -262,12 +252,122 @@ impl LightFetch {
 				hdr,
 				env_info,
 				engine: client.engine().clone(),
+				analytics,
 				on_demand,
 				sync,
 			}))
 		}))
 	}
 
+	/// Helper for getting proved execution of a signed transaction, traced as requested by `analytics`.
+	pub fn proved_transaction_execution(&self, tx: SignedTransaction, num: Trailing<BlockNumber>, analytics: CallAnalytics) -> impl Future<Item = ExecutionResult, Error = Error> + Send {
+		let (sync, on_demand, client) = (self.sync.clone(), self.on_demand.clone(), self.client.clone());
+
+		self.header(execution_block_id(num)).and_then(move |hdr| {
+			let env_info = match client.env_info(BlockId::Hash(hdr.hash())) {
+				Some(env_info) => env_info,
+				_ => return Either::A(future::err(errors::unknown_block())),
+			};
+
+			Either::B(execute_read_only_tx(true, ExecuteParams {
+				from: tx.sender(),
+				tx: tx.as_unsigned().clone(),
+				hdr,
+				env_info,
+				engine: client.engine().clone(),
+				analytics,
+				on_demand,
+				sync,
+			}))
+		})
+	}
+
+	/// Replay the transactions of a block on the state of its parent, each traced as requested by
+	/// `analytics`. Only the first `count` transactions are replayed if given.
+	///
+	/// Light servers only prove executions on the state at the end of a block, so every transaction
+	/// is proved on its own against the parent state and the proofs are merged before replaying the
+	/// transactions in sequence. A replay needing state outside of the merged proof fails.
+	pub fn replay_block_transactions(&self, id: BlockId, count: Option<usize>, analytics: CallAnalytics)
+		-> impl Future<Item = Vec<(H256, ExecutionResult)>, Error = Error> + Send
+	{
+		let fetcher: Self = self.clone();
+
+		self.block(id).and_then(move |block| {
+			let env_info = match fetcher.client.env_info(BlockId::Hash(block.hash())) {
+				Some(env_info) => env_info,
+				_ => return Either::A(future::err(errors::unknown_block())),
+			};
+
+			let transactions = block.transactions().into_iter()
+				.take(count.unwrap_or(usize::max_value()))
+				.map(|tx| SignedTransaction::new(tx).map_err(errors::transaction))
+				.collect::<Result<Vec<_>>>();
+			let transactions = match transactions {
+				Ok(transactions) => transactions,
+				Err(e) => return Either::A(future::err(e)),
+			};
+
+			let proofs_fetcher = fetcher.clone();
+			let proofs = fetcher.header(BlockId::Hash(block.parent_hash())).and_then(move |parent| {
+				let engine = proofs_fetcher.client.engine().clone();
+				let reqs: Vec<OnDemandRequest> = transactions.iter().map(|tx| request::ExecutionProof(request::TransactionProof {
+					tx: tx.clone(),
+					header: parent.clone().into(),
+					env_info: env_info.clone(),
+					engine: engine.clone(),
+					analytics: Default::default(),
+				}).into()).collect();
+
+				proofs_fetcher.send_requests(reqs, |res| res.into_iter().flat_map(|res| match res {
+					OnDemandResponse::ExecutionProof(items) => items,
+					_ => panic!(WRONG_RESPONSE_AMOUNT_TYPE_PROOF),
+				}).collect::<Vec<_>>()).map(move |items| (parent, env_info, engine, transactions, items))
+			});
+
+			Either::B(proofs.and_then(move |(parent, env_info, engine, transactions, items)| {
+				let hashes = transactions.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
+				let transactions = transactions.into_iter().map(|tx| (tx, analytics)).collect::<Vec<_>>();
+				let results = state::check_proof_many(&items, parent.state_root(), &transactions, engine.machine(), &env_info);
+
+				let mut replayed = Vec::with_capacity(results.len());
+				for (hash, result) in hashes.into_iter().zip(results) {
+					match result {
+						ProvedExecution::Complete(executed) => replayed.push((hash, Ok(executed))),
+						ProvedExecution::Failed(e) => replayed.push((hash, Err(e))),
+						ProvedExecution::BadProof => return Err(errors::insufficient_replay_proof()),
+					}
+				}
+
+				Ok(replayed)
+			}))
+		})
+	}
+
+	/// Replay a transaction at its position within its block, traced as requested by `analytics`.
+	/// Returns `None` if the transaction isn't in the canonical chain.
+	pub fn replay_transaction(&self, tx_hash: H256, analytics: CallAnalytics)
+		-> impl Future<Item = Option<ExecutionResult>, Error = Error> + Send
+	{
+		let fetcher: Self = self.clone();
+
+		self.transaction_by_hash(tx_hash).and_then(move |tx| match tx {
+			Some((tx, index)) => {
+				let block_hash = match tx.block_hash {
+					Some(hash) => hash.into(),
+					None => return Either::A(future::ok(None)),
+				};
+
+				Either::B(fetcher.replay_block_transactions(BlockId::Hash(block_hash), Some(index + 1), analytics)
+					.map(move |mut replayed| match replayed.pop() {
+						Some((hash, result)) if hash == tx_hash => Some(result),
+						_ => None,
+					}))
+			},
+			None => Either::A(future::ok(None)),
+		})
+	}
+
 	/// Get a block itself. Fails on unknown block ID.
 	pub fn block(&self, id: BlockId) -> impl Future<Item = encoded::Block, Error = Error> + Send {
 		let mut reqs = Vec::new();
@@ -595,6 +695,21 @@ impl LightFetch {
 	}
 }
 
+// Note: Here we treat `Pending` as `Latest`.
+//       Since light clients don't produce pending blocks
+//       (they don't have state) we can safely fallback to `Latest`.
+fn execution_block_id(num: Trailing<BlockNumber>) -> BlockId {
+	match num.unwrap_or_default() {
+		BlockNumber::Num(n) => BlockId::Number(n),
+		BlockNumber::Earliest => BlockId::Earliest,
+		BlockNumber::Latest => BlockId::Latest,
+		BlockNumber::Pending => {
+			warn!("`Pending` is deprecated and may be removed in future versions. Falling back to `Latest`");
+			BlockId::Latest
+		}
+	}
+}
+
 #[derive(Clone)]
 struct ExecuteParams {
 	from: Address,
@@ -602,6 +717,7 @@ struct ExecuteParams {
 	hdr: encoded::Header,
 	env_info: ::vm::EnvInfo,
 	engine: Arc<::ethcore::engines::EthEngine>,
+	analytics: CallAnalytics,
 	on_demand: Arc<OnDemand>,
 	sync: Arc<LightSync>,
 }
@@ -641,6 +757,7 @@ fn execute_read_only_tx(gas_known: bool, params: ExecuteParams) -> impl Future<I
 			header: params.hdr.into(),
 			env_info: params.env_info,
 			engine: params.engine,
+			analytics: params.analytics,
 		};
 
 		let on_demand = params.on_demand;
@@ -19,6 +19,7 @@ pub mod errors;
 
 pub mod block_import;
 pub mod dispatch;
+pub mod eip712;
 pub mod fake_sign;
 pub mod ipfs;
 pub mod light_fetch;
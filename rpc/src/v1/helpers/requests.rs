@@ -17,7 +17,7 @@
 use ethereum_types::{U256, Address};
 use bytes::Bytes;
 
-use v1::types::{Origin, TransactionCondition};
+use v1::types::{Origin, TransactionCondition, EIP712};
 
 /// Transaction request coming from RPC
 #[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
@@ -119,6 +119,8 @@ pub enum ConfirmationPayload {
 	EthSignMessage(Address, Bytes),
 	/// Decrypt request
 	Decrypt(Address, Bytes),
+	/// Sign EIP-712 typed structured data.
+	EIP712SignMessage(Address, EIP712),
 }
 
 impl ConfirmationPayload {
@@ -128,6 +130,7 @@ impl ConfirmationPayload {
 			ConfirmationPayload::SignTransaction(ref request) => request.from,
 			ConfirmationPayload::EthSignMessage(ref address, _) => *address,
 			ConfirmationPayload::Decrypt(ref address, _) => *address,
+			ConfirmationPayload::EIP712SignMessage(ref address, _) => *address,
 		}
 	}
 }
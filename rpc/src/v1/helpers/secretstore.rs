@@ -23,6 +23,7 @@ use jsonrpc_core::Error;
 use v1::helpers::errors;
 use v1::types::{H256, H512, EncryptedDocumentKey};
 use tiny_keccak::Keccak;
+use ethcore_secretstore;
 
 /// Initialization vector length.
 const INIT_VEC_LEN: usize = 16;
@@ -102,6 +103,16 @@ pub fn ordered_servers_keccak(servers_set: BTreeSet<H512>) -> H256 {
 	servers_set_keccak_value.into()
 }
 
+/// Verify RFC 8032 Ed25519 signature of the message.
+pub fn verify_ed25519_signature(public: H256, message: &[u8], signature: &[u8]) -> Result<bool, Error> {
+	if signature.len() != 64 {
+		return Err(errors::invalid_params("signature", "invalid Ed25519 signature length"));
+	}
+
+	ethcore_secretstore::verify_ed25519_signature(&public.into(), message, signature)
+		.map_err(|_| errors::invalid_params("server_key_public", "invalid Ed25519 public key"))
+}
+
 fn into_document_key(key: Bytes) -> Result<Bytes, Error> {
 	// key is a previously distributely generated Public
 	if key.len() != 64 {
@@ -18,12 +18,46 @@
 
 use std::sync::Arc;
 
-use ethcore::client::BlockChainClient;
+use ethcore::client::{BlockChainClient, BlockId, TransactionId, StateClient, StateInfo, Call};
+use ethcore::trace::{DebugTracer, StructLogConfig};
 use transaction::LocalizedTransaction;
 
 use jsonrpc_core::Result;
+use jsonrpc_macros::Trailing;
+use v1::helpers::{errors, fake_sign};
 use v1::traits::Debug;
-use v1::types::{Block, Bytes, RichBlock, BlockTransactions, Transaction};
+use v1::types::{
+	Block, Bytes, RichBlock, BlockTransactions, Transaction, BlockNumber, CallRequest,
+	DebugTrace, TransactionDebugTrace, TraceConfig, StorageRange, AccountRange, H160, H256,
+};
+
+/// Maximal number of accounts returned by `debug_accountRange`.
+const MAX_ACCOUNT_RANGE: u64 = 256;
+/// Maximal number of storage slots returned by `debug_storageRangeAt`.
+const MAX_STORAGE_RANGE: u64 = 256;
+
+fn to_debug_tracer(config: TraceConfig) -> Result<DebugTracer> {
+	match config.tracer.as_ref().map(String::as_str) {
+		None => Ok(DebugTracer::StructLogs(StructLogConfig {
+			disable_memory: config.disable_memory,
+			disable_stack: config.disable_stack,
+			disable_storage: config.disable_storage,
+		})),
+		Some("callTracer") => Ok(DebugTracer::Call),
+		Some("prestateTracer") => Ok(DebugTracer::Prestate),
+		Some(tracer) => Err(errors::invalid_params("tracer", format!("Unsupported tracer: {}. Expected `callTracer` or `prestateTracer`.", tracer))),
+	}
+}
+
+fn to_block_id(number: BlockNumber) -> Result<BlockId> {
+	match number {
+		BlockNumber::Num(num) => Ok(BlockId::Number(num)),
+		BlockNumber::Earliest => Ok(BlockId::Earliest),
+		BlockNumber::Latest => Ok(BlockId::Latest),
+
+		BlockNumber::Pending => Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
+	}
+}
 
 /// Debug rpc implementation.
 pub struct DebugClient<C> {
@@ -39,7 +73,10 @@ impl<C> DebugClient<C> {
 	}
 }
 
-impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
+impl<C, S> Debug for DebugClient<C> where
+	S: StateInfo + 'static,
+	C: BlockChainClient + StateClient<State=S> + Call<State=S> + 'static
+{
 	fn bad_blocks(&self) -> Result<Vec<RichBlock>> {
 		fn cast<O, T: Copy + Into<O>>(t: &T) -> O {
 			(*t).into()
@@ -61,6 +98,7 @@ impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
 					number: Some(number.into()),
 					gas_used: cast(block.header.gas_used()),
 					gas_limit: cast(block.header.gas_limit()),
+					base_fee_per_gas: block.header.base_fee().map(Into::into),
 					logs_bloom: Some(cast(block.header.log_bloom())),
 					timestamp: block.header.timestamp().into(),
 					difficulty: cast(block.header.difficulty()),
@@ -89,6 +127,55 @@ impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
 			}
 		}).collect())
 	}
+
+	fn trace_transaction(&self, transaction_hash: H256, config: Trailing<TraceConfig>) -> Result<DebugTrace> {
+		let tracer = to_debug_tracer(config.unwrap_or_default())?;
+
+		self.client.debug_replay(TransactionId::Hash(transaction_hash.into()), tracer)
+			.map(DebugTrace::from)
+			.map_err(errors::call)
+	}
+
+	fn trace_call(&self, request: CallRequest, block: BlockNumber, config: Trailing<TraceConfig>) -> Result<DebugTrace> {
+		let tracer = to_debug_tracer(config.unwrap_or_default())?;
+		let signed = fake_sign::sign_call(CallRequest::into(request))?;
+		let id = to_block_id(block)?;
+
+		let mut state = self.client.state_at(id).ok_or(errors::state_pruned())?;
+		let header = self.client.block_header_decoded(id).ok_or(errors::state_pruned())?;
+
+		self.client.debug_call(&signed, tracer, &mut state, &header)
+			.map(DebugTrace::from)
+			.map_err(errors::call)
+	}
+
+	fn trace_block_by_number(&self, block: BlockNumber, config: Trailing<TraceConfig>) -> Result<Vec<TransactionDebugTrace>> {
+		let tracer = to_debug_tracer(config.unwrap_or_default())?;
+		let id = to_block_id(block)?;
+
+		self.client.debug_replay_block_transactions(id, tracer)
+			.map(|traces| traces.map(|(hash, trace)| TransactionDebugTrace {
+				transaction_hash: hash.into(),
+				result: trace.into(),
+			}).collect())
+			.map_err(errors::call)
+	}
+
+	fn storage_range_at(&self, block_hash: H256, tx_index: u64, address: H160, key_start: H256, max_result: u64) -> Result<StorageRange> {
+		let count = ::std::cmp::min(max_result, MAX_STORAGE_RANGE);
+		self.client.storage_range_at(BlockId::Hash(block_hash.into()), tx_index as usize, &address.into(), &key_start.into(), count)
+			.map(Into::into)
+			.map_err(errors::call)
+	}
+
+	fn account_range(&self, block: BlockNumber, start: H256, max_results: u64, no_code: bool, no_storage: bool, incompletes: bool) -> Result<AccountRange> {
+		let id = to_block_id(block)?;
+		let count = ::std::cmp::min(max_results, MAX_ACCOUNT_RANGE);
+
+		self.client.account_range_at(id, None, &start.into(), count, !no_code, !no_storage)
+			.map(|range| AccountRange::new(range, incompletes))
+			.map_err(errors::call)
+	}
 }
 
 fn serialize<T: ::serde::Serialize>(t: &T) -> String {
@@ -16,41 +16,45 @@
 
 //! Eth rpc implementation.
 
+use std::cmp;
 use std::thread;
 use std::time::{Instant, Duration, SystemTime, UNIX_EPOCH};
 use std::sync::Arc;
 
-use rlp::Rlp;
 use ethereum_types::{U256, H256, Address};
 use parking_lot::Mutex;
 
 use ethash::{self, SeedHashCompute};
 use ethcore::account_provider::AccountProvider;
-use ethcore::client::{BlockChainClient, BlockId, TransactionId, UncleId, StateOrBlock, StateClient, StateInfo, Call, EngineInfo};
+use ethcore::client::{BlockChainClient, BlockId, TransactionId, UncleId, StateOrBlock, StateClient, StateInfo, Call, EngineInfo, ProvingBlockChainClient};
 use ethcore::filter::Filter as EthcoreFilter;
-use ethcore::header::{BlockNumber as EthBlockNumber};
+use ethcore::header::{BlockNumber as EthBlockNumber, Header};
 use ethcore::miner::{self, MinerService};
+use ethcore::receipt::LocalizedReceipt;
 use ethcore::snapshot::SnapshotService;
 use ethcore::encoded;
 use sync::SyncProvider;
 use miner::external::ExternalMinerService;
-use transaction::{SignedTransaction, LocalizedTransaction};
+use transaction::{SignedTransaction, LocalizedTransaction, UnverifiedTransaction};
 
 use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_core::futures::future;
 use jsonrpc_macros::Trailing;
 
 use v1::helpers::{self, errors, limit_logs, fake_sign};
-use v1::helpers::dispatch::{FullDispatcher, default_gas_price};
+use v1::helpers::dispatch::{FullDispatcher, default_gas_price, default_max_priority_fee_per_gas};
 use v1::helpers::block_import::is_major_importing;
 use v1::traits::Eth;
 use v1::types::{
-	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
-	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
+	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo, AccountProof, StorageProof,
+	Transaction, CallRequest, FeeHistory, Index, Filter, Log, Receipt, Work,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256, block_number_to_id,
 };
 use v1::metadata::Metadata;
 
+/// Maximal number of blocks returned by `eth_feeHistory`.
+const MAX_FEE_HISTORY_BLOCKS: u64 = 1024;
+
 const EXTRA_INFO_PROOF: &str = "Object exists in blockchain (fetched earlier), extra_info is always available if object exists; qed";
 
 /// Eth RPC options
@@ -220,6 +224,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthClient<C, SN, S
 		match (block, difficulty) {
 			(Some(block), Some(total_difficulty)) => {
 				let view = block.header_view();
+				let header = block.decode_header(self.client.engine().params().eip1559_transition);
 				Ok(Some(RichBlock {
 					inner: Block {
 						hash: match is_pending {
@@ -240,6 +245,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthClient<C, SN, S
 						},
 						gas_used: view.gas_used().into(),
 						gas_limit: view.gas_limit().into(),
+						base_fee_per_gas: header.base_fee().map(Into::into),
 						logs_bloom: match is_pending {
 							true => None,
 							false => Some(view.log_bloom().into()),
@@ -247,7 +253,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthClient<C, SN, S
 						timestamp: view.timestamp().into(),
 						difficulty: view.difficulty().into(),
 						total_difficulty: Some(total_difficulty.into()),
-						seal_fields: view.seal().into_iter().map(Into::into).collect(),
+						seal_fields: header.seal().iter().cloned().map(Into::into).collect(),
 						uncles: block.uncle_hashes().into_iter().map(Into::into).collect(),
 						transactions: match include_txs {
 							true => BlockTransactions::Full(block.view().localized_transactions().into_iter().map(|t| Transaction::from_localized(t)).collect()),
@@ -345,7 +351,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthClient<C, SN, S
 				let uncle_id = UncleId { block: block_id, position };
 
 				let uncle = match client.uncle(uncle_id) {
-					Some(hdr) => match hdr.decode() {
+					Some(hdr) => match hdr.decode(client.engine().params().eip1559_transition) {
 						Ok(h) => h,
 						Err(e) => return Err(errors::decode(e))
 					},
@@ -381,6 +387,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthClient<C, SN, S
 				number: Some(uncle.number().into()),
 				gas_used: uncle.gas_used().clone().into(),
 				gas_limit: uncle.gas_limit().clone().into(),
+				base_fee_per_gas: uncle.base_fee().map(Into::into),
 				logs_bloom: Some(uncle.log_bloom().clone().into()),
 				timestamp: uncle.timestamp().into(),
 				difficulty: uncle.difficulty().clone().into(),
@@ -413,6 +420,84 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthClient<C, SN, S
 			}
 		}
 	}
+
+	fn collect_fee_history(&self, block_count: U256, newest_block: BlockNumber, reward_percentiles: Option<Vec<f64>>) -> Result<FeeHistory> {
+		if let Some(ref percentiles) = reward_percentiles {
+			let in_range = percentiles.iter().all(|p| *p >= 0.0 && *p <= 100.0);
+			let increasing = percentiles.windows(2).all(|w| w[0] <= w[1]);
+			if !in_range || !increasing {
+				return Err(errors::invalid_params("rewardPercentiles", "Expected increasing percentiles between 0 and 100."));
+			}
+		}
+
+		let newest_block = match newest_block {
+			BlockNumber::Pending => BlockId::Latest,
+			number => block_number_to_id(number),
+		};
+		let newest_block = self.client.block_number(newest_block).ok_or_else(errors::unknown_block)?;
+		let block_count = cmp::min(block_count, MAX_FEE_HISTORY_BLOCKS.into()).low_u64();
+		let oldest_block = (newest_block + 1).saturating_sub(block_count);
+		let eip1559_transition = self.client.engine().params().eip1559_transition;
+
+		let mut history = FeeHistory {
+			oldest_block: oldest_block.into(),
+			reward: reward_percentiles.as_ref().map(|_| Vec::new()),
+			..Default::default()
+		};
+		let mut newest_header = None;
+		for number in oldest_block..newest_block + 1 {
+			let block = self.client.block(BlockId::Number(number)).ok_or_else(errors::unknown_block)?;
+			let header = block.decode_header(eip1559_transition);
+
+			history.base_fee_per_gas.push(header.base_fee().unwrap_or_default().into());
+			history.gas_used_ratio.push(match header.gas_limit().low_u64() {
+				0 => 0.0,
+				gas_limit => header.gas_used().low_u64() as f64 / gas_limit as f64,
+			});
+			if let (Some(ref percentiles), Some(ref mut reward)) = (reward_percentiles.as_ref(), history.reward.as_mut()) {
+				let receipts = self.client.block_receipts(BlockId::Number(number)).unwrap_or_default();
+				reward.push(priority_fee_percentiles(&block.transactions(), &receipts, &header, percentiles));
+			}
+			newest_header = Some(header);
+		}
+
+		if let Some(header) = newest_header {
+			let next_base_fee = self.client.engine().machine().calc_base_fee(header.number() + 1, &header);
+			history.base_fee_per_gas.push(next_base_fee.unwrap_or_default().into());
+		}
+
+		Ok(history)
+	}
+}
+
+/// Effective priority fees paid at the given percentiles of gas used in a block.
+fn priority_fee_percentiles(
+	transactions: &[UnverifiedTransaction],
+	receipts: &[LocalizedReceipt],
+	header: &Header,
+	percentiles: &[f64],
+) -> Vec<RpcU256> {
+	if transactions.is_empty() || transactions.len() != receipts.len() {
+		return percentiles.iter().map(|_| RpcU256::default()).collect();
+	}
+
+	let mut fees = transactions.iter()
+		.zip(receipts)
+		.map(|(t, r)| (t.effective_priority_fee(header.base_fee()), r.gas_used.low_u64()))
+		.collect::<Vec<_>>();
+	fees.sort_by(|a, b| a.0.cmp(&b.0));
+
+	let gas_used = header.gas_used().low_u64() as f64;
+	let mut index = 0;
+	let mut cumulative_gas = fees[0].1;
+	percentiles.iter().map(|p| {
+		let threshold = gas_used * p / 100.0;
+		while (cumulative_gas as f64) < threshold && index + 1 < fees.len() {
+			index += 1;
+			cumulative_gas += fees[index].1;
+		}
+		fees[index].0.into()
+	}).collect()
 }
 
 pub fn pending_logs<M>(miner: &M, best_block: EthBlockNumber, filter: &EthcoreFilter) -> Vec<Log> where M: MinerService {
@@ -452,7 +537,7 @@ fn check_known<C>(client: &C, number: BlockNumber) -> Result<()> where C: BlockC
 const MAX_QUEUE_SIZE_TO_MINE_ON: usize = 4;	// because uncles go back 6.
 
 impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<C, SN, S, M, EM> where
-	C: miner::BlockChainClient + BlockChainClient + StateClient<State=T> + Call<State=T> + EngineInfo + 'static,
+	C: miner::BlockChainClient + BlockChainClient + StateClient<State=T> + Call<State=T> + EngineInfo + ProvingBlockChainClient + 'static,
 	SN: SnapshotService + 'static,
 	S: SyncProvider + 'static,
 	M: MinerService<State=T> + 'static,
@@ -521,6 +606,14 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 		Ok(RpcU256::from(default_gas_price(&*self.client, &*self.miner, self.options.gas_price_percentile)))
 	}
 
+	fn max_priority_fee_per_gas(&self) -> Result<RpcU256> {
+		Ok(RpcU256::from(default_max_priority_fee_per_gas(&*self.client, &*self.miner, self.options.gas_price_percentile)))
+	}
+
+	fn fee_history(&self, block_count: RpcU256, newest_block: BlockNumber, reward_percentiles: Trailing<Vec<f64>>) -> BoxFuture<FeeHistory> {
+		Box::new(future::done(self.collect_fee_history(block_count.into(), newest_block, reward_percentiles.into())))
+	}
+
 	fn accounts(&self) -> Result<Vec<RpcH160>> {
 		let accounts = self.accounts.accounts()
 			.map_err(|e| errors::account("Could not fetch accounts.", e))?;
@@ -560,6 +653,35 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 		Box::new(future::done(res))
 	}
 
+	fn proof(&self, address: RpcH160, storage_keys: Vec<RpcH256>, num: Trailing<BlockNumber>) -> BoxFuture<AccountProof> {
+		let num = num.unwrap_or_default();
+		if num == BlockNumber::Pending {
+			return Box::new(future::err(errors::invalid_params("block", "Proofs of the pending state are not supported.")));
+		}
+
+		try_bf!(check_known(&*self.client, num.clone()));
+		let account_address: Address = address.clone().into();
+		let keys: Vec<H256> = storage_keys.into_iter().map(Into::into).collect();
+		let res = match self.client.prove_account_storage(&account_address, &keys, block_number_to_id(num)) {
+			Some((account_proof, account, storage)) => Ok(AccountProof {
+				address,
+				account_proof: account_proof.into_iter().map(Into::into).collect(),
+				balance: account.balance.into(),
+				code_hash: account.code_hash.into(),
+				nonce: account.nonce.into(),
+				storage_hash: account.storage_root.into(),
+				storage_proof: keys.into_iter().zip(storage).map(|(key, (proof, value))| StorageProof {
+					key: key.into(),
+					value: U256::from(value).into(),
+					proof: proof.into_iter().map(Into::into).collect(),
+				}).collect(),
+			}),
+			None => Err(errors::state_pruned()),
+		};
+
+		Box::new(future::done(res))
+	}
+
 	fn transaction_count(&self, address: RpcH160, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256> {
 		let address: Address = RpcH160::into(address);
 
@@ -801,7 +923,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn send_raw_transaction(&self, raw: Bytes) -> Result<RpcH256> {
-		Rlp::new(&raw.into_vec()).as_val()
+		UnverifiedTransaction::decode_envelope(&raw.into_vec())
 			.map_err(errors::rlp)
 			.and_then(|tx| SignedTransaction::new(tx).map_err(errors::transaction))
 			.and_then(|signed_transaction| {
@@ -840,7 +962,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 			};
 
 			let state = try_bf!(self.client.state_at(id).ok_or(errors::state_pruned()));
-			let header = try_bf!(self.client.block_header(id).ok_or(errors::state_pruned()).and_then(|h| h.decode().map_err(errors::decode)));
+			let header = try_bf!(self.client.block_header_decoded(id).ok_or(errors::state_pruned()));
 
 			(state, header)
 		};
@@ -879,7 +1001,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 			};
 
 			let state = try_bf!(self.client.state_at(id).ok_or(errors::state_pruned()));
-			let header = try_bf!(self.client.block_header(id).ok_or(errors::state_pruned()).and_then(|h| h.decode().map_err(errors::decode)));
+			let header = try_bf!(self.client.block_header_decoded(id).ok_or(errors::state_pruned()));
 
 			(state, header)
 		};
@@ -30,15 +30,16 @@ use v1::helpers::{errors, limit_logs, Subscribers};
 use v1::helpers::light_fetch::LightFetch;
 use v1::metadata::Metadata;
 use v1::traits::EthPubSub;
-use v1::types::{pubsub, RichHeader, Log};
+use v1::types::{pubsub, RichHeader, Log, Transaction};
 
-use ethcore::encoded;
+use ethcore::header::Header;
 use ethcore::filter::Filter as EthFilter;
 use ethcore::client::{BlockChainClient, ChainNotify, ChainRoute, ChainRouteType, BlockId};
 use sync::LightSync;
 use light::cache::Cache;
 use light::on_demand::OnDemand;
 use light::client::{LightChainClient, LightChainNotify};
+use miner::pool::{VerifiedTransaction, DroppedTransaction};
 use parity_reactor::Remote;
 use ethereum_types::H256;
 use bytes::Bytes;
@@ -52,6 +53,8 @@ pub struct EthPubSubClient<C> {
 	heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
 	logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
 	transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	full_transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	dropped_transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
 }
 
 impl<C> EthPubSubClient<C> {
@@ -60,6 +63,8 @@ impl<C> EthPubSubClient<C> {
 		let heads_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		let logs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		let transactions_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let full_transactions_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let dropped_transactions_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 
 		EthPubSubClient {
 			handler: Arc::new(ChainNotificationHandler {
@@ -68,10 +73,14 @@ impl<C> EthPubSubClient<C> {
 				heads_subscribers: heads_subscribers.clone(),
 				logs_subscribers: logs_subscribers.clone(),
 				transactions_subscribers: transactions_subscribers.clone(),
+				full_transactions_subscribers: full_transactions_subscribers.clone(),
+				dropped_transactions_subscribers: dropped_transactions_subscribers.clone(),
 			}),
 			heads_subscribers,
 			logs_subscribers,
 			transactions_subscribers,
+			full_transactions_subscribers,
+			dropped_transactions_subscribers,
 		}
 	}
 
@@ -82,6 +91,8 @@ impl<C> EthPubSubClient<C> {
 		*client.heads_subscribers.write() = Subscribers::new_test();
 		*client.logs_subscribers.write() = Subscribers::new_test();
 		*client.transactions_subscribers.write() = Subscribers::new_test();
+		*client.full_transactions_subscribers.write() = Subscribers::new_test();
+		*client.dropped_transactions_subscribers.write() = Subscribers::new_test();
 		client
 	}
 
@@ -119,6 +130,8 @@ pub struct ChainNotificationHandler<C> {
 	heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
 	logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
 	transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	full_transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	dropped_transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
 }
 
 impl<C> ChainNotificationHandler<C> {
@@ -130,7 +143,7 @@ impl<C> ChainNotificationHandler<C> {
 		);
 	}
 
-	fn notify_heads(&self, headers: &[(encoded::Header, BTreeMap<String, String>)]) {
+	fn notify_heads(&self, headers: &[(Header, BTreeMap<String, String>)]) {
 		for subscriber in self.heads_subscribers.read().values() {
 			for &(ref header, ref extra_info) in headers {
 				Self::notify(&self.remote, subscriber, pubsub::Result::Header(RichHeader {
@@ -182,20 +195,52 @@ impl<C> ChainNotificationHandler<C> {
 			}
 		}
 	}
+
+	/// Notify all subscribers about new pending transactions.
+	/// Hash subscribers receive transaction hashes, full subscribers the transaction objects.
+	pub fn notify_pending_transactions(&self, transactions: &[Arc<VerifiedTransaction>]) {
+		let hashes = transactions.iter().map(|tx| *tx.hash()).collect::<Vec<_>>();
+		self.notify_new_transactions(&hashes);
+
+		let subscribers = self.full_transactions_subscribers.read();
+		if subscribers.is_empty() {
+			return;
+		}
+
+		let transactions = transactions
+			.iter()
+			.map(|tx| Transaction::from_signed(tx.signed().clone()))
+			.collect::<Vec<_>>();
+		for subscriber in subscribers.values() {
+			for transaction in &transactions {
+				Self::notify(&self.remote, subscriber, pubsub::Result::FullTransaction(transaction.clone()));
+			}
+		}
+	}
+
+	/// Notify all subscribers about transactions dropped from the queue.
+	pub fn notify_dropped_transactions(&self, dropped: &[DroppedTransaction]) {
+		for subscriber in self.dropped_transactions_subscribers.read().values() {
+			for dropped in dropped {
+				Self::notify(&self.remote, subscriber, pubsub::Result::DroppedTransaction(dropped.into()));
+			}
+		}
+	}
 }
 
 /// A light client wrapper struct.
 pub trait LightClient: Send + Sync {
-	/// Get a recent block header.
-	fn block_header(&self, id: BlockId) -> Option<encoded::Header>;
+	/// Get a recent, decoded block header.
+	fn block_header(&self, id: BlockId) -> Option<Header>;
 
 	/// Fetch logs.
 	fn logs(&self, filter: EthFilter) -> BoxFuture<Vec<Log>>;
 }
 
 impl LightClient for LightFetch {
-	fn block_header(&self, id: BlockId) -> Option<encoded::Header> {
+	fn block_header(&self, id: BlockId) -> Option<Header> {
 		self.client.block_header(id)
+			.and_then(|header| header.decode(self.client.engine().params().eip1559_transition).ok())
 	}
 
 	fn logs(&self, filter: EthFilter) -> BoxFuture<Vec<Log>> {
@@ -236,7 +281,7 @@ impl<C: BlockChainClient> ChainNotify for ChainNotificationHandler<C> {
 			.filter_map(|&(hash, ref typ)| {
 				match typ {
 					&ChainRouteType::Retracted => None,
-					&ChainRouteType::Enacted => self.client.block_header(BlockId::Hash(hash))
+					&ChainRouteType::Enacted => self.client.block_header_decoded(BlockId::Hash(hash))
 				}
 			})
 			.map(|header| {
@@ -298,8 +343,23 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 				self.transactions_subscribers.write().push(subscriber);
 				return;
 			},
+			(pubsub::Kind::NewPendingTransactions, Some(pubsub::Params::Transactions(params))) => {
+				if params.include_transactions {
+					self.full_transactions_subscribers.write().push(subscriber);
+				} else {
+					self.transactions_subscribers.write().push(subscriber);
+				}
+				return;
+			},
 			(pubsub::Kind::NewPendingTransactions, _) => {
-				errors::invalid_params("newPendingTransactions", "Expected no parameters.")
+				errors::invalid_params("newPendingTransactions", "Expected no parameters or an object with `includeTransactions`.")
+			},
+			(pubsub::Kind::DroppedPendingTransactions, None) => {
+				self.dropped_transactions_subscribers.write().push(subscriber);
+				return;
+			},
+			(pubsub::Kind::DroppedPendingTransactions, _) => {
+				errors::invalid_params("droppedPendingTransactions", "Expected no parameters.")
 			},
 			_ => {
 				errors::unimplemented(None)
@@ -313,7 +373,9 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 		let res = self.heads_subscribers.write().remove(&id).is_some();
 		let res2 = self.logs_subscribers.write().remove(&id).is_some();
 		let res3 = self.transactions_subscribers.write().remove(&id).is_some();
+		let res4 = self.full_transactions_subscribers.write().remove(&id).is_some();
+		let res5 = self.dropped_transactions_subscribers.write().remove(&id).is_some();
 
-		Ok(res || res2 || res3)
+		Ok(res || res2 || res3 || res4 || res5)
 	}
 }
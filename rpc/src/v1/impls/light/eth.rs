@@ -37,8 +37,7 @@ use sync::LightSync;
 use hash::{KECCAK_NULL_RLP, KECCAK_EMPTY_LIST_RLP};
 use ethereum_types::U256;
 use parking_lot::{RwLock, Mutex};
-use rlp::Rlp;
-use transaction::SignedTransaction;
+use transaction::{SignedTransaction, UnverifiedTransaction};
 
 use v1::impls::eth_filter::Filterable;
 use v1::helpers::{errors, limit_logs};
@@ -46,8 +45,8 @@ use v1::helpers::{SyncPollFilter, PollManager};
 use v1::helpers::light_fetch::{self, LightFetch};
 use v1::traits::Eth;
 use v1::types::{
-	RichBlock, Block, BlockTransactions, BlockNumber, LightBlockNumber, Bytes, SyncStatus, SyncInfo,
-	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
+	RichBlock, Block, BlockTransactions, BlockNumber, LightBlockNumber, Bytes, SyncStatus, SyncInfo, AccountProof,
+	Transaction, CallRequest, FeeHistory, Index, Filter, Log, Receipt, Work,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256,
 };
 use v1::metadata::Metadata;
@@ -128,7 +127,7 @@ impl<T: LightChainClient + 'static> EthClient<T> {
 
 		// helper for filling out a rich block once we've got a block and a score.
 		let fill_rich = move |block: encoded::Block, score: Option<U256>| {
-			let header = block.decode_header();
+			let header = block.decode_header(engine.params().eip1559_transition);
 			let extra_info = engine.extra_info(&header);
 			RichBlock {
 				inner: Block {
@@ -144,6 +143,7 @@ impl<T: LightChainClient + 'static> EthClient<T> {
 					number: Some(header.number().into()),
 					gas_used: header.gas_used().clone().into(),
 					gas_limit: header.gas_limit().clone().into(),
+					base_fee_per_gas: header.base_fee().map(Into::into),
 					logs_bloom: Some(header.log_bloom().clone().into()),
 					timestamp: header.timestamp().into(),
 					difficulty: header.difficulty().clone().into(),
@@ -257,6 +257,14 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 			.unwrap_or_else(Default::default))
 	}
 
+	fn max_priority_fee_per_gas(&self) -> Result<RpcU256> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn fee_history(&self, _block_count: RpcU256, _newest_block: BlockNumber, _reward_percentiles: Trailing<Vec<f64>>) -> BoxFuture<FeeHistory> {
+		Box::new(future::err(errors::light_unimplemented(None)))
+	}
+
 	fn accounts(&self) -> Result<Vec<RpcH160>> {
 		self.accounts.accounts()
 			.map_err(|e| errors::account("Could not fetch accounts.", e))
@@ -276,6 +284,10 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 		Box::new(future::err(errors::unimplemented(None)))
 	}
 
+	fn proof(&self, _address: RpcH160, _storage_keys: Vec<RpcH256>, _num: Trailing<BlockNumber>) -> BoxFuture<AccountProof> {
+		Box::new(future::err(errors::light_unimplemented(None)))
+	}
+
 	fn block_by_hash(&self, hash: RpcH256, include_txs: bool) -> BoxFuture<Option<RichBlock>> {
 		Box::new(self.rich_block(BlockId::Hash(hash.into()), include_txs).map(Some))
 	}
@@ -358,9 +370,11 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 	}
 
 	fn send_raw_transaction(&self, raw: Bytes) -> Result<RpcH256> {
-		let best_header = self.client.best_block_header().decode().map_err(errors::decode)?;
+		let best_header = self.client.best_block_header()
+			.decode(self.client.engine().params().eip1559_transition)
+			.map_err(errors::decode)?;
 
-		Rlp::new(&raw.into_vec()).as_val()
+		UnverifiedTransaction::decode_envelope(&raw.into_vec())
 			.map_err(errors::rlp)
 			.and_then(|tx| {
 				self.client.engine().verify_transaction_basic(&tx, &best_header)
@@ -381,7 +395,7 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 	}
 
 	fn call(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<Bytes> {
-		Box::new(self.fetcher().proved_read_only_execution(req, num).and_then(|res| {
+		Box::new(self.fetcher().proved_read_only_execution(req, num, Default::default()).and_then(|res| {
 			match res {
 				Ok(exec) => Ok(exec.output.into()),
 				Err(e) => Err(errors::execution(e)),
@@ -391,7 +405,7 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 
 	fn estimate_gas(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256> {
 		// TODO: binary chop for more accurate estimates.
-		Box::new(self.fetcher().proved_read_only_execution(req, num).and_then(|res| {
+		Box::new(self.fetcher().proved_read_only_execution(req, num, Default::default()).and_then(|res| {
 			match res {
 				Ok(exec) => Ok((exec.refunded + exec.gas_used).into()),
 				Err(e) => Err(errors::execution(e)),
@@ -539,7 +553,11 @@ impl<T: LightChainClient + 'static> Filterable for EthClient<T> {
 }
 
 fn extract_uncle_at_index<T: LightChainClient>(block: encoded::Block, index: Index, client: Arc<T>) -> Option<RichBlock> {
-		let uncle = match block.uncles().into_iter().nth(index.value()) {
+		let uncles = match block.decode(client.engine().params().eip1559_transition) {
+			Ok(block) => block.uncles,
+			Err(_) => return None,
+		};
+		let uncle = match uncles.into_iter().nth(index.value()) {
 			Some(u) => u,
 			None => return None,
 		};
@@ -558,6 +576,7 @@ fn extract_uncle_at_index<T: LightChainClient>(block: encoded::Block, index: Ind
 				number: Some(uncle.number().into()),
 				gas_used: uncle.gas_used().clone().into(),
 				gas_limit: uncle.gas_limit().clone().into(),
+				base_fee_per_gas: uncle.base_fee().map(Into::into),
 				logs_bloom: Some(uncle.log_bloom().clone().into()),
 				timestamp: uncle.timestamp().into(),
 				difficulty: uncle.difficulty().clone().into(),
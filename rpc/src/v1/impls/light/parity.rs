@@ -336,8 +336,16 @@ impl Parity for ParityClient {
 		Ok(self.settings.chain.clone())
 	}
 
-	fn enode(&self) -> Result<String> {
-		self.light_dispatch.sync.enode().ok_or_else(errors::network_disabled)
+	fn enode(&self, format: Trailing<String>) -> Result<String> {
+		match format.unwrap_or_default().as_str() {
+			"" | "enode" => self.light_dispatch.sync.enode().ok_or_else(errors::network_disabled),
+			"enr" => self.node_record(),
+			other => Err(errors::invalid_params("format", other)),
+		}
+	}
+
+	fn node_record(&self) -> Result<String> {
+		self.light_dispatch.sync.node_record().ok_or_else(errors::network_disabled)
 	}
 
 	fn consensus_capability(&self) -> Result<ConsensusCapability> {
@@ -377,7 +385,7 @@ impl Parity for ParityClient {
 
 		let engine = self.light_dispatch.client.engine().clone();
 		let from_encoded = move |encoded: encoded::Header| {
-			let header = encoded.decode().map_err(errors::decode)?;
+			let header = encoded.decode(engine.params().eip1559_transition).map_err(errors::decode)?;
 			let extra_info = engine.extra_info(&header);
 			Ok(RichHeader {
 				inner: Header {
@@ -393,6 +401,7 @@ impl Parity for ParityClient {
 					number: Some(header.number().into()),
 					gas_used: header.gas_used().clone().into(),
 					gas_limit: header.gas_limit().clone().into(),
+					base_fee_per_gas: header.base_fee().map(Into::into),
 					logs_bloom: header.log_bloom().clone().into(),
 					timestamp: header.timestamp().into(),
 					difficulty: header.difficulty().clone().into(),
@@ -16,16 +16,50 @@
 
 //! Traces api implementation.
 
-use jsonrpc_core::Result;
+use std::sync::Arc;
+
+use transaction::{SignedTransaction, UnverifiedTransaction};
+
+use jsonrpc_core::{BoxFuture, Result};
+use jsonrpc_core::futures::{future, Future};
 use jsonrpc_macros::Trailing;
 use v1::Metadata;
 use v1::traits::Traces;
 use v1::helpers::errors;
-use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TraceResultsWithTransactionHash, TraceOptions, H256};
+use v1::helpers::dispatch::LightDispatcher;
+use v1::helpers::light_fetch::LightFetch;
+use v1::impls::traces::to_call_analytics;
+use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TraceResultsWithTransactionHash, TraceOptions, H256, block_number_to_id};
 
 /// Traces api implementation.
-// TODO: all calling APIs should be possible w. proved remote TX execution.
-pub struct TracesClient;
+///
+/// Calls and replays are executed locally over state proofs fetched from the network,
+/// which is slow but doesn't need the traces database of a full node.
+pub struct TracesClient {
+	light_dispatch: Arc<LightDispatcher>,
+	gas_price_percentile: usize,
+}
+
+impl TracesClient {
+	/// Creates new Traces client.
+	pub fn new(light_dispatch: Arc<LightDispatcher>, gas_price_percentile: usize) -> Self {
+		TracesClient {
+			light_dispatch,
+			gas_price_percentile,
+		}
+	}
+
+	/// Create a light blockchain data fetcher.
+	fn fetcher(&self) -> LightFetch {
+		LightFetch {
+			client: self.light_dispatch.client.clone(),
+			on_demand: self.light_dispatch.on_demand.clone(),
+			sync: self.light_dispatch.sync.clone(),
+			cache: self.light_dispatch.cache.clone(),
+			gas_price_percentile: self.gas_price_percentile,
+		}
+	}
+}
 
 impl Traces for TracesClient {
 	type Metadata = Metadata;
@@ -46,23 +80,54 @@ impl Traces for TracesClient {
 		Err(errors::light_unimplemented(None))
 	}
 
-	fn call(&self, _request: CallRequest, _flags: TraceOptions, _block: Trailing<BlockNumber>) -> Result<TraceResults> {
-		Err(errors::light_unimplemented(None))
+	fn call(&self, request: CallRequest, flags: TraceOptions, block: Trailing<BlockNumber>) -> BoxFuture<TraceResults> {
+		Box::new(self.fetcher().proved_read_only_execution(request, block, to_call_analytics(flags)).and_then(|res| {
+			match res {
+				Ok(executed) => Ok(TraceResults::from(executed)),
+				Err(e) => Err(errors::execution(e)),
+			}
+		}))
 	}
 
 	fn call_many(&self, _request: Vec<(CallRequest, TraceOptions)>, _block: Trailing<BlockNumber>) -> Result<Vec<TraceResults>> {
 		Err(errors::light_unimplemented(None))
 	}
 
-	fn raw_transaction(&self, _raw_transaction: Bytes, _flags: TraceOptions, _block: Trailing<BlockNumber>) -> Result<TraceResults> {
-		Err(errors::light_unimplemented(None))
+	fn raw_transaction(&self, raw_transaction: Bytes, flags: TraceOptions, block: Trailing<BlockNumber>) -> BoxFuture<TraceResults> {
+		let tx = try_bf!(UnverifiedTransaction::decode_envelope(&raw_transaction.into_vec()).map_err(|e| errors::invalid_params("Transaction is not valid RLP", e)));
+		let signed = try_bf!(SignedTransaction::new(tx).map_err(errors::transaction));
+
+		Box::new(self.fetcher().proved_transaction_execution(signed, block, to_call_analytics(flags)).and_then(|res| {
+			match res {
+				Ok(executed) => Ok(TraceResults::from(executed)),
+				Err(e) => Err(errors::execution(e)),
+			}
+		}))
 	}
 
-	fn replay_transaction(&self, _transaction_hash: H256, _flags: TraceOptions) -> Result<TraceResults> {
-		Err(errors::light_unimplemented(None))
+	fn replay_transaction(&self, transaction_hash: H256, flags: TraceOptions) -> BoxFuture<TraceResults> {
+		Box::new(self.fetcher().replay_transaction(transaction_hash.into(), to_call_analytics(flags)).and_then(|res| {
+			match res {
+				Some(Ok(executed)) => Ok(TraceResults::from(executed)),
+				Some(Err(e)) => Err(errors::execution(e)),
+				None => Err(errors::invalid_params("transaction", "Transaction not found in the canonical chain.")),
+			}
+		}))
 	}
 
-	fn replay_block_transactions(&self,  _block_number: BlockNumber, _flags: TraceOptions) ->  Result<Vec<TraceResultsWithTransactionHash>> {
-		Err(errors::light_unimplemented(None))
+	fn replay_block_transactions(&self, block_number: BlockNumber, flags: TraceOptions) -> BoxFuture<Vec<TraceResultsWithTransactionHash>> {
+		let id = match block_number {
+			BlockNumber::Pending => return Box::new(future::err(errors::invalid_params("`BlockNumber::Pending` is not supported", ()))),
+			num => block_number_to_id(num),
+		};
+
+		Box::new(self.fetcher().replay_block_transactions(id, None, to_call_analytics(flags)).and_then(|replayed| {
+			replayed.into_iter()
+				.map(|(hash, res)| match res {
+					Ok(executed) => Ok(TraceResultsWithTransactionHash::from((hash, executed))),
+					Err(e) => Err(errors::execution(e)),
+				})
+				.collect()
+		}))
 	}
 }
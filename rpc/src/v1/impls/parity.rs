@@ -345,8 +345,16 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		Ok(self.client.mode().to_string())
 	}
 
-	fn enode(&self) -> Result<String> {
-		self.sync.enode().ok_or_else(errors::network_disabled)
+	fn enode(&self, format: Trailing<String>) -> Result<String> {
+		match format.unwrap_or_default().as_str() {
+			"" | "enode" => self.sync.enode().ok_or_else(errors::network_disabled),
+			"enr" => self.node_record(),
+			other => Err(errors::invalid_params("format", other)),
+		}
+	}
+
+	fn node_record(&self) -> Result<String> {
+		self.sync.node_record().ok_or_else(errors::network_disabled)
 	}
 
 	fn consensus_capability(&self) -> Result<ConsensusCapability> {
@@ -390,7 +398,7 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			let header =
 				try_bf!(self.miner.pending_block_header(info.best_block_number).ok_or_else(errors::unknown_block));
 
-			(header.encoded(), None)
+			(header, None)
 		} else {
 			let id = match number {
 				BlockNumber::Num(num) => BlockId::Number(num),
@@ -399,14 +407,14 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 				BlockNumber::Pending => unreachable!(), // Already covered
 			};
 
-			let header = try_bf!(self.client.block_header(id.clone()).ok_or_else(errors::unknown_block));
+			let header = try_bf!(self.client.block_header_decoded(id.clone()).ok_or_else(errors::unknown_block));
 			let info = self.client.block_extra_info(id).expect(EXTRA_INFO_PROOF);
 
 			(header, Some(info))
 		};
 
 		Box::new(future::ok(RichHeader {
-			inner: header.into(),
+			inner: (&header).into(),
 			extra_info: extra.unwrap_or_default(),
 		}))
 	}
@@ -462,7 +470,7 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			};
 
 			let state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
-			let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?.decode().map_err(errors::decode)?;
+			let header = self.client.block_header_decoded(id).ok_or_else(errors::state_pruned)?;
 
 			(state, header)
 		};
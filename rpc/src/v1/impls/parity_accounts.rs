@@ -19,13 +19,14 @@ use std::sync::Arc;
 use std::collections::btree_map::{BTreeMap, Entry};
 use ethereum_types::Address;
 
-use ethkey::{Brain, Generator, Secret};
-use ethstore::KeyFile;
+use ethkey::{Brain, Generator, Secret, Mnemonic};
+use ethstore::{KeyFile, MAX_HD_WALLET_ADDRESSES};
 use ethcore::account_provider::AccountProvider;
 use jsonrpc_core::Result;
+use jsonrpc_macros::Trailing;
 use v1::helpers::errors;
 use v1::traits::ParityAccounts;
-use v1::types::{H160 as RpcH160, H256 as RpcH256, H520 as RpcH520, Derive, DeriveHierarchical, DeriveHash, ExtAccountInfo};
+use v1::types::{H160 as RpcH160, H256 as RpcH256, H520 as RpcH520, Derive, DeriveHierarchical, DeriveHash, ExtAccountInfo, HdWalletInfo, NewHdWallet};
 use ethkey::Password;
 
 /// Account management (personal) rpc implementation.
@@ -262,6 +263,51 @@ impl ParityAccounts for ParityAccountsClient {
 			.map_err(|e| errors::account("Could not sign message.", e))
 	}
 
+	fn generate_mnemonic(&self, words: Trailing<u64>) -> Result<String> {
+		let words = words.unwrap_or(12);
+		Mnemonic::new(words as usize)
+			.map(|mnemonic| mnemonic.phrase().to_owned())
+			.map_err(|e| errors::invalid_params("words", e))
+	}
+
+	fn new_hd_wallet(&self, phrase: String, passphrase: String, pass: Password) -> Result<NewHdWallet> {
+		let mnemonic = Mnemonic::from_phrase(&phrase).map_err(|e| errors::invalid_params("phrase", e))?;
+		self.accounts.new_hd_wallet(&mnemonic, &passphrase, &pass, String::new())
+			.map(|(id, address)| NewHdWallet {
+				id: id,
+				address: address.into(),
+			})
+			.map_err(|e| errors::account("Could not create HD wallet.", e))
+	}
+
+	fn list_hd_wallets(&self) -> Result<Vec<HdWalletInfo>> {
+		self.accounts.hd_wallets()
+			.map(|wallets| wallets.into_iter().map(|wallet| HdWalletInfo {
+				id: wallet.id.to_string(),
+				name: wallet.name,
+				path: wallet.path.to_string(),
+			}).collect())
+			.map_err(|e| errors::account("Could not fetch HD wallets.", e))
+	}
+
+	fn hd_wallet_addresses(&self, wallet: String, pass: Password, from: u64, count: u64) -> Result<Vec<RpcH160>> {
+		let from = to_index(from, "from")?;
+		if count > u64::from(MAX_HD_WALLET_ADDRESSES) {
+			return Err(errors::invalid_params("count", format!("At most {} addresses can be derived at once", MAX_HD_WALLET_ADDRESSES)));
+		}
+		let count = count as u32;
+		self.accounts.hd_wallet_addresses(&wallet, &pass, from, count)
+			.map(|addresses| addresses.into_iter().map(Into::into).collect())
+			.map_err(|e| errors::account("Could not derive HD wallet addresses.", e))
+	}
+
+	fn new_account_from_hd_wallet(&self, wallet: String, pass: Password, index: u64) -> Result<RpcH160> {
+		let index = to_index(index, "index")?;
+		self.accounts.new_hd_account(&wallet, &pass, index)
+			.map(Into::into)
+			.map_err(|e| errors::account("Could not create account.", e))
+	}
+
 	fn hardware_pin_matrix_ack(&self, path: String, pin: String) -> Result<bool> {
 		self.accounts.hardware_pin_matrix_ack(&path, &pin).map_err(|e| errors::account("Error communicating with hardware wallet.", e))
 	}
@@ -272,3 +318,10 @@ fn into_vec<A, B>(a: Vec<A>) -> Vec<B> where
 {
 	a.into_iter().map(Into::into).collect()
 }
+
+fn to_index(value: u64, param: &str) -> Result<u32> {
+	match value < u64::from(::ethkey::HARDENED_OFFSET) {
+		true => Ok(value as u32),
+		false => Err(errors::invalid_params(param, "Index must be lower than 2^31")),
+	}
+}
@@ -17,16 +17,19 @@
 //! Privte transaction signing RPC implementation.
 
 use std::sync::Arc;
+use std::collections::BTreeMap;
 
 use rlp::Rlp;
 
 use ethcore_private_tx::Provider as PrivateTransactionManager;
-use ethereum_types::Address;
+use ethereum_types::{Address, H256 as EthH256, U256 as EthU256};
+use ethcore::client::BlockId;
+use ethkey::Password;
 use transaction::SignedTransaction;
 
 use jsonrpc_core::{Error};
 use v1::types::{Bytes, PrivateTransactionReceipt, H160, H256, TransactionRequest, U256,
-	BlockNumber, PrivateTransactionReceiptAndTransaction, CallRequest, block_number_to_id};
+	BlockNumber, PrivateTransactionReceiptAndTransaction, PrivateTransactionStatus, CallRequest, block_number_to_id};
 use v1::traits::Private;
 use v1::metadata::Metadata;
 use v1::helpers::{errors, fake_sign};
@@ -50,6 +53,13 @@ impl PrivateClient {
 			None => Err(errors::light_unimplemented(None)),
 		}
 	}
+
+	fn block_id(block_number: BlockNumber) -> Result<BlockId, Error> {
+		match block_number {
+			BlockNumber::Pending => Err(errors::private_message_block_id_not_supported()),
+			num => Ok(block_number_to_id(num)),
+		}
+	}
 }
 
 impl Private for PrivateClient {
@@ -71,10 +81,7 @@ impl Private for PrivateClient {
 		let client = self.unwrap_manager()?;
 
 		let addresses: Vec<Address> = validators.into_iter().map(Into::into).collect();
-		let id = match block_number {
-			BlockNumber::Pending => return Err(errors::private_message_block_id_not_supported()),
-			num => block_number_to_id(num)
-		};
+		let id = Self::block_id(block_number)?;
 
 		let (transaction, contract_address) = client.public_creation_transaction(id, &signed_transaction, addresses.as_slice(), gas_price.into())
 			.map_err(|e| errors::private_message(e))?;
@@ -101,10 +108,7 @@ impl Private for PrivateClient {
 	}
 
 	fn private_call(&self, block_number: BlockNumber, request: CallRequest) -> Result<Bytes, Error> {
-		let id = match block_number {
-			BlockNumber::Pending => return Err(errors::private_message_block_id_not_supported()),
-			num => block_number_to_id(num)
-		};
+		let id = Self::block_id(block_number)?;
 
 		let request = CallRequest::into(request);
 		let signed = fake_sign::sign_call(request)?;
@@ -118,4 +122,35 @@ impl Private for PrivateClient {
 		let key = client.contract_key_id(&contract_address.into()).map_err(|e| errors::private_message(e))?;
 		Ok(key.into())
 	}
+
+	fn private_storage_at(&self, contract_address: H160, position: U256, block_number: BlockNumber, from: H160, password: Password) -> Result<H256, Error> {
+		let id = Self::block_id(block_number)?;
+		let client = self.unwrap_manager()?;
+		let position = EthH256::from(EthU256::from(position));
+		let value = client.private_storage_at(&contract_address.into(), &position, id, &from.into(), &password)
+			.map_err(|e| errors::private_message(e))?;
+		Ok(value.into())
+	}
+
+	fn private_code(&self, contract_address: H160, block_number: BlockNumber, from: H160, password: Password) -> Result<Bytes, Error> {
+		let id = Self::block_id(block_number)?;
+		let client = self.unwrap_manager()?;
+		let code = client.private_code(&contract_address.into(), id, &from.into(), &password)
+			.map_err(|e| errors::private_message(e))?;
+		Ok(code.into())
+	}
+
+	fn private_state(&self, contract_address: H160, block_number: BlockNumber, from: H160, password: Password) -> Result<BTreeMap<H256, H256>, Error> {
+		let id = Self::block_id(block_number)?;
+		let client = self.unwrap_manager()?;
+		let state = client.private_state(&contract_address.into(), id, &from.into(), &password)
+			.map_err(|e| errors::private_message(e))?;
+		Ok(state.into_iter().map(|(key, value)| (key.into(), value.into())).collect())
+	}
+
+	fn private_transaction_status(&self, hash: H256) -> Result<Option<PrivateTransactionStatus>, Error> {
+		let client = self.unwrap_manager()?;
+		let status = client.private_transaction_status(&hash.into()).map_err(|e| errors::private_message(e))?;
+		Ok(status.map(Into::into))
+	}
 }
@@ -26,7 +26,7 @@ use ethcore::account_provider::AccountProvider;
 use jsonrpc_core::Result;
 use v1::helpers::errors;
 use v1::helpers::secretstore::{generate_document_key, encrypt_document,
-	decrypt_document, decrypt_document_with_shadow, ordered_servers_keccak};
+	decrypt_document, decrypt_document_with_shadow, ordered_servers_keccak, verify_ed25519_signature};
 use v1::traits::SecretStore;
 use v1::types::{H160, H256, H512, Bytes, EncryptedDocumentKey};
 use ethkey::Password;
@@ -94,4 +94,8 @@ impl SecretStore for SecretStoreClient {
 			.map(|s| Bytes::new((*s).to_vec()))
 			.map_err(|e| errors::account("Could not sign raw hash.", e))
 	}
+
+	fn verify_ed25519_signature(&self, server_key_public: H256, message: Bytes, signature: Bytes) -> Result<bool> {
+		verify_ed25519_signature(server_key_public, &message.0, &signature.0)
+	}
 }
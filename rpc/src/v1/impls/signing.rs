@@ -37,6 +37,7 @@ use v1::traits::{EthSigning, ParitySigning};
 use v1::types::{
 	H160 as RpcH160, H256 as RpcH256, U256 as RpcU256, Bytes as RpcBytes, H520 as RpcH520,
 	Either as RpcEither,
+	EIP712,
 	RichRawTransaction as RpcRichRawTransaction,
 	TransactionRequest as RpcTransactionRequest,
 	ConfirmationPayload as RpcConfirmationPayload,
@@ -217,6 +218,21 @@ impl<D: Dispatcher + 'static> EthSigning for SigningQueueClient<D> {
 		}))
 	}
 
+	fn sign_typed_data(&self, meta: Metadata, address: RpcH160, data: EIP712) -> BoxFuture<RpcH520> {
+		let res = self.dispatch(
+			RpcConfirmationPayload::EIP712SignMessage((address.clone(), data).into()),
+			address.into(),
+			meta.origin,
+		);
+
+		Box::new(res.flatten().and_then(move |response| {
+			match response {
+				RpcConfirmationResponse::Signature(sig) => Ok(sig),
+				e => Err(errors::internal("Unexpected result.", e)),
+			}
+		}))
+	}
+
 	fn send_transaction(&self, meta: Metadata, request: RpcTransactionRequest) -> BoxFuture<RpcH256> {
 		let res = self.dispatch(
 			RpcConfirmationPayload::SendTransaction(request),
@@ -30,6 +30,7 @@ use v1::types::{
 	U256 as RpcU256,
 	H160 as RpcH160, H256 as RpcH256, H520 as RpcH520, Bytes as RpcBytes,
 	Either as RpcEither,
+	EIP712,
 	RichRawTransaction as RpcRichRawTransaction,
 	TransactionRequest as RpcTransactionRequest,
 	ConfirmationPayload as RpcConfirmationPayload,
@@ -79,6 +80,15 @@ impl<D: Dispatcher + 'static> EthSigning for SigningUnsafeClient<D>
 			}))
 	}
 
+	fn sign_typed_data(&self, _: Metadata, address: RpcH160, data: EIP712) -> BoxFuture<RpcH520> {
+		Box::new(self.handle(RpcConfirmationPayload::EIP712SignMessage((address.clone(), data).into()), address.into())
+			.then(|res| match res {
+				Ok(RpcConfirmationResponse::Signature(signature)) => Ok(signature),
+				Err(e) => Err(e),
+				e => Err(errors::internal("Unexpected result", e)),
+			}))
+	}
+
 	fn send_transaction(&self, _meta: Metadata, request: RpcTransactionRequest) -> BoxFuture<RpcH256> {
 		Box::new(self.handle(RpcConfirmationPayload::SendTransaction(request), DefaultAccount::Provided(self.accounts.default_account().ok().unwrap_or_default()))
 			.then(|res| match res {
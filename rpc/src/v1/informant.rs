@@ -16,6 +16,7 @@
 
 //! RPC Requests Statistics
 
+use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
 use std::sync::atomic::{self, AtomicUsize};
@@ -24,6 +25,7 @@ use futures_cpupool as pool;
 use jsonrpc_core as rpc;
 use order_stat;
 use parking_lot::RwLock;
+use stats::{PrometheusMetrics, PrometheusRegistry};
 
 pub use self::pool::CpuPool;
 
@@ -130,12 +132,27 @@ impl<T: Default + Copy + Ord> StatsCalculator<T> {
 	}
 }
 
+/// Statistics of a single RPC method.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct MethodStats {
+	/// Number of calls.
+	pub calls: u64,
+	/// Number of calls that returned an error.
+	pub errors: u64,
+	/// Total time spent handling the calls in microseconds.
+	pub time: u64,
+}
+
+/// Name under which calls to methods that don't exist are counted.
+const UNKNOWN_METHOD: &'static str = "unknown";
+
 /// RPC Statistics
 #[derive(Default, Debug)]
 pub struct RpcStats {
 	requests: RwLock<RateCalculator>,
 	roundtrips: RwLock<StatsCalculator<u32>>,
 	active_sessions: AtomicUsize,
+	methods: RwLock<HashMap<String, MethodStats>>,
 }
 
 impl RpcStats {
@@ -160,6 +177,22 @@ impl RpcStats {
 		self.roundtrips.write().add(microseconds)
 	}
 
+	/// Add a call of a single method (microseconds).
+	pub fn add_method_call(&self, method: &str, microseconds: u32, failed: bool) {
+		let mut methods = self.methods.write();
+		let stats = methods.entry(method.to_owned()).or_insert_with(Default::default);
+		stats.calls += 1;
+		stats.time += microseconds as u64;
+		if failed {
+			stats.errors += 1;
+		}
+	}
+
+	/// Returns statistics of all called methods.
+	pub fn methods(&self) -> HashMap<String, MethodStats> {
+		self.methods.read().clone()
+	}
+
 	/// Returns number of open sessions
 	pub fn sessions(&self) -> usize {
 		self.active_sessions.load(atomic::Ordering::Relaxed)
@@ -176,6 +209,20 @@ impl RpcStats {
 	}
 }
 
+impl PrometheusMetrics for RpcStats {
+	fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+		r.register_gauge("rpc_sessions", "Open RPC sessions", self.sessions() as f64);
+		r.register_gauge("rpc_requests_rate", "RPC requests per second", self.requests_rate() as f64);
+		r.register_gauge("rpc_roundtrip_median_seconds", "Approximated median RPC roundtrip", self.approximated_roundtrip() as f64 / 1_000_000.0);
+
+		for (method, stats) in self.methods.read().iter() {
+			let labels = [("method", method.as_str())];
+			r.register_summary_with_labels("rpc_call_seconds", "Time spent handling RPC calls", &labels, stats.calls, stats.time as f64 / 1_000_000.0);
+			r.register_counter_with_labels("rpc_call_errors", "RPC calls that returned an error", &labels, stats.errors as f64);
+		}
+	}
+}
+
 /// Notifies about RPC activity.
 pub trait ActivityNotifier: Send + Sync + 'static {
 	/// Activity on RPC interface
@@ -221,9 +268,9 @@ impl<M: rpc::Metadata, T: ActivityNotifier> rpc::Middleware<M> for Middleware<T>
 		self.notifier.active();
 		self.stats.count_request();
 
-		let id = match request {
-			rpc::Request::Single(rpc::Call::MethodCall(ref call)) => Some(call.id.clone()),
-			_ => None,
+		let (id, method) = match request {
+			rpc::Request::Single(rpc::Call::MethodCall(ref call)) => (Some(call.id.clone()), Some(call.method.clone())),
+			_ => (None, None),
 		};
 		let stats = self.stats.clone();
 		let future = process(request, meta).map(move |res| {
@@ -232,6 +279,18 @@ impl<M: rpc::Metadata, T: ActivityNotifier> rpc::Middleware<M> for Middleware<T>
 				debug!(target: "rpc", "[{:?}] Took {}ms", id, time / 1_000);
 			}
 			stats.add_roundtrip(time);
+			if let Some(method) = method {
+				let error = match res {
+					Some(rpc::Response::Single(rpc::Output::Failure(ref failure))) => Some(failure.error.code.clone()),
+					_ => None,
+				};
+				// don't let arbitrary method names grow the statistics
+				let method = match error {
+					Some(rpc::ErrorCode::MethodNotFound) => UNKNOWN_METHOD,
+					_ => method.as_str(),
+				};
+				stats.add_method_call(method, time, error.is_some());
+			}
 			res
 		});
 
@@ -257,7 +316,8 @@ impl ActivityNotifier for ClientNotifier {
 #[cfg(test)]
 mod tests {
 
-	use super::{RateCalculator, StatsCalculator, RpcStats};
+	use super::{RateCalculator, StatsCalculator, RpcStats, MethodStats};
+	use stats::{PrometheusMetrics, PrometheusRegistry};
 
 	#[test]
 	fn should_calculate_rate() {
@@ -314,6 +374,25 @@ mod tests {
 		assert_eq!(stats.approximated_roundtrip(), 125);
 	}
 
+	#[test]
+	fn should_count_method_calls() {
+		// given
+		let stats = RpcStats::default();
+
+		// when
+		stats.add_method_call("eth_call", 100, false);
+		stats.add_method_call("eth_call", 300, true);
+
+		// then
+		assert_eq!(stats.methods()["eth_call"], MethodStats { calls: 2, errors: 1, time: 400 });
+
+		let mut registry = PrometheusRegistry::new("");
+		stats.prometheus_metrics(&mut registry);
+		let rendered = registry.render();
+		assert!(rendered.contains("rpc_call_seconds_count{method=\"eth_call\"} 2\n"));
+		assert!(rendered.contains("rpc_call_errors_total{method=\"eth_call\"} 1\n"));
+	}
+
 	#[test]
 	fn should_be_sync_and_send() {
 		let stats = RpcStats::default();
@@ -85,7 +85,7 @@ impl EthTester {
 		let tester = Self::from_spec(make_spec(chain));
 
 		for b in chain.blocks_rlp() {
-			if let Ok(block) = Unverified::from_rlp(b) {
+			if let Ok(block) = Unverified::from_rlp(b, tester.client.engine().params().eip1559_transition) {
 				let _ = tester.client.import_block(block);
 				tester.client.flush_queue();
 				tester.client.import_verified_blocks();
@@ -422,7 +422,7 @@ fn verify_transaction_counts(name: String, chain: BlockChain) {
 	let tester = EthTester::from_chain(&chain);
 
 	let mut id = 1;
-	for b in chain.blocks_rlp().into_iter().filter_map(|b| Unverified::from_rlp(b).ok()) {
+	for b in chain.blocks_rlp().into_iter().filter_map(|b| Unverified::from_rlp(b, tester.client.engine().params().eip1559_transition).ok()) {
 		let count = b.transactions.len();
 
 		let hash = b.header.hash();
@@ -263,6 +263,14 @@ impl MinerService for TestMinerService {
 		}
 	}
 
+	fn queue_full_status<C>(&self, _chain: &C) -> txpool::Status {
+		txpool::Status {
+			stalled: 0,
+			pending: self.queued_transactions().len(),
+			future: 0,
+		}
+	}
+
 	/// Submit `seal` as a valid solution for the header of `pow_hash`.
 	/// Will check the seal, but not actually insert the block into the chain.
 	fn submit_seal(&self, _pow_hash: H256, _seal: Vec<Bytes>) -> Result<SealedBlock, Error> {
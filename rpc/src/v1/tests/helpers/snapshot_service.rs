@@ -17,7 +17,7 @@
 use ethcore::snapshot::{ManifestData, RestorationStatus, SnapshotService};
 
 use bytes::Bytes;
-use ethereum_types::H256;
+use ethereum_types::{H256, U256};
 use parking_lot::Mutex;
 
 /// Mocked snapshot service (used for sync info extensions).
@@ -50,5 +50,9 @@ impl SnapshotService for TestSnapshotService {
 	fn abort_restore(&self) { }
 	fn restore_state_chunk(&self, _hash: H256, _chunk: Bytes) { }
 	fn restore_block_chunk(&self, _hash: H256, _chunk: Bytes) { }
+	fn begin_state_restore(&self, _manifest: ManifestData) { }
+	fn restore_state_nodes(&self, _account: Option<H256>, _nodes: Vec<Bytes>) { }
+	fn restored_state_node(&self, _account: Option<H256>, _hash: &H256) -> Option<Bytes> { None }
+	fn finish_state_restore(&self, _block: Bytes, _receipts: Bytes, _parent_total_difficulty: U256) { }
 	fn shutdown(&self) { }
 }
@@ -106,6 +106,10 @@ impl SyncProvider for TestSyncProvider {
 		None
 	}
 
+	fn node_record(&self) -> Option<String> {
+		Some("enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8".into())
+	}
+
 	fn transactions_stats(&self) -> BTreeMap<H256, TransactionStats> {
 		map![
 			1.into() => TransactionStats {
@@ -14,24 +14,73 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use ethcore::client::TestBlockChainClient;
+use ethcore::trace::{DebugTrace, StructLog};
 
 use jsonrpc_core::IoHandler;
 use v1::{Debug, DebugClient};
 
-fn io() -> IoHandler {
-	let client = Arc::new(TestBlockChainClient::new());
-
+fn io_with_client(client: Arc<TestBlockChainClient>) -> IoHandler {
 	let mut io = IoHandler::new();
 	io.extend_with(DebugClient::new(client).to_delegate());
 	io
 }
 
+fn io() -> IoHandler {
+	io_with_client(Arc::new(TestBlockChainClient::new()))
+}
+
 #[test]
 fn rpc_debug_get_bad_blocks() {
 	let request = r#"{"jsonrpc": "2.0", "method": "debug_getBadBlocks", "params": [], "id": 1}"#;
 	let response = "{\"jsonrpc\":\"2.0\",\"result\":[{\"author\":\"0x0000000000000000000000000000000000000000\",\"difficulty\":\"0x0\",\"extraData\":\"0x\",\"gasLimit\":\"0x0\",\"gasUsed\":\"0x0\",\"hash\":\"0x27bfb37e507ce90da141307204b1c6ba24194380613590ac50ca4b1d7198ff65\",\"logsBloom\":\"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000\",\"miner\":\"0x0000000000000000000000000000000000000000\",\"number\":\"0x0\",\"parentHash\":\"0x0000000000000000000000000000000000000000000000000000000000000000\",\"reason\":\"Invalid block\",\"receiptsRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"rlp\":\"\\\"0x010203\\\"\",\"sealFields\":[],\"sha3Uncles\":\"0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347\",\"size\":\"0x3\",\"stateRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"timestamp\":\"0x0\",\"totalDifficulty\":null,\"transactions\":[],\"transactionsRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"uncles\":[]}],\"id\":1}";
 	assert_eq!(io().handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_debug_trace_transaction() {
+	let client = Arc::new(TestBlockChainClient::new());
+	let mut storage = BTreeMap::new();
+	storage.insert(1.into(), 2.into());
+	client.set_debug_trace_result(Ok(DebugTrace::StructLogs {
+		gas_used: 21003.into(),
+		failed: false,
+		output: vec![0x12],
+		logs: vec![StructLog {
+			pc: 0,
+			instruction: 0x54,
+			gas: 79000.into(),
+			gas_cost: 800.into(),
+			depth: 1,
+			stack: Some(vec![1.into()]),
+			memory: Some(vec![0xff; 32]),
+			storage: Some(storage),
+		}],
+	}));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_traceTransaction", "params": ["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"gas":21003,"failed":false,"returnValue":"12","structLogs":[{"pc":0,"op":"SLOAD","gas":79000,"gasCost":800,"depth":1,"stack":["0x1"],"memory":["ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"],"storage":{"0000000000000000000000000000000000000000000000000000000000000001":"0000000000000000000000000000000000000000000000000000000000000002"}}]},"id":1}"#;
+	assert_eq!(io_with_client(client).handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_transaction_rejects_unknown_tracer() {
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_traceTransaction", "params": ["0x0000000000000000000000000000000000000000000000000000000000000001", {"tracer": "4byteTracer"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: tracer","data":"\"Unsupported tracer: 4byteTracer. Expected `callTracer` or `prestateTracer`.\""},"id":1}"#;
+	assert_eq!(io().handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_storage_range_at() {
+	let client = Arc::new(TestBlockChainClient::new());
+	client.set_storage(1.into(), 0.into(), 5.into());
+	client.set_storage(1.into(), 1.into(), 6.into());
+	client.set_storage(2.into(), 0.into(), 7.into());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_storageRangeAt", "params": ["0x0000000000000000000000000000000000000000000000000000000000000001", 0, "0x0000000000000000000000000000000000000001", "0x0000000000000000000000000000000000000000000000000000000000000000", 1], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"storage":{"0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563":{"key":"0x0000000000000000000000000000000000000000000000000000000000000000","value":"0x0000000000000000000000000000000000000000000000000000000000000005"}},"nextKey":"0xb10e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf6"},"id":1}"#;
+	assert_eq!(io_with_client(client).handle_request_sync(request), Some(response.to_owned()));
+}
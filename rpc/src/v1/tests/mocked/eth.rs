@@ -401,6 +401,30 @@ fn rpc_eth_gas_price() {
 	assert_eq!(EthTester::default().io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_max_priority_fee_per_gas() {
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_maxPriorityFeePerGas", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x4a817c800","id":1}"#;
+
+	assert_eq!(EthTester::default().io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_fee_history() {
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_feeHistory", "params": ["0x1", "latest", [25, 75]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"oldestBlock":"0x0","baseFeePerGas":["0x0","0x0"],"gasUsedRatio":[0.0],"reward":[["0x0","0x0"]]},"id":1}"#;
+
+	assert_eq!(EthTester::default().io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_fee_history_rejects_invalid_percentiles() {
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_feeHistory", "params": ["0x1", "latest", [75, 25]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: rewardPercentiles","data":"\"Expected increasing percentiles between 0 and 100.\""},"id":1}"#;
+
+	assert_eq!(EthTester::default().io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_accounts() {
 	let tester = EthTester::default();
@@ -474,6 +498,33 @@ fn rpc_eth_storage_at() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_get_proof() {
+	let tester = EthTester::default();
+	tester.client.set_balance(Address::from(1), U256::from(5));
+	tester.client.set_storage(Address::from(1), H256::from(4), H256::from(7));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getProof",
+		"params": ["0x0000000000000000000000000000000000000001", ["0x0000000000000000000000000000000000000000000000000000000000000004"], "latest"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"address":"0x0000000000000000000000000000000000000001","accountProof":[],"balance":"0x5","codeHash":"0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470","nonce":"0x0","storageHash":"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421","storageProof":[{"key":"0x0000000000000000000000000000000000000000000000000000000000000004","value":"0x7","proof":[]}]},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getProof",
+		"params": ["0x0000000000000000000000000000000000000001", [], "pending"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: block","data":"\"Proofs of the pending state are not supported.\""},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_transaction_count() {
 	let request = r#"{
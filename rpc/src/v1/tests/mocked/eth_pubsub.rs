@@ -23,8 +23,12 @@ use jsonrpc_pubsub::Session;
 use std::time::Duration;
 
 use v1::{EthPubSub, EthPubSubClient, Metadata};
+use v1::types::Transaction;
 
 use ethcore::client::{TestBlockChainClient, EachBlockWith, ChainNotify, ChainRoute, ChainRouteType};
+use miner::pool::{VerifiedTransaction, DroppedTransaction, DropReason};
+use serde_json;
+use transaction;
 use parity_reactor::EventLoop;
 
 const DURATION_ZERO: Duration = Duration::from_millis(0);
@@ -172,7 +176,7 @@ fn should_subscribe_to_pending_transactions() {
 
 	// Fail if params are provided
 	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newPendingTransactions", {}], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: newPendingTransactions","data":"\"Expected no parameters.\""},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: newPendingTransactions","data":"\"Expected no parameters or an object with `includeTransactions`.\""},"id":1}"#;
 	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
 
 	// Subscribe
@@ -220,3 +224,108 @@ fn should_return_unimplemented() {
 	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["syncing"], "id": 1}"#;
 	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
 }
+
+fn verified_transaction(nonce: u64) -> Arc<VerifiedTransaction> {
+	let tx = transaction::Transaction {
+		nonce: nonce.into(),
+		..Default::default()
+	}.fake_sign(5.into());
+	Arc::new(VerifiedTransaction::from_pending_block_transaction(tx))
+}
+
+#[test]
+fn should_subscribe_to_full_pending_transactions() {
+	// given
+	let el = EventLoop::spawn();
+	let client = TestBlockChainClient::new();
+
+	let pubsub = EthPubSubClient::new_test(Arc::new(client), el.remote());
+	let handler = pubsub.handler().upgrade().unwrap();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Fail on unknown fields
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newPendingTransactions", {"includeTransactions": true, "limit": 1}], "id": 1}"#;
+	let response = io.handle_request_sync(request, metadata.clone()).unwrap();
+	assert!(response.contains(r#""code":-32602"#));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newPendingTransactions", {"includeTransactions": true}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x416d77337e24399d","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Send new transactions
+	let tx = verified_transaction(1);
+	handler.notify_pending_transactions(&[tx.clone()]);
+
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let transaction = serde_json::to_string(&Transaction::from_signed(tx.signed().clone())).unwrap();
+	let response = format!(r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"result":{},"subscription":"0x416d77337e24399d"}}}}"#, transaction);
+	assert_eq!(res, Some(response));
+
+	// And unsubscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_unsubscribe", "params": ["0x416d77337e24399d"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	assert_eq!(res, None);
+}
+
+#[test]
+fn should_subscribe_to_dropped_transactions() {
+	// given
+	let el = EventLoop::spawn();
+	let client = TestBlockChainClient::new();
+
+	let pubsub = EthPubSubClient::new_test(Arc::new(client), el.remote());
+	let handler = pubsub.handler().upgrade().unwrap();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Fail if params are provided
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["droppedPendingTransactions", {}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: droppedPendingTransactions","data":"\"Expected no parameters.\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["droppedPendingTransactions"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x416d77337e24399d","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Drop transactions
+	let old = verified_transaction(1);
+	let invalid = verified_transaction(2);
+	handler.notify_dropped_transactions(&[
+		DroppedTransaction { transaction: old.clone(), reason: DropReason::Replaced(7.into()) },
+		DroppedTransaction { transaction: invalid.clone(), reason: DropReason::Invalid },
+	]);
+
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = format!(r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"result":{{"hash":"0x{:x}","reason":"replaced","replacedBy":"0x0000000000000000000000000000000000000000000000000000000000000007"}},"subscription":"0x416d77337e24399d"}}}}"#, old.hash());
+	assert_eq!(res, Some(response));
+
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = format!(r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"result":{{"hash":"0x{:x}","reason":"invalid","replacedBy":null}},"subscription":"0x416d77337e24399d"}}}}"#, invalid.hash());
+	assert_eq!(res, Some(response));
+
+	// And unsubscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_unsubscribe", "params": ["0x416d77337e24399d"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	assert_eq!(res, None);
+}
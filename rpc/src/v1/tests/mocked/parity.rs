@@ -298,6 +298,28 @@ fn rpc_parity_chain() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_node_record() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	let record = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_nodeRecord", "params":[], "id": 1}"#;
+	let response = format!(r#"{{"jsonrpc":"2.0","result":"{}","id":1}}"#, record);
+	assert_eq!(io.handle_request_sync(request), Some(response.clone()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_enode", "params":["enr"], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_enode", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Network is disabled or not yet up."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_enode", "params":["url"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: format","data":"\"url\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_net_peers() {
 	let deps = Dependencies::new();
@@ -435,3 +435,53 @@ fn should_sign_message() {
 	let res = tester.io.handle_request_sync(&request);
 	assert_eq!(res, Some(response.into()));
 }
+
+#[test]
+fn should_generate_mnemonic() {
+	let tester = setup();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_generateMnemonic", "params": [24], "id": 1}"#;
+	let res = tester.io.handle_request_sync(&request).unwrap();
+	let phrase = res.split('"').nth(7).unwrap().to_owned();
+	assert_eq!(phrase.split(' ').count(), 24);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_generateMnemonic", "params": [13], "id": 1}"#;
+	let res = tester.io.handle_request_sync(&request).unwrap();
+	assert!(res.contains(r#""code":-32602"#));
+}
+
+#[test]
+fn should_manage_hd_wallets() {
+	let tester = setup();
+	let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_newHdWallet", "params": ["<PHRASE>", "", "password1"], "id": 1}"#.replace("<PHRASE>", phrase);
+	let res = tester.io.handle_request_sync(&request).unwrap();
+	assert!(res.contains(r#""address":"0x9858effd232b4033e47d90003d41ec34ecaeda94""#));
+	assert_eq!(tester.accounts.accounts().unwrap(), vec!["9858effd232b4033e47d90003d41ec34ecaeda94".into()]);
+
+	let wallets = tester.accounts.hd_wallets().unwrap();
+	assert_eq!(wallets.len(), 1);
+	let id = wallets[0].id.to_string();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_listHdWallets", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"id":"<ID>","name":"","path":"m/44'/60'/0'/0"}],"id":1}"#.replace("<ID>", &id);
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_hdWalletAddresses", "params": ["<ID>", "password1", 0, 2], "id": 1}"#.replace("<ID>", &id);
+	let res = tester.io.handle_request_sync(&request).unwrap();
+	let second = res.split('"').nth(9).unwrap().to_owned();
+	assert!(res.contains(r#""result":["0x9858effd232b4033e47d90003d41ec34ecaeda94","#));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_newAccountFromHdWallet", "params": ["<ID>", "password1", 1], "id": 1}"#.replace("<ID>", &id);
+	let response = r#"{"jsonrpc":"2.0","result":"<ADDRESS>","id":1}"#.replace("<ADDRESS>", &second);
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response));
+	assert_eq!(tester.accounts.accounts().unwrap().len(), 2);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_hdWalletAddresses", "params": ["<ID>", "wrong", 0, 2], "id": 1}"#.replace("<ID>", &id);
+	let res = tester.io.handle_request_sync(&request).unwrap();
+	assert!(res.contains("Could not derive HD wallet addresses."));
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_hdWalletAddresses", "params": ["<ID>", "password1", 0, 1001], "id": 1}"#.replace("<ID>", &id);
+	let res = tester.io.handle_request_sync(&request).unwrap();
+	assert!(res.contains("Couldn't parse parameters: count"));
+}
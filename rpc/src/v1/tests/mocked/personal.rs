@@ -21,6 +21,7 @@ use bytes::ToPretty;
 use ethereum_types::{U256, Address};
 use ethcore::account_provider::AccountProvider;
 use ethcore::client::TestBlockChainClient;
+use hash::keccak;
 use jsonrpc_core::IoHandler;
 use parking_lot::Mutex;
 use transaction::{Action, Transaction};
@@ -145,6 +146,62 @@ fn sign() {
 	assert_eq!(tester.io.handle_request_sync(request.as_ref()), Some(response));
 }
 
+#[test]
+fn sign_typed_data() {
+	let tester = setup();
+	// example from EIP-712, signed with `keccak256("cow")`
+	let address = tester.accounts.insert_account(keccak("cow").into(), &"password123".into()).unwrap();
+	assert_eq!(address, "cd2a3d9f938e13cd947ec05abc7fe734df8dd826".into());
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "personal_signTypedData",
+		"params": [
+			{
+				"types": {
+					"EIP712Domain": [
+						{ "name": "name", "type": "string" },
+						{ "name": "version", "type": "string" },
+						{ "name": "chainId", "type": "uint256" },
+						{ "name": "verifyingContract", "type": "address" }
+					],
+					"Person": [
+						{ "name": "name", "type": "string" },
+						{ "name": "wallet", "type": "address" }
+					],
+					"Mail": [
+						{ "name": "from", "type": "Person" },
+						{ "name": "to", "type": "Person" },
+						{ "name": "contents", "type": "string" }
+					]
+				},
+				"primaryType": "Mail",
+				"domain": {
+					"name": "Ether Mail",
+					"version": "1",
+					"chainId": 1,
+					"verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+				},
+				"message": {
+					"from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+					"to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+					"contents": "Hello, Bob!"
+				}
+			},
+			"0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826",
+			"password123"
+		],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b915621c","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.into()));
+
+	// malformed typed data is rejected before signing
+	let request = request.replace(r#""chainId": 1"#, r#""chainId": "one""#);
+	let response = tester.io.handle_request_sync(&request).unwrap();
+	assert!(response.contains(r#""code":-32602"#));
+}
+
 #[test]
 fn sign_with_invalid_password() {
 	let tester = setup();
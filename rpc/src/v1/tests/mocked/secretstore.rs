@@ -173,3 +173,22 @@ fn rpc_secretstore_generate_document_key() {
 		&DEFAULT_MAC,
 		&generation_response.encrypted_key.0).is_ok());
 }
+
+#[test]
+fn rpc_secretstore_verify_ed25519_signature() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	// RFC 8032, TEST 1
+	let valid_request = r#"{"jsonrpc": "2.0", "method": "secretstore_verifyEd25519Signature", "params":[
+		"0xd75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a", "0x",
+		"0xe5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"
+	], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(&valid_request).unwrap(), r#"{"jsonrpc":"2.0","result":true,"id":1}"#);
+
+	let invalid_request = r#"{"jsonrpc": "2.0", "method": "secretstore_verifyEd25519Signature", "params":[
+		"0xd75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a", "0x00",
+		"0xe5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"
+	], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(&invalid_request).unwrap(), r#"{"jsonrpc":"2.0","result":false,"id":1}"#);
+}
@@ -230,6 +230,80 @@ fn should_sign_if_account_is_unlocked() {
 	assert_eq!(tester.signer.requests().len(), 0);
 }
 
+#[test]
+fn should_add_typed_data_sign_to_queue() {
+	// given
+	let tester = eth_signing();
+	let address = Address::random();
+	assert_eq!(tester.signer.requests().len(), 0);
+
+	// when
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_signTypedData",
+		"params": [
+			""#.to_owned() + format!("0x{:x}", address).as_ref() + r#"",
+			{
+				"types": {
+					"EIP712Domain": [{ "name": "name", "type": "string" }],
+					"Mail": [{ "name": "contents", "type": "string" }]
+				},
+				"primaryType": "Mail",
+				"domain": { "name": "Ether Mail" },
+				"message": { "contents": "Hello, Bob!" }
+			}
+		],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","id":1}"#;
+
+	// then
+	let promise = tester.io.handle_request(&request);
+
+	// the future must be polled at least once before request is queued.
+	let signer = tester.signer.clone();
+	::std::thread::spawn(move || loop {
+		if signer.requests().len() == 1 {
+			// respond
+			let sender = signer.take(&1.into()).unwrap();
+			signer.request_confirmed(sender, Ok(ConfirmationResponse::Signature(0.into())));
+			break
+		}
+		::std::thread::sleep(Duration::from_millis(100))
+	});
+
+	let res = promise.wait().unwrap();
+	assert_eq!(res, Some(response.to_owned()));
+}
+
+#[test]
+fn should_not_queue_invalid_typed_data() {
+	// given
+	let tester = eth_signing();
+	let address = Address::random();
+
+	// when
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_signTypedData",
+		"params": [
+			""#.to_owned() + format!("0x{:x}", address).as_ref() + r#"",
+			{
+				"types": { "Mail": [{ "name": "contents", "type": "string" }] },
+				"primaryType": "Mail",
+				"domain": {},
+				"message": { "contents": "Hello, Bob!" }
+			}
+		],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: typed data","data":"\"Missing EIP712Domain type\""},"id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+	assert_eq!(tester.signer.requests().len(), 0);
+}
+
 #[test]
 fn should_add_transaction_to_queue() {
 	// given
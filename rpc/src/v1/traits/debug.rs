@@ -17,8 +17,12 @@
 //! Debug RPC interface.
 
 use jsonrpc_core::Result;
+use jsonrpc_macros::Trailing;
 
-use v1::types::RichBlock;
+use v1::types::{
+	RichBlock, BlockNumber, CallRequest, DebugTrace, TransactionDebugTrace, TraceConfig,
+	StorageRange, AccountRange, H160, H256,
+};
 
 build_rpc_trait! {
 	/// Debug RPC interface.
@@ -26,5 +30,28 @@ build_rpc_trait! {
 		/// Returns recently seen bad blocks.
 		#[rpc(name = "debug_getBadBlocks")]
 		fn bad_blocks(&self) -> Result<Vec<RichBlock>>;
+
+		/// Replays a transaction and returns its struct logs or the output of a built-in tracer.
+		#[rpc(name = "debug_traceTransaction")]
+		fn trace_transaction(&self, H256, Trailing<TraceConfig>) -> Result<DebugTrace>;
+
+		/// Executes a call at the given block and returns its trace.
+		#[rpc(name = "debug_traceCall")]
+		fn trace_call(&self, CallRequest, BlockNumber, Trailing<TraceConfig>) -> Result<DebugTrace>;
+
+		/// Replays all transactions of a block and returns their traces.
+		#[rpc(name = "debug_traceBlockByNumber")]
+		fn trace_block_by_number(&self, BlockNumber, Trailing<TraceConfig>) -> Result<Vec<TransactionDebugTrace>>;
+
+		/// Returns a page of contract storage after the given number of transactions of a block are applied.
+		/// Params: block hash, transaction index, contract address, hashed key to start with, maximal number of slots.
+		#[rpc(name = "debug_storageRangeAt")]
+		fn storage_range_at(&self, H256, u64, H160, H256, u64) -> Result<StorageRange>;
+
+		/// Returns a page of accounts at the given block.
+		/// Params: block, hashed address to start with, maximal number of accounts, skip code, skip storage,
+		/// include accounts with unknown addresses.
+		#[rpc(name = "debug_accountRange")]
+		fn account_range(&self, BlockNumber, H256, u64, bool, bool, bool) -> Result<AccountRange>;
 	}
 }
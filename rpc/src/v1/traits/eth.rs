@@ -18,7 +18,7 @@
 use jsonrpc_core::{Result, BoxFuture};
 use jsonrpc_macros::Trailing;
 
-use v1::types::{RichBlock, BlockNumber, Bytes, CallRequest, Filter, FilterChanges, Index};
+use v1::types::{RichBlock, AccountProof, BlockNumber, Bytes, CallRequest, FeeHistory, Filter, FilterChanges, Index};
 use v1::types::{Log, Receipt, SyncStatus, Transaction, Work};
 use v1::types::{H64, H160, H256, U256};
 
@@ -51,6 +51,14 @@ build_rpc_trait! {
 		#[rpc(name = "eth_gasPrice")]
 		fn gas_price(&self) -> Result<U256>;
 
+		/// Returns suggested priority fee per gas for dynamic fee transactions.
+		#[rpc(name = "eth_maxPriorityFeePerGas")]
+		fn max_priority_fee_per_gas(&self) -> Result<U256>;
+
+		/// Returns base fees, gas usage ratios and priority fee percentiles of a range of blocks.
+		#[rpc(name = "eth_feeHistory")]
+		fn fee_history(&self, U256, BlockNumber, Trailing<Vec<f64>>) -> BoxFuture<FeeHistory>;
+
 		/// Returns accounts list.
 		#[rpc(name = "eth_accounts")]
 		fn accounts(&self) -> Result<Vec<H160>>;
@@ -67,6 +75,10 @@ build_rpc_trait! {
 		#[rpc(name = "eth_getStorageAt")]
 		fn storage_at(&self, H160, U256, Trailing<BlockNumber>) -> BoxFuture<H256>;
 
+		/// Returns the account and storage values of the given address with Merkle proofs (EIP-1186).
+		#[rpc(name = "eth_getProof")]
+		fn proof(&self, H160, Vec<H256>, Trailing<BlockNumber>) -> BoxFuture<AccountProof>;
+
 		/// Returns block with given hash.
 		#[rpc(name = "eth_getBlockByHash")]
 		fn block_by_hash(&self, H256, bool) -> BoxFuture<Option<RichBlock>>;
@@ -182,9 +182,13 @@ build_rpc_trait! {
 		#[rpc(name = "parity_chain")]
 		fn chain(&self) -> Result<String>;
 
-		/// Get the enode of this node.
+		/// Get the enode of this node, or its EIP-778 node record when `"enr"` format is requested.
 		#[rpc(name = "parity_enode")]
-		fn enode(&self) -> Result<String>;
+		fn enode(&self, Trailing<String>) -> Result<String>;
+
+		/// Get the EIP-778 node record of this node.
+		#[rpc(name = "parity_nodeRecord")]
+		fn node_record(&self) -> Result<String>;
 
 		/// Returns information on current consensus capability.
 		#[rpc(name = "parity_consensusCapability")]
@@ -18,9 +18,10 @@
 use std::collections::BTreeMap;
 
 use jsonrpc_core::Result;
+use jsonrpc_macros::Trailing;
 use ethkey::Password;
 use ethstore::KeyFile;
-use v1::types::{H160, H256, H520, DeriveHash, DeriveHierarchical, ExtAccountInfo};
+use v1::types::{H160, H256, H520, DeriveHash, DeriveHierarchical, ExtAccountInfo, HdWalletInfo, NewHdWallet};
 
 build_rpc_trait! {
 	/// Personal Parity rpc interface.
@@ -135,6 +136,29 @@ build_rpc_trait! {
 		#[rpc(name = "parity_signMessage")]
 		fn sign_message(&self, H160, Password, H256) -> Result<H520>;
 
+		/// Generates new random BIP-39 mnemonic phrase with given number of words (12 by default).
+		/// The phrase is not stored.
+		#[rpc(name = "parity_generateMnemonic")]
+		fn generate_mnemonic(&self, Trailing<u64>) -> Result<String>;
+
+		/// Creates new HD wallet from BIP-39 mnemonic phrase, passphrase and password.
+		/// Stores the encrypted seed and the first wallet account (`m/44'/60'/0'/0/0`).
+		#[rpc(name = "parity_newHdWallet")]
+		fn new_hd_wallet(&self, String, String, Password) -> Result<NewHdWallet>;
+
+		/// Lists stored HD wallets.
+		#[rpc(name = "parity_listHdWallets")]
+		fn list_hd_wallets(&self) -> Result<Vec<HdWalletInfo>>;
+
+		/// Returns `count` addresses of HD wallet accounts starting at index `from`
+		/// without storing the accounts.
+		#[rpc(name = "parity_hdWalletAddresses")]
+		fn hd_wallet_addresses(&self, String, Password, u64, u64) -> Result<Vec<H160>>;
+
+		/// Stores HD wallet account with given index (with the wallet password).
+		#[rpc(name = "parity_newAccountFromHdWallet")]
+		fn new_account_from_hd_wallet(&self, String, Password, u64) -> Result<H160>;
+
 		/// Send a PinMatrixAck to a hardware wallet, unlocking it
 		#[rpc(name = "parity_hardwarePinMatrixAck")]
 		fn hardware_pin_matrix_ack(&self, String, String) -> Result<bool>;
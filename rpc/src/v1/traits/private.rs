@@ -16,10 +16,13 @@
 
 //! SecretStore-specific rpc interface.
 
+use std::collections::BTreeMap;
+
+use ethkey::Password;
 use jsonrpc_core::Error;
 
 use v1::types::{Bytes, PrivateTransactionReceipt, H160, H256, U256, BlockNumber,
-	PrivateTransactionReceiptAndTransaction, CallRequest};
+	PrivateTransactionReceiptAndTransaction, PrivateTransactionStatus, CallRequest};
 
 build_rpc_trait! {
 	/// Private transaction management RPC interface.
@@ -41,5 +44,24 @@ build_rpc_trait! {
 		/// Retrieve the id of the key associated with the contract
 		#[rpc(name = "private_contractKey")]
 		fn private_contract_key(&self, H160) -> Result<H256, Error>;
+
+		/// Returns value from the decrypted private state of the contract at given position.
+		/// Requester must be a validator of the contract, proven with the password of its account.
+		#[rpc(name = "private_getStorageAt")]
+		fn private_storage_at(&self, H160, U256, BlockNumber, H160, Password) -> Result<H256, Error>;
+
+		/// Returns decrypted code of the private contract.
+		/// Requester must be a validator of the contract, proven with the password of its account.
+		#[rpc(name = "private_getCode")]
+		fn private_code(&self, H160, BlockNumber, H160, Password) -> Result<Bytes, Error>;
+
+		/// Returns decrypted private state of the contract.
+		/// Requester must be a validator of the contract, proven with the password of its account.
+		#[rpc(name = "private_getState")]
+		fn private_state(&self, H160, BlockNumber, H160, Password) -> Result<BTreeMap<H256, H256>, Error>;
+
+		/// Returns status of the private transaction, waiting for validators signatures
+		#[rpc(name = "private_transactionStatus")]
+		fn private_transaction_status(&self, H256) -> Result<Option<PrivateTransactionStatus>, Error>;
 	}
 }
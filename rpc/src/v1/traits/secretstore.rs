@@ -56,5 +56,10 @@ build_rpc_trait! {
 		/// Arguments: `account`, `password`, `raw_hash`.
 		#[rpc(name = "secretstore_signRawHash")]
 		fn sign_raw_hash(&self, H160, Password, H256) -> Result<Bytes>;
+
+		/// Verify Ed25519 signature, generated by the secret store.
+		/// Arguments: `server_key_public`, `message`, `signature`.
+		#[rpc(name = "secretstore_verifyEd25519Signature")]
+		fn verify_ed25519_signature(&self, H256, Bytes, Bytes) -> Result<bool>;
 	}
 }
@@ -16,7 +16,7 @@
 
 //! Traces specific rpc interface.
 
-use jsonrpc_core::Result;
+use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_macros::Trailing;
 use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TraceResultsWithTransactionHash, H256, TraceOptions};
 
@@ -43,7 +43,7 @@ build_rpc_trait! {
 
 		/// Executes the given call and returns a number of possible traces for it.
 		#[rpc(name = "trace_call")]
-		fn call(&self, CallRequest, TraceOptions, Trailing<BlockNumber>) -> Result<TraceResults>;
+		fn call(&self, CallRequest, TraceOptions, Trailing<BlockNumber>) -> BoxFuture<TraceResults>;
 
 		/// Executes all given calls and returns a number of possible traces for each of it.
 		#[rpc(name = "trace_callMany")]
@@ -51,14 +51,14 @@ build_rpc_trait! {
 
 		/// Executes the given raw transaction and returns a number of possible traces for it.
 		#[rpc(name = "trace_rawTransaction")]
-		fn raw_transaction(&self, Bytes, TraceOptions, Trailing<BlockNumber>) -> Result<TraceResults>;
+		fn raw_transaction(&self, Bytes, TraceOptions, Trailing<BlockNumber>) -> BoxFuture<TraceResults>;
 
 		/// Executes the transaction with the given hash and returns a number of possible traces for it.
 		#[rpc(name = "trace_replayTransaction")]
-		fn replay_transaction(&self, H256, TraceOptions) -> Result<TraceResults>;
+		fn replay_transaction(&self, H256, TraceOptions) -> BoxFuture<TraceResults>;
 
 		/// Executes all the transactions at the given block and returns a number of possible traces for each transaction.
 		#[rpc(name = "trace_replayBlockTransactions")]
-		fn replay_block_transactions(&self, BlockNumber, TraceOptions) ->  Result<Vec<TraceResultsWithTransactionHash>>;
+		fn replay_block_transactions(&self, BlockNumber, TraceOptions) -> BoxFuture<Vec<TraceResultsWithTransactionHash>>;
 	}
 }
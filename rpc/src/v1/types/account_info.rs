@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use v1::types::H160;
+
 /// Account information.
 #[derive(Debug, Default, Clone, PartialEq, Serialize)]
 pub struct AccountInfo {
@@ -33,6 +35,26 @@ pub struct ExtAccountInfo {
 	pub uuid: Option<String>,
 }
 
+/// HD wallet information (used by `parity_listHdWallets`).
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct HdWalletInfo {
+	/// Wallet UUID
+	pub id: String,
+	/// Wallet name
+	pub name: String,
+	/// Derivation path of wallet accounts (without account index)
+	pub path: String,
+}
+
+/// Newly created HD wallet (used by `parity_newHdWallet`).
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct NewHdWallet {
+	/// Wallet UUID
+	pub id: String,
+	/// Address of the first wallet account
+	pub address: H160,
+}
+
 /// Hardware wallet information.
 #[derive(Debug, Default, Clone, PartialEq, Serialize)]
 pub struct HwAccountInfo {
@@ -0,0 +1,50 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EIP-1186 account and storage proofs.
+
+use v1::types::{Bytes, H160, H256, U256};
+
+/// Account with a Merkle proof of its state, returned by `eth_getProof`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct AccountProof {
+	/// Account address
+	pub address: H160,
+	/// Trie nodes on the path from the state root to the account
+	pub account_proof: Vec<Bytes>,
+	/// Account balance
+	pub balance: U256,
+	/// Hash of the account code
+	pub code_hash: H256,
+	/// Account nonce
+	pub nonce: U256,
+	/// Root of the account storage trie
+	pub storage_hash: H256,
+	/// Proofs of the requested storage keys
+	pub storage_proof: Vec<StorageProof>,
+}
+
+/// Storage slot with a Merkle proof of its value.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct StorageProof {
+	/// Storage key
+	pub key: H256,
+	/// Storage value
+	pub value: U256,
+	/// Trie nodes on the path from the storage root to the value
+	pub proof: Vec<Bytes>,
+}
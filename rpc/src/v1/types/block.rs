@@ -17,7 +17,8 @@
 use std::ops::Deref;
 use std::collections::BTreeMap;
 
-use ethcore::encoded::Header as EthHeader;
+use ethcore::header::Header as EthHeader;
+use rlp;
 
 use serde::{Serialize, Serializer};
 use serde::ser::Error;
@@ -69,6 +70,9 @@ pub struct Block {
 	pub gas_used: U256,
 	/// Gas Limit
 	pub gas_limit: U256,
+	/// Base fee per gas (EIP-1559)
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub base_fee_per_gas: Option<U256>,
 	/// Extra data
 	pub extra_data: Bytes,
 	/// Logs bloom
@@ -116,6 +120,9 @@ pub struct Header {
 	pub gas_used: U256,
 	/// Gas Limit
 	pub gas_limit: U256,
+	/// Base fee per gas (EIP-1559)
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub base_fee_per_gas: Option<U256>,
 	/// Extra data
 	pub extra_data: Bytes,
 	/// Logs bloom
@@ -140,24 +147,23 @@ impl<'a> From<&'a EthHeader> for Header {
 	fn from(h: &'a EthHeader) -> Self {
 		Header {
 			hash: Some(h.hash().into()),
-			size: Some(h.rlp().as_raw().len().into()),
-			parent_hash: h.parent_hash().into(),
-			uncles_hash: h.uncles_hash().into(),
-			author: h.author().into(),
-			miner: h.author().into(),
-			state_root: h.state_root().into(),
-			transactions_root: h.transactions_root().into(),
-			receipts_root: h.receipts_root().into(),
+			size: Some(rlp::encode(h).len().into()),
+			parent_hash: h.parent_hash().clone().into(),
+			uncles_hash: h.uncles_hash().clone().into(),
+			author: h.author().clone().into(),
+			miner: h.author().clone().into(),
+			state_root: h.state_root().clone().into(),
+			transactions_root: h.transactions_root().clone().into(),
+			receipts_root: h.receipts_root().clone().into(),
 			number: Some(h.number().into()),
-			gas_used: h.gas_used().into(),
-			gas_limit: h.gas_limit().into(),
-			logs_bloom: h.log_bloom().into(),
+			gas_used: h.gas_used().clone().into(),
+			gas_limit: h.gas_limit().clone().into(),
+			base_fee_per_gas: h.base_fee().map(Into::into),
+			logs_bloom: h.log_bloom().clone().into(),
 			timestamp: h.timestamp().into(),
-			difficulty: h.difficulty().into(),
-			extra_data: h.extra_data().into(),
-			seal_fields: h.view().decode_seal()
-				.expect("Client/Miner returns only valid headers. We only serialize headers from Client/Miner; qed")
-				.into_iter().map(Into::into).collect(),
+			difficulty: h.difficulty().clone().into(),
+			extra_data: h.extra_data().clone().into(),
+			seal_fields: h.seal().iter().cloned().map(Into::into).collect(),
 		}
 	}
 }
@@ -234,6 +240,7 @@ mod tests {
 			number: Some(U256::default()),
 			gas_used: U256::default(),
 			gas_limit: U256::default(),
+			base_fee_per_gas: None,
 			extra_data: Bytes::default(),
 			logs_bloom: Some(H2048::default()),
 			timestamp: U256::default(),
@@ -272,6 +279,7 @@ mod tests {
 			number: Some(U256::default()),
 			gas_used: U256::default(),
 			gas_limit: U256::default(),
+			base_fee_per_gas: None,
 			extra_data: Bytes::default(),
 			logs_bloom: Some(H2048::default()),
 			timestamp: U256::default(),
@@ -310,6 +318,7 @@ mod tests {
 			number: Some(U256::default()),
 			gas_used: U256::default(),
 			gas_limit: U256::default(),
+			base_fee_per_gas: None,
 			extra_data: Bytes::default(),
 			logs_bloom: H2048::default(),
 			timestamp: U256::default(),
@@ -21,7 +21,7 @@ use serde::{Serialize, Serializer};
 use ansi_term::Colour;
 use bytes::ToPretty;
 
-use v1::types::{U256, TransactionRequest, RichRawTransaction, H160, H256, H520, Bytes, TransactionCondition, Origin};
+use v1::types::{U256, TransactionRequest, RichRawTransaction, H160, H256, H520, Bytes, TransactionCondition, Origin, EIP712};
 use v1::helpers;
 use ethkey::Password;
 
@@ -60,6 +60,7 @@ impl fmt::Display for ConfirmationPayload {
 			ConfirmationPayload::SignTransaction(ref transaction) => write!(f, "(Sign only) {}", transaction),
 			ConfirmationPayload::EthSignMessage(ref sign) => write!(f, "{}", sign),
 			ConfirmationPayload::Decrypt(ref decrypt) => write!(f, "{}", decrypt),
+			ConfirmationPayload::EIP712SignMessage(ref sign) => write!(f, "{}", sign),
 		}
 	}
 }
@@ -94,6 +95,37 @@ impl fmt::Display for SignRequest {
 	}
 }
 
+/// EIP-712 typed data sign request
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EIP712SignRequest {
+	/// Address
+	pub address: H160,
+	/// Typed structured data to sign
+	pub data: EIP712,
+}
+
+impl From<(H160, EIP712)> for EIP712SignRequest {
+	fn from(tuple: (H160, EIP712)) -> Self {
+		EIP712SignRequest {
+			address: tuple.0,
+			data: tuple.1,
+		}
+	}
+}
+
+impl fmt::Display for EIP712SignRequest {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"sign typed data {} {} with {}",
+			self.data.primary_type,
+			self.data.message,
+			Colour::White.bold().paint(format!("0x{:?}", self.address)),
+		)
+	}
+}
+
 /// Decrypt request
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -174,6 +206,9 @@ pub enum ConfirmationPayload {
 	/// Decryption
 	#[serde(rename="decrypt")]
 	Decrypt(DecryptRequest),
+	/// EIP-712 typed data signature
+	#[serde(rename="signTypedData")]
+	EIP712SignMessage(EIP712SignRequest),
 }
 
 impl From<helpers::ConfirmationPayload> for ConfirmationPayload {
@@ -189,6 +224,10 @@ impl From<helpers::ConfirmationPayload> for ConfirmationPayload {
 				address: address.into(),
 				msg: msg.into(),
 			}),
+			helpers::ConfirmationPayload::EIP712SignMessage(address, data) => ConfirmationPayload::EIP712SignMessage(EIP712SignRequest {
+				address: address.into(),
+				data: data,
+			}),
 		}
 	}
 }
@@ -343,6 +382,26 @@ mod tests {
 		assert_eq!(res.unwrap(), expected.to_owned());
 	}
 
+	#[test]
+	fn should_serialize_typed_data_sign_confirmation() {
+		// given
+		let data = r#"{"types":{"EIP712Domain":[{"name":"name","type":"string"}],"Mail":[{"name":"contents","type":"string"}]},"primaryType":"Mail","domain":{"name":"Test"},"message":{"contents":"Hello"}}"#;
+		let request = helpers::ConfirmationRequest {
+			id: 15.into(),
+			payload: helpers::ConfirmationPayload::EIP712SignMessage(
+				10.into(), serde_json::from_str(data).unwrap(),
+			),
+			origin: Default::default(),
+		};
+
+		// when
+		let res = serde_json::to_string(&ConfirmationRequest::from(request));
+		let expected = r#"{"id":"0xf","payload":{"signTypedData":{"address":"0x000000000000000000000000000000000000000a","data":<DATA>}},"origin":"unknown"}"#.replace("<DATA>", data);
+
+		// then
+		assert_eq!(res.unwrap(), expected);
+	}
+
 	#[test]
 	fn should_deserialize_modification() {
 		// given
@@ -0,0 +1,348 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Geth-compatible `debug_trace*` types.
+
+use std::collections::BTreeMap;
+use serde::{Serialize, Serializer};
+use rustc_hex::ToHex;
+use ethcore::pod_state::PodState;
+use ethcore::trace::{self as et, FlatTrace};
+use ethcore::trace::trace::{Action, Res};
+use vm::CallType;
+use v1::types::{Bytes, H160, H256, U256};
+
+/// Options of the `debug_trace*` calls.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct TraceConfig {
+	/// Don't record memory in struct logs.
+	#[serde(rename="disableMemory", default)]
+	pub disable_memory: bool,
+	/// Don't record the stack in struct logs.
+	#[serde(rename="disableStack", default)]
+	pub disable_stack: bool,
+	/// Don't record storage in struct logs.
+	#[serde(rename="disableStorage", default)]
+	pub disable_storage: bool,
+	/// Built-in tracer to use instead of the struct logger: `callTracer` or `prestateTracer`.
+	pub tracer: Option<String>,
+}
+
+/// A single traced instruction.
+#[derive(Debug, Serialize)]
+pub struct StructLog {
+	/// Program counter.
+	pub pc: u64,
+	/// Instruction mnemonic.
+	pub op: String,
+	/// Gas left before the instruction.
+	pub gas: u64,
+	/// Gas cost of the instruction.
+	#[serde(rename="gasCost")]
+	pub gas_cost: u64,
+	/// Call depth.
+	pub depth: usize,
+	/// Stack before the instruction.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stack: Option<Vec<U256>>,
+	/// Memory before the instruction, in 32 byte words.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub memory: Option<Vec<String>>,
+	/// Accessed storage of the executing contract.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<BTreeMap<String, String>>,
+}
+
+impl From<et::StructLog> for StructLog {
+	fn from(log: et::StructLog) -> Self {
+		StructLog {
+			pc: log.pc as u64,
+			op: log.op_name().into(),
+			gas: log.gas.low_u64(),
+			gas_cost: log.gas_cost.low_u64(),
+			depth: log.depth,
+			stack: log.stack.map(|stack| stack.into_iter().map(Into::into).collect()),
+			memory: log.memory.map(|memory| memory.chunks(32).map(|word| word.to_hex()).collect()),
+			storage: log.storage.map(|storage| storage.into_iter()
+				.map(|(key, value)| (format!("{:x}", key), format!("{:x}", value)))
+				.collect()),
+		}
+	}
+}
+
+/// Result of the struct logger.
+#[derive(Debug, Serialize)]
+pub struct StructLogs {
+	/// Gas used by the transaction.
+	pub gas: u64,
+	/// True if the transaction failed.
+	pub failed: bool,
+	/// Output of the transaction.
+	#[serde(rename="returnValue")]
+	pub return_value: String,
+	/// Traced instructions.
+	#[serde(rename="structLogs")]
+	pub struct_logs: Vec<StructLog>,
+}
+
+/// A call frame of the `callTracer`.
+#[derive(Debug, Default, Serialize)]
+pub struct CallFrame {
+	/// Call type, e.g. `CALL` or `CREATE`.
+	#[serde(rename="type")]
+	pub call_type: String,
+	/// Sender.
+	pub from: H160,
+	/// Recipient or created contract.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub to: Option<H160>,
+	/// Transferred value.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub value: Option<U256>,
+	/// Gas provided.
+	pub gas: U256,
+	/// Gas used.
+	#[serde(rename="gasUsed")]
+	pub gas_used: U256,
+	/// Input data or init code.
+	pub input: Bytes,
+	/// Output data or created code.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub output: Option<Bytes>,
+	/// Failure reason.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// Nested calls.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+	/// Nests flat traces of a transaction into a call tree.
+	/// Returns `None` if there are no traces.
+	pub fn from_flat_traces(traces: Vec<FlatTrace>) -> Option<Self> {
+		let mut traces = traces.into_iter();
+		let mut root = CallFrame::from(traces.next()?);
+		for trace in traces {
+			let address = trace.trace_address.clone();
+			root.insert(&address, trace.into());
+		}
+		Some(root)
+	}
+
+	fn insert(&mut self, address: &[usize], frame: CallFrame) {
+		match address.split_first() {
+			Some((&index, rest)) if !rest.is_empty() && index < self.calls.len() => self.calls[index].insert(rest, frame),
+			_ => self.calls.push(frame),
+		}
+	}
+}
+
+impl From<FlatTrace> for CallFrame {
+	fn from(trace: FlatTrace) -> Self {
+		let mut frame = match trace.action {
+			Action::Call(call) => CallFrame {
+				call_type: match call.call_type {
+					CallType::None | CallType::Call => "CALL",
+					CallType::CallCode => "CALLCODE",
+					CallType::DelegateCall => "DELEGATECALL",
+					CallType::StaticCall => "STATICCALL",
+				}.into(),
+				from: call.from.into(),
+				to: Some(call.to.into()),
+				value: match call.call_type {
+					CallType::DelegateCall | CallType::StaticCall => None,
+					_ => Some(call.value.into()),
+				},
+				gas: call.gas.into(),
+				input: call.input.into(),
+				..Default::default()
+			},
+			Action::Create(create) => CallFrame {
+				call_type: "CREATE".into(),
+				from: create.from.into(),
+				value: Some(create.value.into()),
+				gas: create.gas.into(),
+				input: create.init.into(),
+				..Default::default()
+			},
+			Action::Suicide(suicide) => CallFrame {
+				call_type: "SELFDESTRUCT".into(),
+				from: suicide.address.into(),
+				to: Some(suicide.refund_address.into()),
+				value: Some(suicide.balance.into()),
+				..Default::default()
+			},
+			Action::Reward(reward) => CallFrame {
+				call_type: "REWARD".into(),
+				to: Some(reward.author.into()),
+				value: Some(reward.value.into()),
+				..Default::default()
+			},
+		};
+
+		match trace.result {
+			Res::Call(result) => {
+				frame.gas_used = result.gas_used.into();
+				frame.output = Some(result.output.into());
+			},
+			Res::Create(result) => {
+				frame.gas_used = result.gas_used.into();
+				frame.to = Some(result.address.into());
+				frame.output = Some(result.code.into());
+			},
+			Res::FailedCall(error) | Res::FailedCreate(error) => {
+				frame.gas_used = frame.gas;
+				frame.error = Some(match error {
+					et::TraceError::Reverted => "execution reverted".into(),
+					error => error.to_string(),
+				});
+			},
+			Res::None => {},
+		}
+
+		frame
+	}
+}
+
+/// Account state before a transaction, as reported by the `prestateTracer`.
+#[derive(Debug, Serialize)]
+pub struct PrestateAccount {
+	/// Balance.
+	pub balance: U256,
+	/// Nonce.
+	pub nonce: u64,
+	/// Code.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub code: Option<Bytes>,
+	/// Storage slots modified by the transaction.
+	#[serde(skip_serializing_if = "BTreeMap::is_empty")]
+	pub storage: BTreeMap<H256, H256>,
+}
+
+/// Output of a `debug_trace*` call.
+#[derive(Debug)]
+pub enum DebugTrace {
+	/// Struct logs.
+	StructLogs(StructLogs),
+	/// Call tree.
+	Call(Option<CallFrame>),
+	/// Touched accounts before the transaction.
+	Prestate(BTreeMap<H160, PrestateAccount>),
+}
+
+impl Serialize for DebugTrace {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where S: Serializer {
+		match *self {
+			DebugTrace::StructLogs(ref logs) => logs.serialize(serializer),
+			DebugTrace::Call(ref frame) => frame.serialize(serializer),
+			DebugTrace::Prestate(ref accounts) => accounts.serialize(serializer),
+		}
+	}
+}
+
+impl From<et::DebugTrace> for DebugTrace {
+	fn from(trace: et::DebugTrace) -> Self {
+		match trace {
+			et::DebugTrace::StructLogs { gas_used, failed, output, logs } => DebugTrace::StructLogs(StructLogs {
+				gas: gas_used.low_u64(),
+				failed,
+				return_value: output.to_hex(),
+				struct_logs: logs.into_iter().map(Into::into).collect(),
+			}),
+			et::DebugTrace::Call(traces) => DebugTrace::Call(CallFrame::from_flat_traces(traces)),
+			et::DebugTrace::Prestate(pod) => DebugTrace::Prestate(prestate(pod)),
+		}
+	}
+}
+
+fn prestate(pod: PodState) -> BTreeMap<H160, PrestateAccount> {
+	pod.drain().into_iter().map(|(address, account)| (address.into(), PrestateAccount {
+		balance: account.balance.into(),
+		nonce: account.nonce.low_u64(),
+		code: account.code.and_then(|code| if code.is_empty() { None } else { Some(code.into()) }),
+		storage: account.storage.into_iter().map(|(key, value)| (key.into(), value.into())).collect(),
+	})).collect()
+}
+
+/// Trace of a single transaction in a block.
+#[derive(Debug, Serialize)]
+pub struct TransactionDebugTrace {
+	/// Transaction hash.
+	#[serde(rename="txHash")]
+	pub transaction_hash: H256,
+	/// Trace of the transaction.
+	pub result: DebugTrace,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethcore::trace::{FlatTrace, TraceError};
+	use ethcore::trace::trace::{Action, Res, Call, CallResult};
+	use vm::CallType;
+	use super::{CallFrame, TraceConfig};
+
+	fn call(trace_address: Vec<usize>, result: Res) -> FlatTrace {
+		FlatTrace {
+			action: Action::Call(Call {
+				from: 1.into(),
+				to: 2.into(),
+				value: 3.into(),
+				gas: 100.into(),
+				input: vec![],
+				call_type: CallType::Call,
+			}),
+			result,
+			subtraces: 0,
+			trace_address,
+		}
+	}
+
+	#[test]
+	fn should_deserialize_trace_config() {
+		let config: TraceConfig = serde_json::from_str(r#"{"disableStorage":true,"tracer":"callTracer","timeout":"5s"}"#).unwrap();
+		assert_eq!(config, TraceConfig {
+			disable_storage: true,
+			tracer: Some("callTracer".into()),
+			..Default::default()
+		});
+	}
+
+	#[test]
+	fn should_nest_flat_traces() {
+		let ok = || Res::Call(CallResult { gas_used: 10.into(), output: vec![] });
+		let frame = CallFrame::from_flat_traces(vec![
+			call(vec![], ok()),
+			call(vec![0], ok()),
+			call(vec![0, 0], Res::FailedCall(TraceError::Reverted)),
+			call(vec![1], ok()),
+		]).unwrap();
+
+		assert_eq!(frame.calls.len(), 2);
+		assert_eq!(frame.calls[0].calls.len(), 1);
+		assert_eq!(frame.calls[0].calls[0].error, Some("execution reverted".into()));
+		assert!(frame.calls[1].calls.is_empty());
+	}
+
+	#[test]
+	fn should_serialize_call_frame() {
+		let frame = CallFrame::from(call(vec![], Res::Call(CallResult { gas_used: 10.into(), output: vec![0x1] })));
+		let serialized = serde_json::to_string(&frame).unwrap();
+		assert_eq!(serialized, r#"{"type":"CALL","from":"0x0000000000000000000000000000000000000001","to":"0x0000000000000000000000000000000000000002","value":"0x3","gas":"0x64","gasUsed":"0xa","input":"0x","output":"0x01"}"#);
+	}
+}
@@ -0,0 +1,79 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EIP-712 typed structured data.
+
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use serde_json::{self, Value};
+
+/// Member of a struct type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EIP712Field {
+	/// Member name
+	pub name: String,
+	/// Member type, e.g. `address`, `uint256[]` or name of another struct type
+	#[serde(rename = "type")]
+	pub type_: String,
+}
+
+/// Typed structured data to be signed (`eth_signTypedData` argument).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EIP712 {
+	/// Struct types definitions; must contain `EIP712Domain`.
+	pub types: BTreeMap<String, Vec<EIP712Field>>,
+	/// Type of the `message`
+	#[serde(rename = "primaryType")]
+	pub primary_type: String,
+	/// Signing domain (instance of `EIP712Domain`)
+	pub domain: Value,
+	/// Message to sign (instance of `primaryType`)
+	pub message: Value,
+}
+
+impl Hash for EIP712 {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		// `Value` doesn't implement `Hash`, its canonical serialization is used instead.
+		serde_json::to_string(self).expect("EIP712 is always serializable; qed").hash(state)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::EIP712;
+
+	#[test]
+	fn should_deserialize_typed_data() {
+		let s = r#"{
+			"types": {
+				"EIP712Domain": [{ "name": "name", "type": "string" }],
+				"Mail": [{ "name": "contents", "type": "string" }]
+			},
+			"primaryType": "Mail",
+			"domain": { "name": "Ether Mail" },
+			"message": { "contents": "Hello, Bob!" }
+		}"#;
+		let data: EIP712 = serde_json::from_str(s).unwrap();
+
+		assert_eq!(data.primary_type, "Mail");
+		assert_eq!(data.types["Mail"][0].type_, "string");
+		assert_eq!(data.message["contents"], "Hello, Bob!");
+		assert!(serde_json::from_str::<EIP712>(&s.replace("primaryType", "primary")).is_err());
+	}
+}
@@ -0,0 +1,54 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fee history (EIP-1559).
+
+use v1::types::U256;
+
+/// Base fees, gas usage and priority fee percentiles of a range of blocks.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct FeeHistory {
+	/// Lowest block number of the returned range.
+	#[serde(rename="oldestBlock")]
+	pub oldest_block: U256,
+	/// Base fee of each block in the range, followed by the base fee of the next block.
+	#[serde(rename="baseFeePerGas")]
+	pub base_fee_per_gas: Vec<U256>,
+	/// Ratio of gas used to gas limit of each block in the range.
+	#[serde(rename="gasUsedRatio")]
+	pub gas_used_ratio: Vec<f64>,
+	/// Requested effective priority fee percentiles of each block in the range.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub reward: Option<Vec<Vec<U256>>>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::FeeHistory;
+
+	#[test]
+	fn test_serialize_fee_history() {
+		let history = FeeHistory {
+			oldest_block: 5.into(),
+			base_fee_per_gas: vec![7.into(), 8.into()],
+			gas_used_ratio: vec![0.5],
+			reward: Some(vec![vec![1.into(), 2.into()]]),
+		};
+		let serialized = serde_json::to_string(&history).unwrap();
+		assert_eq!(serialized, r#"{"oldestBlock":"0x5","baseFeePerGas":["0x7","0x8"],"gasUsedRatio":[0.5],"reward":[["0x1","0x2"]]}"#);
+	}
+}
@@ -17,13 +17,17 @@
 //! RPC types
 
 mod account_info;
+mod account_proof;
 mod block;
 mod block_number;
 mod bytes;
 mod call_request;
 mod confirmations;
 mod consensus_status;
+mod debug_trace;
 mod derivation;
+mod eip712;
+mod fee_history;
 mod filter;
 mod hash;
 mod histogram;
@@ -34,6 +38,7 @@ mod provenance;
 mod receipt;
 mod rpc_settings;
 mod secretstore;
+mod state_range;
 mod sync;
 mod trace;
 mod trace_filter;
@@ -46,17 +51,21 @@ mod private_receipt;
 
 pub mod pubsub;
 
-pub use self::account_info::{AccountInfo, ExtAccountInfo, HwAccountInfo};
+pub use self::account_info::{AccountInfo, ExtAccountInfo, HwAccountInfo, HdWalletInfo, NewHdWallet};
+pub use self::account_proof::{AccountProof, StorageProof};
 pub use self::bytes::Bytes;
 pub use self::block::{RichBlock, Block, BlockTransactions, Header, RichHeader, Rich};
 pub use self::block_number::{BlockNumber, LightBlockNumber, block_number_to_id};
 pub use self::call_request::CallRequest;
 pub use self::confirmations::{
 	ConfirmationPayload, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken,
-	TransactionModification, SignRequest, DecryptRequest, Either
+	TransactionModification, SignRequest, DecryptRequest, EIP712SignRequest, Either
 };
 pub use self::consensus_status::*;
+pub use self::debug_trace::{TraceConfig, DebugTrace, TransactionDebugTrace};
 pub use self::derivation::{DeriveHash, DeriveHierarchical, Derive};
+pub use self::eip712::{EIP712, EIP712Field};
+pub use self::fee_history::FeeHistory;
 pub use self::filter::{Filter, FilterChanges};
 pub use self::hash::{H64, H160, H256, H512, H520, H2048};
 pub use self::histogram::Histogram;
@@ -67,18 +76,19 @@ pub use self::provenance::Origin;
 pub use self::receipt::Receipt;
 pub use self::rpc_settings::RpcSettings;
 pub use self::secretstore::EncryptedDocumentKey;
+pub use self::state_range::{StorageRange, AccountRange};
 pub use self::sync::{
 	SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
 	TransactionStats, ChainStatus, EthProtocolInfo, PipProtocolInfo,
 };
 pub use self::trace::{LocalizedTrace, TraceResults, TraceResultsWithTransactionHash};
 pub use self::trace_filter::TraceFilter;
-pub use self::transaction::{Transaction, RichRawTransaction, LocalTransactionStatus};
+pub use self::transaction::{Transaction, RichRawTransaction, LocalTransactionStatus, AccessListItem};
 pub use self::transaction_request::TransactionRequest;
 pub use self::transaction_condition::TransactionCondition;
 pub use self::uint::{U128, U256, U64};
 pub use self::work::Work;
-pub use self::private_receipt::{PrivateTransactionReceipt, PrivateTransactionReceiptAndTransaction};
+pub use self::private_receipt::{PrivateTransactionReceipt, PrivateTransactionReceiptAndTransaction, PrivateTransactionStatus};
 
 // TODO [ToDr] Refactor to a proper type Vec of enums?
 /// Expected tracing type.
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use v1::types::{H160, H256, TransactionRequest};
-use ethcore_private_tx::{Receipt as EthPrivateReceipt};
+use ethcore_private_tx::{Receipt as EthPrivateReceipt, PrivateTransactionStatus as EthPrivateTransactionStatus};
 
 /// Receipt
 #[derive(Debug, Serialize)]
@@ -51,3 +51,35 @@ pub struct PrivateTransactionReceiptAndTransaction {
 	#[serde(rename="transaction")]
 	pub transaction: TransactionRequest,
 }
+
+/// Status of private transaction, waiting for validators signatures
+#[derive(Debug, Serialize)]
+pub struct PrivateTransactionStatus {
+	/// Private transaction hash
+	#[serde(rename="privateTransactionHash")]
+	pub private_transaction_hash: H256,
+	/// Original transaction hash
+	#[serde(rename="transactionHash")]
+	pub transaction_hash: H256,
+	/// Private contract address
+	#[serde(rename="contractAddress")]
+	pub contract_address: H160,
+	/// Validators, which have signed the private state
+	#[serde(rename="signedValidators")]
+	pub signed_validators: Vec<H160>,
+	/// Validators, which haven't signed the private state yet
+	#[serde(rename="pendingValidators")]
+	pub pending_validators: Vec<H160>,
+}
+
+impl From<EthPrivateTransactionStatus> for PrivateTransactionStatus {
+	fn from(s: EthPrivateTransactionStatus) -> Self {
+		PrivateTransactionStatus {
+			private_transaction_hash: s.private_transaction_hash.into(),
+			transaction_hash: s.transaction_hash.into(),
+			contract_address: s.contract_address.into(),
+			signed_validators: s.signed_validators.into_iter().map(Into::into).collect(),
+			pending_validators: s.pending_validators.into_iter().map(Into::into).collect(),
+		}
+	}
+}
@@ -19,10 +19,11 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::Error;
 use serde_json::{Value, from_value};
-use v1::types::{RichHeader, Filter, Log, H256};
+use miner::pool::{self, DropReason};
+use v1::types::{RichHeader, Filter, Log, Transaction, H256};
 
 /// Subscription result.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Result {
 	/// New block header.
 	Header(RichHeader),
@@ -30,6 +31,10 @@ pub enum Result {
 	Log(Log),
 	/// Transaction hash
 	TransactionHash(H256),
+	/// Full transaction
+	FullTransaction(Transaction),
+	/// Transaction dropped from the queue
+	DroppedTransaction(DroppedTransaction),
 }
 
 impl Serialize for Result {
@@ -40,6 +45,37 @@ impl Serialize for Result {
 			Result::Header(ref header) => header.serialize(serializer),
 			Result::Log(ref log) => log.serialize(serializer),
 			Result::TransactionHash(ref hash) => hash.serialize(serializer),
+			Result::FullTransaction(ref transaction) => transaction.serialize(serializer),
+			Result::DroppedTransaction(ref dropped) => dropped.serialize(serializer),
+		}
+	}
+}
+
+/// Transaction that left the queue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DroppedTransaction {
+	/// Transaction hash
+	pub hash: H256,
+	/// Reason: `replaced`, `evicted`, `invalid` or `canceled`
+	pub reason: String,
+	/// Hash of the transaction that replaced or evicted this one, if known
+	#[serde(rename="replacedBy")]
+	pub replaced_by: Option<H256>,
+}
+
+impl<'a> From<&'a pool::DroppedTransaction> for DroppedTransaction {
+	fn from(dropped: &'a pool::DroppedTransaction) -> Self {
+		let (reason, replaced_by) = match dropped.reason {
+			DropReason::Replaced(ref hash) => ("replaced", Some(hash.clone().into())),
+			DropReason::Evicted(ref hash) => ("evicted", hash.clone().map(Into::into)),
+			DropReason::Invalid => ("invalid", None),
+			DropReason::Canceled => ("canceled", None),
+		};
+
+		DroppedTransaction {
+			hash: (*dropped.transaction.hash()).into(),
+			reason: reason.into(),
+			replaced_by,
 		}
 	}
 }
@@ -60,6 +96,9 @@ pub enum Kind {
 	/// Node syncing status subscription.
 	#[serde(rename="syncing")]
 	Syncing,
+	/// Transactions dropped from the queue subscription.
+	#[serde(rename="droppedPendingTransactions")]
+	DroppedPendingTransactions,
 }
 
 /// Subscription kind.
@@ -69,6 +108,17 @@ pub enum Params {
 	None,
 	/// Log parameters.
 	Logs(Filter),
+	/// Pending transactions parameters.
+	Transactions(TransactionsParams),
+}
+
+/// Parameters of the pending transactions subscription.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionsParams {
+	/// Send full transaction objects instead of hashes.
+	#[serde(rename="includeTransactions", default)]
+	pub include_transactions: bool,
 }
 
 impl Default for Params {
@@ -86,6 +136,11 @@ impl<'a> Deserialize<'a> for Params {
 			return Ok(Params::None);
 		}
 
+		if v.get("includeTransactions").is_some() {
+			return from_value(v).map(Params::Transactions)
+				.map_err(|e| D::Error::custom(format!("Invalid Pub-Sub parameters: {}", e)));
+		}
+
 		from_value(v.clone()).map(Params::Logs)
 			.map_err(|e| D::Error::custom(format!("Invalid Pub-Sub parameters: {}", e)))
 	}
@@ -94,7 +149,9 @@ impl<'a> Deserialize<'a> for Params {
 #[cfg(test)]
 mod tests {
 	use serde_json;
-	use super::{Result, Kind, Params};
+	use std::sync::Arc;
+	use miner::pool::{self, DropReason, VerifiedTransaction};
+	use super::{Result, Kind, Params, TransactionsParams, DroppedTransaction};
 	use v1::types::{RichHeader, Header, Filter};
 	use v1::types::filter::VariadicValue;
 
@@ -104,6 +161,27 @@ mod tests {
 		assert_eq!(serde_json::from_str::<Kind>(r#""logs""#).unwrap(), Kind::Logs);
 		assert_eq!(serde_json::from_str::<Kind>(r#""newPendingTransactions""#).unwrap(), Kind::NewPendingTransactions);
 		assert_eq!(serde_json::from_str::<Kind>(r#""syncing""#).unwrap(), Kind::Syncing);
+		assert_eq!(serde_json::from_str::<Kind>(r#""droppedPendingTransactions""#).unwrap(), Kind::DroppedPendingTransactions);
+	}
+
+	#[test]
+	fn should_deserialize_transactions_params() {
+		let full = serde_json::from_str::<Params>(r#"{"includeTransactions":true}"#).unwrap();
+		let hashes = serde_json::from_str::<Params>(r#"{"includeTransactions":false}"#).unwrap();
+		assert_eq!(full, Params::Transactions(TransactionsParams { include_transactions: true }));
+		assert_eq!(hashes, Params::Transactions(TransactionsParams { include_transactions: false }));
+		assert!(serde_json::from_str::<Params>(r#"{"includeTransactions":true,"limit":10}"#).is_err());
+	}
+
+	#[test]
+	fn should_serialize_dropped_transaction() {
+		let tx = Arc::new(VerifiedTransaction::from_pending_block_transaction(::transaction::Transaction::default().fake_sign(5.into())));
+		let dropped = Result::DroppedTransaction(DroppedTransaction::from(&pool::DroppedTransaction {
+			transaction: tx.clone(),
+			reason: DropReason::Replaced(10.into()),
+		}));
+		let expected = format!(r#"{{"hash":"0x{:x}","reason":"replaced","replacedBy":"0x000000000000000000000000000000000000000000000000000000000000000a"}}"#, tx.hash());
+		assert_eq!(serde_json::to_string(&dropped).unwrap(), expected);
 	}
 
 	#[test]
@@ -160,6 +238,7 @@ mod tests {
 				number: Some(Default::default()),
 				gas_used: Default::default(),
 				gas_limit: Default::default(),
+				base_fee_per_gas: None,
 				extra_data: Default::default(),
 				logs_bloom: Default::default(),
 				timestamp: Default::default(),
@@ -0,0 +1,176 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Geth-compatible `debug_storageRangeAt` and `debug_accountRange` types.
+
+use std::collections::BTreeMap;
+use ethcore::state_range;
+use v1::types::{Bytes, H160, H256};
+
+/// A storage slot.
+#[derive(Debug, Serialize)]
+pub struct StorageEntry {
+	/// Slot key, if its preimage is known.
+	pub key: Option<H256>,
+	/// Slot value.
+	pub value: H256,
+}
+
+/// Storage slots of an account, keyed by hashed slot key.
+#[derive(Debug, Serialize)]
+pub struct StorageRange {
+	/// Storage slots.
+	pub storage: BTreeMap<H256, StorageEntry>,
+	/// Hashed key of the first slot of the next page.
+	#[serde(rename="nextKey")]
+	pub next_key: Option<H256>,
+}
+
+impl From<state_range::StorageRange> for StorageRange {
+	fn from(range: state_range::StorageRange) -> Self {
+		StorageRange {
+			storage: range.storage.into_iter().map(|slot| (slot.hashed_key.into(), StorageEntry {
+				key: slot.key.map(Into::into),
+				value: ::ethereum_types::H256::from(slot.value).into(),
+			})).collect(),
+			next_key: range.next_key.map(Into::into),
+		}
+	}
+}
+
+/// A single account of an account range.
+#[derive(Debug, Serialize)]
+pub struct RangeAccount {
+	/// Balance in decimal.
+	pub balance: String,
+	/// Nonce.
+	pub nonce: u64,
+	/// Storage root.
+	pub root: H256,
+	/// Code hash.
+	#[serde(rename="codeHash")]
+	pub code_hash: H256,
+	/// Code.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub code: Option<Bytes>,
+	/// Storage slots, keyed by slot key, or by hashed slot key if the preimage is not known.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<BTreeMap<H256, H256>>,
+	/// Hashed key of the first storage slot not included in `storage`.
+	#[serde(rename="nextStorageKey", skip_serializing_if = "Option::is_none")]
+	pub next_storage_key: Option<H256>,
+	/// Address, if known.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub address: Option<H160>,
+	/// Hashed address.
+	pub key: H256,
+}
+
+impl From<state_range::RangeAccount> for RangeAccount {
+	fn from(account: state_range::RangeAccount) -> Self {
+		RangeAccount {
+			balance: account.account.balance.to_string(),
+			nonce: account.account.nonce.low_u64(),
+			root: account.account.storage_root.into(),
+			code_hash: account.account.code_hash.into(),
+			code: account.code.and_then(|code| if code.is_empty() { None } else { Some(code.into()) }),
+			storage: account.storage.map(|storage| storage.into_iter().map(|slot| (
+				slot.key.unwrap_or(slot.hashed_key).into(),
+				::ethereum_types::H256::from(slot.value).into(),
+			)).collect()),
+			next_storage_key: account.storage_next.map(Into::into),
+			address: account.address.map(Into::into),
+			key: account.hashed_address.into(),
+		}
+	}
+}
+
+/// Accounts of the state trie.
+#[derive(Debug, Serialize)]
+pub struct AccountRange {
+	/// State root.
+	pub root: H256,
+	/// Accounts keyed by address, or by `pre(<hashed address>)` if the address is not known.
+	pub accounts: BTreeMap<String, RangeAccount>,
+	/// Hashed address of the first account of the next page.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub next: Option<H256>,
+}
+
+impl AccountRange {
+	/// Converts the range, skipping accounts with unknown addresses unless `incompletes` is set.
+	pub fn new(range: state_range::AccountRange, incompletes: bool) -> Self {
+		AccountRange {
+			root: range.root.into(),
+			accounts: range.accounts.into_iter()
+				.filter(|account| incompletes || account.address.is_some())
+				.map(|account| {
+					let key = match account.address {
+						Some(ref address) => format!("{:#x}", address),
+						None => format!("pre({:#x})", account.hashed_address),
+					};
+					(key, account.into())
+				})
+				.collect(),
+			next: range.next.map(Into::into),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethcore::state_range::{self, StorageSlot};
+	use ethcore::basic_account::BasicAccount;
+	use super::{StorageRange, AccountRange};
+
+	#[test]
+	fn should_serialize_storage_range() {
+		let range = StorageRange::from(state_range::StorageRange {
+			storage: vec![StorageSlot { hashed_key: 1.into(), key: None, value: 2.into() }],
+			next_key: Some(3.into()),
+		});
+		let serialized = serde_json::to_string(&range).unwrap();
+		assert_eq!(serialized, r#"{"storage":{"0x0000000000000000000000000000000000000000000000000000000000000001":{"key":null,"value":"0x0000000000000000000000000000000000000000000000000000000000000002"}},"nextKey":"0x0000000000000000000000000000000000000000000000000000000000000003"}"#);
+	}
+
+	#[test]
+	fn should_skip_incomplete_accounts() {
+		let account = |address| state_range::RangeAccount {
+			hashed_address: 5.into(),
+			address,
+			account: BasicAccount {
+				nonce: 1.into(),
+				balance: 1000.into(),
+				storage_root: 0.into(),
+				code_hash: 0.into(),
+			},
+			code: None,
+			storage: None,
+			storage_next: None,
+		};
+		let range = state_range::AccountRange {
+			root: 0.into(),
+			accounts: vec![account(Some(1.into())), account(None)],
+			next: None,
+		};
+
+		assert_eq!(AccountRange::new(range.clone(), false).accounts.len(), 1);
+		let complete = AccountRange::new(range, true);
+		assert!(complete.accounts.contains_key("pre(0x0000000000000000000000000000000000000000000000000000000000000005)"));
+		assert_eq!(complete.accounts["0x0000000000000000000000000000000000000001"].balance, "1000");
+	}
+}
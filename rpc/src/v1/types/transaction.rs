@@ -20,7 +20,7 @@ use serde::{Serialize, Serializer};
 use serde::ser::SerializeStruct;
 use ethcore::{contract_address, CreateContractAddress};
 use miner;
-use transaction::{LocalizedTransaction, Action, PendingTransaction, SignedTransaction};
+use transaction::{self, LocalizedTransaction, Action, PendingTransaction, SignedTransaction, UnverifiedTransaction, TypedTxId};
 use v1::types::{Bytes, H160, H256, U256, H512, U64, TransactionCondition};
 
 /// Transaction
@@ -73,6 +73,65 @@ pub struct Transaction {
 	pub s: U256,
 	/// Transaction activates at specified block.
 	pub condition: Option<TransactionCondition>,
+	/// Transaction type (EIP-2718), omitted for legacy transactions.
+	#[serde(rename="type", skip_serializing_if = "Option::is_none")]
+	pub transaction_type: Option<U64>,
+	/// Access list (EIP-2930), omitted for legacy transactions.
+	#[serde(rename="accessList", skip_serializing_if = "Option::is_none")]
+	pub access_list: Option<Vec<AccessListItem>>,
+	/// Max fee per gas (EIP-1559), omitted for other transaction types.
+	#[serde(rename="maxFeePerGas", skip_serializing_if = "Option::is_none")]
+	pub max_fee_per_gas: Option<U256>,
+	/// Max priority fee per gas (EIP-1559), omitted for other transaction types.
+	#[serde(rename="maxPriorityFeePerGas", skip_serializing_if = "Option::is_none")]
+	pub max_priority_fee_per_gas: Option<U256>,
+}
+
+/// Access list entry (EIP-2930)
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessListItem {
+	/// Accessed account
+	pub address: H160,
+	/// Accessed storage keys of the account
+	#[serde(rename="storageKeys")]
+	pub storage_keys: Vec<H256>,
+}
+
+impl From<transaction::AccessListItem> for AccessListItem {
+	fn from(item: transaction::AccessListItem) -> Self {
+		AccessListItem {
+			address: item.address.into(),
+			storage_keys: item.storage_keys.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+impl Into<transaction::AccessListItem> for AccessListItem {
+	fn into(self) -> transaction::AccessListItem {
+		transaction::AccessListItem {
+			address: self.address.into(),
+			storage_keys: self.storage_keys.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+/// Returns the RPC type and access list fields of a transaction.
+fn typed_fields(t: &UnverifiedTransaction) -> (Option<U64>, Option<Vec<AccessListItem>>) {
+	match t.transaction_type().to_byte() {
+		None => (None, None),
+		Some(b) => (
+			Some(U64::from(b as u64)),
+			Some(t.access_list().iter().cloned().map(Into::into).collect()),
+		),
+	}
+}
+
+/// Returns the max fee and max priority fee fields of a dynamic fee transaction.
+fn fee_fields(t: &UnverifiedTransaction) -> (Option<U256>, Option<U256>) {
+	match t.transaction_type() {
+		TypedTxId::EIP1559Transaction => (Some(t.gas_price.into()), Some(t.max_priority_fee_per_gas().into())),
+		_ => (None, None),
+	}
 }
 
 /// Local Transaction Status
@@ -181,6 +240,8 @@ impl Transaction {
 	pub fn from_localized(mut t: LocalizedTransaction) -> Transaction {
 		let signature = t.signature();
 		let scheme = CreateContractAddress::FromSenderAndNonce;
+		let (transaction_type, access_list) = typed_fields(&t.signed);
+		let (max_fee_per_gas, max_priority_fee_per_gas) = fee_fields(&t.signed);
 		Transaction {
 			hash: t.hash().into(),
 			nonce: t.nonce.into(),
@@ -200,7 +261,7 @@ impl Transaction {
 				Action::Create => Some(contract_address(scheme, &t.sender(), &t.nonce, &t.data).0.into()),
 				Action::Call(_) => None,
 			},
-			raw: ::rlp::encode(&t.signed).into(),
+			raw: t.signed.encode_envelope().into(),
 			public_key: t.recover_public().ok().map(Into::into),
 			chain_id: t.chain_id().map(U64::from),
 			standard_v: t.standard_v().into(),
@@ -208,6 +269,10 @@ impl Transaction {
 			r: signature.r().into(),
 			s: signature.s().into(),
 			condition: None,
+			transaction_type: transaction_type,
+			access_list: access_list,
+			max_fee_per_gas: max_fee_per_gas,
+			max_priority_fee_per_gas: max_priority_fee_per_gas,
 		}
 	}
 
@@ -215,6 +280,8 @@ impl Transaction {
 	pub fn from_signed(t: SignedTransaction) -> Transaction {
 		let signature = t.signature();
 		let scheme = CreateContractAddress::FromSenderAndNonce;
+		let (transaction_type, access_list) = typed_fields(&t);
+		let (max_fee_per_gas, max_priority_fee_per_gas) = fee_fields(&t);
 		Transaction {
 			hash: t.hash().into(),
 			nonce: t.nonce.into(),
@@ -234,7 +301,7 @@ impl Transaction {
 				Action::Create => Some(contract_address(scheme, &t.sender(), &t.nonce, &t.data).0.into()),
 				Action::Call(_) => None,
 			},
-			raw: ::rlp::encode(&t).into(),
+			raw: t.encode_envelope().into(),
 			public_key: t.public_key().map(Into::into),
 			chain_id: t.chain_id().map(U64::from),
 			standard_v: t.standard_v().into(),
@@ -242,6 +309,10 @@ impl Transaction {
 			r: signature.r().into(),
 			s: signature.s().into(),
 			condition: None,
+			transaction_type: transaction_type,
+			access_list: access_list,
+			max_fee_per_gas: max_fee_per_gas,
+			max_priority_fee_per_gas: max_priority_fee_per_gas,
 		}
 	}
 
@@ -279,7 +350,7 @@ impl LocalTransactionStatus {
 
 #[cfg(test)]
 mod tests {
-	use super::{Transaction, LocalTransactionStatus};
+	use super::{Transaction, LocalTransactionStatus, AccessListItem};
 	use serde_json;
 
 	#[test]
@@ -289,6 +360,33 @@ mod tests {
 		assert_eq!(serialized, r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000000","nonce":"0x0","blockHash":null,"blockNumber":null,"transactionIndex":null,"from":"0x0000000000000000000000000000000000000000","to":null,"value":"0x0","gasPrice":"0x0","gas":"0x0","input":"0x","creates":null,"raw":"0x","publicKey":null,"chainId":null,"standardV":"0x0","v":"0x0","r":"0x0","s":"0x0","condition":null}"#);
 	}
 
+	#[test]
+	fn test_typed_transaction_serialize() {
+		let t = Transaction {
+			transaction_type: Some(1.into()),
+			access_list: Some(vec![AccessListItem {
+				address: 1.into(),
+				storage_keys: vec![2.into()],
+			}]),
+			..Transaction::default()
+		};
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert!(serialized.ends_with(r#""condition":null,"type":"0x1","accessList":[{"address":"0x0000000000000000000000000000000000000001","storageKeys":["0x0000000000000000000000000000000000000000000000000000000000000002"]}]}"#));
+	}
+
+	#[test]
+	fn test_dynamic_fee_transaction_serialize() {
+		let t = Transaction {
+			transaction_type: Some(2.into()),
+			access_list: Some(vec![]),
+			max_fee_per_gas: Some(10.into()),
+			max_priority_fee_per_gas: Some(2.into()),
+			..Transaction::default()
+		};
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert!(serialized.ends_with(r#""condition":null,"type":"0x2","accessList":[],"maxFeePerGas":"0xa","maxPriorityFeePerGas":"0x2"}"#));
+	}
+
 	#[test]
 	fn test_local_transaction_status_serialize() {
 		let tx_ser = serde_json::to_string(&Transaction::default()).unwrap();
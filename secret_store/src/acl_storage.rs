@@ -16,23 +16,76 @@
 
 use std::sync::Arc;
 use std::collections::{HashMap, HashSet};
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use parking_lot::{Mutex, RwLock};
-use ethcore::client::{BlockId, ChainNotify, ChainRoute, CallContract};
-use ethereum_types::{H256, Address};
+use byteorder::{BigEndian, ByteOrder};
+use ethcore::client::{BlockId, ChainNotify, ChainRoute, CallContract, Client};
+use ethereum_types::{H256, U256, Address};
 use ethabi::FunctionOutputDecoder;
+use kvdb::KeyValueDB;
 use bytes::Bytes;
 use trusted_client::TrustedClient;
 use types::{Error, ServerKeyId, ContractAddress};
 
 use_contract!(acl_storage, "res/acl_storage.json");
+use_contract!(acl_storage_v2, "res/acl_storage_v2.json");
 
 const ACL_CHECKER_CONTRACT_REGISTRY_NAME: &'static str = "secretstore_acl_checker";
 
+/// Operation id, used to cache result of the `check` call (which isn't bound to any operation).
+const ANY_OPERATION_ID: u8 = 0xff;
+/// Size of cached permission entry: allowed flag + valid until + cached at.
+const CACHED_PERMISSION_SIZE: usize = 1 + 8 + 8;
+
 /// ACL storage of Secret Store
 pub trait AclStorage: Send + Sync {
 	/// Check if requestor can access document with hash `document`
 	fn check(&self, requester: Address, document: &ServerKeyId) -> Result<bool, Error>;
+	/// Read permission of requestor to perform given operation on document with hash `document`.
+	/// Storages without per-operation permissions grant every operation if `check` passes.
+	fn permission(&self, requester: Address, document: &ServerKeyId, _operation: AclOperation) -> Result<AclPermission, Error> {
+		self.check(requester, document).map(AclPermission::unlimited)
+	}
+	/// Check if storage has per-operation permissions. Otherwise every operation is granted by `check`.
+	fn has_operation_permissions(&self) -> Result<bool, Error> {
+		Ok(false)
+	}
+	/// Check if requestor can perform given operation on document with hash `document`
+	fn check_operation(&self, requester: Address, document: &ServerKeyId, operation: AclOperation) -> Result<bool, Error> {
+		self.permission(requester, document, operation).map(|permission| permission.is_allowed_at(unix_timestamp()))
+	}
+}
+
+/// Operation on the server key, which is permissioned by the ACL storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AclOperation {
+	/// Document key (shadow) retrieval.
+	Decrypt,
+	/// Message signing.
+	Sign,
+	/// Adding new shares of the server key.
+	ShareAdd,
+}
+
+/// Permission to perform an operation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AclPermission {
+	/// Is operation allowed?
+	pub allowed: bool,
+	/// Unix timestamp (in seconds) of the last moment when the grant is valid. None if the grant is not time-bounded.
+	pub valid_until: Option<u64>,
+}
+
+/// ACL storage, which persists permissions read from the inner storage. Persisted permissions are used
+/// when the inner storage is unable to answer (i.e. when the blockchain client is syncing or unavailable),
+/// unless they are older than the configured window.
+pub struct CachedAclStorage {
+	/// Inner ACL storage.
+	storage: Arc<AclStorage>,
+	/// Permissions cache database.
+	db: Arc<KeyValueDB>,
+	/// Time window, within which cached permissions are valid.
+	window: Duration,
 }
 
 /// On-chain ACL storage implementation.
@@ -49,12 +102,24 @@ struct CachedContract {
 	address_source: ContractAddress,
 	/// Current contract address.
 	contract_address: Option<Address>,
+	/// Current contract ABI version. None if not yet detected.
+	contract_version: Option<AclContractVersion>,
+}
+
+/// Version of the ACL contract ABI.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AclContractVersion {
+	/// Single `checkPermissions` method for all operations.
+	V1,
+	/// Per-operation permissions with time-bounded grants.
+	V2,
 }
 
 /// Dummy ACL storage implementation (check always passed).
 #[derive(Default, Debug)]
 pub struct DummyAclStorage {
 	prohibited: RwLock<HashMap<Address, HashSet<ServerKeyId>>>,
+	prohibited_operations: RwLock<HashMap<(Address, ServerKeyId), HashSet<AclOperation>>>,
 }
 
 impl OnChainAclStorage {
@@ -74,6 +139,14 @@ impl AclStorage for OnChainAclStorage {
 	fn check(&self, requester: Address, document: &ServerKeyId) -> Result<bool, Error> {
 		self.contract.lock().check(requester, document)
 	}
+
+	fn permission(&self, requester: Address, document: &ServerKeyId, operation: AclOperation) -> Result<AclPermission, Error> {
+		self.contract.lock().permission(requester, document, operation)
+	}
+
+	fn has_operation_permissions(&self) -> Result<bool, Error> {
+		self.contract.lock().has_operation_permissions()
+	}
 }
 
 impl ChainNotify for OnChainAclStorage {
@@ -90,6 +163,7 @@ impl CachedContract {
 			client,
 			address_source,
 			contract_address: None,
+			contract_version: None,
 		};
 		contract.update_contract_address();
 		contract
@@ -102,6 +176,7 @@ impl CachedContract {
 				contract_address);
 
 			self.contract_address = contract_address;
+			self.contract_version = None;
 		}
 	}
 
@@ -122,6 +197,127 @@ impl CachedContract {
 			Err(Error::Internal("Calling ACL contract without trusted blockchain client".into()))
 		}
 	}
+
+	pub fn permission(&mut self, requester: Address, document: &ServerKeyId, operation: AclOperation) -> Result<AclPermission, Error> {
+		let client = self.client.get()
+			.ok_or_else(|| Error::Internal("Calling ACL contract without trusted blockchain client".into()))?;
+		let contract_address = self.contract_address
+			.ok_or_else(|| Error::Internal("ACL checker contract is not configured".to_owned()))?;
+
+		match self.contract_version(&*client, contract_address)? {
+			AclContractVersion::V1 => self.check(requester, document).map(AclPermission::unlimited),
+			AclContractVersion::V2 => {
+				let permission = read_operation_permission(&*client, contract_address, requester, document, operation);
+				if permission.is_err() {
+					// the contract could have been misdetected => detect version again on next request
+					self.contract_version = None;
+				}
+				permission
+			},
+		}
+	}
+
+	pub fn has_operation_permissions(&mut self) -> Result<bool, Error> {
+		let client = self.client.get()
+			.ok_or_else(|| Error::Internal("Calling ACL contract without trusted blockchain client".into()))?;
+		let contract_address = self.contract_address
+			.ok_or_else(|| Error::Internal("ACL checker contract is not configured".to_owned()))?;
+
+		self.contract_version(&*client, contract_address)
+			.map(|contract_version| contract_version == AclContractVersion::V2)
+	}
+
+	fn contract_version(&mut self, client: &Client, contract_address: Address) -> Result<AclContractVersion, Error> {
+		if let Some(contract_version) = self.contract_version {
+			return Ok(contract_version);
+		}
+
+		let contract_version = read_contract_version(client, contract_address)?;
+		trace!(target: "secretstore", "Detected ACL checker contract version {:?}", contract_version);
+
+		self.contract_version = Some(contract_version);
+		Ok(contract_version)
+	}
+}
+
+impl AclOperation {
+	/// Operation id, as it is passed to the ACL contract.
+	pub fn id(&self) -> u8 {
+		match *self {
+			AclOperation::Decrypt => 0,
+			AclOperation::Sign => 1,
+			AclOperation::ShareAdd => 2,
+		}
+	}
+}
+
+impl AclPermission {
+	/// Create permission, which is not time-bounded.
+	pub fn unlimited(allowed: bool) -> Self {
+		AclPermission {
+			allowed,
+			valid_until: None,
+		}
+	}
+
+	/// Check if operation is allowed at given moment.
+	pub fn is_allowed_at(&self, timestamp: u64) -> bool {
+		self.allowed && self.valid_until.map(|valid_until| timestamp <= valid_until).unwrap_or(true)
+	}
+}
+
+impl CachedAclStorage {
+	pub fn new(storage: Arc<AclStorage>, db: Arc<KeyValueDB>, window: Duration) -> Self {
+		CachedAclStorage {
+			storage,
+			db,
+			window,
+		}
+	}
+
+	/// Read permission from the inner storage, falling back to the cached permission if inner storage fails.
+	fn cached_permission<F>(&self, requester: Address, document: &ServerKeyId, operation_id: u8, now: u64, read: F) -> Result<AclPermission, Error>
+		where F: FnOnce(&AclStorage) -> Result<AclPermission, Error> {
+		let db_key = cache_db_key(&requester, document, operation_id);
+		match read(&*self.storage) {
+			Ok(permission) => {
+				let mut batch = self.db.transaction();
+				batch.put(None, &db_key, &serialize_cached_permission(&permission, now));
+				if let Err(error) = self.db.write(batch) {
+					warn!(target: "secretstore", "Failed to cache ACL permission of {:?} on {:?}: {}", requester, document, error);
+				}
+				Ok(permission)
+			},
+			Err(error) => {
+				let cached = self.db.get(None, &db_key)?
+					.and_then(|value| deserialize_cached_permission(&value));
+				match cached {
+					Some((permission, cached_at)) if now.saturating_sub(cached_at) <= self.window.as_secs() => {
+						trace!(target: "secretstore", "Using cached ACL permission of {:?} on {:?}: {}", requester, document, error);
+						Ok(permission)
+					},
+					_ => Err(error),
+				}
+			},
+		}
+	}
+}
+
+impl AclStorage for CachedAclStorage {
+	fn check(&self, requester: Address, document: &ServerKeyId) -> Result<bool, Error> {
+		self.cached_permission(requester, document, ANY_OPERATION_ID, unix_timestamp(),
+			|storage| storage.check(requester, document).map(AclPermission::unlimited))
+			.map(|permission| permission.allowed)
+	}
+
+	fn permission(&self, requester: Address, document: &ServerKeyId, operation: AclOperation) -> Result<AclPermission, Error> {
+		self.cached_permission(requester, document, operation.id(), unix_timestamp(),
+			|storage| storage.permission(requester, document, operation))
+	}
+
+	fn has_operation_permissions(&self) -> Result<bool, Error> {
+		self.storage.has_operation_permissions()
+	}
 }
 
 impl DummyAclStorage {
@@ -133,6 +329,15 @@ impl DummyAclStorage {
 			.or_insert_with(Default::default)
 			.insert(document);
 	}
+
+	/// Prohibit given requestor to perform given operation on given documents
+	#[cfg(test)]
+	pub fn prohibit_operation(&self, requester: Address, document: ServerKeyId, operation: AclOperation) {
+		self.prohibited_operations.write()
+			.entry((requester, document))
+			.or_insert_with(Default::default)
+			.insert(operation);
+	}
 }
 
 impl AclStorage for DummyAclStorage {
@@ -142,4 +347,195 @@ impl AclStorage for DummyAclStorage {
 			.map(|docs| !docs.contains(document))
 			.unwrap_or(true))
 	}
+
+	fn permission(&self, requester: Address, document: &ServerKeyId, operation: AclOperation) -> Result<AclPermission, Error> {
+		let is_operation_prohibited = self.prohibited_operations.read()
+			.get(&(requester, document.clone()))
+			.map(|operations| operations.contains(&operation))
+			.unwrap_or(false);
+		self.check(requester, document).map(|allowed| AclPermission::unlimited(allowed && !is_operation_prohibited))
+	}
+
+	fn has_operation_permissions(&self) -> Result<bool, Error> {
+		Ok(true)
+	}
+}
+
+/// Detect ABI version of the ACL contract. Contracts, which revert the `aclVersion` call, are treated as V1.
+fn read_contract_version(client: &Client, contract_address: Address) -> Result<AclContractVersion, Error> {
+	let (encoded, decoder) = acl_storage_v2::functions::acl_version::call();
+	let d = client.call_contract(BlockId::Latest, contract_address, encoded)
+		.map_err(|e| Error::Internal(format!("ACL checker version call error: {}", e.to_string())))?;
+	// reverted call has no (decodable) output
+	match decoder.decode(&d) {
+		Ok(ref version) if *version >= 2.into() => Ok(AclContractVersion::V2),
+		_ => Ok(AclContractVersion::V1),
+	}
+}
+
+/// Read permission from the V2 ACL contract.
+fn read_operation_permission(client: &Client, contract_address: Address, requester: Address, document: &ServerKeyId, operation: AclOperation) -> Result<AclPermission, Error> {
+	let (encoded, decoder) = acl_storage_v2::functions::check_operation_permissions::call(requester, document.clone(), operation.id());
+	let d = client.call_contract(BlockId::Latest, contract_address, encoded)
+		.map_err(|e| Error::Internal(format!("ACL checker call error: {}", e.to_string())))?;
+	let (allowed, valid_until) = decoder.decode(&d)
+		.map_err(|e| Error::Internal(format!("ACL checker call error: {}", e.to_string())))?;
+	Ok(AclPermission {
+		allowed,
+		valid_until: parse_valid_until(valid_until),
+	})
+}
+
+/// Parse `validUntil` returned by the ACL contract. Zero means that the grant is not time-bounded.
+fn parse_valid_until(valid_until: U256) -> Option<u64> {
+	match valid_until.is_zero() {
+		true => None,
+		false if valid_until > u64::max_value().into() => Some(u64::max_value()),
+		false => Some(valid_until.low_u64()),
+	}
+}
+
+/// Current unix timestamp (in seconds).
+fn unix_timestamp() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs())
+		.unwrap_or(0)
+}
+
+/// Key of cached permission in the cache database.
+fn cache_db_key(requester: &Address, document: &ServerKeyId, operation_id: u8) -> Vec<u8> {
+	let mut db_key = Vec::with_capacity(20 + 32 + 1);
+	db_key.extend_from_slice(&**requester);
+	db_key.extend_from_slice(&**document);
+	db_key.push(operation_id);
+	db_key
+}
+
+/// Serialize cached permission.
+fn serialize_cached_permission(permission: &AclPermission, cached_at: u64) -> Vec<u8> {
+	let mut value = vec![0u8; CACHED_PERMISSION_SIZE];
+	value[0] = permission.allowed as u8;
+	BigEndian::write_u64(&mut value[1..9], permission.valid_until.unwrap_or(0));
+	BigEndian::write_u64(&mut value[9..17], cached_at);
+	value
+}
+
+/// Deserialize cached permission. Returns None if the entry is corrupted.
+fn deserialize_cached_permission(value: &[u8]) -> Option<(AclPermission, u64)> {
+	if value.len() != CACHED_PERMISSION_SIZE {
+		return None;
+	}
+
+	let valid_until = BigEndian::read_u64(&value[1..9]);
+	Some((AclPermission {
+		allowed: value[0] != 0,
+		valid_until: if valid_until == 0 { None } else { Some(valid_until) },
+	}, BigEndian::read_u64(&value[9..17])))
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate tempdir;
+
+	use std::sync::Arc;
+	use std::time::Duration;
+	use parking_lot::Mutex;
+	use self::tempdir::TempDir;
+	use ethereum_types::{H256, Address};
+	use kvdb::KeyValueDB;
+	use kvdb_rocksdb::Database;
+	use types::{Error, ServerKeyId};
+	use super::{AclStorage, AclOperation, AclPermission, CachedAclStorage, DummyAclStorage};
+
+	/// ACL storage, which answers with configured permission or fails if there's no permission.
+	#[derive(Default)]
+	struct ScriptedAclStorage {
+		permission: Mutex<Option<AclPermission>>,
+	}
+
+	impl AclStorage for ScriptedAclStorage {
+		fn check(&self, _requester: Address, _document: &ServerKeyId) -> Result<bool, Error> {
+			self.permission.lock()
+				.map(|permission| permission.allowed)
+				.ok_or_else(|| Error::Internal("client is syncing".into()))
+		}
+
+		fn permission(&self, _requester: Address, _document: &ServerKeyId, _operation: AclOperation) -> Result<AclPermission, Error> {
+			self.permission.lock().clone()
+				.ok_or_else(|| Error::Internal("client is syncing".into()))
+		}
+	}
+
+	fn cached_storage(tempdir: &TempDir, window: u64) -> (Arc<ScriptedAclStorage>, CachedAclStorage) {
+		let db: Arc<KeyValueDB> = Arc::new(Database::open_default(&tempdir.path().display().to_string()).unwrap());
+		let storage = Arc::new(ScriptedAclStorage::default());
+		(storage.clone(), CachedAclStorage::new(storage, db, Duration::from_secs(window)))
+	}
+
+	#[test]
+	fn permission_is_time_bounded() {
+		let permission = AclPermission { allowed: true, valid_until: Some(100) };
+		assert!(permission.is_allowed_at(100));
+		assert!(!permission.is_allowed_at(101));
+		assert!(AclPermission::unlimited(true).is_allowed_at(u64::max_value()));
+		assert!(!AclPermission::unlimited(false).is_allowed_at(0));
+	}
+
+	#[test]
+	fn dummy_acl_storage_prohibits_single_operation() {
+		let requester = Address::from(1);
+		let document = H256::from(2);
+		let storage = DummyAclStorage::default();
+		storage.prohibit_operation(requester, document.clone(), AclOperation::Sign);
+
+		assert_eq!(storage.check_operation(requester, &document, AclOperation::Decrypt), Ok(true));
+		assert_eq!(storage.check_operation(requester, &document, AclOperation::Sign), Ok(false));
+		assert_eq!(storage.check_operation(requester, &document, AclOperation::ShareAdd), Ok(true));
+		assert_eq!(storage.check_operation(Address::from(3), &document, AclOperation::Sign), Ok(true));
+	}
+
+	#[test]
+	fn cached_permission_is_used_when_storage_is_unavailable() {
+		let tempdir = TempDir::new("").unwrap();
+		let (storage, cached) = cached_storage(&tempdir, 60);
+		let (requester, document) = (Address::from(1), H256::from(2));
+
+		// nothing is cached yet => error is returned
+		assert!(cached.cached_permission(requester, &document, AclOperation::Decrypt.id(), 1000,
+			|s| s.permission(requester, &document, AclOperation::Decrypt)).is_err());
+
+		// permission is read from the storage and cached
+		let permission = AclPermission { allowed: true, valid_until: Some(5000) };
+		*storage.permission.lock() = Some(permission);
+		assert_eq!(cached.cached_permission(requester, &document, AclOperation::Decrypt.id(), 1000,
+			|s| s.permission(requester, &document, AclOperation::Decrypt)), Ok(permission));
+
+		// storage becomes unavailable => cached permission is used within the window
+		*storage.permission.lock() = None;
+		assert_eq!(cached.cached_permission(requester, &document, AclOperation::Decrypt.id(), 1060,
+			|s| s.permission(requester, &document, AclOperation::Decrypt)), Ok(permission));
+
+		// cached permissions are per-operation
+		assert!(cached.cached_permission(requester, &document, AclOperation::Sign.id(), 1060,
+			|s| s.permission(requester, &document, AclOperation::Sign)).is_err());
+
+		// cached permission is outdated
+		assert!(cached.cached_permission(requester, &document, AclOperation::Decrypt.id(), 1061,
+			|s| s.permission(requester, &document, AclOperation::Decrypt)).is_err());
+	}
+
+	#[test]
+	fn cached_permission_is_replaced_with_fresh_one() {
+		let tempdir = TempDir::new("").unwrap();
+		let (storage, cached) = cached_storage(&tempdir, 60);
+		let (requester, document) = (Address::from(1), H256::from(2));
+
+		*storage.permission.lock() = Some(AclPermission::unlimited(true));
+		assert_eq!(cached.permission(requester, &document, AclOperation::ShareAdd), Ok(AclPermission::unlimited(true)));
+		*storage.permission.lock() = Some(AclPermission::unlimited(false));
+		assert_eq!(cached.permission(requester, &document, AclOperation::ShareAdd), Ok(AclPermission::unlimited(false)));
+
+		*storage.permission.lock() = None;
+		assert_eq!(cached.check_operation(requester, &document, AclOperation::ShareAdd), Ok(false));
+	}
 }
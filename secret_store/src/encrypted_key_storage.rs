@@ -0,0 +1,632 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Key storage, which seals every key share under node-local master key before writing it to the database.
+//!
+//! Every value is stored as `iv ++ AES-128-CTR(ciphertext) ++ MAC`, where the first half of the 256-bit master key
+//! is used as AES key and the second half is used to compute `MAC = KECCAK(key[16..32] ++ iv ++ ciphertext ++ db_key)`.
+//! Binding the MAC to the database key prevents sealed values from being moved between keys unnoticed.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use rand::{Rng, OsRng};
+use serde_json;
+use crypto::{self, Keccak256};
+use ethkey::{Password, Secret};
+use kvdb::KeyValueDB;
+use key_storage::{KeyStorage, DocumentKeyShare, Ed25519KeyShare, PersistentKeyStorage, DB_META_KEY_VERSION, SEALED_VERSION,
+	db_version, upgrade_db, serialize_key_share, deserialize_key_share, serialize_ed25519_key_share,
+	deserialize_ed25519_key_share, ed25519_db_key, ed25519_key_id, is_ed25519_db_key};
+use serialization::{SerializableBytes, SerializableH256};
+use types::{Error, ServerKeyId};
+
+/// Key of master key salt value.
+const DB_META_KEY_SALT: &'static [u8; 15] = b"master_key_salt";
+/// Key of master key check value.
+const DB_META_KEY_CHECK: &'static [u8; 16] = b"master_key_check";
+/// Plain value, sealed to check that master key is valid.
+const MASTER_KEY_CHECK: &'static [u8] = b"secretstore";
+/// Number of PBKDF2 iterations used to derive master key from passphrase.
+const KEY_ITERATIONS: u32 = 10240;
+/// Length of the AES initial vector.
+const IV_LENGTH: usize = 16;
+/// Length of the MAC.
+const MAC_LENGTH: usize = 32;
+/// Version of key shares export file.
+const EXPORT_VERSION: u8 = 1;
+
+/// Node-local master key, which is used to seal key shares at rest.
+#[derive(Clone)]
+pub enum MasterKey {
+	/// Master key is derived from passphrase and random per-database salt.
+	Passphrase(Password),
+	/// Raw 256-bit master key (read from the key file).
+	Raw(Secret),
+}
+
+/// Master key, ready to seal/open values.
+struct SealingKey {
+	/// AES-128 key.
+	encryption_key: Vec<u8>,
+	/// MAC key.
+	mac_key: Vec<u8>,
+}
+
+/// Document encryption keys storage, which seals every key share under node master key.
+pub struct EncryptedKeyStorage {
+	db: Arc<KeyValueDB>,
+	key: SealingKey,
+}
+
+/// Encrypted document encryption keys storage iterator. Yields an error for every share that can't be opened.
+pub struct EncryptedKeyStorageIterator<'a> {
+	iter: Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a>,
+	key: &'a SealingKey,
+}
+
+/// Key shares, exported from the key storage.
+#[derive(Serialize, Deserialize)]
+struct SerializableKeySharesExport {
+	/// Version of export format.
+	pub version: u8,
+	/// Salt, used to derive transfer key from transfer passphrase.
+	pub salt: SerializableH256,
+	/// Key shares, sealed under transfer key.
+	pub shares: BTreeMap<SerializableH256, SerializableBytes>,
+	/// Ed25519 key shares, sealed under transfer key.
+	#[serde(default)]
+	pub ed25519_shares: BTreeMap<SerializableH256, SerializableBytes>,
+}
+
+impl fmt::Debug for MasterKey {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			MasterKey::Passphrase(_) => write!(f, "MasterKey::Passphrase(******)"),
+			MasterKey::Raw(_) => write!(f, "MasterKey::Raw(******)"),
+		}
+	}
+}
+
+impl SealingKey {
+	/// Derive sealing key from given master key and salt.
+	fn new(master_key: &MasterKey, salt: &[u8; 32]) -> Self {
+		let (encryption_key, mac_key) = match *master_key {
+			MasterKey::Passphrase(ref passphrase) => crypto::derive_key_iterations(passphrase.as_bytes(), salt, KEY_ITERATIONS),
+			MasterKey::Raw(ref key) => {
+				let key: &[u8] = &**key;
+				(key[0..16].to_vec(), key[16..32].to_vec())
+			},
+		};
+
+		SealingKey {
+			encryption_key: encryption_key,
+			mac_key: mac_key,
+		}
+	}
+
+	/// Read salt from the database (or generate and write new salt) and derive sealing key.
+	fn from_db(db: &KeyValueDB, master_key: &MasterKey) -> Result<Self, Error> {
+		let salt = match db.get(None, DB_META_KEY_SALT)? {
+			Some(ref salt) if salt.len() == 32 => {
+				let mut result = [0u8; 32];
+				result.copy_from_slice(&salt);
+				result
+			},
+			Some(_) => return Err(Error::Database("invalid master key salt".into())),
+			None => {
+				let salt = random_salt()?;
+				let mut batch = db.transaction();
+				batch.put(None, DB_META_KEY_SALT, &salt);
+				db.write(batch)?;
+				salt
+			},
+		};
+
+		Ok(SealingKey::new(master_key, &salt))
+	}
+
+	/// Compute MAC of the sealed value, stored under given database key.
+	fn mac(&self, iv: &[u8], ciphertext: &[u8], db_key: &[u8]) -> [u8; 32] {
+		let mut authenticated = Vec::with_capacity(iv.len() + ciphertext.len() + db_key.len());
+		authenticated.extend_from_slice(iv);
+		authenticated.extend_from_slice(ciphertext);
+		authenticated.extend_from_slice(db_key);
+		crypto::derive_mac(&self.mac_key, &authenticated).keccak256()
+	}
+
+	/// Seal value to be stored under given database key.
+	fn seal(&self, db_key: &[u8], plain: &[u8]) -> Result<Vec<u8>, Error> {
+		let mut iv = [0u8; IV_LENGTH];
+		OsRng::new().map_err(|e| Error::Internal(e.to_string()))?.fill_bytes(&mut iv);
+
+		let mut ciphertext = vec![0u8; plain.len()];
+		crypto::aes::encrypt_128_ctr(&self.encryption_key, &iv, plain, &mut ciphertext).map_err(crypto::Error::from)?;
+		let mac = self.mac(&iv, &ciphertext, db_key);
+
+		let mut sealed = Vec::with_capacity(IV_LENGTH + ciphertext.len() + MAC_LENGTH);
+		sealed.extend_from_slice(&iv);
+		sealed.extend_from_slice(&ciphertext);
+		sealed.extend_from_slice(&mac);
+		Ok(sealed)
+	}
+
+	/// Open sealed value, stored under given database key.
+	fn open(&self, db_key: &[u8], sealed: &[u8]) -> Result<Vec<u8>, Error> {
+		if sealed.len() < IV_LENGTH + MAC_LENGTH {
+			return Err(Error::Database("sealed value is too short".into()));
+		}
+
+		let (iv, rest) = sealed.split_at(IV_LENGTH);
+		let (ciphertext, mac) = rest.split_at(rest.len() - MAC_LENGTH);
+		if !crypto::is_equal(&self.mac(iv, ciphertext, db_key), mac) {
+			return Err(Error::Database("invalid master key or corrupted sealed value".into()));
+		}
+
+		let mut plain = vec![0u8; ciphertext.len()];
+		crypto::aes::decrypt_128_ctr(&self.encryption_key, iv, ciphertext, &mut plain).map_err(crypto::Error::from)?;
+		Ok(plain)
+	}
+}
+
+impl EncryptedKeyStorage {
+	/// Open encrypted keys storage. Unsealed database of any supported version is upgraded and sealed
+	/// under given master key.
+	pub fn new(db: Arc<KeyValueDB>, master_key: &MasterKey) -> Result<Self, Error> {
+		let db = match db_version(&*db)? {
+			SEALED_VERSION => db,
+			_ => upgrade_db(db)?,
+		};
+
+		let key = SealingKey::from_db(&*db, master_key)?;
+		match db.get(None, DB_META_KEY_CHECK)? {
+			Some(check) => match key.open(DB_META_KEY_CHECK, &check) {
+				Ok(ref check) if &check[..] == MASTER_KEY_CHECK => (),
+				_ => return Err(Error::Database("invalid SecretStore master key".into())),
+			},
+			None => seal_db(&*db, &key)?,
+		}
+
+		Ok(EncryptedKeyStorage {
+			db: db,
+			key: key,
+		})
+	}
+
+	/// Iterate over all document key shares, reporting shares that can't be opened or decoded.
+	pub fn iter_checked<'a>(&'a self) -> EncryptedKeyStorageIterator<'a> {
+		EncryptedKeyStorageIterator {
+			iter: Box::new(self.db.iter(None).filter(|&(ref k, _)| !is_meta_key(k) && !is_ed25519_db_key(k))),
+			key: &self.key,
+		}
+	}
+
+	/// Iterate over all Ed25519 key shares, reporting shares that can't be opened or decoded.
+	pub fn iter_ed25519_checked<'a>(&'a self) -> Box<Iterator<Item=Result<(ServerKeyId, Ed25519KeyShare), Error>> + 'a> {
+		let key = &self.key;
+		Box::new(self.db.iter(None)
+			.filter(|&(ref k, _)| is_ed25519_db_key(k))
+			.map(move |(db_key, db_val)| key.open(&db_key, &db_val)
+				.and_then(|key| deserialize_ed25519_key_share(&key))
+				.map(|key| (ed25519_key_id(&db_key), key))))
+	}
+}
+
+/// Seal all unsealed (current version) key shares of the database.
+fn seal_db(db: &KeyValueDB, key: &SealingKey) -> Result<(), Error> {
+	let mut batch = db.transaction();
+	batch.put(None, DB_META_KEY_VERSION, &[SEALED_VERSION]);
+	batch.put(None, DB_META_KEY_CHECK, &key.seal(DB_META_KEY_CHECK, MASTER_KEY_CHECK)?);
+	for (db_key, db_value) in db.iter(None).into_iter().filter(|&(ref k, _)| !is_meta_key(k)) {
+		batch.put(None, &*db_key, &key.seal(&db_key, &db_value)?);
+	}
+	db.write(batch)?;
+	Ok(())
+}
+
+impl KeyStorage for EncryptedKeyStorage {
+	fn insert(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
+		let key = self.key.seal(&document, &serialize_key_share(key)?)?;
+		let mut batch = self.db.transaction();
+		batch.put(None, &document, &key);
+		self.db.write(batch).map_err(Into::into)
+	}
+
+	fn update(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
+		self.insert(document, key)
+	}
+
+	fn get(&self, document: &ServerKeyId) -> Result<Option<DocumentKeyShare>, Error> {
+		self.db.get(None, document)
+			.map_err(|e| Error::Database(e.to_string()))
+			.and_then(|key| match key {
+				None => Ok(None),
+				Some(key) => self.key.open(document, &key)
+					.and_then(|key| deserialize_key_share(&key))
+					.map(Some),
+			})
+	}
+
+	fn remove(&self, document: &ServerKeyId) -> Result<(), Error> {
+		let mut batch = self.db.transaction();
+		batch.delete(None, &document);
+		batch.delete(None, &ed25519_db_key(document));
+		self.db.write(batch).map_err(Into::into)
+	}
+
+	fn clear(&self) -> Result<(), Error> {
+		let mut batch = self.db.transaction();
+		for (key, _) in self.db.iter(None).filter(|&(ref k, _)| !is_meta_key(k)) {
+			batch.delete(None, &key);
+		}
+		self.db.write(batch)
+			.map_err(|e| Error::Database(e.to_string()))
+	}
+
+	fn contains(&self, document: &ServerKeyId) -> bool {
+		self.db.get(None, document)
+			.map(|k| k.is_some())
+			.unwrap_or(false)
+	}
+
+	fn iter<'a>(&'a self) -> Box<Iterator<Item=(ServerKeyId, DocumentKeyShare)> + 'a> {
+		Box::new(self.iter_checked().filter_map(|share| share
+			.map_err(|e| warn!(target: "secretstore", "skipping unreadable key share: {}", e))
+			.ok()))
+	}
+
+	fn insert_ed25519(&self, key_id: ServerKeyId, key: Ed25519KeyShare) -> Result<(), Error> {
+		let db_key = ed25519_db_key(&key_id);
+		let key = self.key.seal(&db_key, &serialize_ed25519_key_share(key)?)?;
+		let mut batch = self.db.transaction();
+		batch.put(None, &db_key, &key);
+		self.db.write(batch).map_err(Into::into)
+	}
+
+	fn get_ed25519(&self, key_id: &ServerKeyId) -> Result<Option<Ed25519KeyShare>, Error> {
+		let db_key = ed25519_db_key(key_id);
+		self.db.get(None, &db_key)
+			.map_err(|e| Error::Database(e.to_string()))
+			.and_then(|key| match key {
+				None => Ok(None),
+				Some(key) => self.key.open(&db_key, &key)
+					.and_then(|key| deserialize_ed25519_key_share(&key))
+					.map(Some),
+			})
+	}
+
+	fn iter_ed25519<'a>(&'a self) -> Box<Iterator<Item=(ServerKeyId, Ed25519KeyShare)> + 'a> {
+		Box::new(self.iter_ed25519_checked().filter_map(|share| share
+			.map_err(|e| warn!(target: "secretstore", "skipping unreadable ed25519 key share: {}", e))
+			.ok()))
+	}
+}
+
+impl<'a> Iterator for EncryptedKeyStorageIterator<'a> {
+	type Item = Result<(ServerKeyId, DocumentKeyShare), Error>;
+
+	fn next(&mut self) -> Option<Result<(ServerKeyId, DocumentKeyShare), Error>> {
+		let key = self.key;
+		self.iter.as_mut().next()
+			.map(|(db_key, db_val)| key.open(&db_key, &db_val)
+				.and_then(|key| deserialize_key_share(&key))
+				.map(|key| ((*db_key).into(), key)))
+	}
+}
+
+/// Open key storage of the database. If master key is given, key shares are sealed under this key.
+pub fn open_key_storage(db: Arc<KeyValueDB>, master_key: Option<&MasterKey>) -> Result<Arc<KeyStorage>, Error> {
+	Ok(match master_key {
+		Some(master_key) => Arc::new(EncryptedKeyStorage::new(db, master_key)?),
+		None => Arc::new(PersistentKeyStorage::new(db)?),
+	})
+}
+
+/// Read all key shares of the database. Fails if any of stored shares can't be opened or decoded.
+fn read_key_shares(db: Arc<KeyValueDB>, master_key: Option<&MasterKey>) -> Result<(Vec<(ServerKeyId, DocumentKeyShare)>, Vec<(ServerKeyId, Ed25519KeyShare)>), Error> {
+	match master_key {
+		Some(master_key) => {
+			let key_storage = EncryptedKeyStorage::new(db, master_key)?;
+			let shares = key_storage.iter_checked().collect::<Result<Vec<_>, _>>()?;
+			let ed25519_shares = key_storage.iter_ed25519_checked().collect::<Result<Vec<_>, _>>()?;
+			Ok((shares, ed25519_shares))
+		},
+		None => {
+			// upgrade the database before reading plain shares
+			PersistentKeyStorage::new(db.clone())?;
+
+			let mut shares = Vec::new();
+			let mut ed25519_shares = Vec::new();
+			for (db_key, db_value) in db.iter(None).filter(|&(ref k, _)| !is_meta_key(k)) {
+				if is_ed25519_db_key(&db_key) {
+					ed25519_shares.push((ed25519_key_id(&db_key), deserialize_ed25519_key_share(&db_value)?));
+				} else {
+					shares.push(((*db_key).into(), deserialize_key_share(&db_value)?));
+				}
+			}
+			Ok((shares, ed25519_shares))
+		},
+	}
+}
+
+/// Export all key shares of the database. Exported shares are sealed under the key, derived from transfer passphrase.
+/// Fails if any of stored shares can't be read.
+pub fn export_key_shares(db: Arc<KeyValueDB>, master_key: Option<&MasterKey>, transfer_passphrase: &Password) -> Result<Vec<u8>, Error> {
+	let (key_shares, ed25519_key_shares) = read_key_shares(db, master_key)?;
+	let salt = random_salt()?;
+	let transfer_key = SealingKey::new(&MasterKey::Passphrase(transfer_passphrase.clone()), &salt);
+
+	let mut shares = BTreeMap::new();
+	for (id, share) in key_shares {
+		shares.insert(id.into(), transfer_key.seal(&id, &serialize_key_share(share)?)?.into());
+	}
+	let mut ed25519_shares = BTreeMap::new();
+	for (id, share) in ed25519_key_shares {
+		ed25519_shares.insert(id.into(), transfer_key.seal(&ed25519_db_key(&id), &serialize_ed25519_key_share(share)?)?.into());
+	}
+
+	serde_json::to_vec_pretty(&SerializableKeySharesExport {
+		version: EXPORT_VERSION,
+		salt: salt.into(),
+		shares: shares,
+		ed25519_shares: ed25519_shares,
+	}).map_err(|e| Error::Serde(e.to_string()))
+}
+
+/// Import key shares, exported with `export_key_shares`, into the database. Fails without importing anything
+/// if the database already contains some of imported shares. Returns number of imported shares.
+pub fn import_key_shares(db: Arc<KeyValueDB>, master_key: Option<&MasterKey>, data: &[u8], transfer_passphrase: &Password) -> Result<usize, Error> {
+	let export: SerializableKeySharesExport = serde_json::from_slice(data).map_err(|e| Error::Serde(e.to_string()))?;
+	if export.version != EXPORT_VERSION {
+		return Err(Error::Serde(format!("unsupported key shares export version: {}", export.version)));
+	}
+
+	let mut salt = [0u8; 32];
+	salt.copy_from_slice(&**export.salt);
+	let transfer_key = SealingKey::new(&MasterKey::Passphrase(transfer_passphrase.clone()), &salt);
+	let shares = export.shares.into_iter()
+		.map(|(id, share)| transfer_key.open(&**id, &share)
+			.map_err(|_| Error::Database("invalid transfer passphrase or corrupted export file".into()))
+			.and_then(|share| deserialize_key_share(&share))
+			.map(|share| (id.into(), share)))
+		.collect::<Result<Vec<(ServerKeyId, DocumentKeyShare)>, Error>>()?;
+	let ed25519_shares = export.ed25519_shares.into_iter()
+		.map(|(id, share)| transfer_key.open(&ed25519_db_key(&id), &share)
+			.map_err(|_| Error::Database("invalid transfer passphrase or corrupted export file".into()))
+			.and_then(|share| deserialize_ed25519_key_share(&share))
+			.map(|share| (id.into(), share)))
+		.collect::<Result<Vec<(ServerKeyId, Ed25519KeyShare)>, Error>>()?;
+
+	let key_storage = open_key_storage(db, master_key)?;
+	if let Some(&(ref id, _)) = shares.iter().find(|&&(ref id, _)| key_storage.contains(id)) {
+		return Err(Error::Database(format!("key share {:?} is already stored", id)));
+	}
+	for &(ref id, _) in &ed25519_shares {
+		if key_storage.get_ed25519(id)?.is_some() {
+			return Err(Error::Database(format!("ed25519 key share {:?} is already stored", id)));
+		}
+	}
+
+	let imported = shares.len() + ed25519_shares.len();
+	for (id, share) in shares {
+		key_storage.insert(id, share)?;
+	}
+	for (id, share) in ed25519_shares {
+		key_storage.insert_ed25519(id, share)?;
+	}
+	Ok(imported)
+}
+
+/// Re-seal all key shares of the database under new master key. Unsealed database is sealed.
+/// Fails without modifying the database if any of stored shares can't be read. Returns number of re-sealed shares.
+pub fn reencrypt_key_shares(db: Arc<KeyValueDB>, master_key: Option<&MasterKey>, new_master_key: &MasterKey) -> Result<usize, Error> {
+	let (shares, ed25519_shares) = read_key_shares(db.clone(), master_key)?;
+	// every stored value is overwritten in place => all of them must have been read
+	let stored = db.iter(None).filter(|&(ref k, _)| !is_meta_key(k)).count();
+	if stored != shares.len() + ed25519_shares.len() {
+		return Err(Error::Database(format!("read {} of {} stored key shares", shares.len() + ed25519_shares.len(), stored)));
+	}
+
+	let salt = random_salt()?;
+	let new_key = SealingKey::new(new_master_key, &salt);
+	let mut batch = db.transaction();
+	for &(ref id, ref share) in &shares {
+		batch.put(None, id, &new_key.seal(id, &serialize_key_share(share.clone())?)?);
+	}
+	for &(ref id, ref share) in &ed25519_shares {
+		let db_key = ed25519_db_key(id);
+		batch.put(None, &db_key, &new_key.seal(&db_key, &serialize_ed25519_key_share(share.clone())?)?);
+	}
+	batch.put(None, DB_META_KEY_VERSION, &[SEALED_VERSION]);
+	batch.put(None, DB_META_KEY_SALT, &salt);
+	batch.put(None, DB_META_KEY_CHECK, &new_key.seal(DB_META_KEY_CHECK, MASTER_KEY_CHECK)?);
+	db.write(batch)?;
+
+	Ok(shares.len() + ed25519_shares.len())
+}
+
+fn is_meta_key(key: &[u8]) -> bool {
+	key == &DB_META_KEY_VERSION[..] || key == &DB_META_KEY_SALT[..] || key == &DB_META_KEY_CHECK[..]
+}
+
+fn random_salt() -> Result<[u8; 32], Error> {
+	let mut salt = [0u8; 32];
+	OsRng::new().map_err(|e| Error::Internal(e.to_string()))?.fill_bytes(&mut salt);
+	Ok(salt)
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate tempdir;
+
+	use std::sync::Arc;
+	use self::tempdir::TempDir;
+	use ethkey::{Random, Generator, Public, Secret};
+	use kvdb::KeyValueDB;
+	use kvdb_rocksdb::Database;
+	use key_storage::{KeyStorage, PersistentKeyStorage, DocumentKeyShare, DocumentKeyShareVersion,
+		DB_META_KEY_VERSION, SEALED_VERSION};
+	use super::{MasterKey, EncryptedKeyStorage, export_key_shares, import_key_shares, reencrypt_key_shares};
+
+	fn open_db(tempdir: &TempDir) -> Arc<KeyValueDB> {
+		Arc::new(Database::open_default(&tempdir.path().display().to_string()).unwrap())
+	}
+
+	fn key_share() -> DocumentKeyShare {
+		DocumentKeyShare {
+			author: Default::default(),
+			threshold: 100,
+			public: Public::default(),
+			common_point: Some(Random.generate().unwrap().public().clone()),
+			encrypted_point: Some(Random.generate().unwrap().public().clone()),
+			versions: vec![DocumentKeyShareVersion {
+				hash: Default::default(),
+				id_numbers: vec![
+					(Random.generate().unwrap().public().clone(), Random.generate().unwrap().secret().clone())
+				].into_iter().collect(),
+				secret_share: Random.generate().unwrap().secret().clone(),
+			}],
+		}
+	}
+
+	fn passphrase(passphrase: &str) -> MasterKey {
+		MasterKey::Passphrase(passphrase.into())
+	}
+
+	#[test]
+	fn encrypted_key_storage_works() {
+		let tempdir = TempDir::new("").unwrap();
+		let key1 = Random.generate().unwrap().secret().clone();
+		let value1 = key_share();
+		let key2 = Random.generate().unwrap().secret().clone();
+		let value2 = key_share();
+
+		{
+			let key_storage = EncryptedKeyStorage::new(open_db(&tempdir), &passphrase("password")).unwrap();
+			key_storage.insert(key1.clone(), value1.clone()).unwrap();
+			key_storage.insert(key2.clone(), value2.clone()).unwrap();
+			assert_eq!(key_storage.get(&key1), Ok(Some(value1.clone())));
+			assert_eq!(key_storage.iter().count(), 2);
+		}
+
+		let db = open_db(&tempdir);
+		assert!(EncryptedKeyStorage::new(db.clone(), &passphrase("wrong password")).is_err());
+		assert!(PersistentKeyStorage::new(db.clone()).is_err());
+
+		let key_storage = EncryptedKeyStorage::new(db, &passphrase("password")).unwrap();
+		assert_eq!(key_storage.get(&key1), Ok(Some(value1)));
+		assert_eq!(key_storage.get(&key2), Ok(Some(value2)));
+	}
+
+	#[test]
+	fn sealed_values_are_bound_to_their_keys() {
+		let tempdir = TempDir::new("").unwrap();
+		let key1 = Random.generate().unwrap().secret().clone();
+		let key2 = Random.generate().unwrap().secret().clone();
+
+		let db = open_db(&tempdir);
+		let key_storage = EncryptedKeyStorage::new(db.clone(), &passphrase("password")).unwrap();
+		key_storage.insert(key1.clone(), key_share()).unwrap();
+		key_storage.insert(key2.clone(), key_share()).unwrap();
+
+		// swap the sealed shares of both documents
+		let sealed1 = db.get(None, &key1).unwrap().unwrap();
+		let sealed2 = db.get(None, &key2).unwrap().unwrap();
+		let mut batch = db.transaction();
+		batch.put(None, &key1, &sealed2);
+		batch.put(None, &key2, &sealed1);
+		db.write(batch).unwrap();
+
+		assert!(key_storage.get(&key1).is_err());
+		assert!(key_storage.get(&key2).is_err());
+	}
+
+	#[test]
+	fn unsealed_db_is_sealed_on_open() {
+		let tempdir = TempDir::new("").unwrap();
+		let key = Random.generate().unwrap().secret().clone();
+		let value = key_share();
+
+		let db = open_db(&tempdir);
+		PersistentKeyStorage::new(db.clone()).unwrap().insert(key.clone(), value.clone()).unwrap();
+		let plain = db.get(None, &key).unwrap().unwrap();
+
+		let master_key = MasterKey::Raw(Random.generate().unwrap().secret().clone());
+		let key_storage = EncryptedKeyStorage::new(db.clone(), &master_key).unwrap();
+		assert_eq!(db.get(None, DB_META_KEY_VERSION).unwrap().unwrap()[0], SEALED_VERSION);
+		assert!(db.get(None, &key).unwrap().unwrap() != plain);
+		assert_eq!(key_storage.get(&key), Ok(Some(value)));
+	}
+
+	#[test]
+	fn key_shares_are_exported_and_imported() {
+		let source_dir = TempDir::new("").unwrap();
+		let target_dir = TempDir::new("").unwrap();
+		let source_key = passphrase("source");
+		let target_key = MasterKey::Raw("00125d85a05e5e63e214cb60fe63f132eec8a103aa29266b7e6e6c5b7597230b".parse::<Secret>().unwrap());
+		let key = Random.generate().unwrap().secret().clone();
+		let value = key_share();
+
+		EncryptedKeyStorage::new(open_db(&source_dir), &source_key).unwrap().insert(key.clone(), value.clone()).unwrap();
+		let exported = export_key_shares(open_db(&source_dir), Some(&source_key), &"transfer".into()).unwrap();
+
+		let target_db = open_db(&target_dir);
+		assert!(import_key_shares(target_db.clone(), Some(&target_key), &exported, &"wrong".into()).is_err());
+		assert_eq!(import_key_shares(target_db.clone(), Some(&target_key), &exported, &"transfer".into()), Ok(1));
+		assert!(import_key_shares(target_db.clone(), Some(&target_key), &exported, &"transfer".into()).is_err());
+		assert_eq!(EncryptedKeyStorage::new(target_db, &target_key).unwrap().get(&key), Ok(Some(value)));
+	}
+
+	#[test]
+	fn key_shares_are_reencrypted() {
+		let tempdir = TempDir::new("").unwrap();
+		let key = Random.generate().unwrap().secret().clone();
+		let value = key_share();
+
+		let db = open_db(&tempdir);
+		EncryptedKeyStorage::new(db.clone(), &passphrase("old")).unwrap().insert(key.clone(), value.clone()).unwrap();
+		assert_eq!(reencrypt_key_shares(db.clone(), Some(&passphrase("old")), &passphrase("new")), Ok(1));
+
+		assert!(EncryptedKeyStorage::new(db.clone(), &passphrase("old")).is_err());
+		assert_eq!(EncryptedKeyStorage::new(db, &passphrase("new")).unwrap().get(&key), Ok(Some(value)));
+	}
+
+	#[test]
+	fn unreadable_key_share_fails_export_and_reencryption() {
+		let tempdir = TempDir::new("").unwrap();
+		let key1 = Random.generate().unwrap().secret().clone();
+		let key2 = Random.generate().unwrap().secret().clone();
+		let value2 = key_share();
+
+		let db = open_db(&tempdir);
+		let key_storage = EncryptedKeyStorage::new(db.clone(), &passphrase("old")).unwrap();
+		key_storage.insert(key1.clone(), key_share()).unwrap();
+		key_storage.insert(key2.clone(), value2.clone()).unwrap();
+
+		// corrupt the sealed share of the first document
+		let mut batch = db.transaction();
+		batch.put(None, &key1, &[0u8; 64]);
+		db.write(batch).unwrap();
+		assert_eq!(key_storage.iter().count(), 1);
+
+		assert!(export_key_shares(db.clone(), Some(&passphrase("old")), &"transfer".into()).is_err());
+		assert!(reencrypt_key_shares(db.clone(), Some(&passphrase("old")), &passphrase("new")).is_err());
+
+		assert!(EncryptedKeyStorage::new(db.clone(), &passphrase("new")).is_err());
+		assert_eq!(db.get(None, &key1).unwrap().unwrap().to_vec(), vec![0u8; 64]);
+		assert_eq!(EncryptedKeyStorage::new(db, &passphrase("old")).unwrap().get(&key2), Ok(Some(value2)));
+	}
+}
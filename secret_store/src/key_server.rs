@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, BTreeMap};
 use std::thread;
 use std::sync::Arc;
 use std::sync::mpsc;
@@ -22,14 +22,17 @@ use futures::{self, Future};
 use parking_lot::Mutex;
 use tokio_core::reactor::Core;
 use crypto::DEFAULT_MAC;
-use ethkey::crypto;
+use ethkey::{self, crypto};
+use ethereum_types::H256;
+use bytes::Bytes;
+use hash::keccak;
 use super::acl_storage::AclStorage;
-use super::key_storage::KeyStorage;
+use super::key_storage::{KeyStorage, DocumentKeyShare, Ed25519KeyShare};
 use super::key_server_set::KeyServerSet;
 use key_server_cluster::{math, ClusterCore};
 use traits::{AdminSessionsServer, ServerKeyGenerator, DocumentKeyServer, MessageSigner, KeyServer, NodeKeyPair};
 use types::{Error, Public, RequestSignature, Requester, ServerKeyId, EncryptedDocumentKey, EncryptedDocumentKeyShadow,
-	ClusterConfiguration, MessageHash, EncryptedMessageSignature, NodeId};
+	ClusterConfiguration, MessageHash, EncryptedMessageSignature, NodeId, ServerKeyInfo};
 use key_server_cluster::{ClusterClient, ClusterConfiguration as NetClusterConfiguration};
 
 /// Secret store key server implementation
@@ -42,6 +45,7 @@ pub struct KeyServerCore {
 	close: Option<futures::Complete<()>>,
 	handle: Option<thread::JoinHandle<()>>,
 	cluster: Arc<ClusterClient>,
+	admin_public: Option<Public>,
 }
 
 impl KeyServerImpl {
@@ -68,6 +72,52 @@ impl AdminSessionsServer for KeyServerImpl {
 			.expect("new_servers_set_change_session creates servers_set_change_session; qed")
 			.wait().map_err(Into::into)
 	}
+
+	fn list_server_keys(&self, signature: RequestSignature) -> Result<Vec<ServerKeyInfo>, Error> {
+		let key_storage = {
+			let data = self.data.lock();
+			data.check_admin_signature(&keccak("list_server_keys"), &signature)?;
+			data.cluster.key_storage()
+		};
+
+		let server_keys = key_storage.iter()
+			.map(|(key_id, key_share)| {
+				let versions = key_share.versions.iter()
+					.map(|version| (version.hash.clone(), version.id_numbers.keys().cloned().collect()))
+					.collect();
+				make_server_key_info(key_id, key_share, versions)
+			})
+			.chain(key_storage.iter_ed25519().map(|(key_id, key_share)| make_ed25519_server_key_info(key_id, key_share)))
+			.collect();
+		Ok(server_keys)
+	}
+
+	fn server_key_info(&self, key_id: &ServerKeyId, signature: RequestSignature) -> Result<ServerKeyInfo, Error> {
+		let (key_share, negotiation_session) = {
+			let data = self.data.lock();
+			data.check_admin_signature(key_id, &signature)?;
+			let key_storage = data.cluster.key_storage();
+			let key_share = match key_storage.get(key_id)? {
+				Some(key_share) => key_share,
+				None => return key_storage.get_ed25519(key_id)?
+					.map(|key_share| make_ed25519_server_key_info(key_id.clone(), key_share))
+					.ok_or(Error::ServerKeyIsNotFound),
+			};
+			(key_share, data.cluster.new_key_versions_discovery_session(key_id.clone())?)
+		};
+
+		// collect versions from all connected nodes
+		negotiation_session.wait()?;
+		let versions = negotiation_session.versions()?;
+		Ok(make_server_key_info(key_id.clone(), key_share, versions))
+	}
+
+	fn remove_server_key(&self, key_id: &ServerKeyId, signature: RequestSignature) -> Result<(), Error> {
+		let key_removal_session = self.data.lock().cluster.new_key_removal_session(key_id.clone(), signature)?;
+		key_removal_session.as_key_removal()
+			.expect("new_key_removal_session creates key_removal_session; qed")
+			.wait().map_err(Into::into)
+	}
 }
 
 impl ServerKeyGenerator for KeyServerImpl {
@@ -81,6 +131,15 @@ impl ServerKeyGenerator for KeyServerImpl {
 			.expect("when wait is called without timeout it always returns Some; qed")
 			.map_err(Into::into)
 	}
+
+	fn generate_ed25519_key(&self, key_id: &ServerKeyId, author: &Requester, threshold: usize) -> Result<H256, Error> {
+		// recover requestor' address from signature
+		let address = author.address(key_id).map_err(Error::InsufficientRequesterData)?;
+
+		// generate server key
+		let generation_session = self.data.lock().cluster.new_ed25519_generation_session(key_id.clone(), address, threshold)?;
+		generation_session.wait().map_err(Into::into)
+	}
 }
 
 impl DocumentKeyServer for KeyServerImpl {
@@ -172,6 +231,21 @@ impl MessageSigner for KeyServerImpl {
 			.map_err(|err| Error::Internal(format!("Error encrypting message signature: {}", err)))?;
 		Ok(message_signature)
 	}
+
+	fn sign_message_ed25519(&self, key_id: &ServerKeyId, requester: &Requester, message: Bytes) -> Result<EncryptedMessageSignature, Error> {
+		// recover requestor' public key from signature
+		let public = requester.public(key_id).map_err(Error::InsufficientRequesterData)?;
+
+		// sign message
+		let signing_session = self.data.lock().cluster.new_ed25519_signing_session(key_id.clone(),
+			requester.clone(), message)?;
+		let message_signature = signing_session.wait()?;
+
+		// encrypt signature with requestor public key
+		let message_signature = crypto::ecies::encrypt(&public, &DEFAULT_MAC, &message_signature)
+			.map_err(|err| Error::Internal(format!("Error encrypting message signature: {}", err)))?;
+		Ok(message_signature)
+	}
 }
 
 impl KeyServerCore {
@@ -187,6 +261,7 @@ impl KeyServerCore {
 			admin_public: config.admin_public.clone(),
 			auto_migrate_enabled: config.auto_migrate_enabled,
 		};
+		let admin_public = config.admin_public.clone();
 
 		let (stop, stopped) = futures::oneshot();
 		let (tx, rx) = mpsc::channel();
@@ -212,8 +287,42 @@ impl KeyServerCore {
 			close: Some(stop),
 			handle: Some(handle),
 			cluster: cluster,
+			admin_public: admin_public,
 		})
 	}
+
+	/// Check that the message is signed by administrator.
+	fn check_admin_signature(&self, message: &H256, signature: &RequestSignature) -> Result<(), Error> {
+		let admin_public = self.admin_public.as_ref().ok_or(Error::AccessDenied)?;
+		match ethkey::recover(signature, message) {
+			Ok(ref public) if public == admin_public => Ok(()),
+			_ => Err(Error::AccessDenied),
+		}
+	}
+}
+
+fn make_server_key_info(key_id: ServerKeyId, key_share: DocumentKeyShare, versions: BTreeMap<H256, BTreeSet<NodeId>>) -> ServerKeyInfo {
+	ServerKeyInfo {
+		id: key_id,
+		author: key_share.author,
+		threshold: key_share.threshold,
+		public: Some(key_share.public),
+		ed25519_public: None,
+		has_document_key: key_share.common_point.is_some() && key_share.encrypted_point.is_some(),
+		versions: versions,
+	}
+}
+
+fn make_ed25519_server_key_info(key_id: ServerKeyId, key_share: Ed25519KeyShare) -> ServerKeyInfo {
+	ServerKeyInfo {
+		id: key_id,
+		author: key_share.author,
+		threshold: key_share.threshold,
+		public: None,
+		ed25519_public: key_share.commitments.first().cloned(),
+		has_document_key: false,
+		versions: BTreeMap::new(),
+	}
 }
 
 impl Drop for KeyServerCore {
@@ -231,17 +340,21 @@ pub mod tests {
 	use std::net::SocketAddr;
 	use std::collections::BTreeMap;
 	use crypto::DEFAULT_MAC;
-	use ethkey::{self, crypto, Secret, Random, Generator, verify_public};
+	use ethkey::{self, crypto, Secret, Random, Generator, KeyPair, verify_public};
 	use acl_storage::DummyAclStorage;
 	use key_storage::KeyStorage;
 	use key_storage::tests::DummyKeyStorage;
 	use node_key_pair::PlainNodeKeyPair;
 	use key_server_set::tests::MapKeyServerSet;
 	use key_server_cluster::math;
+	use key_server_cluster::math_ed25519 as ed25519_math;
 	use ethereum_types::{H256, H520};
+	use bytes::Bytes;
+	use hash::keccak;
+	use key_server_cluster::key_removal_session::key_removal_hash;
 	use types::{Error, Public, ClusterConfiguration, NodeAddress, RequestSignature, ServerKeyId,
 		EncryptedDocumentKey, EncryptedDocumentKeyShadow, MessageHash, EncryptedMessageSignature,
-		Requester, NodeId};
+		Requester, NodeId, ServerKeyInfo};
 	use traits::{AdminSessionsServer, ServerKeyGenerator, DocumentKeyServer, MessageSigner, KeyServer};
 	use super::KeyServerImpl;
 
@@ -254,12 +367,28 @@ pub mod tests {
 		fn change_servers_set(&self, _old_set_signature: RequestSignature, _new_set_signature: RequestSignature, _new_servers_set: BTreeSet<NodeId>) -> Result<(), Error> {
 			unimplemented!("test-only")
 		}
+
+		fn list_server_keys(&self, _signature: RequestSignature) -> Result<Vec<ServerKeyInfo>, Error> {
+			unimplemented!("test-only")
+		}
+
+		fn server_key_info(&self, _key_id: &ServerKeyId, _signature: RequestSignature) -> Result<ServerKeyInfo, Error> {
+			unimplemented!("test-only")
+		}
+
+		fn remove_server_key(&self, _key_id: &ServerKeyId, _signature: RequestSignature) -> Result<(), Error> {
+			unimplemented!("test-only")
+		}
 	}
 
 	impl ServerKeyGenerator for DummyKeyServer {
 		fn generate_key(&self, _key_id: &ServerKeyId, _author: &Requester, _threshold: usize) -> Result<Public, Error> {
 			unimplemented!("test-only")
 		}
+
+		fn generate_ed25519_key(&self, _key_id: &ServerKeyId, _author: &Requester, _threshold: usize) -> Result<H256, Error> {
+			unimplemented!("test-only")
+		}
 	}
 
 	impl DocumentKeyServer for DummyKeyServer {
@@ -288,9 +417,17 @@ pub mod tests {
 		fn sign_message_ecdsa(&self, _key_id: &ServerKeyId, _requester: &Requester, _message: MessageHash) -> Result<EncryptedMessageSignature, Error> {
 			unimplemented!("test-only")
 		}
+
+		fn sign_message_ed25519(&self, _key_id: &ServerKeyId, _requester: &Requester, _message: Bytes) -> Result<EncryptedMessageSignature, Error> {
+			unimplemented!("test-only")
+		}
 	}
 
 	fn make_key_servers(start_port: u16, num_nodes: usize) -> (Vec<KeyServerImpl>, Vec<Arc<DummyKeyStorage>>) {
+		make_key_servers_with_admin(start_port, num_nodes, None)
+	}
+
+	fn make_key_servers_with_admin(start_port: u16, num_nodes: usize, admin_public: Option<Public>) -> (Vec<KeyServerImpl>, Vec<Arc<DummyKeyStorage>>) {
 		let key_pairs: Vec<_> = (0..num_nodes).map(|_| Random.generate().unwrap()).collect();
 		let configs: Vec<_> = (0..num_nodes).map(|i| ClusterConfiguration {
 				threads: 1,
@@ -305,7 +442,7 @@ pub mod tests {
 					})).collect(),
 				key_server_set_contract_address: None,
 				allow_connecting_to_higher_nodes: false,
-				admin_public: None,
+				admin_public: admin_public.clone(),
 				auto_migrate_enabled: false,
 			}).collect();
 		let key_servers_set: BTreeMap<Public, SocketAddr> = configs[0].nodes.iter()
@@ -453,6 +590,30 @@ pub mod tests {
 		}
 	}
 
+	#[test]
+	fn ed25519_key_generation_and_message_signing_works_over_network_with_3_nodes() {
+		//::logger::init_log();
+		let (key_servers, _) = make_key_servers(6105, 3);
+
+		let test_cases = [0, 1, 2];
+		for threshold in &test_cases {
+			// generate server key
+			let server_key_id = Random.generate().unwrap().secret().clone();
+			let requestor_secret = Random.generate().unwrap().secret().clone();
+			let signature = ethkey::sign(&requestor_secret, &server_key_id).unwrap();
+			let server_public = key_servers[0].generate_ed25519_key(&server_key_id, &signature.clone().into(), *threshold).unwrap();
+
+			// sign message
+			let message = b"bridge message".to_vec();
+			let message_signature = key_servers[0].sign_message_ed25519(&server_key_id, &signature.into(), message.clone()).unwrap();
+			let message_signature = crypto::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &message_signature).unwrap();
+
+			// check signature
+			let server_public = ed25519_math::point_from_h256(&server_public).unwrap();
+			assert!(ed25519_math::verify_signature(&server_public, &message, &message_signature));
+		}
+	}
+
 	#[test]
 	fn decryption_session_is_delegated_when_node_does_not_have_key_share() {
 		//::logger::init_log();
@@ -526,6 +687,48 @@ pub mod tests {
 		assert!(verify_public(&server_public, &signature.into(), &message_hash).unwrap());
 	}
 
+	#[test]
+	fn server_keys_are_listed_and_removed_over_network() {
+		//::logger::init_log();
+		let admin_key_pair: KeyPair = Random.generate().unwrap();
+		let (key_servers, key_storages) = make_key_servers_with_admin(6122, 3, Some(admin_key_pair.public().clone()));
+		let threshold = 1;
+
+		// generate server key
+		let server_key_id = Random.generate().unwrap().secret().clone();
+		let requestor_secret = Random.generate().unwrap().secret().clone();
+		let signature = ethkey::sign(&requestor_secret, &server_key_id).unwrap();
+		let server_public = key_servers[0].generate_key(&server_key_id, &signature.into(), threshold).unwrap();
+
+		// list keys
+		let list_signature = ethkey::sign(admin_key_pair.secret(), &keccak("list_server_keys")).unwrap();
+		let server_keys = key_servers[1].list_server_keys(list_signature).unwrap();
+		assert_eq!(server_keys.len(), 1);
+		assert_eq!(server_keys[0].id, *server_key_id);
+		assert_eq!(server_keys[0].public, server_public);
+		assert_eq!(server_keys[0].threshold, threshold);
+		assert!(!server_keys[0].has_document_key);
+
+		// only administrator is able to list keys
+		let requestor_list_signature = ethkey::sign(&requestor_secret, &keccak("list_server_keys")).unwrap();
+		assert_eq!(key_servers[1].list_server_keys(requestor_list_signature), Err(Error::AccessDenied));
+
+		// every node is reported as a holder of the single key version
+		let info_signature = ethkey::sign(admin_key_pair.secret(), &server_key_id).unwrap();
+		let server_key_info: ServerKeyInfo = key_servers[2].server_key_info(&server_key_id, info_signature).unwrap();
+		assert_eq!(server_key_info.versions.len(), 1);
+		assert_eq!(server_key_info.versions.values().next().unwrap().len(), 3);
+
+		// key id signature can't be used to remove the key
+		let info_signature = ethkey::sign(admin_key_pair.secret(), &server_key_id).unwrap();
+		assert_eq!(key_servers[0].remove_server_key(&server_key_id, info_signature), Err(Error::AccessDenied));
+
+		// remove key from all nodes
+		let removal_signature = ethkey::sign(admin_key_pair.secret(), &key_removal_hash(&server_key_id)).unwrap();
+		key_servers[0].remove_server_key(&server_key_id, removal_signature).unwrap();
+		assert!(key_storages.iter().all(|key_storage| !key_storage.contains(&server_key_id)));
+	}
+
 	#[test]
 	fn servers_set_change_session_works_over_network() {
 		// TODO [Test]
@@ -0,0 +1,483 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use std::collections::BTreeSet;
+use parking_lot::{Mutex, Condvar};
+use ethkey::{Public, Signature, recover};
+use tiny_keccak::Keccak;
+use key_server_cluster::{Error, SessionId, NodeId, KeyStorage};
+use key_server_cluster::cluster::Cluster;
+use key_server_cluster::cluster_sessions::ClusterSession;
+use key_server_cluster::message::{Message, KeyRemovalMessage, ServersSetChangeMessage, InitializeKeyRemoval,
+	ConfirmKeyRemoval, KeyRemovalError};
+use key_server_cluster::admin_sessions::ShareChangeSessionMeta;
+
+/// Key removal transport.
+pub trait SessionTransport {
+	/// Get all connected nodes.
+	fn nodes(&self) -> BTreeSet<NodeId>;
+	/// Broadcast message to all nodes.
+	fn broadcast(&self, message: KeyRemovalMessage) -> Result<(), Error>;
+	/// Send message to given node.
+	fn send(&self, node: &NodeId, message: KeyRemovalMessage) -> Result<(), Error>;
+}
+
+/// Key removal session. Removes key shares of the given server key from every node of the cluster.
+/// Every node checks that the request is signed by the SecretStore administrator before removing its share.
+pub struct SessionImpl<T: SessionTransport> {
+	/// Session core.
+	core: SessionCore<T>,
+	/// Session data.
+	data: Mutex<SessionData>,
+}
+
+/// Immutable session data.
+struct SessionCore<T: SessionTransport> {
+	/// Session metadata.
+	pub meta: ShareChangeSessionMeta,
+	/// Session-level nonce.
+	pub nonce: u64,
+	/// Session transport.
+	pub transport: T,
+	/// Key storage.
+	pub key_storage: Arc<KeyStorage>,
+	/// Administrator public key.
+	pub admin_public: Option<Public>,
+	/// Session completion signal.
+	pub completed: Condvar,
+}
+
+/// Mutable session data.
+struct SessionData {
+	/// Session state.
+	pub state: SessionState,
+	/// Nodes that have not yet confirmed key removal (on master node only).
+	pub confirmations: Option<BTreeSet<NodeId>>,
+	/// Session result.
+	pub result: Option<Result<(), Error>>,
+}
+
+/// SessionImpl creation parameters
+pub struct SessionParams<T: SessionTransport> {
+	/// Session metadata.
+	pub meta: ShareChangeSessionMeta,
+	/// Session transport.
+	pub transport: T,
+	/// Key storage.
+	pub key_storage: Arc<KeyStorage>,
+	/// Administrator public key.
+	pub admin_public: Option<Public>,
+	/// Session-level nonce.
+	pub nonce: u64,
+}
+
+/// Key removal session state.
+#[derive(Debug, PartialEq)]
+enum SessionState {
+	/// State when session is waiting for initialization.
+	WaitingForInitialization,
+	/// State when master node is waiting for removal confirmations.
+	WaitingForConfirmations,
+	/// Session is completed.
+	Finished,
+}
+
+/// Isolated session transport.
+pub struct IsolatedSessionTransport {
+	/// Cluster.
+	pub cluster: Arc<Cluster>,
+}
+
+/// Hash of the key removal request, which must be signed by the administrator.
+pub fn key_removal_hash(key_id: &SessionId) -> SessionId {
+	let mut request_keccak = Keccak::new_keccak256();
+	request_keccak.update(b"remove_key");
+	request_keccak.update(&**key_id);
+
+	let mut request_keccak_value = [0u8; 32];
+	request_keccak.finalize(&mut request_keccak_value);
+
+	request_keccak_value.into()
+}
+
+impl<T> SessionImpl<T> where T: SessionTransport {
+	/// Create new key removal session.
+	pub fn new(params: SessionParams<T>) -> Self {
+		SessionImpl {
+			core: SessionCore {
+				meta: params.meta,
+				nonce: params.nonce,
+				transport: params.transport,
+				key_storage: params.key_storage,
+				admin_public: params.admin_public,
+				completed: Condvar::new(),
+			},
+			data: Mutex::new(SessionData {
+				state: SessionState::WaitingForInitialization,
+				confirmations: None,
+				result: None,
+			}),
+		}
+	}
+
+	/// Wait for session completion.
+	pub fn wait(&self) -> Result<(), Error> {
+		Self::wait_session(&self.core.completed, &self.data, None, |data| data.result.clone())
+			.expect("wait_session returns Some if called without timeout; qed")
+	}
+
+	/// Initialize key removal session on master node.
+	pub fn initialize(&self, signature: Signature) -> Result<(), Error> {
+		debug_assert_eq!(self.core.meta.self_node_id, self.core.meta.master_node_id);
+
+		let mut data = self.data.lock();
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		self.check_signature(&signature)?;
+
+		let mut confirmations = self.core.transport.nodes();
+		confirmations.remove(&self.core.meta.self_node_id);
+		if !confirmations.is_empty() {
+			self.core.transport.broadcast(KeyRemovalMessage::InitializeKeyRemoval(InitializeKeyRemoval {
+				session: self.core.meta.id.clone().into(),
+				session_nonce: self.core.nonce,
+				signature: signature.into(),
+			}))?;
+		}
+
+		self.core.key_storage.remove(&self.core.meta.id)?;
+
+		if confirmations.is_empty() {
+			Self::complete_session(&self.core, &mut *data, Ok(()));
+		} else {
+			data.state = SessionState::WaitingForConfirmations;
+			data.confirmations = Some(confirmations);
+		}
+
+		Ok(())
+	}
+
+	/// Process key removal message.
+	pub fn process_message(&self, sender: &NodeId, message: &KeyRemovalMessage) -> Result<(), Error> {
+		if self.core.nonce != message.session_nonce() {
+			return Err(Error::ReplayProtection);
+		}
+
+		match message {
+			&KeyRemovalMessage::InitializeKeyRemoval(ref message) =>
+				self.on_initialize_key_removal(sender, message),
+			&KeyRemovalMessage::ConfirmKeyRemoval(ref message) =>
+				self.on_confirm_key_removal(sender, message),
+			&KeyRemovalMessage::KeyRemovalError(ref message) => {
+				self.on_session_error(sender, message.error.clone());
+				Ok(())
+			},
+		}
+	}
+
+	/// When key removal is requested by master node.
+	pub fn on_initialize_key_removal(&self, sender: &NodeId, message: &InitializeKeyRemoval) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		if *sender != self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		self.check_signature(&message.signature.clone().into())?;
+		self.core.key_storage.remove(&self.core.meta.id)?;
+
+		self.core.transport.send(sender, KeyRemovalMessage::ConfirmKeyRemoval(ConfirmKeyRemoval {
+			session: self.core.meta.id.clone().into(),
+			session_nonce: self.core.nonce,
+		}))?;
+
+		Self::complete_session(&self.core, &mut *data, Ok(()));
+		Ok(())
+	}
+
+	/// When key removal is confirmed by slave node.
+	pub fn on_confirm_key_removal(&self, sender: &NodeId, message: &ConfirmKeyRemoval) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+		if data.state != SessionState::WaitingForConfirmations {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		let is_last_confirmation = {
+			let confirmations = data.confirmations.as_mut()
+				.expect("confirmations are filled on master node when initializing; master node is waiting for confirmations; qed");
+			if !confirmations.remove(sender) {
+				return Err(Error::InvalidMessage);
+			}
+
+			confirmations.is_empty()
+		};
+
+		if is_last_confirmation {
+			Self::complete_session(&self.core, &mut *data, Ok(()));
+		}
+
+		Ok(())
+	}
+
+	/// Check that request is signed by the administrator.
+	fn check_signature(&self, signature: &Signature) -> Result<(), Error> {
+		let admin_public = self.core.admin_public.as_ref().ok_or(Error::AccessDenied)?;
+		let actual_public = recover(signature, &key_removal_hash(&self.core.meta.id))?;
+		if actual_public != *admin_public {
+			return Err(Error::AccessDenied);
+		}
+
+		Ok(())
+	}
+
+	/// Complete session with given result.
+	fn complete_session(core: &SessionCore<T>, data: &mut SessionData, result: Result<(), Error>) {
+		data.state = SessionState::Finished;
+		data.result = Some(result);
+		core.completed.notify_all();
+	}
+}
+
+impl<T> ClusterSession for SessionImpl<T> where T: SessionTransport {
+	type Id = SessionId;
+
+	fn type_name() -> &'static str {
+		"key removal"
+	}
+
+	fn id(&self) -> SessionId {
+		self.core.meta.id.clone()
+	}
+
+	fn is_finished(&self) -> bool {
+		self.data.lock().state == SessionState::Finished
+	}
+
+	fn on_session_timeout(&self) {
+		self.on_session_error(&self.core.meta.self_node_id, Error::NodeDisconnected)
+	}
+
+	fn on_node_timeout(&self, node: &NodeId) {
+		self.on_session_error(node, Error::NodeDisconnected)
+	}
+
+	fn on_session_error(&self, node: &NodeId, error: Error) {
+		let mut data = self.data.lock();
+		if data.state == SessionState::Finished {
+			return;
+		}
+
+		// broadcast error if error occured on this node
+		if *node == self.core.meta.self_node_id {
+			// do not bother processing send error, as we already processing error
+			let _ = self.core.transport.broadcast(KeyRemovalMessage::KeyRemovalError(KeyRemovalError {
+				session: self.core.meta.id.clone().into(),
+				session_nonce: self.core.nonce,
+				error: error.clone().into(),
+			}));
+		}
+
+		warn!(target: "secretstore_net", "{}: key removal session failed: {} on {}",
+			self.core.meta.self_node_id, error, node);
+
+		Self::complete_session(&self.core, &mut *data, Err(error));
+	}
+
+	fn on_message(&self, sender: &NodeId, message: &Message) -> Result<(), Error> {
+		match *message {
+			Message::KeyRemoval(ref message) => self.process_message(sender, message),
+			// admin sessions creator reports all creation errors using servers set change error message
+			Message::ServersSetChange(ServersSetChangeMessage::ServersSetChangeError(ref message)) => {
+				self.on_session_error(sender, message.error.clone());
+				Ok(())
+			},
+			_ => unreachable!("cluster checks message to be correct before passing; qed"),
+		}
+	}
+}
+
+impl IsolatedSessionTransport {
+	pub fn new(cluster: Arc<Cluster>) -> Self {
+		IsolatedSessionTransport {
+			cluster: cluster,
+		}
+	}
+}
+
+impl SessionTransport for IsolatedSessionTransport {
+	fn nodes(&self) -> BTreeSet<NodeId> {
+		self.cluster.nodes()
+	}
+
+	fn broadcast(&self, message: KeyRemovalMessage) -> Result<(), Error> {
+		self.cluster.broadcast(Message::KeyRemoval(message))
+	}
+
+	fn send(&self, node: &NodeId, message: KeyRemovalMessage) -> Result<(), Error> {
+		self.cluster.send(node, Message::KeyRemoval(message))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::collections::BTreeMap;
+	use ethkey::{Random, Generator, KeyPair, Signature, sign};
+	use key_server_cluster::{NodeId, SessionId, Error, KeyStorage, DummyKeyStorage, Ed25519KeyShare};
+	use key_server_cluster::cluster::tests::DummyCluster;
+	use key_server_cluster::cluster_sessions::ClusterSession;
+	use key_server_cluster::admin_sessions::ShareChangeSessionMeta;
+	use super::{SessionImpl, SessionParams, IsolatedSessionTransport, key_removal_hash};
+
+	struct Node {
+		pub cluster: Arc<DummyCluster>,
+		pub key_storage: Arc<DummyKeyStorage>,
+		pub session: SessionImpl<IsolatedSessionTransport>,
+	}
+
+	struct MessageLoop {
+		pub admin_key_pair: KeyPair,
+		pub master: NodeId,
+		pub nodes: BTreeMap<NodeId, Node>,
+	}
+
+	impl MessageLoop {
+		pub fn new(num_nodes: usize) -> Self {
+			let key_id = SessionId::default();
+			let admin_key_pair = Random.generate().unwrap();
+			let nodes_ids: Vec<NodeId> = (0..num_nodes).map(|_| Random.generate().unwrap().public().clone()).collect();
+			let master = nodes_ids[0].clone();
+			let nodes = nodes_ids.iter().map(|node_id| {
+				let cluster = Arc::new(DummyCluster::new(node_id.clone()));
+				cluster.add_nodes(nodes_ids.iter().cloned());
+				let key_storage = Arc::new(DummyKeyStorage::default());
+				key_storage.insert(key_id.clone(), Default::default()).unwrap();
+				let session = SessionImpl::new(SessionParams {
+					meta: ShareChangeSessionMeta {
+						id: key_id.clone(),
+						master_node_id: master.clone(),
+						self_node_id: node_id.clone(),
+						configured_nodes_count: num_nodes,
+						connected_nodes_count: num_nodes,
+					},
+					transport: IsolatedSessionTransport::new(cluster.clone()),
+					key_storage: key_storage.clone(),
+					admin_public: Some(admin_key_pair.public().clone()),
+					nonce: 1,
+				});
+
+				(node_id.clone(), Node {
+					cluster: cluster,
+					key_storage: key_storage,
+					session: session,
+				})
+			}).collect();
+
+			MessageLoop {
+				admin_key_pair: admin_key_pair,
+				master: master,
+				nodes: nodes,
+			}
+		}
+
+		pub fn master(&self) -> &SessionImpl<IsolatedSessionTransport> {
+			&self.nodes[&self.master].session
+		}
+
+		pub fn signature(&self) -> Signature {
+			sign(self.admin_key_pair.secret(), &key_removal_hash(&SessionId::default())).unwrap()
+		}
+
+		pub fn run(&self) {
+			loop {
+				let message = self.nodes.iter()
+					.filter_map(|(from, node)| node.cluster.take_message().map(|(to, message)| (from.clone(), to, message)))
+					.nth(0);
+				match message {
+					Some((from, to, message)) => self.nodes[&to].session.on_message(&from, &message).unwrap(),
+					None => break,
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn key_is_removed_from_all_nodes() {
+		let ml = MessageLoop::new(3);
+		ml.master().initialize(ml.signature()).unwrap();
+		ml.run();
+
+		assert_eq!(ml.master().wait(), Ok(()));
+		assert!(ml.nodes.values().all(|node| node.session.is_finished()));
+		assert!(ml.nodes.values().all(|node| !node.key_storage.contains(&SessionId::default())));
+	}
+
+	#[test]
+	fn ed25519_key_share_is_removed_from_all_nodes() {
+		let ml = MessageLoop::new(3);
+		for node in ml.nodes.values() {
+			node.key_storage.insert_ed25519(SessionId::default(), Ed25519KeyShare {
+				author: Default::default(),
+				threshold: 1,
+				nodes: ml.nodes.keys().cloned().collect(),
+				commitments: vec![Default::default(), Default::default()],
+				secret_share: Default::default(),
+			}).unwrap();
+		}
+		ml.master().initialize(ml.signature()).unwrap();
+		ml.run();
+
+		assert_eq!(ml.master().wait(), Ok(()));
+		assert!(ml.nodes.values().all(|node| node.key_storage.get_ed25519(&SessionId::default()) == Ok(None)));
+	}
+
+	#[test]
+	fn key_is_removed_on_single_node_cluster() {
+		let ml = MessageLoop::new(1);
+		ml.master().initialize(ml.signature()).unwrap();
+
+		assert!(ml.master().is_finished());
+		assert_eq!(ml.master().wait(), Ok(()));
+		assert!(!ml.nodes[&ml.master].key_storage.contains(&SessionId::default()));
+	}
+
+	#[test]
+	fn key_removal_fails_if_not_signed_by_administrator() {
+		let ml = MessageLoop::new(3);
+		let signature = sign(Random.generate().unwrap().secret(), &key_removal_hash(&SessionId::default())).unwrap();
+		assert_eq!(ml.master().initialize(signature), Err(Error::AccessDenied));
+		assert!(ml.nodes.values().all(|node| node.key_storage.contains(&SessionId::default())));
+	}
+
+	#[test]
+	fn key_removal_fails_if_key_id_is_signed_instead_of_request_hash() {
+		let ml = MessageLoop::new(3);
+		let signature = sign(ml.admin_key_pair.secret(), &SessionId::default()).unwrap();
+		assert_eq!(ml.master().initialize(signature), Err(Error::AccessDenied));
+	}
+}
@@ -208,6 +208,12 @@ impl<T> SessionImpl<T> where T: SessionTransport {
 			.clone())
 	}
 
+	/// Return all known key versions along with nodes holding these versions.
+	pub fn versions(&self) -> Result<BTreeMap<H256, BTreeSet<NodeId>>, Error> {
+		self.data.lock().versions.clone()
+			.ok_or(Error::InvalidStateForRequest)
+	}
+
 	/// Set continue action.
 	pub fn set_continue_action(&self, action: ContinueAction) {
 		self.data.lock().continue_with = Some(action);
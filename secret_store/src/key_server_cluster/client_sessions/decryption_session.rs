@@ -20,7 +20,7 @@ use std::time;
 use parking_lot::{Mutex, Condvar};
 use ethereum_types::{Address, H256};
 use ethkey::Secret;
-use key_server_cluster::{Error, AclStorage, DocumentKeyShare, NodeId, SessionId, Requester,
+use key_server_cluster::{Error, AclStorage, AclOperation, DocumentKeyShare, NodeId, SessionId, Requester,
 	EncryptedDocumentKeyShadow, SessionMeta};
 use key_server_cluster::cluster::Cluster;
 use key_server_cluster::cluster_sessions::{SessionIdWithSubSession, ClusterSession};
@@ -169,8 +169,8 @@ impl SessionImpl {
 		let consensus_session = ConsensusSession::new(ConsensusSessionParams {
 			meta: params.meta.clone(),
 			consensus_executor: match requester {
-				Some(requester) => KeyAccessJob::new_on_master(params.meta.id.clone(), params.acl_storage.clone(), requester),
-				None => KeyAccessJob::new_on_slave(params.meta.id.clone(), params.acl_storage.clone()),
+				Some(requester) => KeyAccessJob::new_on_master(params.meta.id.clone(), params.acl_storage.clone(), AclOperation::Decrypt, requester),
+				None => KeyAccessJob::new_on_slave(params.meta.id.clone(), params.acl_storage.clone(), AclOperation::Decrypt),
 			},
 			consensus_transport: consensus_transport,
 		})?;
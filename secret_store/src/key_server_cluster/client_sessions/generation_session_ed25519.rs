@@ -0,0 +1,856 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeSet, BTreeMap};
+use std::sync::Arc;
+use parking_lot::{Mutex, Condvar};
+use curve25519_dalek::edwards::EdwardsPoint;
+use curve25519_dalek::scalar::Scalar;
+use ethereum_types::{Address, H256};
+use key_server_cluster::{Error, NodeId, SessionId, KeyStorage, Ed25519KeyShare};
+use key_server_cluster::cluster::Cluster;
+use key_server_cluster::cluster_sessions::ClusterSession;
+use key_server_cluster::math_ed25519 as math;
+use key_server_cluster::message::{Message, Ed25519GenerationMessage, Ed25519InitializeGeneration,
+	Ed25519ConfirmGenerationInitialization, Ed25519CompleteGenerationInitialization, Ed25519KeysDissemination,
+	Ed25519GenerationCompleted, Ed25519GenerationError, Ed25519CommitmentsEcho, Ed25519GenerationConfirmed};
+
+/// Distributed Ed25519 key generation session.
+/// Based on FROST DKG (Pedersen DKG with Feldman commitments and proofs of knowledge), performed over the Ed25519 curve.
+/// Brief overview:
+/// 1) initialization: master node (which has received request for generating joint key) asks every other node to join the session
+/// 2) key dissemination: every node generates random polynom and sends its value in every other node' number to the corresponding node,
+/// along with commitments to the polynom coefficients and proof of knowledge of its constant term
+/// 3) key verification: every node checks received values against commitments to the polynoms coefficients and checks proofs
+/// 4) commitments echo: every node sends hash of all received commitments to every other node, so that dealer which has sent
+/// different commitments to different nodes is detected
+/// 5) key generation: every node computes its share of joint secret and commitments to the joint polynom
+/// 6) completion: every slave node reports joint public key to the master node. Once all keys match, master node asks every node
+/// to store its key share
+pub struct SessionImpl {
+	/// Session core.
+	core: SessionCore,
+	/// Session data.
+	data: Mutex<SessionData>,
+}
+
+/// Immutable session data.
+struct SessionCore {
+	/// Session id (equals to the key id).
+	pub id: SessionId,
+	/// Id of node, on which this session is running.
+	pub self_node_id: NodeId,
+	/// Key storage.
+	pub key_storage: Arc<KeyStorage>,
+	/// Cluster which allows this node to send messages to other nodes in the cluster.
+	pub cluster: Arc<Cluster>,
+	/// Session-level nonce.
+	pub nonce: u64,
+	/// SessionImpl completion condvar.
+	pub completed: Condvar,
+}
+
+/// Mutable session data.
+struct SessionData {
+	/// Current state of the session.
+	pub state: SessionState,
+	/// Reference to the node, which has started this session.
+	pub master: Option<NodeId>,
+	/// Address of the creator of the session.
+	pub author: Option<Address>,
+	/// Signing threshold.
+	pub threshold: Option<usize>,
+	/// All session participants.
+	pub nodes: BTreeSet<NodeId>,
+	/// Nodes, which have not yet confirmed initialization or reported completion (on master node only).
+	pub confirmations: BTreeSet<NodeId>,
+	/// Secret shares and commitments, received from every node (including this node).
+	pub received: BTreeMap<NodeId, (Scalar, Vec<EdwardsPoint>)>,
+	/// Hash of the commitments of all dealers, received by this node.
+	pub commitments_hash: Option<H256>,
+	/// Nodes, which have not yet echoed their commitments hash.
+	pub echoes: BTreeSet<NodeId>,
+	/// Generated public key and key share, which is stored once generation is confirmed by the master node.
+	pub generated: Option<(H256, Ed25519KeyShare)>,
+	/// Session result.
+	pub result: Option<Result<H256, Error>>,
+}
+
+/// Ed25519 key generation session state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionState {
+	/// Every node starts in this state.
+	WaitingForInitialization,
+	/// Master node waits for every other node to confirm initialization.
+	WaitingForInitializationConfirm,
+	/// Slave node waits for master node to complete initialization.
+	WaitingForInitializationComplete,
+	/// Node waits for secret shares from every other node.
+	WaitingForKeysDissemination,
+	/// Node waits for commitments hash from every other node.
+	WaitingForCommitmentsEcho,
+	/// Master node waits for every other node to report generated key.
+	WaitingForGenerationCompleted,
+	/// Slave node waits for master node to confirm that every node has generated the same key.
+	WaitingForGenerationConfirmed,
+	/// Session is completed successfully.
+	Finished,
+	/// Session has failed.
+	Failed,
+}
+
+/// SessionImpl creation parameters
+pub struct SessionParams {
+	/// Session id.
+	pub id: SessionId,
+	/// Id of node, on which this session is running.
+	pub self_node_id: NodeId,
+	/// Key storage.
+	pub key_storage: Arc<KeyStorage>,
+	/// Cluster.
+	pub cluster: Arc<Cluster>,
+	/// Session nonce.
+	pub nonce: u64,
+}
+
+impl SessionImpl {
+	/// Create new Ed25519 key generation session.
+	pub fn new(params: SessionParams) -> Self {
+		SessionImpl {
+			core: SessionCore {
+				id: params.id,
+				self_node_id: params.self_node_id,
+				key_storage: params.key_storage,
+				cluster: params.cluster,
+				nonce: params.nonce,
+				completed: Condvar::new(),
+			},
+			data: Mutex::new(SessionData {
+				state: SessionState::WaitingForInitialization,
+				master: None,
+				author: None,
+				threshold: None,
+				nodes: BTreeSet::new(),
+				confirmations: BTreeSet::new(),
+				received: BTreeMap::new(),
+				commitments_hash: None,
+				echoes: BTreeSet::new(),
+				generated: None,
+				result: None,
+			}),
+		}
+	}
+
+	/// Get current session state.
+	pub fn state(&self) -> SessionState {
+		self.data.lock().state
+	}
+
+	/// Wait for session completion. Returns compressed joint public key.
+	pub fn wait(&self) -> Result<H256, Error> {
+		Self::wait_session(&self.core.completed, &self.data, None, |data| data.result.clone())
+			.expect("wait_session returns Some if called without timeout; qed")
+	}
+
+	/// Start new session initialization. This must be called on master node.
+	pub fn initialize(&self, author: Address, threshold: usize, nodes: BTreeSet<NodeId>) -> Result<(), Error> {
+		let mut data = self.data.lock();
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		self.check_session_params(threshold, &nodes)?;
+
+		data.master = Some(self.core.self_node_id.clone());
+		data.author = Some(author.clone());
+		data.threshold = Some(threshold);
+		data.confirmations = nodes.iter().filter(|n| **n != self.core.self_node_id).cloned().collect();
+		data.nodes = nodes;
+
+		if data.confirmations.is_empty() {
+			return Self::disseminate_keys(&self.core, &mut *data);
+		}
+
+		for node in &data.confirmations {
+			self.core.cluster.send(node, Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519InitializeGeneration(Ed25519InitializeGeneration {
+				session: self.core.id.clone().into(),
+				session_nonce: self.core.nonce,
+				author: author.clone().into(),
+				nodes: data.nodes.iter().cloned().map(Into::into).collect(),
+				threshold: threshold,
+			})))?;
+		}
+		data.state = SessionState::WaitingForInitializationConfirm;
+
+		Ok(())
+	}
+
+	/// Process single message.
+	pub fn process_message(&self, sender: &NodeId, message: &Ed25519GenerationMessage) -> Result<(), Error> {
+		if self.core.nonce != message.session_nonce() {
+			return Err(Error::ReplayProtection);
+		}
+
+		match message {
+			&Ed25519GenerationMessage::Ed25519InitializeGeneration(ref message) =>
+				self.on_initialize_generation(sender, message),
+			&Ed25519GenerationMessage::Ed25519ConfirmGenerationInitialization(ref message) =>
+				self.on_confirm_initialization(sender, message),
+			&Ed25519GenerationMessage::Ed25519CompleteGenerationInitialization(ref message) =>
+				self.on_complete_initialization(sender, message),
+			&Ed25519GenerationMessage::Ed25519KeysDissemination(ref message) =>
+				self.on_keys_dissemination(sender, message),
+			&Ed25519GenerationMessage::Ed25519CommitmentsEcho(ref message) =>
+				self.on_commitments_echo(sender, message),
+			&Ed25519GenerationMessage::Ed25519GenerationCompleted(ref message) =>
+				self.on_generation_completed(sender, message),
+			&Ed25519GenerationMessage::Ed25519GenerationConfirmed(ref message) =>
+				self.on_generation_confirmed(sender, message),
+			&Ed25519GenerationMessage::Ed25519GenerationError(ref message) => {
+				self.on_session_error(sender, message.error.clone());
+				Ok(())
+			},
+		}
+	}
+
+	/// When session initialization message is received.
+	pub fn on_initialize_generation(&self, sender: &NodeId, message: &Ed25519InitializeGeneration) -> Result<(), Error> {
+		debug_assert!(self.core.id == *message.session);
+		debug_assert!(sender != &self.core.self_node_id);
+
+		let mut data = self.data.lock();
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		let nodes: BTreeSet<NodeId> = message.nodes.iter().cloned().map(Into::into).collect();
+		if !nodes.contains(sender) {
+			return Err(Error::InvalidMessage);
+		}
+		self.check_session_params(message.threshold, &nodes)?;
+
+		self.core.cluster.send(sender, Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519ConfirmGenerationInitialization(Ed25519ConfirmGenerationInitialization {
+			session: self.core.id.clone().into(),
+			session_nonce: self.core.nonce,
+		})))?;
+
+		data.master = Some(sender.clone());
+		data.author = Some(message.author.clone().into());
+		data.threshold = Some(message.threshold);
+		data.nodes = nodes;
+		data.state = SessionState::WaitingForInitializationComplete;
+
+		Ok(())
+	}
+
+	/// When session initialization confirmation message is received.
+	pub fn on_confirm_initialization(&self, sender: &NodeId, message: &Ed25519ConfirmGenerationInitialization) -> Result<(), Error> {
+		debug_assert!(self.core.id == *message.session);
+		debug_assert!(sender != &self.core.self_node_id);
+
+		let mut data = self.data.lock();
+		if data.state != SessionState::WaitingForInitializationConfirm {
+			return Err(Error::InvalidStateForRequest);
+		}
+		if !data.confirmations.remove(sender) {
+			return Err(Error::InvalidNodeForRequest);
+		}
+		if !data.confirmations.is_empty() {
+			return Ok(());
+		}
+
+		for node in data.nodes.iter().filter(|n| **n != self.core.self_node_id) {
+			self.core.cluster.send(node, Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519CompleteGenerationInitialization(Ed25519CompleteGenerationInitialization {
+				session: self.core.id.clone().into(),
+				session_nonce: self.core.nonce,
+			})))?;
+		}
+
+		Self::disseminate_keys(&self.core, &mut *data)
+	}
+
+	/// When session initialization completion message is received.
+	pub fn on_complete_initialization(&self, sender: &NodeId, message: &Ed25519CompleteGenerationInitialization) -> Result<(), Error> {
+		debug_assert!(self.core.id == *message.session);
+		debug_assert!(sender != &self.core.self_node_id);
+
+		let mut data = self.data.lock();
+		if data.master.as_ref() != Some(sender) {
+			return Err(Error::InvalidMessage);
+		}
+		if data.state != SessionState::WaitingForInitializationComplete {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		Self::disseminate_keys(&self.core, &mut *data)
+	}
+
+	/// When secret share and commitments are received from other node.
+	pub fn on_keys_dissemination(&self, sender: &NodeId, message: &Ed25519KeysDissemination) -> Result<(), Error> {
+		debug_assert!(self.core.id == *message.session);
+		debug_assert!(sender != &self.core.self_node_id);
+
+		let mut data = self.data.lock();
+		match data.state {
+			SessionState::WaitingForInitializationConfirm | SessionState::WaitingForInitializationComplete =>
+				return Err(Error::TooEarlyForRequest),
+			SessionState::WaitingForKeysDissemination => (),
+			_ => return Err(Error::InvalidStateForRequest),
+		}
+		if !data.nodes.contains(sender) || data.received.contains_key(sender) {
+			return Err(Error::InvalidNodeForRequest);
+		}
+
+		let threshold = data.threshold.expect("threshold is filled in initialization phase; KD phase follows initialization phase; qed");
+		if message.commitments.len() != threshold + 1 {
+			return Err(Error::InvalidMessage);
+		}
+
+		let secret_share = math::scalar_from_h256(&message.secret_share)?;
+		let commitments = message.commitments.iter()
+			.map(|c| math::point_from_h256(c))
+			.collect::<Result<Vec<_>, _>>()?;
+		let proof_commitment = math::point_from_h256(&message.proof_commitment)?;
+		let proof_response = math::scalar_from_h256(&message.proof_response)?;
+		if !math::verify_proof_of_knowledge(sender, &*self.core.id, &commitments[0], &proof_commitment, &proof_response) {
+			return Err(Error::InvalidMessage);
+		}
+		if !math::keys_verification(&math::node_id_number(&self.core.self_node_id), &secret_share, &commitments) {
+			return Err(Error::InvalidMessage);
+		}
+
+		data.received.insert(sender.clone(), (secret_share, commitments));
+		if data.received.len() != data.nodes.len() {
+			return Ok(());
+		}
+
+		Self::echo_commitments(&self.core, &mut *data)
+	}
+
+	/// When other node reports hash of all commitments it has received.
+	pub fn on_commitments_echo(&self, sender: &NodeId, message: &Ed25519CommitmentsEcho) -> Result<(), Error> {
+		debug_assert!(self.core.id == *message.session);
+		debug_assert!(sender != &self.core.self_node_id);
+
+		let mut data = self.data.lock();
+		match data.state {
+			SessionState::WaitingForInitializationConfirm | SessionState::WaitingForInitializationComplete |
+				SessionState::WaitingForKeysDissemination => return Err(Error::TooEarlyForRequest),
+			SessionState::WaitingForCommitmentsEcho => (),
+			_ => return Err(Error::InvalidStateForRequest),
+		}
+		if !data.echoes.remove(sender) {
+			return Err(Error::InvalidNodeForRequest);
+		}
+		if data.commitments_hash.as_ref() != Some(&*message.commitments_hash) {
+			return Err(Error::InvalidMessage);
+		}
+		if !data.echoes.is_empty() {
+			return Ok(());
+		}
+
+		Self::complete_generation(&self.core, &mut *data)
+	}
+
+	/// When slave node reports generated key.
+	pub fn on_generation_completed(&self, sender: &NodeId, message: &Ed25519GenerationCompleted) -> Result<(), Error> {
+		debug_assert!(self.core.id == *message.session);
+		debug_assert!(sender != &self.core.self_node_id);
+
+		let mut data = self.data.lock();
+		if data.master.as_ref() != Some(&self.core.self_node_id) {
+			return Err(Error::InvalidMessage);
+		}
+		match data.state {
+			SessionState::WaitingForKeysDissemination | SessionState::WaitingForCommitmentsEcho => return Err(Error::TooEarlyForRequest),
+			SessionState::WaitingForGenerationCompleted => (),
+			_ => return Err(Error::InvalidStateForRequest),
+		}
+		if !data.confirmations.remove(sender) {
+			return Err(Error::InvalidNodeForRequest);
+		}
+
+		let public = data.generated.as_ref()
+			.map(|&(ref public, _)| public.clone())
+			.expect("key is generated before master waits for completion; qed");
+		if public != *message.public {
+			return Err(Error::InvalidMessage);
+		}
+		if !data.confirmations.is_empty() {
+			return Ok(());
+		}
+
+		for node in data.nodes.iter().filter(|n| **n != self.core.self_node_id) {
+			self.core.cluster.send(node, Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519GenerationConfirmed(Ed25519GenerationConfirmed {
+				session: self.core.id.clone().into(),
+				session_nonce: self.core.nonce,
+			})))?;
+		}
+
+		Self::store_key_share(&self.core, &mut *data)
+	}
+
+	/// When master node confirms that every node has generated the same key.
+	pub fn on_generation_confirmed(&self, sender: &NodeId, message: &Ed25519GenerationConfirmed) -> Result<(), Error> {
+		debug_assert!(self.core.id == *message.session);
+		debug_assert!(sender != &self.core.self_node_id);
+
+		let mut data = self.data.lock();
+		if data.master.as_ref() != Some(sender) {
+			return Err(Error::InvalidMessage);
+		}
+		if data.state != SessionState::WaitingForGenerationConfirmed {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		Self::store_key_share(&self.core, &mut *data)
+	}
+
+	/// Check session parameters.
+	fn check_session_params(&self, threshold: usize, nodes: &BTreeSet<NodeId>) -> Result<(), Error> {
+		if !nodes.contains(&self.core.self_node_id) {
+			return Err(Error::InvalidNodeForRequest);
+		}
+		if threshold >= nodes.len() {
+			return Err(Error::NotEnoughNodesForThreshold);
+		}
+		if self.core.key_storage.get_ed25519(&self.core.id)?.is_some() {
+			return Err(Error::ServerKeyAlreadyGenerated);
+		}
+
+		Ok(())
+	}
+
+	/// Generate random polynom and send its values to every other node.
+	fn disseminate_keys(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		let threshold = data.threshold.expect("threshold is filled in initialization phase; KD phase follows initialization phase; qed");
+		let polynom = math::generate_random_polynom(threshold)?;
+		let commitments = math::public_values_generation(&polynom);
+		let serialized_commitments: Vec<_> = commitments.iter().map(|c| math::point_to_h256(c).into()).collect();
+		let (proof_commitment, proof_response) = math::compute_proof_of_knowledge(&core.self_node_id, &*core.id, &polynom[0], &commitments[0])?;
+
+		for node in data.nodes.iter().filter(|n| **n != core.self_node_id) {
+			let secret_share = math::compute_polynom(&polynom, &math::node_id_number(node));
+			core.cluster.send(node, Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519KeysDissemination(Ed25519KeysDissemination {
+				session: core.id.clone().into(),
+				session_nonce: core.nonce,
+				secret_share: math::scalar_to_h256(&secret_share).into(),
+				commitments: serialized_commitments.clone(),
+				proof_commitment: math::point_to_h256(&proof_commitment).into(),
+				proof_response: math::scalar_to_h256(&proof_response).into(),
+			})))?;
+		}
+
+		let self_secret_share = math::compute_polynom(&polynom, &math::node_id_number(&core.self_node_id));
+		data.received.insert(core.self_node_id.clone(), (self_secret_share, commitments));
+		data.state = SessionState::WaitingForKeysDissemination;
+
+		if data.received.len() != data.nodes.len() {
+			return Ok(());
+		}
+
+		Self::echo_commitments(core, data)
+	}
+
+	/// Send hash of all received commitments to every other node, once values from all nodes are received.
+	fn echo_commitments(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		let commitments: BTreeMap<_, _> = data.received.iter().map(|(node, r)| (node.clone(), r.1.clone())).collect();
+		let commitments_hash = math::compute_commitments_hash(&commitments);
+
+		data.echoes = data.nodes.iter().filter(|n| **n != core.self_node_id).cloned().collect();
+		for node in &data.echoes {
+			core.cluster.send(node, Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519CommitmentsEcho(Ed25519CommitmentsEcho {
+				session: core.id.clone().into(),
+				session_nonce: core.nonce,
+				commitments_hash: commitments_hash.clone().into(),
+			})))?;
+		}
+		data.commitments_hash = Some(commitments_hash);
+		data.state = SessionState::WaitingForCommitmentsEcho;
+
+		if !data.echoes.is_empty() {
+			return Ok(());
+		}
+
+		Self::complete_generation(core, data)
+	}
+
+	/// Compute key share, once every node has received the same commitments, and report it to the master node.
+	fn complete_generation(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		let secret_share = data.received.values().fold(Scalar::zero(), |result, r| result + r.0);
+		let commitments = math::combine_commitments(&data.received.values().map(|r| r.1.clone()).collect::<Vec<_>>())?;
+		let public = math::point_to_h256(&commitments[0]);
+
+		data.generated = Some((public.clone(), Ed25519KeyShare {
+			author: data.author.clone().expect("author is filled in initialization phase; KG phase follows initialization phase; qed"),
+			threshold: data.threshold.expect("threshold is filled in initialization phase; KG phase follows initialization phase; qed"),
+			nodes: data.nodes.clone(),
+			commitments: commitments.iter().map(math::point_to_h256).collect(),
+			secret_share: math::scalar_to_h256(&secret_share),
+		}));
+
+		let master = data.master.clone().expect("master is filled in initialization phase; KG phase follows initialization phase; qed");
+		if master != core.self_node_id {
+			core.cluster.send(&master, Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519GenerationCompleted(Ed25519GenerationCompleted {
+				session: core.id.clone().into(),
+				session_nonce: core.nonce,
+				public: public.into(),
+			})))?;
+			data.state = SessionState::WaitingForGenerationConfirmed;
+			return Ok(());
+		}
+
+		data.confirmations = data.nodes.iter().filter(|n| **n != core.self_node_id).cloned().collect();
+		if !data.confirmations.is_empty() {
+			data.state = SessionState::WaitingForGenerationCompleted;
+			return Ok(());
+		}
+
+		Self::store_key_share(core, data)
+	}
+
+	/// Store generated key share and complete the session.
+	fn store_key_share(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		let (public, key_share) = data.generated.take().expect("key is generated before it is stored; qed");
+		core.key_storage.insert_ed25519(core.id.clone(), key_share)?;
+
+		data.result = Some(Ok(public));
+		data.state = SessionState::Finished;
+		core.completed.notify_all();
+
+		Ok(())
+	}
+
+	/// Complete session with error.
+	fn fail_session(&self, error: Error) {
+		let mut data = self.data.lock();
+		if data.state == SessionState::Finished || data.state == SessionState::Failed {
+			return;
+		}
+
+		data.state = SessionState::Failed;
+		data.result = Some(Err(error));
+		self.core.completed.notify_all();
+	}
+}
+
+impl ClusterSession for SessionImpl {
+	type Id = SessionId;
+
+	fn type_name() -> &'static str {
+		"ed25519 generation"
+	}
+
+	fn id(&self) -> SessionId {
+		self.core.id.clone()
+	}
+
+	fn is_finished(&self) -> bool {
+		let data = self.data.lock();
+		data.state == SessionState::Failed
+			|| data.state == SessionState::Finished
+	}
+
+	fn on_node_timeout(&self, node: &NodeId) {
+		// all nodes are required for generation session
+		// => fail without check
+		warn!("{}: ed25519 generation session failed because {} connection has timeouted", self.core.self_node_id, node);
+
+		self.fail_session(Error::NodeDisconnected);
+	}
+
+	fn on_session_timeout(&self) {
+		warn!("{}: ed25519 generation session failed with timeout", self.core.self_node_id);
+
+		self.fail_session(Error::NodeDisconnected);
+	}
+
+	fn on_session_error(&self, node: &NodeId, error: Error) {
+		// error in generation session is considered fatal
+		// => broadcast error if error occured on this node
+		if *node == self.core.self_node_id {
+			// do not bother processing send error, as we already processing error
+			let _ = self.core.cluster.broadcast(Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519GenerationError(Ed25519GenerationError {
+				session: self.core.id.clone().into(),
+				session_nonce: self.core.nonce,
+				error: error.clone().into(),
+			})));
+		}
+
+		self.fail_session(error);
+	}
+
+	fn on_message(&self, sender: &NodeId, message: &Message) -> Result<(), Error> {
+		match *message {
+			Message::Ed25519Generation(ref message) => self.process_message(sender, message),
+			_ => unreachable!("cluster checks message to be correct before passing; qed"),
+		}
+	}
+}
+
+#[cfg(test)]
+pub mod tests {
+	use std::sync::Arc;
+	use std::collections::{BTreeMap, VecDeque};
+	use curve25519_dalek::scalar::Scalar;
+	use ethkey::{Random, Generator};
+	use key_server_cluster::{NodeId, SessionId, Error, KeyStorage, DummyKeyStorage};
+	use key_server_cluster::cluster::tests::DummyCluster;
+	use key_server_cluster::cluster_sessions::ClusterSession;
+	use key_server_cluster::math_ed25519 as math;
+	use key_server_cluster::message::{Message, Ed25519GenerationMessage};
+	use super::{SessionImpl, SessionParams, SessionState};
+
+	pub struct Node {
+		pub cluster: Arc<DummyCluster>,
+		pub key_storage: Arc<DummyKeyStorage>,
+		pub session: SessionImpl,
+	}
+
+	pub struct MessageLoop {
+		pub session_id: SessionId,
+		pub master: NodeId,
+		pub nodes: BTreeMap<NodeId, Node>,
+		pub queue: VecDeque<(NodeId, NodeId, Message)>,
+	}
+
+	impl MessageLoop {
+		pub fn new(num_nodes: usize) -> Self {
+			let session_id = SessionId::default();
+			let nodes_ids: Vec<NodeId> = (0..num_nodes).map(|_| Random.generate().unwrap().public().clone()).collect();
+			let master = nodes_ids[0].clone();
+			let nodes = nodes_ids.iter().map(|node_id| {
+				let cluster = Arc::new(DummyCluster::new(node_id.clone()));
+				cluster.add_nodes(nodes_ids.iter().cloned());
+				let key_storage = Arc::new(DummyKeyStorage::default());
+				let session = SessionImpl::new(SessionParams {
+					id: session_id.clone(),
+					self_node_id: node_id.clone(),
+					key_storage: key_storage.clone(),
+					cluster: cluster.clone(),
+					nonce: 0,
+				});
+
+				(node_id.clone(), Node {
+					cluster: cluster,
+					key_storage: key_storage,
+					session: session,
+				})
+			}).collect();
+
+			MessageLoop {
+				session_id: session_id,
+				master: master,
+				nodes: nodes,
+				queue: VecDeque::new(),
+			}
+		}
+
+		pub fn master(&self) -> &SessionImpl {
+			&self.nodes[&self.master].session
+		}
+
+		pub fn take_message(&mut self) -> Option<(NodeId, NodeId, Message)> {
+			self.nodes.iter()
+				.filter_map(|(from, node)| node.cluster.take_message().map(|(to, message)| (from.clone(), to, message)))
+				.nth(0)
+				.or_else(|| self.queue.pop_front())
+		}
+
+		pub fn run(&mut self) {
+			while let Some((from, to, message)) = self.take_message() {
+				match self.nodes[&to].session.on_message(&from, &message) {
+					Ok(()) => (),
+					Err(Error::TooEarlyForRequest) => self.queue.push_back((from, to, message)),
+					Err(err) => panic!("unexpected error {:?}", err),
+				}
+			}
+		}
+
+		pub fn run_generation(num_nodes: usize, threshold: usize) -> Self {
+			let mut ml = MessageLoop::new(num_nodes);
+			let nodes = ml.nodes.keys().cloned().collect();
+			ml.master().initialize(Default::default(), threshold, nodes).unwrap();
+			ml.run();
+			ml
+		}
+	}
+
+	#[test]
+	fn ed25519_key_is_generated() {
+		for &(threshold, num_nodes) in &[(0, 1), (1, 3), (2, 5), (4, 5)] {
+			let ml = MessageLoop::run_generation(num_nodes, threshold);
+			let public = ml.master().wait().unwrap();
+			assert!(ml.nodes.values().all(|n| n.session.state() == SessionState::Finished));
+			assert!(ml.nodes.values().all(|n| n.session.wait() == Ok(public.clone())));
+
+			// every stored share matches joint commitments
+			let key_shares: BTreeMap<_, _> = ml.nodes.iter()
+				.map(|(id, n)| (id.clone(), n.key_storage.get_ed25519(&ml.session_id).unwrap().unwrap()))
+				.collect();
+			for (node_id, key_share) in &key_shares {
+				assert_eq!(key_share.public(), Ok(&public));
+				assert_eq!(key_share.commitments, key_shares[&ml.master].commitments);
+				let commitments: Vec<_> = key_share.commitments.iter().map(|c| math::point_from_h256(c).unwrap()).collect();
+				let secret_share = math::scalar_from_h256(&key_share.secret_share).unwrap();
+				assert!(math::keys_verification(&math::node_id_number(node_id), &secret_share, &commitments));
+			}
+
+			// any threshold + 1 shares are enough to restore the joint secret
+			let id_numbers: Vec<_> = key_shares.keys().take(threshold + 1).map(math::node_id_number).collect();
+			let joint_secret = key_shares.iter().take(threshold + 1).enumerate()
+				.map(|(i, (_, key_share))| math::scalar_from_h256(&key_share.secret_share).unwrap()
+					* math::compute_lagrange_coefficient(&id_numbers[i], id_numbers.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, n)| n)).unwrap())
+				.fold(Scalar::zero(), |result, share| result + share);
+			assert_eq!(math::point_to_h256(&math::base_mul(&joint_secret)), public);
+		}
+	}
+
+	#[test]
+	fn fails_to_initialize_when_threshold_is_too_large() {
+		let ml = MessageLoop::new(3);
+		let nodes = ml.nodes.keys().cloned().collect();
+		assert_eq!(ml.master().initialize(Default::default(), 3, nodes), Err(Error::NotEnoughNodesForThreshold));
+	}
+
+	#[test]
+	fn fails_to_initialize_when_key_is_already_generated() {
+		let ml = MessageLoop::run_generation(3, 1);
+		let key_share = ml.nodes[&ml.master].key_storage.get_ed25519(&ml.session_id).unwrap().unwrap();
+
+		let ml2 = MessageLoop::new(3);
+		ml2.nodes[&ml2.master].key_storage.insert_ed25519(ml2.session_id.clone(), key_share).unwrap();
+		let nodes = ml2.nodes.keys().cloned().collect();
+		assert_eq!(ml2.master().initialize(Default::default(), 1, nodes), Err(Error::ServerKeyAlreadyGenerated));
+	}
+
+	#[test]
+	fn fails_when_invalid_share_is_received() {
+		let mut ml = MessageLoop::new(3);
+		let nodes = ml.nodes.keys().cloned().collect();
+		ml.master().initialize(Default::default(), 1, nodes).unwrap();
+
+		loop {
+			let (from, to, mut message) = ml.take_message().unwrap();
+			if let Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519KeysDissemination(ref mut message)) = message {
+				message.secret_share = math::scalar_to_h256(&math::generate_random_scalar().unwrap()).into();
+			}
+
+			match ml.nodes[&to].session.on_message(&from, &message) {
+				Ok(()) => continue,
+				Err(Error::TooEarlyForRequest) => ml.queue.push_back((from, to, message)),
+				Err(err) => {
+					assert_eq!(err, Error::InvalidMessage);
+					break;
+				},
+			}
+		}
+	}
+
+	#[test]
+	fn fails_when_invalid_proof_of_knowledge_is_received() {
+		let mut ml = MessageLoop::new(3);
+		let nodes = ml.nodes.keys().cloned().collect();
+		ml.master().initialize(Default::default(), 1, nodes).unwrap();
+
+		loop {
+			let (from, to, mut message) = ml.take_message().unwrap();
+			if let Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519KeysDissemination(ref mut message)) = message {
+				message.proof_response = math::scalar_to_h256(&math::generate_random_scalar().unwrap()).into();
+			}
+
+			match ml.nodes[&to].session.on_message(&from, &message) {
+				Ok(()) => continue,
+				Err(Error::TooEarlyForRequest) => ml.queue.push_back((from, to, message)),
+				Err(err) => {
+					assert_eq!(err, Error::InvalidMessage);
+					break;
+				},
+			}
+		}
+	}
+
+	#[test]
+	fn fails_when_dealer_sends_different_commitments_to_different_nodes() {
+		let mut ml = MessageLoop::new(3);
+		let nodes = ml.nodes.keys().cloned().collect();
+		ml.master().initialize(Default::default(), 1, nodes).unwrap();
+
+		// master sends shares of another (valid) polynom to a single node
+		let victim = ml.nodes.keys().find(|n| **n != ml.master).cloned().unwrap();
+		let mut replaced = false;
+		loop {
+			let (from, to, mut message) = ml.take_message().unwrap();
+			if let Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519KeysDissemination(ref mut message)) = message {
+				if from == ml.master && to == victim {
+					let polynom = math::generate_random_polynom(1).unwrap();
+					let commitments = math::public_values_generation(&polynom);
+					let (proof_commitment, proof_response) = math::compute_proof_of_knowledge(&from, &*ml.session_id, &polynom[0], &commitments[0]).unwrap();
+					message.secret_share = math::scalar_to_h256(&math::compute_polynom(&polynom, &math::node_id_number(&to))).into();
+					message.commitments = commitments.iter().map(|c| math::point_to_h256(c).into()).collect();
+					message.proof_commitment = math::point_to_h256(&proof_commitment).into();
+					message.proof_response = math::scalar_to_h256(&proof_response).into();
+					replaced = true;
+				}
+			}
+
+			match ml.nodes[&to].session.on_message(&from, &message) {
+				Ok(()) => continue,
+				Err(Error::TooEarlyForRequest) => ml.queue.push_back((from, to, message)),
+				Err(err) => {
+					assert!(replaced);
+					assert_eq!(err, Error::InvalidMessage);
+					match message {
+						Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519CommitmentsEcho(_)) => (),
+						_ => panic!("inconsistency is detected by commitments echo"),
+					}
+					break;
+				},
+			}
+		}
+
+		assert!(ml.nodes.values().all(|n| n.key_storage.get_ed25519(&ml.session_id).unwrap().is_none()));
+	}
+
+	#[test]
+	fn key_share_is_not_stored_until_generation_is_confirmed() {
+		let mut ml = MessageLoop::new(3);
+		let nodes = ml.nodes.keys().cloned().collect();
+		ml.master().initialize(Default::default(), 1, nodes).unwrap();
+
+		while let Some((from, to, message)) = ml.take_message() {
+			if let Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519GenerationConfirmed(_)) = message {
+				continue;
+			}
+
+			match ml.nodes[&to].session.on_message(&from, &message) {
+				Ok(()) => (),
+				Err(Error::TooEarlyForRequest) => ml.queue.push_back((from, to, message)),
+				Err(err) => panic!("unexpected error {:?}", err),
+			}
+		}
+
+		for (node_id, node) in &ml.nodes {
+			match *node_id == ml.master {
+				true => assert!(node.key_storage.get_ed25519(&ml.session_id).unwrap().is_some()),
+				false => {
+					assert_eq!(node.session.state(), SessionState::WaitingForGenerationConfirmed);
+					assert!(node.key_storage.get_ed25519(&ml.session_id).unwrap().is_none());
+				},
+			}
+		}
+	}
+}
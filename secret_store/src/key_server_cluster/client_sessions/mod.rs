@@ -17,5 +17,7 @@
 pub mod decryption_session;
 pub mod encryption_session;
 pub mod generation_session;
+pub mod generation_session_ed25519;
 pub mod signing_session_ecdsa;
+pub mod signing_session_ed25519;
 pub mod signing_session_schnorr;
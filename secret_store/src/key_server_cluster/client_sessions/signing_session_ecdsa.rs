@@ -20,7 +20,7 @@ use std::sync::Arc;
 use parking_lot::{Mutex, Condvar};
 use ethkey::{Public, Secret, Signature, sign};
 use ethereum_types::H256;
-use key_server_cluster::{Error, NodeId, SessionId, SessionMeta, AclStorage, DocumentKeyShare, Requester};
+use key_server_cluster::{Error, NodeId, SessionId, SessionMeta, AclStorage, AclOperation, DocumentKeyShare, Requester};
 use key_server_cluster::cluster::{Cluster};
 use key_server_cluster::cluster_sessions::{SessionIdWithSubSession, ClusterSession};
 use key_server_cluster::generation_session::{SessionImpl as GenerationSession, SessionParams as GenerationSessionParams,
@@ -191,8 +191,8 @@ impl SessionImpl {
 				connected_nodes_count: params.meta.connected_nodes_count,
 			},
 			consensus_executor: match requester {
-				Some(requester) => KeyAccessJob::new_on_master(params.meta.id.clone(), params.acl_storage.clone(), requester),
-				None => KeyAccessJob::new_on_slave(params.meta.id.clone(), params.acl_storage.clone()),
+				Some(requester) => KeyAccessJob::new_on_master(params.meta.id.clone(), params.acl_storage.clone(), AclOperation::Sign, requester),
+				None => KeyAccessJob::new_on_slave(params.meta.id.clone(), params.acl_storage.clone(), AclOperation::Sign),
 			},
 			consensus_transport: consensus_transport,
 		})?;
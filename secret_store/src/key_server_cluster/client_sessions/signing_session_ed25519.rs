@@ -0,0 +1,822 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeSet, BTreeMap};
+use std::sync::Arc;
+use parking_lot::{Mutex, Condvar};
+use bytes::Bytes;
+use ethkey::Secret;
+use ethereum_types::H256;
+use key_server_cluster::{Error, NodeId, SessionId, Requester, SessionMeta, AclStorage, AclOperation, Ed25519KeyShare};
+use key_server_cluster::cluster::Cluster;
+use key_server_cluster::cluster_sessions::{SessionIdWithSubSession, ClusterSession};
+use key_server_cluster::math_ed25519::{self as math, SigningNonces, SigningCommitments};
+use key_server_cluster::message::{Message, Ed25519SigningMessage, Ed25519SigningConsensusMessage, Ed25519RequestNonceCommitment,
+	Ed25519NonceCommitment, Ed25519Commitment, Ed25519RequestPartialSignature, Ed25519PartialSignature,
+	Ed25519SigningSessionError, Ed25519SigningSessionCompleted, ConsensusMessage, InitializeConsensusSession,
+	ConfirmConsensusInitialization};
+use key_server_cluster::jobs::job_session::JobTransport;
+use key_server_cluster::jobs::key_access_job::KeyAccessJob;
+use key_server_cluster::jobs::signing_job_ed25519::{Ed25519PartialSigningRequest, Ed25519PartialSigningResponse, Ed25519SigningJob};
+use key_server_cluster::jobs::consensus_session::{ConsensusSessionParams, ConsensusSessionState, ConsensusSession};
+
+/// Distributed Ed25519 signing session.
+/// Based on "FROST: Flexible Round-Optimized Schnorr Threshold Signatures" paper. Produced signature is a regular
+/// RFC 8032 Ed25519 signature, which could be verified by any Ed25519 implementation.
+/// Brief overview:
+/// 1) initialization: master node (which has received request for signing the message) requests all other nodes to sign the message
+/// 2) ACL check: all nodes which have received the request are querying ACL-contract to check if requestor has access to the private key
+/// 3) nonces commitment: every node of the selected consensus group generates signing nonces and sends commitments to the master node
+/// 4) partial signing: every node of the consensus group computes signature share, using commitments of the whole group
+/// 5) signing: master node checks every signature share and combines shares into the signature
+pub struct SessionImpl {
+	/// Session core.
+	core: SessionCore,
+	/// Session data.
+	data: Mutex<SessionData>,
+}
+
+/// Immutable session data.
+struct SessionCore {
+	/// Session metadata.
+	pub meta: SessionMeta,
+	/// Signing session access key.
+	pub access_key: Secret,
+	/// Key share.
+	pub key_share: Option<Ed25519KeyShare>,
+	/// Cluster which allows this node to send messages to other nodes in the cluster.
+	pub cluster: Arc<Cluster>,
+	/// Session-level nonce.
+	pub nonce: u64,
+	/// SessionImpl completion condvar.
+	pub completed: Condvar,
+}
+
+/// Signing consensus session type.
+type SigningConsensusSession = ConsensusSession<KeyAccessJob, SigningConsensusTransport, Ed25519SigningJob, SigningJobTransport>;
+
+/// Mutable session data.
+struct SessionData {
+	/// Session state.
+	pub state: SessionState,
+	/// Message to sign.
+	pub message: Option<Bytes>,
+	/// Consensus-based signing session.
+	pub consensus_session: SigningConsensusSession,
+	/// Signing nonces of this node.
+	pub nonces: Option<SigningNonces>,
+	/// Signing nonces commitments of the consensus group nodes (on master node only).
+	pub nonces_commitments: BTreeMap<NodeId, SigningCommitments>,
+	/// Consensus group nodes, which have not yet sent their nonces commitments (on master node only).
+	pub pending_commitments: BTreeSet<NodeId>,
+	/// Signing result.
+	pub result: Option<Result<Bytes, Error>>,
+}
+
+/// Signing session state.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(test, derive(Clone, Copy))]
+pub enum SessionState {
+	/// State when consensus is establishing.
+	ConsensusEstablishing,
+	/// State when signing nonces are committed.
+	NoncesCommitment,
+	/// State when signature is computing.
+	SignatureComputing,
+}
+
+/// Session creation parameters
+pub struct SessionParams {
+	/// Session metadata.
+	pub meta: SessionMeta,
+	/// Session access key.
+	pub access_key: Secret,
+	/// Key share.
+	pub key_share: Option<Ed25519KeyShare>,
+	/// ACL storage.
+	pub acl_storage: Arc<AclStorage>,
+	/// Cluster
+	pub cluster: Arc<Cluster>,
+	/// Session nonce.
+	pub nonce: u64,
+}
+
+/// Signing consensus transport.
+struct SigningConsensusTransport {
+	/// Session id.
+	id: SessionId,
+	/// Session access key.
+	access_key: Secret,
+	/// Session-level nonce.
+	nonce: u64,
+	/// Public key of the key (on master node).
+	version: Option<H256>,
+	/// Cluster.
+	cluster: Arc<Cluster>,
+}
+
+/// Signing job transport
+struct SigningJobTransport {
+	/// Session id.
+	id: SessionId,
+	/// Session access key.
+	access_key: Secret,
+	/// Session-level nonce.
+	nonce: u64,
+	/// Cluster.
+	cluster: Arc<Cluster>,
+}
+
+impl SessionImpl {
+	/// Create new signing session.
+	pub fn new(params: SessionParams, requester: Option<Requester>) -> Result<Self, Error> {
+		debug_assert_eq!(params.meta.threshold, params.key_share.as_ref().map(|ks| ks.threshold).unwrap_or_default());
+
+		let consensus_transport = SigningConsensusTransport {
+			id: params.meta.id.clone(),
+			access_key: params.access_key.clone(),
+			nonce: params.nonce,
+			version: None,
+			cluster: params.cluster.clone(),
+		};
+		let consensus_session = ConsensusSession::new(ConsensusSessionParams {
+			meta: params.meta.clone(),
+			consensus_executor: match requester {
+				Some(requester) => KeyAccessJob::new_on_master(params.meta.id.clone(), params.acl_storage.clone(), AclOperation::Sign, requester),
+				None => KeyAccessJob::new_on_slave(params.meta.id.clone(), params.acl_storage.clone(), AclOperation::Sign),
+			},
+			consensus_transport: consensus_transport,
+		})?;
+
+		Ok(SessionImpl {
+			core: SessionCore {
+				meta: params.meta,
+				access_key: params.access_key,
+				key_share: params.key_share,
+				cluster: params.cluster,
+				nonce: params.nonce,
+				completed: Condvar::new(),
+			},
+			data: Mutex::new(SessionData {
+				state: SessionState::ConsensusEstablishing,
+				message: None,
+				consensus_session: consensus_session,
+				nonces: None,
+				nonces_commitments: BTreeMap::new(),
+				pending_commitments: BTreeSet::new(),
+				result: None,
+			}),
+		})
+	}
+
+	/// Get session state.
+	#[cfg(test)]
+	pub fn state(&self) -> SessionState {
+		self.data.lock().state
+	}
+
+	/// Wait for session completion.
+	pub fn wait(&self) -> Result<Bytes, Error> {
+		Self::wait_session(&self.core.completed, &self.data, None, |data| data.result.clone())
+			.expect("wait_session returns Some if called without timeout; qed")
+	}
+
+	/// Initialize signing session on master node.
+	pub fn initialize(&self, message: Bytes) -> Result<(), Error> {
+		debug_assert_eq!(self.core.meta.self_node_id, self.core.meta.master_node_id);
+
+		let key_share = self.core.key_share.as_ref().ok_or(Error::ServerKeyIsNotFound)?;
+		let public = key_share.public()?.clone();
+
+		let mut data = self.data.lock();
+		let non_isolated_nodes = self.core.cluster.nodes();
+		let consensus_nodes: BTreeSet<_> = key_share.nodes.iter()
+			.filter(|n| non_isolated_nodes.contains(*n))
+			.cloned()
+			.chain(::std::iter::once(self.core.meta.self_node_id.clone()))
+			.collect();
+
+		data.consensus_session.consensus_job_mut().transport_mut().version = Some(public);
+		data.message = Some(message);
+		data.consensus_session.initialize(consensus_nodes)?;
+
+		if data.consensus_session.state() == ConsensusSessionState::ConsensusEstablished {
+			self.core.start_nonces_commitment(&mut *data)?;
+			if data.consensus_session.state() == ConsensusSessionState::Finished {
+				let result = data.consensus_session.result()?;
+				Self::set_signing_result(&self.core, &mut *data, Ok(result));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Process signing message.
+	pub fn process_message(&self, sender: &NodeId, message: &Ed25519SigningMessage) -> Result<(), Error> {
+		if self.core.nonce != message.session_nonce() {
+			return Err(Error::ReplayProtection);
+		}
+
+		match message {
+			&Ed25519SigningMessage::Ed25519SigningConsensusMessage(ref message) =>
+				self.on_consensus_message(sender, message),
+			&Ed25519SigningMessage::Ed25519RequestNonceCommitment(ref message) =>
+				self.on_nonce_commitment_requested(sender, message),
+			&Ed25519SigningMessage::Ed25519NonceCommitment(ref message) =>
+				self.on_nonce_commitment(sender, message),
+			&Ed25519SigningMessage::Ed25519RequestPartialSignature(ref message) =>
+				self.on_partial_signature_requested(sender, message),
+			&Ed25519SigningMessage::Ed25519PartialSignature(ref message) =>
+				self.on_partial_signature(sender, message),
+			&Ed25519SigningMessage::Ed25519SigningSessionError(ref message) =>
+				self.process_node_error(Some(&sender), message.error.clone()),
+			&Ed25519SigningMessage::Ed25519SigningSessionCompleted(ref message) =>
+				self.on_session_completed(sender, message),
+		}
+	}
+
+	/// When consensus-related message is received.
+	pub fn on_consensus_message(&self, sender: &NodeId, message: &Ed25519SigningConsensusMessage) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+		let is_establishing_consensus = data.consensus_session.state() == ConsensusSessionState::EstablishingConsensus;
+
+		if let &ConsensusMessage::InitializeConsensusSession(ref msg) = &message.message {
+			let public: H256 = msg.version.clone().into();
+			let has_key_share = self.core.key_share.as_ref()
+				.map(|ks| ks.public().ok() == Some(&public))
+				.unwrap_or(false);
+			data.consensus_session.consensus_job_mut().executor_mut().set_has_key_share(has_key_share);
+		}
+		data.consensus_session.on_consensus_message(&sender, &message.message)?;
+
+		let is_consensus_established = data.consensus_session.state() == ConsensusSessionState::ConsensusEstablished;
+		if self.core.meta.self_node_id != self.core.meta.master_node_id || !is_establishing_consensus || !is_consensus_established {
+			return Ok(());
+		}
+
+		self.core.start_nonces_commitment(&mut *data)
+	}
+
+	/// When signing nonces commitment is requested.
+	pub fn on_nonce_commitment_requested(&self, sender: &NodeId, message: &Ed25519RequestNonceCommitment) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		if self.core.key_share.is_none() {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+		if sender != &self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+		if data.consensus_session.state() != ConsensusSessionState::ConsensusEstablished {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// previous nonces (if any) are dropped here => they're never used
+		let (nonces, commitments) = math::generate_signing_nonces()?;
+		self.core.cluster.send(sender, Message::Ed25519Signing(Ed25519SigningMessage::Ed25519NonceCommitment(Ed25519NonceCommitment {
+			session: self.core.meta.id.clone().into(),
+			sub_session: self.core.access_key.clone().into(),
+			session_nonce: self.core.nonce,
+			commitment: serialize_commitments(&commitments),
+		})))?;
+		data.nonces = Some(nonces);
+		data.state = SessionState::NoncesCommitment;
+
+		Ok(())
+	}
+
+	/// When signing nonces commitment is received.
+	pub fn on_nonce_commitment(&self, sender: &NodeId, message: &Ed25519NonceCommitment) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+		if data.state != SessionState::NoncesCommitment || self.core.meta.self_node_id != self.core.meta.master_node_id {
+			return Err(Error::InvalidStateForRequest);
+		}
+		if !data.pending_commitments.remove(sender) {
+			return Err(Error::InvalidNodeForRequest);
+		}
+
+		data.nonces_commitments.insert(sender.clone(), deserialize_commitments(&message.commitment)?);
+		if !data.pending_commitments.is_empty() {
+			return Ok(());
+		}
+
+		self.core.disseminate_jobs(&mut *data)?;
+		if data.consensus_session.state() == ConsensusSessionState::Finished {
+			self.complete_session(&mut *data)?;
+		}
+
+		Ok(())
+	}
+
+	/// When partial signature is requested.
+	pub fn on_partial_signature_requested(&self, sender: &NodeId, message: &Ed25519RequestPartialSignature) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let key_share = match self.core.key_share.as_ref() {
+			None => return Err(Error::InvalidMessage),
+			Some(key_share) => key_share,
+		};
+
+		let mut data = self.data.lock();
+
+		if sender != &self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+		if data.state != SessionState::NoncesCommitment {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		let commitments = message.commitments.iter()
+			.map(|(node, commitment)| deserialize_commitments(commitment).map(|commitment| (node.clone().into(), commitment)))
+			.collect::<Result<BTreeMap<_, _>, Error>>()?;
+		let signing_job = Ed25519SigningJob::new_on_slave(self.core.meta.self_node_id.clone(), key_share.clone(), data.nonces.take())?;
+		let signing_transport = self.core.signing_transport();
+		data.state = SessionState::SignatureComputing;
+
+		data.consensus_session.on_job_request(sender, Ed25519PartialSigningRequest {
+			id: message.request_id.clone().into(),
+			message: message.message.clone().into(),
+			commitments: commitments,
+		}, signing_job, signing_transport).map(|_| ())
+	}
+
+	/// When partial signature is received.
+	pub fn on_partial_signature(&self, sender: &NodeId, message: &Ed25519PartialSignature) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+		data.consensus_session.on_job_response(sender, Ed25519PartialSigningResponse {
+			request_id: message.request_id.clone().into(),
+			partial_signature: math::scalar_from_h256(&message.partial_signature)?,
+		})?;
+
+		if data.consensus_session.state() != ConsensusSessionState::Finished {
+			return Ok(());
+		}
+
+		self.complete_session(&mut *data)
+	}
+
+	/// When session is completed.
+	pub fn on_session_completed(&self, sender: &NodeId, message: &Ed25519SigningSessionCompleted) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		self.data.lock().consensus_session.on_session_completed(sender)
+	}
+
+	/// Notify non-rejected nodes and set session result on master node.
+	fn complete_session(&self, data: &mut SessionData) -> Result<(), Error> {
+		// send compeltion signal to all nodes, except for rejected nodes
+		for node in data.consensus_session.consensus_non_rejected_nodes() {
+			self.core.cluster.send(&node, Message::Ed25519Signing(Ed25519SigningMessage::Ed25519SigningSessionCompleted(Ed25519SigningSessionCompleted {
+				session: self.core.meta.id.clone().into(),
+				sub_session: self.core.access_key.clone().into(),
+				session_nonce: self.core.nonce,
+			})))?;
+		}
+
+		let result = data.consensus_session.result()?;
+		Self::set_signing_result(&self.core, data, Ok(result));
+
+		Ok(())
+	}
+
+	/// Process error from the other node.
+	fn process_node_error(&self, node: Option<&NodeId>, error: Error) -> Result<(), Error> {
+		let mut data = self.data.lock();
+		let is_self_node_error = node.map(|n| n == &self.core.meta.self_node_id).unwrap_or(false);
+		// error is always fatal if coming from this node
+		if is_self_node_error {
+			Self::set_signing_result(&self.core, &mut *data, Err(error.clone()));
+			return Err(error);
+		}
+
+		// nonces are committed to the selected consensus group => the round can't continue without any of its nodes
+		let is_commitment_node_error = data.state == SessionState::NoncesCommitment
+			&& self.core.meta.self_node_id == self.core.meta.master_node_id
+			&& node.map(|n| data.pending_commitments.contains(n) || data.nonces_commitments.contains_key(n)).unwrap_or(true);
+
+		match {
+			match node {
+				Some(node) => data.consensus_session.on_node_error(node, error.clone()),
+				None => data.consensus_session.on_session_timeout(),
+			}
+		} {
+			Ok(false) if !is_commitment_node_error => {
+				Ok(())
+			},
+			Ok(false) => {
+				warn!("{}: ed25519 signing session failed with error: {:?} from {:?}", &self.core.meta.self_node_id, error, node);
+				Self::set_signing_result(&self.core, &mut *data, Err(Error::ConsensusTemporaryUnreachable));
+				Err(Error::ConsensusTemporaryUnreachable)
+			},
+			Ok(true) => {
+				// signature shares are computed using nonces, committed to the previous consensus group
+				// => new nonces must be committed by the new consensus group
+				match self.core.start_nonces_commitment(&mut *data) {
+					Ok(()) => Ok(()),
+					Err(err) => {
+						warn!("{}: ed25519 signing session failed with error: {:?} from {:?}", &self.core.meta.self_node_id, error, node);
+						Self::set_signing_result(&self.core, &mut *data, Err(err.clone()));
+						Err(err)
+					}
+				}
+			},
+			Err(err) => {
+				warn!("{}: ed25519 signing session failed with error: {:?} from {:?}", &self.core.meta.self_node_id, error, node);
+				Self::set_signing_result(&self.core, &mut *data, Err(err.clone()));
+				Err(err)
+			},
+		}
+	}
+
+	/// Set signing session result.
+	fn set_signing_result(core: &SessionCore, data: &mut SessionData, result: Result<Bytes, Error>) {
+		data.result = Some(result);
+		core.completed.notify_all();
+	}
+}
+
+impl ClusterSession for SessionImpl {
+	type Id = SessionIdWithSubSession;
+
+	fn type_name() -> &'static str {
+		"ed25519 signing"
+	}
+
+	fn id(&self) -> SessionIdWithSubSession {
+		SessionIdWithSubSession::new(self.core.meta.id.clone(), self.core.access_key.clone())
+	}
+
+	fn is_finished(&self) -> bool {
+		let data = self.data.lock();
+		data.consensus_session.state() == ConsensusSessionState::Failed
+			|| data.consensus_session.state() == ConsensusSessionState::Finished
+			|| data.result.is_some()
+	}
+
+	fn on_node_timeout(&self, node: &NodeId) {
+		// ignore error, only state matters
+		let _ = self.process_node_error(Some(node), Error::NodeDisconnected);
+	}
+
+	fn on_session_timeout(&self) {
+		// ignore error, only state matters
+		let _ = self.process_node_error(None, Error::NodeDisconnected);
+	}
+
+	fn on_session_error(&self, node: &NodeId, error: Error) {
+		let is_fatal = self.process_node_error(Some(node), error.clone()).is_err();
+		let is_this_node_error = *node == self.core.meta.self_node_id;
+		if is_fatal || is_this_node_error {
+			// error in signing session is non-fatal, if occurs on slave node
+			// => either respond with error
+			// => or broadcast error
+			let message = Message::Ed25519Signing(Ed25519SigningMessage::Ed25519SigningSessionError(Ed25519SigningSessionError {
+				session: self.core.meta.id.clone().into(),
+				sub_session: self.core.access_key.clone().into(),
+				session_nonce: self.core.nonce,
+				error: error.clone().into(),
+			}));
+
+			// do not bother processing send error, as we already processing error
+			let _ = if self.core.meta.master_node_id == self.core.meta.self_node_id {
+				self.core.cluster.broadcast(message)
+			} else {
+				self.core.cluster.send(&self.core.meta.master_node_id, message)
+			};
+		}
+	}
+
+	fn on_message(&self, sender: &NodeId, message: &Message) -> Result<(), Error> {
+		match *message {
+			Message::Ed25519Signing(ref message) => self.process_message(sender, message),
+			_ => unreachable!("cluster checks message to be correct before passing; qed"),
+		}
+	}
+}
+
+impl SessionCore {
+	pub fn signing_transport(&self) -> SigningJobTransport {
+		SigningJobTransport {
+			id: self.meta.id.clone(),
+			access_key: self.access_key.clone(),
+			nonce: self.nonce,
+			cluster: self.cluster.clone()
+		}
+	}
+
+	/// Ask every node of the consensus group to commit to its signing nonces.
+	pub fn start_nonces_commitment(&self, data: &mut SessionData) -> Result<(), Error> {
+		if self.key_share.is_none() {
+			return Err(Error::ServerKeyIsNotFound);
+		}
+
+		let consensus_group = data.consensus_session.select_consensus_group()?.clone();
+		data.nonces = None;
+		data.nonces_commitments.clear();
+		if consensus_group.contains(&self.meta.self_node_id) {
+			let (nonces, commitments) = math::generate_signing_nonces()?;
+			data.nonces = Some(nonces);
+			data.nonces_commitments.insert(self.meta.self_node_id.clone(), commitments);
+		}
+
+		data.pending_commitments = consensus_group.into_iter().filter(|n| *n != self.meta.self_node_id).collect();
+		data.state = SessionState::NoncesCommitment;
+		for node in &data.pending_commitments {
+			self.cluster.send(node, Message::Ed25519Signing(Ed25519SigningMessage::Ed25519RequestNonceCommitment(Ed25519RequestNonceCommitment {
+				session: self.meta.id.clone().into(),
+				sub_session: self.access_key.clone().into(),
+				session_nonce: self.nonce,
+			})))?;
+		}
+
+		if !data.pending_commitments.is_empty() {
+			return Ok(());
+		}
+
+		self.disseminate_jobs(data)
+	}
+
+	/// Send partial signing requests to every node of the consensus group.
+	pub fn disseminate_jobs(&self, data: &mut SessionData) -> Result<(), Error> {
+		let key_share = match self.key_share.as_ref() {
+			None => return Err(Error::ServerKeyIsNotFound),
+			Some(key_share) => key_share,
+		};
+
+		let message = data.message.clone()
+			.expect("jobs are disseminated on master node only; message is filled in initialize() on master node; qed");
+		let signing_job = Ed25519SigningJob::new_on_master(self.meta.self_node_id.clone(), key_share.clone(),
+			data.nonces.take(), message, data.nonces_commitments.clone())?;
+		data.state = SessionState::SignatureComputing;
+		data.consensus_session.disseminate_jobs(signing_job, self.signing_transport(), false).map(|_| ())
+	}
+}
+
+impl JobTransport for SigningConsensusTransport {
+	type PartialJobRequest=Requester;
+	type PartialJobResponse=bool;
+
+	fn send_partial_request(&self, node: &NodeId, request: Requester) -> Result<(), Error> {
+		let version = self.version.as_ref()
+			.expect("send_partial_request is called on initialized master node only; version is filled in before initialization starts on master node; qed");
+		self.cluster.send(node, Message::Ed25519Signing(Ed25519SigningMessage::Ed25519SigningConsensusMessage(Ed25519SigningConsensusMessage {
+			session: self.id.clone().into(),
+			sub_session: self.access_key.clone().into(),
+			session_nonce: self.nonce,
+			message: ConsensusMessage::InitializeConsensusSession(InitializeConsensusSession {
+				requester: request.into(),
+				version: version.clone().into(),
+			})
+		})))
+	}
+
+	fn send_partial_response(&self, node: &NodeId, response: bool) -> Result<(), Error> {
+		self.cluster.send(node, Message::Ed25519Signing(Ed25519SigningMessage::Ed25519SigningConsensusMessage(Ed25519SigningConsensusMessage {
+			session: self.id.clone().into(),
+			sub_session: self.access_key.clone().into(),
+			session_nonce: self.nonce,
+			message: ConsensusMessage::ConfirmConsensusInitialization(ConfirmConsensusInitialization {
+				is_confirmed: response,
+			})
+		})))
+	}
+}
+
+impl JobTransport for SigningJobTransport {
+	type PartialJobRequest=Ed25519PartialSigningRequest;
+	type PartialJobResponse=Ed25519PartialSigningResponse;
+
+	fn send_partial_request(&self, node: &NodeId, request: Ed25519PartialSigningRequest) -> Result<(), Error> {
+		self.cluster.send(node, Message::Ed25519Signing(Ed25519SigningMessage::Ed25519RequestPartialSignature(Ed25519RequestPartialSignature {
+			session: self.id.clone().into(),
+			sub_session: self.access_key.clone().into(),
+			session_nonce: self.nonce,
+			request_id: request.id.into(),
+			message: request.message.into(),
+			commitments: request.commitments.iter().map(|(n, c)| (n.clone().into(), serialize_commitments(c))).collect(),
+		})))
+	}
+
+	fn send_partial_response(&self, node: &NodeId, response: Ed25519PartialSigningResponse) -> Result<(), Error> {
+		self.cluster.send(node, Message::Ed25519Signing(Ed25519SigningMessage::Ed25519PartialSignature(Ed25519PartialSignature {
+			session: self.id.clone().into(),
+			sub_session: self.access_key.clone().into(),
+			session_nonce: self.nonce,
+			request_id: response.request_id.into(),
+			partial_signature: math::scalar_to_h256(&response.partial_signature).into(),
+		})))
+	}
+}
+
+fn serialize_commitments(commitments: &SigningCommitments) -> Ed25519Commitment {
+	Ed25519Commitment {
+		hiding: math::point_to_h256(&commitments.hiding).into(),
+		binding: math::point_to_h256(&commitments.binding).into(),
+	}
+}
+
+fn deserialize_commitments(commitment: &Ed25519Commitment) -> Result<SigningCommitments, Error> {
+	Ok(SigningCommitments {
+		hiding: math::point_from_h256(&commitment.hiding)?,
+		binding: math::point_from_h256(&commitment.binding)?,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::collections::{BTreeMap, VecDeque};
+	use ethkey::{self, Random, Generator, KeyPair};
+	use acl_storage::DummyAclStorage;
+	use key_server_cluster::{NodeId, SessionId, SessionMeta, Error, KeyStorage, Requester};
+	use key_server_cluster::cluster::tests::DummyCluster;
+	use key_server_cluster::cluster_sessions::ClusterSession;
+	use key_server_cluster::generation_session_ed25519::tests::MessageLoop as KeyGenerationMessageLoop;
+	use key_server_cluster::math_ed25519 as math;
+	use key_server_cluster::message::{Message, Ed25519SigningMessage};
+	use super::{SessionImpl, SessionParams};
+
+	struct Node {
+		pub cluster: Arc<DummyCluster>,
+		pub session: SessionImpl,
+	}
+
+	struct MessageLoop {
+		pub session_id: SessionId,
+		pub requester: KeyPair,
+		pub master: NodeId,
+		pub nodes: BTreeMap<NodeId, Node>,
+		pub queue: VecDeque<(NodeId, NodeId, Message)>,
+		pub acl_storages: Vec<Arc<DummyAclStorage>>,
+	}
+
+	impl MessageLoop {
+		pub fn new(gl: &KeyGenerationMessageLoop) -> Self {
+			let session_id = gl.session_id.clone();
+			let requester = Random.generate().unwrap();
+			let signature = ethkey::sign(requester.secret(), &session_id).unwrap();
+			let access_key: ethkey::Secret = "834cb736f02d9c968dfaf0c37658a1d86ff140554fc8b59c9fdad5a8cf810eec".parse().unwrap();
+			let mut acl_storages = Vec::new();
+			let nodes = gl.nodes.iter().map(|(node_id, gl_node)| {
+				let acl_storage = Arc::new(DummyAclStorage::default());
+				acl_storages.push(acl_storage.clone());
+				let cluster = Arc::new(DummyCluster::new(node_id.clone()));
+				cluster.add_nodes(gl.nodes.keys().cloned());
+				let key_share = gl_node.key_storage.get_ed25519(&session_id).unwrap();
+				let session = SessionImpl::new(SessionParams {
+					meta: SessionMeta {
+						id: session_id.clone(),
+						self_node_id: node_id.clone(),
+						master_node_id: gl.master.clone(),
+						threshold: key_share.as_ref().map(|ks| ks.threshold).unwrap_or_default(),
+						configured_nodes_count: gl.nodes.len(),
+						connected_nodes_count: gl.nodes.len(),
+					},
+					access_key: access_key.clone(),
+					key_share: key_share,
+					acl_storage: acl_storage,
+					cluster: cluster.clone(),
+					nonce: 0,
+				}, if *node_id == gl.master { Some(Requester::Signature(signature.clone())) } else { None }).unwrap();
+
+				(node_id.clone(), Node { cluster: cluster, session: session })
+			}).collect();
+
+			MessageLoop {
+				session_id: session_id,
+				requester: requester,
+				master: gl.master.clone(),
+				nodes: nodes,
+				queue: VecDeque::new(),
+				acl_storages: acl_storages,
+			}
+		}
+
+		pub fn master(&self) -> &SessionImpl {
+			&self.nodes[&self.master].session
+		}
+
+		pub fn take_message(&mut self) -> Option<(NodeId, NodeId, Message)> {
+			self.nodes.iter()
+				.filter_map(|(from, node)| node.cluster.take_message().map(|(to, message)| (from.clone(), to, message)))
+				.nth(0)
+				.or_else(|| self.queue.pop_front())
+		}
+
+		pub fn run(&mut self) {
+			while let Some((from, to, message)) = self.take_message() {
+				match self.nodes[&to].session.on_message(&from, &message) {
+					Ok(()) => (),
+					Err(Error::TooEarlyForRequest) => self.queue.push_back((from, to, message)),
+					Err(err) => panic!("unexpected error {:?}", err),
+				}
+			}
+		}
+	}
+
+	fn public(gl: &KeyGenerationMessageLoop) -> ::curve25519_dalek::edwards::EdwardsPoint {
+		math::point_from_h256(&gl.master().wait().unwrap()).unwrap()
+	}
+
+	#[test]
+	fn ed25519_signature_is_computed() {
+		for &(threshold, num_nodes) in &[(0, 1), (0, 3), (1, 3), (2, 5), (4, 5)] {
+			let gl = KeyGenerationMessageLoop::run_generation(num_nodes, threshold);
+			let mut ml = MessageLoop::new(&gl);
+			let message = b"message to sign".to_vec();
+			ml.master().initialize(message.clone()).unwrap();
+			ml.run();
+
+			let signature = ml.master().wait().unwrap();
+			assert_eq!(signature.len(), 64);
+			assert!(math::verify_signature(&public(&gl), &message, &signature));
+			assert!(ml.nodes.values().all(|n| n.session.is_finished()));
+		}
+	}
+
+	#[test]
+	fn ed25519_signing_fails_when_key_is_unknown_to_master() {
+		let gl = KeyGenerationMessageLoop::new(3);
+		let ml = MessageLoop::new(&gl);
+		assert_eq!(ml.master().initialize(b"message".to_vec()), Err(Error::ServerKeyIsNotFound));
+	}
+
+	#[test]
+	fn ed25519_signing_fails_when_access_is_denied_by_other_nodes() {
+		let gl = KeyGenerationMessageLoop::run_generation(3, 1);
+		let mut ml = MessageLoop::new(&gl);
+		let requester = ethkey::public_to_address(ml.requester.public());
+		for (acl_storage, node_id) in ml.acl_storages.iter().zip(ml.nodes.keys()) {
+			if *node_id != ml.master {
+				acl_storage.prohibit(requester.clone(), ml.session_id.clone());
+			}
+		}
+
+		ml.master().initialize(b"message".to_vec()).unwrap();
+		loop {
+			let (from, to, message) = ml.take_message().unwrap();
+			match ml.nodes[&to].session.on_message(&from, &message) {
+				Ok(()) => continue,
+				Err(err) => {
+					assert_eq!(err, Error::ConsensusUnreachable);
+					break;
+				},
+			}
+		}
+	}
+
+	#[test]
+	fn ed25519_signing_rejects_invalid_signature_share() {
+		let gl = KeyGenerationMessageLoop::run_generation(3, 2);
+		let mut ml = MessageLoop::new(&gl);
+		ml.master().initialize(b"message".to_vec()).unwrap();
+
+		loop {
+			let (from, to, mut message) = ml.take_message().unwrap();
+			if let Message::Ed25519Signing(Ed25519SigningMessage::Ed25519PartialSignature(ref mut message)) = message {
+				message.partial_signature = math::scalar_to_h256(&math::generate_random_scalar().unwrap()).into();
+			}
+
+			match ml.nodes[&to].session.on_message(&from, &message) {
+				Ok(()) => continue,
+				Err(err) => {
+					assert_eq!(err, Error::ConsensusUnreachable);
+					break;
+				},
+			}
+		}
+	}
+}
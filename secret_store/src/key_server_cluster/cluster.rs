@@ -28,6 +28,7 @@ use tokio_core::reactor::{Handle, Remote, Interval};
 use tokio_core::net::{TcpListener, TcpStream};
 use ethkey::{Public, KeyPair, Signature, Random, Generator};
 use ethereum_types::{Address, H256};
+use bytes::Bytes;
 use key_server_cluster::{Error, NodeId, SessionId, Requester, AclStorage, KeyStorage, KeyServerSet, NodeKeyPair};
 use key_server_cluster::cluster_sessions::{ClusterSession, AdminSession, ClusterSessions, SessionIdWithSubSession,
 	ClusterSessionsContainer, SERVERS_SET_CHANGE_SESSION_ID, create_cluster_view, AdminSessionCreationData, ClusterSessionsListener};
@@ -38,8 +39,11 @@ use key_server_cluster::decryption_session::{SessionImpl as DecryptionSession};
 use key_server_cluster::encryption_session::{SessionImpl as EncryptionSession};
 use key_server_cluster::signing_session_ecdsa::{SessionImpl as EcdsaSigningSession};
 use key_server_cluster::signing_session_schnorr::{SessionImpl as SchnorrSigningSession};
+use key_server_cluster::generation_session_ed25519::{SessionImpl as Ed25519GenerationSession};
+use key_server_cluster::signing_session_ed25519::{SessionImpl as Ed25519SigningSession};
 use key_server_cluster::key_version_negotiation_session::{SessionImpl as KeyVersionNegotiationSession,
-	IsolatedSessionTransport as KeyVersionNegotiationSessionTransport, ContinueAction};
+	IsolatedSessionTransport as KeyVersionNegotiationSessionTransport, ContinueAction,
+	SessionResultComputer as KeyVersionNegotiationResultComputer, LargestSupportResultComputer};
 use key_server_cluster::io::{DeadlineStatus, ReadMessage, SharedTcpStream, read_encrypted_message, WriteMessage, write_encrypted_message};
 use key_server_cluster::net::{accept_connection as net_accept_connection, connect as net_connect, Connection as NetConnection};
 use key_server_cluster::connection_trigger::{Maintain, ConnectionTrigger, SimpleConnectionTrigger, ServersSetChangeSessionCreatorConnector};
@@ -75,10 +79,18 @@ pub trait ClusterClient: Send + Sync {
 	fn new_schnorr_signing_session(&self, session_id: SessionId, requester: Requester, version: Option<H256>, message_hash: H256) -> Result<Arc<SchnorrSigningSession>, Error>;
 	/// Start new ECDSA session.
 	fn new_ecdsa_signing_session(&self, session_id: SessionId, requester: Requester, version: Option<H256>, message_hash: H256) -> Result<Arc<EcdsaSigningSession>, Error>;
+	/// Start new Ed25519 key generation session.
+	fn new_ed25519_generation_session(&self, session_id: SessionId, author: Address, threshold: usize) -> Result<Arc<Ed25519GenerationSession>, Error>;
+	/// Start new Ed25519 signing session.
+	fn new_ed25519_signing_session(&self, session_id: SessionId, requester: Requester, message: Bytes) -> Result<Arc<Ed25519SigningSession>, Error>;
 	/// Start new key version negotiation session.
 	fn new_key_version_negotiation_session(&self, session_id: SessionId) -> Result<Arc<KeyVersionNegotiationSession<KeyVersionNegotiationSessionTransport>>, Error>;
+	/// Start new key version negotiation session, which waits for key versions from all connected nodes.
+	fn new_key_versions_discovery_session(&self, session_id: SessionId) -> Result<Arc<KeyVersionNegotiationSession<KeyVersionNegotiationSessionTransport>>, Error>;
 	/// Start new servers set change session.
 	fn new_servers_set_change_session(&self, session_id: Option<SessionId>, migration_id: Option<H256>, new_nodes_set: BTreeSet<NodeId>, old_set_signature: Signature, new_set_signature: Signature) -> Result<Arc<AdminSession>, Error>;
+	/// Start new key removal session.
+	fn new_key_removal_session(&self, session_id: SessionId, signature: Signature) -> Result<Arc<AdminSession>, Error>;
 
 	/// Listen for new generation sessions.
 	fn add_generation_listener(&self, listener: Arc<ClusterSessionsListener<GenerationSession>>);
@@ -97,7 +109,6 @@ pub trait ClusterClient: Send + Sync {
 	#[cfg(test)]
 	fn connect(&self);
 	/// Get key storage.
-	#[cfg(test)]
 	fn key_storage(&self) -> Arc<KeyStorage>;
 }
 
@@ -462,6 +473,10 @@ impl ClusterCore {
 				.map(|_| ()).unwrap_or_default(),
 			Message::EcdsaSigning(message) => Self::process_message(&data, &data.sessions.ecdsa_signing_sessions, connection, Message::EcdsaSigning(message))
 				.map(|_| ()).unwrap_or_default(),
+			Message::Ed25519Generation(message) => Self::process_message(&data, &data.sessions.ed25519_generation_sessions, connection, Message::Ed25519Generation(message))
+				.map(|_| ()).unwrap_or_default(),
+			Message::Ed25519Signing(message) => Self::process_message(&data, &data.sessions.ed25519_signing_sessions, connection, Message::Ed25519Signing(message))
+				.map(|_| ()).unwrap_or_default(),
 			Message::ServersSetChange(message) => {
 				let message = Message::ServersSetChange(message);
 				let is_initialization_message = message.is_initialization_message();
@@ -478,6 +493,8 @@ impl ClusterCore {
 			},
 			Message::ShareAdd(message) => Self::process_message(&data, &data.sessions.admin_sessions, connection, Message::ShareAdd(message))
 				.map(|_| ()).unwrap_or_default(),
+			Message::KeyRemoval(message) => Self::process_message(&data, &data.sessions.admin_sessions, connection, Message::KeyRemoval(message))
+				.map(|_| ()).unwrap_or_default(),
 			Message::Cluster(message) => ClusterCore::process_cluster_message(data, connection, message),
 		}
 	}
@@ -556,8 +573,10 @@ impl ClusterCore {
 		fn requires_all_connections(message: &Message) -> bool {
 			match *message {
 				Message::Generation(_) => true,
+				Message::Ed25519Generation(_) => true,
 				Message::ShareAdd(_) => true,
 				Message::ServersSetChange(_) => true,
+				Message::KeyRemoval(_) => true,
 				_ => false,
 			}
 		}
@@ -909,14 +928,14 @@ impl ClusterClientImpl {
 		}
 	}
 
-	fn create_key_version_negotiation_session(&self, session_id: SessionId) -> Result<Arc<KeyVersionNegotiationSession<KeyVersionNegotiationSessionTransport>>, Error> {
+	fn create_key_version_negotiation_session(&self, session_id: SessionId, result_computer: Option<Arc<KeyVersionNegotiationResultComputer>>) -> Result<Arc<KeyVersionNegotiationSession<KeyVersionNegotiationSessionTransport>>, Error> {
 		let mut connected_nodes = self.data.connections.connected_nodes()?;
 		connected_nodes.insert(self.data.self_key_pair.public().clone());
 
 		let access_key = Random.generate()?.secret().clone();
 		let session_id = SessionIdWithSubSession::new(session_id, access_key);
 		let cluster = create_cluster_view(&self.data, false)?;
-		let session = self.data.sessions.negotiation_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id.clone(), None, false, None)?;
+		let session = self.data.sessions.negotiation_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id.clone(), None, false, result_computer)?;
 		match session.initialize(connected_nodes) {
 			Ok(()) => Ok(session),
 			Err(error) => {
@@ -981,7 +1000,7 @@ impl ClusterClient for ClusterClientImpl {
 		let initialization_result = match version {
 			Some(version) => session.initialize(origin, version, is_shadow_decryption, is_broadcast_decryption),
 			None => {
-				self.create_key_version_negotiation_session(session_id.id.clone())
+				self.create_key_version_negotiation_session(session_id.id.clone(), None)
 					.map(|version_session| {
 						version_session.set_continue_action(ContinueAction::Decrypt(session.clone(), origin, is_shadow_decryption, is_broadcast_decryption));
 						ClusterCore::try_continue_session(&self.data, Some(version_session));
@@ -1006,7 +1025,7 @@ impl ClusterClient for ClusterClientImpl {
 		let initialization_result = match version {
 			Some(version) => session.initialize(version, message_hash),
 			None => {
-				self.create_key_version_negotiation_session(session_id.id.clone())
+				self.create_key_version_negotiation_session(session_id.id.clone(), None)
 					.map(|version_session| {
 						version_session.set_continue_action(ContinueAction::SchnorrSign(session.clone(), message_hash));
 						ClusterCore::try_continue_session(&self.data, Some(version_session));
@@ -1031,7 +1050,7 @@ impl ClusterClient for ClusterClientImpl {
 		let initialization_result = match version {
 			Some(version) => session.initialize(version, message_hash),
 			None => {
-				self.create_key_version_negotiation_session(session_id.id.clone())
+				self.create_key_version_negotiation_session(session_id.id.clone(), None)
 					.map(|version_session| {
 						version_session.set_continue_action(ContinueAction::EcdsaSign(session.clone(), message_hash));
 						ClusterCore::try_continue_session(&self.data, Some(version_session));
@@ -1044,8 +1063,36 @@ impl ClusterClient for ClusterClientImpl {
 			session, &self.data.sessions.ecdsa_signing_sessions)
 	}
 
+	fn new_ed25519_generation_session(&self, session_id: SessionId, author: Address, threshold: usize) -> Result<Arc<Ed25519GenerationSession>, Error> {
+		let mut connected_nodes = self.data.connections.connected_nodes()?;
+		connected_nodes.insert(self.data.self_key_pair.public().clone());
+
+		let cluster = create_cluster_view(&self.data, true)?;
+		let session = self.data.sessions.ed25519_generation_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id, None, false, None)?;
+		Self::process_initialization_result(
+			session.initialize(author, threshold, connected_nodes),
+			session, &self.data.sessions.ed25519_generation_sessions)
+	}
+
+	fn new_ed25519_signing_session(&self, session_id: SessionId, requester: Requester, message: Bytes) -> Result<Arc<Ed25519SigningSession>, Error> {
+		let access_key = Random.generate()?.secret().clone();
+		let session_id = SessionIdWithSubSession::new(session_id, access_key);
+		let cluster = create_cluster_view(&self.data, false)?;
+		let session = self.data.sessions.ed25519_signing_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id, None, false, Some(requester))?;
+
+		Self::process_initialization_result(
+			session.initialize(message),
+			session, &self.data.sessions.ed25519_signing_sessions)
+	}
+
 	fn new_key_version_negotiation_session(&self, session_id: SessionId) -> Result<Arc<KeyVersionNegotiationSession<KeyVersionNegotiationSessionTransport>>, Error> {
-		let session = self.create_key_version_negotiation_session(session_id)?;
+		let session = self.create_key_version_negotiation_session(session_id, None)?;
+		Ok(session)
+	}
+
+	fn new_key_versions_discovery_session(&self, session_id: SessionId) -> Result<Arc<KeyVersionNegotiationSession<KeyVersionNegotiationSessionTransport>>, Error> {
+		let session = self.create_key_version_negotiation_session(session_id, Some(Arc::new(LargestSupportResultComputer)))?;
+		ClusterCore::try_continue_session(&self.data, Some(session.clone()));
 		Ok(session)
 	}
 
@@ -1074,6 +1121,17 @@ impl ClusterClient for ClusterClientImpl {
 			session, &self.data.sessions.admin_sessions)
 	}
 
+	fn new_key_removal_session(&self, session_id: SessionId, signature: Signature) -> Result<Arc<AdminSession>, Error> {
+		let cluster = create_cluster_view(&self.data, true)?;
+		let session = self.data.sessions.admin_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id, None, false, Some(AdminSessionCreationData::KeyRemoval))?;
+		let initialization_result = session.as_key_removal().expect("key removal session is created; qed")
+			.initialize(signature);
+
+		Self::process_initialization_result(
+			initialization_result,
+			session, &self.data.sessions.admin_sessions)
+	}
+
 	fn add_generation_listener(&self, listener: Arc<ClusterSessionsListener<GenerationSession>>) {
 		self.data.sessions.generation_sessions.add_listener(listener);
 	}
@@ -1101,7 +1159,6 @@ impl ClusterClient for ClusterClientImpl {
 		self.data.sessions.generation_sessions.get(session_id, false)
 	}
 
-	#[cfg(test)]
 	fn key_storage(&self) -> Arc<KeyStorage> {
 		self.data.config.key_storage.clone()
 	}
@@ -1121,6 +1178,7 @@ pub mod tests {
 	use parking_lot::Mutex;
 	use tokio_core::reactor::Core;
 	use ethereum_types::{Address, H256};
+	use bytes::Bytes;
 	use ethkey::{Random, Generator, Public, Signature, sign};
 	use key_server_cluster::{NodeId, SessionId, Requester, Error, DummyAclStorage, DummyKeyStorage,
 		MapKeyServerSet, PlainNodeKeyPair, KeyStorage};
@@ -1132,6 +1190,8 @@ pub mod tests {
 	use key_server_cluster::encryption_session::{SessionImpl as EncryptionSession};
 	use key_server_cluster::signing_session_ecdsa::{SessionImpl as EcdsaSigningSession};
 	use key_server_cluster::signing_session_schnorr::{SessionImpl as SchnorrSigningSession};
+	use key_server_cluster::generation_session_ed25519::{SessionImpl as Ed25519GenerationSession};
+	use key_server_cluster::signing_session_ed25519::{SessionImpl as Ed25519SigningSession};
 	use key_server_cluster::key_version_negotiation_session::{SessionImpl as KeyVersionNegotiationSession,
 		IsolatedSessionTransport as KeyVersionNegotiationSessionTransport};
 
@@ -1164,9 +1224,13 @@ pub mod tests {
 		fn new_decryption_session(&self, _session_id: SessionId, _origin: Option<Address>, _requester: Requester, _version: Option<H256>, _is_shadow_decryption: bool, _is_broadcast_session: bool) -> Result<Arc<DecryptionSession>, Error> { unimplemented!("test-only") }
 		fn new_schnorr_signing_session(&self, _session_id: SessionId, _requester: Requester, _version: Option<H256>, _message_hash: H256) -> Result<Arc<SchnorrSigningSession>, Error> { unimplemented!("test-only") }
 		fn new_ecdsa_signing_session(&self, _session_id: SessionId, _requester: Requester, _version: Option<H256>, _message_hash: H256) -> Result<Arc<EcdsaSigningSession>, Error> { unimplemented!("test-only") }
+		fn new_ed25519_generation_session(&self, _session_id: SessionId, _author: Address, _threshold: usize) -> Result<Arc<Ed25519GenerationSession>, Error> { unimplemented!("test-only") }
+		fn new_ed25519_signing_session(&self, _session_id: SessionId, _requester: Requester, _message: Bytes) -> Result<Arc<Ed25519SigningSession>, Error> { unimplemented!("test-only") }
 
 		fn new_key_version_negotiation_session(&self, _session_id: SessionId) -> Result<Arc<KeyVersionNegotiationSession<KeyVersionNegotiationSessionTransport>>, Error> { unimplemented!("test-only") }
+		fn new_key_versions_discovery_session(&self, _session_id: SessionId) -> Result<Arc<KeyVersionNegotiationSession<KeyVersionNegotiationSessionTransport>>, Error> { unimplemented!("test-only") }
 		fn new_servers_set_change_session(&self, _session_id: Option<SessionId>, _migration_id: Option<H256>, _new_nodes_set: BTreeSet<NodeId>, _old_set_signature: Signature, _new_set_signature: Signature) -> Result<Arc<AdminSession>, Error> { unimplemented!("test-only") }
+		fn new_key_removal_session(&self, _session_id: SessionId, _signature: Signature) -> Result<Arc<AdminSession>, Error> { unimplemented!("test-only") }
 
 		fn add_generation_listener(&self, _listener: Arc<ClusterSessionsListener<GenerationSession>>) {}
 		fn add_decryption_listener(&self, _listener: Arc<ClusterSessionsListener<DecryptionSession>>) {}
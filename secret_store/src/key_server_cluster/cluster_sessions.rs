@@ -30,14 +30,17 @@ use key_server_cluster::decryption_session::{SessionImpl as DecryptionSessionImp
 use key_server_cluster::encryption_session::{SessionImpl as EncryptionSessionImpl};
 use key_server_cluster::signing_session_ecdsa::{SessionImpl as EcdsaSigningSessionImpl};
 use key_server_cluster::signing_session_schnorr::{SessionImpl as SchnorrSigningSessionImpl};
+use key_server_cluster::generation_session_ed25519::{SessionImpl as Ed25519GenerationSessionImpl};
+use key_server_cluster::signing_session_ed25519::{SessionImpl as Ed25519SigningSessionImpl};
 use key_server_cluster::share_add_session::{SessionImpl as ShareAddSessionImpl, IsolatedSessionTransport as ShareAddTransport};
 use key_server_cluster::servers_set_change_session::{SessionImpl as ServersSetChangeSessionImpl};
+use key_server_cluster::key_removal_session::{SessionImpl as KeyRemovalSessionImpl, IsolatedSessionTransport as KeyRemovalTransport};
 use key_server_cluster::key_version_negotiation_session::{SessionImpl as KeyVersionNegotiationSessionImpl,
-	IsolatedSessionTransport as VersionNegotiationTransport};
+	IsolatedSessionTransport as VersionNegotiationTransport, SessionResultComputer as KeyVersionNegotiationResultComputer};
 
 use key_server_cluster::cluster_sessions_creator::{GenerationSessionCreator, EncryptionSessionCreator, DecryptionSessionCreator,
 	SchnorrSigningSessionCreator, KeyVersionNegotiationSessionCreator, AdminSessionCreator, SessionCreatorCore,
-	EcdsaSigningSessionCreator, ClusterSessionCreator};
+	EcdsaSigningSessionCreator, Ed25519GenerationSessionCreator, Ed25519SigningSessionCreator, ClusterSessionCreator};
 
 /// When there are no session-related messages for SESSION_TIMEOUT_INTERVAL seconds,
 /// we must treat this session as stalled && finish it with an error.
@@ -108,6 +111,8 @@ pub enum AdminSession {
 	ShareAdd(ShareAddSessionImpl<ShareAddTransport>),
 	/// Servers set change session.
 	ServersSetChange(ServersSetChangeSessionImpl),
+	/// Key removal session.
+	KeyRemoval(KeyRemovalSessionImpl<KeyRemovalTransport>),
 }
 
 /// Administrative session creation data.
@@ -116,6 +121,8 @@ pub enum AdminSessionCreationData {
 	ShareAdd(H256),
 	/// Servers set change session (block id, new_server_set).
 	ServersSetChange(Option<H256>, BTreeSet<NodeId>),
+	/// Key removal session.
+	KeyRemoval,
 }
 
 /// Active sessions on this cluster.
@@ -130,8 +137,12 @@ pub struct ClusterSessions {
 	pub schnorr_signing_sessions: ClusterSessionsContainer<SchnorrSigningSessionImpl, SchnorrSigningSessionCreator, Requester>,
 	/// ECDSA signing sessions.
 	pub ecdsa_signing_sessions: ClusterSessionsContainer<EcdsaSigningSessionImpl, EcdsaSigningSessionCreator, Requester>,
+	/// Ed25519 key generation sessions.
+	pub ed25519_generation_sessions: ClusterSessionsContainer<Ed25519GenerationSessionImpl, Ed25519GenerationSessionCreator, ()>,
+	/// Ed25519 signing sessions.
+	pub ed25519_signing_sessions: ClusterSessionsContainer<Ed25519SigningSessionImpl, Ed25519SigningSessionCreator, Requester>,
 	/// Key version negotiation sessions.
-	pub negotiation_sessions: ClusterSessionsContainer<KeyVersionNegotiationSessionImpl<VersionNegotiationTransport>, KeyVersionNegotiationSessionCreator, ()>,
+	pub negotiation_sessions: ClusterSessionsContainer<KeyVersionNegotiationSessionImpl<VersionNegotiationTransport>, KeyVersionNegotiationSessionCreator, Arc<KeyVersionNegotiationResultComputer>>,
 	/// Administrative sessions.
 	pub admin_sessions: ClusterSessionsContainer<AdminSession, AdminSessionCreator, AdminSessionCreationData>,
 	/// Self node id.
@@ -212,6 +223,12 @@ impl ClusterSessions {
 			ecdsa_signing_sessions: ClusterSessionsContainer::new(EcdsaSigningSessionCreator {
 				core: creator_core.clone(),
 			}, container_state.clone()),
+			ed25519_generation_sessions: ClusterSessionsContainer::new(Ed25519GenerationSessionCreator {
+				core: creator_core.clone(),
+			}, container_state.clone()),
+			ed25519_signing_sessions: ClusterSessionsContainer::new(Ed25519SigningSessionCreator {
+				core: creator_core.clone(),
+			}, container_state.clone()),
 			negotiation_sessions: ClusterSessionsContainer::new(KeyVersionNegotiationSessionCreator {
 				core: creator_core.clone(),
 			}, container_state.clone()),
@@ -248,6 +265,8 @@ impl ClusterSessions {
 		self.decryption_sessions.stop_stalled_sessions();
 		self.schnorr_signing_sessions.stop_stalled_sessions();
 		self.ecdsa_signing_sessions.stop_stalled_sessions();
+		self.ed25519_generation_sessions.stop_stalled_sessions();
+		self.ed25519_signing_sessions.stop_stalled_sessions();
 		self.negotiation_sessions.stop_stalled_sessions();
 		self.admin_sessions.stop_stalled_sessions();
 	}
@@ -259,6 +278,8 @@ impl ClusterSessions {
 		self.decryption_sessions.on_connection_timeout(node_id);
 		self.schnorr_signing_sessions.on_connection_timeout(node_id);
 		self.ecdsa_signing_sessions.on_connection_timeout(node_id);
+		self.ed25519_generation_sessions.on_connection_timeout(node_id);
+		self.ed25519_signing_sessions.on_connection_timeout(node_id);
 		self.negotiation_sessions.on_connection_timeout(node_id);
 		self.admin_sessions.on_connection_timeout(node_id);
 		self.creator_core.on_connection_timeout(node_id);
@@ -500,6 +521,13 @@ impl AdminSession {
 			_ => None
 		}
 	}
+
+	pub fn as_key_removal(&self) -> Option<&KeyRemovalSessionImpl<KeyRemovalTransport>> {
+		match *self {
+			AdminSession::KeyRemoval(ref session) => Some(session),
+			_ => None
+		}
+	}
 }
 
 impl ClusterSession for AdminSession {
@@ -513,6 +541,7 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.id().clone(),
 			AdminSession::ServersSetChange(ref session) => session.id().clone(),
+			AdminSession::KeyRemoval(ref session) => session.id().clone(),
 		}
 	}
 
@@ -520,6 +549,7 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.is_finished(),
 			AdminSession::ServersSetChange(ref session) => session.is_finished(),
+			AdminSession::KeyRemoval(ref session) => session.is_finished(),
 		}
 	}
 
@@ -527,6 +557,7 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.on_session_timeout(),
 			AdminSession::ServersSetChange(ref session) => session.on_session_timeout(),
+			AdminSession::KeyRemoval(ref session) => session.on_session_timeout(),
 		}
 	}
 
@@ -534,6 +565,7 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.on_node_timeout(node_id),
 			AdminSession::ServersSetChange(ref session) => session.on_node_timeout(node_id),
+			AdminSession::KeyRemoval(ref session) => session.on_node_timeout(node_id),
 		}
 	}
 
@@ -541,6 +573,7 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.on_session_error(node, error),
 			AdminSession::ServersSetChange(ref session) => session.on_session_error(node, error),
+			AdminSession::KeyRemoval(ref session) => session.on_session_error(node, error),
 		}
 	}
 
@@ -548,6 +581,7 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.on_message(sender, message),
 			AdminSession::ServersSetChange(ref session) => session.on_message(sender, message),
+			AdminSession::KeyRemoval(ref session) => session.on_message(sender, message),
 		}
 	}
 }
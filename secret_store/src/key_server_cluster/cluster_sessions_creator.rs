@@ -18,13 +18,15 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::collections::BTreeMap;
 use parking_lot::RwLock;
-use ethkey::Public;
-use key_server_cluster::{Error, NodeId, SessionId, Requester, AclStorage, KeyStorage, DocumentKeyShare, SessionMeta};
+use ethkey::{Public, public_to_address};
+use key_server_cluster::{Error, NodeId, SessionId, Requester, AclStorage, AclOperation, KeyStorage, DocumentKeyShare, SessionMeta,
+	Ed25519KeyShare};
 use key_server_cluster::cluster::{Cluster, ClusterConfiguration};
 use key_server_cluster::connection_trigger::ServersSetChangeSessionCreatorConnector;
 use key_server_cluster::cluster_sessions::{ClusterSession, SessionIdWithSubSession, AdminSession, AdminSessionCreationData};
 use key_server_cluster::message::{self, Message, DecryptionMessage, SchnorrSigningMessage, ConsensusMessageOfShareAdd,
-	ShareAddMessage, ServersSetChangeMessage, ConsensusMessage, ConsensusMessageWithServersSet, EcdsaSigningMessage};
+	ShareAddMessage, ServersSetChangeMessage, ConsensusMessage, ConsensusMessageWithServersSet, EcdsaSigningMessage,
+	KeyRemovalMessage, Ed25519SigningMessage};
 use key_server_cluster::generation_session::{SessionImpl as GenerationSessionImpl, SessionParams as GenerationSessionParams};
 use key_server_cluster::decryption_session::{SessionImpl as DecryptionSessionImpl,
 	SessionParams as DecryptionSessionParams};
@@ -33,13 +35,19 @@ use key_server_cluster::signing_session_ecdsa::{SessionImpl as EcdsaSigningSessi
 	SessionParams as EcdsaSigningSessionParams};
 use key_server_cluster::signing_session_schnorr::{SessionImpl as SchnorrSigningSessionImpl,
 	SessionParams as SchnorrSigningSessionParams};
+use key_server_cluster::generation_session_ed25519::{SessionImpl as Ed25519GenerationSessionImpl,
+	SessionParams as Ed25519GenerationSessionParams};
+use key_server_cluster::signing_session_ed25519::{SessionImpl as Ed25519SigningSessionImpl,
+	SessionParams as Ed25519SigningSessionParams};
 use key_server_cluster::share_add_session::{SessionImpl as ShareAddSessionImpl,
 	SessionParams as ShareAddSessionParams, IsolatedSessionTransport as ShareAddTransport};
 use key_server_cluster::servers_set_change_session::{SessionImpl as ServersSetChangeSessionImpl,
 	SessionParams as ServersSetChangeSessionParams};
+use key_server_cluster::key_removal_session::{SessionImpl as KeyRemovalSessionImpl,
+	SessionParams as KeyRemovalSessionParams, IsolatedSessionTransport as KeyRemovalTransport};
 use key_server_cluster::key_version_negotiation_session::{SessionImpl as KeyVersionNegotiationSessionImpl,
 	SessionParams as KeyVersionNegotiationSessionParams, IsolatedSessionTransport as VersionNegotiationTransport,
-	FastestResultComputer as FastestResultKeyVersionsResultComputer};
+	FastestResultComputer as FastestResultKeyVersionsResultComputer, SessionResultComputer as KeyVersionNegotiationResultComputer};
 use key_server_cluster::admin_sessions::ShareChangeSessionMeta;
 
 /// Generic cluster session creator.
@@ -117,6 +125,11 @@ impl SessionCreatorCore {
 	fn read_key_share(&self, key_id: &SessionId) -> Result<Option<DocumentKeyShare>, Error> {
 		self.key_storage.get(key_id)
 	}
+
+	/// Read Ed25519 key share.
+	fn read_ed25519_key_share(&self, key_id: &SessionId) -> Result<Option<Ed25519KeyShare>, Error> {
+		self.key_storage.get_ed25519(key_id)
+	}
 }
 
 /// Generation session creator.
@@ -292,6 +305,85 @@ impl ClusterSessionCreator<SchnorrSigningSessionImpl, Requester> for SchnorrSign
 	}
 }
 
+/// Ed25519 key generation session creator.
+pub struct Ed25519GenerationSessionCreator {
+	/// Creator core.
+	pub core: Arc<SessionCreatorCore>,
+}
+
+impl ClusterSessionCreator<Ed25519GenerationSessionImpl, ()> for Ed25519GenerationSessionCreator {
+	fn make_error_message(sid: SessionId, nonce: u64, err: Error) -> Message {
+		message::Message::Ed25519Generation(message::Ed25519GenerationMessage::Ed25519GenerationError(message::Ed25519GenerationError {
+			session: sid.into(),
+			session_nonce: nonce,
+			error: err.into(),
+		}))
+	}
+
+	fn create(&self, cluster: Arc<Cluster>, master: NodeId, nonce: Option<u64>, id: SessionId, _creation_data: Option<()>) -> Result<Arc<Ed25519GenerationSessionImpl>, Error> {
+		// check that there's no Ed25519 key with the same id
+		if self.core.read_ed25519_key_share(&id)?.is_some() {
+			return Err(Error::ServerKeyAlreadyGenerated);
+		}
+
+		let nonce = self.core.check_session_nonce(&master, nonce)?;
+		Ok(Arc::new(Ed25519GenerationSessionImpl::new(Ed25519GenerationSessionParams {
+			id: id,
+			self_node_id: self.core.self_node_id.clone(),
+			key_storage: self.core.key_storage.clone(),
+			cluster: cluster,
+			nonce: nonce,
+		})))
+	}
+}
+
+/// Ed25519 signing session creator.
+pub struct Ed25519SigningSessionCreator {
+	/// Creator core.
+	pub core: Arc<SessionCreatorCore>,
+}
+
+impl ClusterSessionCreator<Ed25519SigningSessionImpl, Requester> for Ed25519SigningSessionCreator {
+	fn creation_data_from_message(message: &Message) -> Result<Option<Requester>, Error> {
+		match *message {
+			Message::Ed25519Signing(Ed25519SigningMessage::Ed25519SigningConsensusMessage(ref message)) => match &message.message {
+				&ConsensusMessage::InitializeConsensusSession(ref message) => Ok(Some(message.requester.clone().into())),
+				_ => Err(Error::InvalidMessage),
+			},
+			_ => Err(Error::InvalidMessage),
+		}
+	}
+
+	fn make_error_message(sid: SessionIdWithSubSession, nonce: u64, err: Error) -> Message {
+		message::Message::Ed25519Signing(message::Ed25519SigningMessage::Ed25519SigningSessionError(message::Ed25519SigningSessionError {
+			session: sid.id.into(),
+			sub_session: sid.access_key.into(),
+			session_nonce: nonce,
+			error: err.into(),
+		}))
+	}
+
+	fn create(&self, cluster: Arc<Cluster>, master: NodeId, nonce: Option<u64>, id: SessionIdWithSubSession, requester: Option<Requester>) -> Result<Arc<Ed25519SigningSessionImpl>, Error> {
+		let key_share = self.core.read_ed25519_key_share(&id.id)?;
+		let nonce = self.core.check_session_nonce(&master, nonce)?;
+		Ok(Arc::new(Ed25519SigningSessionImpl::new(Ed25519SigningSessionParams {
+			meta: SessionMeta {
+				id: id.id,
+				self_node_id: self.core.self_node_id.clone(),
+				master_node_id: master,
+				threshold: key_share.as_ref().map(|ks| ks.threshold).unwrap_or_default(),
+				configured_nodes_count: cluster.configured_nodes_count(),
+				connected_nodes_count: cluster.connected_nodes_count(),
+			},
+			access_key: id.access_key,
+			key_share: key_share,
+			acl_storage: self.core.acl_storage.clone(),
+			cluster: cluster,
+			nonce: nonce,
+		}, requester)?))
+	}
+}
+
 /// ECDSA signing session creator.
 pub struct EcdsaSigningSessionCreator {
 	/// Creator core.
@@ -346,7 +438,7 @@ pub struct KeyVersionNegotiationSessionCreator {
 	pub core: Arc<SessionCreatorCore>,
 }
 
-impl ClusterSessionCreator<KeyVersionNegotiationSessionImpl<VersionNegotiationTransport>, ()> for KeyVersionNegotiationSessionCreator {
+impl ClusterSessionCreator<KeyVersionNegotiationSessionImpl<VersionNegotiationTransport>, Arc<KeyVersionNegotiationResultComputer>> for KeyVersionNegotiationSessionCreator {
 	fn make_error_message(sid: SessionIdWithSubSession, nonce: u64, err: Error) -> Message {
 		message::Message::KeyVersionNegotiation(message::KeyVersionNegotiationMessage::KeyVersionsError(message::KeyVersionsError {
 			session: sid.id.into(),
@@ -359,13 +451,16 @@ impl ClusterSessionCreator<KeyVersionNegotiationSessionImpl<VersionNegotiationTr
 		}))
 	}
 
-	fn create(&self, cluster: Arc<Cluster>, master: NodeId, nonce: Option<u64>, id: SessionIdWithSubSession, _creation_data: Option<()>) -> Result<Arc<KeyVersionNegotiationSessionImpl<VersionNegotiationTransport>>, Error> {
+	fn create(&self, cluster: Arc<Cluster>, master: NodeId, nonce: Option<u64>, id: SessionIdWithSubSession, creation_data: Option<Arc<KeyVersionNegotiationResultComputer>>) -> Result<Arc<KeyVersionNegotiationSessionImpl<VersionNegotiationTransport>>, Error> {
 		let configured_nodes_count = cluster.configured_nodes_count();
 		let connected_nodes_count = cluster.connected_nodes_count();
 		let encrypted_data = self.core.read_key_share(&id.id)?;
 		let nonce = self.core.check_session_nonce(&master, nonce)?;
-		let computer = Arc::new(FastestResultKeyVersionsResultComputer::new(self.core.self_node_id.clone(), encrypted_data.as_ref(),
-			configured_nodes_count, configured_nodes_count));
+		let computer = match creation_data {
+			Some(computer) => computer,
+			None => Arc::new(FastestResultKeyVersionsResultComputer::new(self.core.self_node_id.clone(), encrypted_data.as_ref(),
+				configured_nodes_count, configured_nodes_count)),
+		};
 		Ok(Arc::new(KeyVersionNegotiationSessionImpl::new(KeyVersionNegotiationSessionParams {
 			meta: ShareChangeSessionMeta {
 				id: id.id.clone(),
@@ -412,6 +507,7 @@ impl ClusterSessionCreator<AdminSession, AdminSessionCreationData> for AdminSess
 				&ConsensusMessageOfShareAdd::InitializeConsensusSession(ref message) => Ok(Some(AdminSessionCreationData::ShareAdd(message.version.clone().into()))),
 				_ => Err(Error::InvalidMessage),
 			},
+			Message::KeyRemoval(KeyRemovalMessage::InitializeKeyRemoval(_)) => Ok(Some(AdminSessionCreationData::KeyRemoval)),
 			_ => Err(Error::InvalidMessage),
 		}
 	}
@@ -428,6 +524,13 @@ impl ClusterSessionCreator<AdminSession, AdminSessionCreationData> for AdminSess
 		let nonce = self.core.check_session_nonce(&master, nonce)?;
 		Ok(Arc::new(match creation_data {
 			Some(AdminSessionCreationData::ShareAdd(version)) => {
+				let admin_public = self.admin_public.clone().ok_or(Error::AccessDenied)?;
+				// storages without per-operation permissions only know document readers => administrator isn't checked there
+				if self.core.acl_storage.has_operation_permissions()?
+					&& !self.core.acl_storage.check_operation(public_to_address(&admin_public), &id, AclOperation::ShareAdd)? {
+					return Err(Error::AccessDenied);
+				}
+
 				AdminSession::ShareAdd(ShareAddSessionImpl::new(ShareAddSessionParams {
 					meta: ShareChangeSessionMeta {
 						id: id.clone(),
@@ -439,7 +542,7 @@ impl ClusterSessionCreator<AdminSession, AdminSessionCreationData> for AdminSess
 					transport: ShareAddTransport::new(id.clone(), Some(version), nonce, cluster),
 					key_storage: self.core.key_storage.clone(),
 					nonce: nonce,
-					admin_public: Some(self.admin_public.clone().ok_or(Error::AccessDenied)?),
+					admin_public: Some(admin_public),
 				})?)
 			},
 			Some(AdminSessionCreationData::ServersSetChange(migration_id, new_nodes_set)) => {
@@ -462,6 +565,21 @@ impl ClusterSessionCreator<AdminSession, AdminSessionCreationData> for AdminSess
 					migration_id: migration_id,
 				})?)
 			},
+			Some(AdminSessionCreationData::KeyRemoval) => {
+				AdminSession::KeyRemoval(KeyRemovalSessionImpl::new(KeyRemovalSessionParams {
+					meta: ShareChangeSessionMeta {
+						id: id.clone(),
+						self_node_id: self.core.self_node_id.clone(),
+						master_node_id: master,
+						configured_nodes_count: cluster.configured_nodes_count(),
+						connected_nodes_count: cluster.connected_nodes_count(),
+					},
+					transport: KeyRemovalTransport::new(cluster),
+					key_storage: self.core.key_storage.clone(),
+					admin_public: Some(self.admin_public.clone().ok_or(Error::AccessDenied)?),
+					nonce: nonce,
+				}))
+			},
 			None => unreachable!("expected to call with non-empty creation data; qed"),
 		}))
 	}
@@ -475,8 +593,11 @@ impl IntoSessionId<SessionId> for Message {
 			Message::Decryption(_) => Err(Error::InvalidMessage),
 			Message::SchnorrSigning(_) => Err(Error::InvalidMessage),
 			Message::EcdsaSigning(_) => Err(Error::InvalidMessage),
+			Message::Ed25519Generation(ref message) => Ok(message.session_id().clone()),
+			Message::Ed25519Signing(_) => Err(Error::InvalidMessage),
 			Message::ServersSetChange(ref message) => Ok(message.session_id().clone()),
 			Message::ShareAdd(ref message) => Ok(message.session_id().clone()),
+			Message::KeyRemoval(ref message) => Ok(message.session_id().clone()),
 			Message::KeyVersionNegotiation(_) => Err(Error::InvalidMessage),
 			Message::Cluster(_) => Err(Error::InvalidMessage),
 		}
@@ -491,8 +612,11 @@ impl IntoSessionId<SessionIdWithSubSession> for Message {
 			Message::Decryption(ref message) => Ok(SessionIdWithSubSession::new(message.session_id().clone(), message.sub_session_id().clone())),
 			Message::SchnorrSigning(ref message) => Ok(SessionIdWithSubSession::new(message.session_id().clone(), message.sub_session_id().clone())),
 			Message::EcdsaSigning(ref message) => Ok(SessionIdWithSubSession::new(message.session_id().clone(), message.sub_session_id().clone())),
+			Message::Ed25519Generation(_) => Err(Error::InvalidMessage),
+			Message::Ed25519Signing(ref message) => Ok(SessionIdWithSubSession::new(message.session_id().clone(), message.sub_session_id().clone())),
 			Message::ServersSetChange(_) => Err(Error::InvalidMessage),
 			Message::ShareAdd(_) => Err(Error::InvalidMessage),
+			Message::KeyRemoval(_) => Err(Error::InvalidMessage),
 			Message::KeyVersionNegotiation(ref message) => Ok(SessionIdWithSubSession::new(message.session_id().clone(), message.sub_session_id().clone())),
 			Message::Cluster(_) => Err(Error::InvalidMessage),
 		}
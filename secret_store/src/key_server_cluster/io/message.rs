@@ -25,7 +25,8 @@ use ethkey::math::curve_order;
 use ethereum_types::{H256, U256};
 use key_server_cluster::Error;
 use key_server_cluster::message::{Message, ClusterMessage, GenerationMessage, EncryptionMessage, DecryptionMessage,
-	SchnorrSigningMessage, EcdsaSigningMessage, ServersSetChangeMessage, ShareAddMessage, KeyVersionNegotiationMessage};
+	SchnorrSigningMessage, EcdsaSigningMessage, ServersSetChangeMessage, ShareAddMessage, KeyVersionNegotiationMessage, KeyRemovalMessage,
+	Ed25519GenerationMessage, Ed25519SigningMessage};
 
 /// Size of serialized header.
 pub const MESSAGE_HEADER_SIZE: usize = 18;
@@ -153,6 +154,42 @@ pub fn serialize_message(message: Message) -> Result<SerializedMessage, Error> {
 		Message::EcdsaSigning(EcdsaSigningMessage::EcdsaSigningSessionDelegation(payload))	=> (509, serde_json::to_vec(&payload)),
 		Message::EcdsaSigning(EcdsaSigningMessage::EcdsaSigningSessionDelegationCompleted(payload))
 																							=> (510, serde_json::to_vec(&payload)),
+
+		Message::KeyRemoval(KeyRemovalMessage::InitializeKeyRemoval(payload))				=> (550, serde_json::to_vec(&payload)),
+		Message::KeyRemoval(KeyRemovalMessage::ConfirmKeyRemoval(payload))					=> (551, serde_json::to_vec(&payload)),
+		Message::KeyRemoval(KeyRemovalMessage::KeyRemovalError(payload))					=> (552, serde_json::to_vec(&payload)),
+
+		Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519InitializeGeneration(payload))
+																							=> (600, serde_json::to_vec(&payload)),
+		Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519ConfirmGenerationInitialization(payload))
+																							=> (601, serde_json::to_vec(&payload)),
+		Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519CompleteGenerationInitialization(payload))
+																							=> (602, serde_json::to_vec(&payload)),
+		Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519KeysDissemination(payload))
+																							=> (603, serde_json::to_vec(&payload)),
+		Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519GenerationCompleted(payload))
+																							=> (604, serde_json::to_vec(&payload)),
+		Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519GenerationError(payload))
+																							=> (605, serde_json::to_vec(&payload)),
+		Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519CommitmentsEcho(payload))
+																							=> (606, serde_json::to_vec(&payload)),
+		Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519GenerationConfirmed(payload))
+																							=> (607, serde_json::to_vec(&payload)),
+
+		Message::Ed25519Signing(Ed25519SigningMessage::Ed25519SigningConsensusMessage(payload))
+																							=> (650, serde_json::to_vec(&payload)),
+		Message::Ed25519Signing(Ed25519SigningMessage::Ed25519RequestNonceCommitment(payload))
+																							=> (651, serde_json::to_vec(&payload)),
+		Message::Ed25519Signing(Ed25519SigningMessage::Ed25519NonceCommitment(payload))
+																							=> (652, serde_json::to_vec(&payload)),
+		Message::Ed25519Signing(Ed25519SigningMessage::Ed25519RequestPartialSignature(payload))
+																							=> (653, serde_json::to_vec(&payload)),
+		Message::Ed25519Signing(Ed25519SigningMessage::Ed25519PartialSignature(payload))
+																							=> (654, serde_json::to_vec(&payload)),
+		Message::Ed25519Signing(Ed25519SigningMessage::Ed25519SigningSessionError(payload))
+																							=> (655, serde_json::to_vec(&payload)),
+		Message::Ed25519Signing(Ed25519SigningMessage::Ed25519SigningSessionCompleted(payload))
+																							=> (656, serde_json::to_vec(&payload)),
 	};
 
 	let payload = payload.map_err(|err| Error::Serde(err.to_string()))?;
@@ -233,6 +270,27 @@ pub fn deserialize_message(header: &MessageHeader, payload: Vec<u8>) -> Result<M
 		509	=> Message::EcdsaSigning(EcdsaSigningMessage::EcdsaSigningSessionDelegation(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 		510	=> Message::EcdsaSigning(EcdsaSigningMessage::EcdsaSigningSessionDelegationCompleted(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 
+		550 => Message::KeyRemoval(KeyRemovalMessage::InitializeKeyRemoval(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		551 => Message::KeyRemoval(KeyRemovalMessage::ConfirmKeyRemoval(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		552 => Message::KeyRemoval(KeyRemovalMessage::KeyRemovalError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
+		600 => Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519InitializeGeneration(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		601 => Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519ConfirmGenerationInitialization(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		602 => Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519CompleteGenerationInitialization(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		603 => Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519KeysDissemination(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		604 => Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519GenerationCompleted(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		605 => Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519GenerationError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		606 => Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519CommitmentsEcho(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		607 => Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519GenerationConfirmed(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
+		650 => Message::Ed25519Signing(Ed25519SigningMessage::Ed25519SigningConsensusMessage(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		651 => Message::Ed25519Signing(Ed25519SigningMessage::Ed25519RequestNonceCommitment(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		652 => Message::Ed25519Signing(Ed25519SigningMessage::Ed25519NonceCommitment(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		653 => Message::Ed25519Signing(Ed25519SigningMessage::Ed25519RequestPartialSignature(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		654 => Message::Ed25519Signing(Ed25519SigningMessage::Ed25519PartialSignature(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		655 => Message::Ed25519Signing(Ed25519SigningMessage::Ed25519SigningSessionError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		656 => Message::Ed25519Signing(Ed25519SigningMessage::Ed25519SigningSessionCompleted(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
 		_ => return Err(Error::Serde(format!("unknown message type {}", header.kind))),
 	})
 }
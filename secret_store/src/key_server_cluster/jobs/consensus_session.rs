@@ -368,7 +368,7 @@ impl<ConsensusExecutor, ConsensusTransport, ComputationExecutor, ComputationTran
 mod tests {
 	use std::sync::Arc;
 	use ethkey::{KeyPair, Random, Generator, sign, public_to_address};
-	use key_server_cluster::{Error, NodeId, SessionId, Requester, DummyAclStorage};
+	use key_server_cluster::{Error, NodeId, SessionId, Requester, DummyAclStorage, AclOperation};
 	use key_server_cluster::message::{ConsensusMessage, InitializeConsensusSession, ConfirmConsensusInitialization};
 	use key_server_cluster::jobs::job_session::tests::{make_master_session_meta, make_slave_session_meta, SquaredSumJobExecutor, DummyJobTransport};
 	use key_server_cluster::jobs::key_access_job::KeyAccessJob;
@@ -381,7 +381,7 @@ mod tests {
 		SquaredSumConsensusSession::new(ConsensusSessionParams {
 			meta: make_master_session_meta(threshold),
 			consensus_executor: KeyAccessJob::new_on_master(SessionId::default(), Arc::new(acl_storage.unwrap_or(DummyAclStorage::default())),
-				sign(&secret, &SessionId::default()).unwrap().into()),
+				AclOperation::Decrypt, sign(&secret, &SessionId::default()).unwrap().into()),
 			consensus_transport: DummyJobTransport::default(),
 		}).unwrap()
 	}
@@ -389,7 +389,7 @@ mod tests {
 	fn make_slave_consensus_session(threshold: usize, acl_storage: Option<DummyAclStorage>) -> SquaredSumConsensusSession {
 		SquaredSumConsensusSession::new(ConsensusSessionParams {
 			meta: make_slave_session_meta(threshold),
-			consensus_executor: KeyAccessJob::new_on_slave(SessionId::default(), Arc::new(acl_storage.unwrap_or(DummyAclStorage::default()))),
+			consensus_executor: KeyAccessJob::new_on_slave(SessionId::default(), Arc::new(acl_storage.unwrap_or(DummyAclStorage::default())), AclOperation::Decrypt),
 			consensus_transport: DummyJobTransport::default(),
 		}).unwrap()
 	}
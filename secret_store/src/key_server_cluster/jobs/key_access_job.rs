@@ -16,7 +16,7 @@
 
 use std::sync::Arc;
 use std::collections::{BTreeSet, BTreeMap};
-use key_server_cluster::{Error, NodeId, SessionId, Requester, AclStorage};
+use key_server_cluster::{Error, NodeId, SessionId, Requester, AclStorage, AclOperation};
 use key_server_cluster::jobs::job_session::{JobPartialResponseAction, JobPartialRequestAction, JobExecutor};
 
 /// Purpose of this job is to construct set of nodes, which have agreed to provide access to the given key for the given requestor.
@@ -27,25 +27,29 @@ pub struct KeyAccessJob {
 	has_key_share: bool,
 	/// ACL storage.
 	acl_storage: Arc<AclStorage>,
+	/// Operation, which requester is going to perform with the key.
+	operation: AclOperation,
 	/// Requester data.
 	requester: Option<Requester>,
 }
 
 impl KeyAccessJob {
-	pub fn new_on_slave(id: SessionId, acl_storage: Arc<AclStorage>) -> Self {
+	pub fn new_on_slave(id: SessionId, acl_storage: Arc<AclStorage>, operation: AclOperation) -> Self {
 		KeyAccessJob {
 			id: id,
 			has_key_share: true,
 			acl_storage: acl_storage,
+			operation: operation,
 			requester: None,
 		}
 	}
 
-	pub fn new_on_master(id: SessionId, acl_storage: Arc<AclStorage>, requester: Requester) -> Self {
+	pub fn new_on_master(id: SessionId, acl_storage: Arc<AclStorage>, operation: AclOperation, requester: Requester) -> Self {
 		KeyAccessJob {
 			id: id,
 			has_key_share: true,
 			acl_storage: acl_storage,
+			operation: operation,
 			requester: Some(requester),
 		}
 	}
@@ -78,7 +82,7 @@ impl JobExecutor for KeyAccessJob {
 		}
 		
 		self.requester = Some(partial_request.clone());
-		self.acl_storage.check(partial_request.address(&self.id).map_err(Error::InsufficientRequesterData)?, &self.id)
+		self.acl_storage.check_operation(partial_request.address(&self.id).map_err(Error::InsufficientRequesterData)?, &self.id, self.operation)
 			.map(|is_confirmed| if is_confirmed { JobPartialRequestAction::Respond(true) } else { JobPartialRequestAction::Reject(false) })
 	}
 
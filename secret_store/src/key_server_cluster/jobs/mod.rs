@@ -21,5 +21,6 @@ pub mod job_session;
 pub mod key_access_job;
 pub mod servers_set_change_access_job;
 pub mod signing_job_ecdsa;
+pub mod signing_job_ed25519;
 pub mod signing_job_schnorr;
 pub mod unknown_sessions_job;
@@ -0,0 +1,194 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeSet, BTreeMap};
+use bytes::Bytes;
+use curve25519_dalek::edwards::EdwardsPoint;
+use curve25519_dalek::scalar::Scalar;
+use ethkey::Secret;
+use key_server_cluster::{Error, NodeId, Ed25519KeyShare};
+use key_server_cluster::math;
+use key_server_cluster::math_ed25519::{self, SigningNonces, SigningCommitments};
+use key_server_cluster::jobs::job_session::{JobPartialRequestAction, JobPartialResponseAction, JobExecutor};
+
+/// Ed25519 signing job.
+pub struct Ed25519SigningJob {
+	/// This node id.
+	self_node_id: NodeId,
+	/// Key share.
+	key_share: Ed25519KeyShare,
+	/// Joint public key.
+	public: EdwardsPoint,
+	/// Commitments to the joint polynom coefficients.
+	commitments: Vec<EdwardsPoint>,
+	/// Signing nonces of this node. Nonces are consumed when partial signature is computed.
+	nonces: Option<SigningNonces>,
+	/// Request id.
+	request_id: Option<Secret>,
+	/// Message to sign.
+	message: Option<Bytes>,
+	/// Signing nonces commitments of all selected nodes.
+	nonces_commitments: Option<BTreeMap<NodeId, SigningCommitments>>,
+}
+
+/// Ed25519 signing job partial request.
+pub struct Ed25519PartialSigningRequest {
+	/// Request id.
+	pub id: Secret,
+	/// Message to sign.
+	pub message: Bytes,
+	/// Signing nonces commitments of all selected nodes.
+	pub commitments: BTreeMap<NodeId, SigningCommitments>,
+}
+
+/// Ed25519 signing job partial response.
+#[derive(Clone)]
+pub struct Ed25519PartialSigningResponse {
+	/// Request id.
+	pub request_id: Secret,
+	/// Signature share.
+	pub partial_signature: Scalar,
+}
+
+impl Ed25519SigningJob {
+	pub fn new_on_slave(self_node_id: NodeId, key_share: Ed25519KeyShare, nonces: Option<SigningNonces>) -> Result<Self, Error> {
+		let commitments = key_share.commitments.iter()
+			.map(math_ed25519::point_from_h256)
+			.collect::<Result<Vec<_>, _>>()?;
+		Ok(Ed25519SigningJob {
+			self_node_id: self_node_id,
+			key_share: key_share,
+			public: commitments.get(0).cloned().ok_or(Error::InvalidMessage)?,
+			commitments: commitments,
+			nonces: nonces,
+			request_id: None,
+			message: None,
+			nonces_commitments: None,
+		})
+	}
+
+	pub fn new_on_master(self_node_id: NodeId, key_share: Ed25519KeyShare, nonces: Option<SigningNonces>, message: Bytes, nonces_commitments: BTreeMap<NodeId, SigningCommitments>) -> Result<Self, Error> {
+		let mut job = Self::new_on_slave(self_node_id, key_share, nonces)?;
+		job.request_id = Some(math::generate_random_scalar()?);
+		job.message = Some(message);
+		job.nonces_commitments = Some(nonces_commitments);
+		Ok(job)
+	}
+
+	/// Compute Lagrange coefficient of given node for given signers set.
+	fn lagrange_coefficient(node: &NodeId, nodes: &BTreeMap<NodeId, SigningCommitments>) -> Result<Scalar, Error> {
+		let other_id_numbers: Vec<_> = nodes.keys()
+			.filter(|n| *n != node)
+			.map(math_ed25519::node_id_number)
+			.collect();
+		math_ed25519::compute_lagrange_coefficient(&math_ed25519::node_id_number(node), other_id_numbers.iter())
+	}
+}
+
+impl JobExecutor for Ed25519SigningJob {
+	type PartialJobRequest = Ed25519PartialSigningRequest;
+	type PartialJobResponse = Ed25519PartialSigningResponse;
+	type JobResponse = Bytes;
+
+	fn prepare_partial_request(&self, _node: &NodeId, nodes: &BTreeSet<NodeId>) -> Result<Ed25519PartialSigningRequest, Error> {
+		debug_assert!(nodes.len() == self.key_share.threshold + 1);
+
+		let request_id = self.request_id.as_ref()
+			.expect("prepare_partial_request is only called on master nodes; request_id is filed in constructor on master nodes; qed");
+		let message = self.message.as_ref()
+			.expect("prepare_partial_request is only called on master nodes; message is filed in constructor on master nodes; qed");
+		let nonces_commitments = self.nonces_commitments.as_ref()
+			.expect("prepare_partial_request is only called on master nodes; nonces_commitments is filed in constructor on master nodes; qed");
+
+		// commitments are collected from the same consensus group, which is used to sign message
+		if nonces_commitments.keys().ne(nodes.iter()) {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		Ok(Ed25519PartialSigningRequest {
+			id: request_id.clone(),
+			message: message.clone(),
+			commitments: nonces_commitments.clone(),
+		})
+	}
+
+	fn process_partial_request(&mut self, partial_request: Ed25519PartialSigningRequest) -> Result<JobPartialRequestAction<Ed25519PartialSigningResponse>, Error> {
+		if partial_request.commitments.len() != self.key_share.threshold + 1
+			|| partial_request.commitments.keys().any(|n| !self.key_share.nodes.contains(n)) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// nonces must never be reused => consume them
+		let nonces = self.nonces.take().ok_or(Error::InvalidStateForRequest)?;
+		match partial_request.commitments.get(&self.self_node_id) {
+			Some(commitments) if commitments.hiding == math_ed25519::base_mul(&nonces.hiding)
+				&& commitments.binding == math_ed25519::base_mul(&nonces.binding) => (),
+			_ => return Err(Error::InvalidMessage),
+		}
+
+		let secret_share = math_ed25519::scalar_from_h256(&self.key_share.secret_share)?;
+		let binding_factor = math_ed25519::compute_binding_factor(&self.self_node_id, &partial_request.message, &partial_request.commitments);
+		let group_commitment = math_ed25519::compute_group_commitment(&partial_request.message, &partial_request.commitments);
+		let challenge = math_ed25519::compute_challenge(&group_commitment, &self.public, &partial_request.message);
+		let lagrange_coefficient = Self::lagrange_coefficient(&self.self_node_id, &partial_request.commitments)?;
+
+		Ok(JobPartialRequestAction::Respond(Ed25519PartialSigningResponse {
+			request_id: partial_request.id,
+			partial_signature: math_ed25519::compute_signature_share(&nonces, &binding_factor, &lagrange_coefficient, &secret_share, &challenge),
+		}))
+	}
+
+	fn check_partial_response(&mut self, sender: &NodeId, partial_response: &Ed25519PartialSigningResponse) -> Result<JobPartialResponseAction, Error> {
+		if Some(&partial_response.request_id) != self.request_id.as_ref() {
+			return Ok(JobPartialResponseAction::Ignore);
+		}
+
+		let message = self.message.as_ref()
+			.expect("check_partial_response is only called on master nodes; message is filed in constructor on master nodes; qed");
+		let nonces_commitments = self.nonces_commitments.as_ref()
+			.expect("check_partial_response is only called on master nodes; nonces_commitments is filed in constructor on master nodes; qed");
+		let sender_commitments = match nonces_commitments.get(sender) {
+			Some(sender_commitments) => sender_commitments,
+			None => return Ok(JobPartialResponseAction::Reject),
+		};
+
+		let binding_factor = math_ed25519::compute_binding_factor(sender, message, nonces_commitments);
+		let group_commitment = math_ed25519::compute_group_commitment(message, nonces_commitments);
+		let challenge = math_ed25519::compute_challenge(&group_commitment, &self.public, message);
+		let lagrange_coefficient = Self::lagrange_coefficient(sender, nonces_commitments)?;
+		let public_share = math_ed25519::compute_public_share(&math_ed25519::node_id_number(sender), &self.commitments);
+		if !math_ed25519::verify_signature_share(sender_commitments, &binding_factor, &lagrange_coefficient, &public_share, &challenge, &partial_response.partial_signature) {
+			return Ok(JobPartialResponseAction::Reject);
+		}
+
+		Ok(JobPartialResponseAction::Accept)
+	}
+
+	fn compute_response(&self, partial_responses: &BTreeMap<NodeId, Ed25519PartialSigningResponse>) -> Result<Bytes, Error> {
+		let message = self.message.as_ref()
+			.expect("compute_response is only called on master nodes; message is filed in constructor on master nodes; qed");
+		let nonces_commitments = self.nonces_commitments.as_ref()
+			.expect("compute_response is only called on master nodes; nonces_commitments is filed in constructor on master nodes; qed");
+
+		let group_commitment = math_ed25519::compute_group_commitment(message, nonces_commitments);
+		let signature = math_ed25519::combine_signature_shares(&group_commitment, partial_responses.values().map(|r| &r.partial_signature));
+		if !math_ed25519::verify_signature(&self.public, message, &signature) {
+			return Err(Error::Internal("computed Ed25519 signature is invalid".into()));
+		}
+
+		Ok(signature.to_vec())
+	}
+}
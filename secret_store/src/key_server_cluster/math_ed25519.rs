@@ -0,0 +1,404 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use rand::{Rng, OsRng};
+use sha2::{Digest, Sha512};
+use ethereum_types::H256;
+use hash::keccak;
+use key_server_cluster::{Error, NodeId};
+
+/// Domain separator of the FROST binding factor hash.
+const BINDING_FACTOR_DOMAIN: &'static [u8] = b"secretstore-frost-ed25519-binding";
+/// Domain separator of the FROST key generation proof of knowledge challenge.
+const PROOF_OF_KNOWLEDGE_DOMAIN: &'static [u8] = b"secretstore-frost-ed25519-keygen";
+
+/// Nonces pair, generated by the signer for the single signing session. Must never be reused.
+#[derive(Clone)]
+pub struct SigningNonces {
+	/// Hiding nonce.
+	pub hiding: Scalar,
+	/// Binding nonce.
+	pub binding: Scalar,
+}
+
+/// Public commitments to the signer nonces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SigningCommitments {
+	/// Hiding nonce commitment.
+	pub hiding: EdwardsPoint,
+	/// Binding nonce commitment.
+	pub binding: EdwardsPoint,
+}
+
+/// Generate random scalar.
+pub fn generate_random_scalar() -> Result<Scalar, Error> {
+	let mut bytes = [0u8; 64];
+	OsRng::new().map_err(|e| Error::Internal(e.to_string()))?.fill_bytes(&mut bytes);
+	Ok(Scalar::from_bytes_mod_order_wide(&bytes))
+}
+
+/// Generate random polynom of given threshold.
+pub fn generate_random_polynom(threshold: usize) -> Result<Vec<Scalar>, Error> {
+	(0..threshold + 1).map(|_| generate_random_scalar()).collect()
+}
+
+/// Multiply base point by given scalar.
+pub fn base_mul(scalar: &Scalar) -> EdwardsPoint {
+	scalar * &ED25519_BASEPOINT_POINT
+}
+
+/// Compute identification number of given node. Every node has the same number in every Ed25519 key.
+pub fn node_id_number(node: &NodeId) -> Scalar {
+	hash_to_scalar(&[&node[..]])
+}
+
+/// Compute value of polynom in given point.
+pub fn compute_polynom(polynom: &[Scalar], x: &Scalar) -> Scalar {
+	polynom.iter().rev().fold(Scalar::zero(), |result, coeff| result * x + coeff)
+}
+
+/// Compute public commitments to the polynom coefficients.
+pub fn public_values_generation(polynom: &[Scalar]) -> Vec<EdwardsPoint> {
+	polynom.iter().map(base_mul).collect()
+}
+
+/// Compute public value of polynom in given point, using public commitments to its coefficients.
+pub fn compute_public_share(x: &Scalar, commitments: &[EdwardsPoint]) -> EdwardsPoint {
+	commitments.iter().rev().fold(EdwardsPoint::identity(), |result, commitment| &result * x + commitment)
+}
+
+/// Check that secret share, received from dealer, matches dealer' public commitments.
+pub fn keys_verification(x: &Scalar, secret_share: &Scalar, commitments: &[EdwardsPoint]) -> bool {
+	base_mul(secret_share) == compute_public_share(x, commitments)
+}
+
+/// Combine public commitments of all dealers into commitments to the joint polynom.
+pub fn combine_commitments(commitments: &[Vec<EdwardsPoint>]) -> Result<Vec<EdwardsPoint>, Error> {
+	let len = commitments.get(0).map(|c| c.len()).ok_or(Error::InvalidMessage)?;
+	if commitments.iter().any(|c| c.len() != len) {
+		return Err(Error::InvalidMessage);
+	}
+
+	Ok((0..len).map(|i| commitments.iter().fold(EdwardsPoint::identity(), |result, c| result + c[i])).collect())
+}
+
+/// Compute hash of the commitments of all dealers, which is echoed to other nodes to check that every node
+/// has received the same commitments.
+pub fn compute_commitments_hash(commitments: &BTreeMap<NodeId, Vec<EdwardsPoint>>) -> H256 {
+	let mut encoded_commitments = Vec::new();
+	for (node, dealer_commitments) in commitments {
+		encoded_commitments.extend_from_slice(&node[..]);
+		for commitment in dealer_commitments {
+			encoded_commitments.extend_from_slice(commitment.compress().as_bytes());
+		}
+	}
+
+	keccak(&encoded_commitments)
+}
+
+/// Prove knowledge of the dealer' secret coefficient, committed to as `public = secret * G`.
+/// Returns proof `(R, mu)`, where `R = k * G` and `mu = k + secret * c`.
+pub fn compute_proof_of_knowledge(dealer: &NodeId, context: &[u8], secret: &Scalar, public: &EdwardsPoint) -> Result<(EdwardsPoint, Scalar), Error> {
+	let nonce = generate_random_scalar()?;
+	let nonce_commitment = base_mul(&nonce);
+	let challenge = compute_proof_of_knowledge_challenge(dealer, context, public, &nonce_commitment);
+	Ok((nonce_commitment, nonce + secret * challenge))
+}
+
+/// Check proof of knowledge of the dealer' secret coefficient: mu * G = R + c * public.
+pub fn verify_proof_of_knowledge(dealer: &NodeId, context: &[u8], public: &EdwardsPoint, nonce_commitment: &EdwardsPoint, response: &Scalar) -> bool {
+	let challenge = compute_proof_of_knowledge_challenge(dealer, context, public, nonce_commitment);
+	base_mul(response) == nonce_commitment + public * challenge
+}
+
+fn compute_proof_of_knowledge_challenge(dealer: &NodeId, context: &[u8], public: &EdwardsPoint, nonce_commitment: &EdwardsPoint) -> Scalar {
+	hash_to_scalar(&[PROOF_OF_KNOWLEDGE_DOMAIN, node_id_number(dealer).as_bytes(), context,
+		public.compress().as_bytes(), nonce_commitment.compress().as_bytes()])
+}
+
+/// Compute Lagrange coefficient of given node to interpolate secret in zero point.
+pub fn compute_lagrange_coefficient<'a, I>(self_id_number: &Scalar, other_id_numbers: I) -> Result<Scalar, Error> where I: Iterator<Item=&'a Scalar> {
+	let mut numerator = Scalar::one();
+	let mut denominator = Scalar::one();
+	for other_id_number in other_id_numbers {
+		if other_id_number == self_id_number {
+			return Err(Error::InvalidMessage);
+		}
+
+		numerator = numerator * other_id_number;
+		denominator = denominator * (other_id_number - self_id_number);
+	}
+
+	Ok(numerator * denominator.invert())
+}
+
+/// Generate signing nonces and their public commitments.
+pub fn generate_signing_nonces() -> Result<(SigningNonces, SigningCommitments), Error> {
+	let nonces = SigningNonces {
+		hiding: generate_random_scalar()?,
+		binding: generate_random_scalar()?,
+	};
+	let commitments = SigningCommitments {
+		hiding: base_mul(&nonces.hiding),
+		binding: base_mul(&nonces.binding),
+	};
+	Ok((nonces, commitments))
+}
+
+/// Compute binding factor of given signer.
+pub fn compute_binding_factor(node: &NodeId, message: &[u8], commitments: &BTreeMap<NodeId, SigningCommitments>) -> Scalar {
+	let mut encoded_commitments = Vec::with_capacity(commitments.len() * 96);
+	for (node, commitment) in commitments {
+		encoded_commitments.extend_from_slice(node_id_number(node).as_bytes());
+		encoded_commitments.extend_from_slice(commitment.hiding.compress().as_bytes());
+		encoded_commitments.extend_from_slice(commitment.binding.compress().as_bytes());
+	}
+
+	let message_hash = Sha512::digest(message);
+	hash_to_scalar(&[BINDING_FACTOR_DOMAIN, node_id_number(node).as_bytes(), &message_hash, &encoded_commitments])
+}
+
+/// Compute group commitment (R-portion of the signature).
+pub fn compute_group_commitment(message: &[u8], commitments: &BTreeMap<NodeId, SigningCommitments>) -> EdwardsPoint {
+	commitments.iter().fold(EdwardsPoint::identity(), |result, (node, commitment)|
+		result + commitment.hiding + commitment.binding * compute_binding_factor(node, message, commitments))
+}
+
+/// Compute Ed25519 challenge: SHA512(R || A || M).
+pub fn compute_challenge(group_commitment: &EdwardsPoint, public: &EdwardsPoint, message: &[u8]) -> Scalar {
+	hash_to_scalar(&[group_commitment.compress().as_bytes(), public.compress().as_bytes(), message])
+}
+
+/// Compute signature share of the signer: z = d + e * rho + lambda * s * c.
+pub fn compute_signature_share(nonces: &SigningNonces, binding_factor: &Scalar, lagrange_coefficient: &Scalar, secret_share: &Scalar, challenge: &Scalar) -> Scalar {
+	nonces.hiding + nonces.binding * binding_factor + lagrange_coefficient * secret_share * challenge
+}
+
+/// Check signature share of the signer: z * G = D + rho * E + lambda * c * Y.
+pub fn verify_signature_share(commitments: &SigningCommitments, binding_factor: &Scalar, lagrange_coefficient: &Scalar, public_share: &EdwardsPoint, challenge: &Scalar, signature_share: &Scalar) -> bool {
+	base_mul(signature_share) == commitments.hiding + commitments.binding * binding_factor + public_share * (lagrange_coefficient * challenge)
+}
+
+/// Combine signature shares into Ed25519 signature (R || S).
+pub fn combine_signature_shares<'a, I>(group_commitment: &EdwardsPoint, signature_shares: I) -> [u8; 64] where I: Iterator<Item=&'a Scalar> {
+	let signature_s = signature_shares.fold(Scalar::zero(), |result, share| result + share);
+
+	let mut signature = [0u8; 64];
+	signature[..32].copy_from_slice(group_commitment.compress().as_bytes());
+	signature[32..].copy_from_slice(signature_s.as_bytes());
+	signature
+}
+
+/// Verify Ed25519 signature.
+pub fn verify_signature(public: &EdwardsPoint, message: &[u8], signature: &[u8]) -> bool {
+	if signature.len() != 64 {
+		return false;
+	}
+
+	let mut signature_r = [0u8; 32];
+	signature_r.copy_from_slice(&signature[..32]);
+	let mut signature_s = [0u8; 32];
+	signature_s.copy_from_slice(&signature[32..]);
+	let (signature_r, signature_s) = match (CompressedEdwardsY(signature_r).decompress(), Scalar::from_canonical_bytes(signature_s)) {
+		(Some(signature_r), Some(signature_s)) => (signature_r, signature_s),
+		_ => return false,
+	};
+
+	let challenge = compute_challenge(&signature_r, public, message);
+	base_mul(&signature_s) == signature_r + public * challenge
+}
+
+/// Verify Ed25519 signature of the message, signed by the given compressed public key.
+pub fn verify_compressed_signature(public: &H256, message: &[u8], signature: &[u8]) -> Result<bool, Error> {
+	point_from_h256(public).map(|public| verify_signature(&public, message, signature))
+}
+
+/// Read scalar from its canonical encoding.
+pub fn scalar_from_h256(scalar: &H256) -> Result<Scalar, Error> {
+	Scalar::from_canonical_bytes(scalar.0).ok_or(Error::InvalidMessage)
+}
+
+/// Encode scalar.
+pub fn scalar_to_h256(scalar: &Scalar) -> H256 {
+	scalar.to_bytes().into()
+}
+
+/// Read point from its compressed encoding. Only points of the prime order subgroup (except the identity) are accepted.
+pub fn point_from_h256(point: &H256) -> Result<EdwardsPoint, Error> {
+	match CompressedEdwardsY(point.0).decompress() {
+		Some(ref point) if point.is_small_order() || !point.is_torsion_free() => Err(Error::InvalidMessage),
+		Some(point) => Ok(point),
+		None => Err(Error::InvalidMessage),
+	}
+}
+
+/// Encode point.
+pub fn point_to_h256(point: &EdwardsPoint) -> H256 {
+	point.compress().to_bytes().into()
+}
+
+fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+	let mut hasher = Sha512::default();
+	for part in parts {
+		hasher.input(part);
+	}
+
+	let mut hash = [0u8; 64];
+	hash.copy_from_slice(&hasher.result());
+	Scalar::from_bytes_mod_order_wide(&hash)
+}
+
+#[cfg(test)]
+pub mod tests {
+	use std::collections::BTreeMap;
+	use rustc_hex::FromHex;
+	use ethkey::{Random, Generator};
+	use key_server_cluster::NodeId;
+	use super::*;
+
+	struct KeyGenerationArtifacts {
+		nodes: Vec<NodeId>,
+		secret_shares: Vec<Scalar>,
+		commitments: Vec<EdwardsPoint>,
+	}
+
+	fn run_key_generation(threshold: usize, num_nodes: usize) -> KeyGenerationArtifacts {
+		let nodes: Vec<NodeId> = (0..num_nodes).map(|_| Random.generate().unwrap().public().clone()).collect();
+		let id_numbers: Vec<_> = nodes.iter().map(node_id_number).collect();
+		let polynoms: Vec<_> = (0..num_nodes).map(|_| generate_random_polynom(threshold).unwrap()).collect();
+		let dealers_commitments: Vec<_> = polynoms.iter().map(|p| public_values_generation(p)).collect();
+
+		let secret_shares = id_numbers.iter().map(|x| {
+			polynoms.iter().zip(dealers_commitments.iter()).fold(Scalar::zero(), |result, (polynom, commitments)| {
+				let share = compute_polynom(polynom, x);
+				assert!(keys_verification(x, &share, commitments));
+				result + share
+			})
+		}).collect();
+
+		KeyGenerationArtifacts {
+			nodes: nodes,
+			secret_shares: secret_shares,
+			commitments: combine_commitments(&dealers_commitments).unwrap(),
+		}
+	}
+
+	fn run_signing(artifacts: &KeyGenerationArtifacts, signers: &[usize], message: &[u8]) -> [u8; 64] {
+		let nonces: BTreeMap<NodeId, (SigningNonces, SigningCommitments)> = signers.iter()
+			.map(|i| (artifacts.nodes[*i].clone(), generate_signing_nonces().unwrap()))
+			.collect();
+		let commitments: BTreeMap<_, _> = nonces.iter().map(|(n, &(_, ref c))| (n.clone(), c.clone())).collect();
+		let group_commitment = compute_group_commitment(message, &commitments);
+		let challenge = compute_challenge(&group_commitment, &artifacts.commitments[0], message);
+
+		let signature_shares: Vec<_> = signers.iter().map(|i| {
+			let node = &artifacts.nodes[*i];
+			let id_number = node_id_number(node);
+			let other_id_numbers: Vec<_> = signers.iter().filter(|j| *j != i).map(|j| node_id_number(&artifacts.nodes[*j])).collect();
+			let lagrange_coefficient = compute_lagrange_coefficient(&id_number, other_id_numbers.iter()).unwrap();
+			let binding_factor = compute_binding_factor(node, message, &commitments);
+			let signature_share = compute_signature_share(&nonces[node].0, &binding_factor, &lagrange_coefficient,
+				&artifacts.secret_shares[*i], &challenge);
+
+			let public_share = compute_public_share(&id_number, &artifacts.commitments);
+			assert!(verify_signature_share(&commitments[node], &binding_factor, &lagrange_coefficient, &public_share, &challenge, &signature_share));
+			signature_share
+		}).collect();
+
+		combine_signature_shares(&group_commitment, signature_shares.iter())
+	}
+
+	#[test]
+	fn verify_signature_works_with_rfc8032_test_vector() {
+		let public: Vec<u8> = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a".from_hex().unwrap();
+		let signature: Vec<u8> = "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b".from_hex().unwrap();
+		let public = point_from_h256(&H256::from_slice(&public)).unwrap();
+
+		assert!(verify_signature(&public, &[], &signature));
+		assert!(!verify_signature(&public, &[1], &signature));
+	}
+
+	#[test]
+	fn threshold_signature_is_valid_ed25519_signature() {
+		let message = b"bridge transfer";
+		for &(threshold, num_nodes) in &[(0, 1), (1, 3), (2, 5), (4, 5)] {
+			let artifacts = run_key_generation(threshold, num_nodes);
+			let public = &artifacts.commitments[0];
+
+			// every subset of threshold + 1 nodes is able to sign
+			let first_signers: Vec<_> = (0..threshold + 1).collect();
+			let last_signers: Vec<_> = (num_nodes - threshold - 1..num_nodes).collect();
+			assert!(verify_signature(public, message, &run_signing(&artifacts, &first_signers, message)));
+			assert!(verify_signature(public, message, &run_signing(&artifacts, &last_signers, message)));
+		}
+	}
+
+	#[test]
+	fn proof_of_knowledge_works() {
+		let dealer = Random.generate().unwrap().public().clone();
+		let other = Random.generate().unwrap().public().clone();
+		let secret = generate_random_scalar().unwrap();
+		let public = base_mul(&secret);
+		let (nonce_commitment, response) = compute_proof_of_knowledge(&dealer, b"session", &secret, &public).unwrap();
+
+		assert!(verify_proof_of_knowledge(&dealer, b"session", &public, &nonce_commitment, &response));
+		assert!(!verify_proof_of_knowledge(&other, b"session", &public, &nonce_commitment, &response));
+		assert!(!verify_proof_of_knowledge(&dealer, b"other session", &public, &nonce_commitment, &response));
+		assert!(!verify_proof_of_knowledge(&dealer, b"session", &(public + base_mul(&Scalar::one())), &nonce_commitment, &response));
+	}
+
+	#[test]
+	fn small_order_points_are_rejected() {
+		// identity
+		let identity = point_to_h256(&EdwardsPoint::identity());
+		assert_eq!(point_from_h256(&identity), Err(Error::InvalidMessage));
+		// point of order 2: (0, -1)
+		let order_2: H256 = "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f".parse().unwrap();
+		assert_eq!(point_from_h256(&order_2), Err(Error::InvalidMessage));
+		// base point plus point of order 2 isn't torsion-free
+		let mixed = point_to_h256(&(ED25519_BASEPOINT_POINT + CompressedEdwardsY(order_2.0).decompress().unwrap()));
+		assert_eq!(point_from_h256(&mixed), Err(Error::InvalidMessage));
+		assert!(point_from_h256(&point_to_h256(&ED25519_BASEPOINT_POINT)).is_ok());
+	}
+
+	#[test]
+	fn invalid_signature_share_is_detected() {
+		let message = b"bridge transfer";
+		let artifacts = run_key_generation(1, 3);
+		let (nonces, commitments) = generate_signing_nonces().unwrap();
+		let (_, other_commitments) = generate_signing_nonces().unwrap();
+		let all_commitments: BTreeMap<_, _> = vec![
+			(artifacts.nodes[0].clone(), commitments.clone()),
+			(artifacts.nodes[1].clone(), other_commitments),
+		].into_iter().collect();
+
+		let id_number = node_id_number(&artifacts.nodes[0]);
+		let other_id_number = node_id_number(&artifacts.nodes[1]);
+		let lagrange_coefficient = compute_lagrange_coefficient(&id_number, ::std::iter::once(&other_id_number)).unwrap();
+		let binding_factor = compute_binding_factor(&artifacts.nodes[0], message, &all_commitments);
+		let challenge = compute_challenge(&compute_group_commitment(message, &all_commitments), &artifacts.commitments[0], message);
+		let public_share = compute_public_share(&id_number, &artifacts.commitments);
+
+		// share, computed using wrong secret share, is rejected
+		let invalid_share = compute_signature_share(&nonces, &binding_factor, &lagrange_coefficient, &artifacts.secret_shares[1], &challenge);
+		assert!(!verify_signature_share(&commitments, &binding_factor, &lagrange_coefficient, &public_share, &challenge, &invalid_share));
+	}
+}
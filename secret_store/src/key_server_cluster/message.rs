@@ -18,7 +18,7 @@ use std::fmt;
 use std::collections::{BTreeSet, BTreeMap};
 use ethkey::Secret;
 use key_server_cluster::SessionId;
-use super::{Error, SerializableH256, SerializablePublic, SerializableSecret,
+use super::{Error, SerializableH256, SerializablePublic, SerializableSecret, SerializableBytes,
 	SerializableSignature, SerializableMessageHash, SerializableRequester, SerializableAddress};
 
 pub type MessageSessionId = SerializableH256;
@@ -45,6 +45,12 @@ pub enum Message {
 	ShareAdd(ShareAddMessage),
 	/// Servers set change message.
 	ServersSetChange(ServersSetChangeMessage),
+	/// Key removal message.
+	KeyRemoval(KeyRemovalMessage),
+	/// Ed25519 key generation message.
+	Ed25519Generation(Ed25519GenerationMessage),
+	/// Ed25519 signing message.
+	Ed25519Signing(Ed25519SigningMessage),
 }
 
 /// All possible cluster-level messages.
@@ -224,6 +230,57 @@ pub enum ShareAddMessage {
 	ShareAddError(ShareAddError),
 }
 
+/// All possible messages that can be sent during key removal session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum KeyRemovalMessage {
+	/// Master node requests key share removal.
+	InitializeKeyRemoval(InitializeKeyRemoval),
+	/// Slave node confirms that key share is removed.
+	ConfirmKeyRemoval(ConfirmKeyRemoval),
+	/// When session error has occured.
+	KeyRemovalError(KeyRemovalError),
+}
+
+/// All possible messages that can be sent during Ed25519 key generation session.
+#[derive(Clone, Debug)]
+pub enum Ed25519GenerationMessage {
+	/// Initialize new Ed25519 DKG session.
+	Ed25519InitializeGeneration(Ed25519InitializeGeneration),
+	/// Confirm Ed25519 DKG session initialization.
+	Ed25519ConfirmGenerationInitialization(Ed25519ConfirmGenerationInitialization),
+	/// All nodes have confirmed initialization.
+	Ed25519CompleteGenerationInitialization(Ed25519CompleteGenerationInitialization),
+	/// Generated keys are sent to every node.
+	Ed25519KeysDissemination(Ed25519KeysDissemination),
+	/// Node has computed its key share.
+	Ed25519GenerationCompleted(Ed25519GenerationCompleted),
+	/// When session error has occured.
+	Ed25519GenerationError(Ed25519GenerationError),
+	/// Hash of all received commitments is echoed to every other node.
+	Ed25519CommitmentsEcho(Ed25519CommitmentsEcho),
+	/// All nodes have computed the same key, so key shares can be stored.
+	Ed25519GenerationConfirmed(Ed25519GenerationConfirmed),
+}
+
+/// All possible messages that can be sent during Ed25519 signing session.
+#[derive(Clone, Debug)]
+pub enum Ed25519SigningMessage {
+	/// Consensus establishing message.
+	Ed25519SigningConsensusMessage(Ed25519SigningConsensusMessage),
+	/// Request signing nonces commitments from node.
+	Ed25519RequestNonceCommitment(Ed25519RequestNonceCommitment),
+	/// Signing nonces commitments.
+	Ed25519NonceCommitment(Ed25519NonceCommitment),
+	/// Request partial signature from node.
+	Ed25519RequestPartialSignature(Ed25519RequestPartialSignature),
+	/// Partial signature is generated.
+	Ed25519PartialSignature(Ed25519PartialSignature),
+	/// Signing error occured.
+	Ed25519SigningSessionError(Ed25519SigningSessionError),
+	/// Signing session completed.
+	Ed25519SigningSessionCompleted(Ed25519SigningSessionCompleted),
+}
+
 /// All possible messages that can be sent during key version negotiation message.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum KeyVersionNegotiationMessage {
@@ -1006,6 +1063,231 @@ pub struct ShareAddError {
 	pub error: Error,
 }
 
+/// Key share removal is requested.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InitializeKeyRemoval {
+	/// Key removal session Id (equals to the server key id).
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Key removal request, signed by the administrator.
+	pub signature: SerializableSignature,
+}
+
+/// Key share removal is confirmed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfirmKeyRemoval {
+	/// Key removal session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// When key removal session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyRemovalError {
+	/// Key removal session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
+/// Ed25519 key generation is requested.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519InitializeGeneration {
+	/// Session Id (equals to the key id).
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Session author.
+	pub author: SerializableAddress,
+	/// All session participants.
+	pub nodes: BTreeSet<MessageNodeId>,
+	/// Signing threshold.
+	pub threshold: usize,
+}
+
+/// Ed25519 key generation initialization is confirmed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519ConfirmGenerationInitialization {
+	/// Session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// Ed25519 key generation initialization is confirmed by all nodes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519CompleteGenerationInitialization {
+	/// Session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// Ed25519 secret share is sent to the node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519KeysDissemination {
+	/// Session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Secret share of the sender polynom, computed for the receiver node.
+	pub secret_share: SerializableH256,
+	/// Compressed commitments to the sender polynom coefficients.
+	pub commitments: Vec<SerializableH256>,
+	/// Compressed nonce commitment of the proof of knowledge of the sender polynom constant term.
+	pub proof_commitment: SerializableH256,
+	/// Response of the proof of knowledge of the sender polynom constant term.
+	pub proof_response: SerializableH256,
+}
+
+/// Hash of the commitments, received by the node from all dealers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519CommitmentsEcho {
+	/// Session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Hash of the commitments of all dealers.
+	pub commitments_hash: SerializableH256,
+}
+
+/// Ed25519 key share is computed by the node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519GenerationCompleted {
+	/// Session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Compressed public key, computed by the node.
+	pub public: SerializableH256,
+}
+
+/// When Ed25519 key generation session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519GenerationError {
+	/// Session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
+/// Ed25519 key is computed by every node, key shares can be stored.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519GenerationConfirmed {
+	/// Session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// Consensus-related Ed25519 signing message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519SigningConsensusMessage {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Consensus message.
+	pub message: ConsensusMessage,
+}
+
+/// Request Ed25519 signing nonces commitments.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519RequestNonceCommitment {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// Ed25519 signing nonces commitments of the node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519NonceCommitment {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Signing nonces commitments.
+	pub commitment: Ed25519Commitment,
+}
+
+/// Compressed commitments to Ed25519 signing nonces.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519Commitment {
+	/// Commitment to hiding nonce.
+	pub hiding: SerializableH256,
+	/// Commitment to binding nonce.
+	pub binding: SerializableH256,
+}
+
+/// Request partial Ed25519 signature.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519RequestPartialSignature {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Request id.
+	pub request_id: SerializableSecret,
+	/// Message to sign.
+	pub message: SerializableBytes,
+	/// Signing nonces commitments of all selected nodes.
+	pub commitments: BTreeMap<MessageNodeId, Ed25519Commitment>,
+}
+
+/// Partial Ed25519 signature.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519PartialSignature {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Request id.
+	pub request_id: SerializableSecret,
+	/// Signature share.
+	pub partial_signature: SerializableH256,
+}
+
+/// When Ed25519 signing session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519SigningSessionError {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
+/// Ed25519 signing session completed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ed25519SigningSessionCompleted {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
 /// Key versions are requested.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RequestKeyVersions {
@@ -1082,6 +1364,12 @@ impl Message {
 				ConsensusMessageWithServersSet::InitializeConsensusSession(_) => true,
 				_ => false
 			},
+			Message::KeyRemoval(KeyRemovalMessage::InitializeKeyRemoval(_)) => true,
+			Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519InitializeGeneration(_)) => true,
+			Message::Ed25519Signing(Ed25519SigningMessage::Ed25519SigningConsensusMessage(ref msg)) => match msg.message {
+				ConsensusMessage::InitializeConsensusSession(_) => true,
+				_ => false
+			},
 			_ => false,
 		}
 	}
@@ -1105,6 +1393,9 @@ impl Message {
 			Message::KeyVersionNegotiation(KeyVersionNegotiationMessage::KeyVersionsError(_)) => true,
 			Message::ShareAdd(ShareAddMessage::ShareAddError(_)) => true,
 			Message::ServersSetChange(ServersSetChangeMessage::ServersSetChangeError(_)) => true,
+			Message::KeyRemoval(KeyRemovalMessage::KeyRemovalError(_)) => true,
+			Message::Ed25519Generation(Ed25519GenerationMessage::Ed25519GenerationError(_)) => true,
+			Message::Ed25519Signing(Ed25519SigningMessage::Ed25519SigningSessionError(_)) => true,
 			_ => false,
 		}
 	}
@@ -1127,6 +1418,9 @@ impl Message {
 			Message::ShareAdd(ref message) => Some(message.session_nonce()),
 			Message::ServersSetChange(ref message) => Some(message.session_nonce()),
 			Message::KeyVersionNegotiation(ref message) => Some(message.session_nonce()),
+			Message::KeyRemoval(ref message) => Some(message.session_nonce()),
+			Message::Ed25519Generation(ref message) => Some(message.session_nonce()),
+			Message::Ed25519Signing(ref message) => Some(message.session_nonce()),
 		}
 	}
 }
@@ -1358,6 +1652,90 @@ impl ShareAddMessage {
 	}
 }
 
+impl KeyRemovalMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			KeyRemovalMessage::InitializeKeyRemoval(ref msg) => &msg.session,
+			KeyRemovalMessage::ConfirmKeyRemoval(ref msg) => &msg.session,
+			KeyRemovalMessage::KeyRemovalError(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			KeyRemovalMessage::InitializeKeyRemoval(ref msg) => msg.session_nonce,
+			KeyRemovalMessage::ConfirmKeyRemoval(ref msg) => msg.session_nonce,
+			KeyRemovalMessage::KeyRemovalError(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
+impl Ed25519GenerationMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			Ed25519GenerationMessage::Ed25519InitializeGeneration(ref msg) => &msg.session,
+			Ed25519GenerationMessage::Ed25519ConfirmGenerationInitialization(ref msg) => &msg.session,
+			Ed25519GenerationMessage::Ed25519CompleteGenerationInitialization(ref msg) => &msg.session,
+			Ed25519GenerationMessage::Ed25519KeysDissemination(ref msg) => &msg.session,
+			Ed25519GenerationMessage::Ed25519GenerationCompleted(ref msg) => &msg.session,
+			Ed25519GenerationMessage::Ed25519GenerationError(ref msg) => &msg.session,
+			Ed25519GenerationMessage::Ed25519CommitmentsEcho(ref msg) => &msg.session,
+			Ed25519GenerationMessage::Ed25519GenerationConfirmed(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			Ed25519GenerationMessage::Ed25519InitializeGeneration(ref msg) => msg.session_nonce,
+			Ed25519GenerationMessage::Ed25519ConfirmGenerationInitialization(ref msg) => msg.session_nonce,
+			Ed25519GenerationMessage::Ed25519CompleteGenerationInitialization(ref msg) => msg.session_nonce,
+			Ed25519GenerationMessage::Ed25519KeysDissemination(ref msg) => msg.session_nonce,
+			Ed25519GenerationMessage::Ed25519GenerationCompleted(ref msg) => msg.session_nonce,
+			Ed25519GenerationMessage::Ed25519GenerationError(ref msg) => msg.session_nonce,
+			Ed25519GenerationMessage::Ed25519CommitmentsEcho(ref msg) => msg.session_nonce,
+			Ed25519GenerationMessage::Ed25519GenerationConfirmed(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
+impl Ed25519SigningMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			Ed25519SigningMessage::Ed25519SigningConsensusMessage(ref msg) => &msg.session,
+			Ed25519SigningMessage::Ed25519RequestNonceCommitment(ref msg) => &msg.session,
+			Ed25519SigningMessage::Ed25519NonceCommitment(ref msg) => &msg.session,
+			Ed25519SigningMessage::Ed25519RequestPartialSignature(ref msg) => &msg.session,
+			Ed25519SigningMessage::Ed25519PartialSignature(ref msg) => &msg.session,
+			Ed25519SigningMessage::Ed25519SigningSessionError(ref msg) => &msg.session,
+			Ed25519SigningMessage::Ed25519SigningSessionCompleted(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn sub_session_id(&self) -> &Secret {
+		match *self {
+			Ed25519SigningMessage::Ed25519SigningConsensusMessage(ref msg) => &msg.sub_session,
+			Ed25519SigningMessage::Ed25519RequestNonceCommitment(ref msg) => &msg.sub_session,
+			Ed25519SigningMessage::Ed25519NonceCommitment(ref msg) => &msg.sub_session,
+			Ed25519SigningMessage::Ed25519RequestPartialSignature(ref msg) => &msg.sub_session,
+			Ed25519SigningMessage::Ed25519PartialSignature(ref msg) => &msg.sub_session,
+			Ed25519SigningMessage::Ed25519SigningSessionError(ref msg) => &msg.sub_session,
+			Ed25519SigningMessage::Ed25519SigningSessionCompleted(ref msg) => &msg.sub_session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			Ed25519SigningMessage::Ed25519SigningConsensusMessage(ref msg) => msg.session_nonce,
+			Ed25519SigningMessage::Ed25519RequestNonceCommitment(ref msg) => msg.session_nonce,
+			Ed25519SigningMessage::Ed25519NonceCommitment(ref msg) => msg.session_nonce,
+			Ed25519SigningMessage::Ed25519RequestPartialSignature(ref msg) => msg.session_nonce,
+			Ed25519SigningMessage::Ed25519PartialSignature(ref msg) => msg.session_nonce,
+			Ed25519SigningMessage::Ed25519SigningSessionError(ref msg) => msg.session_nonce,
+			Ed25519SigningMessage::Ed25519SigningSessionCompleted(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
 impl KeyVersionNegotiationMessage {
 	pub fn session_id(&self) -> &SessionId {
 		match *self {
@@ -1396,6 +1774,9 @@ impl fmt::Display for Message {
 			Message::ServersSetChange(ref message) => write!(f, "ServersSetChange.{}", message),
 			Message::ShareAdd(ref message) => write!(f, "ShareAdd.{}", message),
 			Message::KeyVersionNegotiation(ref message) => write!(f, "KeyVersionNegotiation.{}", message),
+			Message::KeyRemoval(ref message) => write!(f, "KeyRemoval.{}", message),
+			Message::Ed25519Generation(ref message) => write!(f, "Ed25519Generation.{}", message),
+			Message::Ed25519Signing(ref message) => write!(f, "Ed25519Signing.{}", message),
 		}
 	}
 }
@@ -1539,6 +1920,16 @@ impl fmt::Display for ShareAddMessage {
 	}
 }
 
+impl fmt::Display for KeyRemovalMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			KeyRemovalMessage::InitializeKeyRemoval(_) => write!(f, "InitializeKeyRemoval"),
+			KeyRemovalMessage::ConfirmKeyRemoval(_) => write!(f, "ConfirmKeyRemoval"),
+			KeyRemovalMessage::KeyRemovalError(_) => write!(f, "KeyRemovalError"),
+		}
+	}
+}
+
 impl fmt::Display for KeyVersionNegotiationMessage {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match *self {
@@ -1548,3 +1939,32 @@ impl fmt::Display for KeyVersionNegotiationMessage {
 		}
 	}
 }
+
+impl fmt::Display for Ed25519GenerationMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Ed25519GenerationMessage::Ed25519InitializeGeneration(_) => write!(f, "Ed25519InitializeGeneration"),
+			Ed25519GenerationMessage::Ed25519ConfirmGenerationInitialization(_) => write!(f, "Ed25519ConfirmGenerationInitialization"),
+			Ed25519GenerationMessage::Ed25519CompleteGenerationInitialization(_) => write!(f, "Ed25519CompleteGenerationInitialization"),
+			Ed25519GenerationMessage::Ed25519KeysDissemination(_) => write!(f, "Ed25519KeysDissemination"),
+			Ed25519GenerationMessage::Ed25519GenerationCompleted(_) => write!(f, "Ed25519GenerationCompleted"),
+			Ed25519GenerationMessage::Ed25519GenerationError(ref msg) => write!(f, "Ed25519GenerationError({})", msg.error),
+			Ed25519GenerationMessage::Ed25519CommitmentsEcho(_) => write!(f, "Ed25519CommitmentsEcho"),
+			Ed25519GenerationMessage::Ed25519GenerationConfirmed(_) => write!(f, "Ed25519GenerationConfirmed"),
+		}
+	}
+}
+
+impl fmt::Display for Ed25519SigningMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Ed25519SigningMessage::Ed25519SigningConsensusMessage(ref m) => write!(f, "Ed25519SigningConsensusMessage.{}", m.message),
+			Ed25519SigningMessage::Ed25519RequestNonceCommitment(_) => write!(f, "Ed25519RequestNonceCommitment"),
+			Ed25519SigningMessage::Ed25519NonceCommitment(_) => write!(f, "Ed25519NonceCommitment"),
+			Ed25519SigningMessage::Ed25519RequestPartialSignature(_) => write!(f, "Ed25519RequestPartialSignature"),
+			Ed25519SigningMessage::Ed25519PartialSignature(_) => write!(f, "Ed25519PartialSignature"),
+			Ed25519SigningMessage::Ed25519SigningSessionError(_) => write!(f, "Ed25519SigningSessionError"),
+			Ed25519SigningMessage::Ed25519SigningSessionCompleted(_) => write!(f, "Ed25519SigningSessionCompleted"),
+		}
+	}
+}
@@ -18,11 +18,11 @@ use super::types::ServerKeyId;
 
 pub use super::traits::NodeKeyPair;
 pub use super::types::{Error, NodeId, Requester, EncryptedDocumentKeyShadow};
-pub use super::acl_storage::AclStorage;
-pub use super::key_storage::{KeyStorage, DocumentKeyShare, DocumentKeyShareVersion};
+pub use super::acl_storage::{AclStorage, AclOperation};
+pub use super::key_storage::{KeyStorage, DocumentKeyShare, DocumentKeyShareVersion, Ed25519KeyShare};
 pub use super::key_server_set::{is_migration_required, KeyServerSet, KeyServerSetSnapshot, KeyServerSetMigration};
 pub use super::serialization::{SerializableSignature, SerializableH256, SerializableSecret, SerializablePublic,
-	SerializableRequester, SerializableMessageHash, SerializableAddress};
+	SerializableRequester, SerializableMessageHash, SerializableAddress, SerializableBytes};
 pub use self::cluster::{ClusterCore, ClusterConfiguration, ClusterClient};
 pub use self::cluster_sessions::{ClusterSession, ClusterSessionsListener};
 #[cfg(test)]
@@ -58,6 +58,7 @@ pub struct SessionMeta {
 mod admin_sessions;
 mod client_sessions;
 
+pub use self::admin_sessions::key_removal_session;
 pub use self::admin_sessions::key_version_negotiation_session;
 pub use self::admin_sessions::servers_set_change_session;
 pub use self::admin_sessions::share_add_session;
@@ -66,7 +67,9 @@ pub use self::admin_sessions::share_change_session;
 pub use self::client_sessions::decryption_session;
 pub use self::client_sessions::encryption_session;
 pub use self::client_sessions::generation_session;
+pub use self::client_sessions::generation_session_ed25519;
 pub use self::client_sessions::signing_session_ecdsa;
+pub use self::client_sessions::signing_session_ed25519;
 pub use self::client_sessions::signing_session_schnorr;
 
 mod cluster;
@@ -77,5 +80,6 @@ mod connection_trigger_with_migration;
 mod io;
 mod jobs;
 pub mod math;
+pub mod math_ed25519;
 mod message;
 mod net;
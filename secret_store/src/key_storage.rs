@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 use serde_json;
 use tiny_keccak::Keccak;
@@ -25,13 +25,17 @@ use types::{Error, ServerKeyId, NodeId};
 use serialization::{SerializablePublic, SerializableSecret, SerializableH256, SerializableAddress};
 
 /// Key of version value.
-const DB_META_KEY_VERSION: &'static [u8; 7] = b"version";
+pub const DB_META_KEY_VERSION: &'static [u8; 7] = b"version";
 /// Current db version.
 const CURRENT_VERSION: u8 = 3;
+/// Version of db with current key shares sealed under node master key.
+pub const SEALED_VERSION: u8 = 4;
 /// Current type of serialized key shares.
 type CurrentSerializableDocumentKeyShare = SerializableDocumentKeyShareV3;
 /// Current type of serialized key shares versions.
 type CurrentSerializableDocumentKeyVersion = SerializableDocumentKeyShareVersionV3;
+/// Prefix of Ed25519 key shares keys.
+const DB_ED25519_KEY_PREFIX: &'static [u8; 8] = b"ed25519:";
 
 /// Encrypted key share, stored by key storage on the single key server.
 #[derive(Debug, Clone, PartialEq)]
@@ -62,6 +66,21 @@ pub struct DocumentKeyShareVersion {
 	pub secret_share: Secret,
 }
 
+/// Ed25519 key share, stored by key storage on the single key server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ed25519KeyShare {
+	/// Author of the entry.
+	pub author: Address,
+	/// Signing threshold (at least threshold + 1 nodes are required to sign message).
+	pub threshold: usize,
+	/// Nodes, holding shares of the key.
+	pub nodes: BTreeSet<NodeId>,
+	/// Compressed commitments to coefficients of the joint polynom. The first one is the key public.
+	pub commitments: Vec<H256>,
+	/// Node secret share.
+	pub secret_share: H256,
+}
+
 /// Document encryption keys storage
 pub trait KeyStorage: Send + Sync {
 	/// Insert document encryption key
@@ -70,7 +89,7 @@ pub trait KeyStorage: Send + Sync {
 	fn update(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error>;
 	/// Get document encryption key
 	fn get(&self, document: &ServerKeyId) -> Result<Option<DocumentKeyShare>, Error>;
-	/// Remove document encryption key
+	/// Remove document encryption key, along with the Ed25519 key share of the same id
 	fn remove(&self, document: &ServerKeyId) -> Result<(), Error>;
 	/// Clears the database
 	fn clear(&self) -> Result<(), Error>;
@@ -78,6 +97,12 @@ pub trait KeyStorage: Send + Sync {
 	fn contains(&self, document: &ServerKeyId) -> bool;
 	/// Iterate through storage
 	fn iter<'a>(&'a self) -> Box<Iterator<Item=(ServerKeyId, DocumentKeyShare)> + 'a>;
+	/// Insert Ed25519 key share
+	fn insert_ed25519(&self, key_id: ServerKeyId, key: Ed25519KeyShare) -> Result<(), Error>;
+	/// Get Ed25519 key share
+	fn get_ed25519(&self, key_id: &ServerKeyId) -> Result<Option<Ed25519KeyShare>, Error>;
+	/// Iterate through Ed25519 key shares
+	fn iter_ed25519<'a>(&'a self) -> Box<Iterator<Item=(ServerKeyId, Ed25519KeyShare)> + 'a>;
 }
 
 /// Persistent document encryption keys storage
@@ -170,9 +195,28 @@ struct SerializableDocumentKeyShareV3 {
 /// V3 of encrypted key share version, as it is stored by key storage on the single key server.
 type SerializableDocumentKeyShareVersionV3 = SerializableDocumentKeyShareVersionV2;
 
+/// Ed25519 key share, as it is stored by key storage on the single key server.
+#[derive(Serialize, Deserialize)]
+struct SerializableEd25519KeyShare {
+	/// Author of the entry.
+	pub author: SerializableAddress,
+	/// Signing threshold.
+	pub threshold: usize,
+	/// Nodes, holding shares of the key.
+	pub nodes: BTreeSet<SerializablePublic>,
+	/// Commitments to coefficients of the joint polynom.
+	pub commitments: Vec<SerializableH256>,
+	/// Node secret share.
+	pub secret_share: SerializableH256,
+}
+
 impl PersistentKeyStorage {
 	/// Create new persistent document encryption keys storage
 	pub fn new(db: Arc<KeyValueDB>) -> Result<Self, Error> {
+		if db_version(&*db)? == SEALED_VERSION {
+			return Err(Error::Database("SecretStore database is encrypted: master key is required to open it".into()));
+		}
+
 		let db = upgrade_db(db)?;
 
 		Ok(PersistentKeyStorage {
@@ -181,9 +225,15 @@ impl PersistentKeyStorage {
 	}
 }
 
-fn upgrade_db(db: Arc<KeyValueDB>) -> Result<Arc<KeyValueDB>, Error> {
+/// Read version of the database.
+pub fn db_version(db: &KeyValueDB) -> Result<u8, Error> {
 	let version = db.get(None, DB_META_KEY_VERSION)?;
-	let version = version.and_then(|v| v.get(0).cloned()).unwrap_or(0);
+	Ok(version.and_then(|v| v.get(0).cloned()).unwrap_or(0))
+}
+
+/// Upgrade database to the current (unsealed) version.
+pub fn upgrade_db(db: Arc<KeyValueDB>) -> Result<Arc<KeyValueDB>, Error> {
+	let version = db_version(&*db)?;
 	match version {
 		0 => {
 			let mut batch = db.transaction();
@@ -257,10 +307,52 @@ fn upgrade_db(db: Arc<KeyValueDB>) -> Result<Arc<KeyValueDB>, Error> {
 	}
 }
 
+/// Serialize key share using current serialization format.
+pub fn serialize_key_share(key: DocumentKeyShare) -> Result<Vec<u8>, Error> {
+	let key: CurrentSerializableDocumentKeyShare = key.into();
+	serde_json::to_vec(&key).map_err(|e| Error::Database(e.to_string()))
+}
+
+/// Deserialize key share, serialized using current serialization format.
+pub fn deserialize_key_share(key: &[u8]) -> Result<DocumentKeyShare, Error> {
+	serde_json::from_slice::<CurrentSerializableDocumentKeyShare>(key)
+		.map_err(|e| Error::Database(e.to_string()))
+		.map(Into::into)
+}
+
+/// Database key of Ed25519 key share.
+pub fn ed25519_db_key(key_id: &ServerKeyId) -> Vec<u8> {
+	let mut db_key = DB_ED25519_KEY_PREFIX.to_vec();
+	db_key.extend_from_slice(&**key_id);
+	db_key
+}
+
+/// Check if database key is a key of Ed25519 key share.
+pub fn is_ed25519_db_key(db_key: &[u8]) -> bool {
+	db_key.len() == DB_ED25519_KEY_PREFIX.len() + 32 && db_key.starts_with(DB_ED25519_KEY_PREFIX)
+}
+
+/// Read key id from database key of Ed25519 key share.
+pub fn ed25519_key_id(db_key: &[u8]) -> ServerKeyId {
+	db_key[DB_ED25519_KEY_PREFIX.len()..].into()
+}
+
+/// Serialize Ed25519 key share.
+pub fn serialize_ed25519_key_share(key: Ed25519KeyShare) -> Result<Vec<u8>, Error> {
+	let key: SerializableEd25519KeyShare = key.into();
+	serde_json::to_vec(&key).map_err(|e| Error::Database(e.to_string()))
+}
+
+/// Deserialize Ed25519 key share.
+pub fn deserialize_ed25519_key_share(key: &[u8]) -> Result<Ed25519KeyShare, Error> {
+	serde_json::from_slice::<SerializableEd25519KeyShare>(key)
+		.map_err(|e| Error::Database(e.to_string()))
+		.map(Into::into)
+}
+
 impl KeyStorage for PersistentKeyStorage {
 	fn insert(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
-		let key: CurrentSerializableDocumentKeyShare = key.into();
-		let key = serde_json::to_vec(&key).map_err(|e| Error::Database(e.to_string()))?;
+		let key = serialize_key_share(key)?;
 		let mut batch = self.db.transaction();
 		batch.put(None, &document, &key);
 		self.db.write(batch).map_err(Into::into)
@@ -275,22 +367,20 @@ impl KeyStorage for PersistentKeyStorage {
 			.map_err(|e| Error::Database(e.to_string()))
 			.and_then(|key| match key {
 				None => Ok(None),
-				Some(key) => serde_json::from_slice::<CurrentSerializableDocumentKeyShare>(&key)
-					.map_err(|e| Error::Database(e.to_string()))
-					.map(Into::into)
-					.map(Some),
+				Some(key) => deserialize_key_share(&key).map(Some),
 			})
 	}
 
 	fn remove(&self, document: &ServerKeyId) -> Result<(), Error> {
 		let mut batch = self.db.transaction();
 		batch.delete(None, &document);
+		batch.delete(None, &ed25519_db_key(document));
 		self.db.write(batch).map_err(Into::into)
 	}
 
 	fn clear(&self) -> Result<(), Error> {
 		let mut batch = self.db.transaction();
-		for (key, _) in self.iter() {
+		for (key, _) in self.db.iter(None).filter(|&(ref k, _)| **k != *DB_META_KEY_VERSION) {
 			batch.delete(None, &key);
 		}
 		self.db.write(batch)
@@ -305,9 +395,33 @@ impl KeyStorage for PersistentKeyStorage {
 
 	fn iter<'a>(&'a self) -> Box<Iterator<Item=(ServerKeyId, DocumentKeyShare)> + 'a> {
 		Box::new(PersistentKeyStorageIterator {
-			iter: self.db.iter(None),
+			iter: Box::new(self.db.iter(None).filter(|&(ref k, _)| **k != *DB_META_KEY_VERSION && !is_ed25519_db_key(k))),
 		})
 	}
+
+	fn insert_ed25519(&self, key_id: ServerKeyId, key: Ed25519KeyShare) -> Result<(), Error> {
+		let key = serialize_ed25519_key_share(key)?;
+		let mut batch = self.db.transaction();
+		batch.put(None, &ed25519_db_key(&key_id), &key);
+		self.db.write(batch).map_err(Into::into)
+	}
+
+	fn get_ed25519(&self, key_id: &ServerKeyId) -> Result<Option<Ed25519KeyShare>, Error> {
+		self.db.get(None, &ed25519_db_key(key_id))
+			.map_err(|e| Error::Database(e.to_string()))
+			.and_then(|key| match key {
+				None => Ok(None),
+				Some(key) => deserialize_ed25519_key_share(&key).map(Some),
+			})
+	}
+
+	fn iter_ed25519<'a>(&'a self) -> Box<Iterator<Item=(ServerKeyId, Ed25519KeyShare)> + 'a> {
+		Box::new(self.db.iter(None)
+			.filter(|&(ref k, _)| is_ed25519_db_key(k))
+			.filter_map(|(db_key, db_val)| deserialize_ed25519_key_share(&db_val)
+				.ok()
+				.map(|key| (ed25519_key_id(&db_key), key))))
+	}
 }
 
 impl<'a> Iterator for PersistentKeyStorageIterator<'a> {
@@ -315,9 +429,9 @@ impl<'a> Iterator for PersistentKeyStorageIterator<'a> {
 
 	fn next(&mut self) -> Option<(ServerKeyId, DocumentKeyShare)> {
 		self.iter.as_mut().next()
-			.and_then(|(db_key, db_val)| serde_json::from_slice::<CurrentSerializableDocumentKeyShare>(&db_val)
+			.and_then(|(db_key, db_val)| deserialize_key_share(&db_val)
 					  .ok()
-					  .map(|key| ((*db_key).into(), key.into())))
+					  .map(|key| ((*db_key).into(), key)))
 	}
 }
 
@@ -406,6 +520,37 @@ impl From<SerializableDocumentKeyShareV3> for DocumentKeyShare {
 	}
 }
 
+impl Ed25519KeyShare {
+	/// Get compressed Ed25519 public key.
+	pub fn public(&self) -> Result<&H256, Error> {
+		self.commitments.get(0).ok_or_else(|| Error::Database("key share has no commitments".into()))
+	}
+}
+
+impl From<Ed25519KeyShare> for SerializableEd25519KeyShare {
+	fn from(key: Ed25519KeyShare) -> Self {
+		SerializableEd25519KeyShare {
+			author: key.author.into(),
+			threshold: key.threshold,
+			nodes: key.nodes.into_iter().map(Into::into).collect(),
+			commitments: key.commitments.into_iter().map(Into::into).collect(),
+			secret_share: key.secret_share.into(),
+		}
+	}
+}
+
+impl From<SerializableEd25519KeyShare> for Ed25519KeyShare {
+	fn from(key: SerializableEd25519KeyShare) -> Self {
+		Ed25519KeyShare {
+			author: key.author.into(),
+			threshold: key.threshold,
+			nodes: key.nodes.into_iter().map(Into::into).collect(),
+			commitments: key.commitments.into_iter().map(Into::into).collect(),
+			secret_share: key.secret_share.into(),
+		}
+	}
+}
+
 #[cfg(test)]
 pub mod tests {
 	extern crate tempdir;
@@ -419,7 +564,7 @@ pub mod tests {
 	use ethkey::{Random, Generator, Public, Secret, public_to_address};
 	use kvdb_rocksdb::Database;
 	use types::{Error, ServerKeyId};
-	use super::{DB_META_KEY_VERSION, CURRENT_VERSION, KeyStorage, PersistentKeyStorage, DocumentKeyShare,
+	use super::{DB_META_KEY_VERSION, CURRENT_VERSION, KeyStorage, PersistentKeyStorage, DocumentKeyShare, Ed25519KeyShare,
 		DocumentKeyShareVersion, CurrentSerializableDocumentKeyShare, upgrade_db, SerializableDocumentKeyShareV0,
 		SerializableDocumentKeyShareV1, SerializableDocumentKeyShareV2, SerializableDocumentKeyShareVersionV2};
 
@@ -427,6 +572,7 @@ pub mod tests {
 	#[derive(Default)]
 	pub struct DummyKeyStorage {
 		keys: RwLock<HashMap<ServerKeyId, DocumentKeyShare>>,
+		ed25519_keys: RwLock<HashMap<ServerKeyId, Ed25519KeyShare>>,
 	}
 
 	impl KeyStorage for DummyKeyStorage {
@@ -446,11 +592,13 @@ pub mod tests {
 
 		fn remove(&self, document: &ServerKeyId) -> Result<(), Error> {
 			self.keys.write().remove(document);
+			self.ed25519_keys.write().remove(document);
 			Ok(())
 		}
 
 		fn clear(&self) -> Result<(), Error> {
 			self.keys.write().clear();
+			self.ed25519_keys.write().clear();
 			Ok(())
 		}
 
@@ -461,6 +609,19 @@ pub mod tests {
 		fn iter<'a>(&'a self) -> Box<Iterator<Item=(ServerKeyId, DocumentKeyShare)> + 'a> {
 			Box::new(self.keys.read().clone().into_iter())
 		}
+
+		fn insert_ed25519(&self, key_id: ServerKeyId, key: Ed25519KeyShare) -> Result<(), Error> {
+			self.ed25519_keys.write().insert(key_id, key);
+			Ok(())
+		}
+
+		fn get_ed25519(&self, key_id: &ServerKeyId) -> Result<Option<Ed25519KeyShare>, Error> {
+			Ok(self.ed25519_keys.read().get(key_id).cloned())
+		}
+
+		fn iter_ed25519<'a>(&'a self) -> Box<Iterator<Item=(ServerKeyId, Ed25519KeyShare)> + 'a> {
+			Box::new(self.ed25519_keys.read().clone().into_iter())
+		}
 	}
 
 	#[test]
@@ -516,6 +677,30 @@ pub mod tests {
 		assert_eq!(key_storage.get(&key3), Ok(None));
 	}
 
+	#[test]
+	fn persistent_key_storage_keeps_ed25519_shares_apart() {
+		let tempdir = TempDir::new("").unwrap();
+		let key_id = ServerKeyId::from(1);
+		let value = Ed25519KeyShare {
+			author: Default::default(),
+			threshold: 1,
+			nodes: vec![Random.generate().unwrap().public().clone()].into_iter().collect(),
+			commitments: vec![H256::random(), H256::random()],
+			secret_share: H256::random(),
+		};
+
+		let db = Database::open_default(&tempdir.path().display().to_string()).unwrap();
+		let key_storage = PersistentKeyStorage::new(Arc::new(db)).unwrap();
+		key_storage.insert_ed25519(key_id.clone(), value.clone()).unwrap();
+		assert_eq!(key_storage.get_ed25519(&key_id), Ok(Some(value.clone())));
+		assert_eq!(key_storage.get(&key_id), Ok(None));
+		assert_eq!(key_storage.iter().count(), 0);
+		assert_eq!(key_storage.iter_ed25519().collect::<Vec<_>>(), vec![(key_id.clone(), value)]);
+
+		key_storage.remove(&key_id).unwrap();
+		assert_eq!(key_storage.get_ed25519(&key_id), Ok(None));
+	}
+
 	#[test]
 	fn upgrade_db_from_0() {
 		let tempdir = TempDir::new("").unwrap();
@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 extern crate byteorder;
+extern crate curve25519_dalek;
 extern crate ethabi;
 extern crate ethcore;
 extern crate parity_bytes as bytes;
@@ -29,9 +30,11 @@ extern crate hyper;
 extern crate keccak_hash as hash;
 extern crate kvdb;
 extern crate parking_lot;
+extern crate rand;
 extern crate rustc_hex;
 extern crate serde;
 extern crate serde_json;
+extern crate sha2;
 extern crate tiny_keccak;
 extern crate tokio;
 extern crate tokio_core;
@@ -64,6 +67,7 @@ mod traits;
 mod acl_storage;
 mod key_server;
 mod key_storage;
+mod encrypted_key_storage;
 mod serialization;
 mod key_server_set;
 mod node_key_pair;
@@ -77,21 +81,27 @@ use ethcore::miner::Miner;
 use sync::SyncProvider;
 
 pub use types::{ServerKeyId, EncryptedDocumentKey, RequestSignature, Public,
-	Error, NodeAddress, ContractAddress, ServiceConfiguration, ClusterConfiguration};
+	Error, NodeAddress, ContractAddress, ServiceConfiguration, ClusterConfiguration, ServerKeyInfo};
 pub use traits::{NodeKeyPair, KeyServer};
 pub use self::node_key_pair::{PlainNodeKeyPair, KeyStoreNodeKeyPair};
+pub use self::encrypted_key_storage::{MasterKey, export_key_shares, import_key_shares, reencrypt_key_shares};
+pub use self::key_server_cluster::math_ed25519::verify_compressed_signature as verify_ed25519_signature;
 
 /// Start new key server instance
-pub fn start(client: Arc<Client>, sync: Arc<SyncProvider>, miner: Arc<Miner>, self_key_pair: Arc<NodeKeyPair>, mut config: ServiceConfiguration, db: Arc<KeyValueDB>) -> Result<Box<KeyServer>, Error> {
+pub fn start(client: Arc<Client>, sync: Arc<SyncProvider>, miner: Arc<Miner>, self_key_pair: Arc<NodeKeyPair>, mut config: ServiceConfiguration, db: Arc<KeyValueDB>, acl_cache_db: Arc<KeyValueDB>) -> Result<Box<KeyServer>, Error> {
 	let trusted_client = trusted_client::TrustedClient::new(self_key_pair.clone(), client.clone(), sync, miner);
 	let acl_storage: Arc<acl_storage::AclStorage> = match config.acl_check_contract_address.take() {
 		Some(acl_check_contract_address) => acl_storage::OnChainAclStorage::new(trusted_client.clone(), acl_check_contract_address)?,
 		None => Arc::new(acl_storage::DummyAclStorage::default()),
 	};
+	let acl_storage: Arc<acl_storage::AclStorage> = match config.acl_cache_window {
+		Some(acl_cache_window) => Arc::new(acl_storage::CachedAclStorage::new(acl_storage, acl_cache_db, acl_cache_window)),
+		None => acl_storage,
+	};
 
 	let key_server_set = key_server_set::OnChainKeyServerSet::new(trusted_client.clone(), config.cluster_config.key_server_set_contract_address.take(),
 		self_key_pair.clone(), config.cluster_config.auto_migrate_enabled, config.cluster_config.nodes.clone())?;
-	let key_storage = Arc::new(key_storage::PersistentKeyStorage::new(db)?);
+	let key_storage = encrypted_key_storage::open_key_storage(db, config.master_key.as_ref())?;
 	let key_server = Arc::new(key_server::KeyServerImpl::new(&config.cluster_config, key_server_set.clone(), self_key_pair.clone(), acl_storage.clone(), key_storage.clone())?);
 	let cluster = key_server.cluster();
 	let key_server: Arc<KeyServer> = key_server;
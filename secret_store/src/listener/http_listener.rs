@@ -26,11 +26,15 @@ use tokio::runtime::Runtime;
 use tokio_service::Service;
 use futures::{future, Future, Stream};
 use url::percent_encoding::percent_decode;
+use rustc_hex::FromHex;
+use bytes::Bytes;
+use ethereum_types::H256;
 
 use traits::KeyServer;
-use serialization::{SerializableEncryptedDocumentKeyShadow, SerializableBytes, SerializablePublic};
+use serialization::{SerializableEncryptedDocumentKeyShadow, SerializableBytes, SerializablePublic, SerializableServerKeyInfo,
+	SerializableH256};
 use types::{Error, Public, MessageHash, NodeAddress, RequestSignature, ServerKeyId,
-	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId};
+	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId, ServerKeyInfo};
 
 /// Key server http-requests listener. Available requests:
 /// To generate server key:							POST		/shadow/{server_key_id}/{signature}/{threshold}
@@ -40,7 +44,12 @@ use types::{Error, Public, MessageHash, NodeAddress, RequestSignature, ServerKey
 /// To get document key shadow:						GET			/shadow/{server_key_id}/{signature}
 /// To generate Schnorr signature with server key:	GET			/schnorr/{server_key_id}/{signature}/{message_hash}
 /// To generate ECDSA signature with server key:	GET			/ecdsa/{server_key_id}/{signature}/{message_hash}
+/// To generate Ed25519 server key:					POST		/ed25519/{server_key_id}/{signature}/{threshold}
+/// To generate Ed25519 signature with server key:	GET			/ed25519/{server_key_id}/{signature}/{message}
 /// To change servers set:							POST		/admin/servers_set_change/{old_signature}/{new_signature} + BODY: json array of hex-encoded nodes ids
+/// To list server keys:							GET			/admin/keys/{signature}
+/// To get server key metadata:						GET			/admin/keys/{server_key_id}/{signature}
+/// To remove server key from all nodes:			DELETE		/admin/keys/{server_key_id}/{signature}
 
 pub struct KeyServerHttpListener {
 	_runtime: Runtime,
@@ -66,8 +75,18 @@ enum Request {
 	SchnorrSignMessage(ServerKeyId, RequestSignature, MessageHash),
 	/// Generate ECDSA signature for the message.
 	EcdsaSignMessage(ServerKeyId, RequestSignature, MessageHash),
+	/// Generate Ed25519 server key.
+	GenerateEd25519Key(ServerKeyId, RequestSignature, usize),
+	/// Generate Ed25519 signature for the message.
+	Ed25519SignMessage(ServerKeyId, RequestSignature, Bytes),
 	/// Change servers set.
 	ChangeServersSet(RequestSignature, RequestSignature, BTreeSet<NodeId>),
+	/// List server keys.
+	ListServerKeys(RequestSignature),
+	/// Get server key metadata.
+	GetServerKeyInfo(ServerKeyId, RequestSignature),
+	/// Remove server key from all nodes.
+	RemoveServerKey(ServerKeyId, RequestSignature),
 }
 
 /// Cloneable http handler
@@ -184,6 +203,24 @@ impl KeyServerHttpHandler {
 						err
 					}))
 			},
+			Request::GenerateEd25519Key(document, signature, threshold) => {
+				return_ed25519_public_key(&req_uri, self.handler.key_server.upgrade()
+					.map(|key_server| key_server.generate_ed25519_key(&document, &signature.into(), threshold))
+					.unwrap_or(Err(Error::Internal("KeyServer is already destroyed".into())))
+					.map_err(|err| {
+						warn!(target: "secretstore", "GenerateEd25519Key request {} has failed with: {}", req_uri, err);
+						err
+					}))
+			},
+			Request::Ed25519SignMessage(document, signature, message) => {
+				return_message_signature(&req_uri, self.handler.key_server.upgrade()
+					.map(|key_server| key_server.sign_message_ed25519(&document, &signature.into(), message))
+					.unwrap_or(Err(Error::Internal("KeyServer is already destroyed".into())))
+					.map_err(|err| {
+						warn!(target: "secretstore", "Ed25519SignMessage request {} has failed with: {}", req_uri, err);
+						err
+					}))
+			},
 			Request::ChangeServersSet(old_set_signature, new_set_signature, new_servers_set) => {
 				return_empty(&req_uri, self.handler.key_server.upgrade()
 					.map(|key_server| key_server.change_servers_set(old_set_signature, new_set_signature, new_servers_set))
@@ -193,6 +230,33 @@ impl KeyServerHttpHandler {
 						err
 					}))
 				},
+			Request::ListServerKeys(signature) => {
+				return_server_keys_info(&req_uri, self.handler.key_server.upgrade()
+					.map(|key_server| key_server.list_server_keys(signature))
+					.unwrap_or(Err(Error::Internal("KeyServer is already destroyed".into())))
+					.map_err(|err| {
+						warn!(target: "secretstore", "ListServerKeys request {} has failed with: {}", req_uri, err);
+						err
+					}))
+			},
+			Request::GetServerKeyInfo(document, signature) => {
+				return_server_key_info(&req_uri, self.handler.key_server.upgrade()
+					.map(|key_server| key_server.server_key_info(&document, signature))
+					.unwrap_or(Err(Error::Internal("KeyServer is already destroyed".into())))
+					.map_err(|err| {
+						warn!(target: "secretstore", "GetServerKeyInfo request {} has failed with: {}", req_uri, err);
+						err
+					}))
+			},
+			Request::RemoveServerKey(document, signature) => {
+				return_empty(&req_uri, self.handler.key_server.upgrade()
+					.map(|key_server| key_server.remove_server_key(&document, signature))
+					.unwrap_or(Err(Error::Internal("KeyServer is already destroyed".into())))
+					.map_err(|err| {
+						warn!(target: "secretstore", "RemoveServerKey request {} has failed with: {}", req_uri, err);
+						err
+					}))
+			},
 			Request::Invalid => {
 				warn!(target: "secretstore", "Ignoring invalid {}-request {}", req_method, req_uri);
 				HttpResponse::new().with_status(HttpStatusCode::BadRequest)
@@ -238,6 +302,10 @@ fn return_server_public_key(req_uri: &Uri, server_public: Result<Public, Error>)
 	return_bytes(req_uri, server_public.map(|k| Some(SerializablePublic(k))))
 }
 
+fn return_ed25519_public_key(req_uri: &Uri, server_public: Result<H256, Error>) -> HttpResponse {
+	return_bytes(req_uri, server_public.map(|k| Some(SerializableH256(k))))
+}
+
 fn return_message_signature(req_uri: &Uri, signature: Result<EncryptedDocumentKey, Error>) -> HttpResponse {
 	return_bytes(req_uri, signature.map(|s| Some(SerializableBytes(s))))
 }
@@ -254,6 +322,14 @@ fn return_document_key_shadow(req_uri: &Uri, document_key_shadow: Result<Encrypt
 	})))
 }
 
+fn return_server_keys_info(req_uri: &Uri, server_keys: Result<Vec<ServerKeyInfo>, Error>) -> HttpResponse {
+	return_bytes(req_uri, server_keys.map(|keys| Some(keys.into_iter().map(SerializableServerKeyInfo::from).collect::<Vec<_>>())))
+}
+
+fn return_server_key_info(req_uri: &Uri, server_key: Result<ServerKeyInfo, Error>) -> HttpResponse {
+	return_bytes(req_uri, server_key.map(|key| Some(SerializableServerKeyInfo::from(key))))
+}
+
 fn return_bytes<T: Serialize>(req_uri: &Uri, result: Result<Option<T>, Error>) -> HttpResponse {
 	match result {
 		Ok(Some(result)) => match serde_json::to_vec(&result) {
@@ -307,7 +383,7 @@ fn parse_request(method: &HttpMethod, uri_path: &str, body: &[u8]) -> Request {
 		return parse_admin_request(method, path, body);
 	}
 
-	let (prefix, args_offset) = if &path[0] == "shadow" || &path[0] == "schnorr" || &path[0] == "ecdsa"
+	let (prefix, args_offset) = if &path[0] == "shadow" || &path[0] == "schnorr" || &path[0] == "ecdsa" || &path[0] == "ed25519"
 		{ (&*path[0], 1) } else { ("", 0) };
 	let args_count = path.len() - args_offset;
 	if args_count < 2 || path[args_offset].is_empty() || path[args_offset + 1].is_empty() {
@@ -327,27 +403,36 @@ fn parse_request(method: &HttpMethod, uri_path: &str, body: &[u8]) -> Request {
 	let message_hash = path.get(args_offset + 2).map(|v| v.parse());
 	let common_point = path.get(args_offset + 2).map(|v| v.parse());
 	let encrypted_key = path.get(args_offset + 3).map(|v| v.parse());
-	match (prefix, args_count, method, threshold, message_hash, common_point, encrypted_key) {
-		("shadow", 3, &HttpMethod::Post, Some(Ok(threshold)), _, _, _) =>
+	let message = path.get(args_offset + 2).map(|v| v.trim_left_matches("0x").from_hex());
+	match (prefix, args_count, method, threshold, message_hash, common_point, encrypted_key, message) {
+		("shadow", 3, &HttpMethod::Post, Some(Ok(threshold)), _, _, _, _) =>
 			Request::GenerateServerKey(document, signature, threshold),
-		("shadow", 4, &HttpMethod::Post, _, _, Some(Ok(common_point)), Some(Ok(encrypted_key))) =>
+		("shadow", 4, &HttpMethod::Post, _, _, Some(Ok(common_point)), Some(Ok(encrypted_key)), _) =>
 			Request::StoreDocumentKey(document, signature, common_point, encrypted_key),
-		("", 3, &HttpMethod::Post, Some(Ok(threshold)), _, _, _) =>
+		("", 3, &HttpMethod::Post, Some(Ok(threshold)), _, _, _, _) =>
 			Request::GenerateDocumentKey(document, signature, threshold),
-		("", 2, &HttpMethod::Get, _, _, _, _) =>
+		("", 2, &HttpMethod::Get, _, _, _, _, _) =>
 			Request::GetDocumentKey(document, signature),
-		("shadow", 2, &HttpMethod::Get, _, _, _, _) =>
+		("shadow", 2, &HttpMethod::Get, _, _, _, _, _) =>
 			Request::GetDocumentKeyShadow(document, signature),
-		("schnorr", 3, &HttpMethod::Get, _, Some(Ok(message_hash)), _, _) =>
+		("schnorr", 3, &HttpMethod::Get, _, Some(Ok(message_hash)), _, _, _) =>
 			Request::SchnorrSignMessage(document, signature, message_hash),
-		("ecdsa", 3, &HttpMethod::Get, _, Some(Ok(message_hash)), _, _) =>
+		("ecdsa", 3, &HttpMethod::Get, _, Some(Ok(message_hash)), _, _, _) =>
 			Request::EcdsaSignMessage(document, signature, message_hash),
+		("ed25519", 3, &HttpMethod::Post, Some(Ok(threshold)), _, _, _, _) =>
+			Request::GenerateEd25519Key(document, signature, threshold),
+		("ed25519", 3, &HttpMethod::Get, _, _, _, _, Some(Ok(message))) =>
+			Request::Ed25519SignMessage(document, signature, message),
 		_ => Request::Invalid,
 	}
 }
 
 fn parse_admin_request(method: &HttpMethod, path: Vec<String>, body: &[u8]) -> Request {
 	let args_count = path.len();
+	if args_count > 1 && path[1] == "keys" {
+		return parse_admin_keys_request(method, path);
+	}
+
 	if *method != HttpMethod::Post || args_count != 4 || path[1] != "servers_set_change" {
 		return Request::Invalid;
 	}
@@ -371,6 +456,31 @@ fn parse_admin_request(method: &HttpMethod, path: Vec<String>, body: &[u8]) -> R
 		new_servers_set.into_iter().map(Into::into).collect())
 }
 
+fn parse_admin_keys_request(method: &HttpMethod, path: Vec<String>) -> Request {
+	match (method, path.len()) {
+		(&HttpMethod::Get, 3) => match path[2].parse() {
+			Ok(signature) => Request::ListServerKeys(signature),
+			_ => Request::Invalid,
+		},
+		(&HttpMethod::Get, 4) | (&HttpMethod::Delete, 4) => {
+			let document = match path[2].parse() {
+				Ok(document) => document,
+				_ => return Request::Invalid,
+			};
+			let signature = match path[3].parse() {
+				Ok(signature) => signature,
+				_ => return Request::Invalid,
+			};
+
+			match *method {
+				HttpMethod::Get => Request::GetServerKeyInfo(document, signature),
+				_ => Request::RemoveServerKey(document, signature),
+			}
+		},
+		_ => Request::Invalid,
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use std::sync::Arc;
@@ -428,6 +538,15 @@ mod tests {
 			Request::EcdsaSignMessage("0000000000000000000000000000000000000000000000000000000000000001".into(),
 				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
 				"281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c".parse().unwrap()));
+		// POST		/ed25519/{server_key_id}/{signature}/{threshold}						=> generate Ed25519 server key
+		assert_eq!(parse_request(&HttpMethod::Post, "/ed25519/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/2", Default::default()),
+			Request::GenerateEd25519Key("0000000000000000000000000000000000000000000000000000000000000001".into(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(), 2));
+		// GET		/ed25519/{server_key_id}/{signature}/{message}						=> ed25519-sign message with server key
+		assert_eq!(parse_request(&HttpMethod::Get, "/ed25519/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/0x68656c6c6f", Default::default()),
+			Request::Ed25519SignMessage("0000000000000000000000000000000000000000000000000000000000000001".into(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				b"hello".to_vec()));
 		// POST		/admin/servers_set_change/{old_set_signature}/{new_set_signature} + body
 		let node1: Public = "843645726384530ffb0c52f175278143b5a93959af7864460f5a4fec9afd1450cfb8aef63dec90657f43f55b13e0a73c7524d4e9a13c051b4e5f1e53f39ecd91".parse().unwrap();
 		let node2: Public = "07230e34ebfe41337d3ed53b186b3861751f2401ee74b988bba55694e2a6f60c757677e194be2e53c3523cc8548694e636e6acb35c4e8fdc5e29d28679b9b2f3".parse().unwrap();
@@ -440,6 +559,17 @@ mod tests {
 				"b199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
 				nodes,
 			));
+		// GET		/admin/keys/{signature}												=> list server keys
+		assert_eq!(parse_request(&HttpMethod::Get, "/admin/keys/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()),
+			Request::ListServerKeys("a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()));
+		// GET		/admin/keys/{server_key_id}/{signature}								=> get server key metadata
+		assert_eq!(parse_request(&HttpMethod::Get, "/admin/keys/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()),
+			Request::GetServerKeyInfo("0000000000000000000000000000000000000000000000000000000000000001".into(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()));
+		// DELETE	/admin/keys/{server_key_id}/{signature}								=> remove server key
+		assert_eq!(parse_request(&HttpMethod::Delete, "/admin/keys/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()),
+			Request::RemoveServerKey("0000000000000000000000000000000000000000000000000000000000000001".into(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()));
 	}
 
 	#[test]
@@ -453,11 +583,17 @@ mod tests {
 		assert_eq!(parse_request(&HttpMethod::Get, "/a/b", Default::default()), Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::Get, "/schnorr/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/0000000000000000000000000000000000000000000000000000000000000002/0000000000000000000000000000000000000000000000000000000000000002", Default::default()), Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::Get, "/ecdsa/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/0000000000000000000000000000000000000000000000000000000000000002/0000000000000000000000000000000000000000000000000000000000000002", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::Get, "/ed25519/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/xyz", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::Post, "/ed25519/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/xyz", Default::default()), Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::Post, "/admin/servers_set_change/xxx/yyy",
 			&r#"["0x843645726384530ffb0c52f175278143b5a93959af7864460f5a4fec9afd1450cfb8aef63dec90657f43f55b13e0a73c7524d4e9a13c051b4e5f1e53f39ecd91",
 				"0x07230e34ebfe41337d3ed53b186b3861751f2401ee74b988bba55694e2a6f60c757677e194be2e53c3523cc8548694e636e6acb35c4e8fdc5e29d28679b9b2f3"]"#.as_bytes()),
 			Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::Post, "/admin/servers_set_change/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", "".as_bytes()),
 			Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::Post, "/admin/keys/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::Delete, "/admin/keys/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::Get, "/admin/keys/xxx/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::Post, "/admin/keys/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()), Request::Invalid);
 	}
 }
@@ -22,9 +22,11 @@ mod tasks_queue;
 
 use std::collections::BTreeSet;
 use std::sync::Arc;
+use ethereum_types::H256;
+use bytes::Bytes;
 use traits::{ServerKeyGenerator, DocumentKeyServer, MessageSigner, AdminSessionsServer, KeyServer};
 use types::{Error, Public, MessageHash, EncryptedMessageSignature, RequestSignature, ServerKeyId,
-	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId, Requester};
+	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId, Requester, ServerKeyInfo};
 
 /// Available API mask.
 #[derive(Debug, Default)]
@@ -75,6 +77,10 @@ impl ServerKeyGenerator for Listener {
 	fn generate_key(&self, key_id: &ServerKeyId, author: &Requester, threshold: usize) -> Result<Public, Error> {
 		self.key_server.generate_key(key_id, author, threshold)
 	}
+
+	fn generate_ed25519_key(&self, key_id: &ServerKeyId, author: &Requester, threshold: usize) -> Result<H256, Error> {
+		self.key_server.generate_ed25519_key(key_id, author, threshold)
+	}
 }
 
 impl DocumentKeyServer for Listener {
@@ -103,10 +109,26 @@ impl MessageSigner for Listener {
 	fn sign_message_ecdsa(&self, key_id: &ServerKeyId, requester: &Requester, message: MessageHash) -> Result<EncryptedMessageSignature, Error> {
 		self.key_server.sign_message_ecdsa(key_id, requester, message)
 	}
+
+	fn sign_message_ed25519(&self, key_id: &ServerKeyId, requester: &Requester, message: Bytes) -> Result<EncryptedMessageSignature, Error> {
+		self.key_server.sign_message_ed25519(key_id, requester, message)
+	}
 }
 
 impl AdminSessionsServer for Listener {
 	fn change_servers_set(&self, old_set_signature: RequestSignature, new_set_signature: RequestSignature, new_servers_set: BTreeSet<NodeId>) -> Result<(), Error> {
 		self.key_server.change_servers_set(old_set_signature, new_set_signature, new_servers_set)
 	}
+
+	fn list_server_keys(&self, signature: RequestSignature) -> Result<Vec<ServerKeyInfo>, Error> {
+		self.key_server.list_server_keys(signature)
+	}
+
+	fn server_key_info(&self, key_id: &ServerKeyId, signature: RequestSignature) -> Result<ServerKeyInfo, Error> {
+		self.key_server.server_key_info(key_id, signature)
+	}
+
+	fn remove_server_key(&self, key_id: &ServerKeyId, signature: RequestSignature) -> Result<(), Error> {
+		self.key_server.remove_server_key(key_id, signature)
+	}
 }
@@ -16,13 +16,14 @@
 
 use std::fmt;
 use std::ops::Deref;
+use std::collections::{BTreeMap, BTreeSet};
 use rustc_hex::{ToHex, FromHex};
 use serde::{Serialize, Deserialize, Serializer, Deserializer};
 use serde::de::{Visitor, Error as SerdeError};
 use ethkey::{Public, Secret, Signature};
 use ethereum_types::{H160, H256};
 use bytes::Bytes;
-use types::Requester;
+use types::{Requester, ServerKeyInfo};
 
 macro_rules! impl_bytes_deserialize {
 	($name: ident, $value: expr, true) => {
@@ -125,6 +126,27 @@ pub struct SerializableEncryptedDocumentKeyShadow {
 	pub decrypt_shadows: Vec<SerializableBytes>,
 }
 
+/// Serializable server key metadata.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableServerKeyInfo {
+	/// Server key id.
+	pub id: SerializableH256,
+	/// Author of the key entry.
+	pub author: SerializableAddress,
+	/// Key threshold.
+	pub threshold: usize,
+	/// Server key public, if this is a secp256k1 key.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub public: Option<SerializablePublic>,
+	/// Compressed server key public, if this is an Ed25519 key.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub ed25519_public: Option<SerializableH256>,
+	/// Is document key stored for this server key.
+	pub has_document_key: bool,
+	/// Key versions along with nodes holding shares of each version.
+	pub versions: BTreeMap<SerializableH256, BTreeSet<SerializablePublic>>,
+}
+
 /// Serializable requester identification data.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum SerializableRequester {
@@ -156,6 +178,22 @@ impl From<Requester> for SerializableRequester {
 	}
 }
 
+impl From<ServerKeyInfo> for SerializableServerKeyInfo {
+	fn from(info: ServerKeyInfo) -> SerializableServerKeyInfo {
+		SerializableServerKeyInfo {
+			id: info.id.into(),
+			author: info.author.into(),
+			threshold: info.threshold,
+			public: info.public.map(Into::into),
+			ed25519_public: info.ed25519_public.map(Into::into),
+			has_document_key: info.has_document_key,
+			versions: info.versions.into_iter()
+				.map(|(version, nodes)| (version.into(), nodes.into_iter().map(Into::into).collect()))
+				.collect(),
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_json;
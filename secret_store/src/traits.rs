@@ -17,8 +17,9 @@
 use std::collections::BTreeSet;
 use ethkey::{KeyPair, Signature, Error as EthKeyError};
 use ethereum_types::{H256, Address};
+use bytes::Bytes;
 use types::{Error, Public, ServerKeyId, MessageHash, EncryptedMessageSignature, RequestSignature, Requester,
-	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId};
+	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId, ServerKeyInfo};
 
 /// Node key pair.
 pub trait NodeKeyPair: Send + Sync {
@@ -40,6 +41,12 @@ pub trait ServerKeyGenerator {
 	/// `threshold + 1` is the minimal number of nodes, required to restore private key.
 	/// Result is a public portion of SK.
 	fn generate_key(&self, key_id: &ServerKeyId, author: &Requester, threshold: usize) -> Result<Public, Error>;
+	/// Generate new Ed25519 SK.
+	/// `key_id` is the caller-provided identifier of generated SK.
+	/// `author` is the author of key entry.
+	/// `threshold + 1` is the minimal number of nodes, required to sign message with this key.
+	/// Result is a compressed Ed25519 public key.
+	fn generate_ed25519_key(&self, key_id: &ServerKeyId, author: &Requester, threshold: usize) -> Result<H256, Error>;
 }
 
 /// Document key (DK) server.
@@ -89,6 +96,12 @@ pub trait MessageSigner: ServerKeyGenerator {
 	/// `message` is the message to be signed.
 	/// Result is a signed message, encrypted with caller public key.
 	fn sign_message_ecdsa(&self, key_id: &ServerKeyId, signature: &Requester, message: MessageHash) -> Result<EncryptedMessageSignature, Error>;
+	/// Generate Ed25519 signature for message with previously generated Ed25519 SK.
+	/// `key_id` is the caller-provided identifier of generated SK.
+	/// `requester` is the one who requests access to server key private.
+	/// `message` is the message to be signed (Ed25519 signs the message itself, not its hash).
+	/// Result is a signed message, encrypted with caller public key.
+	fn sign_message_ed25519(&self, key_id: &ServerKeyId, requester: &Requester, message: Bytes) -> Result<EncryptedMessageSignature, Error>;
 }
 
 /// Administrative sessions server.
@@ -98,6 +111,16 @@ pub trait AdminSessionsServer {
 	/// WARNING: newly generated keys will be distributed among all cluster nodes. So this session
 	/// must be followed with cluster nodes change (either via contract, or config files).
 	fn change_servers_set(&self, old_set_signature: RequestSignature, new_set_signature: RequestSignature, new_servers_set: BTreeSet<NodeId>) -> Result<(), Error>;
+	/// List all server keys, which shares are stored on this node.
+	/// `signature` is the Keccak("list_server_keys"), signed by administrator.
+	/// Versions in the result are filled from the local key shares.
+	fn list_server_keys(&self, signature: RequestSignature) -> Result<Vec<ServerKeyInfo>, Error>;
+	/// Get metadata of given server key, including nodes that are holding shares of every key version.
+	/// `signature` is the `key_id`, signed by administrator.
+	fn server_key_info(&self, key_id: &ServerKeyId, signature: RequestSignature) -> Result<ServerKeyInfo, Error>;
+	/// Remove shares of given server key from all cluster nodes. This can not be undone.
+	/// `signature` is the Keccak("remove_key" + `key_id`), signed by administrator.
+	fn remove_server_key(&self, key_id: &ServerKeyId, signature: RequestSignature) -> Result<(), Error>;
 }
 
 /// Key server.
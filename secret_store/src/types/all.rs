@@ -14,9 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
 
 use {ethkey, bytes, ethereum_types};
+use encrypted_key_storage::MasterKey;
 
 /// Node id.
 pub type NodeId = ethkey::Public;
@@ -68,6 +70,11 @@ pub struct ServiceConfiguration {
 	pub service_contract_doc_sretr_address: Option<ContractAddress>,
 	/// ACL check contract address. If None, everyone has access to all keys. Useful for tests only.
 	pub acl_check_contract_address: Option<ContractAddress>,
+	/// Time window, within which cached ACL permissions are used when the blockchain client is syncing or unavailable.
+	/// If None, permissions are not cached.
+	pub acl_cache_window: Option<Duration>,
+	/// Master key, used to seal key shares at rest. If None, key shares are stored unencrypted.
+	pub master_key: Option<MasterKey>,
 	/// Cluster configuration.
 	pub cluster_config: ClusterConfiguration,
 }
@@ -93,6 +100,25 @@ pub struct ClusterConfiguration {
 	pub auto_migrate_enabled: bool,
 }
 
+/// Server key metadata, as seen by the SecretStore administrator.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServerKeyInfo {
+	/// Server key id.
+	pub id: ServerKeyId,
+	/// Author of the key entry.
+	pub author: ethkey::Address,
+	/// Key threshold.
+	pub threshold: usize,
+	/// Server key public, if this is a secp256k1 key.
+	pub public: Option<Public>,
+	/// Compressed server key public, if this is an Ed25519 key.
+	pub ed25519_public: Option<ethereum_types::H256>,
+	/// Is document key stored for this server key.
+	pub has_document_key: bool,
+	/// Key versions along with nodes holding shares of each version. Ed25519 keys are not versioned.
+	pub versions: BTreeMap<ethereum_types::H256, BTreeSet<NodeId>>,
+}
+
 /// Shadow decryption result.
 #[derive(Clone, Debug, PartialEq)]
 pub struct EncryptedDocumentKeyShadow {
@@ -20,7 +20,7 @@ extern crate ethereum_types;
 extern crate ethkey;
 
 use std::fmt;
-use ethereum_types::U256;
+use ethereum_types::{H256, U256};
 use ethkey::{Address, Signature};
 
 pub struct WalletInfo {
@@ -92,6 +92,10 @@ impl HardwareWalletManager {
 	pub fn sign_message(&self, _address: &Address, _msg: &[u8]) -> Result<Signature, Error> {
 		Err(Error::NoWallet)
 	}
+
+	pub fn sign_typed_data(&self, _address: &Address, _domain_hash: &H256, _message_hash: &H256) -> Result<Signature, Error> {
+		Err(Error::NoWallet)
+	}
 }
 
 impl fmt::Display for Error {
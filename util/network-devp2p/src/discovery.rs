@@ -16,6 +16,7 @@
 
 use parity_bytes::Bytes;
 use std::net::SocketAddr;
+use std::cmp;
 use std::collections::{HashSet, HashMap, VecDeque};
 use std::collections::hash_map::Entry;
 use std::default::Default;
@@ -27,6 +28,11 @@ use node_table::*;
 use network::{Error, ErrorKind};
 use ethkey::{Secret, KeyPair, sign, recover};
 use network::IpFilter;
+use rand::random;
+use enr::NodeRecord;
+use discovery_v5::{Sessions, Incoming, Message};
+use topic_discovery::{TopicTable, Registration, topic_hash, TOPIC_REFRESH, TOPIC_AD_LIFETIME, RECORDS_PER_PACKET, MAX_TOPIC_NODES,
+	MAX_ADS_PER_TOPIC};
 
 use PROTOCOL_VERSION;
 
@@ -41,6 +47,8 @@ const PACKET_PING: u8 = 1;
 const PACKET_PONG: u8 = 2;
 const PACKET_FIND_NODE: u8 = 3;
 const PACKET_NEIGHBOURS: u8 = 4;
+const PACKET_ENR_REQUEST: u8 = 5;
+const PACKET_ENR_RESPONSE: u8 = 6;
 
 const PING_TIMEOUT: Duration = Duration::from_millis(500);
 const FIND_NODE_TIMEOUT: Duration = Duration::from_secs(2);
@@ -52,11 +60,14 @@ const REQUEST_BACKOFF: [Duration; 4] = [
 	Duration::from_secs(16),
 	Duration::from_secs(64)
 ];
+// Max number of Nodes messages accepted in answer to a topic query
+const MAX_TOPIC_RESPONSES: u64 = (MAX_ADS_PER_TOPIC / RECORDS_PER_PACKET + 1) as u64;
 
 #[derive(Clone, Debug)]
 pub struct NodeEntry {
 	pub id: NodeId,
 	pub endpoint: NodeEndpoint,
+	pub record: Option<NodeRecord>,
 }
 
 pub struct BucketEntry {
@@ -101,6 +112,31 @@ struct PingRequest {
 	deprecated_echo_hash: H256,
 }
 
+struct EnrRequest {
+	// Time when the request was sent
+	sent_at: Instant,
+	// Hash of the request packet, echoed in the response
+	echo_hash: H256,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TopicRequestKind {
+	Register,
+	Query,
+}
+
+struct TopicRequest {
+	// Time when the request was sent
+	sent_at: Instant,
+	// The node to which the request was sent
+	node_id: NodeId,
+	// Hash of the topic
+	topic: H256,
+	kind: TopicRequestKind,
+	// Number of Nodes messages received in answer to a query
+	responses: u64,
+}
+
 pub struct NodeBucket {
 	nodes: VecDeque<BucketEntry>, //sorted by last active
 }
@@ -141,6 +177,18 @@ pub struct Discovery<'a> {
 	adding_nodes: Vec<NodeEntry>,
 	ip_filter: IpFilter,
 	request_backoff: &'a [Duration],
+	record: Option<NodeRecord>,
+	in_flight_enr_requests: HashMap<NodeId, EnrRequest>,
+	v5: Sessions,
+	topic_table: TopicTable,
+	advertised_topics: HashSet<H256>,
+	// Advertisers found for each searched topic, with the time they were last found
+	topic_searches: HashMap<H256, HashMap<NodeId, (NodeEntry, Instant)>>,
+	// Topic requests by request id
+	in_flight_topic_requests: HashMap<Bytes, TopicRequest>,
+	// Tickets of registrars, with the time from which they are valid
+	topic_tickets: HashMap<(H256, NodeId), (Instant, Bytes)>,
+	last_topic_round: Option<Instant>,
 }
 
 pub struct TableUpdates {
@@ -167,9 +215,44 @@ impl<'a> Discovery<'a> {
 			adding_nodes: Vec::new(),
 			ip_filter,
 			request_backoff: &REQUEST_BACKOFF,
+			record: None,
+			in_flight_enr_requests: HashMap::new(),
+			v5: Sessions::new(key),
+			topic_table: TopicTable::default(),
+			advertised_topics: HashSet::new(),
+			topic_searches: HashMap::new(),
+			in_flight_topic_requests: HashMap::new(),
+			topic_tickets: HashMap::new(),
+			last_topic_round: None,
 		}
 	}
 
+	/// Set the local node record, served to other nodes and advertised for topics.
+	pub fn set_record(&mut self, record: NodeRecord) {
+		self.record = Some(record);
+	}
+
+	/// Register this node at topic registrars on each topic round.
+	pub fn advertise_topic(&mut self, topic: &str) {
+		if self.advertised_topics.insert(topic_hash(topic)) {
+			self.last_topic_round = None;
+		}
+	}
+
+	/// Look up advertisers of the topic on each topic round. Found nodes are added to the table.
+	pub fn search_topic(&mut self, topic: &str) {
+		let topic = topic_hash(topic);
+		if !self.topic_searches.contains_key(&topic) {
+			self.topic_searches.insert(topic, HashMap::new());
+			self.last_topic_round = None;
+		}
+	}
+
+	/// Advertisers of a searched topic found so far.
+	pub fn topic_nodes(&self, topic: &str) -> Vec<NodeEntry> {
+		self.topic_searches.get(&topic_hash(topic)).map(|nodes| nodes.values().map(|&(ref node, _)| node.clone()).collect()).unwrap_or_default()
+	}
+
 	/// Add a new node to discovery table. Pings the node.
 	pub fn add_node(&mut self, e: NodeEntry) {
 		// If distance returns None, then we are trying to add ourself.
@@ -323,11 +406,14 @@ impl<'a> Discovery<'a> {
 	}
 
 	fn ping(&mut self, node: &NodeEntry) -> Result<(), Error> {
-		let mut rlp = RlpStream::new_list(4);
+		let mut rlp = RlpStream::new_list(4 + self.record.is_some() as usize);
 		rlp.append(&PROTOCOL_VERSION);
 		self.public_endpoint.to_rlp_list(&mut rlp);
 		node.endpoint.to_rlp_list(&mut rlp);
 		append_expiration(&mut rlp);
+		if let Some(ref record) = self.record {
+			rlp.append(&record.seq());
+		}
 		let old_parity_hash = keccak(rlp.as_raw());
 		let hash = self.send_packet(PACKET_PING, &node.endpoint.udp_address(), &rlp.drain())?;
 
@@ -358,6 +444,44 @@ impl<'a> Discovery<'a> {
 		Ok(())
 	}
 
+	fn request_record(&mut self, node: &NodeEntry) -> Result<(), Error> {
+		let mut rlp = RlpStream::new_list(1);
+		append_expiration(&mut rlp);
+		let hash = self.send_packet(PACKET_ENR_REQUEST, &node.endpoint.udp_address(), &rlp.drain())?;
+
+		self.in_flight_enr_requests.insert(node.id, EnrRequest {
+			sent_at: Instant::now(),
+			echo_hash: hash,
+		});
+
+		trace!(target: "discovery", "Sent ENRRequest to {:?}", &node.endpoint);
+		Ok(())
+	}
+
+	/// Requests the record of a node in the routing table if it advertises a newer one.
+	fn check_record_seq(&mut self, node_id: &NodeId, seq: Option<u64>) {
+		let seq = match seq {
+			Some(seq) => seq,
+			None => return,
+		};
+		if self.in_flight_enr_requests.contains_key(node_id) {
+			return;
+		}
+		let node = match self.bucket_entry(node_id) {
+			Some(entry) if entry.address.record.as_ref().map_or(true, |r| r.seq() < seq) => entry.address.clone(),
+			_ => return,
+		};
+		self.request_record(&node).unwrap_or_else(|e| {
+			warn!(target: "discovery", "Error sending ENRRequest packet: {:?}", e);
+		});
+	}
+
+	fn bucket_entry(&self, node_id: &NodeId) -> Option<&BucketEntry> {
+		let id_hash = keccak(node_id);
+		Discovery::distance(&self.id_hash, &id_hash)
+			.and_then(|dist| self.node_buckets[dist].nodes.iter().find(|n| n.id_hash == id_hash))
+	}
+
 	fn send_packet(&mut self, packet_id: u8, address: &SocketAddr, payload: &[u8]) -> Result<H256, Error> {
 		let packet = assemble_packet(packet_id, payload, &self.secret)?;
 		let hash = H256::from(&packet[0..32]);
@@ -366,7 +490,11 @@ impl<'a> Discovery<'a> {
 	}
 
 	fn nearest_node_entries(&self, target: &NodeId) -> Vec<NodeEntry> {
-		let target_hash = keccak(target);
+		self.nearest_node_entries_to(&keccak(target))
+	}
+
+	fn nearest_node_entries_to(&self, target_hash: &H256) -> Vec<NodeEntry> {
+		let target_hash = *target_hash;
 		let target_distance = self.id_hash ^ target_hash;
 
 		let mut ret = Vec::<NodeEntry>::with_capacity(BUCKET_SIZE);
@@ -422,15 +550,11 @@ impl<'a> Discovery<'a> {
 
 
 	pub fn on_packet(&mut self, packet: &[u8], from: SocketAddr) -> Result<Option<TableUpdates>, Error> {
-		// validate packet
-		if packet.len() < 32 + 65 + 4 + 1 {
-			return Err(ErrorKind::BadProtocol.into());
-		}
-
-		let hash_signed = keccak(&packet[32..]);
-		if hash_signed[..] != packet[0..32] {
-			return Err(ErrorKind::BadProtocol.into());
+		// v4 packets start with their hash, anything else may be a masked v5 packet
+		if packet.len() < 32 + 65 + 4 + 1 || keccak(&packet[32..])[..] != packet[0..32] {
+			return self.on_v5_packet(packet, &from);
 		}
+		let hash_signed = H256::from_slice(&packet[0..32]);
 
 		let signed = &packet[(32 + 65)..];
 		let signature = H520::from_slice(&packet[32..(32 + 65)]);
@@ -442,6 +566,8 @@ impl<'a> Discovery<'a> {
 			PACKET_PONG => self.on_pong(&rlp, &node_id, &from),
 			PACKET_FIND_NODE => self.on_find_node(&rlp, &node_id, &from),
 			PACKET_NEIGHBOURS => self.on_neighbours(&rlp, &node_id, &from),
+			PACKET_ENR_REQUEST => self.on_enr_request(&rlp, &node_id, &from, &hash_signed),
+			PACKET_ENR_RESPONSE => self.on_enr_response(&rlp, &node_id, &from),
 			_ => {
 				debug!(target: "discovery", "Unknown UDP packet: {}", packet_id);
 				Ok(None)
@@ -469,7 +595,8 @@ impl<'a> Discovery<'a> {
 		let ping_to = NodeEndpoint::from_rlp(&rlp.at(2)?)?;
 		let timestamp: u64 = rlp.val_at(3)?;
 		self.check_timestamp(timestamp)?;
-		let mut response = RlpStream::new_list(3);
+		let record_seq: Option<u64> = rlp.val_at(4).ok();
+		let mut response = RlpStream::new_list(3 + self.record.is_some() as usize);
 		let pong_to = NodeEndpoint {
 			address: from.clone(),
 			udp_port: ping_from.udp_port
@@ -484,15 +611,19 @@ impl<'a> Discovery<'a> {
 
 		response.append(&echo_hash);
 		append_expiration(&mut response);
+		if let Some(ref record) = self.record {
+			response.append(&record.seq());
+		}
 		self.send_packet(PACKET_PONG, from, &response.drain())?;
 
-		let entry = NodeEntry { id: *node_id, endpoint: pong_to.clone() };
+		let entry = NodeEntry { id: *node_id, endpoint: pong_to.clone(), record: None };
 		if !entry.endpoint.is_valid() {
 			debug!(target: "discovery", "Got bad address: {:?}", entry);
 		} else if !self.is_allowed(&entry) {
 			debug!(target: "discovery", "Address not allowed: {:?}", entry);
 		} else {
 			self.add_node(entry.clone());
+			self.check_record_seq(node_id, record_seq);
 		}
 		Ok(None)
 	}
@@ -503,6 +634,7 @@ impl<'a> Discovery<'a> {
 		let echo_hash: H256 = rlp.val_at(1)?;
 		let timestamp: u64 = rlp.val_at(2)?;
 		self.check_timestamp(timestamp)?;
+		let record_seq: Option<u64> = rlp.val_at(3).ok();
 
 		let expected_node = match self.in_flight_pings.entry(*node_id) {
 			Entry::Occupied(entry) => {
@@ -530,7 +662,9 @@ impl<'a> Discovery<'a> {
 		};
 
 		if let Some(node) = expected_node {
-			Ok(self.update_node(node))
+			let updates = self.update_node(node);
+			self.check_record_seq(node_id, record_seq);
+			Ok(updates)
 		} else {
 			debug!(target: "discovery", "Got unexpected Pong from {:?} ; request not found", &from);
 			Ok(None)
@@ -614,7 +748,7 @@ impl<'a> Discovery<'a> {
 			if node_id == self.id {
 				continue;
 			}
-			let entry = NodeEntry { id: node_id, endpoint };
+			let entry = NodeEntry { id: node_id, endpoint, record: None };
 			if !self.is_allowed(&entry) {
 				debug!(target: "discovery", "Address not allowed: {:?}", entry);
 				continue;
@@ -624,6 +758,361 @@ impl<'a> Discovery<'a> {
 		Ok(None)
 	}
 
+	fn on_enr_request(&mut self, rlp: &Rlp, node_id: &NodeId, from: &SocketAddr, echo_hash: &H256) -> Result<Option<TableUpdates>, Error> {
+		trace!(target: "discovery", "Got ENRRequest from {:?}", &from);
+		let timestamp: u64 = rlp.val_at(0)?;
+		self.check_timestamp(timestamp)?;
+		// records are only sent to nodes with a verified endpoint to avoid traffic amplification
+		if self.bucket_entry(node_id).map_or(true, |n| n.address.endpoint.udp_address() != *from) {
+			debug!(target: "discovery", "Got ENRRequest from unknown node {:?}", &from);
+			return Ok(None);
+		}
+		let record = match self.record {
+			Some(ref record) => record.clone(),
+			None => return Ok(None),
+		};
+		let mut response = RlpStream::new_list(2);
+		response.append(echo_hash);
+		response.append(&record);
+		self.send_packet(PACKET_ENR_RESPONSE, from, &response.drain())?;
+		Ok(None)
+	}
+
+	fn on_enr_response(&mut self, rlp: &Rlp, node_id: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, Error> {
+		trace!(target: "discovery", "Got ENRResponse from {:?}", &from);
+		let echo_hash: H256 = rlp.val_at(0)?;
+		let record: NodeRecord = rlp.val_at(1)?;
+
+		if !self.in_flight_enr_requests.get(node_id).map_or(false, |r| r.echo_hash == echo_hash) {
+			debug!(target: "discovery", "Got unexpected ENRResponse from {:?} ; node_id={:#x}", &from, node_id);
+			return Ok(None);
+		}
+		self.in_flight_enr_requests.remove(node_id);
+		if record.id() != node_id {
+			debug!(target: "discovery", "Got ENRResponse with foreign record from {:?}", &from);
+			return Ok(None);
+		}
+
+		let id_hash = keccak(node_id);
+		let entry = match Discovery::distance(&self.id_hash, &id_hash) {
+			Some(dist) => match self.node_buckets[dist].nodes.iter_mut().find(|n| n.id_hash == id_hash) {
+				Some(node) => {
+					node.address.record = Some(record);
+					node.address.clone()
+				},
+				None => return Ok(None),
+			},
+			None => return Ok(None),
+		};
+		let mut added = HashMap::new();
+		added.insert(entry.id, entry);
+		Ok(Some(TableUpdates { added, removed: HashSet::new() }))
+	}
+
+	fn on_v5_packet(&mut self, packet: &[u8], from: &SocketAddr) -> Result<Option<TableUpdates>, Error> {
+		match self.v5.decode(packet, from, self.record.as_ref())? {
+			Incoming::Message(node_id, record, message) => Ok(self.on_v5_message(&node_id, record, message, from)),
+			Incoming::Reply(packet) => {
+				self.send_to(packet, *from);
+				Ok(None)
+			},
+			Incoming::Nothing => Ok(None),
+		}
+	}
+
+	// Messages arrive over sessions whose handshake was answered from the sender's endpoint,
+	// which proves the endpoint as well as a v4 ping does, so they are answered right away.
+	fn on_v5_message(&mut self, node_id: &NodeId, record: Option<NodeRecord>, message: Message, from: &SocketAddr) -> Option<TableUpdates> {
+		let updates = record.and_then(|record| self.on_v5_record(record));
+		match message {
+			Message::Ping { request_id, .. } => {
+				trace!(target: "discovery", "Got v5 Ping from {:?}", &from);
+				let enr_seq = self.record.as_ref().map_or(0, |record| record.seq());
+				self.send_v5(node_id, from, Message::Pong { request_id, enr_seq, address: *from });
+			},
+			Message::FindNode { request_id, distances } => self.on_v5_find_node(node_id, from, request_id, &distances),
+			Message::Nodes { request_id, total, records } => self.on_v5_nodes(node_id, from, &request_id, total, records),
+			Message::TalkRequest { request_id, .. } => {
+				// no application protocols are served over discovery
+				self.send_v5(node_id, from, Message::TalkResponse { request_id, response: Bytes::new() });
+			},
+			Message::RegisterTopic { request_id, topic, record, ticket } => self.on_register_topic(node_id, from, request_id, topic, record, &ticket),
+			Message::Ticket { request_id, ticket, wait_time } => self.on_ticket(node_id, from, &request_id, ticket, wait_time),
+			Message::RegistrationConfirmation { request_id, topic } => self.on_registration_confirmation(node_id, from, &request_id, &topic),
+			Message::TopicQuery { request_id, topic } => self.on_topic_query(node_id, from, request_id, &topic),
+			Message::Pong { .. } | Message::TalkResponse { .. } => {
+				debug!(target: "discovery", "Got unexpected v5 response from {:?}", &from);
+			},
+		}
+		updates
+	}
+
+	/// Updates the record of a node sent in a handshake, unknown nodes are pinged to be added to the table.
+	fn on_v5_record(&mut self, record: NodeRecord) -> Option<TableUpdates> {
+		let id_hash = keccak(record.id());
+		let dist = Discovery::distance(&self.id_hash, &id_hash)?;
+		if !self.node_buckets[dist].nodes.iter().any(|n| n.id_hash == id_hash) {
+			let entry = match record.endpoint() {
+				Some(endpoint) => NodeEntry { id: *record.id(), endpoint, record: Some(record) },
+				None => return None,
+			};
+			if entry.endpoint.is_valid() && self.is_allowed(&entry) {
+				self.add_node(entry);
+			}
+			return None;
+		}
+
+		let entry = {
+			let node = self.node_buckets[dist].nodes.iter_mut().find(|n| n.id_hash == id_hash).expect("node was found above; qed");
+			if node.address.record.as_ref().map_or(false, |r| r.seq() >= record.seq()) {
+				return None;
+			}
+			node.address.record = Some(record);
+			node.address.clone()
+		};
+		let mut added = HashMap::new();
+		added.insert(entry.id, entry);
+		Some(TableUpdates { added, removed: HashSet::new() })
+	}
+
+	fn on_v5_find_node(&mut self, node_id: &NodeId, from: &SocketAddr, request_id: Bytes, distances: &[u16]) {
+		trace!(target: "discovery", "Got v5 FindNode from {:?}", &from);
+		let mut records = Vec::new();
+		for distance in distances.iter().map(|d| *d as usize) {
+			if distance == 0 {
+				records.extend(self.record.clone());
+			} else if distance <= ADDRESS_BITS {
+				// nodes at log distance `d` are kept in bucket `d - 1`
+				records.extend(self.node_buckets[distance - 1].nodes.iter().filter_map(|n| n.address.record.clone()));
+			}
+			if records.len() >= BUCKET_SIZE {
+				break;
+			}
+		}
+		records.truncate(BUCKET_SIZE);
+		self.send_v5_nodes(node_id, from, request_id, &records);
+	}
+
+	fn on_v5_nodes(&mut self, node_id: &NodeId, from: &SocketAddr, request_id: &[u8], total: u64, records: Vec<NodeRecord>) {
+		// only topic queries are answered with records
+		let topic = match self.in_flight_topic_requests.get_mut(request_id) {
+			Some(request) => {
+				if request.node_id == *node_id && request.kind == TopicRequestKind::Query && request.responses < cmp::min(total, MAX_TOPIC_RESPONSES) {
+					request.responses += 1;
+					Some(request.topic)
+				} else {
+					None
+				}
+			},
+			None => None,
+		};
+		let topic = match topic {
+			Some(topic) => topic,
+			None => {
+				debug!(target: "discovery", "Got unexpected Nodes from {:?}", &from);
+				return;
+			}
+		};
+
+		trace!(target: "discovery", "Got {} advertisers of {:x} from {:?}", records.len(), topic, &from);
+		for record in records.into_iter().take(RECORDS_PER_PACKET) {
+			let entry = match record.endpoint() {
+				Some(endpoint) => NodeEntry { id: *record.id(), endpoint, record: Some(record) },
+				None => continue,
+			};
+			if entry.id == self.id || !entry.endpoint.is_valid() || !self.is_allowed(&entry) {
+				continue;
+			}
+			if let Some(nodes) = self.topic_searches.get_mut(&topic) {
+				if nodes.len() < MAX_TOPIC_NODES || nodes.contains_key(&entry.id) {
+					nodes.insert(entry.id, (entry.clone(), Instant::now()));
+				}
+			}
+			self.add_node(entry);
+		}
+	}
+
+	fn on_register_topic(&mut self, node_id: &NodeId, from: &SocketAddr, request_id: Bytes, topic: H256, record: NodeRecord, ticket: &[u8]) {
+		trace!(target: "discovery", "Got RegTopic from {:?}", &from);
+		// only the sender can register itself, from the endpoint it advertises
+		let endpoint = match record.endpoint() {
+			Some(ref endpoint) if record.id() == node_id && endpoint.udp_address() == *from => endpoint.clone(),
+			_ => {
+				debug!(target: "discovery", "Got RegTopic with mismatching record from {:?}", &from);
+				return;
+			}
+		};
+		if !self.is_allowed(&NodeEntry { id: *node_id, endpoint, record: None }) {
+			debug!(target: "discovery", "Address not allowed: {:?}", &from);
+			return;
+		}
+		// the topic table keeps the wait times, tickets only have to belong to the registrant
+		let valid_ticket = self.topic_ticket(node_id, &topic);
+		if !ticket.is_empty() && ticket != &valid_ticket[..] {
+			debug!(target: "discovery", "Got RegTopic with foreign ticket from {:?}", &from);
+			return;
+		}
+		match self.topic_table.register(&topic, record, Instant::now()) {
+			Registration::Registered => {
+				self.send_v5(node_id, from, Message::Ticket { request_id: request_id.clone(), ticket: valid_ticket, wait_time: 0 });
+				self.send_v5(node_id, from, Message::RegistrationConfirmation { request_id, topic });
+			},
+			Registration::Wait(wait) => {
+				// round up so that the ticket is valid when the registrant comes back
+				let wait_time = wait.as_secs() + (wait.subsec_nanos() > 0) as u64;
+				self.send_v5(node_id, from, Message::Ticket { request_id, ticket: valid_ticket, wait_time });
+			},
+			Registration::Rejected => {
+				debug!(target: "discovery", "Topic table full, ignoring registration for {:x}", topic);
+			},
+		}
+	}
+
+	// Ticket handed out to a registrant of the topic.
+	fn topic_ticket(&self, node_id: &NodeId, topic: &H256) -> Bytes {
+		let mut data = Vec::with_capacity(32 + 64 + 32);
+		data.extend_from_slice(&self.secret);
+		data.extend_from_slice(node_id);
+		data.extend_from_slice(topic);
+		keccak(&data).to_vec()
+	}
+
+	fn on_ticket(&mut self, node_id: &NodeId, from: &SocketAddr, request_id: &[u8], ticket: Bytes, wait_time: u64) {
+		trace!(target: "discovery", "Got Ticket from {:?}", &from);
+		let topic = match self.in_flight_topic_requests.get(request_id) {
+			Some(request) if request.node_id == *node_id && request.kind == TopicRequestKind::Register => request.topic,
+			_ => {
+				debug!(target: "discovery", "Got unexpected Ticket from {:?}", &from);
+				return;
+			}
+		};
+		// the registration was accepted, its confirmation follows
+		if wait_time == 0 {
+			return;
+		}
+		self.in_flight_topic_requests.remove(request_id);
+		if !self.advertised_topics.contains(&topic) || wait_time > TOPIC_AD_LIFETIME.as_secs() * 2 {
+			debug!(target: "discovery", "Ignoring Ticket from {:?}", &from);
+			return;
+		}
+		self.topic_tickets.insert((topic, *node_id), (Instant::now() + Duration::from_secs(wait_time), ticket));
+	}
+
+	fn on_registration_confirmation(&mut self, node_id: &NodeId, from: &SocketAddr, request_id: &[u8], topic: &H256) {
+		match self.in_flight_topic_requests.get(request_id) {
+			Some(request) if request.node_id == *node_id && request.kind == TopicRequestKind::Register && request.topic == *topic => {},
+			_ => {
+				debug!(target: "discovery", "Got unexpected RegConfirmation from {:?}", &from);
+				return;
+			}
+		}
+		self.in_flight_topic_requests.remove(request_id);
+		trace!(target: "discovery", "Registered topic {:x} at {:?}", topic, &from);
+	}
+
+	fn on_topic_query(&mut self, node_id: &NodeId, from: &SocketAddr, request_id: Bytes, topic: &H256) {
+		trace!(target: "discovery", "Got TopicQuery from {:?}", &from);
+		let records = self.topic_table.records(topic, Instant::now());
+		self.send_v5_nodes(node_id, from, request_id, &records);
+		trace!(target: "discovery", "Sent {} advertisers of {:x} to {:?}", records.len(), topic, &from);
+	}
+
+	fn send_v5(&mut self, node_id: &NodeId, address: &SocketAddr, message: Message) {
+		let packet = self.v5.encode(node_id, address, &message);
+		self.send_to(packet, *address);
+	}
+
+	fn send_v5_nodes(&mut self, node_id: &NodeId, address: &SocketAddr, request_id: Bytes, records: &[NodeRecord]) {
+		let mut chunks: Vec<&[NodeRecord]> = records.chunks(RECORDS_PER_PACKET).collect();
+		if chunks.is_empty() {
+			// tells the requester there is nothing to wait for
+			chunks.push(records);
+		}
+		let total = chunks.len() as u64;
+		for chunk in chunks {
+			self.send_v5(node_id, address, Message::Nodes { request_id: request_id.clone(), total, records: chunk.to_vec() });
+		}
+	}
+
+	/// Registers advertised topics and sends topic queries to the nodes closest to each topic.
+	fn topic_round(&mut self, now: Instant) {
+		self.redeem_topic_tickets(now);
+		if self.last_topic_round.map_or(false, |t| now.duration_since(t) < TOPIC_REFRESH) {
+			return;
+		}
+		if self.advertised_topics.is_empty() && self.topic_searches.is_empty() {
+			return;
+		}
+		if self.node_buckets.iter().all(|b| b.nodes.is_empty()) {
+			return;
+		}
+		self.last_topic_round = Some(now);
+		// advertisers which aren't found again have let their ads expire
+		for nodes in self.topic_searches.values_mut() {
+			nodes.retain(|_, &mut (_, found)| found + TOPIC_AD_LIFETIME > now);
+		}
+
+		let topics: Vec<H256> = self.advertised_topics.iter().cloned().collect();
+		for topic in topics {
+			for node in self.topic_registrars(&topic) {
+				// registrars that issued a ticket are only contacted once it is valid
+				if !self.topic_tickets.contains_key(&(topic, node.id)) {
+					self.send_topic_register(&topic, &node, Bytes::new());
+				}
+			}
+		}
+
+		let topics: Vec<H256> = self.topic_searches.keys().cloned().collect();
+		for topic in topics {
+			for node in self.topic_registrars(&topic) {
+				let request_id = self.topic_request(&node, topic, TopicRequestKind::Query);
+				self.send_v5(&node.id, &node.endpoint.udp_address(), Message::TopicQuery { request_id, topic });
+			}
+		}
+	}
+
+	// Nodes closest to the topic hash, only nodes with records are expected to know discovery v5.
+	fn topic_registrars(&self, topic: &H256) -> Vec<NodeEntry> {
+		self.nearest_node_entries_to(topic).into_iter().filter(|node| node.record.is_some()).take(ALPHA).collect()
+	}
+
+	/// Registers again at registrars whose tickets became valid.
+	fn redeem_topic_tickets(&mut self, now: Instant) {
+		let due: Vec<(H256, NodeId)> = self.topic_tickets.iter()
+			.filter(|&(_, &(valid_from, _))| valid_from <= now)
+			.map(|(key, _)| *key)
+			.collect();
+		for key in due {
+			let (_, ticket) = self.topic_tickets.remove(&key).expect("ticket was found above; qed");
+			let node = match self.bucket_entry(&key.1) {
+				Some(node) if self.advertised_topics.contains(&key.0) => node.address.clone(),
+				_ => continue,
+			};
+			self.send_topic_register(&key.0, &node, ticket);
+		}
+	}
+
+	fn send_topic_register(&mut self, topic: &H256, node: &NodeEntry, ticket: Bytes) {
+		let record = match self.record {
+			Some(ref record) => record.clone(),
+			None => return,
+		};
+		let request_id = self.topic_request(node, *topic, TopicRequestKind::Register);
+		self.send_v5(&node.id, &node.endpoint.udp_address(), Message::RegisterTopic { request_id, topic: *topic, record, ticket });
+	}
+
+	fn topic_request(&mut self, node: &NodeEntry, topic: H256, kind: TopicRequestKind) -> Bytes {
+		let request_id = random::<[u8; 8]>().to_vec();
+		self.in_flight_topic_requests.insert(request_id.clone(), TopicRequest {
+			sent_at: Instant::now(),
+			node_id: node.id,
+			topic,
+			kind,
+			responses: 0,
+		});
+		request_id
+	}
+
 	fn check_expired(&mut self, time: Instant) {
 		let mut nodes_to_expire = Vec::new();
 		self.in_flight_pings.retain(|node_id, ping_request| {
@@ -646,6 +1135,9 @@ impl<'a> Discovery<'a> {
 				true
 			}
 		});
+		self.in_flight_enr_requests.retain(|_, request| time.duration_since(request.sent_at) <= PING_TIMEOUT);
+		self.in_flight_topic_requests.retain(|_, request| request.sent_at + FIND_NODE_TIMEOUT > time);
+		self.v5.expire(time);
 		for node_id in nodes_to_expire {
 			self.expire_node_request(node_id);
 		}
@@ -678,6 +1170,7 @@ impl<'a> Discovery<'a> {
 	pub fn round(&mut self) {
 		self.check_expired(Instant::now());
 		self.update_new_nodes();
+		self.topic_round(Instant::now());
 
 		if self.discovery_round.is_some() {
 			self.discover();
@@ -770,7 +1263,7 @@ mod tests {
 		let mut nearest = Vec::new();
 		let node = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@127.0.0.1:7770").unwrap();
 		for _ in 0..1000 {
-			nearest.push( NodeEntry { id: node.id.clone(), endpoint: node.endpoint.clone() });
+			nearest.push( NodeEntry { id: node.id.clone(), endpoint: node.endpoint.clone(), record: None });
 		}
 
 		let packets = Discovery::prepare_neighbours_packets(&nearest);
@@ -789,13 +1282,13 @@ mod tests {
 		let mut discovery = Discovery::new(&key, ep.clone(), IpFilter::default());
 
 		for i in 1..(MAX_NODES_PING+1) {
-			discovery.add_node(NodeEntry { id: NodeId::random(), endpoint: ep.clone() });
+			discovery.add_node(NodeEntry { id: NodeId::random(), endpoint: ep.clone(), record: None });
 			assert_eq!(discovery.in_flight_pings.len(), i);
 			assert_eq!(discovery.send_queue.len(), i);
 			assert_eq!(discovery.adding_nodes.len(), 0);
 		}
 		for i in 1..20 {
-			discovery.add_node(NodeEntry { id: NodeId::random(), endpoint: ep.clone() });
+			discovery.add_node(NodeEntry { id: NodeId::random(), endpoint: ep.clone(), record: None });
 			assert_eq!(discovery.in_flight_pings.len(), MAX_NODES_PING);
 			assert_eq!(discovery.send_queue.len(), MAX_NODES_PING);
 			assert_eq!(discovery.adding_nodes.len(), i);
@@ -822,6 +1315,7 @@ mod tests {
 			let node = NodeEntry {
 				id: discovery_handlers[(i + 1) % 5].id,
 				endpoint: discovery_handlers[(i + 1) % 5].public_endpoint.clone(),
+				record: None,
 			};
 			discovery_handlers[i].update_node(node);
 		}
@@ -866,7 +1360,7 @@ mod tests {
 		};
 
 		let node_entries = (0..1200)
-			.map(|_| NodeEntry { id: NodeId::random(), endpoint: ep.clone() })
+			.map(|_| NodeEntry { id: NodeId::random(), endpoint: ep.clone(), record: None })
 			.collect::<Vec<_>>();
 
 		discovery.init_node_list(node_entries.clone());
@@ -886,7 +1380,7 @@ mod tests {
 		assert_eq!(total_bucket_nodes(&discovery.node_buckets), 1200 - removed);
 
 		for _ in 0..100 {
-			discovery.add_node(NodeEntry { id: NodeId::random(), endpoint: ep.clone() });
+			discovery.add_node(NodeEntry { id: NodeId::random(), endpoint: ep.clone(), record: None });
 		}
 		assert!(discovery.in_flight_pings.len() > 0);
 
@@ -952,7 +1446,7 @@ mod tests {
 		let mut discovery = Discovery::new(&key, ep.clone(), IpFilter::default());
 
 		for _ in 0..(16 + 10) {
-			let entry = BucketEntry::new(NodeEntry { id: NodeId::new(), endpoint: ep.clone() });
+			let entry = BucketEntry::new(NodeEntry { id: NodeId::new(), endpoint: ep.clone(), record: None });
 			discovery.node_buckets[0].nodes.push_back(entry);
 		}
 		let nearest = discovery.nearest_node_entries(&NodeId::new());
@@ -999,7 +1493,7 @@ mod tests {
 		];
 		let node_entries = node_ids_hex.iter()
 			.map(|node_id_hex| NodeId::from_str(node_id_hex).unwrap())
-			.map(|node_id| NodeEntry { id: node_id, endpoint: ep.clone() })
+			.map(|node_id| NodeEntry { id: node_id, endpoint: ep.clone(), record: None })
 			.collect::<Vec<_>>();
 
 		let secret_hex = "6c71d1b8930d29e6371be1081f2c909c64b46440a1716314c3c9df995cb3aed1";
@@ -1123,7 +1617,7 @@ mod tests {
 		let mut discovery1 = Discovery::new(&key1, ep1.clone(), IpFilter::default());
 		let mut discovery2 = Discovery::new(&key2, ep2.clone(), IpFilter::default());
 
-		discovery1.ping(&NodeEntry { id: discovery2.id, endpoint: ep2.clone() }).unwrap();
+		discovery1.ping(&NodeEntry { id: discovery2.id, endpoint: ep2.clone(), record: None }).unwrap();
 		let ping_data = discovery1.dequeue_send().unwrap();
 		assert!(!discovery1.any_sends_queued());
 		let data = &ping_data.payload[(32 + 65)..];
@@ -1184,4 +1678,172 @@ mod tests {
 			panic!("Expected no changes to discovery1's table for unexpected pong");
 		}
 	}
+
+	#[test]
+	fn exchanges_node_records() {
+		let key1 = Random.generate().unwrap();
+		let key2 = Random.generate().unwrap();
+		let ep1 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40350").unwrap(), udp_port: 40350 };
+		let ep2 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40351").unwrap(), udp_port: 40351 };
+		let mut discovery1 = Discovery::new(&key1, ep1.clone(), IpFilter::default());
+		let mut discovery2 = Discovery::new(&key2, ep2.clone(), IpFilter::default());
+		discovery1.set_record(NodeRecord::new(&key1, 1, &ep1).unwrap());
+		discovery2.set_record(NodeRecord::new(&key2, 3, &ep2).unwrap());
+
+		discovery1.ping(&NodeEntry { id: discovery2.id, endpoint: ep2.clone(), record: None }).unwrap();
+		let mut updates2 = Vec::new();
+		while discovery1.any_sends_queued() || discovery2.any_sends_queued() {
+			while let Some(datagram) = discovery1.dequeue_send() {
+				updates2.extend(discovery2.on_packet(&datagram.payload, ep1.address.clone()).unwrap());
+			}
+			while let Some(datagram) = discovery2.dequeue_send() {
+				discovery1.on_packet(&datagram.payload, ep2.address.clone()).unwrap();
+			}
+		}
+
+		// `discovery2` asked for the record once `discovery1` was verified.
+		let record = updates2.last().and_then(|u| u.added.get(&discovery1.id)).and_then(|n| n.record.clone());
+		assert_eq!(record.map(|r| r.seq()), Some(1));
+		assert_eq!(discovery2.bucket_entry(&discovery1.id).unwrap().address.record, discovery1.record);
+
+		// Request of `discovery1` arrived before its endpoint was verified and was ignored.
+		assert!(discovery1.in_flight_enr_requests.contains_key(&discovery2.id));
+		assert!(discovery1.bucket_entry(&discovery2.id).unwrap().address.record.is_none());
+		discovery1.check_expired(Instant::now() + PING_TIMEOUT + Duration::from_millis(1));
+		assert!(discovery1.in_flight_enr_requests.is_empty());
+	}
+
+	// Delivers queued packets between the nodes until there are none left.
+	fn deliver(nodes: &mut [&mut Discovery]) {
+		loop {
+			let mut datagrams = Vec::new();
+			for node in nodes.iter_mut() {
+				let from = node.public_endpoint.address;
+				while let Some(datagram) = node.dequeue_send() {
+					datagrams.push((from, datagram));
+				}
+			}
+			if datagrams.is_empty() {
+				break;
+			}
+			for (from, datagram) in datagrams {
+				if let Some(node) = nodes.iter_mut().find(|node| node.public_endpoint.address == datagram.address) {
+					node.on_packet(&datagram.payload, from).ok();
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn advertises_topics() {
+		let new_discovery = |port: u16| {
+			let key = Random.generate().unwrap();
+			let ep = NodeEndpoint { address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), port), udp_port: port };
+			let mut discovery = Discovery::new(&key, ep.clone(), IpFilter::default());
+			discovery.set_record(NodeRecord::new(&key, 1, &ep).unwrap());
+			discovery
+		};
+		let mut registrar = new_discovery(40360);
+		let mut advertiser = new_discovery(40361);
+		let mut searcher = new_discovery(40362);
+		let registrar_entry = NodeEntry { id: registrar.id, endpoint: registrar.public_endpoint.clone(), record: registrar.record.clone() };
+		advertiser.init_node_list(vec![registrar_entry.clone()]);
+		searcher.init_node_list(vec![registrar_entry]);
+		let topic = topic_hash("eth");
+
+		// The registration is sent in the v5 handshake and confirmed.
+		advertiser.advertise_topic("eth");
+		advertiser.topic_round(Instant::now());
+		let register = advertiser.dequeue_send().unwrap();
+		assert_eq!(register.address, registrar.public_endpoint.address);
+		advertiser.requeue_send(register);
+		deliver(&mut [&mut registrar, &mut advertiser]);
+		assert_eq!(registrar.topic_table.records(&topic, Instant::now()), vec![advertiser.record.clone().unwrap()]);
+		assert!(advertiser.in_flight_topic_requests.is_empty());
+		// the advertiser introduced itself in the handshake and was verified with a ping
+		assert_eq!(registrar.bucket_entry(&advertiser.id).unwrap().address.record, advertiser.record);
+
+		// Registering again while the ad is live yields a ticket valid once it expires.
+		advertiser.last_topic_round = None;
+		advertiser.topic_round(Instant::now());
+		deliver(&mut [&mut registrar, &mut advertiser]);
+		let valid_from = advertiser.topic_tickets[&(topic, registrar.id)].0;
+		assert!(valid_from > Instant::now() + TOPIC_AD_LIFETIME - Duration::from_secs(10));
+		advertiser.topic_round(valid_from - Duration::from_secs(1));
+		assert!(!advertiser.any_sends_queued());
+		advertiser.topic_round(valid_from);
+		assert!(advertiser.any_sends_queued());
+		assert!(advertiser.topic_tickets.is_empty());
+		// the registrar accepts its own ticket and has the advertiser wait again
+		deliver(&mut [&mut registrar, &mut advertiser]);
+		assert!(advertiser.topic_tickets.contains_key(&(topic, registrar.id)));
+
+		searcher.search_topic("eth");
+		searcher.topic_round(Instant::now());
+		deliver(&mut [&mut registrar, &mut advertiser, &mut searcher]);
+		let found = searcher.topic_nodes("eth");
+		assert_eq!(found.len(), 1);
+		assert_eq!(found[0].id, advertiser.id);
+		assert_eq!(found[0].endpoint, advertiser.public_endpoint);
+		assert_eq!(found[0].record, advertiser.record);
+		// the advertiser was pinged and added to the routing table
+		assert!(searcher.bucket_entry(&advertiser.id).is_some());
+
+		// Next topic round only after the refresh interval.
+		searcher.topic_round(Instant::now());
+		assert!(!searcher.any_sends_queued());
+
+		// Records are split in messages.
+		for _ in 0..RECORDS_PER_PACKET {
+			let key = Random.generate().unwrap();
+			let record = NodeRecord::new(&key, 1, &advertiser.public_endpoint).unwrap();
+			registrar.topic_table.register(&topic, record, Instant::now());
+		}
+		searcher.last_topic_round = None;
+		searcher.topic_round(Instant::now());
+		deliver(&mut [&mut registrar, &mut advertiser, &mut searcher]);
+		assert_eq!(searcher.topic_nodes("eth").len(), RECORDS_PER_PACKET + 1);
+
+		// Found advertisers are dropped once their ads would have expired.
+		searcher.topic_round(Instant::now() + TOPIC_AD_LIFETIME);
+		assert!(searcher.topic_nodes("eth").is_empty());
+	}
+
+	#[test]
+	fn answers_v5_find_node() {
+		let key1 = Random.generate().unwrap();
+		let key2 = Random.generate().unwrap();
+		let ep1 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40363").unwrap(), udp_port: 40363 };
+		let ep2 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40364").unwrap(), udp_port: 40364 };
+		let mut discovery = Discovery::new(&key1, ep1.clone(), IpFilter::default());
+		discovery.set_record(NodeRecord::new(&key1, 1, &ep1).unwrap());
+		let record2 = NodeRecord::new(&key2, 1, &ep2).unwrap();
+		let mut sessions = Sessions::new(&key2);
+
+		// node records at distance 0 and at the distance of a known node
+		let key3 = Random.generate().unwrap();
+		let ep3 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40365").unwrap(), udp_port: 40365 };
+		let record3 = NodeRecord::new(&key3, 1, &ep3).unwrap();
+		discovery.init_node_list(vec![NodeEntry { id: *key3.public(), endpoint: ep3, record: Some(record3.clone()) }]);
+		let distance = Discovery::distance(&discovery.id_hash, &keccak(key3.public())).unwrap() as u16 + 1;
+		let find_node = Message::FindNode { request_id: vec![1], distances: vec![0, distance] };
+
+		let packet = sessions.encode(&discovery.id, &ep1.address, &find_node);
+		discovery.on_packet(&packet, ep2.address).unwrap();
+		let whoareyou = discovery.dequeue_send().unwrap();
+		let handshake = match sessions.decode(&whoareyou.payload, &ep1.address, Some(&record2)).unwrap() {
+			Incoming::Reply(handshake) => handshake,
+			_ => panic!("Expected handshake"),
+		};
+		discovery.on_packet(&handshake, ep2.address).unwrap();
+		// the sender of the handshake is pinged to be added to the table
+		let ping = discovery.dequeue_send().unwrap();
+		assert_eq!(ping.payload[32 + 65], PACKET_PING);
+		let nodes = discovery.dequeue_send().unwrap();
+		assert_eq!(nodes.address, ep2.address);
+		assert_eq!(
+			sessions.decode(&nodes.payload, &ep1.address, Some(&record2)).unwrap(),
+			Incoming::Message(discovery.id, None, Message::Nodes { request_id: vec![1], total: 1, records: vec![discovery.record.clone().unwrap(), record3] })
+		);
+	}
 }
@@ -0,0 +1,729 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Discovery v5 wire protocol.
+//!
+//! Packets start with a random masking IV, v4 packets start with their hash, so both are
+//! received on the same UDP socket. Messages are encrypted with session keys agreed on in a
+//! WHOAREYOU handshake: the first packet to a node carries random data, the node answers with
+//! a challenge and the message is sent again in a handshake packet proving the sender's identity.
+//! Node ids are keccak hashes of the public keys, the same as the routing table of v4 uses.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, Instant};
+use ethereum_types::H256;
+use ethkey::{KeyPair, Secret, Signature, Random, Generator, sign, recover};
+use ethkey::math::{public_mul_secret, public_from_compressed};
+use hash::keccak;
+use parity_bytes::Bytes;
+use rand::random;
+use rcrypto::aead::{AeadEncryptor, AeadDecryptor};
+use rcrypto::aes::{self, KeySize};
+use rcrypto::aes_gcm::AesGcm;
+use rcrypto::digest::Digest;
+use rcrypto::hkdf::{hkdf_extract, hkdf_expand};
+use rcrypto::sha2::Sha256;
+use rcrypto::symmetriccipher::SynchronousStreamCipher;
+use rlp::{self, Rlp, RlpStream, DecoderError};
+use network::{Error, ErrorKind};
+use enr::{self, NodeRecord};
+use node_table::NodeId;
+
+const PROTOCOL_ID: &[u8] = b"discv5";
+const PROTOCOL_VERSION: u16 = 1;
+
+const FLAG_MESSAGE: u8 = 0;
+const FLAG_WHOAREYOU: u8 = 1;
+const FLAG_HANDSHAKE: u8 = 2;
+
+const MESSAGE_PING: u8 = 1;
+const MESSAGE_PONG: u8 = 2;
+const MESSAGE_FIND_NODE: u8 = 3;
+const MESSAGE_NODES: u8 = 4;
+const MESSAGE_TALK_REQUEST: u8 = 5;
+const MESSAGE_TALK_RESPONSE: u8 = 6;
+const MESSAGE_REGISTER_TOPIC: u8 = 7;
+const MESSAGE_TICKET: u8 = 8;
+const MESSAGE_REGISTRATION_CONFIRMATION: u8 = 9;
+const MESSAGE_TOPIC_QUERY: u8 = 10;
+
+const MASKING_IV_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+// protocol id, version, flag, nonce and authdata size
+const STATIC_HEADER_SIZE: usize = 6 + 2 + 1 + NONCE_SIZE + 2;
+const ID_NONCE_SIZE: usize = 16;
+const WHOAREYOU_AUTHDATA_SIZE: usize = ID_NONCE_SIZE + 8;
+// source id, signature and key sizes, signature and compressed ephemeral key
+const HANDSHAKE_AUTHDATA_SIZE: usize = 32 + 2 + 64 + 33;
+const TAG_SIZE: usize = 16;
+const MIN_PACKET_SIZE: usize = MASKING_IV_SIZE + STATIC_HEADER_SIZE + WHOAREYOU_AUTHDATA_SIZE;
+const MAX_PACKET_SIZE: usize = 1280;
+const MAX_REQUEST_ID_SIZE: usize = 8;
+// size of the random data sent in place of a message to nodes without a session
+const RANDOM_MESSAGE_SIZE: usize = 20;
+
+const ID_SIGNATURE_TEXT: &[u8] = b"discovery v5 identity proof";
+const KEY_AGREEMENT_TEXT: &[u8] = b"discovery v5 key agreement";
+
+/// How long a packet waits for a challenge and a challenge waits for the handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(1);
+const MAX_SESSIONS: usize = 1024;
+// Max number of packets waiting for a challenge and of challenges waiting for a handshake
+const MAX_PENDING: usize = 1024;
+
+/// Node id of discovery v5.
+pub fn node_id(public: &NodeId) -> H256 {
+	keccak(public)
+}
+
+/// Messages of the protocol. Request ids are at most 8 bytes long and echoed in responses.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+	/// Liveness check: `[request-id, enr-seq]`.
+	Ping { request_id: Bytes, enr_seq: u64 },
+	/// Answer to `Ping`: `[request-id, enr-seq, recipient-ip, recipient-port]`.
+	Pong { request_id: Bytes, enr_seq: u64, address: SocketAddr },
+	/// Ask for records at the given log distances from the recipient: `[request-id, [distance, ...]]`.
+	FindNode { request_id: Bytes, distances: Vec<u16> },
+	/// Records sent in `total` packets: `[request-id, total, [record, ...]]`.
+	Nodes { request_id: Bytes, total: u64, records: Vec<NodeRecord> },
+	/// Application request: `[request-id, protocol, request]`.
+	TalkRequest { request_id: Bytes, protocol: Bytes, request: Bytes },
+	/// Answer to `TalkRequest`, empty for unknown protocols: `[request-id, response]`.
+	TalkResponse { request_id: Bytes, response: Bytes },
+	/// Advertise the sender for a topic: `[request-id, topic, record, ticket]`.
+	RegisterTopic { request_id: Bytes, topic: H256, record: NodeRecord, ticket: Bytes },
+	/// Ticket to present when registering again after `wait_time` seconds: `[request-id, ticket, wait-time]`.
+	Ticket { request_id: Bytes, ticket: Bytes, wait_time: u64 },
+	/// The registration was accepted: `[request-id, topic]`.
+	RegistrationConfirmation { request_id: Bytes, topic: H256 },
+	/// Ask for advertisers of a topic, answered with `Nodes`: `[request-id, topic]`.
+	TopicQuery { request_id: Bytes, topic: H256 },
+}
+
+impl Message {
+	fn encode(&self) -> Bytes {
+		let (message_type, rlp) = match *self {
+			Message::Ping { ref request_id, enr_seq } => {
+				let mut rlp = RlpStream::new_list(2);
+				rlp.append(request_id).append(&enr_seq);
+				(MESSAGE_PING, rlp)
+			},
+			Message::Pong { ref request_id, enr_seq, ref address } => {
+				let ip = match address.ip() {
+					IpAddr::V4(ip) => ip.octets().to_vec(),
+					IpAddr::V6(ip) => ip.octets().to_vec(),
+				};
+				let mut rlp = RlpStream::new_list(4);
+				rlp.append(request_id).append(&enr_seq).append(&ip).append(&address.port());
+				(MESSAGE_PONG, rlp)
+			},
+			Message::FindNode { ref request_id, ref distances } => {
+				let mut rlp = RlpStream::new_list(2);
+				rlp.append(request_id).append_list(distances);
+				(MESSAGE_FIND_NODE, rlp)
+			},
+			Message::Nodes { ref request_id, total, ref records } => {
+				let mut rlp = RlpStream::new_list(3);
+				rlp.append(request_id).append(&total).append_list(records);
+				(MESSAGE_NODES, rlp)
+			},
+			Message::TalkRequest { ref request_id, ref protocol, ref request } => {
+				let mut rlp = RlpStream::new_list(3);
+				rlp.append(request_id).append(protocol).append(request);
+				(MESSAGE_TALK_REQUEST, rlp)
+			},
+			Message::TalkResponse { ref request_id, ref response } => {
+				let mut rlp = RlpStream::new_list(2);
+				rlp.append(request_id).append(response);
+				(MESSAGE_TALK_RESPONSE, rlp)
+			},
+			Message::RegisterTopic { ref request_id, ref topic, ref record, ref ticket } => {
+				let mut rlp = RlpStream::new_list(4);
+				rlp.append(request_id).append(topic).append(record).append(ticket);
+				(MESSAGE_REGISTER_TOPIC, rlp)
+			},
+			Message::Ticket { ref request_id, ref ticket, wait_time } => {
+				let mut rlp = RlpStream::new_list(3);
+				rlp.append(request_id).append(ticket).append(&wait_time);
+				(MESSAGE_TICKET, rlp)
+			},
+			Message::RegistrationConfirmation { ref request_id, ref topic } => {
+				let mut rlp = RlpStream::new_list(2);
+				rlp.append(request_id).append(topic);
+				(MESSAGE_REGISTRATION_CONFIRMATION, rlp)
+			},
+			Message::TopicQuery { ref request_id, ref topic } => {
+				let mut rlp = RlpStream::new_list(2);
+				rlp.append(request_id).append(topic);
+				(MESSAGE_TOPIC_QUERY, rlp)
+			},
+		};
+		let mut message = vec![message_type];
+		message.extend_from_slice(&rlp.drain());
+		message
+	}
+
+	fn decode(message: &[u8]) -> Result<Message, DecoderError> {
+		if message.is_empty() {
+			return Err(DecoderError::RlpIsTooShort);
+		}
+		let rlp = Rlp::new(&message[1..]);
+		let request_id: Bytes = rlp.val_at(0)?;
+		if request_id.len() > MAX_REQUEST_ID_SIZE {
+			return Err(DecoderError::Custom("Request id is too long"));
+		}
+		let message = match message[0] {
+			MESSAGE_PING => Message::Ping { request_id, enr_seq: rlp.val_at(1)? },
+			MESSAGE_PONG => {
+				let ip: Bytes = rlp.val_at(2)?;
+				let ip = match ip.len() {
+					4 => IpAddr::V4(Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3])),
+					16 => {
+						let mut octets = [0u8; 16];
+						octets.copy_from_slice(&ip);
+						IpAddr::V6(Ipv6Addr::from(octets))
+					},
+					_ => return Err(DecoderError::Custom("Invalid recipient ip")),
+				};
+				Message::Pong { request_id, enr_seq: rlp.val_at(1)?, address: SocketAddr::new(ip, rlp.val_at(3)?) }
+			},
+			MESSAGE_FIND_NODE => Message::FindNode { request_id, distances: rlp.list_at(1)? },
+			MESSAGE_NODES => Message::Nodes { request_id, total: rlp.val_at(1)?, records: rlp.list_at(2)? },
+			MESSAGE_TALK_REQUEST => Message::TalkRequest { request_id, protocol: rlp.val_at(1)?, request: rlp.val_at(2)? },
+			MESSAGE_TALK_RESPONSE => Message::TalkResponse { request_id, response: rlp.val_at(1)? },
+			MESSAGE_REGISTER_TOPIC => Message::RegisterTopic {
+				request_id,
+				topic: rlp.val_at(1)?,
+				record: rlp.val_at(2)?,
+				ticket: rlp.val_at(3)?,
+			},
+			MESSAGE_TICKET => Message::Ticket { request_id, ticket: rlp.val_at(1)?, wait_time: rlp.val_at(2)? },
+			MESSAGE_REGISTRATION_CONFIRMATION => Message::RegistrationConfirmation { request_id, topic: rlp.val_at(1)? },
+			MESSAGE_TOPIC_QUERY => Message::TopicQuery { request_id, topic: rlp.val_at(1)? },
+			_ => return Err(DecoderError::Custom("Unknown message type")),
+		};
+		Ok(message)
+	}
+}
+
+/// Outcome of a received packet.
+#[derive(Debug, PartialEq)]
+pub enum Incoming {
+	/// Message of an authenticated node. Its record is given if it was sent in the handshake.
+	Message(NodeId, Option<NodeRecord>, Message),
+	/// Packet to send back to the sender.
+	Reply(Bytes),
+	/// Nothing to do.
+	Nothing,
+}
+
+struct Session {
+	node: NodeId,
+	write_key: [u8; 16],
+	read_key: [u8; 16],
+	last_used: Instant,
+}
+
+// Packet which may be challenged by the recipient.
+struct SentPacket {
+	node: NodeId,
+	address: SocketAddr,
+	// encoded message, sent again in the handshake
+	message: Bytes,
+	sent_at: Instant,
+}
+
+struct Challenge {
+	// masking IV, header and authdata of the WHOAREYOU packet
+	data: Bytes,
+	sent_at: Instant,
+}
+
+struct Packet<'a> {
+	flag: u8,
+	nonce: [u8; NONCE_SIZE],
+	authdata: Bytes,
+	// masking IV and unmasked header, authenticated with the message
+	header: Bytes,
+	message: &'a [u8],
+}
+
+/// Sessions with other nodes, keyed by their node id and endpoint.
+pub struct Sessions {
+	keys: KeyPair,
+	id: H256,
+	sessions: HashMap<(H256, SocketAddr), Session>,
+	sent: HashMap<[u8; NONCE_SIZE], SentPacket>,
+	challenges: HashMap<(H256, SocketAddr), Challenge>,
+}
+
+impl Sessions {
+	pub fn new(keys: &KeyPair) -> Sessions {
+		Sessions {
+			keys: keys.clone(),
+			id: node_id(keys.public()),
+			sessions: HashMap::new(),
+			sent: HashMap::new(),
+			challenges: HashMap::new(),
+		}
+	}
+
+	/// Encode a message for the node. Without a session, random data is sent instead and
+	/// the message follows in the handshake once the node challenges it.
+	pub fn encode(&mut self, node: &NodeId, address: &SocketAddr, message: &Message) -> Bytes {
+		let remote_id = node_id(node);
+		let nonce: [u8; NONCE_SIZE] = random();
+		let message = message.encode();
+		let now = Instant::now();
+		let packet = match self.sessions.get_mut(&(remote_id, *address)) {
+			Some(session) => {
+				session.last_used = now;
+				assemble_packet(&remote_id, FLAG_MESSAGE, &nonce, &self.id, &message, Some(&session.write_key)).0
+			},
+			None => {
+				let random_data: [u8; RANDOM_MESSAGE_SIZE] = random();
+				assemble_packet(&remote_id, FLAG_MESSAGE, &nonce, &self.id, &random_data, None).0
+			},
+		};
+		if self.sent.len() < MAX_PENDING {
+			self.sent.insert(nonce, SentPacket { node: *node, address: *address, message, sent_at: now });
+		}
+		packet
+	}
+
+	/// Handle a packet received from `from`. `record` is the local node record, sent in
+	/// handshakes to nodes which don't know it.
+	pub fn decode(&mut self, packet: &[u8], from: &SocketAddr, record: Option<&NodeRecord>) -> Result<Incoming, Error> {
+		let packet = open_packet(&self.id, packet)?;
+		match packet.flag {
+			FLAG_MESSAGE => self.on_message(packet, from),
+			FLAG_WHOAREYOU => self.on_whoareyou(packet, from, record),
+			_ => self.on_handshake(packet, from),
+		}
+	}
+
+	/// Drop packets and challenges not answered in time.
+	pub fn expire(&mut self, now: Instant) {
+		self.sent.retain(|_, packet| packet.sent_at + HANDSHAKE_TIMEOUT > now);
+		self.challenges.retain(|_, challenge| challenge.sent_at + HANDSHAKE_TIMEOUT > now);
+	}
+
+	fn on_message(&mut self, packet: Packet, from: &SocketAddr) -> Result<Incoming, Error> {
+		if packet.authdata.len() != 32 {
+			return Err(ErrorKind::BadProtocol.into());
+		}
+		let remote_id = H256::from_slice(&packet.authdata);
+		let now = Instant::now();
+		let decrypted = match self.sessions.get_mut(&(remote_id, *from)) {
+			Some(session) => decrypt_message(&session.read_key, &packet.nonce, packet.message, &packet.header).map(|message| {
+				session.last_used = now;
+				(session.node, message)
+			}),
+			None => None,
+		};
+		if let Some((node, message)) = decrypted {
+			return Ok(Incoming::Message(node, None, Message::decode(&message)?));
+		}
+
+		// unknown sender or a session it has lost, one challenge at a time
+		let key = (remote_id, *from);
+		if self.challenges.contains_key(&key) || self.challenges.len() >= MAX_PENDING {
+			trace!(target: "discovery", "Not challenging {:?} again", from);
+			return Ok(Incoming::Nothing);
+		}
+		let id_nonce: [u8; ID_NONCE_SIZE] = random();
+		let mut authdata = id_nonce.to_vec();
+		// zero sequence number: the record of the sender is always asked for, it holds its public key
+		authdata.extend_from_slice(&[0u8; 8]);
+		let (whoareyou, data) = assemble_packet(&remote_id, FLAG_WHOAREYOU, &packet.nonce, &authdata, &[], None);
+		self.challenges.insert(key, Challenge { data, sent_at: now });
+		Ok(Incoming::Reply(whoareyou))
+	}
+
+	fn on_whoareyou(&mut self, packet: Packet, from: &SocketAddr, record: Option<&NodeRecord>) -> Result<Incoming, Error> {
+		if packet.authdata.len() != WHOAREYOU_AUTHDATA_SIZE {
+			return Err(ErrorKind::BadProtocol.into());
+		}
+		// only packets sent to the node can be challenged
+		match self.sent.get(&packet.nonce) {
+			Some(sent) if sent.address == *from => {},
+			_ => {
+				debug!(target: "discovery", "Got unexpected WHOAREYOU from {:?}", from);
+				return Ok(Incoming::Nothing);
+			}
+		}
+		let sent = self.sent.remove(&packet.nonce).expect("packet was found above; qed");
+		let enr_seq = packet.authdata[ID_NONCE_SIZE..].iter().fold(0u64, |seq, b| (seq << 8) | *b as u64);
+		let remote_id = node_id(&sent.node);
+		let challenge_data = &packet.header;
+
+		let ephemeral = Random.generate()?;
+		let ephemeral_public = enr::compress(ephemeral.public());
+		let (initiator_key, recipient_key) = derive_keys(ephemeral.secret(), &sent.node, &self.id, &remote_id, challenge_data)?;
+		let signature = sign(self.keys.secret(), &id_signature_hash(challenge_data, &ephemeral_public, &remote_id))?;
+
+		let mut authdata = Vec::with_capacity(HANDSHAKE_AUTHDATA_SIZE + enr::MAX_RECORD_SIZE);
+		authdata.extend_from_slice(&self.id);
+		authdata.extend_from_slice(&[64, 33]);
+		authdata.extend_from_slice(&signature[0..64]);
+		authdata.extend_from_slice(&ephemeral_public);
+		if let Some(record) = record {
+			if record.seq() > enr_seq {
+				authdata.extend_from_slice(&rlp::encode(record));
+			}
+		}
+
+		let nonce: [u8; NONCE_SIZE] = random();
+		let (handshake, _) = assemble_packet(&remote_id, FLAG_HANDSHAKE, &nonce, &authdata, &sent.message, Some(&initiator_key));
+		if handshake.len() > MAX_PACKET_SIZE {
+			debug!(target: "discovery", "Message to {:?} doesn't fit in a handshake", from);
+			return Ok(Incoming::Nothing);
+		}
+		let now = Instant::now();
+		self.insert_session(remote_id, *from, Session { node: sent.node, write_key: initiator_key, read_key: recipient_key, last_used: now });
+		Ok(Incoming::Reply(handshake))
+	}
+
+	fn on_handshake(&mut self, packet: Packet, from: &SocketAddr) -> Result<Incoming, Error> {
+		let authdata = &packet.authdata;
+		if authdata.len() < HANDSHAKE_AUTHDATA_SIZE || authdata[32] != 64 || authdata[33] != 33 {
+			return Err(ErrorKind::BadProtocol.into());
+		}
+		let remote_id = H256::from_slice(&authdata[0..32]);
+		let signature = &authdata[34..98];
+		let ephemeral_public = &authdata[98..HANDSHAKE_AUTHDATA_SIZE];
+
+		let challenge = match self.challenges.remove(&(remote_id, *from)) {
+			Some(challenge) => challenge,
+			None => {
+				debug!(target: "discovery", "Got unexpected handshake from {:?}", from);
+				return Ok(Incoming::Nothing);
+			}
+		};
+		// the challenge always asks for the record
+		let record: NodeRecord = rlp::decode(&authdata[HANDSHAKE_AUTHDATA_SIZE..])?;
+		if node_id(record.id()) != remote_id {
+			return Err(ErrorKind::InvalidNodeRecord.into());
+		}
+		let node = *record.id();
+		if !verify_id_signature(&node, signature, &id_signature_hash(&challenge.data, ephemeral_public, &self.id)) {
+			return Err(ErrorKind::Auth.into());
+		}
+
+		let ephemeral = public_from_compressed(ephemeral_public)?;
+		let (initiator_key, recipient_key) = derive_keys(self.keys.secret(), &ephemeral, &remote_id, &self.id, &challenge.data)?;
+		let message = decrypt_message(&initiator_key, &packet.nonce, packet.message, &packet.header).ok_or(ErrorKind::Auth)?;
+		let message = Message::decode(&message)?;
+		self.insert_session(remote_id, *from, Session { node, write_key: recipient_key, read_key: initiator_key, last_used: Instant::now() });
+		Ok(Incoming::Message(node, Some(record), message))
+	}
+
+	fn insert_session(&mut self, remote_id: H256, address: SocketAddr, session: Session) {
+		let key = (remote_id, address);
+		if self.sessions.len() >= MAX_SESSIONS && !self.sessions.contains_key(&key) {
+			let oldest = self.sessions.iter().min_by_key(|&(_, session)| session.last_used).map(|(key, _)| *key);
+			if let Some(oldest) = oldest {
+				self.sessions.remove(&oldest);
+			}
+		}
+		self.sessions.insert(key, session);
+	}
+}
+
+// Assemble a packet, encrypting the message if a key is given. Returns the packet and
+// its masking IV followed by the unmasked header.
+fn assemble_packet(dest_id: &H256, flag: u8, nonce: &[u8; NONCE_SIZE], authdata: &[u8], message: &[u8], key: Option<&[u8; 16]>) -> (Bytes, Bytes) {
+	let masking_iv: [u8; MASKING_IV_SIZE] = random();
+	let mut header = Vec::with_capacity(MASKING_IV_SIZE + STATIC_HEADER_SIZE + authdata.len());
+	header.extend_from_slice(&masking_iv);
+	header.extend_from_slice(PROTOCOL_ID);
+	header.extend_from_slice(&[(PROTOCOL_VERSION >> 8) as u8, PROTOCOL_VERSION as u8, flag]);
+	header.extend_from_slice(nonce);
+	header.extend_from_slice(&[(authdata.len() >> 8) as u8, authdata.len() as u8]);
+	header.extend_from_slice(authdata);
+
+	let mut packet = vec![0u8; header.len()];
+	packet[..MASKING_IV_SIZE].copy_from_slice(&masking_iv);
+	aes_ctr(&dest_id[..16], &masking_iv, &header[MASKING_IV_SIZE..], &mut packet[MASKING_IV_SIZE..]);
+	match key {
+		Some(key) => packet.extend_from_slice(&encrypt_message(key, nonce, message, &header)),
+		None => packet.extend_from_slice(message),
+	}
+	(packet, header)
+}
+
+// Unmask the header of a packet sent to `local_id`.
+fn open_packet<'a>(local_id: &H256, packet: &'a [u8]) -> Result<Packet<'a>, Error> {
+	if packet.len() < MIN_PACKET_SIZE || packet.len() > MAX_PACKET_SIZE {
+		return Err(ErrorKind::BadProtocol.into());
+	}
+	let masking_iv = &packet[..MASKING_IV_SIZE];
+	let mut static_header = [0u8; STATIC_HEADER_SIZE];
+	aes_ctr(&local_id[..16], masking_iv, &packet[MASKING_IV_SIZE..MASKING_IV_SIZE + STATIC_HEADER_SIZE], &mut static_header);
+	let version = ((static_header[6] as u16) << 8) | static_header[7] as u16;
+	let flag = static_header[8];
+	if &static_header[0..6] != PROTOCOL_ID || version != PROTOCOL_VERSION || flag > FLAG_HANDSHAKE {
+		return Err(ErrorKind::BadProtocol.into());
+	}
+	let authdata_size = ((static_header[STATIC_HEADER_SIZE - 2] as usize) << 8) | static_header[STATIC_HEADER_SIZE - 1] as usize;
+	let header_end = MASKING_IV_SIZE + STATIC_HEADER_SIZE + authdata_size;
+	if header_end > packet.len() {
+		return Err(ErrorKind::BadProtocol.into());
+	}
+
+	// the stream is unmasked again from the start to get the authdata
+	let mut header = masking_iv.to_vec();
+	header.resize(header_end, 0);
+	aes_ctr(&local_id[..16], masking_iv, &packet[MASKING_IV_SIZE..header_end], &mut header[MASKING_IV_SIZE..]);
+	let mut nonce = [0u8; NONCE_SIZE];
+	nonce.copy_from_slice(&static_header[9..9 + NONCE_SIZE]);
+	Ok(Packet {
+		flag,
+		nonce,
+		authdata: header[MASKING_IV_SIZE + STATIC_HEADER_SIZE..].to_vec(),
+		header,
+		message: &packet[header_end..],
+	})
+}
+
+fn aes_ctr(key: &[u8], iv: &[u8], input: &[u8], output: &mut [u8]) {
+	aes::ctr(KeySize::KeySize128, key, iv).process(input, output);
+}
+
+fn encrypt_message(key: &[u8; 16], nonce: &[u8; NONCE_SIZE], message: &[u8], header: &[u8]) -> Bytes {
+	let mut encrypted = vec![0u8; message.len() + TAG_SIZE];
+	{
+		let (ciphertext, tag) = encrypted.split_at_mut(message.len());
+		AesGcm::new(KeySize::KeySize128, key, nonce, header).encrypt(message, ciphertext, tag);
+	}
+	encrypted
+}
+
+fn decrypt_message(key: &[u8; 16], nonce: &[u8; NONCE_SIZE], encrypted: &[u8], header: &[u8]) -> Option<Bytes> {
+	if encrypted.len() < TAG_SIZE {
+		return None;
+	}
+	let (ciphertext, tag) = encrypted.split_at(encrypted.len() - TAG_SIZE);
+	let mut message = vec![0u8; ciphertext.len()];
+	if AesGcm::new(KeySize::KeySize128, key, nonce, header).decrypt(ciphertext, &mut message, tag) {
+		Some(message)
+	} else {
+		None
+	}
+}
+
+// Session keys of the initiator and the recipient of a handshake, agreed on with ECDH
+// between the ephemeral key of the initiator and the static key of the recipient.
+fn derive_keys(secret: &Secret, public: &NodeId, initiator: &H256, recipient: &H256, challenge_data: &[u8]) -> Result<([u8; 16], [u8; 16]), Error> {
+	let mut shared = *public;
+	public_mul_secret(&mut shared, secret)?;
+	let mut prk = [0u8; 32];
+	hkdf_extract(Sha256::new(), challenge_data, &enr::compress(&shared), &mut prk);
+
+	let mut info = KEY_AGREEMENT_TEXT.to_vec();
+	info.extend_from_slice(initiator);
+	info.extend_from_slice(recipient);
+	let mut key_data = [0u8; 32];
+	hkdf_expand(Sha256::new(), &prk, &info, &mut key_data);
+
+	let (mut initiator_key, mut recipient_key) = ([0u8; 16], [0u8; 16]);
+	initiator_key.copy_from_slice(&key_data[..16]);
+	recipient_key.copy_from_slice(&key_data[16..]);
+	Ok((initiator_key, recipient_key))
+}
+
+// Hash signed by the initiator of a handshake to prove it holds the key of its node id.
+fn id_signature_hash(challenge_data: &[u8], ephemeral_public: &[u8], recipient: &H256) -> H256 {
+	let mut hasher = Sha256::new();
+	hasher.input(ID_SIGNATURE_TEXT);
+	hasher.input(challenge_data);
+	hasher.input(ephemeral_public);
+	hasher.input(recipient);
+	let mut hash = H256::default();
+	hasher.result(&mut hash);
+	hash
+}
+
+fn verify_id_signature(node: &NodeId, signature: &[u8], hash: &H256) -> bool {
+	// the signature is sent without recovery id, try both candidates
+	let r = H256::from_slice(&signature[0..32]);
+	let s = H256::from_slice(&signature[32..64]);
+	(0..2).filter_map(|v| recover(&Signature::from_rsv(&r, &s, v), hash).ok()).any(|public| public == *node)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+	use rustc_hex::FromHex;
+	use node_table::NodeEndpoint;
+	use super::*;
+
+	fn sessions(port: u16) -> (Sessions, NodeRecord, SocketAddr) {
+		let keys = Random.generate().unwrap();
+		let address = SocketAddr::from(([127, 0, 0, 1], port));
+		let record = NodeRecord::new(&keys, 1, &NodeEndpoint { address, udp_port: port }).unwrap();
+		(Sessions::new(&keys), record, address)
+	}
+
+	#[test]
+	fn messages_roundtrip() {
+		let keys = Random.generate().unwrap();
+		let endpoint = NodeEndpoint { address: SocketAddr::from(([127, 0, 0, 1], 30303)), udp_port: 30303 };
+		let record = NodeRecord::new(&keys, 1, &endpoint).unwrap();
+		let request_id = vec![1, 2, 3];
+		let messages = vec![
+			Message::Ping { request_id: request_id.clone(), enr_seq: 2 },
+			Message::Pong { request_id: request_id.clone(), enr_seq: 2, address: SocketAddr::from_str("[::1]:30303").unwrap() },
+			Message::FindNode { request_id: request_id.clone(), distances: vec![0, 255, 256] },
+			Message::Nodes { request_id: request_id.clone(), total: 1, records: vec![record.clone()] },
+			Message::TalkRequest { request_id: request_id.clone(), protocol: b"les".to_vec(), request: vec![1] },
+			Message::TalkResponse { request_id: request_id.clone(), response: vec![] },
+			Message::RegisterTopic { request_id: request_id.clone(), topic: H256::random(), record, ticket: vec![] },
+			Message::Ticket { request_id: request_id.clone(), ticket: vec![1, 2], wait_time: 5 },
+			Message::RegistrationConfirmation { request_id: request_id.clone(), topic: H256::random() },
+			Message::TopicQuery { request_id: request_id.clone(), topic: H256::random() },
+		];
+		for message in messages {
+			assert_eq!(Message::decode(&message.encode()).unwrap(), message);
+		}
+
+		let long_id = Message::Ping { request_id: vec![0; 9], enr_seq: 2 };
+		assert!(Message::decode(&long_id.encode()).is_err());
+		assert!(Message::decode(&[0x0b, 0xc2, 0x80, 0x80]).is_err());
+	}
+
+	#[test]
+	fn opens_message_packet() {
+		// "Ping message packet (flag 0)" of the discv5 wire test vectors
+		let packet = "\
+			00000000000000000000000000000000088b3d4342774649325f313964a39e55ea96c005ad52be8c7560413a\
+			7008f16c9e6d2f43bbea8814a546b7409ce783d34c4f53245d08dab84102ed931f66d1492acb308fa1c6715b\
+			9d139b81acbdcc\
+		".from_hex().unwrap();
+		let dest_id = H256::from_str("bbbb9d047f0488c0b5a93c1c3f2d8bafc7c8ff337024a55434a0d0555de64db9").unwrap();
+		let src_id = H256::from_str("aaaa8419e9f49d0083561b48287df592939a8d19947d8c0ef88f2a4856a69fbb").unwrap();
+
+		let opened = open_packet(&dest_id, &packet).unwrap();
+		assert_eq!(opened.flag, FLAG_MESSAGE);
+		assert_eq!(opened.nonce, [0xff; NONCE_SIZE]);
+		assert_eq!(&opened.authdata[..], &src_id[..]);
+		let message = decrypt_message(&[0u8; 16], &opened.nonce, opened.message, &opened.header).unwrap();
+		assert_eq!(Message::decode(&message).unwrap(), Message::Ping { request_id: vec![0, 0, 0, 1], enr_seq: 2 });
+
+		assert!(open_packet(&src_id, &packet).is_err());
+		assert!(open_packet(&dest_id, &packet[..MIN_PACKET_SIZE - 1]).is_err());
+		let mut tampered = packet.clone();
+		*tampered.last_mut().unwrap() ^= 1;
+		let opened = open_packet(&dest_id, &tampered).unwrap();
+		assert!(decrypt_message(&[0u8; 16], &opened.nonce, opened.message, &opened.header).is_none());
+	}
+
+	#[test]
+	fn derives_keys_and_id_signature() {
+		// "ECDH", "Key Derivation" and "ID Nonce Signing" of the discv5 wire test vectors
+		let secret = Secret::from_str("fb757dc581730490a1d7a00deea65e9b1936924caaea8f44d476014856b68736").unwrap();
+		let dest_public = public_from_compressed(&"0317931e6e0840220642f230037d285d122bc59063221ef3226b1f403ddc69ca91".from_hex().unwrap()).unwrap();
+		let node_a = H256::from_str("aaaa8419e9f49d0083561b48287df592939a8d19947d8c0ef88f2a4856a69fbb").unwrap();
+		let node_b = H256::from_str("bbbb9d047f0488c0b5a93c1c3f2d8bafc7c8ff337024a55434a0d0555de64db9").unwrap();
+		let challenge_data = "\
+			000000000000000000000000000000006469736376350001010102030405060708090a0b0c00180102030405\
+			060708090a0b0c0d0e0f100000000000000000\
+		".from_hex().unwrap();
+
+		let (initiator_key, recipient_key) = derive_keys(&secret, &dest_public, &node_a, &node_b, &challenge_data).unwrap();
+		assert_eq!(initiator_key.to_vec(), "dccc82d81bd610f4f76d3ebe97a40571".from_hex().unwrap());
+		assert_eq!(recipient_key.to_vec(), "ac74bb8773749920b0d3a8881c173ec5".from_hex().unwrap());
+
+		let ephemeral_public = "039961e4c2356d61bedb83052c115d311acb3a96f5777296dcf297351130266231".from_hex().unwrap();
+		let hash = id_signature_hash(&challenge_data, &ephemeral_public, &node_b);
+		let signature = sign(&secret, &hash).unwrap();
+		assert_eq!(signature[0..64].to_vec(), "\
+			94852a1e2318c4e5e9d422c98eaf19d1d90d876b29cd06ca7cb7546d0fff7b484fe86c09a064fe72bdbef73b\
+			a8e9c34df0cd2b53e9d65528c2c7f336d5dfc6e6\
+		".from_hex().unwrap());
+		let public = KeyPair::from_secret(secret).unwrap().public().clone();
+		assert!(verify_id_signature(&public, &signature[0..64], &hash));
+		assert!(!verify_id_signature(&dest_public, &signature[0..64], &hash));
+	}
+
+	#[test]
+	fn handshake_establishes_session() {
+		let (mut a, record_a, address_a) = sessions(30310);
+		let (mut b, record_b, address_b) = sessions(30311);
+		let ping = Message::Ping { request_id: vec![1], enr_seq: 1 };
+
+		// the first packet carries random data which `b` can't decrypt
+		let packet = a.encode(record_b.id(), &address_b, &ping);
+		let whoareyou = match b.decode(&packet, &address_a, Some(&record_b)).unwrap() {
+			Incoming::Reply(whoareyou) => whoareyou,
+			other => panic!("Expected WHOAREYOU, got {:?}", other),
+		};
+		// a single challenge is sent until it expires
+		assert_eq!(b.decode(&packet, &address_a, Some(&record_b)).unwrap(), Incoming::Nothing);
+		// challenges are only answered by the endpoint the challenged packet was sent to
+		assert_eq!(a.decode(&whoareyou, &SocketAddr::from(([127, 0, 0, 1], 30312)), Some(&record_a)).unwrap(), Incoming::Nothing);
+
+		let handshake = match a.decode(&whoareyou, &address_b, Some(&record_a)).unwrap() {
+			Incoming::Reply(handshake) => handshake,
+			other => panic!("Expected handshake, got {:?}", other),
+		};
+		assert_eq!(
+			b.decode(&handshake, &address_a, Some(&record_b)).unwrap(),
+			Incoming::Message(*record_a.id(), Some(record_a.clone()), ping)
+		);
+		// the challenge is used up
+		assert_eq!(b.decode(&handshake, &address_a, Some(&record_b)).unwrap(), Incoming::Nothing);
+
+		// both ends use the session from now on
+		let pong = Message::Pong { request_id: vec![1], enr_seq: 1, address: address_a };
+		let packet = b.encode(record_a.id(), &address_a, &pong);
+		assert_eq!(a.decode(&packet, &address_b, Some(&record_a)).unwrap(), Incoming::Message(*record_b.id(), None, pong));
+		let find_node = Message::FindNode { request_id: vec![2], distances: vec![0] };
+		let packet = a.encode(record_b.id(), &address_b, &find_node);
+		assert_eq!(b.decode(&packet, &address_a, Some(&record_b)).unwrap(), Incoming::Message(*record_a.id(), None, find_node));
+
+		// packets of other endpoints are challenged
+		match b.decode(&packet, &SocketAddr::from(([127, 0, 0, 1], 30312)), Some(&record_b)).unwrap() {
+			Incoming::Reply(_) => {},
+			other => panic!("Expected WHOAREYOU, got {:?}", other),
+		}
+		b.expire(Instant::now() + HANDSHAKE_TIMEOUT);
+		assert!(b.challenges.is_empty());
+	}
+
+	#[test]
+	fn rejects_forged_handshake() {
+		let (mut a, record_a, address_a) = sessions(30313);
+		let (mut b, record_b, address_b) = sessions(30314);
+		let (_, record_c, _) = sessions(30315);
+		let ping = Message::Ping { request_id: vec![1], enr_seq: 1 };
+
+		let packet = a.encode(record_b.id(), &address_b, &ping);
+		let whoareyou = match b.decode(&packet, &address_a, Some(&record_b)).unwrap() {
+			Incoming::Reply(whoareyou) => whoareyou,
+			other => panic!("Expected WHOAREYOU, got {:?}", other),
+		};
+		// `a` claims the identity of another node in its handshake
+		let handshake = match a.decode(&whoareyou, &address_b, Some(&record_c)).unwrap() {
+			Incoming::Reply(handshake) => handshake,
+			other => panic!("Expected handshake, got {:?}", other),
+		};
+		assert!(b.decode(&handshake, &address_a, Some(&record_b)).is_err());
+		assert!(b.sessions.is_empty());
+	}
+}
@@ -0,0 +1,375 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ethereum Node Records (EIP-778) using the "v4" identity scheme.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+use ethereum_types::H256;
+use ethkey::{KeyPair, Public, Signature, sign, recover};
+use hash::keccak;
+use parity_bytes::Bytes;
+use rlp::{self, Rlp, RlpStream, Encodable, Decodable, DecoderError};
+use network::{Error, ErrorKind};
+use node_table::{NodeEndpoint, NodeId};
+
+/// Maximal size of RLP encoded record.
+pub const MAX_RECORD_SIZE: usize = 300;
+/// Identity scheme of records signed with secp256k1 node keys.
+pub const ID_SCHEME: &str = "v4";
+/// Prefix of the textual representation of a record.
+pub const TEXT_PREFIX: &str = "enr:";
+
+const KEY_ID: &[u8] = b"id";
+const KEY_SECP256K1: &[u8] = b"secp256k1";
+const KEY_IP: &[u8] = b"ip";
+const KEY_TCP: &[u8] = b"tcp";
+const KEY_UDP: &[u8] = b"udp";
+const KEY_IP6: &[u8] = b"ip6";
+const KEY_TCP6: &[u8] = b"tcp6";
+const KEY_UDP6: &[u8] = b"udp6";
+
+/// Signed node record. Every change of the content increases the sequence number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeRecord {
+	seq: u64,
+	// Keys mapped to RLP encoded values, sorted as required by the encoding.
+	pairs: BTreeMap<Bytes, Bytes>,
+	signature: Bytes,
+	id: NodeId,
+}
+
+impl NodeRecord {
+	/// Create a record with given sequence number advertising `endpoint`.
+	pub fn new(keys: &KeyPair, seq: u64, endpoint: &NodeEndpoint) -> Result<Self, Error> {
+		NodeRecord::signed(keys, seq, endpoint_pairs(keys.public(), endpoint))
+	}
+
+	fn signed(keys: &KeyPair, seq: u64, pairs: BTreeMap<Bytes, Bytes>) -> Result<Self, Error> {
+		let hash = keccak(content(seq, &pairs).out());
+		let signature = sign(keys.secret(), &hash)?;
+		let record = NodeRecord {
+			seq,
+			pairs,
+			signature: signature[0..64].to_vec(),
+			id: *keys.public(),
+		};
+		if record.rlp_bytes().len() > MAX_RECORD_SIZE {
+			bail!(ErrorKind::InvalidNodeRecord);
+		}
+		Ok(record)
+	}
+
+	/// Sequence number of the record.
+	pub fn seq(&self) -> u64 {
+		self.seq
+	}
+
+	/// Id of the node which signed the record.
+	pub fn id(&self) -> &NodeId {
+		&self.id
+	}
+
+	/// Decoded value of the given key.
+	pub fn get<T: Decodable>(&self, key: &str) -> Option<T> {
+		self.pairs.get(key.as_bytes()).and_then(|value| rlp::decode(value).ok())
+	}
+
+	/// Endpoint advertised by the record, IPv4 address is preferred.
+	pub fn endpoint(&self) -> Option<NodeEndpoint> {
+		let ip4 = self.get::<Bytes>("ip").and_then(|ip| match ip.len() {
+			4 => Some(IpAddr::V4(Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3]))),
+			_ => None,
+		});
+		let endpoint = |ip: IpAddr, tcp: Option<u16>, udp: Option<u16>| tcp.map(|tcp| NodeEndpoint {
+			address: SocketAddr::new(ip, tcp),
+			udp_port: udp.unwrap_or(tcp),
+		});
+
+		if let Some(ip) = ip4 {
+			return endpoint(ip, self.get("tcp"), self.get("udp"));
+		}
+		self.get::<Bytes>("ip6").and_then(|ip| match ip.len() {
+			16 => {
+				let mut octets = [0u8; 16];
+				octets.copy_from_slice(&ip);
+				endpoint(IpAddr::V6(Ipv6Addr::from(octets)), self.get("tcp6"), self.get("udp6"))
+			},
+			_ => None,
+		})
+	}
+
+	/// Set a custom key, increasing the sequence number if the value changed.
+	/// Returns `true` if the record was updated.
+	pub fn set<T: Encodable>(&mut self, keys: &KeyPair, key: &str, value: &T) -> Result<bool, Error> {
+		let mut pairs = self.pairs.clone();
+		pairs.insert(key.as_bytes().to_vec(), rlp::encode(value).into_vec());
+		self.update(keys, pairs)
+	}
+
+	/// Advertise new endpoint, increasing the sequence number if it changed.
+	/// Returns `true` if the record was updated.
+	pub fn set_endpoint(&mut self, keys: &KeyPair, endpoint: &NodeEndpoint) -> Result<bool, Error> {
+		let mut pairs: BTreeMap<Bytes, Bytes> = self.pairs.iter()
+			.filter(|&(key, _)| !is_endpoint_key(key))
+			.map(|(key, value)| (key.clone(), value.clone()))
+			.collect();
+		pairs.extend(endpoint_pairs(keys.public(), endpoint));
+		self.update(keys, pairs)
+	}
+
+	fn update(&mut self, keys: &KeyPair, pairs: BTreeMap<Bytes, Bytes>) -> Result<bool, Error> {
+		if self.id != *keys.public() {
+			bail!(ErrorKind::InvalidNodeRecord);
+		}
+		if pairs == self.pairs {
+			return Ok(false);
+		}
+		*self = NodeRecord::signed(keys, self.seq + 1, pairs)?;
+		Ok(true)
+	}
+}
+
+impl Encodable for NodeRecord {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(2 + self.pairs.len() * 2);
+		s.append(&self.signature);
+		s.append(&self.seq);
+		for (key, value) in &self.pairs {
+			s.append(key);
+			s.append_raw(value, 1);
+		}
+	}
+}
+
+impl Decodable for NodeRecord {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.as_raw().len() > MAX_RECORD_SIZE {
+			return Err(DecoderError::Custom("Node record is too large"));
+		}
+		let count = rlp.item_count()?;
+		if count < 2 || count % 2 != 0 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+
+		let signature: Bytes = rlp.val_at(0)?;
+		let seq: u64 = rlp.val_at(1)?;
+		let mut pairs = BTreeMap::new();
+		let mut last_key: Option<Bytes> = None;
+		for i in 1..(count / 2) {
+			let key: Bytes = rlp.val_at(i * 2)?;
+			if last_key.as_ref().map_or(false, |last| *last >= key) {
+				return Err(DecoderError::Custom("Node record keys are not sorted"));
+			}
+			last_key = Some(key.clone());
+			pairs.insert(key, rlp.at(i * 2 + 1)?.as_raw().to_vec());
+		}
+
+		let id = verify(seq, &pairs, &signature)
+			.ok_or(DecoderError::Custom("Invalid node record signature"))?;
+		Ok(NodeRecord { seq, pairs, signature, id })
+	}
+}
+
+impl fmt::Display for NodeRecord {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}{}", TEXT_PREFIX, base64_encode(&self.rlp_bytes()))
+	}
+}
+
+impl FromStr for NodeRecord {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if !s.starts_with(TEXT_PREFIX) {
+			bail!(ErrorKind::InvalidNodeRecord);
+		}
+		let bytes = base64_decode(&s[TEXT_PREFIX.len()..]).ok_or(ErrorKind::InvalidNodeRecord)?;
+		rlp::decode(&bytes).map_err(|_| ErrorKind::InvalidNodeRecord.into())
+	}
+}
+
+fn is_endpoint_key(key: &[u8]) -> bool {
+	[KEY_IP, KEY_TCP, KEY_UDP, KEY_IP6, KEY_TCP6, KEY_UDP6].iter().any(|k| *k == key)
+}
+
+fn endpoint_pairs(public: &Public, endpoint: &NodeEndpoint) -> BTreeMap<Bytes, Bytes> {
+	let mut pairs = BTreeMap::new();
+	pairs.insert(KEY_ID.to_vec(), rlp::encode(&ID_SCHEME.as_bytes().to_vec()).into_vec());
+	pairs.insert(KEY_SECP256K1.to_vec(), rlp::encode(&compress(public).to_vec()).into_vec());
+	let (ip, tcp, udp) = match endpoint.address.ip() {
+		IpAddr::V4(ip) => ((KEY_IP, ip.octets().to_vec()), KEY_TCP, KEY_UDP),
+		IpAddr::V6(ip) => ((KEY_IP6, ip.octets().to_vec()), KEY_TCP6, KEY_UDP6),
+	};
+	pairs.insert(ip.0.to_vec(), rlp::encode(&ip.1).into_vec());
+	pairs.insert(tcp.to_vec(), rlp::encode(&endpoint.address.port()).into_vec());
+	pairs.insert(udp.to_vec(), rlp::encode(&endpoint.udp_port).into_vec());
+	pairs
+}
+
+// RLP list of the signed content: `[seq, k, v, ...]`.
+fn content(seq: u64, pairs: &BTreeMap<Bytes, Bytes>) -> RlpStream {
+	let mut s = RlpStream::new_list(1 + pairs.len() * 2);
+	s.append(&seq);
+	for (key, value) in pairs {
+		s.append(key);
+		s.append_raw(value, 1);
+	}
+	s
+}
+
+// Checks "v4" scheme signature and returns the id of the signer.
+fn verify(seq: u64, pairs: &BTreeMap<Bytes, Bytes>, signature: &[u8]) -> Option<NodeId> {
+	let scheme: Bytes = pairs.get(KEY_ID).and_then(|v| rlp::decode(v).ok())?;
+	let key: Bytes = pairs.get(KEY_SECP256K1).and_then(|v| rlp::decode(v).ok())?;
+	if scheme != ID_SCHEME.as_bytes() || key.len() != 33 || signature.len() != 64 {
+		return None;
+	}
+
+	// the record stores signature without recovery id, try both candidates
+	let hash = keccak(content(seq, pairs).out());
+	let r = H256::from_slice(&signature[0..32]);
+	let s = H256::from_slice(&signature[32..64]);
+	(0..2)
+		.filter_map(|v| recover(&Signature::from_rsv(&r, &s, v), &hash).ok())
+		.find(|public| compress(public)[..] == key[..])
+}
+
+// SEC1 compressed form of a public key.
+pub fn compress(public: &Public) -> [u8; 33] {
+	let mut compressed = [0u8; 33];
+	compressed[0] = 2 + (public[63] & 1);
+	compressed[1..].copy_from_slice(&public[0..32]);
+	compressed
+}
+
+const BASE64_URL: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// URL-safe base64 without padding, as used by textual records.
+fn base64_encode(bytes: &[u8]) -> String {
+	let mut out = String::with_capacity((bytes.len() * 4 + 2) / 3);
+	for chunk in bytes.chunks(3) {
+		let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+		let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+		for i in 0..(chunk.len() + 1) {
+			out.push(BASE64_URL[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+		}
+	}
+	out
+}
+
+fn base64_decode(s: &str) -> Option<Bytes> {
+	let s = s.trim_right_matches('=');
+	if s.len() % 4 == 1 {
+		return None;
+	}
+	let mut out = Vec::with_capacity(s.len() * 3 / 4);
+	for chunk in s.as_bytes().chunks(4) {
+		let mut n = 0u32;
+		for (i, c) in chunk.iter().enumerate() {
+			let value = BASE64_URL.iter().position(|x| x == c)? as u32;
+			n |= value << (18 - 6 * i);
+		}
+		for i in 0..(chunk.len() - 1) {
+			out.push((n >> (16 - 8 * i)) as u8);
+		}
+	}
+	Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+	use ethkey::{Random, Generator, Secret};
+	use super::*;
+
+	// Example record from EIP-778.
+	const EXAMPLE: &str = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+
+	fn example_keys() -> KeyPair {
+		let secret = Secret::from_str("b71c71a67e1177ad4e901695e1b4b9ee17ae16c6668d313eac2f96dbcda3f291").unwrap();
+		KeyPair::from_secret(secret).unwrap()
+	}
+
+	#[test]
+	fn should_decode_example_record() {
+		let record: NodeRecord = EXAMPLE.parse().unwrap();
+		assert_eq!(record.seq(), 1);
+		assert_eq!(record.id(), example_keys().public());
+		assert_eq!(keccak(record.id()), "a448f24c6d18e575453db13171562b71999873db5b286df957af199ec94617f7".into());
+		assert_eq!(record.get::<u16>("udp"), Some(30303));
+		// no tcp port advertised
+		assert_eq!(record.endpoint(), None);
+		assert_eq!(record.to_string(), EXAMPLE);
+	}
+
+	#[test]
+	fn should_sign_example_record() {
+		let keys = example_keys();
+		let mut pairs = BTreeMap::new();
+		pairs.insert(b"id".to_vec(), rlp::encode(&b"v4".to_vec()).into_vec());
+		pairs.insert(b"ip".to_vec(), rlp::encode(&vec![127u8, 0, 0, 1]).into_vec());
+		pairs.insert(b"secp256k1".to_vec(), rlp::encode(&compress(keys.public()).to_vec()).into_vec());
+		pairs.insert(b"udp".to_vec(), rlp::encode(&30303u16).into_vec());
+		let record = NodeRecord::signed(&keys, 1, pairs).unwrap();
+		assert_eq!(record.to_string(), EXAMPLE);
+	}
+
+	#[test]
+	fn should_reject_tampered_records() {
+		let record: NodeRecord = EXAMPLE.parse().unwrap();
+		let mut tampered = record.clone();
+		tampered.seq = 2;
+		assert!(rlp::decode::<NodeRecord>(&rlp::encode(&tampered)).is_err());
+		assert!("enr:invalid".parse::<NodeRecord>().is_err());
+		assert!(EXAMPLE[TEXT_PREFIX.len()..].parse::<NodeRecord>().is_err());
+	}
+
+	#[test]
+	fn should_bump_sequence_on_endpoint_change() {
+		let keys = Random.generate().unwrap();
+		let endpoint = NodeEndpoint::from_str("22.99.55.44:30303").unwrap();
+		let mut record = NodeRecord::new(&keys, 1, &endpoint).unwrap();
+		assert_eq!(record.endpoint(), Some(endpoint.clone()));
+
+		assert!(!record.set_endpoint(&keys, &endpoint).unwrap());
+		assert_eq!(record.seq(), 1);
+
+		let moved = NodeEndpoint { address: "[2001:db8::1]:30305".parse().unwrap(), udp_port: 30306 };
+		assert!(record.set_endpoint(&keys, &moved).unwrap());
+		assert_eq!(record.seq(), 2);
+		assert_eq!(record.endpoint(), Some(moved));
+		assert_eq!(record.get::<Bytes>("ip"), None);
+
+		assert!(record.set(&keys, "eth", &7u64).unwrap());
+		assert_eq!(record.seq(), 3);
+		assert_eq!(record.get::<u64>("eth"), Some(7));
+
+		let decoded: NodeRecord = record.to_string().parse().unwrap();
+		assert_eq!(decoded, record);
+		assert!(record.set_endpoint(&Random.generate().unwrap(), &endpoint).is_err());
+	}
+
+	#[test]
+	fn base64_roundtrip() {
+		for len in 0..8 {
+			let bytes: Vec<u8> = (0..len).map(|i| (i * 37 + 250) as u8).collect();
+			assert_eq!(base64_decode(&base64_encode(&bytes)).unwrap(), bytes);
+		}
+		assert_eq!(base64_encode(b"\xfb\xff"), "-_8");
+	}
+}
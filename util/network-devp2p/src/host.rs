@@ -42,6 +42,7 @@ use network::{NetworkConfiguration, NetworkIoMessage, ProtocolId, PeerId, Packet
 use network::{NonReservedPeerMode, NetworkContext as NetworkContextTrait};
 use network::{SessionInfo, Error, ErrorKind, DisconnectReason, NetworkProtocolHandler};
 use discovery::{Discovery, TableUpdates, NodeEntry, MAX_DATAGRAM_SIZE};
+use enr::NodeRecord;
 use ip_utils::{map_external_address, select_public_address};
 use parity_path::restrict_permissions_owner;
 use parking_lot::{Mutex, RwLock};
@@ -218,6 +219,8 @@ pub struct HostInfo {
 	pub local_endpoint: NodeEndpoint,
 	/// Public address + discovery port
 	pub public_endpoint: Option<NodeEndpoint>,
+	/// Local node record advertising the public endpoint
+	pub record: Option<NodeRecord>,
 }
 
 impl HostInfo {
@@ -304,6 +307,7 @@ impl Host {
 				protocol_version: PROTOCOL_VERSION,
 				capabilities: Vec::new(),
 				public_endpoint: None,
+				record: None,
 				local_endpoint,
 			}),
 			discovery: Mutex::new(None),
@@ -335,7 +339,7 @@ impl Host {
 		match Node::from_str(id) {
 			Err(e) => { debug!(target: "network", "Could not add node {}: {:?}", id, e); },
 			Ok(n) => {
-				let entry = NodeEntry { endpoint: n.endpoint.clone(), id: n.id };
+				let entry = NodeEntry { endpoint: n.endpoint.clone(), id: n.id, record: None };
 
 				self.nodes.write().add_node(n);
 				if let Some(ref mut discovery) = *self.discovery.lock() {
@@ -348,7 +352,7 @@ impl Host {
 	pub fn add_reserved_node(&self, id: &str) -> Result<(), Error> {
 		let n = Node::from_str(id)?;
 
-		let entry = NodeEntry { endpoint: n.endpoint.clone(), id: n.id };
+		let entry = NodeEntry { endpoint: n.endpoint.clone(), id: n.id, record: None };
 		self.reserved_nodes.write().insert(n.id);
 		self.nodes.write().add_node(Node::new(entry.id, entry.endpoint.clone()));
 
@@ -406,6 +410,10 @@ impl Host {
 		format!("{}", Node::new(*info.id(), info.local_endpoint.clone()))
 	}
 
+	pub fn node_record(&self) -> Option<String> {
+		self.info.read().record.as_ref().map(|r| r.to_string())
+	}
+
 	pub fn stop(&self, io: &IoContext<NetworkIoMessage>) {
 		self.stopping.store(true, AtomicOrdering::Release);
 		let mut to_kill = Vec::new();
@@ -462,6 +470,7 @@ impl Host {
 		};
 
 		self.info.write().public_endpoint = Some(public_endpoint.clone());
+		self.init_record(&public_endpoint);
 
 		if let Some(url) = self.external_url() {
 			io.message(NetworkIoMessage::NetworkStarted(url)).unwrap_or_else(|e| warn!("Error sending IO notification: {:?}", e));
@@ -476,6 +485,17 @@ impl Host {
 		};
 
 		if let Some(mut discovery) = discovery {
+			{
+				let info = self.info.read();
+				if let Some(ref record) = info.record {
+					discovery.set_record(record.clone());
+				}
+				for cap in &info.capabilities {
+					let topic = String::from_utf8_lossy(&cap.protocol[..]);
+					discovery.advertise_topic(&topic);
+					discovery.search_topic(&topic);
+				}
+			}
 			let mut udp_addr = local_endpoint.address;
 			udp_addr.set_port(local_endpoint.udp_port);
 			let socket = UdpSocket::bind(&udp_addr).expect("Error binding UDP socket");
@@ -493,6 +513,34 @@ impl Host {
 		Ok(())
 	}
 
+	/// Restore the node record saved by the previous run, its sequence number is
+	/// increased if the public endpoint has changed since then.
+	fn init_record(&self, public_endpoint: &NodeEndpoint) {
+		let mut info = self.info.write();
+		let path = info.config.config_path.clone();
+		let saved = path.as_ref().and_then(|p| load_record(Path::new(p)));
+		let record = match saved {
+			Some(ref saved) if saved.id() == info.id() => {
+				let mut record = saved.clone();
+				record.set_endpoint(&info.keys, public_endpoint).map(|_| record)
+			},
+			_ => NodeRecord::new(&info.keys, saved.as_ref().map_or(1, |r| r.seq() + 1), public_endpoint),
+		};
+
+		match record {
+			Ok(record) => {
+				if let Some(ref path) = path {
+					if saved.as_ref() != Some(&record) {
+						save_record(Path::new(path), &record);
+					}
+				}
+				debug!(target: "network", "Local node record: {}", record);
+				info.record = Some(record);
+			},
+			Err(e) => warn!(target: "network", "Error creating node record: {:?}", e),
+		}
+	}
+
 	fn maintain_network(&self, io: &IoContext<NetworkIoMessage>) {
 		self.keep_alive(io);
 		self.connect_peers(io);
@@ -775,7 +823,7 @@ impl Host {
 								if let Ok(address) = s.remote_addr() {
 									// We can't know remote listening ports, so just assume defaults and hope for the best.
 									let endpoint = NodeEndpoint { address: SocketAddr::new(address.ip(), DEFAULT_PORT), udp_port: DEFAULT_PORT };
-									let entry = NodeEntry { id, endpoint };
+									let entry = NodeEntry { id, endpoint, record: None };
 									let mut nodes = self.nodes.write();
 									if !nodes.contains(&entry.id) {
 										nodes.add_node(Node::new(entry.id, entry.endpoint.clone()));
@@ -1085,13 +1133,20 @@ impl IoHandler<NetworkIoMessage> for Host {
 					&NetworkContext::new(io, *protocol, None, self.sessions.clone(), &reserved),
 				);
 				self.handlers.write().insert(*protocol, h);
-				let mut info = self.info.write();
-				for &(version, packet_count) in versions {
-					info.capabilities.push(CapabilityInfo {
-						protocol: *protocol,
-						version,
-						packet_count,
-					});
+				{
+					let mut info = self.info.write();
+					for &(version, packet_count) in versions {
+						info.capabilities.push(CapabilityInfo {
+							protocol: *protocol,
+							version,
+							packet_count,
+						});
+					}
+				}
+				if let Some(ref mut discovery) = *self.discovery.lock() {
+					let topic = String::from_utf8_lossy(&protocol[..]);
+					discovery.advertise_topic(&topic);
+					discovery.search_topic(&topic);
 				}
 			},
 			NetworkIoMessage::AddTimer {
@@ -1249,6 +1304,33 @@ fn load_key(path: &Path) -> Option<Secret> {
 	}
 }
 
+fn save_record(path: &Path, record: &NodeRecord) {
+	if let Err(e) = fs::create_dir_all(path) {
+		warn!("Error creating node record directory: {:?}", e);
+		return;
+	}
+	let path_buf = PathBuf::from(path).join("enr");
+	if let Err(e) = fs::File::create(&path_buf).and_then(|mut file| file.write_all(record.to_string().as_bytes())) {
+		warn!("Error writing node record file: {:?}", e);
+	}
+}
+
+fn load_record(path: &Path) -> Option<NodeRecord> {
+	let path_buf = PathBuf::from(path).join("enr");
+	let mut buf = String::new();
+	if let Err(e) = fs::File::open(&path_buf).and_then(|mut file| file.read_to_string(&mut buf)) {
+		debug!("Error reading node record file: {:?}", e);
+		return None;
+	}
+	match buf.trim().parse() {
+		Ok(record) => Some(record),
+		Err(e) => {
+			warn!("Error parsing node record file: {:?}", e);
+			None
+		}
+	}
+}
+
 #[test]
 fn key_save_load() {
 	use tempdir::TempDir;
@@ -1268,3 +1350,36 @@ fn host_client_url() {
 	let host: Host = Host::new(config, None).unwrap();
 	assert!(host.local_url().starts_with("enode://101b3ef5a4ea7a1c7928e24c4c75fd053c235d7b80c22ae5c03d145d0ac7396e2a4ffff9adee3133a7b05044a5cee08115fd65145e5165d646bde371010d803c@"));
 }
+
+#[test]
+fn host_node_record() {
+	use tempdir::TempDir;
+
+	let tempdir = TempDir::new("").unwrap();
+	let key = KeyPair::from_secret("6f7b0d801bc7b5ce7bbd930b84fd0369b3eb25d09be58d64ba811091046f3aa2".parse().unwrap()).unwrap();
+	let mut config = NetworkConfiguration::new_local();
+	config.use_secret = Some(key.secret().clone());
+	config.config_path = Some(tempdir.path().to_str().unwrap().to_owned());
+	let endpoint = NodeEndpoint::from_str("22.99.55.44:30303").unwrap();
+
+	let host = Host::new(config.clone(), None).unwrap();
+	assert_eq!(host.node_record(), None);
+	host.init_record(&endpoint);
+	let record: NodeRecord = host.node_record().unwrap().parse().unwrap();
+	assert_eq!(record.id(), key.public());
+	assert_eq!(record.endpoint(), Some(endpoint.clone()));
+	drop(host);
+
+	// same endpoint after restart keeps the record
+	let host = Host::new(config.clone(), None).unwrap();
+	host.init_record(&endpoint);
+	assert_eq!(host.node_record(), Some(record.to_string()));
+	drop(host);
+
+	let moved = NodeEndpoint::from_str("22.99.55.45:30303").unwrap();
+	let host = Host::new(config, None).unwrap();
+	host.init_record(&moved);
+	let moved_record: NodeRecord = host.node_record().unwrap().parse().unwrap();
+	assert_eq!(moved_record.seq(), record.seq() + 1);
+	assert_eq!(moved_record.endpoint(), Some(moved));
+}
@@ -103,6 +103,9 @@ mod connection;
 mod handshake;
 mod session;
 mod discovery;
+mod discovery_v5;
+mod topic_discovery;
+mod enr;
 mod service;
 mod node_table;
 mod ip_utils;
@@ -112,5 +115,6 @@ pub use host::NetworkContext;
 
 pub use io::TimerToken;
 pub use node_table::{validate_node_url, NodeId};
+pub use enr::NodeRecord;
 
 const PROTOCOL_VERSION: u32 = 5;
@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use discovery::{TableUpdates, NodeEntry};
+use enr::{NodeRecord, TEXT_PREFIX as RECORD_PREFIX};
 use ethereum_types::H512;
 use ip_utils::*;
 use network::{Error, ErrorKind, AllowIP, IpFilter};
@@ -179,6 +180,8 @@ pub struct Node {
 	pub endpoint: NodeEndpoint,
 	pub peer_type: PeerType,
 	pub last_contact: Option<NodeContact>,
+	/// Latest known node record
+	pub record: Option<NodeRecord>,
 }
 
 impl Node {
@@ -188,6 +191,14 @@ impl Node {
 			endpoint,
 			peer_type: PeerType::Optional,
 			last_contact: None,
+			record: None,
+		}
+	}
+
+	/// Replace the node record if the given one is newer.
+	pub fn update_record(&mut self, record: NodeRecord) {
+		if record.id() == &self.id && self.record.as_ref().map_or(true, |r| r.seq() < record.seq()) {
+			self.record = Some(record);
 		}
 	}
 }
@@ -206,6 +217,14 @@ impl Display for Node {
 impl FromStr for Node {
 	type Err = Error;
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.starts_with(RECORD_PREFIX) {
+			let record: NodeRecord = s.parse()?;
+			let endpoint = record.endpoint().ok_or(ErrorKind::InvalidNodeRecord)?;
+			let mut node = Node::new(*record.id(), endpoint);
+			node.record = Some(record);
+			return Ok(node);
+		}
+
 		let (id, endpoint) = if s.len() > 136 && &s[0..8] == "enode://" && &s[136..137] == "@" {
 			(s[8..136].parse().map_err(|_| ErrorKind::InvalidNodeId)?, NodeEndpoint::from_str(&s[137..])?)
 		}
@@ -218,6 +237,7 @@ impl FromStr for Node {
 			endpoint,
 			peer_type: PeerType::Optional,
 			last_contact: None,
+			record: None,
 		})
 	}
 }
@@ -256,8 +276,13 @@ impl NodeTable {
 
 	/// Add a node to table
 	pub fn add_node(&mut self, mut node: Node) {
-		// preserve node last_contact
-		node.last_contact = self.nodes.get(&node.id).and_then(|n| n.last_contact);
+		// preserve node last_contact and newer record
+		if let Some(old) = self.nodes.remove(&node.id) {
+			node.last_contact = old.last_contact;
+			if let Some(record) = old.record {
+				node.update_record(record);
+			}
+		}
 		self.nodes.insert(node.id, node);
 	}
 
@@ -328,6 +353,7 @@ impl NodeTable {
 		self.ordered_entries().iter().map(|n| NodeEntry {
 			endpoint: n.endpoint.clone(),
 			id: n.id,
+			record: n.record.clone(),
 		}).collect()
 	}
 
@@ -346,6 +372,9 @@ impl NodeTable {
 		for (_, node) in update.added.drain() {
 			let entry = self.nodes.entry(node.id).or_insert_with(|| Node::new(node.id, node.endpoint.clone()));
 			entry.endpoint = node.endpoint;
+			if let Some(record) = node.record {
+				entry.update_record(record);
+			}
 		}
 		for r in update.removed {
 			if !reserved.contains(&r) {
@@ -485,6 +514,8 @@ mod json {
 	pub struct Node {
 		pub url: String,
 		pub last_contact: Option<NodeContact>,
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub record: Option<String>,
 	}
 
 	impl Node {
@@ -492,6 +523,9 @@ mod json {
 			match super::Node::from_str(&self.url) {
 				Ok(mut node) => {
 					node.last_contact = self.last_contact.map(|c| c.into_node_contact());
+					if let Some(record) = self.record.and_then(|r| r.parse().ok()) {
+						node.update_record(record);
+					}
 					Some(node)
 				},
 				_ => None,
@@ -512,7 +546,8 @@ mod json {
 
 			Node {
 				url: format!("{}", node),
-				last_contact
+				last_contact,
+				record: node.record.as_ref().map(|r| r.to_string()),
 			}
 		}
 	}
@@ -678,6 +713,37 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn table_keeps_newest_record() {
+		use ethkey::{Random, Generator};
+		use enr::NodeRecord;
+
+		let tempdir = TempDir::new("").unwrap();
+		let keys = Random.generate().unwrap();
+		let endpoint = NodeEndpoint::from_str("22.99.55.44:7770").unwrap();
+		let mut record = NodeRecord::new(&keys, 1, &endpoint).unwrap();
+		let old_record = record.clone();
+		record.set_endpoint(&keys, &NodeEndpoint::from_str("22.99.55.45:7770").unwrap()).unwrap();
+
+		let node = Node::from_str(&record.to_string()).unwrap();
+		assert_eq!(&node.id, keys.public());
+		assert_eq!(node.endpoint, record.endpoint().unwrap());
+
+		{
+			let mut table = NodeTable::new(Some(tempdir.path().to_str().unwrap().to_owned()));
+			table.add_node(node);
+			let mut stale = Node::new(*keys.public(), endpoint);
+			stale.record = Some(old_record);
+			table.add_node(stale);
+			assert_eq!(table.entries()[0].record.as_ref().map(|r| r.seq()), Some(2));
+		}
+
+		{
+			let table = NodeTable::new(Some(tempdir.path().to_str().unwrap().to_owned()));
+			assert_eq!(table.entries()[0].record, Some(record));
+		}
+	}
+
 	#[test]
 	fn custom_allow() {
 		let filter = IpFilter {
@@ -109,6 +109,12 @@ impl NetworkService {
 		host.as_ref().and_then(|h| h.external_url())
 	}
 
+	/// Returns local node record in its textual form if available.
+	pub fn node_record(&self) -> Option<String> {
+		let host = self.host.read();
+		host.as_ref().and_then(|h| h.node_record())
+	}
+
 	/// Returns external url if available.
 	pub fn local_url(&self) -> Option<String> {
 		let host = self.host.read();
@@ -0,0 +1,231 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Topic advertisement of discovery v5.
+//!
+//! Nodes advertise a topic by registering their record at the nodes closest to the topic
+//! hash; those answer topic queries with the records of live advertisers. Registrars never
+//! evict live ads: once a topic queue is full, registrants get a ticket telling them how
+//! long to wait for a free slot, and slots are handed out in the order tickets were issued.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use ethereum_types::H256;
+use rcrypto::digest::Digest;
+use rcrypto::sha2::Sha256;
+use enr::NodeRecord;
+use node_table::NodeId;
+
+/// How long a registration is kept by the registrar.
+pub const TOPIC_AD_LIFETIME: Duration = Duration::from_secs(5 * 60);
+/// Interval of registering advertised topics at new registrars and refreshing searches.
+pub const TOPIC_REFRESH: Duration = Duration::from_secs(2 * 60);
+/// Number of records in a single `NODES` message, records are at most 300 bytes.
+pub const RECORDS_PER_PACKET: usize = 3;
+/// How long a ticket can be used once its wait time is over.
+pub const TICKET_WINDOW: Duration = Duration::from_secs(10);
+/// Maximum number of advertisers kept for a searched topic.
+pub const MAX_TOPIC_NODES: usize = 64;
+/// Maximum number of live ads of a topic.
+pub const MAX_ADS_PER_TOPIC: usize = 32;
+
+const MAX_TOPICS: usize = 64;
+
+/// Hash of a topic, registrations are placed at nodes closest to it.
+pub fn topic_hash(topic: &str) -> H256 {
+	let mut hasher = Sha256::new();
+	hasher.input(topic.as_bytes());
+	let mut hash = H256::default();
+	hasher.result(&mut hash);
+	hash
+}
+
+/// Outcome of a topic registration.
+#[derive(Debug, PartialEq)]
+pub enum Registration {
+	/// The record was added to the topic queue.
+	Registered,
+	/// The registrant holds a ticket and has to register again after the given time.
+	Wait(Duration),
+	/// The table can't accept the registration.
+	Rejected,
+}
+
+struct Ad {
+	record: NodeRecord,
+	expires: Instant,
+}
+
+#[derive(Default)]
+struct TopicQueue {
+	// ordered by expiration, ads are never refreshed
+	ads: VecDeque<Ad>,
+	// time from which the ticket holder may take a slot
+	tickets: HashMap<NodeId, Instant>,
+}
+
+impl TopicQueue {
+	/// Issue a ticket for the first slot not promised to other ticket holders.
+	fn issue_ticket(&mut self, id: NodeId, now: Instant) -> Registration {
+		let waiting = self.tickets.len();
+		if waiting >= MAX_ADS_PER_TOPIC {
+			return Registration::Rejected;
+		}
+		let free = MAX_ADS_PER_TOPIC - self.ads.len();
+		let valid_from = match waiting.checked_sub(free) {
+			Some(index) => self.ads[index].expires,
+			None => now,
+		};
+		self.tickets.insert(id, valid_from);
+		Registration::Wait(valid_from - now)
+	}
+}
+
+/// Topic registrations served by this node.
+#[derive(Default)]
+pub struct TopicTable {
+	topics: HashMap<H256, TopicQueue>,
+}
+
+impl TopicTable {
+	/// Register the record for the topic if there is a slot for it,
+	/// otherwise issue a ticket for the next slot to be freed.
+	pub fn register(&mut self, topic: &H256, record: NodeRecord, now: Instant) -> Registration {
+		self.expire(now);
+		if !self.topics.contains_key(topic) && self.topics.len() >= MAX_TOPICS {
+			return Registration::Rejected;
+		}
+
+		let queue = self.topics.entry(*topic).or_insert_with(TopicQueue::default);
+		let id = *record.id();
+		// the advertiser queues up again for a slot once its ad expires
+		if let Some(expires) = queue.ads.iter().find(|ad| ad.record.id() == &id).map(|ad| ad.expires) {
+			queue.tickets.insert(id, expires);
+			return Registration::Wait(expires - now);
+		}
+
+		let has_slot = match queue.tickets.get(&id).cloned() {
+			Some(valid_from) if valid_from > now => return Registration::Wait(valid_from - now),
+			Some(_) => queue.ads.len() < MAX_ADS_PER_TOPIC,
+			// slots up for grabs are reserved for ticket holders
+			None => queue.ads.len() + queue.tickets.len() < MAX_ADS_PER_TOPIC,
+		};
+		queue.tickets.remove(&id);
+		if has_slot {
+			queue.ads.push_back(Ad { record, expires: now + TOPIC_AD_LIFETIME });
+			Registration::Registered
+		} else {
+			queue.issue_ticket(id, now)
+		}
+	}
+
+	/// Records of live advertisers of the topic, most recent first.
+	pub fn records(&self, topic: &H256, now: Instant) -> Vec<NodeRecord> {
+		self.topics.get(topic)
+			.map(|queue| queue.ads.iter().rev().filter(|ad| ad.expires > now).map(|ad| ad.record.clone()).collect())
+			.unwrap_or_default()
+	}
+
+	/// Remove expired registrations and tickets.
+	pub fn expire(&mut self, now: Instant) {
+		for queue in self.topics.values_mut() {
+			while queue.ads.front().map_or(false, |ad| ad.expires <= now) {
+				queue.ads.pop_front();
+			}
+			queue.tickets.retain(|_, valid_from| *valid_from + TICKET_WINDOW > now);
+		}
+		self.topics.retain(|_, queue| !queue.ads.is_empty() || !queue.tickets.is_empty());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::net::SocketAddr;
+	use std::time::Instant;
+	use ethkey::{Random, Generator};
+	use enr::NodeRecord;
+	use node_table::NodeEndpoint;
+	use super::*;
+
+	fn record() -> NodeRecord {
+		let keys = Random.generate().unwrap();
+		let endpoint = NodeEndpoint { address: SocketAddr::from(([127, 0, 0, 1], 30303)), udp_port: 30303 };
+		NodeRecord::new(&keys, 1, &endpoint).unwrap()
+	}
+
+	#[test]
+	fn topic_registrations_expire() {
+		let now = Instant::now();
+		let eth = topic_hash("eth");
+		let mut table = TopicTable::default();
+		let (a, b) = (record(), record());
+		assert_eq!(table.register(&eth, a.clone(), now), Registration::Registered);
+		assert_eq!(table.register(&eth, b.clone(), now + Duration::from_secs(1)), Registration::Registered);
+		// live ads are not refreshed
+		assert_eq!(table.register(&eth, a.clone(), now + Duration::from_secs(2)), Registration::Wait(TOPIC_AD_LIFETIME - Duration::from_secs(2)));
+
+		assert_eq!(table.records(&eth, now + Duration::from_secs(2)), vec![b.clone(), a.clone()]);
+		assert_eq!(table.records(&topic_hash("par"), now), vec![]);
+		assert_eq!(table.records(&eth, now + TOPIC_AD_LIFETIME), vec![b.clone()]);
+
+		// the ticket lets `a` register again once its ad expired
+		assert_eq!(table.register(&eth, a.clone(), now + TOPIC_AD_LIFETIME), Registration::Registered);
+		table.expire(now + TOPIC_AD_LIFETIME * 2 + Duration::from_secs(1));
+		assert!(table.topics.is_empty());
+	}
+
+	#[test]
+	fn full_topic_issues_tickets_in_order() {
+		let now = Instant::now();
+		let eth = topic_hash("eth");
+		let mut table = TopicTable::default();
+		let ads: Vec<_> = (0..MAX_ADS_PER_TOPIC).map(|_| record()).collect();
+		for (i, ad) in ads.iter().enumerate() {
+			assert_eq!(table.register(&eth, ad.clone(), now + Duration::from_secs(i as u64)), Registration::Registered);
+		}
+
+		// newcomers don't evict live ads, they wait for the oldest ones to expire
+		let late = now + Duration::from_secs(MAX_ADS_PER_TOPIC as u64);
+		let (first, second) = (record(), record());
+		assert_eq!(table.register(&eth, first.clone(), late), Registration::Wait(now + TOPIC_AD_LIFETIME - late));
+		assert_eq!(table.register(&eth, second.clone(), late), Registration::Wait(now + TOPIC_AD_LIFETIME + Duration::from_secs(1) - late));
+		assert_eq!(table.records(&eth, late).len(), MAX_ADS_PER_TOPIC);
+
+		// slots freed are kept for ticket holders
+		let freed = now + TOPIC_AD_LIFETIME + Duration::from_secs(1);
+		assert_eq!(table.register(&eth, record(), freed), Registration::Wait(now + TOPIC_AD_LIFETIME + Duration::from_secs(2) - freed));
+		assert_eq!(table.register(&eth, second.clone(), freed), Registration::Registered);
+		assert_eq!(table.register(&eth, first.clone(), freed), Registration::Registered);
+		assert_eq!(table.records(&eth, freed)[..2], [first, second]);
+	}
+
+	#[test]
+	fn topic_table_is_bounded() {
+		let now = Instant::now();
+		let (eth, zero) = (topic_hash("eth"), topic_hash("0"));
+		let mut table = TopicTable::default();
+		for i in 0..MAX_TOPICS {
+			assert_eq!(table.register(&topic_hash(&i.to_string()), record(), now), Registration::Registered);
+		}
+		assert_eq!(table.register(&eth, record(), now), Registration::Rejected);
+
+		for _ in 0..(MAX_ADS_PER_TOPIC * 2 + 5) {
+			table.register(&zero, record(), now);
+		}
+		assert_eq!(table.records(&zero, now).len(), MAX_ADS_PER_TOPIC);
+		assert_eq!(table.topics[&zero].tickets.len(), MAX_ADS_PER_TOPIC);
+	}
+}
@@ -137,6 +137,12 @@ error_chain! {
 			display("Invalid node id"),
 		}
 
+		#[doc = "Invalid or badly signed node record"]
+		InvalidNodeRecord {
+			description("Invalid node record"),
+			display("Invalid node record"),
+		}
+
 		#[doc = "Packet size is over the protocol limit"]
 		OversizedPacket {
 			description("Packet is too large"),
@@ -0,0 +1,185 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Metrics in the Prometheus text exposition format.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Something that can export metrics.
+pub trait PrometheusMetrics {
+	/// Add the current values of all metrics to the registry.
+	fn prometheus_metrics(&self, registry: &mut PrometheusRegistry);
+}
+
+/// Type of a metric family.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MetricType {
+	Counter,
+	Gauge,
+	Summary,
+}
+
+impl MetricType {
+	fn name(&self) -> &'static str {
+		match *self {
+			MetricType::Counter => "counter",
+			MetricType::Gauge => "gauge",
+			MetricType::Summary => "summary",
+		}
+	}
+}
+
+#[derive(Debug)]
+struct Family {
+	help: String,
+	metric_type: MetricType,
+	/// Samples as suffix of the metric name, rendered labels and value.
+	samples: Vec<(&'static str, String, f64)>,
+}
+
+/// Snapshot of metric values, rendered on every scrape.
+#[derive(Debug)]
+pub struct PrometheusRegistry {
+	prefix: String,
+	families: BTreeMap<String, Family>,
+}
+
+impl PrometheusRegistry {
+	/// Create an empty registry. All metric names are prefixed with `prefix`.
+	pub fn new(prefix: &str) -> Self {
+		PrometheusRegistry {
+			prefix: prefix.into(),
+			families: BTreeMap::new(),
+		}
+	}
+
+	/// Add a monotonically increasing value.
+	pub fn register_counter(&mut self, name: &str, help: &str, value: f64) {
+		self.register_counter_with_labels(name, help, &[], value)
+	}
+
+	/// Add a monotonically increasing value of a labelled series.
+	///
+	/// The name gets the conventional `_total` suffix.
+	pub fn register_counter_with_labels(&mut self, name: &str, help: &str, labels: &[(&str, &str)], value: f64) {
+		let name = format!("{}_total", name);
+		self.add_sample(&name, help, MetricType::Counter, "", labels, value)
+	}
+
+	/// Add a value that can go up and down.
+	pub fn register_gauge(&mut self, name: &str, help: &str, value: f64) {
+		self.register_gauge_with_labels(name, help, &[], value)
+	}
+
+	/// Add a value of a labelled series that can go up and down.
+	pub fn register_gauge_with_labels(&mut self, name: &str, help: &str, labels: &[(&str, &str)], value: f64) {
+		self.add_sample(name, help, MetricType::Gauge, "", labels, value)
+	}
+
+	/// Add the number and total size of observed events, e.g. durations, of a labelled series.
+	pub fn register_summary_with_labels(&mut self, name: &str, help: &str, labels: &[(&str, &str)], count: u64, sum: f64) {
+		self.add_sample(name, help, MetricType::Summary, "_count", labels, count as f64);
+		self.add_sample(name, help, MetricType::Summary, "_sum", labels, sum);
+	}
+
+	fn add_sample(&mut self, name: &str, help: &str, metric_type: MetricType, suffix: &'static str, labels: &[(&str, &str)], value: f64) {
+		let name = format!("{}{}", self.prefix, name);
+		let family = self.families.entry(name).or_insert_with(|| Family {
+			help: help.into(),
+			metric_type,
+			samples: Vec::new(),
+		});
+		debug_assert_eq!(family.metric_type, metric_type, "metric registered with different types");
+		family.samples.push((suffix, render_labels(labels), value));
+	}
+
+	/// Render all metrics in the text exposition format.
+	pub fn render(&self) -> String {
+		let mut out = String::new();
+		for (name, family) in &self.families {
+			// writing to a string never fails
+			let _ = writeln!(out, "# HELP {} {}", name, escape(&family.help, false));
+			let _ = writeln!(out, "# TYPE {} {}", name, family.metric_type.name());
+			for &(suffix, ref labels, value) in &family.samples {
+				let _ = writeln!(out, "{}{}{} {}", name, suffix, labels, render_value(value));
+			}
+		}
+		out
+	}
+}
+
+fn render_labels(labels: &[(&str, &str)]) -> String {
+	if labels.is_empty() {
+		return String::new();
+	}
+
+	let labels: Vec<_> = labels.iter()
+		.map(|&(name, value)| format!("{}=\"{}\"", name, escape(value, true)))
+		.collect();
+	format!("{{{}}}", labels.join(","))
+}
+
+fn render_value(value: f64) -> String {
+	if value.is_nan() {
+		"NaN".into()
+	} else if value.is_infinite() {
+		if value > 0.0 { "+Inf".into() } else { "-Inf".into() }
+	} else {
+		format!("{}", value)
+	}
+}
+
+fn escape(s: &str, quotes: bool) -> String {
+	let mut escaped = String::with_capacity(s.len());
+	for c in s.chars() {
+		match c {
+			'\\' => escaped.push_str("\\\\"),
+			'\n' => escaped.push_str("\\n"),
+			'"' if quotes => escaped.push_str("\\\""),
+			c => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+#[cfg(test)]
+mod tests {
+	use super::PrometheusRegistry;
+
+	#[test]
+	fn should_render_metrics() {
+		let mut registry = PrometheusRegistry::new("parity_");
+		registry.register_gauge("peers", "Connected peers", 3.0);
+		registry.register_counter_with_labels("rpc_errors", "RPC errors", &[("method", "eth_call")], 2.0);
+		registry.register_counter_with_labels("rpc_errors", "RPC errors", &[("method", "say \"hi\"")], 1.0);
+		registry.register_summary_with_labels("import_seconds", "Import time", &[], 4, 0.5);
+
+		assert_eq!(registry.render(), "\
+# HELP parity_import_seconds Import time
+# TYPE parity_import_seconds summary
+parity_import_seconds_count 4
+parity_import_seconds_sum 0.5
+# HELP parity_peers Connected peers
+# TYPE parity_peers gauge
+parity_peers 3
+# HELP parity_rpc_errors_total RPC errors
+# TYPE parity_rpc_errors_total counter
+parity_rpc_errors_total{method=\"eth_call\"} 2
+parity_rpc_errors_total{method=\"say \\\"hi\\\"\"} 1
+");
+	}
+}
@@ -104,6 +104,17 @@ impl whisper::rpc::PoolHandle for WhisperPoolHandle {
 	fn pool_status(&self) -> whisper::net::PoolStatus {
 		self.handle.pool_status()
 	}
+
+	fn request_messages(&self, peer: net::NodeId, request: whisper::mail_server::MailRequest) -> bool {
+		let mut res = false;
+		let mut request = Some(request);
+		self.with_proto_context(whisper::net::PROTOCOL_ID, &mut |ctx| {
+			if let Some(request) = request.take() {
+				res = self.handle.request_messages(&peer, request, ctx);
+			}
+		});
+		res
+	}
 }
 
 impl WhisperPoolHandle {
@@ -23,6 +23,7 @@ extern crate ethcore_network as network;
 extern crate ethereum_types;
 extern crate ethkey;
 extern crate hex;
+extern crate kvdb;
 extern crate mem;
 extern crate ordered_float;
 extern crate parking_lot;
@@ -48,12 +49,16 @@ extern crate jsonrpc_macros;
 #[macro_use]
 extern crate serde_derive;
 
+#[cfg(test)]
+extern crate kvdb_memorydb;
+
 #[cfg(test)]
 extern crate serde_json;
 
 pub use self::message::Message;
 pub use self::net::{Network, MessageHandler};
 
+pub mod mail_server;
 pub mod message;
 pub mod net;
 pub mod rpc;
@@ -0,0 +1,291 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Whisper mail server: persistent storage of envelopes for peers which were
+//! offline when the messages were relayed.
+//!
+//! Envelopes are keyed by the time they were sent followed by their hash, so
+//! historical requests can be served by walking the database in key order.
+//! Envelopes are kept for the retention period after they were sent.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ethereum_types::H512;
+use kvdb::{KeyValueDB, DBTransaction};
+use parking_lot::Mutex;
+use rlp::{self, DecoderError, Rlp, RlpStream};
+
+use message::{bloom_topics, Envelope, Message};
+
+/// Maximum number of envelopes served in response to a single request.
+pub const MAX_RESPONSE_ENVELOPES: usize = 1000;
+
+/// Default period of keeping envelopes in the archive.
+pub const DEFAULT_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+// minimal interval between removals of envelopes past the retention period, in seconds.
+const PRUNE_INTERVAL_SECS: u64 = 60;
+
+// key length: big-endian sent timestamp followed by the message hash.
+const KEY_LEN: usize = 8 + 32;
+
+/// A request for historical envelopes, sent to a mail server peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MailRequest {
+	/// Lower bound of the envelope sent time, unix seconds, inclusive.
+	pub from: u64,
+	/// Upper bound of the envelope sent time, unix seconds, inclusive.
+	pub to: u64,
+	/// Bloom filter of the requested topics.
+	pub bloom: H512,
+	/// Maximum number of envelopes to return.
+	pub limit: u32,
+}
+
+impl rlp::Encodable for MailRequest {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(4)
+			.append(&self.from)
+			.append(&self.to)
+			.append(&self.bloom)
+			.append(&self.limit);
+	}
+}
+
+impl rlp::Decodable for MailRequest {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 4 { return Err(DecoderError::RlpIncorrectListLen) }
+
+		Ok(MailRequest {
+			from: rlp.val_at(0)?,
+			to: rlp.val_at(1)?,
+			bloom: rlp.val_at(2)?,
+			limit: rlp.val_at(3)?,
+		})
+	}
+}
+
+/// Persistent envelope archive backing mail server mode.
+pub struct MailServer {
+	db: Arc<KeyValueDB>,
+	col: Option<u32>,
+	retention: Duration,
+	last_prune: Mutex<u64>,
+}
+
+impl MailServer {
+	/// Create a mail server storing envelopes in the given database column.
+	pub fn new(db: Arc<KeyValueDB>, col: Option<u32>) -> Self {
+		MailServer {
+			db: db,
+			col: col,
+			retention: DEFAULT_RETENTION,
+			last_prune: Mutex::new(0),
+		}
+	}
+
+	/// Set the period of keeping envelopes in the archive, counted from the time they were sent.
+	pub fn with_retention(mut self, retention: Duration) -> Self {
+		self.retention = retention;
+		self
+	}
+
+	/// Archive messages. Already known messages are overwritten.
+	/// Envelopes past the retention period are removed from the archive.
+	pub fn archive(&self, messages: &[Message]) {
+		if messages.is_empty() { return }
+
+		let now = SystemTime::now().duration_since(UNIX_EPOCH)
+			.map(|d| d.as_secs())
+			.unwrap_or(0);
+		self.prune(now);
+
+		let mut batch = DBTransaction::new();
+		for message in messages {
+			let envelope = message.envelope();
+			let sent = envelope.expiry - envelope.ttl;
+
+			let mut key = Vec::with_capacity(KEY_LEN);
+			key.extend_from_slice(&u64_to_be(sent));
+			key.extend_from_slice(&message.hash()[..]);
+
+			batch.put_vec(self.col, &key, rlp::encode(envelope).into_vec());
+		}
+
+		if let Err(e) = self.db.write(batch) {
+			warn!(target: "whisper", "Failed to archive {} messages: {}", messages.len(), e);
+		}
+	}
+
+	/// Fetch archived envelopes matching the request, oldest first.
+	pub fn query(&self, request: &MailRequest) -> Vec<Envelope> {
+		let limit = ::std::cmp::min(request.limit as usize, MAX_RESPONSE_ENVELOPES);
+
+		let from = u64_to_be(request.from);
+		let mut entries = self.db.iter_from_prefix(self.col, &from).peekable();
+		// in-memory databases only yield keys starting with `from`, so nothing unless an envelope was
+		// sent exactly at `request.from` => scan the archive from the oldest envelope, earlier ones are
+		// skipped below.
+		let from_oldest = match entries.peek().is_some() {
+			true => None,
+			false => Some(self.db.iter(self.col)),
+		};
+
+		entries.chain(from_oldest.into_iter().flat_map(|entries| entries))
+			.filter(|&(ref key, _)| key.len() == KEY_LEN)
+			.map(|(key, value)| (be_to_u64(&key[..8]), value))
+			.skip_while(|&(sent, _)| sent < request.from)
+			.take_while(|&(sent, _)| sent <= request.to)
+			.filter_map(|(_, value)| match rlp::decode::<Envelope>(&value) {
+				Ok(envelope) => Some(envelope),
+				Err(e) => {
+					warn!(target: "whisper", "Skipping corrupted archived envelope: {}", e);
+					None
+				}
+			})
+			.filter(|envelope| {
+				let bloom = bloom_topics(&envelope.topics);
+				&(&request.bloom & &bloom) == &bloom
+			})
+			.take(limit)
+			.collect()
+	}
+
+	// remove envelopes, sent before the retention period. Runs at most once per prune interval.
+	fn prune(&self, now: u64) {
+		{
+			let mut last_prune = self.last_prune.lock();
+			if now < last_prune.saturating_add(PRUNE_INTERVAL_SECS) { return }
+			*last_prune = now;
+		}
+
+		self.remove_sent_before(now.saturating_sub(self.retention.as_secs()));
+	}
+
+	// remove envelopes, sent before the given time.
+	fn remove_sent_before(&self, oldest_kept: u64) {
+		let mut batch = DBTransaction::new();
+		let mut pruned = 0;
+		for (key, _) in self.db.iter(self.col)
+			.filter(|&(ref key, _)| key.len() == KEY_LEN)
+			.take_while(|&(ref key, _)| be_to_u64(&key[..8]) < oldest_kept)
+		{
+			batch.delete(self.col, &key);
+			pruned += 1;
+		}
+
+		if pruned == 0 { return }
+		match self.db.write(batch) {
+			Ok(()) => trace!(target: "whisper", "Pruned {} archived envelopes", pruned),
+			Err(e) => warn!(target: "whisper", "Failed to prune archived envelopes: {}", e),
+		}
+	}
+}
+
+fn u64_to_be(value: u64) -> [u8; 8] {
+	use byteorder::{BigEndian, ByteOrder};
+
+	let mut buf = [0u8; 8];
+	BigEndian::write_u64(&mut buf, value);
+	buf
+}
+
+fn be_to_u64(bytes: &[u8]) -> u64 {
+	use byteorder::{BigEndian, ByteOrder};
+
+	BigEndian::read_u64(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::{Duration, UNIX_EPOCH};
+	use kvdb_memorydb;
+	use rlp::Rlp;
+	use message::{Message, Topic};
+
+	fn message(sent: u64, topic: Topic) -> Message {
+		let envelope = Envelope {
+			expiry: sent + 100,
+			ttl: 100,
+			topics: vec![topic].into_iter().collect(),
+			data: vec![1, 2, 3],
+			nonce: sent,
+		};
+
+		let encoded = rlp::encode(&envelope);
+		Message::decode(Rlp::new(&*encoded), UNIX_EPOCH + Duration::from_secs(sent)).unwrap()
+	}
+
+	fn server() -> MailServer {
+		MailServer::new(Arc::new(kvdb_memorydb::create(0)), None)
+	}
+
+	#[test]
+	fn query_by_time_range_and_topic() {
+		let server = server();
+		let topic_a = Topic([1, 2, 3, 4]);
+		let topic_b = Topic([5, 6, 7, 8]);
+
+		let messages = vec![
+			message(1000, topic_a),
+			message(2000, topic_b),
+			message(3000, topic_a),
+			message(4000, topic_a),
+		];
+		server.archive(&messages);
+
+		let found = server.query(&MailRequest {
+			from: 1500,
+			to: 3500,
+			bloom: topic_a.bloom(),
+			limit: 10,
+		});
+		assert_eq!(found, vec![messages[2].envelope().clone()]);
+
+		let found = server.query(&MailRequest {
+			from: 0,
+			to: 5000,
+			bloom: topic_a.bloom() | topic_b.bloom(),
+			limit: 3,
+		});
+		assert_eq!(found, messages[..3].iter().map(|m| m.envelope().clone()).collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn old_envelopes_are_removed() {
+		let server = server();
+		let topic = Topic([1, 2, 3, 4]);
+		let messages = vec![message(1000, topic), message(2000, topic), message(3000, topic)];
+		server.archive(&messages);
+
+		server.remove_sent_before(1500);
+		let found = server.query(&MailRequest {
+			from: 0,
+			to: 5000,
+			bloom: topic.bloom(),
+			limit: 10,
+		});
+		assert_eq!(found, messages[1..].iter().map(|m| m.envelope().clone()).collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn request_rlp_roundtrip() {
+		let request = MailRequest { from: 1, to: 2, bloom: Topic([9, 9, 9, 9]).bloom(), limit: 50 };
+		assert_eq!(rlp::decode::<MailRequest>(&rlp::encode(&request)).unwrap(), request);
+	}
+}
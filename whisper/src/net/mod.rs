@@ -28,6 +28,7 @@ use ordered_float::OrderedFloat;
 use parking_lot::{Mutex, RwLock};
 use rlp::{DecoderError, RlpStream, Rlp};
 
+use mail_server::{MailRequest, MailServer};
 use message::{Message, Error as MessageError};
 
 #[cfg(test)]
@@ -52,6 +53,13 @@ pub const SUPPORTED_VERSIONS: &'static [(u8, u8)] = &[
 // maximum tolerated delay between messages packets.
 const MAX_TOLERATED_DELAY: Duration = Duration::from_millis(5000);
 
+// cannot be greater than 16MB (protocol limitation)
+const MAX_MESSAGES_PACKET_SIZE: usize = 8 * 1024 * 1024;
+
+// maximum number of historical messages requests a peer may send within the window.
+const MAX_MAIL_REQUESTS_PER_WINDOW: usize = 10;
+const MAIL_REQUESTS_WINDOW: Duration = Duration::from_secs(60);
+
 /// Whisper protocol ID
 pub const PROTOCOL_ID: ::network::ProtocolId = *b"shh";
 
@@ -66,18 +74,59 @@ mod packet {
 	pub const POW_REQUIREMENT: u8 = 2;
 	pub const TOPIC_FILTER: u8 = 3;
 
-	// 126, 127 for mail server stuff we will never implement here.
+	// mail server requests for historical messages and their responses.
+	pub const P2P_REQUEST: u8 = 126;
+	pub const P2P_MESSAGES: u8 = 127;
+}
+
+// bloom filter matching every topic.
+fn full_bloom() -> H512 {
+	H512([0xff; 64])
+}
+
+fn encode_pow_requirement(pow_requirement: f64) -> Vec<u8> {
+	use byteorder::{ByteOrder, BigEndian};
+
+	let mut bytes = vec![0u8; ::std::mem::size_of::<f64>()];
+	BigEndian::write_f64(&mut bytes, pow_requirement);
+	bytes
+}
+
+fn decode_pow_requirement(rlp: Rlp) -> Result<f64, Error> {
+	use byteorder::{ByteOrder, BigEndian};
+
+	let bytes: Vec<u8> = rlp.as_val()?;
+	if bytes.len() != ::std::mem::size_of::<f64>() {
+		return Err(Error::InvalidPowReq);
+	}
+
+	// as of byteorder 1.1.0, this is always defined.
+	let req = BigEndian::read_f64(&bytes[..]);
+
+	// zero is the lowest requirement a peer can ask for.
+	if !req.is_normal() && req != 0f64 {
+		return Err(Error::InvalidPowReq);
+	}
+
+	Ok(req)
 }
 
 /// Handles messages within a single packet.
 pub trait MessageHandler: Send + Sync {
 	/// Evaluate the message and handle it.
 	///
-	/// The same message will not be passed twice.
+	/// The same message will not be passed twice, except when it is
+	/// explicitly requested again from a mail server.
 	/// Heavy handling should be done asynchronously.
 	/// If there is a significant overhead in this thread, then an attacker
 	/// can determine which kinds of messages we are listening for.
 	fn handle_messages(&self, message: &[Message]);
+
+	/// Bloom filter of the topics this handler is interested in, or `None`
+	/// if it wants to see all messages.
+	///
+	/// Only advertised to peers when topic filtering is enabled.
+	fn topic_bloom(&self) -> Option<H512> { None }
 }
 
 // errors in importing a whisper message.
@@ -89,6 +138,7 @@ enum Error {
 	UnknownPeer(PeerId),
 	UnexpectedMessage,
 	InvalidPowReq,
+	TooManyMailRequests,
 }
 
 impl From<DecoderError> for Error {
@@ -118,6 +168,7 @@ impl fmt::Display for Error {
 			Error::UnknownPeer(ref id) => write!(f, "Message received from unknown peer: {}", id),
 			Error::UnexpectedMessage => write!(f, "Unexpected message."),
 			Error::InvalidPowReq => write!(f, "Peer sent invalid PoW requirement."),
+			Error::TooManyMailRequests => write!(f, "Peer sent too many historical messages requests."),
 		}
 	}
 }
@@ -315,7 +366,6 @@ enum State {
 	Confirmed,
 }
 
-#[allow(dead_code)] // for node key. this will be useful for topic routing.
 struct Peer {
 	node_key: NodeId,
 	state: State,
@@ -323,10 +373,27 @@ struct Peer {
 	topic_filter: Option<H512>,
 	pow_requirement: f64,
 	is_parity: bool,
+	pending_mail_requests: usize,
+	mail_requests_window: SystemTime,
+	mail_requests_in_window: usize,
 	_protocol_version: usize,
 }
 
 impl Peer {
+	// note the historical messages request from this peer. Returns false if peer has exceeded the limit.
+	fn note_mail_request(&mut self, now: SystemTime) -> bool {
+		let window_passed = now.duration_since(self.mail_requests_window)
+			.map(|elapsed| elapsed >= MAIL_REQUESTS_WINDOW)
+			.unwrap_or(false);
+		if window_passed {
+			self.mail_requests_window = now;
+			self.mail_requests_in_window = 0;
+		}
+
+		self.mail_requests_in_window += 1;
+		self.mail_requests_in_window <= MAX_MAIL_REQUESTS_PER_WINDOW
+	}
+
 	// note that a message has been evicted from the queue.
 	fn note_evicted(&mut self, messages: &[H256]) {
 		for message_hash in messages {
@@ -423,6 +490,10 @@ pub struct Network<T> {
 	messages: Arc<RwLock<Messages>>,
 	handler: T,
 	peers: RwLock<HashMap<PeerId, Mutex<Peer>>>,
+	pow_requirement: f64,
+	topic_filtering: bool,
+	advertised_bloom: Mutex<H512>,
+	mail_server: Option<MailServer>,
 }
 
 // public API.
@@ -433,18 +504,72 @@ impl<T> Network<T> {
 			messages: Arc::new(RwLock::new(Messages::new(messages_size_bytes))),
 			handler: handler,
 			peers: RwLock::new(HashMap::new()),
+			pow_requirement: 0f64,
+			topic_filtering: false,
+			advertised_bloom: Mutex::new(full_bloom()),
+			mail_server: None,
 		}
 	}
 
+	/// Set the minimum PoW of messages this node accepts from peers.
+	/// It is advertised to peers in the status packet.
+	pub fn with_pow_requirement(mut self, pow_requirement: f64) -> Self {
+		self.pow_requirement = pow_requirement;
+		self
+	}
+
+	/// Only receive messages matching the topics of the message handler,
+	/// advertising them to peers as a bloom filter.
+	/// Messages with other topics will no longer be relayed by this node.
+	pub fn with_topic_filtering(mut self, enabled: bool) -> Self {
+		self.topic_filtering = enabled;
+		self
+	}
+
+	/// Persist all received messages and serve historical message requests
+	/// from peers. Mail servers always receive messages with any topic.
+	pub fn with_mail_server(mut self, mail_server: MailServer) -> Self {
+		self.mail_server = Some(mail_server);
+		self
+	}
+
 	/// Post a message to the whisper network to be relayed.
 	pub fn post_message<C: ?Sized + Context>(&self, message: Message, context: &C) -> bool
 		where T: MessageHandler
 	{
+		if let Some(ref mail_server) = self.mail_server {
+			mail_server.archive(&[message.clone()]);
+		}
+
 		let ok = self.messages.write().insert(message);
 		if ok { self.rally(context) }
 		ok
 	}
 
+	/// Request historical messages from a connected mail server peer,
+	/// identified by its node key. Received messages are passed to the
+	/// message handler.
+	///
+	/// Returns false if no such peer is connected.
+	pub fn request_messages<C: ?Sized + Context>(&self, node_key: &NodeId, request: MailRequest, context: &C) -> bool {
+		let peers = self.peers.read();
+		let peer_id = peers.iter().find(|&(_, peer)| {
+			let mut peer = peer.lock();
+			if &peer.node_key != node_key || !peer.can_send_messages() { return false }
+
+			peer.pending_mail_requests += 1;
+			true
+		}).map(|(peer_id, _)| *peer_id);
+
+		match peer_id {
+			Some(peer_id) => {
+				context.send(peer_id, packet::P2P_REQUEST, ::rlp::encode(&request).into_vec());
+				true
+			}
+			None => false,
+		}
+	}
+
 	/// Get number of messages and amount of memory used by them.
 	pub fn pool_status(&self) -> PoolStatus {
 		self.messages.read().status()
@@ -452,14 +577,29 @@ impl<T> Network<T> {
 }
 
 impl<T: MessageHandler> Network<T> {
-	fn rally<C: ?Sized + Context>(&self, io: &C) {
-		// cannot be greater than 16MB (protocol limitation)
-		const MAX_MESSAGES_PACKET_SIZE: usize = 8 * 1024 * 1024;
+	// bloom filter of the topics we want to receive.
+	fn local_bloom(&self) -> H512 {
+		if !self.topic_filtering || self.mail_server.is_some() {
+			return full_bloom();
+		}
+
+		self.handler.topic_bloom().unwrap_or_else(full_bloom)
+	}
 
+	fn rally<C: ?Sized + Context>(&self, io: &C) {
 		// prune messages.
 		let now = SystemTime::now();
 		let pruned_hashes = self.messages.write().prune(now);
 
+		// notify peers if the set of topics we're interested in has changed.
+		let bloom = self.local_bloom();
+		let bloom_changed = {
+			let mut advertised = self.advertised_bloom.lock();
+			let changed = *advertised != bloom;
+			*advertised = bloom;
+			changed
+		};
+
 		let messages = self.messages.read();
 		let peers = self.peers.read();
 
@@ -484,6 +624,10 @@ impl<T: MessageHandler> Network<T> {
 				State::Confirmed => {}
 			}
 
+			if bloom_changed {
+				io.send(*peer_id, packet::TOPIC_FILTER, ::rlp::encode(&bloom).into_vec());
+			}
+
 			// construct packet, skipping messages the peer won't accept.
 			let mut stream = RlpStream::new();
 			stream.begin_unbounded_list();
@@ -506,14 +650,26 @@ impl<T: MessageHandler> Network<T> {
 	}
 
 	// handle status packet from peer.
-	fn on_status(&self, peer: &PeerId, _status: Rlp)
+	fn on_status(&self, peer: &PeerId, status: Rlp)
 		-> Result<(), Error>
 	{
+		// v6 peers advertise their PoW requirement and topic bloom filter.
+		// older peers send an empty status.
+		let (pow_requirement, topic_filter) = match status.item_count()? {
+			0 => (0f64, None),
+			_ => (decode_pow_requirement(status.at(0)?)?, Some(status.val_at(1)?)),
+		};
+
 		let peers = self.peers.read();
 
 		match peers.get(peer) {
 			Some(peer) => {
-				peer.lock().state = State::Confirmed;
+				let mut peer = peer.lock();
+				peer.state = State::Confirmed;
+				peer.set_pow_requirement(pow_requirement);
+				if let Some(topic_filter) = topic_filter {
+					peer.set_topic_filter(topic_filter);
+				}
 				Ok(())
 			}
 			None => {
@@ -553,6 +709,18 @@ impl<T: MessageHandler> Network<T> {
 			messages_vec
 		};
 
+		// drop messages below our requirements. these may have been sent
+		// before the peer learned about them, so the peer isn't punished.
+		let bloom = *self.advertised_bloom.lock();
+		messages_vec.retain(|message| {
+			message.work_proved() >= self.pow_requirement
+				&& &(&bloom & message.bloom()) == message.bloom()
+		});
+
+		if let Some(ref mail_server) = self.mail_server {
+			mail_server.archive(&messages_vec);
+		}
+
 		// import for relaying.
 		let mut messages = self.messages.write();
 
@@ -571,8 +739,6 @@ impl<T: MessageHandler> Network<T> {
 	fn on_pow_requirement(&self, peer: &PeerId, requirement: Rlp)
 		-> Result<(), Error>
 	{
-		use byteorder::{ByteOrder, BigEndian};
-
 		let peers = self.peers.read();
 		match peers.get(peer) {
 			Some(peer) => {
@@ -581,19 +747,8 @@ impl<T: MessageHandler> Network<T> {
 				if let State::Unconfirmed(_) = peer.state {
 					return Err(Error::UnexpectedMessage);
 				}
-				let bytes: Vec<u8> = requirement.as_val()?;
-				if bytes.len() != ::std::mem::size_of::<f64>() {
-					return Err(Error::InvalidPowReq);
-				}
 
-				// as of byteorder 1.1.0, this is always defined.
-				let req = BigEndian::read_f64(&bytes[..]);
-
-				if !req.is_normal() {
-					return Err(Error::InvalidPowReq);
-				}
-
-				peer.set_pow_requirement(req);
+				peer.set_pow_requirement(decode_pow_requirement(requirement)?);
 			}
 			None => {
 				debug!(target: "whisper", "Received message from unknown peer.");
@@ -627,6 +782,92 @@ impl<T: MessageHandler> Network<T> {
 		Ok(())
 	}
 
+	// serve a historical messages request, if we are a mail server.
+	fn on_mail_request<C: ?Sized + Context>(&self, io: &C, peer: &PeerId, request: Rlp)
+		-> Result<(), Error>
+	{
+		let mail_server = match self.mail_server {
+			Some(ref mail_server) => mail_server,
+			None => return Ok(()),
+		};
+
+		match self.peers.read().get(peer) {
+			Some(peer) => {
+				let mut peer = peer.lock();
+				if !peer.can_send_messages() {
+					return Err(Error::UnexpectedMessage);
+				}
+				if !peer.note_mail_request(SystemTime::now()) {
+					return Err(Error::TooManyMailRequests);
+				}
+			},
+			None => {
+				debug!(target: "whisper", "Received message from unknown peer.");
+				return Err(Error::UnknownPeer(*peer));
+			}
+		}
+
+		let request: MailRequest = request.as_val()?;
+
+		let mut stream = RlpStream::new();
+		stream.begin_unbounded_list();
+
+		for envelope in mail_server.query(&request) {
+			let encoded = ::rlp::encode(&envelope);
+			if stream.estimate_size(encoded.len()) > MAX_MESSAGES_PACKET_SIZE {
+				break;
+			}
+
+			stream.append_raw(&encoded, 1);
+		}
+
+		stream.complete_unbounded_list();
+
+		io.send(*peer, packet::P2P_MESSAGES, stream.out());
+		Ok(())
+	}
+
+	// handle historical messages sent by a mail server in response to our request.
+	fn on_mail_messages(&self, peer: &PeerId, message_packet: Rlp)
+		-> Result<(), Error>
+	{
+		let messages_vec = {
+			let peers = self.peers.read();
+			let peer = match peers.get(peer) {
+				Some(peer) => peer,
+				None => {
+					debug!(target: "whisper", "Received message from unknown peer.");
+					return Err(Error::UnknownPeer(*peer));
+				}
+			};
+
+			let mut peer = peer.lock();
+
+			if peer.pending_mail_requests == 0 {
+				return Err(Error::UnexpectedMessage);
+			}
+			peer.pending_mail_requests -= 1;
+
+			let now = SystemTime::now();
+			let messages_vec = message_packet.iter().map(|rlp| Message::decode(rlp, now))
+				.collect::<Result<Vec<_>, _>>()?;
+
+			for message in &messages_vec {
+				peer.note_known(message);
+			}
+
+			messages_vec
+		};
+
+		// historical messages are likely expired, so they're only passed
+		// to the handler and never relayed.
+		if !messages_vec.is_empty() {
+			self.handler.handle_messages(&messages_vec);
+		}
+
+		Ok(())
+	}
+
 	fn on_connect<C: ?Sized + Context>(&self, io: &C, peer: &PeerId) {
 		trace!(target: "whisper", "Connecting peer {}", peer);
 
@@ -654,10 +895,17 @@ impl<T: MessageHandler> Network<T> {
 			topic_filter: None,
 			pow_requirement: 0f64,
 			is_parity: io.protocol_version(PARITY_PROTOCOL_ID, *peer).is_some(),
+			pending_mail_requests: 0,
+			mail_requests_window: SystemTime::now(),
+			mail_requests_in_window: 0,
 			_protocol_version: version,
 		}));
 
-		io.send(*peer, packet::STATUS, ::rlp::EMPTY_LIST_RLP.to_vec());
+		let mut status = RlpStream::new_list(2);
+		status.append(&encode_pow_requirement(self.pow_requirement))
+			.append(&*self.advertised_bloom.lock());
+
+		io.send(*peer, packet::STATUS, status.out());
 	}
 
 	fn on_packet<C: ?Sized + Context>(&self, io: &C, peer: &PeerId, packet_id: u8, data: &[u8]) {
@@ -667,6 +915,8 @@ impl<T: MessageHandler> Network<T> {
 			packet::MESSAGES => self.on_messages(peer, rlp),
 			packet::POW_REQUIREMENT => self.on_pow_requirement(peer, rlp),
 			packet::TOPIC_FILTER => self.on_topic_filter(peer, rlp),
+			packet::P2P_REQUEST => self.on_mail_request(io, peer, rlp),
+			packet::P2P_MESSAGES => self.on_mail_messages(peer, rlp),
 			_ => Ok(()), // ignore unknown packets.
 		};
 
@@ -19,13 +19,15 @@
 use std::collections::HashSet;
 use std::sync::mpsc;
 
+use kvdb_memorydb;
 use parking_lot::Mutex;
 use network::{NodeId, PeerId};
 
-use message::{CreateParams, Message};
+use mail_server::{MailRequest, MailServer};
+use message::{CreateParams, Message, Topic};
 use super::*;
 
-struct TestHandler(Mutex<mpsc::Sender<Message>>);
+struct TestHandler(Mutex<mpsc::Sender<Message>>, Option<H512>);
 
 impl MessageHandler for TestHandler {
 	fn handle_messages(&self, messages: &[Message]) {
@@ -34,6 +36,10 @@ impl MessageHandler for TestHandler {
 			let _ = tx.send(message.clone());
 		}
 	}
+
+	fn topic_bloom(&self) -> Option<H512> {
+		self.1
+	}
 }
 
 struct TestPeer {
@@ -44,10 +50,16 @@ struct TestPeer {
 
 impl TestPeer {
 	fn create() -> Self {
+		TestPeer::create_with(None, |network| network)
+	}
+
+	fn create_with<F>(topic_bloom: Option<H512>, configure: F) -> Self
+		where F: FnOnce(Network<TestHandler>) -> Network<TestHandler>
+	{
 		let (tx, rx) = mpsc::channel();
 
 		TestPeer {
-			network: Network::new(10 * 1024 * 1024, TestHandler(Mutex::new(tx))),
+			network: configure(Network::new(10 * 1024 * 1024, TestHandler(Mutex::new(tx), topic_bloom))),
 			recv: rx,
 			disconnected: Mutex::new(HashSet::new()),
 		}
@@ -60,7 +72,11 @@ struct TestNetwork {
 
 impl TestNetwork {
 	fn new(n_peers: usize) -> Self {
-		let unconnected_peers: Vec<_> = (0..n_peers).map(|_| TestPeer::create()).collect();
+		TestNetwork::with_peers((0..n_peers).map(|_| TestPeer::create()).collect())
+	}
+
+	fn with_peers(unconnected_peers: Vec<TestPeer>) -> Self {
+		let n_peers = unconnected_peers.len();
 		for i in 0..n_peers {
 			for j in (i + 1)..n_peers {
 				let (peer1, peer2) = (&unconnected_peers[i], &unconnected_peers[j]);
@@ -80,6 +96,19 @@ impl TestNetwork {
 	fn post_message_from(&self, id: PeerId, msg: Message) {
 		self.peers[id].network.post_message(msg, &TestContext::new(&self.peers, id));
 	}
+
+	fn rally_from(&self, id: PeerId) {
+		self.peers[id].network.rally(&TestContext::new(&self.peers, id));
+	}
+}
+
+fn create_message(topic: Topic, work: u64) -> Message {
+	Message::create(CreateParams {
+		ttl: 500,
+		payload: b"this is my payload, pal".to_vec(),
+		topics: vec![topic],
+		work: work,
+	}).unwrap()
 }
 
 enum Event {
@@ -190,3 +219,111 @@ fn message_gets_relayed() {
 		assert_eq!(network.peers[i].recv.try_recv().unwrap(), message);
 	}
 }
+
+#[test]
+fn pow_requirement_from_status_is_respected() {
+	let network = TestNetwork::with_peers(vec![
+		TestPeer::create(),
+		TestPeer::create_with(None, |network| network.with_pow_requirement(1e100)),
+	]);
+
+	network.post_message_from(0, create_message([0, 1, 2, 3].into(), 25));
+
+	assert!(network.peers[1].recv.try_recv().is_err());
+	assert!(network.peers[1].disconnected.lock().is_empty());
+}
+
+#[test]
+fn topic_filter_is_advertised_on_change() {
+	let wanted: Topic = [0, 1, 2, 3].into();
+	let network = TestNetwork::with_peers(vec![
+		TestPeer::create(),
+		TestPeer::create_with(Some(wanted.bloom()), |network| network.with_topic_filtering(true)),
+	]);
+
+	// the filter is sent to peers on the next rally.
+	network.rally_from(1);
+
+	let unwanted = create_message([4, 5, 6, 7].into(), 25);
+	let wanted = create_message(wanted, 25);
+	network.post_message_from(0, unwanted);
+	network.post_message_from(0, wanted.clone());
+
+	assert_eq!(network.peers[1].recv.try_recv().unwrap(), wanted);
+	assert!(network.peers[1].recv.try_recv().is_err());
+}
+
+#[test]
+fn mail_server_serves_historical_messages() {
+	let db = Arc::new(kvdb_memorydb::create(0));
+	let network = TestNetwork::with_peers(vec![
+		TestPeer::create_with(None, |network| network.with_mail_server(MailServer::new(db, None))),
+		TestPeer::create(),
+	]);
+
+	let topic: Topic = [0, 1, 2, 3].into();
+	let message = create_message(topic, 25);
+	network.post_message_from(0, message.clone());
+	assert_eq!(network.peers[1].recv.try_recv().unwrap(), message);
+
+	let sent = message.envelope().expiry - message.envelope().ttl;
+	let mut mail_server_key = NodeId::default();
+	mail_server_key[0] = 0;
+
+	let requested = network.peers[1].network.request_messages(&mail_server_key, MailRequest {
+		from: sent,
+		to: sent,
+		bloom: topic.bloom(),
+		limit: 10,
+	}, &TestContext::new(&network.peers, 1));
+
+	assert!(requested);
+	assert_eq!(network.peers[1].recv.try_recv().unwrap(), message);
+	assert!(network.peers[1].disconnected.lock().is_empty());
+}
+
+#[test]
+fn mail_requests_are_rate_limited() {
+	let db = Arc::new(kvdb_memorydb::create(0));
+	let network = TestNetwork::with_peers(vec![
+		TestPeer::create_with(None, |network| network.with_mail_server(MailServer::new(db, None))),
+		TestPeer::create(),
+	]);
+
+	let request = MailRequest {
+		from: 0,
+		to: 1,
+		bloom: full_bloom(),
+		limit: 10,
+	};
+	let mut mail_server_key = NodeId::default();
+	mail_server_key[0] = 0;
+
+	{
+		let ctx = TestContext::new(&network.peers, 1);
+		for _ in 0..MAX_MAIL_REQUESTS_PER_WINDOW {
+			assert!(network.peers[1].network.request_messages(&mail_server_key, request.clone(), &ctx));
+		}
+	}
+	assert!(network.peers[0].disconnected.lock().is_empty());
+
+	network.peers[1].network.request_messages(&mail_server_key, request, &TestContext::new(&network.peers, 1));
+	assert!(network.peers[0].disconnected.lock().contains(&1));
+}
+
+#[test]
+fn unrequested_mail_messages_are_rejected() {
+	let network = TestNetwork::new(2);
+	let message = create_message([0, 1, 2, 3].into(), 25);
+
+	let mut stream = RlpStream::new_list(1);
+	stream.append(message.envelope());
+
+	{
+		let ctx = TestContext::new(&network.peers, 0);
+		ctx.send(1, packet::P2P_MESSAGES, stream.out());
+	}
+
+	assert!(network.peers[1].recv.try_recv().is_err());
+	assert!(network.peers[1].disconnected.lock().contains(&0));
+}
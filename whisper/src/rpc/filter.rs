@@ -189,6 +189,17 @@ impl ::net::MessageHandler for Arc<Manager> {
 			}
 		}
 	}
+
+	fn topic_bloom(&self) -> Option<H512> {
+		let filters = self.filters.read();
+		let bloom = filters.values()
+			.flat_map(|filter| match *filter {
+				FilterEntry::Poll(ref filter, _) | FilterEntry::Subscription(ref filter, _) => filter.topics.iter(),
+			})
+			.fold(H512::default(), |bloom, &(_, ref topic_bloom, _)| &bloom | topic_bloom);
+
+		Some(bloom)
+	}
 }
 
 impl Drop for Manager {
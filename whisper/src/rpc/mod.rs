@@ -35,7 +35,8 @@ use self::filter::Filter;
 use self::key_store::{Key, KeyStore};
 use self::types::HexEncode;
 
-use message::{CreateParams, Message, Topic};
+use mail_server::{self, MailRequest};
+use message::{bloom_topics, CreateParams, Message, Topic};
 
 mod crypto;
 mod filter;
@@ -124,6 +125,11 @@ build_rpc_trait! {
 		/// Delete polled filter. Return bool indicating success.
 		#[rpc(name = "shh_deleteMessageFilter")]
 		fn delete_filter(&self, types::Identity) -> Result<bool, Error>;
+
+		/// Request historical messages from a mail server peer.
+		/// Received messages are delivered to matching filters and subscriptions.
+		#[rpc(name = "shh_requestMessages")]
+		fn request_messages(&self, types::MessagesRequest) -> Result<bool, Error>;
 	}
 }
 
@@ -153,6 +159,10 @@ pub trait PoolHandle: Send + Sync {
 
 	/// Number of messages and memory used by resident messages.
 	fn pool_status(&self) -> ::net::PoolStatus;
+
+	/// Request historical messages from the mail server peer with given node key.
+	/// Returns false if the peer isn't connected.
+	fn request_messages(&self, peer: ::network::NodeId, request: MailRequest) -> bool;
 }
 
 /// Default, simple metadata implementation.
@@ -356,6 +366,30 @@ impl<P: PoolHandle + 'static, M: Send + Sync + 'static> Whisper for WhisperClien
 	fn delete_filter(&self, id: types::Identity) -> Result<bool, Error> {
 		Ok(self.delete_filter_kind(id.into_inner(), filter::Kind::Poll))
 	}
+
+	fn request_messages(&self, req: types::MessagesRequest) -> Result<bool, Error> {
+		if req.topics.is_empty() {
+			return Err(whisper_error("must supply at least one topic"));
+		}
+
+		if req.from > req.to {
+			return Err(whisper_error("`from` must not be later than `to`"));
+		}
+
+		let topics: Vec<_> = req.topics.into_iter().map(|x| abridge_topic(&x.into_inner())).collect();
+		let request = MailRequest {
+			from: req.from,
+			to: req.to,
+			bloom: bloom_topics(&topics),
+			limit: req.limit.unwrap_or(mail_server::MAX_RESPONSE_ENVELOPES as u32),
+		};
+
+		if !self.pool.request_messages(req.mail_server_peer.into_inner(), request) {
+			Err(whisper_error("Mail server peer is not connected"))
+		} else {
+			Ok(true)
+		}
+	}
 }
 
 impl<P: PoolHandle + 'static, M: Send + Sync + PubSubMetadata> WhisperPubSub for WhisperClient<P, M> {
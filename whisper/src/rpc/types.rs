@@ -206,6 +206,26 @@ pub struct FilterRequest {
 	pub topics: Vec<Bytes>,
 }
 
+/// Request for historical messages from a mail server peer.
+#[derive(Deserialize)]
+pub struct MessagesRequest {
+	/// Node key of the mail server peer. It must be connected.
+	#[serde(rename = "mailServerPeer")]
+	pub mail_server_peer: Public,
+
+	/// Unix timestamp of the oldest message generation to request.
+	pub from: u64,
+
+	/// Unix timestamp of the newest message generation to request.
+	pub to: u64,
+
+	/// Full topics to request messages for. Cannot be empty.
+	pub topics: Vec<Bytes>,
+
+	/// Maximum number of messages to return.
+	pub limit: Option<u32>,
+}
+
 /// A message captured by a filter or subscription.
 #[derive(Serialize, Clone)]
 pub struct FilterItem {